@@ -0,0 +1,154 @@
+use anyhow::Result;
+use sqlx::{Postgres, QueryBuilder, Row};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::admin::AdminUserSummary;
+use crate::infra::db::Db;
+
+/// A composable predicate over the admin user search surface. Combinators
+/// nest recursively, compiling to a single parameterized `WHERE` clause via
+/// `sqlx::QueryBuilder` -- operator-supplied values are always bound as
+/// parameters, never string-concatenated into the SQL.
+#[derive(Debug, Clone)]
+pub enum UserFilter {
+    HandlePrefix(String),
+    EmailContains(String),
+    TrustLevelRange { min: i32, max: i32 },
+    IsVerified(bool),
+    MinDeviceRiskScore(i32),
+    And(Vec<UserFilter>),
+    Or(Vec<UserFilter>),
+    Not(Box<UserFilter>),
+}
+
+impl UserFilter {
+    fn render<'a>(&'a self, builder: &mut QueryBuilder<'a, Postgres>) {
+        match self {
+            UserFilter::HandlePrefix(prefix) => {
+                builder.push("u.handle LIKE ");
+                builder.push_bind(format!("{}%", prefix));
+            }
+            UserFilter::EmailContains(substring) => {
+                builder.push("u.email ILIKE ");
+                builder.push_bind(format!("%{}%", substring));
+            }
+            UserFilter::TrustLevelRange { min, max } => {
+                builder.push("t.trust_level BETWEEN ");
+                builder.push_bind(*min);
+                builder.push(" AND ");
+                builder.push_bind(*max);
+            }
+            UserFilter::IsVerified(verified) => {
+                if *verified {
+                    builder.push("u.email_verified_at IS NOT NULL");
+                } else {
+                    builder.push("u.email_verified_at IS NULL");
+                }
+            }
+            UserFilter::MinDeviceRiskScore(min) => {
+                builder.push(
+                    "EXISTS (SELECT 1 FROM device_fingerprints df \
+                     WHERE u.id = ANY(df.user_ids) AND df.risk_score >= ",
+                );
+                builder.push_bind(*min);
+                builder.push(")");
+            }
+            UserFilter::And(filters) => Self::render_combinator(filters, "AND", "TRUE", builder),
+            UserFilter::Or(filters) => Self::render_combinator(filters, "OR", "FALSE", builder),
+            UserFilter::Not(inner) => {
+                builder.push("NOT (");
+                inner.render(builder);
+                builder.push(")");
+            }
+        }
+    }
+
+    /// Empty `And`/`Or` render to their identity element (`TRUE`/`FALSE`
+    /// respectively) so the empty filter matches every row rather than
+    /// producing invalid SQL.
+    fn render_combinator<'a>(
+        filters: &'a [UserFilter],
+        joiner: &'static str,
+        identity: &'static str,
+        builder: &mut QueryBuilder<'a, Postgres>,
+    ) {
+        if filters.is_empty() {
+            builder.push(identity);
+            return;
+        }
+
+        builder.push("(");
+        for (i, filter) in filters.iter().enumerate() {
+            if i > 0 {
+                builder.push(" ").push(joiner).push(" ");
+            }
+            filter.render(builder);
+        }
+        builder.push(")");
+    }
+}
+
+#[derive(Clone)]
+pub struct AdminUserService {
+    db: Db,
+}
+
+impl AdminUserService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    pub async fn search(
+        &self,
+        filter: &UserFilter,
+        cursor: Option<(OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<AdminUserSummary>> {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "SELECT u.id, u.handle, u.email, u.display_name, u.created_at, \
+                    COALESCE(t.trust_level, 0) AS trust_level, \
+                    (u.email_verified_at IS NOT NULL) AS is_verified, \
+                    COALESCE( \
+                        (SELECT MAX(df.risk_score) FROM device_fingerprints df WHERE u.id = ANY(df.user_ids)), \
+                        0 \
+                    ) AS device_risk_score \
+             FROM users u \
+             LEFT JOIN user_trust_scores t ON t.user_id = u.id \
+             WHERE u.deleted_at IS NULL AND (",
+        );
+        filter.render(&mut builder);
+        builder.push(")");
+
+        if let Some((created_at, id)) = cursor {
+            builder.push(" AND (u.created_at < ");
+            builder.push_bind(created_at);
+            builder.push(" OR (u.created_at = ");
+            builder.push_bind(created_at);
+            builder.push(" AND u.id < ");
+            builder.push_bind(id);
+            builder.push("))");
+        }
+
+        builder.push(" ORDER BY u.created_at DESC, u.id DESC LIMIT ");
+        builder.push_bind(limit);
+
+        let rows = builder.build().fetch_all(self.db.pool()).await?;
+
+        let users = rows
+            .into_iter()
+            .map(|row| AdminUserSummary {
+                id: row.get("id"),
+                handle: row.get("handle"),
+                email: row.get("email"),
+                display_name: row.get("display_name"),
+                trust_level: row.get("trust_level"),
+                is_verified: row.get("is_verified"),
+                device_risk_score: row.get("device_risk_score"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(users)
+    }
+}
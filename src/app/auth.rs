@@ -5,19 +5,87 @@ use pasetors::claims::{Claims, ClaimsValidationRules};
 use pasetors::keys::SymmetricKey;
 use pasetors::token::UntrustedToken;
 use pasetors::{local, Local, version4::V4};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use sqlx::Row;
 use time::{Duration, OffsetDateTime};
 use uuid::Uuid;
 
+use crate::domain::session::SessionSummary;
 use crate::domain::user::User;
+use crate::app::blocklist::BlocklistService;
 use crate::app::invites::InviteService;
 use crate::app::trust::TrustService;
+use crate::config::LdapConfig;
+use crate::infra::cache::RedisCache;
 use crate::infra::db::Db;
 
+/// How long a cached session epoch is trusted before re-reading Postgres.
+/// Bumping `revoke_all_sessions` also overwrites this key, so the TTL only
+/// bounds staleness for instances that never hear about the invalidation.
+const SESSION_EPOCH_CACHE_TTL_SECONDS: u64 = 30;
+
+/// A permission an access token can carry. Tokens are minted with an
+/// explicit scope set (see `AuthService::login`) rather than being
+/// all-or-nothing, so a third-party integration can request a reduced set
+/// at login time and `RequireScope` extractors can gate individual routes
+/// on exactly the permission they need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    ReadFeed,
+    WritePosts,
+    ManageAccount,
+    AdminModeration,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::ReadFeed => "read:feed",
+            Scope::WritePosts => "write:posts",
+            Scope::ManageAccount => "manage:account",
+            Scope::AdminModeration => "admin:moderation",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "read:feed" => Some(Scope::ReadFeed),
+            "write:posts" => Some(Scope::WritePosts),
+            "manage:account" => Some(Scope::ManageAccount),
+            "admin:moderation" => Some(Scope::AdminModeration),
+            _ => None,
+        }
+    }
+
+    /// The full scope set granted to a normal interactive login when the
+    /// caller doesn't request a reduced set.
+    pub fn all() -> Vec<Scope> {
+        vec![
+            Scope::ReadFeed,
+            Scope::WritePosts,
+            Scope::ManageAccount,
+            Scope::AdminModeration,
+        ]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AuthSession {
     pub user_id: Uuid,
+    pub scopes: Vec<Scope>,
+    pub session_id: Uuid,
+}
+
+/// Device metadata captured at login/refresh time and persisted alongside
+/// the refresh token so it can be surfaced by `list_sessions` and lets a
+/// user recognize ("MacBook Pro -- Chrome") and individually revoke a
+/// device from `GET /v1/auth/sessions`.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceContext {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub device_label: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,29 +96,105 @@ pub struct TokenPair {
     pub refresh_expires_at: OffsetDateTime,
 }
 
+/// What `login` hands back once credentials check out. Most accounts get
+/// `Tokens` straight away; an account with a confirmed TOTP secret (see
+/// `app::totp::TotpService`) gets `TotpRequired` instead, and the client
+/// must complete the challenge via `TotpService::complete_login` before it
+/// sees a `TokenPair`.
+#[derive(Debug, Clone)]
+pub enum LoginOutcome {
+    Tokens(TokenPair),
+    TotpRequired { handle: String },
+}
+
+/// Footer stamped on every PASETO we mint, read back (without trusting it --
+/// it's unauthenticated until `local::decrypt` confirms the MAC) to pick
+/// which key in the `KeyRing` to try first.
+#[derive(Serialize, Deserialize)]
+struct TokenFooter {
+    kid: String,
+}
+
+/// An ordered, versioned set of PASETO `local` symmetric keys. `keys[0]` is
+/// always "current": the one `encrypt_*` signs new tokens with and stamps
+/// into the footer as `kid`. Every other entry is decrypt-only, kept around
+/// so a token minted before a rotation still verifies. Once
+/// `refresh_ttl_days` has elapsed since a key stopped being current, no live
+/// token can still reference it and it's safe to drop from the list.
+#[derive(Clone)]
+pub struct KeyRing {
+    keys: Vec<(String, [u8; 32])>,
+}
+
+impl KeyRing {
+    pub fn new(keys: Vec<(String, [u8; 32])>) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(anyhow!("keyring must contain at least one key"));
+        }
+        Ok(Self { keys })
+    }
+
+    /// A keyring with a single key, `kid` "v1" -- the common case before a
+    /// key has ever been rotated.
+    pub fn single(key: [u8; 32]) -> Self {
+        Self {
+            keys: vec![("v1".to_string(), key)],
+        }
+    }
+
+    fn current(&self) -> &(String, [u8; 32]) {
+        &self.keys[0]
+    }
+
+    fn find(&self, kid: &str) -> Option<&[u8; 32]> {
+        self.keys.iter().find(|(id, _)| id == kid).map(|(_, key)| key)
+    }
+
+    fn all(&self) -> impl Iterator<Item = &[u8; 32]> {
+        self.keys.iter().map(|(_, key)| key)
+    }
+
+    /// Rotates in a new current key, demoting the previous current (and
+    /// every older key) to decrypt-only. Existing access/refresh tokens
+    /// keep verifying against their original key until they expire; new
+    /// tokens are signed with `kid` from here on.
+    pub fn rotate_key(&mut self, kid: String, key: [u8; 32]) {
+        self.keys.insert(0, (kid, key));
+    }
+}
+
 #[derive(Clone)]
 pub struct AuthService {
     db: Db,
-    access_key: [u8; 32],
-    refresh_key: [u8; 32],
+    cache: RedisCache,
+    access_keys: KeyRing,
+    refresh_keys: KeyRing,
     access_ttl_minutes: u64,
     refresh_ttl_days: u64,
+    account_recovery_grace_days: i64,
+    ldap: Option<LdapConfig>,
 }
 
 impl AuthService {
     pub fn new(
         db: Db,
-        access_key: [u8; 32],
-        refresh_key: [u8; 32],
+        cache: RedisCache,
+        access_keys: KeyRing,
+        refresh_keys: KeyRing,
         access_ttl_minutes: u64,
         refresh_ttl_days: u64,
+        account_recovery_grace_days: i64,
+        ldap: Option<LdapConfig>,
     ) -> Self {
         Self {
             db,
-            access_key,
-            refresh_key,
+            cache,
+            access_keys,
+            refresh_keys,
             access_ttl_minutes,
             refresh_ttl_days,
+            account_recovery_grace_days,
+            ldap,
         }
     }
 
@@ -63,21 +207,33 @@ impl AuthService {
         avatar_key: Option<String>,
         password: String,
         invite_code: String,
+        invite_signing_key: &[u8; 32],
     ) -> Result<User> {
+        let blocklist = BlocklistService::new(self.db.clone());
+        if blocklist.is_blocked(&email).await? {
+            return Err(anyhow!("this email address is not allowed to sign up"));
+        }
+
         let mut tx = self.db.pool().begin().await?;
 
         let password_hash = hash_password(&password)?;
+        let (actor_public_key_pem, actor_private_key_pem) =
+            crate::app::federation::generate_actor_keypair()?;
         let row = sqlx::query(
-            "INSERT INTO users (handle, email, display_name, bio, avatar_key, password_hash) \
-             VALUES ($1, $2, $3, $4, $5, $6) \
-             RETURNING id, handle, email, display_name, bio, avatar_key, created_at",
+            "INSERT INTO users \
+                 (handle, email, display_name, bio, avatar_key, password_hash, \
+                  actor_public_key_pem, actor_private_key_pem) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+             RETURNING id, handle, email, display_name, bio, avatar_key, created_at, is_private",
         )
         .bind(handle)
-        .bind(email)
+        .bind(&email)
         .bind(display_name)
         .bind(bio)
         .bind(avatar_key)
         .bind(password_hash)
+        .bind(actor_public_key_pem)
+        .bind(actor_private_key_pem)
         .fetch_one(&mut *tx)
         .await?;
 
@@ -88,7 +244,9 @@ impl AuthService {
             display_name: row.get("display_name"),
             bio: row.get("bio"),
             avatar_key: row.get("avatar_key"),
+            avatar_url: None,
             created_at: row.get("created_at"),
+            is_locked: row.get("is_private"),
         };
 
         let trust_service = TrustService::new(self.db.clone());
@@ -97,18 +255,52 @@ impl AuthService {
             .await?;
 
         let invite_service = InviteService::new(self.db.clone());
-        invite_service
-            .consume_invite_with_tx(&invite_code, user.id, &mut tx)
-            .await?;
+        // A signed token verifies offline (signature + embedded expiry), so
+        // try that first; anything that doesn't parse/verify as one falls
+        // back to the random-code path, letting both formats coexist behind
+        // one `invite_code` field.
+        if crate::app::invites::signed::verify(invite_signing_key, &invite_code).is_ok() {
+            invite_service
+                .consume_signed_invite(invite_signing_key, &invite_code, user.id, &mut tx)
+                .await?;
+        } else {
+            invite_service
+                .consume_invite_with_tx(&invite_code, user.id, &email, &mut tx)
+                .await?;
+        }
 
         tx.commit().await?;
 
         Ok(user)
     }
 
-    pub async fn login(&self, identifier: &str, password: &str) -> Result<Option<TokenPair>> {
+    /// Returns `Err` containing "recoverable" (see `UserService::account_recovery_status`
+    /// for the same cutoff) when the account is deactivated but still inside its
+    /// recovery window, so the handler can point the client at
+    /// `UserService::restore_account` instead of a flat "invalid credentials".
+    /// A deactivated account past the window is indistinguishable from a
+    /// nonexistent one.
+    ///
+    /// An account with a confirmed TOTP secret doesn't get a `TokenPair`
+    /// here at all -- it gets `LoginOutcome::TotpRequired`, and the caller
+    /// has to complete `TotpService::complete_login` with the second factor
+    /// before tokens are minted.
+    pub async fn login(
+        &self,
+        identifier: &str,
+        password: &str,
+        requested_scopes: Option<Vec<Scope>>,
+        device: DeviceContext,
+    ) -> Result<Option<LoginOutcome>> {
+        if let Some(ldap) = &self.ldap {
+            if let Some(user_id) = self.try_ldap_login(ldap, identifier, password).await? {
+                let scopes = requested_scopes.unwrap_or_else(Scope::all);
+                return Ok(Some(self.finish_login(user_id, &scopes, &device).await?));
+            }
+        }
+
         let row = sqlx::query(
-            "SELECT id, password_hash \
+            "SELECT id, password_hash, deleted_at \
              FROM users WHERE email = $1 OR handle = $1",
         )
         .bind(identifier)
@@ -130,26 +322,246 @@ impl AuthService {
             return Ok(None);
         }
 
-        let tokens = self.issue_token_pair(user_id).await?;
-        Ok(Some(tokens))
+        let deleted_at: Option<OffsetDateTime> = row.get("deleted_at");
+        if let Some(deleted_at) = deleted_at {
+            let recoverable =
+                deleted_at > OffsetDateTime::now_utc() - Duration::days(self.account_recovery_grace_days);
+            if recoverable {
+                return Err(anyhow!("account deactivated: recoverable"));
+            }
+            return Ok(None);
+        }
+
+        let scopes = requested_scopes.unwrap_or_else(Scope::all);
+        Ok(Some(self.finish_login(user_id, &scopes, &device).await?))
     }
 
-    pub async fn refresh(&self, refresh_token: &str) -> Result<Option<TokenPair>> {
-        let (user_id, refresh_id) = match self.verify_refresh_token(refresh_token) {
-            Ok((user_id, refresh_id)) => (user_id, refresh_id),
+    /// Shared tail of `login`'s password and LDAP branches: gate on a
+    /// confirmed TOTP secret before handing out tokens. `device` is only
+    /// ever used for its actual `DeviceContext`, never replaced with
+    /// `DeviceContext::default()`, because `TotpService::begin_login` has
+    /// to park it for `complete_login` to issue tokens against later.
+    async fn finish_login(
+        &self,
+        user_id: Uuid,
+        scopes: &[Scope],
+        device: &DeviceContext,
+    ) -> Result<LoginOutcome> {
+        let totp = crate::app::totp::TotpService::new(self.db.clone(), self.cache.clone());
+        if totp.has_confirmed_secret(user_id).await? {
+            let handle = totp.begin_login(user_id, scopes, device).await?;
+            return Ok(LoginOutcome::TotpRequired { handle });
+        }
+
+        let tokens = self.issue_token_pair(user_id, scopes, device).await?;
+        Ok(LoginOutcome::Tokens(tokens))
+    }
+
+    /// Binds to the directory as `identifier`/`password` and, on success,
+    /// provisions (or reuses) the local user row backing that directory
+    /// entry. `Ok(None)` covers both "wrong password" and "no LDAP
+    /// configured reachable" -- either way `login` should fall back to
+    /// checking the local password hash, so an outage or a typo'd
+    /// `bind_dn_template` doesn't lock out accounts that also have a local
+    /// password set.
+    async fn try_ldap_login(
+        &self,
+        ldap: &LdapConfig,
+        identifier: &str,
+        password: &str,
+    ) -> Result<Option<Uuid>> {
+        use ldap3::{LdapConnAsync, Scope as LdapScope, SearchEntry};
+
+        let (conn, mut ldap_conn) = match LdapConnAsync::new(&ldap.url).await {
+            Ok(pair) => pair,
+            Err(err) => {
+                tracing::warn!(error = ?err, "failed to connect to LDAP server");
+                return Ok(None);
+            }
+        };
+        ldap3::drive!(conn);
+
+        let bind_dn = ldap.bind_dn_template.replace("{username}", identifier);
+        if ldap_conn
+            .simple_bind(&bind_dn, password)
+            .await?
+            .success()
+            .is_err()
+        {
+            return Ok(None);
+        }
+
+        let (entries, _) = ldap_conn
+            .search(
+                &ldap.search_base,
+                LdapScope::Subtree,
+                &format!("({}={})", ldap.username_attr, identifier),
+                vec![
+                    ldap.handle_attr.as_str(),
+                    ldap.email_attr.as_str(),
+                    ldap.display_name_attr.as_str(),
+                ],
+            )
+            .await?
+            .success()?;
+
+        let entry = match entries.into_iter().next() {
+            Some(entry) => SearchEntry::construct(entry),
+            None => return Ok(None),
+        };
+        ldap_conn.unbind().await.ok();
+
+        let handle = entry
+            .attrs
+            .get(&ldap.handle_attr)
+            .and_then(|values| values.first())
+            .ok_or_else(|| anyhow!("LDAP entry missing {} attribute", ldap.handle_attr))?
+            .clone();
+        let email = entry
+            .attrs
+            .get(&ldap.email_attr)
+            .and_then(|values| values.first())
+            .ok_or_else(|| anyhow!("LDAP entry missing {} attribute", ldap.email_attr))?
+            .clone();
+        let display_name = entry
+            .attrs
+            .get(&ldap.display_name_attr)
+            .and_then(|values| values.first())
+            .cloned()
+            .unwrap_or_else(|| handle.clone());
+
+        self.provision_ldap_user(&handle, &email, &display_name)
+            .await
+            .map(Some)
+    }
+
+    /// Finds or creates the local user row backing an LDAP-authenticated
+    /// login, keyed on handle/email since the directory -- not an invite
+    /// code -- is what's authorizing the signup here. Provisioned users get
+    /// an empty `password_hash`, so the `is_empty()` checks already in
+    /// `login`/`verify_credentials` naturally keep them from ever
+    /// authenticating with a local password.
+    async fn provision_ldap_user(&self, handle: &str, email: &str, display_name: &str) -> Result<Uuid> {
+        if let Some(row) = sqlx::query("SELECT id FROM users WHERE handle = $1 OR email = $2")
+            .bind(handle)
+            .bind(email)
+            .fetch_optional(self.db.pool())
+            .await?
+        {
+            return Ok(row.get("id"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let (actor_public_key_pem, actor_private_key_pem) =
+            crate::app::federation::generate_actor_keypair()?;
+        let row = sqlx::query(
+            "INSERT INTO users \
+                 (handle, email, display_name, password_hash, \
+                  actor_public_key_pem, actor_private_key_pem) \
+             VALUES ($1, $2, $3, '', $4, $5) \
+             RETURNING id",
+        )
+        .bind(handle)
+        .bind(email)
+        .bind(display_name)
+        .bind(actor_public_key_pem)
+        .bind(actor_private_key_pem)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let user_id: Uuid = row.get("id");
+
+        let trust_service = TrustService::new(self.db.clone());
+        trust_service
+            .initialize_user_with_tx(user_id, &mut tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    /// Verifies `identifier`/`password` like `login` does, but ignores
+    /// `deleted_at` -- used by `UserService::restore_account`, where the
+    /// whole point of the call is that the account is currently
+    /// deactivated. Returns the user's id on success.
+    pub async fn verify_credentials(&self, identifier: &str, password: &str) -> Result<Option<Uuid>> {
+        let row = sqlx::query("SELECT id, password_hash FROM users WHERE email = $1 OR handle = $1")
+            .bind(identifier)
+            .fetch_optional(self.db.pool())
+            .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let user_id: Uuid = row.get("id");
+        let password_hash: String = row.get("password_hash");
+        if password_hash.is_empty() || !verify_password(password, &password_hash)? {
+            return Ok(None);
+        }
+
+        Ok(Some(user_id))
+    }
+
+    /// Change `user_id`'s password after verifying `current_password` against
+    /// the stored hash. Returns `false` (rather than an error) if the user
+    /// doesn't exist or the current password is wrong, so the handler can map
+    /// both to the same 400 without leaking which one it was. On success,
+    /// revokes every refresh token for the user -- the old password is no
+    /// longer valid, so neither should any session authenticated under it.
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<bool> {
+        let row = sqlx::query("SELECT password_hash FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(self.db.pool())
+            .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(false),
+        };
+
+        let password_hash: String = row.get("password_hash");
+        if password_hash.is_empty() || !verify_password(current_password, &password_hash)? {
+            return Ok(false);
+        }
+
+        let new_hash = hash_password(new_password)?;
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(new_hash)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await?;
+
+        self.revoke_sessions(user_id, None).await?;
+
+        Ok(true)
+    }
+
+    pub async fn refresh(
+        &self,
+        refresh_token: &str,
+        device: DeviceContext,
+    ) -> Result<Option<TokenPair>> {
+        let (user_id, refresh_id, scopes) = match self.verify_refresh_token(refresh_token).await {
+            Ok((user_id, refresh_id, scopes)) => (user_id, refresh_id, scopes),
             Err(_) => return Ok(None),
         };
         let token_hash = hash_token(refresh_token);
 
         let mut tx = self.db.pool().begin().await?;
         let row = sqlx::query(
-            "SELECT id \
+            "SELECT revoked_at, replaced_by, expires_at, family_id \
              FROM refresh_tokens \
-             WHERE id = $1 \
-               AND user_id = $2 \
-               AND token_hash = $3 \
-               AND revoked_at IS NULL \
-               AND expires_at > now()",
+             WHERE id = $1 AND user_id = $2 AND token_hash = $3 \
+             FOR UPDATE",
         )
         .bind(refresh_id)
         .bind(user_id)
@@ -157,15 +569,47 @@ impl AuthService {
         .fetch_optional(&mut *tx)
         .await?;
 
-        if row.is_none() {
+        let row = match row {
+            Some(row) => row,
+            None => {
+                tx.rollback().await?;
+                return Ok(None);
+            }
+        };
+
+        let revoked_at: Option<OffsetDateTime> = row.get("revoked_at");
+        let replaced_by: Option<Uuid> = row.get("replaced_by");
+        let expires_at: OffsetDateTime = row.get("expires_at");
+        let family_id: Uuid = row.get("family_id");
+
+        if revoked_at.is_some() {
             tx.rollback().await?;
+            if replaced_by.is_some() {
+                // This token was already rotated away, so this is a replay of a
+                // stale refresh token -- a strong signal of theft. Kill every
+                // descendant in the family, including the legitimate live token,
+                // rather than just failing this one request.
+                tracing::warn!(
+                    user_id = %user_id,
+                    family_id = %family_id,
+                    "refresh token reuse detected, revoking token family"
+                );
+                self.revoke_family(family_id).await?;
+            }
             return Ok(None);
         }
 
-        let tokens = self.issue_token_pair_with_tx(user_id, &mut tx).await?;
+        if expires_at <= OffsetDateTime::now_utc() {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let tokens = self
+            .issue_token_pair_with_tx(user_id, &scopes, &device, Some(family_id), &mut tx)
+            .await?;
         sqlx::query(
             "UPDATE refresh_tokens \
-             SET revoked_at = now(), replaced_by = $1 \
+             SET revoked_at = now(), replaced_by = $1, last_used_at = now() \
              WHERE id = $2 AND revoked_at IS NULL",
         )
         .bind(tokens.refresh_id)
@@ -177,8 +621,27 @@ impl AuthService {
         Ok(Some(tokens.pair))
     }
 
+    /// Revoke every token in a refresh-token family. Used when a replayed,
+    /// already-rotated refresh token is detected in `refresh`, since a
+    /// replay is strong evidence the whole family's chain has been
+    /// compromised, not just the single reused token.
+    pub async fn revoke_family(&self, family_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = now() \
+             WHERE family_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(family_id)
+        .execute(self.db.pool())
+        .await?;
+        Ok(())
+    }
+
     pub async fn revoke_refresh_token(&self, refresh_token: &str) -> Result<bool> {
-        let (user_id, refresh_id) = match self.verify_refresh_token(refresh_token) {
+        let (user_id, refresh_id) = match self
+            .verify_refresh_token(refresh_token)
+            .await
+            .map(|(user_id, refresh_id, _scopes)| (user_id, refresh_id))
+        {
             Ok((user_id, refresh_id)) => (user_id, refresh_id),
             Err(_) => return Ok(false),
         };
@@ -199,7 +662,7 @@ impl AuthService {
     }
 
     pub async fn authenticate_access_token(&self, token: &str) -> Result<Option<AuthSession>> {
-        let claims = match self.decrypt_claims(token, self.access_key)? {
+        let claims = match self.decrypt_claims(token, &self.access_keys)? {
             Some(claims) => claims,
             None => return Ok(None),
         };
@@ -207,12 +670,149 @@ impl AuthService {
             return Ok(None);
         }
         let user_id = claim_uuid(&claims, "sub")?;
-        Ok(Some(AuthSession { user_id }))
+        let token_epoch = claim_i64(&claims, "epoch")?;
+        if token_epoch < self.current_session_epoch(user_id).await? {
+            return Ok(None);
+        }
+        let scopes = claim_scopes(&claims)?;
+        let session_id = claim_uuid(&claims, "jti")?;
+        Ok(Some(AuthSession {
+            user_id,
+            scopes,
+            session_id,
+        }))
+    }
+
+    /// Issue a fresh token pair for `user_id` outside of the normal
+    /// login/refresh path, e.g. once an approved-device auth request
+    /// has been granted. Always grants the full scope set, matching the
+    /// trust level of an interactive login.
+    pub async fn issue_tokens_for_user(
+        &self,
+        user_id: Uuid,
+        device: DeviceContext,
+    ) -> Result<TokenPair> {
+        self.issue_token_pair(user_id, &Scope::all(), &device).await
+    }
+
+    /// List a user's active (non-revoked, unexpired) sessions, newest-used
+    /// first. `current_session_id` (the caller's own token's `jti`) flags
+    /// which row is the one making this request.
+    pub async fn list_sessions(
+        &self,
+        user_id: Uuid,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<SessionSummary>> {
+        let rows = sqlx::query(
+            "SELECT id, device_label, user_agent, ip, created_at, last_used_at \
+             FROM refresh_tokens \
+             WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > now() \
+             ORDER BY last_used_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let sessions = rows
+            .into_iter()
+            .map(|row| {
+                let id: Uuid = row.get("id");
+                SessionSummary {
+                    id,
+                    device_label: row.get("device_label"),
+                    user_agent: row.get("user_agent"),
+                    ip: row.get("ip"),
+                    created_at: row.get("created_at"),
+                    last_used_at: row.get("last_used_at"),
+                    is_current: current_session_id == Some(id),
+                }
+            })
+            .collect();
+
+        Ok(sessions)
+    }
+
+    /// Revoke a single session (refresh token) by id. Returns `false` if it
+    /// didn't exist, belonged to another user, or was already revoked.
+    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = now() \
+             WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every session for `user_id`, optionally keeping `except_session_id`
+    /// alive. When nothing is excluded this also bumps the session epoch (see
+    /// `revoke_all_sessions`) so already-issued access tokens stop working
+    /// immediately rather than lingering until their natural TTL -- excluding a
+    /// session only makes sense if that session's own access token should keep
+    /// working, so the epoch bump is skipped in that case.
+    pub async fn revoke_sessions(
+        &self,
+        user_id: Uuid,
+        except_session_id: Option<Uuid>,
+    ) -> Result<()> {
+        match except_session_id {
+            Some(keep_id) => {
+                sqlx::query(
+                    "UPDATE refresh_tokens SET revoked_at = now() \
+                     WHERE user_id = $1 AND id != $2 AND revoked_at IS NULL",
+                )
+                .bind(user_id)
+                .bind(keep_id)
+                .execute(self.db.pool())
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    "UPDATE refresh_tokens SET revoked_at = now() \
+                     WHERE user_id = $1 AND revoked_at IS NULL",
+                )
+                .bind(user_id)
+                .execute(self.db.pool())
+                .await?;
+                self.revoke_all_sessions(user_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up a user by email or handle without verifying a password,
+    /// e.g. for the device-auth-request flow where a new device identifies
+    /// the account it wants to log into before anyone approves it.
+    pub async fn get_current_user_by_identifier(&self, identifier: &str) -> Result<Option<User>> {
+        let row = sqlx::query(
+            "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private \
+             FROM users WHERE email = $1 OR handle = $1",
+        )
+        .bind(identifier)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let user = row.map(|row| User {
+            id: row.get("id"),
+            handle: row.get("handle"),
+            email: row.get("email"),
+            display_name: row.get("display_name"),
+            bio: row.get("bio"),
+            avatar_key: row.get("avatar_key"),
+            avatar_url: None,
+            created_at: row.get("created_at"),
+            is_locked: row.get("is_private"),
+        });
+
+        Ok(user)
     }
 
     pub async fn get_current_user(&self, user_id: Uuid) -> Result<Option<User>> {
         let row = sqlx::query(
-            "SELECT id, handle, email, display_name, bio, avatar_key, created_at \
+            "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private \
              FROM users WHERE id = $1",
         )
         .bind(user_id)
@@ -226,14 +826,20 @@ impl AuthService {
             display_name: row.get("display_name"),
             bio: row.get("bio"),
             avatar_key: row.get("avatar_key"),
+            avatar_url: None,
             created_at: row.get("created_at"),
+            is_locked: row.get("is_private"),
         });
 
         Ok(user)
     }
 
-    fn decrypt_claims(&self, token: &str, key_bytes: [u8; 32]) -> Result<Option<Claims>> {
-        let key = SymmetricKey::<V4>::from(&key_bytes)?;
+    /// Tries to decrypt `token` with whichever key in `keyring` its footer
+    /// names. The footer isn't trusted until `local::decrypt` confirms the
+    /// MAC, so an unrecognized or tampered `kid` just falls back to trying
+    /// every key in the ring -- the same fallback used for a token minted
+    /// before rotation ever attached a footer at all.
+    fn decrypt_claims(&self, token: &str, keyring: &KeyRing) -> Result<Option<Claims>> {
         let mut rules = ClaimsValidationRules::new();
         rules.validate_issuer_with("ciel");
         rules.validate_audience_with("ciel");
@@ -242,20 +848,43 @@ impl AuthService {
             Ok(token) => token,
             Err(_) => return Ok(None),
         };
-        let trusted = match local::decrypt(&key, &untrusted, &rules, None, None) {
-            Ok(token) => token,
-            Err(_) => return Ok(None),
+
+        let kid = std::str::from_utf8(untrusted.untrusted_footer())
+            .ok()
+            .and_then(|footer| serde_json::from_str::<TokenFooter>(footer).ok())
+            .map(|footer| footer.kid);
+
+        let candidates: Vec<&[u8; 32]> = match kid.as_deref().and_then(|kid| keyring.find(kid)) {
+            Some(key) => vec![key],
+            None => keyring.all().collect(),
         };
-        Ok(trusted.payload_claims().cloned())
+
+        for key_bytes in candidates {
+            let key = SymmetricKey::<V4>::from(key_bytes)?;
+            if let Ok(trusted) = local::decrypt(&key, &untrusted, &rules, None, None) {
+                return Ok(trusted.payload_claims().cloned());
+            }
+        }
+
+        Ok(None)
     }
 
-    fn build_access_claims(&self, user_id: Uuid) -> Result<(Claims, OffsetDateTime)> {
+    fn build_access_claims(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        epoch: i64,
+        scopes: &[Scope],
+    ) -> Result<(Claims, OffsetDateTime)> {
         let duration = std::time::Duration::from_secs(self.access_ttl_minutes * 60);
         let mut claims = Claims::new_expires_in(&duration)?;
         claims.issuer("ciel")?;
         claims.audience("ciel")?;
         claims.subject(&user_id.to_string())?;
+        claims.token_identifier(&session_id.to_string())?;
         claims.add_additional("typ", "access")?;
+        claims.add_additional("epoch", epoch)?;
+        claims.add_additional("scopes", scope_strs(scopes))?;
         let expires_at = OffsetDateTime::now_utc() + Duration::minutes(self.access_ttl_minutes as i64);
         Ok((claims, expires_at))
     }
@@ -264,6 +893,8 @@ impl AuthService {
         &self,
         user_id: Uuid,
         refresh_id: Uuid,
+        epoch: i64,
+        scopes: &[Scope],
     ) -> Result<(Claims, OffsetDateTime)> {
         let duration = std::time::Duration::from_secs(self.refresh_ttl_days * 24 * 60 * 60);
         let mut claims = Claims::new_expires_in(&duration)?;
@@ -272,40 +903,122 @@ impl AuthService {
         claims.subject(&user_id.to_string())?;
         claims.token_identifier(&refresh_id.to_string())?;
         claims.add_additional("typ", "refresh")?;
+        claims.add_additional("epoch", epoch)?;
+        claims.add_additional("scopes", scope_strs(scopes))?;
         let expires_at = OffsetDateTime::now_utc() + Duration::days(self.refresh_ttl_days as i64);
         Ok((claims, expires_at))
     }
 
-    async fn issue_token_pair(&self, user_id: Uuid) -> Result<TokenPair> {
+    /// Current session epoch for `user_id`, read through a short-lived Redis
+    /// cache so that per-request token verification doesn't hit Postgres.
+    /// `revoke_all_sessions` invalidates the cache entry on bump, so the TTL
+    /// here only bounds staleness for instances that miss that write.
+    async fn current_session_epoch(&self, user_id: Uuid) -> Result<i64> {
+        use redis::AsyncCommands;
+        let key = session_epoch_key(user_id);
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        if let Some(cached) = conn.get::<_, Option<i64>>(&key).await? {
+            return Ok(cached);
+        }
+
+        let epoch: i64 = sqlx::query("SELECT session_epoch FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(self.db.pool())
+            .await?
+            .get("session_epoch");
+
+        conn.set_ex(&key, epoch, SESSION_EPOCH_CACHE_TTL_SECONDS)
+            .await?;
+        Ok(epoch)
+    }
+
+    /// Invalidate every access/refresh token already issued to `user_id` by
+    /// bumping their session epoch. Any token minted before this call carries
+    /// a stale `epoch` claim and will be rejected by `authenticate_access_token`
+    /// / `refresh` from this point on, including mid-flight refresh attempts --
+    /// `refresh` re-reads the epoch rather than trusting the token's claim, so
+    /// rotating a refresh token cannot resurrect a revoked family.
+    pub async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<()> {
+        use redis::AsyncCommands;
+        let epoch: i64 = sqlx::query(
+            "UPDATE users SET session_epoch = session_epoch + 1 WHERE id = $1 \
+             RETURNING session_epoch",
+        )
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?
+        .get("session_epoch");
+
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        conn.set_ex(&session_epoch_key(user_id), epoch, SESSION_EPOCH_CACHE_TTL_SECONDS)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn issue_token_pair(
+        &self,
+        user_id: Uuid,
+        scopes: &[Scope],
+        device: &DeviceContext,
+    ) -> Result<TokenPair> {
         let mut tx = self.db.pool().begin().await?;
-        let tokens = self.issue_token_pair_with_tx(user_id, &mut tx).await?;
+        // A fresh `issue_token_pair` call is always a new login, never a
+        // rotation, so it starts a brand new token family (`None` below).
+        let tokens = self
+            .issue_token_pair_with_tx(user_id, scopes, device, None, &mut tx)
+            .await?;
         tx.commit().await?;
         Ok(tokens.pair)
     }
 
+    /// Issue a fresh access/refresh token pair. `family_id` links the new
+    /// refresh token to the lineage of the token being rotated (see
+    /// `refresh`); pass `None` to start a brand new family, e.g. on login.
     async fn issue_token_pair_with_tx(
         &self,
         user_id: Uuid,
+        scopes: &[Scope],
+        device: &DeviceContext,
+        family_id: Option<Uuid>,
         tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     ) -> Result<IssuedTokens> {
-        let (access_claims, access_expires_at) = self.build_access_claims(user_id)?;
-        let access_key = SymmetricKey::<V4>::from(&self.access_key)?;
-        let access_token = local::encrypt(&access_key, &access_claims, None, None)?;
-
+        let epoch = self.current_session_epoch(user_id).await?;
         let refresh_id = Uuid::new_v4();
-        let (refresh_claims, refresh_expires_at) = self.build_refresh_claims(user_id, refresh_id)?;
-        let refresh_key = SymmetricKey::<V4>::from(&self.refresh_key)?;
-        let refresh_token = local::encrypt(&refresh_key, &refresh_claims, None, None)?;
+        let family_id = family_id.unwrap_or_else(Uuid::new_v4);
+
+        let (access_claims, access_expires_at) =
+            self.build_access_claims(user_id, refresh_id, epoch, scopes)?;
+        let (access_kid, access_key_bytes) = self.access_keys.current();
+        let access_key = SymmetricKey::<V4>::from(access_key_bytes)?;
+        let access_footer = serde_json::to_vec(&TokenFooter {
+            kid: access_kid.clone(),
+        })?;
+        let access_token = local::encrypt(&access_key, &access_claims, Some(&access_footer), None)?;
+
+        let (refresh_claims, refresh_expires_at) =
+            self.build_refresh_claims(user_id, refresh_id, epoch, scopes)?;
+        let (refresh_kid, refresh_key_bytes) = self.refresh_keys.current();
+        let refresh_key = SymmetricKey::<V4>::from(refresh_key_bytes)?;
+        let refresh_footer = serde_json::to_vec(&TokenFooter {
+            kid: refresh_kid.clone(),
+        })?;
+        let refresh_token = local::encrypt(&refresh_key, &refresh_claims, Some(&refresh_footer), None)?;
         let token_hash = hash_token(&refresh_token);
 
         sqlx::query(
-            "INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at) \
-             VALUES ($1, $2, $3, $4)",
+            "INSERT INTO refresh_tokens \
+             (id, user_id, token_hash, expires_at, scopes, user_agent, ip, device_label, family_id, last_used_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, now())",
         )
         .bind(refresh_id)
         .bind(user_id)
         .bind(token_hash)
         .bind(refresh_expires_at)
+        .bind(scope_strs(scopes))
+        .bind(&device.user_agent)
+        .bind(&device.ip)
+        .bind(device_label(device))
+        .bind(family_id)
         .execute(&mut **tx)
         .await?;
 
@@ -320,8 +1033,13 @@ impl AuthService {
         })
     }
 
-    fn verify_refresh_token(&self, token: &str) -> Result<(Uuid, Uuid)> {
-        let claims = match self.decrypt_claims(token, self.refresh_key)? {
+    /// Decode a refresh token and confirm its embedded epoch is still
+    /// current. Re-reading the epoch here (rather than trusting the claim
+    /// once at issue time) is what makes revocation stick across rotation --
+    /// a refresh token minted before a `revoke_all_sessions` bump fails this
+    /// check even though its signature and expiry are still fine.
+    async fn verify_refresh_token(&self, token: &str) -> Result<(Uuid, Uuid, Vec<Scope>)> {
+        let claims = match self.decrypt_claims(token, &self.refresh_keys)? {
             Some(claims) => claims,
             None => return Err(anyhow!("invalid refresh token")),
         };
@@ -330,7 +1048,12 @@ impl AuthService {
         }
         let user_id = claim_uuid(&claims, "sub")?;
         let refresh_id = claim_uuid(&claims, "jti")?;
-        Ok((user_id, refresh_id))
+        let token_epoch = claim_i64(&claims, "epoch")?;
+        if token_epoch < self.current_session_epoch(user_id).await? {
+            return Err(anyhow!("session has been revoked"));
+        }
+        let scopes = claim_scopes(&claims)?;
+        Ok((user_id, refresh_id, scopes))
     }
 }
 
@@ -339,7 +1062,7 @@ struct IssuedTokens {
     pair: TokenPair,
 }
 
-fn hash_password(password: &str) -> Result<String> {
+pub(crate) fn hash_password(password: &str) -> Result<String> {
     let salt = SaltString::generate(&mut argon2::password_hash::rand_core::OsRng);
     let argon2 = Argon2::default();
     let hash = argon2
@@ -348,7 +1071,7 @@ fn hash_password(password: &str) -> Result<String> {
     Ok(hash.to_string())
 }
 
-fn verify_password(password: &str, hash: &str) -> Result<bool> {
+pub(crate) fn verify_password(password: &str, hash: &str) -> Result<bool> {
     let parsed = PasswordHash::new(hash)
         .map_err(|err| anyhow!("failed to parse password hash: {}", err))?;
     Ok(Argon2::default()
@@ -356,7 +1079,7 @@ fn verify_password(password: &str, hash: &str) -> Result<bool> {
         .is_ok())
 }
 
-fn hash_token(token: &str) -> String {
+pub(crate) fn hash_token(token: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     let digest = hasher.finalize();
@@ -371,6 +1094,47 @@ fn claim_uuid(claims: &Claims, name: &str) -> Result<Uuid> {
     Ok(Uuid::parse_str(value)?)
 }
 
+fn claim_i64(claims: &Claims, name: &str) -> Result<i64> {
+    claims
+        .get_claim(name)
+        .and_then(|value| value.as_i64())
+        .ok_or_else(|| anyhow!("missing {} claim", name))
+}
+
+/// A stable label for a session's device list entry: the caller-supplied
+/// label if one was given, else a short slice of the user-agent string,
+/// else "unknown device" so the column is never null-ish in the UI.
+fn device_label(device: &DeviceContext) -> String {
+    if let Some(label) = &device.device_label {
+        if !label.trim().is_empty() {
+            return label.clone();
+        }
+    }
+    match &device.user_agent {
+        Some(ua) if !ua.trim().is_empty() => ua.chars().take(80).collect(),
+        _ => "unknown device".to_string(),
+    }
+}
+
+fn scope_strs(scopes: &[Scope]) -> Vec<&'static str> {
+    scopes.iter().map(|scope| scope.as_str()).collect()
+}
+
+fn claim_scopes(claims: &Claims) -> Result<Vec<Scope>> {
+    let values = claims
+        .get_claim("scopes")
+        .and_then(|value| value.as_array())
+        .ok_or_else(|| anyhow!("missing scopes claim"))?;
+
+    values
+        .iter()
+        .map(|value| {
+            let raw = value.as_str().ok_or_else(|| anyhow!("malformed scopes claim"))?;
+            Scope::from_str(raw).ok_or_else(|| anyhow!("unknown scope in token: {}", raw))
+        })
+        .collect()
+}
+
 fn has_token_type(claims: &Claims, expected: &str) -> bool {
     claims
         .get_claim("typ")
@@ -378,3 +1142,7 @@ fn has_token_type(claims: &Claims, expected: &str) -> bool {
         .map(|value| value == expected)
         .unwrap_or(false)
 }
+
+fn session_epoch_key(user_id: Uuid) -> String {
+    format!("session_epoch:{}", user_id)
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::db::Db;
+
+/// One admin-managed entry on the signup email blocklist. `pattern` is
+/// either an exact address ("abuse@example.com") or a wildcard domain
+/// pattern ("*@tempmail.com"); see `BlocklistService::is_blocked`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlocklistedEmail {
+    pub id: Uuid,
+    pub pattern: String,
+    pub reason: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Clone)]
+pub struct BlocklistService {
+    db: Db,
+}
+
+impl BlocklistService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Add a pattern to the blocklist. Patterns are matched
+    /// case-insensitively, so they're normalized to lowercase here; an exact
+    /// address is further run through `normalize_email` so it still matches
+    /// the tag-stripped/dot-collapsed form `is_blocked` checks against.
+    pub async fn add(&self, pattern: &str, reason: Option<String>) -> Result<BlocklistedEmail> {
+        let pattern = pattern.trim().to_lowercase();
+        if pattern.is_empty() {
+            return Err(anyhow!("pattern cannot be empty"));
+        }
+        let pattern = if pattern.starts_with("*@") {
+            pattern
+        } else {
+            normalize_email(&pattern)
+        };
+
+        let row = sqlx::query(
+            "INSERT INTO email_blocklist (pattern, reason) \
+             VALUES ($1, $2) \
+             RETURNING id, pattern, reason, created_at",
+        )
+        .bind(&pattern)
+        .bind(&reason)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row_to_blocklisted_email(row))
+    }
+
+    /// Remove a blocklist entry by id. Returns whether a row was deleted.
+    pub async fn remove(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM email_blocklist WHERE id = $1")
+            .bind(id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List all blocklist entries, for the admin blocklist-management surface.
+    pub async fn list(&self) -> Result<Vec<BlocklistedEmail>> {
+        let rows = sqlx::query(
+            "SELECT id, pattern, reason, created_at \
+             FROM email_blocklist \
+             ORDER BY created_at DESC",
+        )
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(row_to_blocklisted_email).collect())
+    }
+
+    /// Check whether `email` matches any blocklisted pattern -- either an
+    /// exact address, or a `*@domain` wildcard covering every address at
+    /// that domain. Called from `AuthService::signup` before the account is
+    /// inserted, so a blocklisted address never reaches the `users` table.
+    pub async fn is_blocked(&self, email: &str) -> Result<bool> {
+        let email = normalize_email(email);
+        let domain = email.split('@').nth(1).unwrap_or("");
+        let domain_pattern = format!("*@{}", domain);
+
+        let blocked: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM email_blocklist WHERE pattern = $1 OR pattern = $2)",
+        )
+        .bind(&email)
+        .bind(&domain_pattern)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(blocked)
+    }
+}
+
+/// Canonicalizes an address so that plus-tags and (for Gmail/Googlemail)
+/// dots in the local part can't be used to dodge an exact-address blocklist
+/// entry -- `abuse+foo@gmail.com` and `a.b.u.s.e@gmail.com` both normalize
+/// to `abuse@gmail.com`. Domain-wildcard patterns don't need this, since
+/// they never look at the local part.
+fn normalize_email(email: &str) -> String {
+    let email = email.trim().to_lowercase();
+    let Some((local, domain)) = email.split_once('@') else {
+        return email;
+    };
+    let local = local.split('+').next().unwrap_or(local);
+    let local = if matches!(domain, "gmail.com" | "googlemail.com") {
+        local.replace('.', "")
+    } else {
+        local.to_string()
+    };
+    format!("{}@{}", local, domain)
+}
+
+fn row_to_blocklisted_email(row: sqlx::postgres::PgRow) -> BlocklistedEmail {
+    BlocklistedEmail {
+        id: row.get("id"),
+        pattern: row.get("pattern"),
+        reason: row.get("reason"),
+        created_at: row.get("created_at"),
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::app::auth::hash_token;
+use crate::domain::bot::Bot;
+use crate::infra::db::Db;
+
+/// The distinct principal a bot's bearer token resolves to -- kept separate
+/// from `http::auth::AuthUser::user_id` so callers (the rate limiter, in
+/// particular) can key off the bot itself rather than silently attributing
+/// its traffic to `owner_id`.
+#[derive(Debug, Clone, Copy)]
+pub struct BotPrincipal {
+    pub bot_id: Uuid,
+    pub owner_id: Uuid,
+}
+
+#[derive(Clone)]
+pub struct BotService {
+    db: Db,
+}
+
+impl BotService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Create a bot owned by `owner_id`. Returns it alongside the plaintext
+    /// bearer token, shown to the caller exactly once -- only
+    /// `hash_token(token)` is persisted, the same discipline as an OAuth
+    /// client secret (`OAuthServerService::register_client`). Unlike a
+    /// PASETO access token this token never expires, so there's no
+    /// `access_ttl_minutes`-equivalent to set here; `rotate_token` is the
+    /// only way to invalidate a leaked one.
+    ///
+    /// When `interactions_url` is set, also mints a plaintext
+    /// `webhook_secret` used to HMAC-sign the events
+    /// `jobs::bot_webhook_dispatcher` delivers there -- stored in the clear
+    /// (like `users.actor_private_key_pem`), since signing a payload
+    /// requires the original secret, not a one-way hash of it.
+    pub async fn create_bot(
+        &self,
+        owner_id: Uuid,
+        public: bool,
+        interactions_url: Option<String>,
+    ) -> Result<(Bot, String)> {
+        let token = generate_secret();
+        let token_hash = hash_token(&token);
+        let webhook_secret = interactions_url.as_ref().map(|_| generate_secret());
+
+        let row = sqlx::query(
+            "INSERT INTO bots (owner_id, token_hash, public, interactions_url, webhook_secret) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id, owner_id, public, interactions_url, created_at",
+        )
+        .bind(owner_id)
+        .bind(&token_hash)
+        .bind(public)
+        .bind(&interactions_url)
+        .bind(&webhook_secret)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok((row_to_bot(&row), token))
+    }
+
+    /// List the bots `owner_id` has created.
+    pub async fn list_bots(&self, owner_id: Uuid) -> Result<Vec<Bot>> {
+        let rows = sqlx::query(
+            "SELECT id, owner_id, public, interactions_url, created_at \
+             FROM bots WHERE owner_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(owner_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.iter().map(row_to_bot).collect())
+    }
+
+    /// Mint a fresh bearer token for `bot_id`, invalidating the old one.
+    /// Only `owner_id` (the bot's own owner) may rotate it.
+    pub async fn rotate_token(&self, bot_id: Uuid, owner_id: Uuid) -> Result<String> {
+        let token = generate_secret();
+        let token_hash = hash_token(&token);
+
+        let result = sqlx::query(
+            "UPDATE bots SET token_hash = $1 WHERE id = $2 AND owner_id = $3",
+        )
+        .bind(&token_hash)
+        .bind(bot_id)
+        .bind(owner_id)
+        .execute(self.db.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("bot not found for this owner"));
+        }
+        Ok(token)
+    }
+
+    /// Resolves a bearer token presented as `Authorization: Bearer <token>`
+    /// to the bot it belongs to. Returns `Ok(None)` for a token that simply
+    /// isn't a bot token (as opposed to a PASETO access token), mirroring
+    /// `AuthService::authenticate_access_token`'s clean "not mine" signal so
+    /// `AuthUser::from_request_parts` can try this as a fallback rather than
+    /// treating a miss here as a hard error.
+    pub async fn authenticate(&self, token: &str) -> Result<Option<BotPrincipal>> {
+        let token_hash = hash_token(token);
+        let row = sqlx::query("SELECT id, owner_id FROM bots WHERE token_hash = $1")
+            .bind(&token_hash)
+            .fetch_optional(self.db.pool())
+            .await?;
+
+        Ok(row.map(|row| BotPrincipal {
+            bot_id: row.get("id"),
+            owner_id: row.get("owner_id"),
+        }))
+    }
+
+    /// Every bot `owner_id` has configured with an `interactions_url` --
+    /// the recipients `NotificationService::create` dispatches a signed
+    /// webhook to when `owner_id`'s account is mentioned or followed.
+    pub async fn bots_with_webhook(&self, owner_id: Uuid) -> Result<Vec<Bot>> {
+        let rows = sqlx::query(
+            "SELECT id, owner_id, public, interactions_url, created_at \
+             FROM bots WHERE owner_id = $1 AND interactions_url IS NOT NULL",
+        )
+        .bind(owner_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.iter().map(row_to_bot).collect())
+    }
+
+    /// The plaintext webhook-signing secret minted alongside `bot_id`'s
+    /// `interactions_url` in `create_bot`. `None` if the bot has no
+    /// `interactions_url` (and so was never issued one).
+    pub async fn webhook_secret(&self, bot_id: Uuid) -> Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT webhook_secret FROM bots WHERE id = $1")
+                .bind(bot_id)
+                .fetch_optional(self.db.pool())
+                .await?
+                .flatten(),
+        )
+    }
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn row_to_bot(row: &sqlx::postgres::PgRow) -> Bot {
+    Bot {
+        id: row.get("id"),
+        owner_id: row.get("owner_id"),
+        public: row.get("public"),
+        interactions_url: row.get("interactions_url"),
+        created_at: row.get("created_at"),
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::config::FilterMode;
+
+/// A compiled banned-term list, built once at startup from `BANNED_TERMS`
+/// (see `AppConfig::from_env`) and shared read-only on `AppState` the same
+/// way `OAuthService` shares its provider map -- rebuilding the
+/// normalized set per request would be wasted work for something that
+/// never changes without a restart.
+///
+/// Matching splits the input on non-alphanumeric boundaries and normalizes
+/// each resulting token (lowercased, diacritics stripped, common leetspeak
+/// substitutions undone, repeated characters collapsed) before comparing it
+/// against the list, so obfuscated variants of a banned word are still
+/// caught while a token that merely contains one as a substring ("class"
+/// vs "ass") is not -- the list itself is normalized the same way at
+/// construction time, so the comparison is always apples to apples.
+#[derive(Clone)]
+pub struct ContentFilter {
+    terms: Arc<HashSet<String>>,
+}
+
+impl ContentFilter {
+    pub fn new(terms: Vec<String>) -> Self {
+        Self {
+            terms: Arc::new(terms.iter().map(|term| normalize_token(term)).collect()),
+        }
+    }
+
+    /// Checks `text` against the compiled list and applies `mode`. `Reject`
+    /// returns `Err` with a message in the same register as the existing
+    /// "bio must be at most 500 characters" style validation errors;
+    /// `Censor` returns `text` with each matched token replaced by
+    /// asterisks of equal length. A filter compiled from an empty term list
+    /// never matches, so this is a no-op wherever `BANNED_TERMS` is unset.
+    pub fn enforce(&self, field: &str, text: &str, mode: FilterMode) -> Result<String> {
+        if self.terms.is_empty() {
+            return Ok(text.to_string());
+        }
+
+        let spans = self.matching_spans(text);
+        if spans.is_empty() {
+            return Ok(text.to_string());
+        }
+
+        match mode {
+            FilterMode::Reject => Err(anyhow!("{} contains disallowed language", field)),
+            FilterMode::Censor => {
+                let mut censored = text.to_string();
+                for (start, end) in spans.into_iter().rev() {
+                    let char_len = text[start..end].chars().count();
+                    censored.replace_range(start..end, &"*".repeat(char_len));
+                }
+                Ok(censored)
+            }
+        }
+    }
+
+    /// Byte ranges into `text` of every alphanumeric-bounded token that
+    /// normalizes to a banned term.
+    fn matching_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut token_start: Option<usize> = None;
+
+        for (idx, ch) in text.char_indices() {
+            if ch.is_alphanumeric() {
+                token_start.get_or_insert(idx);
+            } else if let Some(start) = token_start.take() {
+                if self.terms.contains(&normalize_token(&text[start..idx])) {
+                    spans.push((start, idx));
+                }
+            }
+        }
+        if let Some(start) = token_start {
+            if self.terms.contains(&normalize_token(&text[start..])) {
+                spans.push((start, text.len()));
+            }
+        }
+
+        spans
+    }
+}
+
+/// Lowercases, strips common Latin diacritics, undoes the leetspeak
+/// substitutions named in the feature request (`0`->o, `1`->i, `3`->e,
+/// `@`->a, `$`->s), then collapses runs of 2+ identical characters down to
+/// one -- catching stretched-out obfuscations like "fuuuck" the same way
+/// it harmlessly collapses "ball" to "bal", since the banned-term list
+/// goes through the exact same normalization.
+fn normalize_token(raw: &str) -> String {
+    let mut collapsed = String::with_capacity(raw.len());
+    let mut last: Option<char> = None;
+    for ch in raw.to_lowercase().chars() {
+        let ch = undo_leetspeak(strip_diacritic(ch));
+        if last != Some(ch) {
+            collapsed.push(ch);
+        }
+        last = Some(ch);
+    }
+    collapsed
+}
+
+fn undo_leetspeak(ch: char) -> char {
+    match ch {
+        '0' => 'o',
+        '1' => 'i',
+        '3' => 'e',
+        '@' => 'a',
+        '$' => 's',
+        other => other,
+    }
+}
+
+/// Strips the diacritic off the common Latin letters likely to show up in
+/// handle/bio obfuscation attempts. Not an exhaustive Unicode
+/// decomposition -- just enough to catch "ásshole"-style accent swaps
+/// without pulling in a normalization dependency for a handful of letters.
+fn strip_diacritic(ch: char) -> char {
+    match ch {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ñ' => 'n',
+        'ç' => 'c',
+        other => other,
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::Result;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use sqlx::Row;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::device_auth::DeviceAuthRequest;
+use crate::infra::db::Db;
+
+/// How long a pending "approve this new device" request stays valid.
+const REQUEST_TTL_MINUTES: i64 = 10;
+
+#[derive(Clone)]
+pub struct DeviceAuthService {
+    db: Db,
+}
+
+impl DeviceAuthService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Create a pending auth request for `user_id` from an unrecognized
+    /// device. Callers must check `FingerprintService::check_device_risk`
+    /// for `fingerprint_hash` and refuse blocked devices before calling this.
+    pub async fn create_request(
+        &self,
+        user_id: Uuid,
+        fingerprint_hash: String,
+        request_ip: String,
+    ) -> Result<(DeviceAuthRequest, String)> {
+        let access_code: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+        let expires_at = OffsetDateTime::now_utc() + Duration::minutes(REQUEST_TTL_MINUTES);
+
+        let row = sqlx::query(
+            "INSERT INTO auth_requests \
+                (user_id, fingerprint_hash, request_ip, access_code, expires_at) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id, user_id, fingerprint_hash, request_ip, access_code, \
+                       approved, created_at, response_date, expires_at",
+        )
+        .bind(user_id)
+        .bind(&fingerprint_hash)
+        .bind(&request_ip)
+        .bind(&access_code)
+        .bind(expires_at)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let request = row_to_request(&row);
+        Ok((request, access_code))
+    }
+
+    /// Fetch a request by id, verifying the caller presented the access
+    /// code it was issued (so only the requesting device can poll its
+    /// status).
+    pub async fn get_by_access_code(
+        &self,
+        request_id: Uuid,
+        access_code: &str,
+    ) -> Result<Option<DeviceAuthRequest>> {
+        let row = sqlx::query(
+            "SELECT id, user_id, fingerprint_hash, request_ip, access_code, \
+                    approved, created_at, response_date, expires_at \
+             FROM auth_requests \
+             WHERE id = $1 AND access_code = $2",
+        )
+        .bind(request_id)
+        .bind(access_code)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|row| row_to_request(&row)))
+    }
+
+    /// Pending requests for the account, for an already-trusted device to
+    /// review.
+    pub async fn list_pending(&self, user_id: Uuid) -> Result<Vec<DeviceAuthRequest>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, fingerprint_hash, request_ip, access_code, \
+                    approved, created_at, response_date, expires_at \
+             FROM auth_requests \
+             WHERE user_id = $1 AND approved IS NULL AND expires_at > now() \
+             ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.iter().map(row_to_request).collect())
+    }
+
+    /// Approve or deny a pending request. `responder_user_id` must own the
+    /// account the request was made against. Records a moderation-style
+    /// audit entry for either outcome.
+    pub async fn respond(
+        &self,
+        request_id: Uuid,
+        responder_user_id: Uuid,
+        approved: bool,
+    ) -> Result<Option<DeviceAuthRequest>> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let row = sqlx::query(
+            "UPDATE auth_requests \
+             SET approved = $1, response_date = now() \
+             WHERE id = $2 AND user_id = $3 AND approved IS NULL AND expires_at > now() \
+             RETURNING id, user_id, fingerprint_hash, request_ip, access_code, \
+                       approved, created_at, response_date, expires_at",
+        )
+        .bind(approved)
+        .bind(request_id)
+        .bind(responder_user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        let request = row_to_request(&row);
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'device_auth_request', $2, $3)",
+        )
+        .bind(responder_user_id)
+        .bind(request_id)
+        .bind(if approved { "approved" } else { "denied" })
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(request))
+    }
+
+    /// Delete stale (expired, never responded to) requests. Intended to run
+    /// from the same periodic cleanup task that expires stories and invites.
+    pub async fn expire_stale(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM auth_requests WHERE approved IS NULL AND expires_at < now()",
+        )
+        .execute(self.db.pool())
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn row_to_request(row: &sqlx::postgres::PgRow) -> DeviceAuthRequest {
+    DeviceAuthRequest {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        fingerprint_hash: row.get("fingerprint_hash"),
+        request_ip: row.get("request_ip"),
+        access_code: row.get("access_code"),
+        approved: row.get("approved"),
+        created_at: row.get("created_at"),
+        response_date: row.get("response_date"),
+        expires_at: row.get("expires_at"),
+    }
+}
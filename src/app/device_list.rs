@@ -0,0 +1,196 @@
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use sqlx::Row;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::device_list::{SignedDeviceList, StoredDeviceList};
+use crate::infra::db::Db;
+
+/// How far a device list's `timestamp` may drift from "now" before an
+/// update is rejected as a (possible) replay.
+const VALIDITY_WINDOW_SECONDS: i64 = 5 * 60;
+
+#[derive(Clone)]
+pub struct DeviceListService {
+    db: Db,
+}
+
+impl DeviceListService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Register (or rotate) the ed25519 public key a device will use to
+    /// sign future device-list updates.
+    pub async fn register_device_key(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        public_key_b64: &str,
+    ) -> Result<()> {
+        // Reject unparsable keys up front rather than storing garbage a
+        // later verification attempt can never succeed against.
+        decode_public_key(public_key_b64)?;
+
+        sqlx::query(
+            "INSERT INTO device_keys (device_id, user_id, public_key) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (device_id) DO UPDATE SET public_key = EXCLUDED.public_key",
+        )
+        .bind(device_id)
+        .bind(user_id)
+        .bind(public_key_b64)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_device_list(&self, user_id: Uuid) -> Result<Option<StoredDeviceList>> {
+        let row = sqlx::query(
+            "SELECT user_id, devices, timestamp, updated_at FROM device_lists WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(row_to_list))
+    }
+
+    /// Validate and apply a signed device-list update. Checks, in order:
+    /// the timestamp strictly increases, it falls within the replay
+    /// window, `cur_primary_signature` verifies against the new primary's
+    /// key (when present), and -- if the primary changed -- that
+    /// `last_primary_signature` verifies against the *old* primary's key.
+    pub async fn update_device_list(
+        &self,
+        user_id: Uuid,
+        update: SignedDeviceList,
+    ) -> Result<StoredDeviceList> {
+        if update.raw.devices.is_empty() {
+            bail!("device list must name at least one device");
+        }
+
+        let existing = self.get_device_list(user_id).await?;
+
+        if let Some(existing) = &existing {
+            if update.raw.timestamp <= existing.timestamp {
+                bail!("device list timestamp must strictly increase");
+            }
+        }
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if (now - update.raw.timestamp).unsigned_abs() > VALIDITY_WINDOW_SECONDS as u64 {
+            bail!("device list timestamp is outside the validity window");
+        }
+
+        let raw_json = serde_json::to_vec(&update.raw)
+            .map_err(|err| anyhow!("failed to serialize device list: {}", err))?;
+
+        if let Some(signature_b64) = &update.cur_primary_signature {
+            let new_primary = update.raw.devices.first().expect("checked non-empty above");
+            if !self.verify(user_id, new_primary, &raw_json, signature_b64).await? {
+                bail!("cur_primary_signature verification failed");
+            }
+        }
+
+        let old_primary = existing.as_ref().and_then(|list| list.primary_device());
+        let new_primary = update.raw.devices.first().map(String::as_str);
+        if old_primary.is_some() && old_primary != new_primary {
+            let signature_b64 = update
+                .last_primary_signature
+                .as_ref()
+                .ok_or_else(|| anyhow!("primary device changed but last_primary_signature is missing"))?;
+            let old_primary = old_primary.expect("checked Some above");
+            if !self.verify(user_id, old_primary, &raw_json, signature_b64).await? {
+                bail!("last_primary_signature verification failed");
+            }
+        }
+
+        let row = sqlx::query(
+            "INSERT INTO device_lists (user_id, devices, timestamp, cur_primary_signature, last_primary_signature, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, now()) \
+             ON CONFLICT (user_id) DO UPDATE SET \
+                devices = EXCLUDED.devices, \
+                timestamp = EXCLUDED.timestamp, \
+                cur_primary_signature = EXCLUDED.cur_primary_signature, \
+                last_primary_signature = EXCLUDED.last_primary_signature, \
+                updated_at = now() \
+             RETURNING user_id, devices, timestamp, updated_at",
+        )
+        .bind(user_id)
+        .bind(&update.raw.devices)
+        .bind(update.raw.timestamp)
+        .bind(&update.cur_primary_signature)
+        .bind(&update.last_primary_signature)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row_to_list(row))
+    }
+
+    /// Verify that `device_id`'s registered public key produced `signature_b64`
+    /// over `payload`. Returns `false` (rather than erroring) when the device
+    /// has no registered key, since an unsigned/unknown device can never
+    /// produce a valid proof.
+    async fn verify(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        payload: &[u8],
+        signature_b64: &str,
+    ) -> Result<bool> {
+        let row = sqlx::query(
+            "SELECT public_key FROM device_keys WHERE device_id = $1 AND user_id = $2",
+        )
+        .bind(device_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let public_key_b64: String = row.get("public_key");
+
+        let verifying_key = match decode_public_key(&public_key_b64) {
+            Ok(key) => key,
+            Err(_) => return Ok(false),
+        };
+
+        let signature_bytes = match BASE64.decode(signature_b64) {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(false),
+        };
+        let signature_bytes: [u8; 64] = match signature_bytes.try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(false),
+        };
+        let signature = Signature::from_bytes(&signature_bytes);
+
+        Ok(verifying_key.verify(payload, &signature).is_ok())
+    }
+}
+
+fn decode_public_key(public_key_b64: &str) -> Result<VerifyingKey> {
+    let bytes = BASE64
+        .decode(public_key_b64)
+        .map_err(|_| anyhow!("public key is not valid base64"))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("public key must be 32 bytes"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| anyhow!("invalid ed25519 public key"))
+}
+
+fn row_to_list(row: sqlx::postgres::PgRow) -> StoredDeviceList {
+    StoredDeviceList {
+        user_id: row.get("user_id"),
+        devices: row.get("devices"),
+        timestamp: row.get("timestamp"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::domain::email_verification::EmailVerification;
+use crate::infra::db::Db;
+use crate::infra::mailer::Mailer;
+
+#[derive(Clone)]
+pub struct EmailVerificationService {
+    db: Db,
+    mailer: Arc<dyn Mailer>,
+    ttl_hours: i64,
+}
+
+impl EmailVerificationService {
+    pub fn new(db: Db, mailer: Arc<dyn Mailer>, ttl_hours: i64) -> Self {
+        Self {
+            db,
+            mailer,
+            ttl_hours,
+        }
+    }
+
+    /// Issue a fresh token for `user_id` and email it, invalidating any
+    /// token already outstanding for that user so only the most recently
+    /// requested link is usable (also covers "resend").
+    pub async fn request_verification(&self, user_id: Uuid, email: &str) -> Result<()> {
+        let mut token_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut token_bytes);
+        let token = URL_SAFE_NO_PAD.encode(token_bytes);
+
+        let expires_at =
+            time::OffsetDateTime::now_utc() + time::Duration::hours(self.ttl_hours);
+
+        let mut tx = self.db.pool().begin().await?;
+
+        sqlx::query(
+            "DELETE FROM email_verifications WHERE user_id = $1 AND consumed_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO email_verifications (token, user_id, expires_at) VALUES ($1, $2, $3)",
+        )
+        .bind(&token)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.mailer
+            .send(
+                email,
+                "Verify your email",
+                &format!(
+                    "Confirm your email address by submitting this token: {}",
+                    token
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Consume a verification token, marking the owning user's email as
+    /// verified. Errors if the token is unknown, already used, or expired.
+    pub async fn confirm(&self, token: &str) -> Result<Uuid> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let row = sqlx::query(
+            "SELECT user_id, expires_at, consumed_at FROM email_verifications \
+             WHERE token = $1 FOR UPDATE",
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow!("invalid verification token"))?;
+
+        let consumed_at: Option<time::OffsetDateTime> = row.get("consumed_at");
+        if consumed_at.is_some() {
+            return Err(anyhow!("verification token already used"));
+        }
+
+        let expires_at: time::OffsetDateTime = row.get("expires_at");
+        if expires_at < time::OffsetDateTime::now_utc() {
+            return Err(anyhow!("verification token has expired"));
+        }
+
+        let user_id: Uuid = row.get("user_id");
+
+        sqlx::query("UPDATE email_verifications SET consumed_at = now() WHERE token = $1")
+            .bind(token)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE users SET email_verified_at = now() WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    /// Whether `user_id` has completed email verification. Used to gate
+    /// sensitive actions like issuing invite codes.
+    pub async fn is_verified(&self, user_id: Uuid) -> Result<bool> {
+        let verified_at: Option<time::OffsetDateTime> =
+            sqlx::query_scalar("SELECT email_verified_at FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_one(self.db.pool())
+                .await?;
+        Ok(verified_at.is_some())
+    }
+
+    #[allow(dead_code)]
+    pub async fn get_latest(&self, user_id: Uuid) -> Result<Option<EmailVerification>> {
+        let row = sqlx::query(
+            "SELECT user_id, token, created_at, expires_at, consumed_at \
+             FROM email_verifications WHERE user_id = $1 \
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|row| EmailVerification {
+            user_id: row.get("user_id"),
+            token: row.get("token"),
+            created_at: row.get("created_at"),
+            expires_at: row.get("expires_at"),
+            consumed_at: row.get("consumed_at"),
+        }))
+    }
+}
@@ -3,7 +3,7 @@ use sqlx::Row;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::engagement::{Comment, Like};
+use crate::domain::engagement::{Comment, Like, ReactionCount};
 use crate::infra::db::Db;
 
 #[derive(Clone)]
@@ -16,14 +16,26 @@ impl EngagementService {
         Self { db }
     }
 
-    pub async fn like_post(&self, user_id: Uuid, post_id: Uuid) -> Result<Option<Like>> {
+    /// Upserts the caller's reaction to `post_id`, swapping `reaction_type`
+    /// in place if they'd already reacted with a different kind instead of
+    /// no-op'ing. Returns `None` only when the caller already had this exact
+    /// `reaction_type` recorded, so callers can tell a real change (insert or
+    /// swap) from a true no-op before firing notifications/federation.
+    pub async fn react_post(
+        &self,
+        user_id: Uuid,
+        post_id: Uuid,
+        reaction_type: &str,
+    ) -> Result<Option<Like>> {
         let row = sqlx::query(
-            "INSERT INTO likes (user_id, post_id) VALUES ($1, $2) \
-             ON CONFLICT DO NOTHING \
-             RETURNING id, user_id, post_id, created_at",
+            "INSERT INTO likes (user_id, post_id, reaction_type) VALUES ($1, $2, $3) \
+             ON CONFLICT (user_id, post_id) DO UPDATE SET reaction_type = EXCLUDED.reaction_type \
+             WHERE likes.reaction_type IS DISTINCT FROM EXCLUDED.reaction_type \
+             RETURNING id, user_id, post_id, reaction_type, created_at",
         )
         .bind(user_id)
         .bind(post_id)
+        .bind(reaction_type)
         .fetch_optional(self.db.pool())
         .await?;
 
@@ -31,25 +43,86 @@ impl EngagementService {
             id: row.get("id"),
             user_id: row.get("user_id"),
             post_id: row.get("post_id"),
+            reaction_type: row.get("reaction_type"),
             created_at: row.get("created_at"),
         });
 
         Ok(like)
     }
 
+    /// Compatibility shim over `react_post` for the original binary-like
+    /// endpoints.
+    pub async fn like_post(&self, user_id: Uuid, post_id: Uuid) -> Result<Option<Like>> {
+        self.react_post(user_id, post_id, "like").await
+    }
+
+    /// Grouped reaction counts for `post_id`, one row per distinct
+    /// `reaction_type` present.
+    pub async fn reaction_counts(&self, post_id: Uuid) -> Result<Vec<ReactionCount>> {
+        let rows = sqlx::query(
+            "SELECT reaction_type, COUNT(*) AS count FROM likes \
+             WHERE post_id = $1 \
+             GROUP BY reaction_type \
+             ORDER BY count DESC",
+        )
+        .bind(post_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ReactionCount {
+                reaction_type: row.get("reaction_type"),
+                count: row.get("count"),
+            })
+            .collect())
+    }
+
+    /// Insert a comment, optionally as a reply to `parent_comment_id`. A
+    /// `Some` parent must belong to `post_id` and must not already sit at
+    /// `max_depth`, otherwise an error is returned describing which check
+    /// failed (see `http::handlers::comment_post`'s message matching).
     pub async fn comment_post(
         &self,
         user_id: Uuid,
         post_id: Uuid,
         body: String,
+        parent_comment_id: Option<Uuid>,
+        max_depth: i32,
     ) -> Result<Comment> {
+        let depth = match parent_comment_id {
+            Some(parent_id) => {
+                let parent = sqlx::query("SELECT post_id, depth FROM comments WHERE id = $1")
+                    .bind(parent_id)
+                    .fetch_optional(self.db.pool())
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("parent comment does not exist"))?;
+
+                let parent_post_id: Uuid = parent.get("post_id");
+                if parent_post_id != post_id {
+                    return Err(anyhow::anyhow!("parent comment belongs to a different post"));
+                }
+
+                let parent_depth: i32 = parent.get("depth");
+                let depth = parent_depth + 1;
+                if depth > max_depth {
+                    return Err(anyhow::anyhow!("comment nesting exceeds max depth"));
+                }
+                depth
+            }
+            None => 0,
+        };
+
         let row = sqlx::query(
-            "INSERT INTO comments (user_id, post_id, body) VALUES ($1, $2, $3) \
-             RETURNING id, user_id, post_id, body, created_at",
+            "INSERT INTO comments (user_id, post_id, body, parent_comment_id, depth) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id, user_id, post_id, body, parent_comment_id, created_at",
         )
         .bind(user_id)
         .bind(post_id)
         .bind(body)
+        .bind(parent_comment_id)
+        .bind(depth)
         .fetch_one(self.db.pool())
         .await?;
 
@@ -58,37 +131,55 @@ impl EngagementService {
             user_id: row.get("user_id"),
             post_id: row.get("post_id"),
             body: row.get("body"),
+            parent_comment_id: row.get("parent_comment_id"),
             created_at: row.get("created_at"),
         })
     }
 
-    pub async fn unlike_post(&self, user_id: Uuid, post_id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM likes WHERE user_id = $1 AND post_id = $2")
-            .bind(user_id)
-            .bind(post_id)
-            .execute(self.db.pool())
-            .await?;
+    /// Removes the caller's reaction to `post_id`, whatever its kind.
+    /// Returns the removed `reaction_type`, so a caller can tell whether an
+    /// outbound `Undo{Like}` federation activity applies (only `"like"`
+    /// reactions are ever relayed as ActivityPub `Like`s).
+    pub async fn unreact_post(&self, user_id: Uuid, post_id: Uuid) -> Result<Option<String>> {
+        let row = sqlx::query(
+            "DELETE FROM likes WHERE user_id = $1 AND post_id = $2 RETURNING reaction_type",
+        )
+        .bind(user_id)
+        .bind(post_id)
+        .fetch_optional(self.db.pool())
+        .await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(row.map(|row| row.get("reaction_type")))
+    }
+
+    /// Compatibility shim over `unreact_post` for the original binary-like
+    /// endpoints.
+    pub async fn unlike_post(&self, user_id: Uuid, post_id: Uuid) -> Result<bool> {
+        Ok(self.unreact_post(user_id, post_id).await?.is_some())
     }
 
+    /// Lists `post_id`'s reactions, newest first. When `kind` is set, only
+    /// reactions of that type are returned.
     pub async fn list_likes(
         &self,
         post_id: Uuid,
+        kind: Option<&str>,
         cursor: Option<(OffsetDateTime, Uuid)>,
         limit: i64,
     ) -> Result<Vec<Like>> {
         let rows = match cursor {
             Some((created_at, like_id)) => {
                 sqlx::query(
-                    "SELECT id, user_id, post_id, created_at \
+                    "SELECT id, user_id, post_id, reaction_type, created_at \
                      FROM likes \
                      WHERE post_id = $1 \
-                       AND (created_at < $2 OR (created_at = $2 AND id < $3)) \
+                       AND ($2::text IS NULL OR reaction_type = $2) \
+                       AND (created_at < $3 OR (created_at = $3 AND id < $4)) \
                      ORDER BY created_at DESC, id DESC \
-                     LIMIT $4",
+                     LIMIT $5",
                 )
                 .bind(post_id)
+                .bind(kind)
                 .bind(created_at)
                 .bind(like_id)
                 .bind(limit)
@@ -97,13 +188,15 @@ impl EngagementService {
             }
             None => {
                 sqlx::query(
-                    "SELECT id, user_id, post_id, created_at \
+                    "SELECT id, user_id, post_id, reaction_type, created_at \
                      FROM likes \
                      WHERE post_id = $1 \
+                       AND ($2::text IS NULL OR reaction_type = $2) \
                      ORDER BY created_at DESC, id DESC \
-                     LIMIT $2",
+                     LIMIT $3",
                 )
                 .bind(post_id)
+                .bind(kind)
                 .bind(limit)
                 .fetch_all(self.db.pool())
                 .await?
@@ -116,6 +209,7 @@ impl EngagementService {
                 id: row.get("id"),
                 user_id: row.get("user_id"),
                 post_id: row.get("post_id"),
+                reaction_type: row.get("reaction_type"),
                 created_at: row.get("created_at"),
             });
         }
@@ -123,23 +217,31 @@ impl EngagementService {
         Ok(likes)
     }
 
+    /// Lists `post_id`'s comments, newest first. When `top_level_only` is
+    /// set, only root comments (`parent_comment_id IS NULL`) are returned,
+    /// so a client can lazy-load each thread's replies separately via
+    /// `list_comment_replies` instead of paging through a flattened list.
     pub async fn list_comments(
         &self,
         post_id: Uuid,
+        top_level_only: bool,
         cursor: Option<(OffsetDateTime, Uuid)>,
         limit: i64,
     ) -> Result<Vec<Comment>> {
         let rows = match cursor {
             Some((created_at, comment_id)) => {
                 sqlx::query(
-                    "SELECT id, user_id, post_id, body, created_at \
+                    "SELECT id, user_id, post_id, body, parent_comment_id, created_at \
                      FROM comments \
                      WHERE post_id = $1 \
-                       AND (created_at < $2 OR (created_at = $2 AND id < $3)) \
+                       AND removed_at IS NULL \
+                       AND ($2 OR parent_comment_id IS NULL) \
+                       AND (created_at < $3 OR (created_at = $3 AND id < $4)) \
                      ORDER BY created_at DESC, id DESC \
-                     LIMIT $4",
+                     LIMIT $5",
                 )
                 .bind(post_id)
+                .bind(!top_level_only)
                 .bind(created_at)
                 .bind(comment_id)
                 .bind(limit)
@@ -148,13 +250,16 @@ impl EngagementService {
             }
             None => {
                 sqlx::query(
-                    "SELECT id, user_id, post_id, body, created_at \
+                    "SELECT id, user_id, post_id, body, parent_comment_id, created_at \
                      FROM comments \
                      WHERE post_id = $1 \
+                       AND removed_at IS NULL \
+                       AND ($2 OR parent_comment_id IS NULL) \
                      ORDER BY created_at DESC, id DESC \
-                     LIMIT $2",
+                     LIMIT $3",
                 )
                 .bind(post_id)
+                .bind(!top_level_only)
                 .bind(limit)
                 .fetch_all(self.db.pool())
                 .await?
@@ -168,6 +273,7 @@ impl EngagementService {
                 user_id: row.get("user_id"),
                 post_id: row.get("post_id"),
                 body: row.get("body"),
+                parent_comment_id: row.get("parent_comment_id"),
                 created_at: row.get("created_at"),
             });
         }
@@ -175,21 +281,99 @@ impl EngagementService {
         Ok(comments)
     }
 
+    /// Lists direct replies to `parent_id`, newest first -- the lazy-load
+    /// companion to `list_comments(top_level_only = true)`.
+    pub async fn list_comment_replies(
+        &self,
+        parent_id: Uuid,
+        cursor: Option<(OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<Comment>> {
+        let rows = match cursor {
+            Some((created_at, comment_id)) => {
+                sqlx::query(
+                    "SELECT id, user_id, post_id, body, parent_comment_id, created_at \
+                     FROM comments \
+                     WHERE parent_comment_id = $1 \
+                       AND removed_at IS NULL \
+                       AND (created_at < $2 OR (created_at = $2 AND id < $3)) \
+                     ORDER BY created_at DESC, id DESC \
+                     LIMIT $4",
+                )
+                .bind(parent_id)
+                .bind(created_at)
+                .bind(comment_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT id, user_id, post_id, body, parent_comment_id, created_at \
+                     FROM comments \
+                     WHERE parent_comment_id = $1 AND removed_at IS NULL \
+                     ORDER BY created_at DESC, id DESC \
+                     LIMIT $2",
+                )
+                .bind(parent_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        let mut comments = Vec::with_capacity(rows.len());
+        for row in rows {
+            comments.push(Comment {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                post_id: row.get("post_id"),
+                body: row.get("body"),
+                parent_comment_id: row.get("parent_comment_id"),
+                created_at: row.get("created_at"),
+            });
+        }
+
+        Ok(comments)
+    }
+
+    /// Deletes a comment the caller owns. A comment with no replies is
+    /// removed outright; one with replies is soft-deleted instead -- its
+    /// body replaced with a tombstone and `deleted_at` stamped -- so the
+    /// reply chain underneath it doesn't end up pointing at a missing
+    /// parent.
     pub async fn delete_comment(
         &self,
         comment_id: Uuid,
         post_id: Uuid,
         user_id: Uuid,
     ) -> Result<bool> {
-        let result = sqlx::query(
-            "DELETE FROM comments WHERE id = $1 AND post_id = $2 AND user_id = $3",
+        let has_replies: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM comments WHERE parent_comment_id = $1)",
         )
         .bind(comment_id)
-        .bind(post_id)
-        .bind(user_id)
-        .execute(self.db.pool())
+        .fetch_one(self.db.pool())
         .await?;
 
+        let result = if has_replies {
+            sqlx::query(
+                "UPDATE comments SET body = '[deleted]', deleted_at = now() \
+                 WHERE id = $1 AND post_id = $2 AND user_id = $3 AND deleted_at IS NULL",
+            )
+            .bind(comment_id)
+            .bind(post_id)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await?
+        } else {
+            sqlx::query("DELETE FROM comments WHERE id = $1 AND post_id = $2 AND user_id = $3")
+                .bind(comment_id)
+                .bind(post_id)
+                .bind(user_id)
+                .execute(self.db.pool())
+                .await?
+        };
+
         Ok(result.rows_affected() > 0)
     }
 }
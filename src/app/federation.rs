@@ -0,0 +1,839 @@
+use anyhow::{anyhow, Result};
+use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::domain::post::Post;
+use crate::domain::story::{Story, StoryHighlight, StoryVisibility};
+use crate::domain::user::User;
+use crate::infra::db::Db;
+
+const PUBLIC_COLLECTION: &str = "https://www.w3.org/ns/activitystreams#Public";
+const ACTIVITYSTREAMS_CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+const SECURITY_CONTEXT: &str = "https://w3id.org/security/v1";
+
+/// Generates a fresh RSA keypair for a newly-signed-up user's ActivityPub
+/// actor. Called once per new account, from `AuthService::signup` or from
+/// LDAP provisioning (`AuthService::provision_ldap_user`); the private key is stored
+/// alongside the user row (`users.actor_private_key_pem`) and never leaves
+/// this process, while the public key is served from the actor document.
+pub fn generate_actor_keypair() -> Result<(String, String)> {
+    let mut rng = rand::thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, 2048)
+        .map_err(|err| anyhow!("failed to generate actor keypair: {}", err))?;
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let private_pem = private_key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|err| anyhow!("failed to encode actor private key: {}", err))?
+        .to_string();
+    let public_pem = public_key
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|err| anyhow!("failed to encode actor public key: {}", err))?;
+
+    Ok((public_pem, private_pem))
+}
+
+/// The resolved `to`/`cc` addressing for an outbound activity, derived from
+/// a story's `StoryVisibility` the same way the read-path queries
+/// (`push_visibility_and_cursor`) derive access: `public` reaches the public
+/// collection plus the author's followers collection, while
+/// `friends_only`/`close_friends_only` are addressed only to the author's
+/// mutual followers so non-mutuals never receive them over federation
+/// either.
+pub struct StoryAudience {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+}
+
+/// The outcome of `FederationService::follow_remote_actor`: everything the
+/// caller needs to enqueue delivery of the `Follow` to the discovered
+/// actor's inbox.
+pub struct RemoteFollowTarget {
+    pub inbox: String,
+    pub actor_url: String,
+    pub activity: serde_json::Value,
+}
+
+/// An activity `ingest_activity` owes back to the actor it was ingested
+/// from -- currently just the `Accept` for a `Follow` -- paired with the
+/// inbox to deliver it to, so the caller can enqueue it directly rather
+/// than relying on `FederationJob`'s broadcast-style fan-out (which is for
+/// activities with no single addressee, e.g. a story `Create`).
+pub struct ReplyActivity {
+    pub activity: serde_json::Value,
+    pub inbox: String,
+}
+
+#[derive(Clone)]
+pub struct FederationService {
+    db: Db,
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl FederationService {
+    pub fn new(db: Db, base_url: String) -> Self {
+        Self {
+            db,
+            base_url,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// The actor's ActivityStreams `id`, which must dereference (via
+    /// content negotiation on `GET`) to the `Person` document itself --
+    /// i.e. this is also the route `actor_document` is served from.
+    pub fn actor_uri(&self, user_id: Uuid) -> String {
+        format!("{}/users/{}/actor", self.base_url, user_id)
+    }
+
+    fn followers_uri(&self, user_id: Uuid) -> String {
+        format!("{}/users/{}/followers", self.base_url, user_id)
+    }
+
+    fn outbox_uri(&self, user_id: Uuid) -> String {
+        format!("{}/users/{}/outbox", self.base_url, user_id)
+    }
+
+    fn inbox_uri(&self, user_id: Uuid) -> String {
+        format!("{}/users/{}/inbox", self.base_url, user_id)
+    }
+
+    fn object_uri(&self, story_id: Uuid) -> String {
+        format!("{}/stories/{}", self.base_url, story_id)
+    }
+
+    fn highlight_uri(&self, user_id: Uuid, highlight_id: Uuid) -> String {
+        format!("{}/users/{}/highlights/{}", self.base_url, user_id, highlight_id)
+    }
+
+    fn post_uri(&self, post_id: Uuid) -> String {
+        format!("{}/posts/{}", self.base_url, post_id)
+    }
+
+    /// Fetches the PEM generated for `user_id` at signup (see
+    /// `generate_actor_keypair`), for embedding in its actor document.
+    /// `None` for a user that somehow predates federation being enabled.
+    pub async fn actor_public_key_pem(&self, user_id: Uuid) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT actor_public_key_pem FROM users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?)
+    }
+
+    /// Resolves `acct:handle@domain` to the local user's actor URL, per
+    /// RFC 7033. `domain` is checked against `base_url` so a WebFinger
+    /// lookup for a different host's handle -- this server isn't
+    /// authoritative for it -- returns `None` rather than leaking whether
+    /// the handle exists anywhere.
+    pub async fn webfinger(&self, resource: &str, user: &User) -> Result<Option<serde_json::Value>> {
+        let Some(acct) = resource.strip_prefix("acct:") else {
+            return Ok(None);
+        };
+        let Some((handle, domain)) = acct.split_once('@') else {
+            return Ok(None);
+        };
+        if handle != user.handle || !self.base_url.ends_with(domain) {
+            return Ok(None);
+        }
+
+        let actor_uri = self.actor_uri(user.id);
+        Ok(Some(serde_json::json!({
+            "subject": resource,
+            "aliases": [actor_uri],
+            "links": [
+                {
+                    "rel": "self",
+                    "type": "application/activity+json",
+                    "href": actor_uri,
+                }
+            ],
+        })))
+    }
+
+    /// The ActivityStreams `Person` document served at `user`'s actor URL,
+    /// carrying the public key WebFinger-discovering servers use to verify
+    /// this user's signed outbound activities (and that this inbox's
+    /// signature verification checks against for *inbound* ones from the
+    /// same user, in the unusual case of a self-follow-style loopback).
+    pub fn actor_document(&self, user: &User, public_key_pem: &str) -> serde_json::Value {
+        let actor_uri = self.actor_uri(user.id);
+        serde_json::json!({
+            "@context": [ACTIVITYSTREAMS_CONTEXT, SECURITY_CONTEXT],
+            "id": actor_uri,
+            "type": "Person",
+            "preferredUsername": user.handle,
+            "name": user.display_name,
+            "summary": user.bio,
+            "inbox": self.inbox_uri(user.id),
+            "outbox": self.outbox_uri(user.id),
+            "followers": self.followers_uri(user.id),
+            "publicKey": {
+                "id": format!("{}#main-key", actor_uri),
+                "owner": actor_uri,
+                "publicKeyPem": public_key_pem,
+            },
+        })
+    }
+
+    /// `posts` must already be filtered to what an anonymous/public viewer
+    /// may see (i.e. fetched with `viewer_id: None`) -- this just wraps
+    /// them in the `OrderedCollection` envelope, it doesn't apply
+    /// visibility itself. `next_cursor`, when present, is an already
+    /// HMAC-sealed cursor from the same `parse_cursor`/`encode_cursor` pair
+    /// `list_user_posts` uses, so a remote server paging the outbox can't
+    /// forge a window it hasn't legitimately paged through either.
+    pub fn outbox(&self, user_id: Uuid, posts: &[Post], next_cursor: Option<String>) -> serde_json::Value {
+        let actor_uri = self.actor_uri(user_id);
+        let items: Vec<serde_json::Value> = posts
+            .iter()
+            .map(|post| {
+                serde_json::json!({
+                    "id": self.post_uri(post.id),
+                    "type": "Create",
+                    "actor": actor_uri,
+                    "published": post.created_at.format(&time::format_description::well_known::Rfc3339).unwrap_or_default(),
+                    "to": [PUBLIC_COLLECTION],
+                    "object": {
+                        "id": self.post_uri(post.id),
+                        "type": "Note",
+                        "attributedTo": actor_uri,
+                        "content": post.caption,
+                        "to": [PUBLIC_COLLECTION],
+                    },
+                })
+            })
+            .collect();
+
+        let mut document = serde_json::json!({
+            "@context": ACTIVITYSTREAMS_CONTEXT,
+            "id": self.outbox_uri(user_id),
+            "type": "OrderedCollection",
+            "totalItems": items.len(),
+            "orderedItems": items,
+        });
+        if let Some(cursor) = next_cursor {
+            document["next"] = serde_json::Value::String(format!("{}?cursor={}", self.outbox_uri(user_id), cursor));
+        }
+        document
+    }
+
+    /// Upserts a shadow row for a remote actor we've just verified a
+    /// signed activity from, so `remote_follows`/future inbound activity
+    /// lookups can key off a stable local id instead of re-resolving the
+    /// actor URL every time.
+    async fn upsert_remote_actor(&self, actor_uri: &str) -> Result<Uuid> {
+        Ok(sqlx::query_scalar(
+            "INSERT INTO remote_actors (actor_uri) VALUES ($1) \
+             ON CONFLICT (actor_uri) DO UPDATE SET actor_uri = EXCLUDED.actor_uri \
+             RETURNING id",
+        )
+        .bind(actor_uri)
+        .fetch_one(self.db.pool())
+        .await?)
+    }
+
+    /// Like `upsert_remote_actor`, but also records the actor's inbox URL.
+    /// This is the persisted cache `federation_sweeper` reads from to
+    /// deliver a broadcast-style activity to every remote follower's inbox
+    /// without re-fetching each recipient's actor document at send time.
+    async fn upsert_remote_actor_with_inbox(&self, actor_uri: &str, inbox: &str) -> Result<Uuid> {
+        Ok(sqlx::query_scalar(
+            "INSERT INTO remote_actors (actor_uri, inbox_url) VALUES ($1, $2) \
+             ON CONFLICT (actor_uri) DO UPDATE SET inbox_url = EXCLUDED.inbox_url \
+             RETURNING id",
+        )
+        .bind(actor_uri)
+        .bind(inbox)
+        .fetch_one(self.db.pool())
+        .await?)
+    }
+
+    /// The persisted inbox for `actor_uri`, if we've already recorded one
+    /// (from a prior `follow_remote_actor` or `resolve_or_fetch_inbox`
+    /// call) -- `None` when this actor has never been resolved before.
+    async fn persisted_inbox(&self, actor_uri: &str) -> Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT inbox_url FROM remote_actors WHERE actor_uri = $1")
+                .bind(actor_uri)
+                .fetch_optional(self.db.pool())
+                .await?
+                .flatten(),
+        )
+    }
+
+    /// Resolves `actor_uri`'s inbox, preferring the persisted cache over a
+    /// fresh fetch of the actor document. Used by `ingest_activity`'s
+    /// `Follow` handling, which needs the follower's inbox to deliver the
+    /// `Accept` it owes back but -- unlike `follow_remote_actor` -- doesn't
+    /// already have it from a WebFinger lookup.
+    async fn resolve_or_fetch_inbox(&self, actor_uri: &str) -> Result<String> {
+        if let Some(inbox) = self.persisted_inbox(actor_uri).await? {
+            return Ok(inbox);
+        }
+
+        let actor_document: serde_json::Value = self
+            .http
+            .get(actor_uri)
+            .header("Accept", "application/activity+json")
+            .send()
+            .await?
+            .json()
+            .await?;
+        let inbox = actor_document["inbox"]
+            .as_str()
+            .ok_or_else(|| anyhow!("remote actor document is missing inbox"))?
+            .to_string();
+
+        self.upsert_remote_actor_with_inbox(actor_uri, &inbox).await?;
+        Ok(inbox)
+    }
+
+    /// Resolves `acct:handle@domain` (the `acct:` prefix is optional) to its
+    /// ActivityStreams actor id via the target domain's `/.well-known/
+    /// webfinger` endpoint (RFC 7033), picking the `rel = "self"` link
+    /// whose `type` is a JSON media type.
+    async fn resolve_actor_url(&self, acct: &str) -> Result<String> {
+        let acct = acct.strip_prefix("acct:").unwrap_or(acct);
+        let (handle, domain) = acct
+            .split_once('@')
+            .ok_or_else(|| anyhow!("remote follow target must be handle@domain"))?;
+
+        let webfinger: serde_json::Value = self
+            .http
+            .get(format!(
+                "https://{}/.well-known/webfinger?resource=acct:{}@{}",
+                domain, handle, domain
+            ))
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        webfinger["links"]
+            .as_array()
+            .and_then(|links| {
+                links.iter().find(|link| {
+                    link["rel"] == "self"
+                        && link["type"]
+                            .as_str()
+                            .map(|media_type| media_type.contains("json"))
+                            .unwrap_or(false)
+                })
+            })
+            .and_then(|link| link["href"].as_str())
+            .map(|href| href.to_string())
+            .ok_or_else(|| anyhow!("webfinger response for {} has no self link", acct))
+    }
+
+    /// Fetches the discovered actor's document for its `inbox`, and records
+    /// a `pending` `remote_follow_requests` row -- mirrors
+    /// `ingest_activity`'s "enqueue now, deliver later" contract: the
+    /// caller hands the returned activity to
+    /// `state.queue.enqueue_federation_job` rather than this method making
+    /// the delivery itself. The row flips to `accepted` when the remote
+    /// actor's `Accept` reaches `ingest_activity`.
+    pub async fn follow_remote_actor(&self, follower_id: Uuid, acct: &str) -> Result<RemoteFollowTarget> {
+        let actor_url = self.resolve_actor_url(acct).await?;
+
+        let actor_document: serde_json::Value = self
+            .http
+            .get(&actor_url)
+            .header("Accept", "application/activity+json")
+            .send()
+            .await?
+            .json()
+            .await?;
+        let inbox = actor_document["inbox"]
+            .as_str()
+            .ok_or_else(|| anyhow!("remote actor document is missing inbox"))?
+            .to_string();
+
+        let remote_actor_id = self.upsert_remote_actor_with_inbox(&actor_url, &inbox).await?;
+        sqlx::query(
+            "INSERT INTO remote_follow_requests (follower_id, remote_actor_id, state) \
+             VALUES ($1, $2, 'pending') \
+             ON CONFLICT (follower_id, remote_actor_id) DO NOTHING",
+        )
+        .bind(follower_id)
+        .bind(remote_actor_id)
+        .execute(self.db.pool())
+        .await?;
+
+        let activity = serde_json::json!({
+            "@context": ACTIVITYSTREAMS_CONTEXT,
+            "type": "Follow",
+            "actor": self.actor_uri(follower_id),
+            "object": actor_url,
+        });
+
+        Ok(RemoteFollowTarget {
+            inbox,
+            actor_url,
+            activity,
+        })
+    }
+
+    /// Re-resolves `acct:handle@domain` via WebFinger and reports how
+    /// `follower_id`'s `Follow` toward it currently stands -- used by the
+    /// `/relationship` endpoint to report a remote follow the same way
+    /// `RelationshipStatus::is_pending` reports a local one.
+    pub async fn remote_follow_state(&self, follower_id: Uuid, acct: &str) -> Result<Option<String>> {
+        let actor_url = self.resolve_actor_url(acct).await?;
+        Ok(sqlx::query_scalar(
+            "SELECT r.state FROM remote_follow_requests r \
+             JOIN remote_actors a ON a.id = r.remote_actor_id \
+             WHERE r.follower_id = $1 AND a.actor_uri = $2",
+        )
+        .bind(follower_id)
+        .bind(actor_url)
+        .fetch_optional(self.db.pool())
+        .await?)
+    }
+
+    /// Dispatches an already-signature-verified inbound activity.
+    /// `actor_uri` is the verified signer (see
+    /// `signature::SignatureService::verify_signed_request`),
+    /// used here rather than trusting the activity body's own `actor`
+    /// field, which an attacker controls.
+    ///
+    /// `Follow`/`Undo(Follow)` are recorded in `remote_follows`, and
+    /// `Like`/`Undo(Like)` in `remote_likes` -- both separate from their
+    /// local (`follows`/`likes`) counterparts, which foreign-key to
+    /// `users` and have no row for a remote actor. `Create(Note)` is
+    /// stored for display and likewise does not flow through
+    /// `PostService`/`EngagementService`: a top-level note lands in
+    /// `remote_posts`, one with `inReplyTo` set to a local post lands in
+    /// `remote_comments`. `Delete` removes a previously-ingested remote
+    /// post by its object id.
+    ///
+    /// Returns a `ReplyActivity` the caller should hand to
+    /// `state.queue.enqueue_federation_job` for delivery back to the
+    /// remote actor -- currently just the `Accept` owed in response to a
+    /// `Follow`, per the same "enqueue now, deliver later" contract
+    /// `create_story` already uses for outbound `Create`/`Delete`.
+    pub async fn ingest_activity(
+        &self,
+        actor_uri: &str,
+        activity: &serde_json::Value,
+    ) -> Result<Option<ReplyActivity>> {
+        let activity_type = activity["type"]
+            .as_str()
+            .ok_or_else(|| anyhow!("activity is missing type"))?;
+
+        match activity_type {
+            "Follow" => {
+                let followee_id = self.local_user_id_from_object(&activity["object"])?;
+                let remote_actor_id = self.upsert_remote_actor(actor_uri).await?;
+                sqlx::query(
+                    "INSERT INTO remote_follows (remote_actor_id, followee_id) \
+                     VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                )
+                .bind(remote_actor_id)
+                .bind(followee_id)
+                .execute(self.db.pool())
+                .await?;
+
+                let inbox = self.resolve_or_fetch_inbox(actor_uri).await?;
+                Ok(Some(ReplyActivity {
+                    activity: self.build_accept_activity(followee_id, activity),
+                    inbox,
+                }))
+            }
+            "Accept" if activity["object"]["type"] == "Follow" => {
+                // The embedded `object` is the `Follow` we sent (see
+                // `follow_remote_actor`); its `actor` is the local follower,
+                // and the outer activity's verified signer is the remote
+                // actor who just accepted.
+                let follower_id = self.local_user_id_from_object(&activity["object"]["actor"])?;
+                let remote_actor_id = self.upsert_remote_actor(actor_uri).await?;
+                sqlx::query(
+                    "UPDATE remote_follow_requests SET state = 'accepted' \
+                     WHERE follower_id = $1 AND remote_actor_id = $2",
+                )
+                .bind(follower_id)
+                .bind(remote_actor_id)
+                .execute(self.db.pool())
+                .await?;
+
+                Ok(None)
+            }
+            "Undo" if activity["object"]["type"] == "Follow" => {
+                let followee_id = self.local_user_id_from_object(&activity["object"]["object"])?;
+                let remote_actor_id = self.upsert_remote_actor(actor_uri).await?;
+                sqlx::query(
+                    "DELETE FROM remote_follows WHERE remote_actor_id = $1 AND followee_id = $2",
+                )
+                .bind(remote_actor_id)
+                .bind(followee_id)
+                .execute(self.db.pool())
+                .await?;
+
+                Ok(None)
+            }
+            "Like" => {
+                let object_uri = activity["object"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Like activity object is not a string URI"))?;
+                let remote_actor_id = self.upsert_remote_actor(actor_uri).await?;
+
+                if let Some(story_id) = self.local_story_id_from_uri(object_uri) {
+                    sqlx::query(
+                        "INSERT INTO remote_story_reactions (remote_actor_id, story_id) \
+                         VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                    )
+                    .bind(remote_actor_id)
+                    .bind(story_id)
+                    .execute(self.db.pool())
+                    .await?;
+                } else {
+                    let post_id = self.local_post_id_from_uri(object_uri)?;
+                    sqlx::query(
+                        "INSERT INTO remote_likes (remote_actor_id, post_id) \
+                         VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                    )
+                    .bind(remote_actor_id)
+                    .bind(post_id)
+                    .execute(self.db.pool())
+                    .await?;
+                }
+
+                Ok(None)
+            }
+            "Undo" if activity["object"]["type"] == "Like" => {
+                let object_uri = activity["object"]["object"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Undo(Like) activity object is not a string URI"))?;
+                let remote_actor_id = self.upsert_remote_actor(actor_uri).await?;
+
+                if let Some(story_id) = self.local_story_id_from_uri(object_uri) {
+                    sqlx::query(
+                        "DELETE FROM remote_story_reactions WHERE remote_actor_id = $1 AND story_id = $2",
+                    )
+                    .bind(remote_actor_id)
+                    .bind(story_id)
+                    .execute(self.db.pool())
+                    .await?;
+                } else {
+                    let post_id = self.local_post_id_from_uri(object_uri)?;
+                    sqlx::query(
+                        "DELETE FROM remote_likes WHERE remote_actor_id = $1 AND post_id = $2",
+                    )
+                    .bind(remote_actor_id)
+                    .bind(post_id)
+                    .execute(self.db.pool())
+                    .await?;
+                }
+
+                Ok(None)
+            }
+            "Create" if activity["object"]["type"] == "Note" => {
+                let remote_actor_id = self.upsert_remote_actor(actor_uri).await?;
+                let object_uri = activity["object"]["id"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Create(Note) object is missing id"))?;
+                let content = activity["object"]["content"].as_str();
+                let in_reply_to_post_id = activity["object"]["inReplyTo"]
+                    .as_str()
+                    .and_then(|uri| self.local_post_id_from_uri(uri).ok());
+
+                match in_reply_to_post_id {
+                    Some(post_id) => {
+                        sqlx::query(
+                            "INSERT INTO remote_comments (remote_actor_id, post_id, object_uri, content) \
+                             VALUES ($1, $2, $3, $4) \
+                             ON CONFLICT (object_uri) DO UPDATE SET content = EXCLUDED.content",
+                        )
+                        .bind(remote_actor_id)
+                        .bind(post_id)
+                        .bind(object_uri)
+                        .bind(content)
+                        .execute(self.db.pool())
+                        .await?;
+                    }
+                    None => {
+                        sqlx::query(
+                            "INSERT INTO remote_posts (remote_actor_id, object_uri, content) \
+                             VALUES ($1, $2, $3) \
+                             ON CONFLICT (object_uri) DO UPDATE SET content = EXCLUDED.content",
+                        )
+                        .bind(remote_actor_id)
+                        .bind(object_uri)
+                        .bind(content)
+                        .execute(self.db.pool())
+                        .await?;
+                    }
+                }
+
+                Ok(None)
+            }
+            "Delete" => {
+                let object_uri = activity["object"]
+                    .as_str()
+                    .or_else(|| activity["object"]["id"].as_str())
+                    .ok_or_else(|| anyhow!("Delete activity is missing object"))?;
+                sqlx::query("DELETE FROM remote_posts WHERE object_uri = $1")
+                    .bind(object_uri)
+                    .execute(self.db.pool())
+                    .await?;
+
+                Ok(None)
+            }
+            other => Err(anyhow!("unsupported activity type: {}", other)),
+        }
+    }
+
+    /// Builds the `Accept{Follow}` a local actor owes a remote follower in
+    /// response to an inbound `Follow` -- wraps the original activity back
+    /// as `object`, per the ActivityPub spec, so the remote server can
+    /// match it to the `Follow` it sent.
+    fn build_accept_activity(&self, followee_id: Uuid, follow_activity: &serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "@context": ACTIVITYSTREAMS_CONTEXT,
+            "type": "Accept",
+            "actor": self.actor_uri(followee_id),
+            "object": follow_activity,
+        })
+    }
+
+    /// Extracts the local user id from an inbound activity's `object`
+    /// field, which for `Follow`/`Undo(Follow)` is this server's actor URI
+    /// (`{base_url}/users/{id}/actor`, see `actor_uri`).
+    fn local_user_id_from_object(&self, object: &serde_json::Value) -> Result<Uuid> {
+        let object_uri = object
+            .as_str()
+            .ok_or_else(|| anyhow!("activity object is not a string actor URI"))?;
+        let id_str = object_uri
+            .strip_prefix(&format!("{}/users/", self.base_url))
+            .and_then(|rest| rest.strip_suffix("/actor"))
+            .ok_or_else(|| anyhow!("activity object is not a local actor URI"))?;
+        Uuid::parse_str(id_str).map_err(|err| anyhow!("invalid actor URI: {}", err))
+    }
+
+    fn local_post_id_from_uri(&self, object_uri: &str) -> Result<Uuid> {
+        let id_str = object_uri
+            .strip_prefix(&format!("{}/posts/", self.base_url))
+            .ok_or_else(|| anyhow!("activity object is not a local post URI"))?;
+        Uuid::parse_str(id_str).map_err(|err| anyhow!("invalid post URI: {}", err))
+    }
+
+    /// Extracts the local story id from an inbound `Like`/`Undo(Like)`
+    /// object URI (`{base_url}/stories/{id}`, see `object_uri`). Unlike
+    /// `local_post_id_from_uri` this returns `Option` rather than `Result`:
+    /// callers use it to distinguish a story `Like` from a post `Like`
+    /// sharing the same activity shape, not to reject malformed input.
+    fn local_story_id_from_uri(&self, object_uri: &str) -> Option<Uuid> {
+        let id_str = object_uri.strip_prefix(&format!("{}/stories/", self.base_url))?;
+        Uuid::parse_str(id_str).ok()
+    }
+
+    /// Resolve the `to`/`cc` addressing for federating `story`. Mutual
+    /// followers are users who follow the author AND are followed back by
+    /// the author -- the same relationship `push_visibility_and_cursor`
+    /// checks locally for `friends_only`/`close_friends_only` visibility.
+    pub async fn resolve_audience(&self, story: &Story) -> Result<StoryAudience> {
+        match story.visibility {
+            StoryVisibility::Public => Ok(StoryAudience {
+                to: vec![PUBLIC_COLLECTION.to_string()],
+                cc: vec![self.followers_uri(story.user_id)],
+            }),
+            StoryVisibility::FriendsOnly | StoryVisibility::CloseFriendsOnly => {
+                let mutual_follower_ids: Vec<Uuid> = sqlx::query(
+                    "SELECT follower_id FROM follows \
+                     WHERE followee_id = $1 \
+                       AND EXISTS ( \
+                           SELECT 1 FROM follows AS back \
+                           WHERE back.follower_id = $1 AND back.followee_id = follows.follower_id \
+                       )",
+                )
+                .bind(story.user_id)
+                .fetch_all(self.db.pool())
+                .await?
+                .into_iter()
+                .map(|row| row.get("follower_id"))
+                .collect();
+
+                Ok(StoryAudience {
+                    to: mutual_follower_ids
+                        .into_iter()
+                        .map(|id| self.actor_uri(id))
+                        .collect(),
+                    cc: Vec::new(),
+                })
+            }
+            // `story_audience_lists` membership is resolved against local
+            // user ids/handles only (see `push_visibility_and_cursor`), so
+            // there's no remote actor to address here -- a list-visibility
+            // story simply isn't federated.
+            StoryVisibility::List => Ok(StoryAudience {
+                to: Vec::new(),
+                cc: Vec::new(),
+            }),
+        }
+    }
+
+    /// Build the `Create` activity for a newly published story and record
+    /// the `story_federation` mapping so a later inbound `Delete` or
+    /// reaction can be resolved back to the local story id.
+    ///
+    /// The object is an ephemeral `Note` carrying a `"Story"` type
+    /// extension (remote servers that don't understand it still have a
+    /// perfectly renderable `Note` to fall back to) and an `endTime` equal
+    /// to the story's own 24h `expires_at`, so federated viewers expire it
+    /// the same time local ones do.
+    pub async fn build_create_activity(&self, story: &Story) -> Result<serde_json::Value> {
+        let audience = self.resolve_audience(story).await?;
+        let object_uri = self.object_uri(story.id);
+        let actor_uri = self.actor_uri(story.user_id);
+        let end_time = story
+            .expires_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_default();
+
+        sqlx::query(
+            "INSERT INTO story_federation (story_id, object_uri) \
+             VALUES ($1, $2) \
+             ON CONFLICT (story_id) DO NOTHING",
+        )
+        .bind(story.id)
+        .bind(&object_uri)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(serde_json::json!({
+            "type": "Create",
+            "actor": actor_uri,
+            "to": audience.to,
+            "cc": audience.cc,
+            "object": {
+                "id": object_uri,
+                "type": "Note",
+                "ephemeral": true,
+                "category": "Story",
+                "attributedTo": actor_uri,
+                "content": story.caption,
+                "endTime": end_time,
+                "to": audience.to,
+                "cc": audience.cc,
+            },
+        }))
+    }
+
+    /// Build the `Delete` activity for a story that was removed or expired.
+    /// Reuses the recorded `story_federation` mapping rather than re-minting
+    /// the object URI, so a `Delete` still resolves correctly even if the
+    /// story row itself is already gone by the time this runs (e.g. from
+    /// the expiry sweeper).
+    pub async fn build_delete_activity(
+        &self,
+        story_id: Uuid,
+        actor_id: Uuid,
+    ) -> Result<Option<serde_json::Value>> {
+        let object_uri: Option<String> =
+            sqlx::query_scalar("SELECT object_uri FROM story_federation WHERE story_id = $1")
+                .bind(story_id)
+                .fetch_optional(self.db.pool())
+                .await?;
+
+        let Some(object_uri) = object_uri else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::json!({
+            "type": "Delete",
+            "actor": self.actor_uri(actor_id),
+            "object": object_uri,
+        })))
+    }
+
+    /// Build the `Add` activity announcing that `story_id` was pinned into
+    /// `highlight` -- the ActivityPub verb for adding an object to a named
+    /// collection, per the same vocabulary `Follow`/`Accept` use for
+    /// membership in the followers collection. The story's object URI is
+    /// deterministic (see `object_uri`), so this doesn't need a
+    /// `story_federation` lookup the way `build_delete_activity` does.
+    pub fn build_add_to_highlight_activity(
+        &self,
+        highlight: &StoryHighlight,
+        story_id: Uuid,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "type": "Add",
+            "actor": self.actor_uri(highlight.user_id),
+            "object": self.object_uri(story_id),
+            "target": self.highlight_uri(highlight.user_id, highlight.id),
+        })
+    }
+
+    /// Build the `Like` a local actor owes the author of `post_id` when
+    /// they like it, addressed directly to the author the same way
+    /// `Follow` addresses its target -- a reaction to one post isn't
+    /// broadcast content the way a story `Create` is, so it carries no
+    /// `cc`.
+    pub fn build_like_activity(&self, actor_id: Uuid, post_id: Uuid, post_owner_id: Uuid) -> serde_json::Value {
+        serde_json::json!({
+            "@context": ACTIVITYSTREAMS_CONTEXT,
+            "type": "Like",
+            "actor": self.actor_uri(actor_id),
+            "object": self.post_uri(post_id),
+            "to": [self.actor_uri(post_owner_id)],
+        })
+    }
+
+    /// Build the `Undo{Like}` for an unlike, wrapping a freshly-built `Like`
+    /// back as `object` per the ActivityPub spec -- mirrors
+    /// `build_accept_activity` wrapping its `Follow`, just for the other
+    /// verb pair.
+    pub fn build_undo_like_activity(&self, actor_id: Uuid, post_id: Uuid, post_owner_id: Uuid) -> serde_json::Value {
+        serde_json::json!({
+            "@context": ACTIVITYSTREAMS_CONTEXT,
+            "type": "Undo",
+            "actor": self.actor_uri(actor_id),
+            "object": self.build_like_activity(actor_id, post_id, post_owner_id),
+        })
+    }
+
+    /// Build the `Create{Note}` for a comment, addressed to the post's
+    /// author with `inReplyTo` set to the post's own URI -- the same shape
+    /// `ingest_activity`'s `"Create" if object is a Note"` arm expects when
+    /// resolving an inbound reply back to `remote_comments`.
+    pub fn build_comment_activity(
+        &self,
+        actor_id: Uuid,
+        post_id: Uuid,
+        post_owner_id: Uuid,
+        comment: &crate::domain::engagement::Comment,
+    ) -> serde_json::Value {
+        let actor_uri = self.actor_uri(actor_id);
+        let object_uri = format!("{}/posts/{}/comments/{}", self.base_url, post_id, comment.id);
+        serde_json::json!({
+            "@context": ACTIVITYSTREAMS_CONTEXT,
+            "type": "Create",
+            "actor": actor_uri,
+            "to": [self.actor_uri(post_owner_id)],
+            "object": {
+                "id": object_uri,
+                "type": "Note",
+                "attributedTo": actor_uri,
+                "inReplyTo": self.post_uri(post_id),
+                "content": comment.body,
+                "to": [self.actor_uri(post_owner_id)],
+            },
+        })
+    }
+
+    /// Resolve an inbound `object_uri` (from a `Delete` or reaction
+    /// activity) back to a local story id.
+    pub async fn resolve_story_id(&self, object_uri: &str) -> Result<Option<Uuid>> {
+        Ok(
+            sqlx::query_scalar("SELECT story_id FROM story_federation WHERE object_uri = $1")
+                .bind(object_uri)
+                .fetch_optional(self.db.pool())
+                .await?,
+        )
+    }
+}
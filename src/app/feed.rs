@@ -6,6 +6,8 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 use tracing::warn;
 
+use crate::app::media::list_attachments_for_posts;
+use crate::app::posts::list_mentions_for_posts;
 use crate::domain::post::Post;
 use crate::domain::post::PostVisibility;
 use crate::infra::{cache::RedisCache, db::Db};
@@ -18,6 +20,27 @@ pub struct FeedService {
 
 const FEED_CACHE_TTL_SECONDS: u64 = 30;
 
+// Bounded window kept per timeline: enough to serve several pages of a cold
+// "load more" session without falling back to the SQL path.
+const FEED_TIMELINE_MAX_LEN: isize = 800;
+
+// Accounts with at least this many followers stay on the pull (fan-out-on-
+// read) path -- pushing their posts to every follower's timeline on write
+// would amplify one write into tens of thousands.
+const CELEBRITY_FOLLOWER_THRESHOLD: i64 = 10_000;
+
+fn timeline_key(user_id: Uuid) -> String {
+    format!("feed:timeline:{}", user_id)
+}
+
+// Redis sorted-set scores are f64; a Unix-seconds timestamp fits losslessly,
+// so (created_at, id) ties within the same second fall back to Redis's
+// lexical ordering of the id member. That's an acceptable approximation for
+// a precomputed timeline -- the SQL fallback path still orders exactly.
+fn timeline_score(created_at: OffsetDateTime) -> f64 {
+    created_at.unix_timestamp() as f64
+}
+
 impl FeedService {
     pub fn new(db: Db, cache: RedisCache) -> Self {
         Self { db, cache }
@@ -56,22 +79,48 @@ impl FeedService {
             }
         }
 
+        // Fan-out on write: hydrate from the user's precomputed timeline when
+        // it exists. Falls through to the SQL join below on a cold start or
+        // cache eviction, which also rebuilds the timeline for next time.
+        if let Some((posts, next_cursor)) =
+            self.get_home_feed_from_timeline(user_id, cursor, limit).await?
+        {
+            if let Ok(mut conn) = self.cache.client().get_multiplexed_async_connection().await {
+                if let Ok(payload) = serde_json::to_string(&posts) {
+                    if let Err(err) = conn.set_ex::<_, _, ()>(&cache_key, payload, ttl).await {
+                        warn!(error = ?err, "failed to write feed cache");
+                    }
+                }
+            }
+            return Ok((posts, next_cursor));
+        }
+
         let limit_plus = limit + 1;
         let rows = match cursor {
             Some((created_at, post_id)) => {
                 sqlx::query(
                     "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                            p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                      FROM posts p \
-                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
-                     WHERE (p.owner_id = $1 \
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                     WHERE p.removed_at IS NULL \
+                       AND (p.owner_id = $1 \
                         OR (p.owner_id IN ( \
                             SELECT followee_id FROM follows WHERE follower_id = $1 \
                         ) AND NOT EXISTS ( \
                             SELECT 1 FROM blocks \
                             WHERE (blocker_id = p.owner_id AND blocked_id = $1) \
                                OR (blocker_id = $1 AND blocked_id = p.owner_id) \
+                        ) AND NOT EXISTS ( \
+                            SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = p.owner_id \
                         ))) \
+                       AND (p.owner_id = $1 \
+                            OR p.visibility = 'public' \
+                            OR p.visibility = 'followers' \
+                            OR (p.visibility = 'mentioned' AND EXISTS ( \
+                                SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $1 \
+                            ))) \
                        AND (p.created_at < $2 OR (p.created_at = $2 AND p.id < $3)) \
                      ORDER BY p.created_at DESC, p.id DESC \
                      LIMIT $4",
@@ -86,17 +135,27 @@ impl FeedService {
             None => {
                 sqlx::query(
                     "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                            p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                      FROM posts p \
-                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
-                     WHERE p.owner_id = $1 \
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                     WHERE p.removed_at IS NULL \
+                       AND (p.owner_id = $1 \
                         OR (p.owner_id IN ( \
                             SELECT followee_id FROM follows WHERE follower_id = $1 \
                         ) AND NOT EXISTS ( \
                             SELECT 1 FROM blocks \
                             WHERE (blocker_id = p.owner_id AND blocked_id = $1) \
                                OR (blocker_id = $1 AND blocked_id = p.owner_id) \
-                        )) \
+                        ) AND NOT EXISTS ( \
+                            SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = p.owner_id \
+                        ))) \
+                       AND (p.owner_id = $1 \
+                            OR p.visibility = 'public' \
+                            OR p.visibility = 'followers' \
+                            OR (p.visibility = 'mentioned' AND EXISTS ( \
+                                SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $1 \
+                            ))) \
                      ORDER BY p.created_at DESC, p.id DESC \
                      LIMIT $2",
                 )
@@ -107,22 +166,33 @@ impl FeedService {
             }
         };
 
+        let post_ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+        let mut attachments_by_post = list_attachments_for_posts(self.db.pool(), &post_ids).await?;
+        let mut mentions_by_post = list_mentions_for_posts(self.db.pool(), &post_ids).await?;
+
         let mut posts = Vec::with_capacity(rows.len());
         for row in rows {
+            let id: Uuid = row.get("id");
             let visibility: String = row.get("visibility");
             let visibility = PostVisibility::from_db(&visibility).ok_or_else(|| {
                 anyhow::anyhow!("unknown post visibility: {}", visibility)
             })?;
 
             posts.push(Post {
-                id: row.get("id"),
+                id,
                 owner_id: row.get("owner_id"),
                 owner_handle: Some(row.get("owner_handle")),
                 owner_display_name: Some(row.get("owner_display_name")),
-                media_id: row.get("media_id"),
+                attachments: attachments_by_post.remove(&id).unwrap_or_default(),
+                mentions: mentions_by_post.remove(&id).unwrap_or_default(),
                 caption: row.get("caption"),
                 visibility,
+                in_reply_to_id: row.get("in_reply_to_id"),
+                repost_of_id: row.get("repost_of_id"),
+                reposted_post: None,
                 created_at: row.get("created_at"),
+                owner_avatar_key: None,
+                owner_avatar_url: None,
             });
         }
 
@@ -140,6 +210,16 @@ impl FeedService {
             }
         }
 
+        // Only worth rebuilding the timeline from the very first page -- a
+        // deep cursor means the caller was already paginating before the
+        // cache miss, and a partial rebuild from that point on would leave
+        // the timeline missing its newest entries.
+        if cursor.is_none() {
+            if let Err(err) = self.rebuild_timeline(user_id).await {
+                warn!(error = ?err, user_id = %user_id, "failed to rebuild feed timeline");
+            }
+        }
+
         Ok((posts, next_cursor))
     }
 
@@ -150,5 +230,284 @@ impl FeedService {
         }
         Ok(())
     }
+
+    /// Push a newly created post onto the precomputed timeline of every
+    /// follower (and the author), skipping blocked relationships. Called
+    /// from the post-creation handler so the fan-out happens inline with
+    /// the write rather than on a background queue. No-ops for celebrity
+    /// accounts, whose posts are merged into followers' feeds at read time
+    /// instead (see `get_home_feed`).
+    pub async fn fan_out_post(&self, post: &Post) -> Result<()> {
+        let follower_count: i64 =
+            sqlx::query_scalar("SELECT count(*) FROM follows WHERE followee_id = $1")
+                .bind(post.owner_id)
+                .fetch_one(self.db.pool())
+                .await?;
+        if follower_count >= CELEBRITY_FOLLOWER_THRESHOLD {
+            return Ok(());
+        }
+
+        // Private and Mentioned posts never go out to followers' timelines,
+        // only the author's own. A Mentioned post is not pushed onto the
+        // mentioned users' timelines either; they only see it through the
+        // SQL read path's `post_mentions` check.
+        let follower_ids: Vec<Uuid> = match post.visibility {
+            PostVisibility::Private | PostVisibility::Mentioned => Vec::new(),
+            PostVisibility::Public | PostVisibility::Followers => sqlx::query_scalar(
+                "SELECT f.follower_id FROM follows f \
+                 WHERE f.followee_id = $1 \
+                   AND NOT EXISTS ( \
+                       SELECT 1 FROM blocks \
+                       WHERE (blocker_id = f.followee_id AND blocked_id = f.follower_id) \
+                          OR (blocker_id = f.follower_id AND blocked_id = f.followee_id) \
+                   ) \
+                   AND NOT EXISTS ( \
+                       SELECT 1 FROM mutes WHERE muter_id = f.follower_id AND muted_id = f.followee_id \
+                   )",
+            )
+            .bind(post.owner_id)
+            .fetch_all(self.db.pool())
+            .await?,
+        };
+
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for feed fan-out");
+                return Ok(());
+            }
+        };
+
+        let score = timeline_score(post.created_at);
+        let member = post.id.to_string();
+        for recipient_id in follower_ids.into_iter().chain(std::iter::once(post.owner_id)) {
+            let key = timeline_key(recipient_id);
+            if let Err(err) = conn.zadd::<_, _, _, ()>(&key, &member, score).await {
+                warn!(error = ?err, recipient_id = %recipient_id, "failed to push post onto timeline");
+                continue;
+            }
+            if let Err(err) = conn
+                .zremrangebyrank::<_, ()>(&key, 0, -(FEED_TIMELINE_MAX_LEN + 1))
+                .await
+            {
+                warn!(error = ?err, recipient_id = %recipient_id, "failed to trim timeline");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read a page of the precomputed timeline, hydrate it into `Post`
+    /// rows, and merge in recent posts from followed celebrity accounts
+    /// (which never get pushed). Returns `None` when the timeline key is
+    /// missing entirely (cold start / cache eviction) so the caller can
+    /// fall back to the SQL join and rebuild it.
+    async fn get_home_feed_from_timeline(
+        &self,
+        user_id: Uuid,
+        cursor: Option<(OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Option<(Vec<Post>, Option<(OffsetDateTime, Uuid)>)>> {
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for feed timeline read");
+                return Ok(None);
+            }
+        };
+
+        let key = timeline_key(user_id);
+        let entries: Vec<(String, f64)> = match conn
+            .zrevrangebyscore_withscores(&key, "+inf", "-inf")
+            .await
+        {
+            Ok(entries) => entries,
+            Err(err) => {
+                warn!(error = ?err, "failed to read feed timeline");
+                return Ok(None);
+            }
+        };
+
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let mut timeline_ids = Vec::with_capacity(entries.len());
+        for (member, _score) in &entries {
+            if let Ok(id) = Uuid::parse_str(member) {
+                timeline_ids.push(id);
+            }
+        }
+
+        let celebrity_owner_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT f.followee_id FROM follows f \
+             JOIN ( \
+                 SELECT followee_id, count(*) AS follower_count \
+                 FROM follows GROUP BY followee_id \
+             ) counts ON counts.followee_id = f.followee_id \
+             WHERE f.follower_id = $1 AND counts.follower_count >= $2",
+        )
+        .bind(user_id)
+        .bind(CELEBRITY_FOLLOWER_THRESHOLD)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut rows = sqlx::query(
+            "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                    p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
+             FROM posts p \
+             JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+             WHERE p.id = ANY($1) AND p.removed_at IS NULL",
+        )
+        .bind(&timeline_ids)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        if !celebrity_owner_ids.is_empty() {
+            let celebrity_rows = sqlx::query(
+                "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                        p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
+                 FROM posts p \
+                 JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                 WHERE p.owner_id = ANY($1) \
+                   AND p.removed_at IS NULL \
+                   AND (p.owner_id = $3 \
+                        OR p.visibility = 'public' \
+                        OR p.visibility = 'followers' \
+                        OR (p.visibility = 'mentioned' AND EXISTS ( \
+                            SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $3 \
+                        ))) \
+                 ORDER BY p.created_at DESC, p.id DESC \
+                 LIMIT $2",
+            )
+            .bind(&celebrity_owner_ids)
+            .bind(limit + 1)
+            .bind(user_id)
+            .fetch_all(self.db.pool())
+            .await?;
+            rows.extend(celebrity_rows);
+        }
+
+        let dedup_post_ids: Vec<Uuid> = {
+            let mut seen = std::collections::HashSet::with_capacity(rows.len());
+            rows.iter()
+                .map(|row| row.get::<Uuid, _>("id"))
+                .filter(|id| seen.insert(*id))
+                .collect()
+        };
+        let mut attachments_by_post =
+            list_attachments_for_posts(self.db.pool(), &dedup_post_ids).await?;
+        let mut mentions_by_post = list_mentions_for_posts(self.db.pool(), &dedup_post_ids).await?;
+
+        let mut posts = Vec::with_capacity(rows.len());
+        let mut seen = std::collections::HashSet::with_capacity(rows.len());
+        for row in rows {
+            let id: Uuid = row.get("id");
+            if !seen.insert(id) {
+                continue;
+            }
+
+            let visibility: String = row.get("visibility");
+            let visibility = PostVisibility::from_db(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+
+            posts.push(Post {
+                id,
+                owner_id: row.get("owner_id"),
+                owner_handle: Some(row.get("owner_handle")),
+                owner_display_name: Some(row.get("owner_display_name")),
+                attachments: attachments_by_post.remove(&id).unwrap_or_default(),
+                mentions: mentions_by_post.remove(&id).unwrap_or_default(),
+                caption: row.get("caption"),
+                visibility,
+                in_reply_to_id: row.get("in_reply_to_id"),
+                repost_of_id: row.get("repost_of_id"),
+                reposted_post: None,
+                created_at: row.get("created_at"),
+                owner_avatar_key: None,
+                owner_avatar_url: None,
+            });
+        }
+
+        posts.sort_by(|a, b| (b.created_at, b.id).cmp(&(a.created_at, a.id)));
+
+        if let Some((cursor_created_at, cursor_id)) = cursor {
+            posts.retain(|post| (post.created_at, post.id) < (cursor_created_at, cursor_id));
+        }
+
+        posts.truncate(limit as usize + 1);
+
+        let next_cursor = if posts.len() > limit as usize {
+            posts.pop().map(|extra| (extra.created_at, extra.id))
+        } else {
+            None
+        };
+
+        Ok(Some((posts, next_cursor)))
+    }
+
+    /// Rebuild a user's timeline from the SQL fan-out-on-read query after a
+    /// cold start (missing key) or cache eviction, so subsequent requests
+    /// can hit the fast path again.
+    async fn rebuild_timeline(&self, user_id: Uuid) -> Result<()> {
+        let rows = sqlx::query(
+            "SELECT p.id, p.created_at \
+             FROM posts p \
+             JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+             WHERE p.removed_at IS NULL \
+               AND (p.owner_id = $1 \
+                OR (p.owner_id IN ( \
+                    SELECT followee_id FROM follows WHERE follower_id = $1 \
+                ) AND NOT EXISTS ( \
+                    SELECT 1 FROM blocks \
+                    WHERE (blocker_id = p.owner_id AND blocked_id = $1) \
+                       OR (blocker_id = $1 AND blocked_id = p.owner_id) \
+                ) AND NOT EXISTS ( \
+                    SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = p.owner_id \
+                ))) \
+               AND (p.owner_id = $1 \
+                    OR p.visibility = 'public' \
+                    OR p.visibility = 'followers' \
+                    OR (p.visibility = 'mentioned' AND EXISTS ( \
+                        SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $1 \
+                    ))) \
+             ORDER BY p.created_at DESC, p.id DESC \
+             LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(FEED_TIMELINE_MAX_LEN as i64)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for timeline rebuild");
+                return Ok(());
+            }
+        };
+
+        let key = timeline_key(user_id);
+        let members: Vec<(f64, String)> = rows
+            .into_iter()
+            .map(|row| {
+                let id: Uuid = row.get("id");
+                let created_at: OffsetDateTime = row.get("created_at");
+                (timeline_score(created_at), id.to_string())
+            })
+            .collect();
+
+        if let Err(err) = conn.zadd_multiple::<_, _, _, ()>(&key, &members).await {
+            warn!(error = ?err, user_id = %user_id, "failed to rebuild feed timeline");
+        }
+
+        Ok(())
+    }
 }
 
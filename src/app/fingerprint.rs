@@ -1,9 +1,18 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use rand::{thread_rng, Rng};
 use sha2::{Digest, Sha256};
 use sqlx::Row;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::infra::db::Db;
+use crate::infra::db::{is_transaction_conflict, Db};
+
+/// How many times to retry the read-modify-write below before giving up and
+/// surfacing the conflict to the caller.
+const MAX_CONFLICT_RETRIES: u32 = 5;
+
+/// Default page size for the thin-wrapper, first-page-only accessors.
+const DEFAULT_PAGE_SIZE: i64 = 100;
 
 #[derive(Clone)]
 pub struct FingerprintService {
@@ -18,6 +27,15 @@ pub struct DeviceInfo {
     pub account_count: i32,
     pub risk_score: i32,
     pub is_blocked: bool,
+    pub last_seen_at: OffsetDateTime,
+}
+
+/// Keyset-paginated device listing: `cursor` is the `(last_seen_at,
+/// fingerprint_hash)` of the last row of the previous page, `None` for the
+/// first page.
+pub struct DeviceQuery {
+    pub cursor: Option<(OffsetDateTime, String)>,
+    pub limit: i64,
 }
 
 impl FingerprintService {
@@ -41,7 +59,7 @@ impl FingerprintService {
     ) -> Result<DeviceInfo> {
         // Check if fingerprint exists
         let existing = sqlx::query(
-            "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked \
+            "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked, version, last_seen_at \
              FROM device_fingerprints \
              WHERE fingerprint_hash = $1",
         )
@@ -50,10 +68,11 @@ impl FingerprintService {
         .await?;
 
         if let Some(row) = existing {
-            let mut user_ids: Vec<Uuid> = row.get("user_ids");
+            let user_ids: Vec<Uuid> = row.get("user_ids");
             let account_count: i32 = row.get("account_count");
-            let mut risk_score: i32 = row.get("risk_score");
+            let risk_score: i32 = row.get("risk_score");
             let is_blocked: bool = row.get("is_blocked");
+            let last_seen_at: OffsetDateTime = row.get("last_seen_at");
 
             // Check if already blocked
             if is_blocked {
@@ -63,6 +82,7 @@ impl FingerprintService {
                     account_count,
                     risk_score,
                     is_blocked: true,
+                    last_seen_at,
                 });
             }
 
@@ -70,59 +90,21 @@ impl FingerprintService {
             if let Some(user_id) = user_id {
                 // Add user if not already associated
                 if !user_ids.contains(&user_id) {
-                    user_ids.push(user_id);
-
-                    // Increase risk score based on number of accounts
-                    let risk_increase = match account_count {
-                        0..=2 => 5,
-                        3..=5 => 15,
-                        6..=10 => 30,
-                        _ => 50,
-                    };
-
-                    risk_score = (risk_score + risk_increase).min(100);
-                    let new_account_count = user_ids.len() as i32;
-
-                    sqlx::query(
-                        "UPDATE device_fingerprints \
-                         SET user_ids = $1, \
-                             account_count = $2, \
-                             risk_score = $3, \
-                             last_seen_at = NOW(), \
-                             updated_at = NOW() \
-                         WHERE fingerprint_hash = $4",
-                    )
-                    .bind(&user_ids)
-                    .bind(new_account_count)
-                    .bind(risk_score)
-                    .bind(&fingerprint_hash)
-                    .execute(self.db.pool())
-                    .await?;
-
-                    tracing::info!(
-                        fingerprint_hash = &fingerprint_hash[..8],
-                        account_count = new_account_count,
-                        risk_score = risk_score,
-                        "Device fingerprint updated"
-                    );
-
-                    return Ok(DeviceInfo {
-                        fingerprint_hash,
-                        user_ids,
-                        account_count: new_account_count,
-                        risk_score,
-                        is_blocked: false,
-                    });
+                    return self
+                        .register_new_association(fingerprint_hash, user_id)
+                        .await;
                 } else {
                     // Just update last seen
-                    sqlx::query(
+                    let now = sqlx::query(
                         "UPDATE device_fingerprints \
                          SET last_seen_at = NOW() \
-                         WHERE fingerprint_hash = $1",
+                         WHERE fingerprint_hash = $1 \
+                         RETURNING last_seen_at",
                     )
                     .bind(&fingerprint_hash)
-                    .execute(self.db.pool())
-                    .await?;
+                    .fetch_one(self.db.pool())
+                    .await?
+                    .get("last_seen_at");
 
                     return Ok(DeviceInfo {
                         fingerprint_hash,
@@ -130,18 +112,21 @@ impl FingerprintService {
                         account_count,
                         risk_score,
                         is_blocked: false,
+                        last_seen_at: now,
                     });
                 }
             } else {
                 // No user_id provided, just update last seen for existing device
-                sqlx::query(
+                let now = sqlx::query(
                     "UPDATE device_fingerprints \
                      SET last_seen_at = NOW() \
-                     WHERE fingerprint_hash = $1",
+                     WHERE fingerprint_hash = $1 \
+                     RETURNING last_seen_at",
                 )
                 .bind(&fingerprint_hash)
-                .execute(self.db.pool())
-                .await?;
+                .fetch_one(self.db.pool())
+                .await?
+                .get("last_seen_at");
 
                 return Ok(DeviceInfo {
                     fingerprint_hash,
@@ -149,22 +134,25 @@ impl FingerprintService {
                     account_count,
                     risk_score,
                     is_blocked: false,
+                    last_seen_at: now,
                 });
             }
         } else {
             // New fingerprint
             if let Some(user_id) = user_id {
                 // Authenticated registration - associate with user
-                sqlx::query(
+                let last_seen_at = sqlx::query(
                     "INSERT INTO device_fingerprints \
                      (fingerprint_hash, user_ids, account_count, risk_score, user_agent) \
-                     VALUES ($1, $2, 1, 0, $3)",
+                     VALUES ($1, $2, 1, 0, $3) \
+                     RETURNING last_seen_at",
                 )
                 .bind(&fingerprint_hash)
                 .bind(&vec![user_id])
                 .bind(user_agent)
-                .execute(self.db.pool())
-                .await?;
+                .fetch_one(self.db.pool())
+                .await?
+                .get("last_seen_at");
 
                 tracing::info!(
                     fingerprint_hash = &fingerprint_hash[..8],
@@ -177,19 +165,22 @@ impl FingerprintService {
                     account_count: 1,
                     risk_score: 0,
                     is_blocked: false,
+                    last_seen_at,
                 })
             } else {
                 // Unauthenticated registration - no user association
-                sqlx::query(
+                let last_seen_at = sqlx::query(
                     "INSERT INTO device_fingerprints \
                      (fingerprint_hash, user_ids, account_count, risk_score, user_agent) \
-                     VALUES ($1, $2, 0, 0, $3)",
+                     VALUES ($1, $2, 0, 0, $3) \
+                     RETURNING last_seen_at",
                 )
                 .bind(&fingerprint_hash)
                 .bind(&vec![] as &Vec<Uuid>)
                 .bind(user_agent)
-                .execute(self.db.pool())
-                .await?;
+                .fetch_one(self.db.pool())
+                .await?
+                .get("last_seen_at");
 
                 tracing::info!(
                     fingerprint_hash = &fingerprint_hash[..8],
@@ -202,11 +193,128 @@ impl FingerprintService {
                     account_count: 0,
                     risk_score: 0,
                     is_blocked: false,
+                    last_seen_at,
                 })
             }
         }
     }
 
+    /// Associate `user_id` with an already-registered fingerprint, bumping
+    /// `account_count` and `risk_score`. Uses optimistic concurrency
+    /// (`version`) instead of a blind read-modify-write so two concurrent
+    /// signups from the same device can't clobber each other's `user_ids`.
+    async fn register_new_association(
+        &self,
+        fingerprint_hash: String,
+        user_id: Uuid,
+    ) -> Result<DeviceInfo> {
+        for attempt in 0..MAX_CONFLICT_RETRIES {
+            let row = sqlx::query(
+                "SELECT user_ids, account_count, risk_score, is_blocked, version, last_seen_at \
+                 FROM device_fingerprints \
+                 WHERE fingerprint_hash = $1",
+            )
+            .bind(&fingerprint_hash)
+            .fetch_optional(self.db.pool())
+            .await?
+            .ok_or_else(|| anyhow!("device fingerprint disappeared mid-registration"))?;
+
+            let mut user_ids: Vec<Uuid> = row.get("user_ids");
+            let account_count: i32 = row.get("account_count");
+            let risk_score: i32 = row.get("risk_score");
+            let is_blocked: bool = row.get("is_blocked");
+            let version: i32 = row.get("version");
+            let last_seen_at: OffsetDateTime = row.get("last_seen_at");
+
+            if is_blocked {
+                return Ok(DeviceInfo {
+                    fingerprint_hash,
+                    user_ids,
+                    account_count,
+                    risk_score,
+                    is_blocked: true,
+                    last_seen_at,
+                });
+            }
+
+            if user_ids.contains(&user_id) {
+                return Ok(DeviceInfo {
+                    fingerprint_hash,
+                    user_ids,
+                    account_count,
+                    risk_score,
+                    is_blocked: false,
+                    last_seen_at,
+                });
+            }
+
+            user_ids.push(user_id);
+            let risk_increase = match account_count {
+                0..=2 => 5,
+                3..=5 => 15,
+                6..=10 => 30,
+                _ => 50,
+            };
+            let new_risk_score = (risk_score + risk_increase).min(100);
+            let new_account_count = user_ids.len() as i32;
+
+            let result = sqlx::query(
+                "UPDATE device_fingerprints \
+                 SET user_ids = $1, \
+                     account_count = $2, \
+                     risk_score = $3, \
+                     version = version + 1, \
+                     last_seen_at = NOW(), \
+                     updated_at = NOW() \
+                 WHERE fingerprint_hash = $4 AND version = $5 \
+                 RETURNING last_seen_at",
+            )
+            .bind(&user_ids)
+            .bind(new_account_count)
+            .bind(new_risk_score)
+            .bind(&fingerprint_hash)
+            .bind(version)
+            .fetch_optional(self.db.pool())
+            .await;
+
+            match result {
+                Ok(Some(row)) => {
+                    tracing::info!(
+                        fingerprint_hash = &fingerprint_hash[..8],
+                        account_count = new_account_count,
+                        risk_score = new_risk_score,
+                        "Device fingerprint updated"
+                    );
+
+                    return Ok(DeviceInfo {
+                        fingerprint_hash,
+                        user_ids,
+                        account_count: new_account_count,
+                        risk_score: new_risk_score,
+                        is_blocked: false,
+                        last_seen_at: row.get("last_seen_at"),
+                    });
+                }
+                Ok(None) => {
+                    // `version` moved under us -- another writer won the race.
+                    // Re-read current state and recompute before retrying.
+                    backoff(attempt).await;
+                    continue;
+                }
+                Err(err) if is_transaction_conflict(&err) => {
+                    backoff(attempt).await;
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Err(anyhow!(
+            "failed to register device fingerprint after {} conflicting retries",
+            MAX_CONFLICT_RETRIES
+        ))
+    }
+
     /// Check if device is suspicious or blocked
     pub async fn check_device_risk(&self, fingerprint_hash: &str) -> Result<(i32, bool)> {
         let row = sqlx::query(
@@ -271,19 +379,101 @@ impl FingerprintService {
         Ok(())
     }
 
-    /// Get all devices for a user
-    pub async fn get_user_devices(&self, user_id: Uuid) -> Result<Vec<DeviceInfo>> {
-        let rows = sqlx::query(
-            "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked \
-             FROM device_fingerprints \
-             WHERE $1 = ANY(user_ids) \
-             ORDER BY last_seen_at DESC",
+    /// Lower the risk score of every device associated with `user_id` by
+    /// `amount`, floored at zero. Called when the user verifies a strong
+    /// identity signal (a linked wallet or Farcaster FID) that makes them
+    /// less likely to be part of a bulk-signup ring.
+    pub async fn lower_risk_for_user(&self, user_id: Uuid, amount: i32) -> Result<()> {
+        sqlx::query(
+            "UPDATE device_fingerprints \
+             SET risk_score = GREATEST(risk_score - $1, 0), \
+                 updated_at = NOW() \
+             WHERE $2 = ANY(user_ids)",
         )
+        .bind(amount)
         .bind(user_id)
-        .fetch_all(self.db.pool())
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a single device without loading the rest of its user's devices.
+    pub async fn get_device(&self, fingerprint_hash: &str) -> Result<Option<DeviceInfo>> {
+        let row = sqlx::query(
+            "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked, last_seen_at \
+             FROM device_fingerprints \
+             WHERE fingerprint_hash = $1",
+        )
+        .bind(fingerprint_hash)
+        .fetch_optional(self.db.pool())
         .await?;
 
-        let devices = rows
+        Ok(row.map(|row| DeviceInfo {
+            fingerprint_hash: row.get("fingerprint_hash"),
+            user_ids: row.get("user_ids"),
+            account_count: row.get("account_count"),
+            risk_score: row.get("risk_score"),
+            is_blocked: row.get("is_blocked"),
+            last_seen_at: row.get("last_seen_at"),
+        }))
+    }
+
+    /// Get all devices for a user, first page only. Prefer
+    /// `get_user_devices_page` for admin sweeps or heavy-device users.
+    pub async fn get_user_devices(&self, user_id: Uuid) -> Result<Vec<DeviceInfo>> {
+        self.get_user_devices_page(
+            user_id,
+            DeviceQuery {
+                cursor: None,
+                limit: DEFAULT_PAGE_SIZE,
+            },
+        )
+        .await
+    }
+
+    /// Keyset-paginated device listing for a user, ordered by recency. The
+    /// caller should request `limit + 1` rows and treat a result longer than
+    /// `limit` as "there's another page" (see `list_pending_flags` for the
+    /// same convention at the HTTP layer).
+    pub async fn get_user_devices_page(
+        &self,
+        user_id: Uuid,
+        query: DeviceQuery,
+    ) -> Result<Vec<DeviceInfo>> {
+        let rows = match query.cursor {
+            Some((last_seen_at, fingerprint_hash)) => {
+                sqlx::query(
+                    "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked, last_seen_at \
+                     FROM device_fingerprints \
+                     WHERE $1 = ANY(user_ids) \
+                       AND (last_seen_at, fingerprint_hash) < ($2, $3) \
+                     ORDER BY last_seen_at DESC, fingerprint_hash DESC \
+                     LIMIT $4",
+                )
+                .bind(user_id)
+                .bind(last_seen_at)
+                .bind(fingerprint_hash)
+                .bind(query.limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked, last_seen_at \
+                     FROM device_fingerprints \
+                     WHERE $1 = ANY(user_ids) \
+                     ORDER BY last_seen_at DESC, fingerprint_hash DESC \
+                     LIMIT $2",
+                )
+                .bind(user_id)
+                .bind(query.limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        Ok(rows
             .into_iter()
             .map(|row| DeviceInfo {
                 fingerprint_hash: row.get("fingerprint_hash"),
@@ -291,27 +481,65 @@ impl FingerprintService {
                 account_count: row.get("account_count"),
                 risk_score: row.get("risk_score"),
                 is_blocked: row.get("is_blocked"),
+                last_seen_at: row.get("last_seen_at"),
             })
-            .collect();
-
-        Ok(devices)
+            .collect())
     }
 
-    /// Get high-risk devices (for admin review)
+    /// Get high-risk devices (for admin review), first page only.
     #[allow(dead_code)]
     pub async fn get_high_risk_devices(&self, min_risk_score: i32) -> Result<Vec<DeviceInfo>> {
-        let rows = sqlx::query(
-            "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked \
-             FROM device_fingerprints \
-             WHERE risk_score >= $1 AND is_blocked = FALSE \
-             ORDER BY risk_score DESC \
-             LIMIT 100",
+        self.get_high_risk_devices_page(
+            min_risk_score,
+            DeviceQuery {
+                cursor: None,
+                limit: DEFAULT_PAGE_SIZE,
+            },
         )
-        .bind(min_risk_score)
-        .fetch_all(self.db.pool())
-        .await?;
+        .await
+    }
 
-        let devices = rows
+    /// Keyset-paginated high-risk device sweep, for admin tooling that needs
+    /// to walk every flagged device without holding the whole set in memory.
+    #[allow(dead_code)]
+    pub async fn get_high_risk_devices_page(
+        &self,
+        min_risk_score: i32,
+        query: DeviceQuery,
+    ) -> Result<Vec<DeviceInfo>> {
+        let rows = match query.cursor {
+            Some((last_seen_at, fingerprint_hash)) => {
+                sqlx::query(
+                    "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked, last_seen_at \
+                     FROM device_fingerprints \
+                     WHERE risk_score >= $1 AND is_blocked = FALSE \
+                       AND (last_seen_at, fingerprint_hash) < ($2, $3) \
+                     ORDER BY last_seen_at DESC, fingerprint_hash DESC \
+                     LIMIT $4",
+                )
+                .bind(min_risk_score)
+                .bind(last_seen_at)
+                .bind(fingerprint_hash)
+                .bind(query.limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT fingerprint_hash, user_ids, account_count, risk_score, is_blocked, last_seen_at \
+                     FROM device_fingerprints \
+                     WHERE risk_score >= $1 AND is_blocked = FALSE \
+                     ORDER BY last_seen_at DESC, fingerprint_hash DESC \
+                     LIMIT $2",
+                )
+                .bind(min_risk_score)
+                .bind(query.limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        Ok(rows
             .into_iter()
             .map(|row| DeviceInfo {
                 fingerprint_hash: row.get("fingerprint_hash"),
@@ -319,9 +547,17 @@ impl FingerprintService {
                 account_count: row.get("account_count"),
                 risk_score: row.get("risk_score"),
                 is_blocked: row.get("is_blocked"),
+                last_seen_at: row.get("last_seen_at"),
             })
-            .collect();
-
-        Ok(devices)
+            .collect())
     }
 }
+
+/// Jittered exponential backoff between conflict retries: `2^attempt * 10ms`
+/// plus up to 10ms of jitter, so a burst of racing writers don't all retry
+/// in lockstep.
+async fn backoff(attempt: u32) {
+    let base_ms = 10u64.saturating_mul(1 << attempt.min(6));
+    let jitter_ms: u64 = thread_rng().gen_range(0..10);
+    tokio::time::sleep(std::time::Duration::from_millis(base_ms + jitter_ms)).await;
+}
@@ -8,15 +8,33 @@ use uuid::Uuid;
 
 use crate::infra::db::Db;
 
+/// Max invites a user may have outstanding, by `user_trust_scores.trust_level`.
+/// Shared by `create_invite`, `issue_signed_invite`, and `get_invite_stats` so
+/// the quota can't drift between the random-code and signed-token paths.
+fn max_invites_for_trust_level(trust_level: i32) -> i32 {
+    match trust_level {
+        0 => 3,   // New users: 3 invites
+        1 => 10,  // Basic: 10 invites
+        2 => 50,  // Trusted: 50 invites
+        3 => 200, // Verified: 200 invites
+        _ => 3,
+    }
+}
+
 #[derive(Clone)]
 pub struct InviteService {
     db: Db,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct InviteCode {
     pub code: String,
-    pub created_by: Uuid,
+    // NULL for admin-issued codes (see `create_admin_invite`), which have no
+    // inviting user to credit/reward on consumption.
+    pub created_by: Option<Uuid>,
+    // If set, only a signup using this exact email address may consume the
+    // code -- lets an admin send a code that's only good for one invitee.
+    pub bound_email: Option<String>,
     pub used_by: Option<Uuid>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
@@ -28,6 +46,12 @@ pub struct InviteCode {
     pub invite_type: String,
     pub use_count: i32,
     pub max_uses: i32,
+    /// `max_uses - use_count`, so a client doesn't have to do the
+    /// subtraction itself to show a remaining-uses badge.
+    pub uses_remaining: i32,
+    /// Free-text note an admin can attach when minting a code via
+    /// `create_admin_invite`, e.g. who it was issued to and why.
+    pub note: Option<String>,
 }
 
 impl InviteService {
@@ -61,13 +85,7 @@ impl InviteService {
         };
 
         // Calculate max invites based on trust level
-        let max_invites = match trust_level {
-            0 => 3,   // New users: 3 invites
-            1 => 10,  // Basic: 10 invites
-            2 => 50,  // Trusted: 50 invites
-            3 => 200, // Verified: 200 invites
-            _ => 3,
-        };
+        let max_invites = max_invites_for_trust_level(trust_level);
 
         if invites_sent >= max_invites {
             return Err(anyhow!(
@@ -109,7 +127,8 @@ impl InviteService {
 
         Ok(InviteCode {
             code,
-            created_by: user_id,
+            created_by: Some(user_id),
+            bound_email: None,
             used_by: None,
             created_at: OffsetDateTime::now_utc(),
             used_at: None,
@@ -118,9 +137,228 @@ impl InviteService {
             invite_type: "standard".to_string(),
             use_count: 0,
             max_uses: 1,
+            uses_remaining: 1,
+            note: None,
+        })
+    }
+
+    /// Generate an invite code on an admin's behalf, bypassing the per-user
+    /// trust-level quota in `create_invite`. Used to run a closed beta: an
+    /// operator can hand out codes (optionally tied to one email address,
+    /// with a usage budget above the default single-use, and a free-text
+    /// `note` recording who it was issued to and why) without touching any
+    /// invite-quota bookkeeping.
+    pub async fn create_admin_invite(
+        &self,
+        days_valid: i64,
+        bound_email: Option<String>,
+        max_uses: i32,
+        note: Option<String>,
+    ) -> Result<InviteCode> {
+        let code = self.generate_unique_code().await?;
+        let expires_at = OffsetDateTime::now_utc() + time::Duration::days(days_valid);
+
+        sqlx::query(
+            "INSERT INTO invite_codes (code, created_by, bound_email, expires_at, invite_type, max_uses, note) \
+             VALUES ($1, NULL, $2, $3, 'admin', $4, $5)",
+        )
+        .bind(&code)
+        .bind(&bound_email)
+        .bind(expires_at)
+        .bind(max_uses)
+        .bind(&note)
+        .execute(self.db.pool())
+        .await?;
+
+        tracing::info!(code = &code, bound_email = ?bound_email, max_uses, "admin invite code created");
+
+        Ok(InviteCode {
+            code,
+            created_by: None,
+            bound_email,
+            used_by: None,
+            created_at: OffsetDateTime::now_utc(),
+            used_at: None,
+            expires_at,
+            is_valid: true,
+            invite_type: "admin".to_string(),
+            use_count: 0,
+            max_uses,
+            uses_remaining: max_uses,
+            note,
         })
     }
 
+    /// Issue a stateless, ed25519-signed invite token for `user_id`, subject
+    /// to the same per-trust-level quota as `create_invite`. Unlike a random
+    /// code, the token carries everything needed to verify it offline (see
+    /// `signed::verify`); only `consume_signed_invite` touches the database,
+    /// and only to do multi-use accounting and record the relationship.
+    pub async fn issue_signed_invite(
+        &self,
+        signing_key_seed: &[u8; 32],
+        user_id: Uuid,
+        days_valid: i64,
+        max_uses: i32,
+    ) -> Result<String> {
+        let quota_check = sqlx::query(
+            "SELECT trust_level, invites_sent \
+             FROM user_trust_scores \
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let (trust_level, invites_sent) = match quota_check {
+            Some(row) => (row.get::<i32, _>("trust_level"), row.get::<i32, _>("invites_sent")),
+            None => return Err(anyhow!("User trust score not found")),
+        };
+
+        let max_invites = max_invites_for_trust_level(trust_level);
+        if invites_sent >= max_invites {
+            return Err(anyhow!(
+                "Maximum invite limit reached for your trust level ({})",
+                max_invites
+            ));
+        }
+
+        let token = signed::issue(signing_key_seed, user_id, days_valid, max_uses);
+
+        sqlx::query(
+            "UPDATE user_trust_scores \
+             SET invites_sent = invites_sent + 1 \
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        tracing::info!(user_id = %user_id, max_uses, "signed invite token issued");
+
+        Ok(token)
+    }
+
+    /// Validate and consume a stateless signed invite token as part of an
+    /// existing transaction, mirroring `consume_invite_with_tx`. Signature
+    /// and expiry are checked purely against the token's own bytes; only the
+    /// token's `nonce` round-trips to the database, to track multi-use counts
+    /// and to let `revoke_signed_invite` deny-list one token without rotating
+    /// the whole signing key.
+    pub async fn consume_signed_invite(
+        &self,
+        signing_key_seed: &[u8; 32],
+        token: &str,
+        new_user_id: Uuid,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ) -> Result<()> {
+        let payload = signed::verify(signing_key_seed, token)?;
+
+        sqlx::query(
+            "INSERT INTO invite_token_nonces (nonce, inviter_id, max_uses, use_count, revoked) \
+             VALUES ($1, $2, $3, 0, FALSE) \
+             ON CONFLICT (nonce) DO NOTHING",
+        )
+        .bind(&payload.nonce)
+        .bind(payload.inviter_id)
+        .bind(payload.max_uses)
+        .execute(&mut **tx)
+        .await?;
+
+        let consumed = sqlx::query(
+            "UPDATE invite_token_nonces \
+             SET use_count = use_count + 1 \
+             WHERE nonce = $1 AND use_count < max_uses AND revoked = FALSE",
+        )
+        .bind(&payload.nonce)
+        .execute(&mut **tx)
+        .await?;
+
+        if consumed.rows_affected() == 0 {
+            return Err(anyhow!("Invite token has been fully used or revoked"));
+        }
+
+        sqlx::query(
+            "INSERT INTO invite_relationships (inviter_id, invitee_id, invite_code) \
+             VALUES ($1, $2, $3)",
+        )
+        .bind(payload.inviter_id)
+        .bind(new_user_id)
+        .bind(format!("signed:{}", payload.nonce))
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE user_trust_scores \
+             SET successful_invites = successful_invites + 1, \
+                 trust_points = trust_points + 10 \
+             WHERE user_id = $1",
+        )
+        .bind(payload.inviter_id)
+        .execute(&mut **tx)
+        .await?;
+
+        tracing::info!(
+            inviter_id = %payload.inviter_id,
+            invitee_id = %new_user_id,
+            nonce = %payload.nonce,
+            "signed invite token consumed"
+        );
+
+        Ok(())
+    }
+
+    /// Deny-list a signed invite token by its embedded nonce, without
+    /// rotating the signing key (which would invalidate every other
+    /// outstanding token too). If the nonce hasn't been seen by
+    /// `consume_signed_invite` yet, this seeds an already-revoked row so a
+    /// later consume attempt finds it revoked.
+    pub async fn revoke_signed_invite(&self, nonce: &str, inviter_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO invite_token_nonces (nonce, inviter_id, max_uses, use_count, revoked) \
+             VALUES ($1, $2, 0, 0, TRUE) \
+             ON CONFLICT (nonce) DO UPDATE SET revoked = TRUE",
+        )
+        .bind(nonce)
+        .bind(inviter_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// List outstanding (unconsumed, unexpired) invite codes across all
+    /// users, for the admin invite-management surface.
+    pub async fn list_all_invites(&self, limit: i64) -> Result<Vec<InviteCode>> {
+        let rows = sqlx::query(
+            "SELECT code, created_by, bound_email, used_by, created_at, used_at, expires_at, \
+                    is_valid, invite_type, use_count, max_uses, note \
+             FROM invite_codes \
+             WHERE is_valid = TRUE AND expires_at > now() \
+             ORDER BY created_at DESC \
+             LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(row_to_invite_code).collect())
+    }
+
+    /// Revoke any invite code by an admin, regardless of who created it.
+    pub async fn revoke_invite_as_admin(&self, code: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE invite_codes \
+             SET is_valid = FALSE, updated_at = NOW() \
+             WHERE code = $1 AND is_valid = TRUE",
+        )
+        .bind(code)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Generate a unique invite code
     async fn generate_unique_code(&self) -> Result<String> {
         for _ in 0..10 {
@@ -148,19 +386,38 @@ impl InviteService {
         Err(anyhow!("Failed to generate unique invite code after 10 attempts"))
     }
 
-    /// Validate and consume an invite code during signup
-    pub async fn consume_invite(&self, code: &str, new_user_id: Uuid) -> Result<Uuid> {
+    /// Validate and consume an invite code outside of any other transaction
+    /// (opens and commits its own). Prefer `consume_invite_with_tx` when the
+    /// caller needs consumption to succeed or fail atomically with some
+    /// other write, e.g. the user row it's gating.
+    pub async fn consume_invite(&self, code: &str, new_user_id: Uuid, email: &str) -> Result<()> {
         let mut tx = self.db.pool().begin().await?;
+        self.consume_invite_with_tx(code, new_user_id, email, &mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
 
+    /// Validate and consume an invite code as part of an existing
+    /// transaction, so it's only marked used if the surrounding write (e.g.
+    /// user creation) also commits. Checks the optional email binding set by
+    /// `create_admin_invite` before consuming.
+    pub async fn consume_invite_with_tx(
+        &self,
+        code: &str,
+        new_user_id: Uuid,
+        email: &str,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ) -> Result<()> {
         // Fetch and validate invite with row lock
         let row = sqlx::query(
-            "SELECT code, created_by, is_valid, expires_at, use_count, max_uses \
+            "SELECT created_by, bound_email, is_valid, expires_at, use_count, max_uses \
              FROM invite_codes \
              WHERE code = $1 \
              FOR UPDATE",
         )
         .bind(code)
-        .fetch_optional(&mut *tx)
+        .fetch_optional(&mut **tx)
         .await?;
 
         let row = row.ok_or_else(|| anyhow!("Invalid invite code"))?;
@@ -169,7 +426,8 @@ impl InviteService {
         let expires_at: OffsetDateTime = row.get("expires_at");
         let use_count: i32 = row.get("use_count");
         let max_uses: i32 = row.get("max_uses");
-        let created_by: Uuid = row.get("created_by");
+        let created_by: Option<Uuid> = row.get("created_by");
+        let bound_email: Option<String> = row.get("bound_email");
 
         // Validation checks
         if !is_valid {
@@ -184,6 +442,12 @@ impl InviteService {
             return Err(anyhow!("Invite code has been fully used"));
         }
 
+        if let Some(bound_email) = &bound_email {
+            if !bound_email.eq_ignore_ascii_case(email) {
+                return Err(anyhow!("Invite code is bound to a different email address"));
+            }
+        }
+
         // Mark as used
         let is_fully_used = use_count + 1 >= max_uses;
         sqlx::query(
@@ -197,48 +461,49 @@ impl InviteService {
         .bind(new_user_id)
         .bind(!is_fully_used) // Mark invalid if fully used
         .bind(code)
-        .execute(&mut *tx)
-        .await?;
-
-        // Record relationship
-        sqlx::query(
-            "INSERT INTO invite_relationships (inviter_id, invitee_id, invite_code) \
-             VALUES ($1, $2, $3)",
-        )
-        .bind(created_by)
-        .bind(new_user_id)
-        .bind(code)
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
 
-        // Update inviter's successful invite count and reward with trust points
-        sqlx::query(
-            "UPDATE user_trust_scores \
-             SET successful_invites = successful_invites + 1, \
-                 trust_points = trust_points + 10 \
-             WHERE user_id = $1",
-        )
-        .bind(created_by)
-        .execute(&mut *tx)
-        .await?;
+        // Admin-issued codes have no inviter to credit/reward.
+        if let Some(inviter_id) = created_by {
+            // Record relationship
+            sqlx::query(
+                "INSERT INTO invite_relationships (inviter_id, invitee_id, invite_code) \
+                 VALUES ($1, $2, $3)",
+            )
+            .bind(inviter_id)
+            .bind(new_user_id)
+            .bind(code)
+            .execute(&mut **tx)
+            .await?;
 
-        tx.commit().await?;
+            // Update inviter's successful invite count and reward with trust points
+            sqlx::query(
+                "UPDATE user_trust_scores \
+                 SET successful_invites = successful_invites + 1, \
+                     trust_points = trust_points + 10 \
+                 WHERE user_id = $1",
+            )
+            .bind(inviter_id)
+            .execute(&mut **tx)
+            .await?;
+        }
 
         tracing::info!(
-            inviter_id = %created_by,
+            inviter_id = ?created_by,
             invitee_id = %new_user_id,
             code = code,
             "Invite code consumed"
         );
 
-        Ok(created_by)
+        Ok(())
     }
 
     /// Get user's invite codes
     pub async fn list_user_invites(&self, user_id: Uuid) -> Result<Vec<InviteCode>> {
         let rows = sqlx::query(
-            "SELECT code, created_by, used_by, created_at, used_at, expires_at, \
-                    is_valid, invite_type, use_count, max_uses \
+            "SELECT code, created_by, bound_email, used_by, created_at, used_at, expires_at, \
+                    is_valid, invite_type, use_count, max_uses, note \
              FROM invite_codes \
              WHERE created_by = $1 \
              ORDER BY created_at DESC \
@@ -248,23 +513,7 @@ impl InviteService {
         .fetch_all(self.db.pool())
         .await?;
 
-        let invites = rows
-            .into_iter()
-            .map(|row| InviteCode {
-                code: row.get("code"),
-                created_by: row.get("created_by"),
-                used_by: row.get("used_by"),
-                created_at: row.get("created_at"),
-                used_at: row.get("used_at"),
-                expires_at: row.get("expires_at"),
-                is_valid: row.get("is_valid"),
-                invite_type: row.get("invite_type"),
-                use_count: row.get("use_count"),
-                max_uses: row.get("max_uses"),
-            })
-            .collect();
-
-        Ok(invites)
+        Ok(rows.into_iter().map(row_to_invite_code).collect())
     }
 
     /// Get invite statistics for a user
@@ -282,13 +531,7 @@ impl InviteService {
         let successful_invites: i32 = row.get("successful_invites");
         let trust_level: i32 = row.get("trust_level");
 
-        let max_invites = match trust_level {
-            0 => 3,
-            1 => 10,
-            2 => 50,
-            3 => 200,
-            _ => 3,
-        };
+        let max_invites = max_invites_for_trust_level(trust_level);
 
         let remaining = max_invites - invites_sent;
 
@@ -315,51 +558,56 @@ impl InviteService {
         Ok(result.rows_affected() > 0)
     }
 
-    /// Get invite tree (who invited whom) for a user
+    /// Get the invite tree (who invited whom, transitively) rooted at
+    /// `user_id`, down to `depth` levels. A single recursive CTE rather than
+    /// one `SELECT` per level -- the old per-node recursion both N+1'd the
+    /// query count and held a connection for the whole depth-first walk on
+    /// large referral networks. `path` guards against a cycle in
+    /// `invite_relationships` turning the recursion into an infinite loop,
+    /// and `MAX_INVITE_TREE_ROWS` bounds pathological fan-out.
     pub async fn get_invite_tree(&self, user_id: Uuid, depth: i32) -> Result<Vec<InviteRelationship>> {
-        let mut relationships = Vec::new();
-        self.get_invite_tree_recursive(user_id, depth, &mut relationships).await?;
-        Ok(relationships)
-    }
-
-    fn get_invite_tree_recursive<'a>(
-        &'a self,
-        user_id: Uuid,
-        depth: i32,
-        relationships: &'a mut Vec<InviteRelationship>,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
-        Box::pin(async move {
-            if depth <= 0 {
-                return Ok(());
-            }
-
-            let rows = sqlx::query(
-                "SELECT inviter_id, invitee_id, invite_code, invited_at \
+        let rows = sqlx::query(
+            "WITH RECURSIVE invite_tree AS ( \
+                 SELECT inviter_id, invitee_id, invite_code, invited_at, \
+                        1 AS level, ARRAY[inviter_id, invitee_id] AS path \
                  FROM invite_relationships \
-                 WHERE inviter_id = $1",
-            )
-            .bind(user_id)
-            .fetch_all(self.db.pool())
-            .await?;
-
-            for row in rows {
-                let invitee_id: Uuid = row.get("invitee_id");
-                relationships.push(InviteRelationship {
-                    inviter_id: row.get("inviter_id"),
-                    invitee_id,
-                    invite_code: row.get("invite_code"),
-                    invited_at: row.get("invited_at"),
-                });
-
-                // Recurse for children
-                self.get_invite_tree_recursive(invitee_id, depth - 1, relationships).await?;
-            }
+                 WHERE inviter_id = $1 \
+                 UNION ALL \
+                 SELECT r.inviter_id, r.invitee_id, r.invite_code, r.invited_at, \
+                        t.level + 1, t.path || r.invitee_id \
+                 FROM invite_relationships r \
+                 JOIN invite_tree t ON t.invitee_id = r.inviter_id \
+                 WHERE t.level < $2 AND NOT r.invitee_id = ANY(t.path) \
+             ) \
+             SELECT inviter_id, invitee_id, invite_code, invited_at, level \
+             FROM invite_tree \
+             ORDER BY level \
+             LIMIT $3",
+        )
+        .bind(user_id)
+        .bind(depth)
+        .bind(MAX_INVITE_TREE_ROWS)
+        .fetch_all(self.db.pool())
+        .await?;
 
-            Ok(())
-        })
+        Ok(rows
+            .into_iter()
+            .map(|row| InviteRelationship {
+                inviter_id: row.get("inviter_id"),
+                invitee_id: row.get("invitee_id"),
+                invite_code: row.get("invite_code"),
+                invited_at: row.get("invited_at"),
+                level: row.get("level"),
+            })
+            .collect())
     }
 }
 
+/// Caps the row count `get_invite_tree` returns regardless of `depth`, so a
+/// pathologically wide referral network can't blow up one request's memory
+/// or response size.
+const MAX_INVITE_TREE_ROWS: i64 = 5_000;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct InviteStats {
     pub invites_sent: i32,
@@ -375,4 +623,126 @@ pub struct InviteRelationship {
     pub invite_code: String,
     #[serde(with = "time::serde::rfc3339")]
     pub invited_at: OffsetDateTime,
+    /// Distance from the tree's root (`get_invite_tree`'s `user_id`), 1 for
+    /// their direct invitees, 2 for invitees-of-invitees, and so on -- lets
+    /// callers reconstruct the hierarchy from the flattened row list.
+    pub level: i32,
+}
+
+fn row_to_invite_code(row: sqlx::postgres::PgRow) -> InviteCode {
+    let use_count: i32 = row.get("use_count");
+    let max_uses: i32 = row.get("max_uses");
+    InviteCode {
+        code: row.get("code"),
+        created_by: row.get("created_by"),
+        bound_email: row.get("bound_email"),
+        used_by: row.get("used_by"),
+        created_at: row.get("created_at"),
+        used_at: row.get("used_at"),
+        expires_at: row.get("expires_at"),
+        is_valid: row.get("is_valid"),
+        invite_type: row.get("invite_type"),
+        use_count,
+        max_uses,
+        uses_remaining: (max_uses - use_count).max(0),
+        note: row.get("note"),
+    }
+}
+
+/// Stateless, ed25519-signed invite tokens: everything needed to verify one
+/// (signature, expiry) is embedded in the token itself, so `signed::verify`
+/// never touches the database. `InviteService::consume_signed_invite` is
+/// still responsible for the parts that inherently need shared state --
+/// multi-use accounting and `invite_relationships` bookkeeping -- keyed off
+/// the token's `nonce` rather than the full token string.
+pub mod signed {
+    use anyhow::{anyhow, bail, Result};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+    use rand::RngCore;
+    use serde::{Deserialize, Serialize};
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    /// The data embedded in a signed invite token. Mirrors the columns
+    /// `InviteCode` stores for a random-code invite, minus anything that
+    /// requires a database (`used_by`, `use_count`, `is_valid`).
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SignedInvitePayload {
+        pub inviter_id: Uuid,
+        pub issued_at: i64,
+        pub expires_at: i64,
+        pub max_uses: i32,
+        /// Random per-token bytes (base64), letting an operator deny-list
+        /// this one token via `InviteService::revoke_signed_invite` without
+        /// rotating the signing key and invalidating every other one.
+        pub nonce: String,
+    }
+
+    /// Mint a new signed token for `inviter_id`. Pure and DB-free -- callers
+    /// that want the per-trust-level quota enforced go through
+    /// `InviteService::issue_signed_invite` instead of calling this directly.
+    pub fn issue(signing_key_seed: &[u8; 32], inviter_id: Uuid, days_valid: i64, max_uses: i32) -> String {
+        let issued_at = OffsetDateTime::now_utc();
+        let expires_at = issued_at + time::Duration::days(days_valid);
+
+        let mut nonce_bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+        let payload = SignedInvitePayload {
+            inviter_id,
+            issued_at: issued_at.unix_timestamp(),
+            expires_at: expires_at.unix_timestamp(),
+            max_uses,
+            nonce: URL_SAFE_NO_PAD.encode(nonce_bytes),
+        };
+
+        encode(signing_key_seed, &payload)
+    }
+
+    fn encode(signing_key_seed: &[u8; 32], payload: &SignedInvitePayload) -> String {
+        let signing_key = SigningKey::from_bytes(signing_key_seed);
+        let payload_json =
+            serde_json::to_vec(payload).expect("SignedInvitePayload always serializes");
+        let signature = signing_key.sign(&payload_json);
+
+        let mut token_bytes = payload_json;
+        token_bytes.extend_from_slice(&signature.to_bytes());
+        URL_SAFE_NO_PAD.encode(token_bytes)
+    }
+
+    /// Verify a token's signature and expiry purely from its own bytes --
+    /// no database read. Does not check use-count or the revocation
+    /// deny-list; that's `InviteService::consume_signed_invite`'s job, since
+    /// it needs a transaction.
+    pub fn verify(signing_key_seed: &[u8; 32], token: &str) -> Result<SignedInvitePayload> {
+        let token_bytes = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|_| anyhow!("invite token is not valid base64"))?;
+
+        if token_bytes.len() <= 64 {
+            bail!("invite token is too short");
+        }
+        let (payload_json, signature_bytes) = token_bytes.split_at(token_bytes.len() - 64);
+        let signature_bytes: [u8; 64] = signature_bytes
+            .try_into()
+            .expect("split_at above guarantees exactly 64 bytes");
+        let signature = Signature::from_bytes(&signature_bytes);
+
+        let signing_key = SigningKey::from_bytes(signing_key_seed);
+        signing_key
+            .verifying_key()
+            .verify(payload_json, &signature)
+            .map_err(|_| anyhow!("invite token signature is invalid"))?;
+
+        let payload: SignedInvitePayload = serde_json::from_slice(payload_json)
+            .map_err(|_| anyhow!("invite token payload is malformed"))?;
+
+        if payload.expires_at < OffsetDateTime::now_utc().unix_timestamp() {
+            bail!("invite token has expired");
+        }
+
+        Ok(payload)
+    }
 }
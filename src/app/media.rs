@@ -1,13 +1,20 @@
 use anyhow::{anyhow, Result};
 use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use dashmap::DashMap;
+use image::ImageFormat;
 use redis::AsyncCommands;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::Row;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 use url::Url;
 
-use crate::domain::media::Media;
+use crate::domain::media::{DeletionQueue, Media, MediaKind};
 use crate::infra::{cache::RedisCache, db::Db, queue::QueueClient, storage::ObjectStorage};
 use crate::jobs::media_processor::MediaJob;
 
@@ -18,8 +25,17 @@ pub struct MediaService {
     storage: ObjectStorage,
     queue: QueueClient,
     s3_public_endpoint: Option<String>,
+    variant_guard: VariantGenerationGuard,
+    derivative_mode: crate::config::DerivativeMode,
+    concurrency_limiter: MediaConcurrencyLimiter,
 }
 
+/// Fixed size ladder for on-demand derivatives -- intentionally narrow so the
+/// variant cache (and, in `Eager` mode, pre-rendering) can't be forced to
+/// generate and store an unbounded number of distinct sizes for one media
+/// item. Shared by `get_media_derivative` and `prerender_derivatives`.
+pub const DERIVATIVE_SIZES: &[u32] = &[80, 160, 320, 640, 1080, 2160];
+
 #[derive(Debug, Serialize)]
 pub struct UploadIntent {
     pub upload_id: Uuid,
@@ -35,15 +51,312 @@ pub struct UploadHeader {
     pub value: String,
 }
 
+/// Returned by `create_upload_direct`. Its `status` is already `uploaded`,
+/// since the direct-upload flow skips the `pending` -> `uploaded`
+/// transition a client would otherwise have to poll for.
+#[derive(Debug, Serialize)]
+pub struct DirectUploadResult {
+    pub upload_id: Uuid,
+    pub object_key: String,
+    pub status: String,
+    /// Echoes the sniffed-and-confirmed content type back to the caller, so
+    /// a CLI/small-client caller that skipped the presigned flow doesn't
+    /// have to separately poll `get_upload_status` just to learn what the
+    /// server ended up storing it as.
+    pub content_type: String,
+}
+
+/// Returned by `create_multipart_upload`: one presigned `UploadPart` URL per
+/// part, so a large file can be uploaded in parallel, resumable chunks
+/// instead of the single presigned `put_object` `UploadIntent` uses.
+#[derive(Debug, Serialize)]
+pub struct MultipartUploadIntent {
+    pub upload_id: Uuid,
+    pub object_key: String,
+    pub parts: Vec<MultipartUploadPart>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MultipartUploadPart {
+    pub part_number: i32,
+    pub upload_url: String,
+    pub headers: Vec<UploadHeader>,
+}
+
+/// A part's S3-assigned ETag, reported back by the client after it finishes
+/// uploading each presigned part, so `complete_multipart_upload` can tell S3
+/// how to reassemble them in order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletedPart {
+    pub part_number: i32,
+    pub etag: String,
+}
+
+/// Result of `MediaService::complete_upload`, letting the handler distinguish
+/// a missing upload from one the synchronous content-type sniff rejected.
+pub enum CompleteUploadOutcome {
+    Queued,
+    NotFound,
+    ContentTypeMismatch,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UploadStatus {
     pub status: String,
     pub processed_media_id: Option<Uuid>,
+    /// Set when `status` is `rejected` -- why the uploaded bytes didn't pass
+    /// server-side format validation, so the client can show an error
+    /// instead of polling forever.
+    pub rejection_reason: Option<String>,
+    /// The SHA-256 hash of the processed media's content-addressed blob
+    /// (`media_blobs.hash`), once processing has completed. Lets a client
+    /// recognize it already uploaded this exact file before re-uploading it.
+    pub content_hash: Option<String>,
+}
+
+/// One resize/crop/thumbnail operation in a [`ProcessSpec`] chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum VariantOp {
+    Resize { width: u32, height: u32 },
+    Crop { x: u32, y: u32, width: u32, height: u32 },
+    Thumbnail { width: u32, height: u32 },
+}
+
+/// Output format for a generated variant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl VariantFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            VariantFormat::Jpeg => "jpg",
+            VariantFormat::Png => "png",
+            VariantFormat::Webp => "webp",
+        }
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            VariantFormat::Jpeg => ImageFormat::Jpeg,
+            VariantFormat::Png => ImageFormat::Png,
+            VariantFormat::Webp => ImageFormat::WebP,
+        }
+    }
+}
+
+/// An on-demand processing request for [`MediaService::get_media_variant`],
+/// modeled on pict-rs's processor: an ordered chain of operations plus an
+/// output format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSpec {
+    pub ops: Vec<VariantOp>,
+    pub format: VariantFormat,
+}
+
+impl ProcessSpec {
+    /// Canonical, order-independent encoding of this spec, used to derive
+    /// the variant's storage key -- the same operations requested in a
+    /// different order resolve to the one cached object rather than being
+    /// regenerated and stored again under a different key.
+    fn canonical(&self) -> String {
+        let mut parts: Vec<String> = self
+            .ops
+            .iter()
+            .map(|op| match op {
+                VariantOp::Resize { width, height } => format!("resize-{}x{}", width, height),
+                VariantOp::Crop { x, y, width, height } => {
+                    format!("crop-{}-{}-{}x{}", x, y, width, height)
+                }
+                VariantOp::Thumbnail { width, height } => format!("thumbnail-{}x{}", width, height),
+            })
+            .collect();
+        parts.sort();
+        parts.join("_")
+    }
+}
+
+struct VariantLock {
+    mutex: Arc<tokio::sync::Mutex<()>>,
+    last_used_unix: AtomicI64,
+}
+
+const VARIANT_LOCK_IDLE_GC_AFTER_SECONDS: i64 = 600;
+const VARIANT_LOCK_GC_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Per-variant-key mutual exclusion so concurrent requests for the same
+/// `(media_id, ProcessSpec)` collapse into a single generate-and-store
+/// instead of redoing the same resize work in parallel. Mirrors
+/// `ConcurrencyLimiter`'s DashMap-of-`Arc`s-with-GC shape, keyed on the
+/// variant's storage key instead of a user id.
+#[derive(Clone)]
+pub struct VariantGenerationGuard {
+    locks: Arc<DashMap<String, Arc<VariantLock>>>,
+}
+
+impl VariantGenerationGuard {
+    pub fn new() -> Self {
+        let guard = Self {
+            locks: Arc::new(DashMap::new()),
+        };
+        guard.spawn_gc_loop();
+        guard
+    }
+
+    fn spawn_gc_loop(&self) {
+        let locks = self.locks.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(VARIANT_LOCK_GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = now_unix();
+                locks.retain(|_, lock| {
+                    let idle_seconds = now - lock.last_used_unix.load(Ordering::Relaxed);
+                    idle_seconds < VARIANT_LOCK_IDLE_GC_AFTER_SECONDS || Arc::strong_count(lock) > 1
+                });
+            }
+        });
+    }
+
+    async fn lock(&self, key: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        let entry = self
+            .locks
+            .entry(key.to_string())
+            .or_insert_with(|| {
+                Arc::new(VariantLock {
+                    mutex: Arc::new(tokio::sync::Mutex::new(())),
+                    last_used_unix: AtomicI64::new(now_unix()),
+                })
+            })
+            .clone();
+        entry.last_used_unix.store(now_unix(), Ordering::Relaxed);
+        entry.mutex.clone().lock_owned().await
+    }
+}
+
+impl Default for VariantGenerationGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Caps simultaneous CPU/IO-heavy media work -- format sniffing, content
+/// hashing, and derivative rendering -- across the whole process, on top of
+/// the per-user `ConcurrencyLimiter`. Unlike `VariantGenerationGuard`, which
+/// keys a lock per variant, this is a single process-wide `Semaphore`:
+/// `max_concurrency` is `None` when `MEDIA_MAX_CONCURRENCY` is unset, which
+/// means unlimited (every `acquire` succeeds immediately). `in_flight` and
+/// `queued` are surfaced on `GET /metrics` so operators can tell whether the
+/// cap is actually binding before tuning it.
+#[derive(Clone)]
+pub struct MediaConcurrencyLimiter {
+    semaphore: Option<Arc<tokio::sync::Semaphore>>,
+    max_concurrency: Option<usize>,
+    acquire_timeout: Duration,
+    in_flight: Arc<AtomicI64>,
+    queued: Arc<AtomicI64>,
+}
+
+impl MediaConcurrencyLimiter {
+    pub fn new(max_concurrency: Option<usize>, acquire_timeout: Duration) -> Self {
+        Self {
+            semaphore: max_concurrency.map(|n| Arc::new(tokio::sync::Semaphore::new(n.max(1)))),
+            max_concurrency,
+            acquire_timeout,
+            in_flight: Arc::new(AtomicI64::new(0)),
+            queued: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Waits up to `acquire_timeout` for a permit if the cap is currently
+    /// exhausted. Returns `None` -- the caller should treat this as "server
+    /// busy" -- if the timeout elapses first; always returns `Some` when
+    /// unlimited.
+    pub async fn acquire(&self) -> Option<MediaConcurrencyPermit> {
+        let Some(semaphore) = &self.semaphore else {
+            self.in_flight.fetch_add(1, Ordering::Relaxed);
+            return Some(MediaConcurrencyPermit {
+                _permit: None,
+                in_flight: self.in_flight.clone(),
+            });
+        };
+
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        let acquired = tokio::time::timeout(self.acquire_timeout, semaphore.clone().acquire_owned()).await;
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+
+        let permit = acquired.ok()?.ok()?;
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        Some(MediaConcurrencyPermit {
+            _permit: Some(permit),
+            in_flight: self.in_flight.clone(),
+        })
+    }
+
+    pub fn stats(&self) -> MediaConcurrencyStats {
+        MediaConcurrencyStats {
+            max_concurrency: self.max_concurrency,
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Held for the duration of one guarded operation; releases the permit (and
+/// decrements `in_flight`) on drop, on every path including early returns.
+pub struct MediaConcurrencyPermit {
+    _permit: Option<tokio::sync::OwnedSemaphorePermit>,
+    in_flight: Arc<AtomicI64>,
+}
+
+impl Drop for MediaConcurrencyPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaConcurrencyStats {
+    pub max_concurrency: Option<usize>,
+    pub in_flight: i64,
+    pub queued: i64,
+}
+
+fn now_unix() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
 }
 
 impl MediaService {
-    pub fn new(db: Db, cache: RedisCache, storage: ObjectStorage, queue: QueueClient, s3_public_endpoint: Option<String>) -> Self {
-        Self { db, cache, storage, queue, s3_public_endpoint }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db: Db,
+        cache: RedisCache,
+        storage: ObjectStorage,
+        queue: QueueClient,
+        s3_public_endpoint: Option<String>,
+        variant_guard: VariantGenerationGuard,
+        derivative_mode: crate::config::DerivativeMode,
+        concurrency_limiter: MediaConcurrencyLimiter,
+    ) -> Self {
+        Self {
+            db,
+            cache,
+            storage,
+            queue,
+            s3_public_endpoint,
+            variant_guard,
+            derivative_mode,
+            concurrency_limiter,
+        }
     }
 
     pub async fn create_upload(
@@ -107,25 +420,154 @@ impl MediaService {
         })
     }
 
-    pub async fn complete_upload(&self, upload_id: Uuid, owner_id: Uuid) -> Result<bool> {
+    /// Single-request alternative to `create_upload` + `complete_upload` for
+    /// clients that can't do a presigned PUT (small-file clients, CLIs):
+    /// the handler already has the full body in hand, so this uploads it to
+    /// storage directly instead of handing back a presigned URL, then
+    /// enqueues the same async processing job. Returns `Ok(None)` if the
+    /// declared `content_type` doesn't match the bytes' magic-byte-sniffed
+    /// format, mirroring `complete_upload`'s `ContentTypeMismatch` outcome.
+    pub async fn create_upload_direct(
+        &self,
+        owner_id: Uuid,
+        content_type: String,
+        data: Vec<u8>,
+    ) -> Result<Option<DirectUploadResult>> {
+        let ext = extension_from_content_type(&content_type)?;
+
+        if let Some(declared_kind) = ImageKind::from_content_type(&content_type) {
+            if ImageKind::sniff(&data) != Some(declared_kind) {
+                return Ok(None);
+            }
+        }
+
+        let upload_id = Uuid::new_v4();
+        let object_key = format!("uploads/{}/{}.{}", owner_id, upload_id, ext);
+        let bytes = data.len() as i64;
+
+        sqlx::query(
+            "INSERT INTO media_uploads (id, owner_id, original_key, content_type, bytes, status, uploaded_at) \
+             VALUES ($1, $2, $3, $4, $5, 'uploaded', now())",
+        )
+        .bind(upload_id)
+        .bind(owner_id)
+        .bind(&object_key)
+        .bind(&content_type)
+        .bind(bytes)
+        .execute(self.db.pool())
+        .await?;
+
+        self.storage
+            .client()
+            .put_object()
+            .bucket(self.storage.bucket())
+            .key(&object_key)
+            .content_type(&content_type)
+            .body(ByteStream::from(bytes::Bytes::from(data)))
+            .send()
+            .await?;
+
+        self.enqueue_processing(upload_id, owner_id, object_key.clone()).await?;
+
+        Ok(Some(DirectUploadResult {
+            upload_id,
+            object_key,
+            status: "uploaded".to_string(),
+            content_type,
+        }))
+    }
+
+    /// Marks an upload `uploaded` and hands it off for async processing --
+    /// but first, for image content types, sniffs the stored object's magic
+    /// bytes against the declared `content_type` so a spoofed upload (e.g.
+    /// `image/png` bytes that are actually a JPEG) gets caught synchronously
+    /// instead of silently passing through as a `.png` with the wrong
+    /// suffix. Video content types are left to the async job's own
+    /// `sniff_video_format` check, since ffprobe is the only reliable way to
+    /// validate those.
+    pub async fn complete_upload(
+        &self,
+        upload_id: Uuid,
+        owner_id: Uuid,
+    ) -> Result<CompleteUploadOutcome> {
         let row = sqlx::query(
             "UPDATE media_uploads \
              SET status = 'uploaded', uploaded_at = now() \
              WHERE id = $1 AND owner_id = $2 AND status = 'pending' \
-             RETURNING original_key",
+             RETURNING original_key, content_type",
         )
         .bind(upload_id)
         .bind(owner_id)
         .fetch_optional(self.db.pool())
         .await?;
 
-        let original_key: String = match row {
-            Some(row) => row.get("original_key"),
-            None => return Ok(false),
+        let (mut original_key, content_type): (String, String) = match row {
+            Some(row) => (row.get("original_key"), row.get("content_type")),
+            None => return Ok(CompleteUploadOutcome::NotFound),
         };
 
+        if let Some(declared_kind) = ImageKind::from_content_type(&content_type) {
+            let head = self
+                .storage
+                .client()
+                .get_object()
+                .bucket(self.storage.bucket())
+                .key(&original_key)
+                .range("bytes=0-15")
+                .send()
+                .await?;
+            let head_bytes = head.body.collect().await?.into_bytes();
+
+            if ImageKind::sniff(&head_bytes) != Some(declared_kind) {
+                sqlx::query(
+                    "UPDATE media_uploads \
+                     SET status = 'rejected', rejection_reason = 'content type mismatch' \
+                     WHERE id = $1 AND owner_id = $2",
+                )
+                .bind(upload_id)
+                .bind(owner_id)
+                .execute(self.db.pool())
+                .await?;
+                return Ok(CompleteUploadOutcome::ContentTypeMismatch);
+            }
+
+            // The sniff matched the declared type, so this only ever
+            // corrects a stale/mismatched suffix -- it can't be used to
+            // smuggle a different format through under the right extension.
+            let detected_ext = declared_kind.extension();
+            if !original_key.ends_with(&format!(".{}", detected_ext)) {
+                let corrected_key = replace_extension(&original_key, detected_ext);
+                self.storage
+                    .client()
+                    .copy_object()
+                    .bucket(self.storage.bucket())
+                    .copy_source(format!("{}/{}", self.storage.bucket(), original_key))
+                    .key(&corrected_key)
+                    .send()
+                    .await?;
+                self.storage
+                    .client()
+                    .delete_object()
+                    .bucket(self.storage.bucket())
+                    .key(&original_key)
+                    .send()
+                    .await?;
+
+                sqlx::query(
+                    "UPDATE media_uploads SET original_key = $1 WHERE id = $2 AND owner_id = $3",
+                )
+                .bind(&corrected_key)
+                .bind(upload_id)
+                .bind(owner_id)
+                .execute(self.db.pool())
+                .await?;
+
+                original_key = corrected_key;
+            }
+        }
+
         self.enqueue_processing(upload_id, owner_id, original_key).await?;
-        Ok(true)
+        Ok(CompleteUploadOutcome::Queued)
     }
 
     pub async fn enqueue_processing(
@@ -138,15 +580,219 @@ impl MediaService {
             upload_id,
             owner_id,
             original_key,
+            eager_derivatives: self.derivative_mode == crate::config::DerivativeMode::Eager,
         };
 
         self.queue.enqueue_media_job(&job).await?;
         Ok(())
     }
 
+    /// Multipart variant of `create_upload`, for large files (videos) over
+    /// flaky connections: each part gets its own presigned `UploadPart` URL
+    /// so the client can upload them independently -- and retry just the
+    /// parts that fail -- instead of one all-or-nothing `put_object`.
+    pub async fn create_multipart_upload(
+        &self,
+        owner_id: Uuid,
+        content_type: String,
+        bytes: i64,
+        part_count: i32,
+        expires_in_seconds: u64,
+    ) -> Result<MultipartUploadIntent> {
+        if part_count < 1 {
+            return Err(anyhow!("part_count must be at least 1"));
+        }
+
+        let ext = extension_from_content_type(&content_type)?;
+        let upload_id = Uuid::new_v4();
+        let object_key = format!("uploads/{}/{}.{}", owner_id, upload_id, ext);
+
+        let created = self
+            .storage
+            .client()
+            .create_multipart_upload()
+            .bucket(self.storage.bucket())
+            .key(&object_key)
+            .content_type(&content_type)
+            .send()
+            .await?;
+        let s3_upload_id = created
+            .upload_id()
+            .ok_or_else(|| anyhow!("S3 did not return a multipart upload id"))?
+            .to_string();
+
+        sqlx::query(
+            "INSERT INTO media_uploads \
+             (id, owner_id, original_key, content_type, bytes, multipart_upload_id) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(upload_id)
+        .bind(owner_id)
+        .bind(&object_key)
+        .bind(&content_type)
+        .bind(bytes)
+        .bind(&s3_upload_id)
+        .execute(self.db.pool())
+        .await?;
+
+        let presign_config = PresigningConfig::expires_in(Duration::from_secs(expires_in_seconds))?;
+        let mut parts = Vec::with_capacity(part_count as usize);
+        for part_number in 1..=part_count {
+            let presigned = self
+                .storage
+                .client()
+                .upload_part()
+                .bucket(self.storage.bucket())
+                .key(&object_key)
+                .upload_id(&s3_upload_id)
+                .part_number(part_number)
+                .presigned(presign_config.clone())
+                .await?;
+
+            let headers = presigned
+                .headers()
+                .map(|(name, value)| UploadHeader {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect();
+
+            let mut upload_url = presigned.uri().to_string();
+            if let Some(ref public_endpoint) = self.s3_public_endpoint {
+                if let Ok(rewritten) = rewrite_presigned_url(&upload_url, public_endpoint) {
+                    upload_url = rewritten;
+                }
+            }
+
+            parts.push(MultipartUploadPart {
+                part_number,
+                upload_url,
+                headers,
+            });
+        }
+
+        Ok(MultipartUploadIntent {
+            upload_id,
+            object_key,
+            parts,
+        })
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        upload_id: Uuid,
+        owner_id: Uuid,
+        parts: Vec<CompletedPart>,
+    ) -> Result<bool> {
+        let row = sqlx::query(
+            "SELECT original_key, multipart_upload_id FROM media_uploads \
+             WHERE id = $1 AND owner_id = $2 AND status = 'pending'",
+        )
+        .bind(upload_id)
+        .bind(owner_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let (original_key, s3_upload_id) = match row {
+            Some(row) => {
+                let original_key: String = row.get("original_key");
+                let s3_upload_id: Option<String> = row.get("multipart_upload_id");
+                (original_key, s3_upload_id)
+            }
+            None => return Ok(false),
+        };
+        let s3_upload_id =
+            s3_upload_id.ok_or_else(|| anyhow!("upload was not started as a multipart upload"))?;
+
+        let completed_parts = parts
+            .into_iter()
+            .map(|part| {
+                aws_sdk_s3::types::CompletedPart::builder()
+                    .part_number(part.part_number)
+                    .e_tag(part.etag)
+                    .build()
+            })
+            .collect();
+
+        self.storage
+            .client()
+            .complete_multipart_upload()
+            .bucket(self.storage.bucket())
+            .key(&original_key)
+            .upload_id(&s3_upload_id)
+            .multipart_upload(
+                aws_sdk_s3::types::CompletedMultipartUpload::builder()
+                    .set_parts(Some(completed_parts))
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        sqlx::query(
+            "UPDATE media_uploads \
+             SET status = 'uploaded', uploaded_at = now() \
+             WHERE id = $1 AND owner_id = $2 AND status = 'pending'",
+        )
+        .bind(upload_id)
+        .bind(owner_id)
+        .execute(self.db.pool())
+        .await?;
+
+        self.enqueue_processing(upload_id, owner_id, original_key).await?;
+        Ok(true)
+    }
+
+    /// Cleans up an orphaned multipart upload -- one the client gave up on
+    /// -- so its uploaded parts don't keep accruing S3 storage cost forever.
+    pub async fn abort_multipart_upload(&self, upload_id: Uuid, owner_id: Uuid) -> Result<bool> {
+        let row = sqlx::query(
+            "SELECT original_key, multipart_upload_id FROM media_uploads \
+             WHERE id = $1 AND owner_id = $2 AND status = 'pending'",
+        )
+        .bind(upload_id)
+        .bind(owner_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let (original_key, s3_upload_id) = match row {
+            Some(row) => {
+                let original_key: String = row.get("original_key");
+                let s3_upload_id: Option<String> = row.get("multipart_upload_id");
+                (original_key, s3_upload_id)
+            }
+            None => return Ok(false),
+        };
+        let s3_upload_id =
+            s3_upload_id.ok_or_else(|| anyhow!("upload was not started as a multipart upload"))?;
+
+        self.storage
+            .client()
+            .abort_multipart_upload()
+            .bucket(self.storage.bucket())
+            .key(&original_key)
+            .upload_id(&s3_upload_id)
+            .send()
+            .await?;
+
+        sqlx::query(
+            "UPDATE media_uploads \
+             SET status = 'failed' \
+             WHERE id = $1 AND owner_id = $2 AND status = 'pending'",
+        )
+        .bind(upload_id)
+        .bind(owner_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(true)
+    }
+
     pub async fn get_media(&self, media_id: Uuid) -> Result<Option<Media>> {
         let row = sqlx::query(
-            "SELECT id, owner_id, original_key, thumb_key, medium_key, width, height, bytes, created_at \
+            "SELECT id, owner_id, post_id, kind, original_key, thumb_key, medium_key, \
+                    original_width, original_height, original_bytes, \
+                    thumb_width, thumb_height, thumb_bytes, \
+                    medium_width, medium_height, medium_bytes, duration_ms, blurhash, created_at \
              FROM media WHERE id = $1",
         )
         .bind(media_id)
@@ -158,16 +804,27 @@ impl MediaService {
                 let original_key: String = row.get("original_key");
                 let thumb_key: String = row.get("thumb_key");
                 let medium_key: String = row.get("medium_key");
+                let kind: String = row.get("kind");
 
                 let mut media = Media {
                     id: row.get("id"),
                     owner_id: row.get("owner_id"),
+                    post_id: row.get("post_id"),
+                    kind: MediaKind::from_db(&kind).unwrap_or(MediaKind::Image),
                     original_key: original_key.clone(),
                     thumb_key: thumb_key.clone(),
                     medium_key: medium_key.clone(),
-                    width: row.get("width"),
-                    height: row.get("height"),
-                    bytes: row.get("bytes"),
+                    original_width: row.get("original_width"),
+                    original_height: row.get("original_height"),
+                    original_bytes: row.get("original_bytes"),
+                    thumb_width: row.get("thumb_width"),
+                    thumb_height: row.get("thumb_height"),
+                    thumb_bytes: row.get("thumb_bytes"),
+                    medium_width: row.get("medium_width"),
+                    medium_height: row.get("medium_height"),
+                    medium_bytes: row.get("medium_bytes"),
+                    duration_ms: row.get("duration_ms"),
+                    thumb_blurhash: row.get("blurhash"),
                     created_at: row.get("created_at"),
                     thumb_url: None,
                     medium_url: None,
@@ -215,18 +872,23 @@ impl MediaService {
     ) -> Result<Option<Media>> {
         let now = time::OffsetDateTime::now_utc();
         let row = sqlx::query(
-            "SELECT m.id, m.owner_id, m.original_key, m.thumb_key, m.medium_key, \
-                    m.width, m.height, m.bytes, m.created_at \
+            "SELECT m.id, m.owner_id, m.post_id, m.kind, m.original_key, m.thumb_key, m.medium_key, \
+                    m.original_width, m.original_height, m.original_bytes, \
+                    m.thumb_width, m.thumb_height, m.thumb_bytes, \
+                    m.medium_width, m.medium_height, m.medium_bytes, m.duration_ms, m.blurhash, m.created_at \
              FROM media m \
              WHERE m.id = $1 \
                AND (m.owner_id = $2 \
                     OR EXISTS ( \
                         SELECT 1 FROM posts p \
-                        WHERE p.media_id = m.id \
+                        WHERE p.id = m.post_id \
                           AND (p.visibility = 'public' \
                                OR p.owner_id = $2 \
-                               OR (p.visibility = 'followers_only' AND EXISTS ( \
+                               OR (p.visibility = 'followers' AND EXISTS ( \
                                    SELECT 1 FROM follows WHERE follower_id = $2 AND followee_id = p.owner_id \
+                               )) \
+                               OR (p.visibility = 'mentioned' AND EXISTS ( \
+                                   SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $2 \
                                ))) \
                           AND NOT EXISTS ( \
                               SELECT 1 FROM blocks \
@@ -261,16 +923,27 @@ impl MediaService {
                 let original_key: String = row.get("original_key");
                 let thumb_key: String = row.get("thumb_key");
                 let medium_key: String = row.get("medium_key");
+                let kind: String = row.get("kind");
 
                 let mut media = Media {
                     id: row.get("id"),
                     owner_id: row.get("owner_id"),
+                    post_id: row.get("post_id"),
+                    kind: MediaKind::from_db(&kind).unwrap_or(MediaKind::Image),
                     original_key: original_key.clone(),
                     thumb_key: thumb_key.clone(),
                     medium_key: medium_key.clone(),
-                    width: row.get("width"),
-                    height: row.get("height"),
-                    bytes: row.get("bytes"),
+                    original_width: row.get("original_width"),
+                    original_height: row.get("original_height"),
+                    original_bytes: row.get("original_bytes"),
+                    thumb_width: row.get("thumb_width"),
+                    thumb_height: row.get("thumb_height"),
+                    thumb_bytes: row.get("thumb_bytes"),
+                    medium_width: row.get("medium_width"),
+                    medium_height: row.get("medium_height"),
+                    medium_bytes: row.get("medium_bytes"),
+                    duration_ms: row.get("duration_ms"),
+                    thumb_blurhash: row.get("blurhash"),
                     created_at: row.get("created_at"),
                     thumb_url: None,
                     medium_url: None,
@@ -311,14 +984,186 @@ impl MediaService {
         Ok(media)
     }
 
+    /// Returns a presigned URL for `spec` applied to `media_id`, generating
+    /// and storing the variant on first request and serving the cached
+    /// object on every one after that. The variant's storage key is
+    /// deterministic (media id + `spec`'s canonicalized operation chain), so
+    /// a Redis membership cache -- the same pattern `generate_presigned_get_url`
+    /// uses for presigned URLs themselves -- can short-circuit straight to
+    /// the presign step once a variant has been generated once.
+    /// `variant_guard` collapses concurrent requests for the same key into a
+    /// single generate-and-store.
+    pub async fn get_media_variant(
+        &self,
+        media_id: Uuid,
+        viewer_id: Uuid,
+        spec: &ProcessSpec,
+    ) -> Result<Option<String>> {
+        let media = match self.get_media_for_user(media_id, viewer_id).await? {
+            Some(media) => media,
+            None => return Ok(None),
+        };
+
+        let variant_key = format!(
+            "media/variants/{}/{}.{}",
+            media_id,
+            spec.canonical(),
+            spec.format.extension()
+        );
+
+        if !self.variant_exists_cached(&variant_key).await {
+            // Collapse duplicate concurrent requests for this exact variant
+            // into one generation; everyone else just waits for the lock.
+            let _permit = self.variant_guard.lock(&variant_key).await;
+
+            // Re-check after acquiring the lock: another request may have
+            // generated and cached this variant while we were waiting.
+            if !self.variant_exists_cached(&variant_key).await {
+                self.generate_variant(&media.original_key, spec, &variant_key).await?;
+                self.mark_variant_cached(&variant_key).await;
+            }
+        }
+
+        let url = self
+            .generate_presigned_get_url(Some(&variant_key), 14400)
+            .await
+            .ok_or_else(|| anyhow!("failed to presign media variant"))?;
+
+        Ok(Some(url))
+    }
+
+    async fn variant_exists_cached(&self, variant_key: &str) -> bool {
+        let cache_key = format!("variant-exists:{}", variant_key);
+        match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(mut conn) => conn.exists::<_, bool>(&cache_key).await.unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    async fn mark_variant_cached(&self, variant_key: &str) {
+        let cache_key = format!("variant-exists:{}", variant_key);
+        if let Ok(mut conn) = self.cache.client().get_multiplexed_async_connection().await {
+            // 30 days: long enough that re-checking storage on every request
+            // would be pure waste, short enough that a dropped cache entry
+            // just costs one redundant (idempotent) regeneration rather than
+            // permanent staleness.
+            let _ = conn.set_ex::<_, _, ()>(&cache_key, 1, 60 * 60 * 24 * 30).await;
+        }
+    }
+
+    /// Downloads the original, applies `spec`'s operations in order, encodes
+    /// in the requested format, and uploads the result to `variant_key`.
+    async fn generate_variant(
+        &self,
+        original_key: &str,
+        spec: &ProcessSpec,
+        variant_key: &str,
+    ) -> Result<()> {
+        let data = self.download_original(original_key).await?;
+        let _permit = self
+            .concurrency_limiter
+            .acquire()
+            .await
+            .ok_or_else(|| anyhow!("media concurrency limit exceeded"))?;
+        let (encoded, content_type) = render_ops(&data, spec)?;
+
+        self.storage
+            .client()
+            .put_object()
+            .bucket(self.storage.bucket())
+            .key(variant_key)
+            .content_type(content_type)
+            .body(ByteStream::from(bytes::Bytes::from(encoded)))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn download_original(&self, original_key: &str) -> Result<bytes::Bytes> {
+        let object = self
+            .storage
+            .client()
+            .get_object()
+            .bucket(self.storage.bucket())
+            .key(original_key)
+            .send()
+            .await?;
+        Ok(object.body.collect().await?.into_bytes())
+    }
+
+    /// `Realtime`-mode counterpart to `get_media_variant`: applies `spec` and
+    /// returns the encoded bytes directly, without writing the result to
+    /// storage or marking it in the variant-exists cache. Every request pays
+    /// the full resize cost again, trading CPU for zero extra storage.
+    pub async fn render_variant_bytes(
+        &self,
+        media_id: Uuid,
+        viewer_id: Uuid,
+        spec: &ProcessSpec,
+    ) -> Result<Option<(Vec<u8>, &'static str)>> {
+        let media = match self.get_media_for_user(media_id, viewer_id).await? {
+            Some(media) => media,
+            None => return Ok(None),
+        };
+
+        let data = self.download_original(&media.original_key).await?;
+        let _permit = self
+            .concurrency_limiter
+            .acquire()
+            .await
+            .ok_or_else(|| anyhow!("media concurrency limit exceeded"))?;
+        render_ops(&data, spec).map(Some)
+    }
+
+    /// `Eager`-mode pre-render: generates and caches every size in
+    /// `DERIVATIVE_SIZES` for the still image at `original_key` right after
+    /// it finishes processing, so the first client request for any of them
+    /// is already a cache hit. Callers only invoke this for `MediaKind::Image`
+    /// -- `generate_variant` decodes the original as an image, which a
+    /// video's `original_key` (an mp4) isn't.
+    pub(crate) async fn prerender_derivatives(
+        &self,
+        media_id: Uuid,
+        original_key: &str,
+    ) -> Result<()> {
+        for &size in DERIVATIVE_SIZES {
+            let spec = ProcessSpec {
+                ops: vec![VariantOp::Thumbnail {
+                    width: size,
+                    height: size,
+                }],
+                format: VariantFormat::Jpeg,
+            };
+            let variant_key = format!(
+                "media/variants/{}/{}.{}",
+                media_id,
+                spec.canonical(),
+                spec.format.extension()
+            );
+
+            if self.variant_exists_cached(&variant_key).await {
+                continue;
+            }
+            self.generate_variant(original_key, &spec, &variant_key).await?;
+            self.mark_variant_cached(&variant_key).await;
+        }
+
+        Ok(())
+    }
+
     pub async fn get_upload_status(
         &self,
         upload_id: Uuid,
         owner_id: Uuid,
     ) -> Result<Option<UploadStatus>> {
         let row = sqlx::query(
-            "SELECT status::text AS status, processed_media_id \
-             FROM media_uploads WHERE id = $1 AND owner_id = $2",
+            "SELECT mu.status::text AS status, mu.processed_media_id, mu.rejection_reason, \
+                    mb.hash AS content_hash \
+             FROM media_uploads mu \
+             LEFT JOIN media m ON m.id = mu.processed_media_id \
+             LEFT JOIN media_blobs mb ON mb.key = m.original_key \
+             WHERE mu.id = $1 AND mu.owner_id = $2",
         )
         .bind(upload_id)
         .bind(owner_id)
@@ -328,47 +1173,68 @@ impl MediaService {
         let status = row.map(|row| UploadStatus {
             status: row.get("status"),
             processed_media_id: row.get("processed_media_id"),
+            rejection_reason: row.get("rejection_reason"),
+            content_hash: row.get("content_hash"),
         });
 
         Ok(status)
     }
 
+    /// Deletes the media row (and any upload record tied to it) and
+    /// enqueues its now-orphaned storage keys for the deletion sweeper to
+    /// remove from S3. The DB transaction stays authoritative -- blob
+    /// deletion only happens once the sweeper drains the queue, so a
+    /// transient S3 outage can never leave the `media` row and its objects
+    /// out of sync.
     pub async fn delete_media(&self, media_id: Uuid, owner_id: Uuid) -> Result<bool> {
+        let mut tx = self.db.pool().begin().await?;
+
         let row = sqlx::query(
-            "SELECT id, owner_id, original_key, thumb_key, medium_key \
-             FROM media WHERE id = $1 AND owner_id = $2",
+            "SELECT original_key, thumb_key, medium_key \
+             FROM media WHERE id = $1 AND owner_id = $2 FOR UPDATE",
         )
         .bind(media_id)
         .bind(owner_id)
-        .fetch_optional(self.db.pool())
+        .fetch_optional(&mut *tx)
         .await?;
 
         let row = match row {
             Some(row) => row,
-            None => return Ok(false),
+            None => {
+                tx.rollback().await?;
+                return Ok(false);
+            }
         };
 
-        let original_key: String = row.get("original_key");
-        let thumb_key: String = row.get("thumb_key");
-        let medium_key: String = row.get("medium_key");
-
-        for key in [original_key, thumb_key, medium_key] {
-            self.storage
-                .client()
-                .delete_object()
-                .bucket(self.storage.bucket())
-                .key(key)
-                .send()
-                .await?;
-        }
+        let keys = [
+            row.get::<String, _>("original_key"),
+            row.get::<String, _>("thumb_key"),
+            row.get::<String, _>("medium_key"),
+        ];
 
-        let result = sqlx::query("DELETE FROM media WHERE id = $1 AND owner_id = $2")
+        sqlx::query("DELETE FROM media_uploads WHERE processed_media_id = $1")
+            .bind(media_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM media WHERE id = $1 AND owner_id = $2")
             .bind(media_id)
             .bind(owner_id)
-            .execute(self.db.pool())
+            .execute(&mut *tx)
             .await?;
 
-        Ok(result.rows_affected() > 0)
+        let deletion_queue = release_blobs(&mut tx, &keys).await?;
+        tx.commit().await?;
+
+        if !deletion_queue.objects.is_empty() {
+            let job = crate::jobs::deletion_sweeper::DeletionJob {
+                keys: deletion_queue.objects,
+            };
+            if let Err(err) = self.queue.enqueue_deletion_job(&job).await {
+                tracing::warn!(error = ?err, media_id = %media_id, "failed to enqueue orphaned media for deletion");
+            }
+        }
+
+        Ok(true)
     }
 
     /// Generate a presigned GET URL for any object key (e.g., avatars)
@@ -389,8 +1255,110 @@ impl MediaService {
             }
         }
 
-        let presign_config = PresigningConfig::expires_in(Duration::from_secs(expires_in_seconds))
-            .ok()?;
+        let url = self.presign(key, expires_in_seconds).await?;
+
+        // Cache with TTL = expires_in - 5 minutes safety margin
+        let cache_ttl = expires_in_seconds.saturating_sub(300);
+        if cache_ttl > 0 {
+            if let Ok(mut conn) = self.cache.client().get_multiplexed_async_connection().await {
+                let _ = conn.set_ex::<_, _, ()>(&cache_key, &url, cache_ttl).await;
+            }
+        }
+
+        Some(url)
+    }
+
+    /// Batched version of `generate_presigned_get_url` for hydrating a whole
+    /// page of results at once: one pipelined `MGET` covers every cache key
+    /// (instead of one Redis connection + `GET` per item), S3 presigns only
+    /// run for the misses (concurrently), and the fresh URLs are written back
+    /// with a single pipelined batch of `SET EX` commands. Returns a map from
+    /// each input key to its presigned URL; keys that fail to presign are
+    /// simply absent from the map.
+    pub async fn generate_presigned_get_urls(
+        &self,
+        keys: &[&str],
+        expires_in_seconds: u64,
+    ) -> HashMap<String, String> {
+        let mut urls = HashMap::new();
+        if keys.is_empty() {
+            return urls;
+        }
+
+        let unique_keys: Vec<&str> = keys
+            .iter()
+            .copied()
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await.ok();
+
+        let cache_keys: Vec<String> = unique_keys
+            .iter()
+            .map(|key| format!("presigned:{}", key))
+            .collect();
+        let cached: Vec<Option<String>> = match conn.as_mut() {
+            Some(conn) => conn
+                .mget(&cache_keys)
+                .await
+                .unwrap_or_else(|_| vec![None; unique_keys.len()]),
+            None => vec![None; unique_keys.len()],
+        };
+
+        let mut misses = Vec::new();
+        for (key, cached) in unique_keys.iter().zip(cached) {
+            match cached {
+                Some(url) => {
+                    urls.insert((*key).to_string(), url);
+                }
+                None => misses.push(*key),
+            }
+        }
+
+        if misses.is_empty() {
+            return urls;
+        }
+
+        let presigned: Vec<(&str, Option<String>)> = futures::future::join_all(
+            misses
+                .iter()
+                .map(|key| async move { (*key, self.presign(key, expires_in_seconds).await) }),
+        )
+        .await;
+
+        let cache_ttl = expires_in_seconds.saturating_sub(300);
+        if cache_ttl > 0 {
+            if let Some(conn) = conn.as_mut() {
+                let mut pipe = redis::pipe();
+                let mut has_writes = false;
+                for (key, url) in &presigned {
+                    if let Some(url) = url {
+                        pipe.set_ex(format!("presigned:{}", key), url, cache_ttl)
+                            .ignore();
+                        has_writes = true;
+                    }
+                }
+                if has_writes {
+                    let _: Result<(), _> = pipe.query_async(conn).await;
+                }
+            }
+        }
+
+        for (key, url) in presigned {
+            if let Some(url) = url {
+                urls.insert(key.to_string(), url);
+            }
+        }
+
+        urls
+    }
+
+    /// The uncached S3 presign + public-endpoint rewrite, shared by
+    /// `generate_presigned_get_url` and `generate_presigned_get_urls`.
+    async fn presign(&self, key: &str, expires_in_seconds: u64) -> Option<String> {
+        let presign_config =
+            PresigningConfig::expires_in(Duration::from_secs(expires_in_seconds)).ok()?;
 
         let presigned = self
             .storage
@@ -403,22 +1371,12 @@ impl MediaService {
             .ok()?;
 
         let mut url = presigned.uri().to_string();
-
-        // Rewrite to public endpoint if configured
         if let Some(ref public_endpoint) = self.s3_public_endpoint {
             if let Ok(rewritten) = rewrite_presigned_url(&url, public_endpoint) {
                 url = rewritten;
             }
         }
 
-        // Cache with TTL = expires_in - 5 minutes safety margin
-        let cache_ttl = expires_in_seconds.saturating_sub(300);
-        if cache_ttl > 0 {
-            if let Ok(mut conn) = self.cache.client().get_multiplexed_async_connection().await {
-                let _ = conn.set_ex::<_, _, ()>(&cache_key, &url, cache_ttl).await;
-            }
-        }
-
         Some(url)
     }
 
@@ -433,43 +1391,31 @@ impl MediaService {
 
     /// Populate owner_avatar_url for posts from owner_avatar_key
     pub async fn populate_post_avatar_urls(&self, posts: &mut [crate::domain::post::Post]) {
-        let futures: Vec<_> = posts
+        let keys: Vec<&str> = posts
             .iter()
-            .enumerate()
-            .filter(|(_, p)| p.owner_avatar_key.is_some())
-            .map(|(i, p)| {
-                let key = p.owner_avatar_key.as_deref().map(|k| k.to_string());
-                async move {
-                    let url = self.generate_presigned_get_url(key.as_deref(), 14400).await;
-                    (i, url)
-                }
-            })
+            .filter_map(|p| p.owner_avatar_key.as_deref())
             .collect();
-
-        let results = futures::future::join_all(futures).await;
-        for (i, url) in results {
-            posts[i].owner_avatar_url = url;
+        let urls = self.generate_presigned_get_urls(&keys, 14400).await;
+        for post in posts.iter_mut() {
+            post.owner_avatar_url = post
+                .owner_avatar_key
+                .as_deref()
+                .and_then(|key| urls.get(key).cloned());
         }
     }
 
     /// Populate user_avatar_url for stories from user_avatar_key
     pub async fn populate_story_avatar_urls(&self, stories: &mut [crate::domain::story::Story]) {
-        let futures: Vec<_> = stories
+        let keys: Vec<&str> = stories
             .iter()
-            .enumerate()
-            .filter(|(_, s)| s.user_avatar_key.is_some())
-            .map(|(i, s)| {
-                let key = s.user_avatar_key.as_deref().map(|k| k.to_string());
-                async move {
-                    let url = self.generate_presigned_get_url(key.as_deref(), 14400).await;
-                    (i, url)
-                }
-            })
+            .filter_map(|s| s.user_avatar_key.as_deref())
             .collect();
-
-        let results = futures::future::join_all(futures).await;
-        for (i, url) in results {
-            stories[i].user_avatar_url = url;
+        let urls = self.generate_presigned_get_urls(&keys, 14400).await;
+        for story in stories.iter_mut() {
+            story.user_avatar_url = story
+                .user_avatar_key
+                .as_deref()
+                .and_then(|key| urls.get(key).cloned());
         }
     }
 
@@ -478,45 +1424,156 @@ impl MediaService {
         &self,
         views: &mut [crate::domain::story::StoryView],
     ) {
-        let futures: Vec<_> = views
+        let keys: Vec<&str> = views
             .iter()
-            .enumerate()
-            .filter(|(_, v)| v.viewer_avatar_key.is_some())
-            .map(|(i, v)| {
-                let key = v.viewer_avatar_key.as_deref().map(|k| k.to_string());
-                async move {
-                    let url = self.generate_presigned_get_url(key.as_deref(), 14400).await;
-                    (i, url)
-                }
-            })
+            .filter_map(|v| v.viewer_avatar_key.as_deref())
             .collect();
-
-        let results = futures::future::join_all(futures).await;
-        for (i, url) in results {
-            views[i].viewer_avatar_url = url;
+        let urls = self.generate_presigned_get_urls(&keys, 14400).await;
+        for view in views.iter_mut() {
+            view.viewer_avatar_url = view
+                .viewer_avatar_key
+                .as_deref()
+                .and_then(|key| urls.get(key).cloned());
         }
     }
 
-    /// Populate avatar URLs for a list of Users (parallelized with futures::join_all)
+    /// Populate avatar URLs for a list of Users in one batched cache round trip
     pub async fn populate_users_avatar_urls(&self, users: &mut [crate::domain::user::User]) {
-        let futures: Vec<_> = users
+        let keys: Vec<&str> = users
             .iter()
-            .enumerate()
-            .filter(|(_, u)| u.avatar_key.is_some())
-            .map(|(i, u)| {
-                let key = u.avatar_key.as_deref().map(|k| k.to_string());
-                async move {
-                    let url = self.generate_presigned_get_url(key.as_deref(), 14400).await;
-                    (i, url)
-                }
-            })
+            .filter_map(|u| u.avatar_key.as_deref())
             .collect();
+        let urls = self.generate_presigned_get_urls(&keys, 14400).await;
+        for user in users.iter_mut() {
+            user.avatar_url = user
+                .avatar_key
+                .as_deref()
+                .and_then(|key| urls.get(key).cloned());
+        }
+    }
+}
+
+/// Loads the attachments for a batch of posts in one query and groups them
+/// by `post_id`, each group ordered by `created_at` so carousel ordering is
+/// deterministic. Used by the post read paths (`PostService`, `FeedService`,
+/// `SearchService`) to hydrate `Post::attachments` without an N+1 query per
+/// post.
+pub(crate) async fn list_attachments_for_posts(
+    pool: &sqlx::PgPool,
+    post_ids: &[Uuid],
+) -> Result<std::collections::HashMap<Uuid, Vec<Media>>> {
+    let rows = sqlx::query(
+        "SELECT id, owner_id, post_id, kind, original_key, thumb_key, medium_key, \
+                original_width, original_height, original_bytes, \
+                thumb_width, thumb_height, thumb_bytes, \
+                medium_width, medium_height, medium_bytes, duration_ms, blurhash, created_at \
+         FROM media \
+         WHERE post_id = ANY($1) \
+         ORDER BY created_at ASC",
+    )
+    .bind(post_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_post: std::collections::HashMap<Uuid, Vec<Media>> =
+        std::collections::HashMap::with_capacity(post_ids.len());
+    for row in rows {
+        let post_id: Uuid = row.get("post_id");
+        let kind: String = row.get("kind");
+        let media = Media {
+            id: row.get("id"),
+            owner_id: row.get("owner_id"),
+            post_id: Some(post_id),
+            kind: MediaKind::from_db(&kind).unwrap_or(MediaKind::Image),
+            original_key: row.get("original_key"),
+            thumb_key: row.get("thumb_key"),
+            medium_key: row.get("medium_key"),
+            original_width: row.get("original_width"),
+            original_height: row.get("original_height"),
+            original_bytes: row.get("original_bytes"),
+            thumb_width: row.get("thumb_width"),
+            thumb_height: row.get("thumb_height"),
+            thumb_bytes: row.get("thumb_bytes"),
+            medium_width: row.get("medium_width"),
+            medium_height: row.get("medium_height"),
+            medium_bytes: row.get("medium_bytes"),
+            duration_ms: row.get("duration_ms"),
+            thumb_blurhash: row.get("blurhash"),
+            created_at: row.get("created_at"),
+            original_url: None,
+            thumb_url: None,
+            medium_url: None,
+        };
+        by_post.entry(post_id).or_default().push(media);
+    }
 
-        let results = futures::future::join_all(futures).await;
-        for (i, url) in results {
-            users[i].avatar_url = url;
+    Ok(by_post)
+}
+
+/// Decrements the `media_blobs` refcount for each key a just-deleted `media`
+/// row referenced, and returns only the ones whose refcount hit zero -- i.e.
+/// no other post's media still shares that blob -- for the sweeper to
+/// actually remove from S3. Must run on the same transaction as the delete
+/// so the decrement sees the post-delete state and can't race another
+/// `acquire_blob` upsert onto the same hash.
+pub(crate) async fn release_blobs(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    keys: &[String],
+) -> Result<DeletionQueue> {
+    let mut objects = Vec::with_capacity(keys.len());
+    for key in keys {
+        let refcount: Option<i64> = sqlx::query_scalar(
+            "UPDATE media_blobs SET refcount = refcount - 1 WHERE key = $1 RETURNING refcount",
+        )
+        .bind(key)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        match refcount {
+            Some(refcount) if refcount <= 0 => {
+                sqlx::query("DELETE FROM media_blobs WHERE key = $1 AND refcount <= 0")
+                    .bind(key)
+                    .execute(&mut **tx)
+                    .await?;
+                objects.push(key.clone());
+            }
+            _ => {}
         }
     }
+
+    Ok(DeletionQueue { objects })
+}
+
+/// Decodes `data` as an image, applies `spec`'s operations in order, and
+/// encodes the result in `spec.format`. Pure and storage-agnostic so both
+/// `generate_variant` (persists the result) and `render_variant_bytes`
+/// (streams it straight back, no persistence) can share it.
+fn render_ops(data: &[u8], spec: &ProcessSpec) -> Result<(Vec<u8>, &'static str)> {
+    let mut image = image::load_from_memory(data)
+        .map_err(|err| anyhow!("failed to decode original for variant: {}", err))?;
+
+    for op in &spec.ops {
+        image = match *op {
+            VariantOp::Resize { width, height } => {
+                image.resize_exact(width, height, image::imageops::FilterType::Lanczos3)
+            }
+            VariantOp::Crop { x, y, width, height } => image.crop_imm(x, y, width, height),
+            VariantOp::Thumbnail { width, height } => image.thumbnail(width, height),
+        };
+    }
+
+    let mut buf = Cursor::new(Vec::new());
+    image
+        .write_to(&mut buf, spec.format.image_format())
+        .map_err(|err| anyhow!("failed to encode media variant: {}", err))?;
+
+    let content_type = match spec.format {
+        VariantFormat::Jpeg => "image/jpeg",
+        VariantFormat::Png => "image/png",
+        VariantFormat::Webp => "image/webp",
+    };
+
+    Ok((buf.into_inner(), content_type))
 }
 
 fn extension_from_content_type(content_type: &str) -> Result<&'static str> {
@@ -524,10 +1581,71 @@ fn extension_from_content_type(content_type: &str) -> Result<&'static str> {
         "image/jpeg" => Ok("jpg"),
         "image/png" => Ok("png"),
         "image/webp" => Ok("webp"),
+        "image/gif" => Ok("gif"),
+        "video/mp4" => Ok("mp4"),
+        "video/webm" => Ok("webm"),
         _ => Err(anyhow!("unsupported content type")),
     }
 }
 
+/// Image formats `complete_upload` can detect from magic bytes. Distinct
+/// from `VariantFormat` (the output formats the variant pipeline can
+/// *encode to*) -- this covers every image format a client may upload,
+/// including GIF, which the variant pipeline never produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageKind {
+    Jpeg,
+    Png,
+    Gif,
+    Webp,
+}
+
+impl ImageKind {
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type {
+            "image/jpeg" => Some(Self::Jpeg),
+            "image/png" => Some(Self::Png),
+            "image/gif" => Some(Self::Gif),
+            "image/webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    /// Identifies the real image format from its leading bytes, ignoring
+    /// whatever content type the client declared at upload time.
+    fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if data.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(Self::Png)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else {
+            None
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Gif => "gif",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+/// Swaps (or appends) the file extension on an object key, used to correct
+/// `original_key` once `complete_upload` has sniffed the real format.
+fn replace_extension(key: &str, new_ext: &str) -> String {
+    match key.rsplit_once('.') {
+        Some((stem, _)) => format!("{}.{}", stem, new_ext),
+        None => format!("{}.{}", key, new_ext),
+    }
+}
+
 fn rewrite_presigned_url(original: &str, public_endpoint: &str) -> Result<String> {
     let mut original_url = Url::parse(original)?;
     let public_url = if public_endpoint.contains("://") {
@@ -1,15 +1,39 @@
+pub mod admin;
 pub mod auth;
+pub mod blocklist;
+pub mod bots;
+pub mod content_filter;
+pub mod device_auth;
+pub mod device_list;
+pub mod email_verification;
 pub mod engagement;
+pub mod external_identity;
+pub mod federation;
 pub mod feed;
 pub mod fingerprint;
 pub mod invites;
 pub mod media;
 pub mod moderation;
 pub mod notifications;
+pub mod oauth;
+pub mod oauth_server;
+pub mod opaque;
+pub mod password_reset;
 pub mod posts;
+pub mod push;
 pub mod rate_limiter;
 pub mod search;
+pub mod search_store;
+pub mod shortid;
+pub mod signature;
+pub mod siwe;
 pub mod social;
+pub mod social_federation;
+pub mod streaming;
+pub mod syndication;
+pub mod totp;
 pub mod trust;
 pub mod users;
+pub mod wallet;
+pub mod webauthn;
 
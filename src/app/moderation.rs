@@ -3,7 +3,11 @@ use sqlx::Row;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::moderation::{ModerationAction, UserFlag};
+use crate::domain::moderation::{
+    EffectiveBanStatus, ModerationAction, ModerationStats, PendingFlagGroup, UserBan, UserFlag,
+    UserRole,
+};
+use crate::domain::user::ProfileHistoryEntry;
 use crate::infra::db::Db;
 
 #[derive(Clone)]
@@ -55,6 +59,8 @@ impl ModerationService {
         })
     }
 
+    /// Soft-delete (hide) a post rather than destroying it, so the takedown
+    /// can be appealed and reversed via `restore_post`.
     pub async fn takedown_post(
         &self,
         actor_id: Uuid,
@@ -62,10 +68,14 @@ impl ModerationService {
         reason: Option<String>,
     ) -> Result<bool> {
         let mut tx = self.db.pool().begin().await?;
-        let result = sqlx::query("DELETE FROM posts WHERE id = $1")
-            .bind(post_id)
-            .execute(&mut *tx)
-            .await?;
+        let result = sqlx::query(
+            "UPDATE posts SET removed_at = now(), removed_by = $1 \
+             WHERE id = $2 AND removed_at IS NULL",
+        )
+        .bind(actor_id)
+        .bind(post_id)
+        .execute(&mut *tx)
+        .await?;
 
         if result.rows_affected() == 0 {
             tx.rollback().await?;
@@ -86,6 +96,60 @@ impl ModerationService {
         Ok(true)
     }
 
+    /// Gate for `delete_post`'s moderator-override path: unlike
+    /// `takedown_post`, which soft-removes a post and leaves it recoverable
+    /// via `restore_post`, this authorizes an actual hard delete of someone
+    /// else's post, so it's checked and logged up front rather than folded
+    /// into the delete itself. Errors (insufficient role) should be treated
+    /// by the caller the same as "not found", so a non-moderator can't tell
+    /// the difference between a missing post and one they're not allowed to
+    /// force-delete.
+    pub async fn authorize_post_force_delete(&self, actor_id: Uuid, post_id: Uuid) -> Result<()> {
+        self.require_role(actor_id, UserRole::Moderator).await?;
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'post', $2, 'deleted by moderator')",
+        )
+        .bind(actor_id)
+        .bind(post_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Reverse a post takedown and log the reversal.
+    pub async fn restore_post(&self, actor_id: Uuid, post_id: Uuid) -> Result<bool> {
+        let mut tx = self.db.pool().begin().await?;
+        let result = sqlx::query(
+            "UPDATE posts SET removed_at = NULL, removed_by = NULL \
+             WHERE id = $1 AND removed_at IS NOT NULL",
+        )
+        .bind(post_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'post_restore', $2, 'reversed takedown')",
+        )
+        .bind(actor_id)
+        .bind(post_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Soft-delete (hide) a comment rather than destroying it, so the
+    /// takedown can be appealed and reversed via `restore_comment`.
     pub async fn takedown_comment(
         &self,
         actor_id: Uuid,
@@ -93,10 +157,14 @@ impl ModerationService {
         reason: Option<String>,
     ) -> Result<bool> {
         let mut tx = self.db.pool().begin().await?;
-        let result = sqlx::query("DELETE FROM comments WHERE id = $1")
-            .bind(comment_id)
-            .execute(&mut *tx)
-            .await?;
+        let result = sqlx::query(
+            "UPDATE comments SET removed_at = now(), removed_by = $1 \
+             WHERE id = $2 AND removed_at IS NULL",
+        )
+        .bind(actor_id)
+        .bind(comment_id)
+        .execute(&mut *tx)
+        .await?;
 
         if result.rows_affected() == 0 {
             tx.rollback().await?;
@@ -117,6 +185,398 @@ impl ModerationService {
         Ok(true)
     }
 
+    /// Suspends a user account: their posts, comments, and profile drop out
+    /// of search and feeds (every read path there filters on
+    /// `u.suspended_at IS NULL`, mirroring the existing `deleted_at`
+    /// convention) without destroying any of their data, so `unsuspend_user`
+    /// can fully reverse it.
+    pub async fn suspend_user(
+        &self,
+        actor_id: Uuid,
+        target_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<bool> {
+        let mut tx = self.db.pool().begin().await?;
+        let result = sqlx::query(
+            "UPDATE users SET suspended_at = now() WHERE id = $1 AND suspended_at IS NULL",
+        )
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'user_suspend', $2, $3)",
+        )
+        .bind(actor_id)
+        .bind(target_id)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Reverse a user suspension and log the reversal.
+    pub async fn unsuspend_user(&self, actor_id: Uuid, target_id: Uuid) -> Result<bool> {
+        let mut tx = self.db.pool().begin().await?;
+        let result = sqlx::query(
+            "UPDATE users SET suspended_at = NULL WHERE id = $1 AND suspended_at IS NOT NULL",
+        )
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'user_unsuspend', $2, 'reversed suspension')",
+        )
+        .bind(actor_id)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// `actor_id`'s role in the moderator hierarchy. `pub` (rather than
+    /// private like the rest of this role-checking machinery) so the
+    /// `ModeratorUser` extractor can resolve a caller's role before a
+    /// handler body ever runs.
+    pub async fn role_of(&self, actor_id: Uuid) -> Result<UserRole> {
+        let role: String = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+            .bind(actor_id)
+            .fetch_one(self.db.pool())
+            .await?;
+        Ok(UserRole::from_str(&role))
+    }
+
+    /// Moderators can ban/suspend; only admins can issue a global ban or
+    /// manage the moderator list. Returns an error describing the missing
+    /// role (mirroring `SocialService::follow`'s "follower limit reached"
+    /// convention) rather than a bool, since this is a distinct failure
+    /// mode from "target not found".
+    async fn require_role(&self, actor_id: Uuid, minimum: UserRole) -> Result<()> {
+        let actual = self.role_of(actor_id).await?;
+        let sufficient = match minimum {
+            UserRole::User => true,
+            UserRole::Moderator => matches!(actual, UserRole::Moderator | UserRole::Admin),
+            UserRole::Admin => actual == UserRole::Admin,
+        };
+        if sufficient {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "insufficient role: requires {}",
+                minimum.as_str()
+            ))
+        }
+    }
+
+    /// Promote or demote a user's role. Admin-only: only admins may add or
+    /// remove moderators.
+    pub async fn set_role(&self, actor_id: Uuid, target_id: Uuid, role: UserRole) -> Result<bool> {
+        self.require_role(actor_id, UserRole::Admin).await?;
+
+        let mut tx = self.db.pool().begin().await?;
+        let result = sqlx::query("UPDATE users SET role = $1 WHERE id = $2")
+            .bind(role.as_str())
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'role_change', $2, $3)",
+        )
+        .bind(actor_id)
+        .bind(target_id)
+        .bind(format!("role set to {}", role.as_str()))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Ban `target_id`, globally (`scope: None`) or for one capability
+    /// (`scope: Some("posting")`), optionally expiring at `expires_at`.
+    /// Moderators may issue scoped bans; only admins may issue a global
+    /// ban.
+    pub async fn ban_user(
+        &self,
+        actor_id: Uuid,
+        target_id: Uuid,
+        reason: Option<String>,
+        scope: Option<String>,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<UserBan> {
+        self.require_role(
+            actor_id,
+            if scope.is_none() { UserRole::Admin } else { UserRole::Moderator },
+        )
+        .await?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let row = sqlx::query(
+            "INSERT INTO bans (target_id, banned_by, reason, scope, expires_at) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id, target_id, banned_by, reason, scope, expires_at, created_at",
+        )
+        .bind(target_id)
+        .bind(actor_id)
+        .bind(&reason)
+        .bind(&scope)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'user_ban', $2, $3)",
+        )
+        .bind(actor_id)
+        .bind(target_id)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(UserBan {
+            id: row.get("id"),
+            target_id: row.get("target_id"),
+            banned_by: row.get("banned_by"),
+            reason: row.get("reason"),
+            scope: row.get("scope"),
+            expires_at: row.get("expires_at"),
+            created_at: row.get("created_at"),
+        })
+    }
+
+    /// Lift every currently-active ban against `target_id` matching
+    /// `scope` (`None` lifts bans of every scope, including the global
+    /// one). A ban is "active" the same way `effective_status` treats it:
+    /// not already lifted and not past its `expires_at`.
+    pub async fn unban_user(
+        &self,
+        actor_id: Uuid,
+        target_id: Uuid,
+        scope: Option<String>,
+    ) -> Result<bool> {
+        self.require_role(
+            actor_id,
+            if scope.is_none() { UserRole::Admin } else { UserRole::Moderator },
+        )
+        .await?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE bans SET lifted_at = now() \
+             WHERE target_id = $1 \
+               AND lifted_at IS NULL \
+               AND (expires_at IS NULL OR expires_at > now()) \
+               AND ($2::text IS NULL OR scope = $2)",
+        )
+        .bind(target_id)
+        .bind(&scope)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'user_unban', $2, 'lifted ban')",
+        )
+        .bind(actor_id)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Coalesce every active, non-expired ban against `user_id` into one
+    /// answer: a global ban wins if present, otherwise the soonest-issued
+    /// scoped ban. Expiry is checked against `now()` in the query itself,
+    /// so an expired ban is treated as inactive without any cleanup job.
+    pub async fn effective_status(&self, user_id: Uuid) -> Result<EffectiveBanStatus> {
+        let row = sqlx::query(
+            "SELECT scope, reason, expires_at FROM bans \
+             WHERE target_id = $1 \
+               AND lifted_at IS NULL \
+               AND (expires_at IS NULL OR expires_at > now()) \
+             ORDER BY scope IS NOT NULL, created_at ASC \
+             LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(match row {
+            Some(row) => EffectiveBanStatus {
+                banned: true,
+                scope: row.get("scope"),
+                reason: row.get("reason"),
+                expires_at: row.get("expires_at"),
+            },
+            None => EffectiveBanStatus {
+                banned: false,
+                scope: None,
+                reason: None,
+                expires_at: None,
+            },
+        })
+    }
+
+    /// Reverse a comment takedown and log the reversal.
+    pub async fn restore_comment(&self, actor_id: Uuid, comment_id: Uuid) -> Result<bool> {
+        let mut tx = self.db.pool().begin().await?;
+        let result = sqlx::query(
+            "UPDATE comments SET removed_at = NULL, removed_by = NULL \
+             WHERE id = $1 AND removed_at IS NOT NULL",
+        )
+        .bind(comment_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'comment_restore', $2, 'reversed takedown')",
+        )
+        .bind(actor_id)
+        .bind(comment_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Unresolved flags aggregated by target, most-reported first within each
+    /// cursor page, for the moderation review queue.
+    pub async fn list_pending_flags(
+        &self,
+        cursor: Option<(OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<PendingFlagGroup>> {
+        let rows = match cursor {
+            Some((last_reported_at, target_id)) => {
+                sqlx::query(
+                    "SELECT target_id, COUNT(*) AS report_count, \
+                            (ARRAY_AGG(reason ORDER BY created_at DESC))[1] AS latest_reason, \
+                            MIN(created_at) AS first_reported_at, \
+                            MAX(created_at) AS last_reported_at \
+                     FROM user_flags \
+                     WHERE resolved_at IS NULL \
+                     GROUP BY target_id \
+                     HAVING (MAX(created_at), target_id) < ($1, $2) \
+                     ORDER BY last_reported_at DESC, target_id DESC \
+                     LIMIT $3",
+                )
+                .bind(last_reported_at)
+                .bind(target_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT target_id, COUNT(*) AS report_count, \
+                            (ARRAY_AGG(reason ORDER BY created_at DESC))[1] AS latest_reason, \
+                            MIN(created_at) AS first_reported_at, \
+                            MAX(created_at) AS last_reported_at \
+                     FROM user_flags \
+                     WHERE resolved_at IS NULL \
+                     GROUP BY target_id \
+                     ORDER BY last_reported_at DESC, target_id DESC \
+                     LIMIT $1",
+                )
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        let groups = rows
+            .into_iter()
+            .map(|row| PendingFlagGroup {
+                target_id: row.get("target_id"),
+                report_count: row.get("report_count"),
+                latest_reason: row.get("latest_reason"),
+                first_reported_at: row.get("first_reported_at"),
+                last_reported_at: row.get("last_reported_at"),
+            })
+            .collect();
+
+        Ok(groups)
+    }
+
+    /// Resolve every pending flag against `target_id` with the moderator's
+    /// decision, and log the resolution.
+    pub async fn resolve_flag(
+        &self,
+        moderator_id: Uuid,
+        target_id: Uuid,
+        outcome: String,
+    ) -> Result<u64> {
+        let mut tx = self.db.pool().begin().await?;
+        let result = sqlx::query(
+            "UPDATE user_flags \
+             SET resolved_at = now(), resolved_by = $1, outcome = $2 \
+             WHERE target_id = $3 AND resolved_at IS NULL",
+        )
+        .bind(moderator_id)
+        .bind(&outcome)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            sqlx::query(
+                "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+                 VALUES ($1, 'flag_resolution', $2, $3)",
+            )
+            .bind(moderator_id)
+            .bind(target_id)
+            .bind(&outcome)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn list_audit(
         &self,
         cursor: Option<(OffsetDateTime, Uuid)>,
@@ -164,4 +624,63 @@ impl ModerationService {
 
         Ok(actions)
     }
+
+    /// Moderation-load snapshot for the admin dashboard: total flags ever
+    /// filed, total actions ever taken, and how many distinct targets are
+    /// still unresolved. Three plain counts rather than a running in-process
+    /// counter, so the numbers stay correct across restarts and multiple
+    /// server instances.
+    pub async fn stats(&self) -> Result<ModerationStats> {
+        let flags_received: i64 = sqlx::query_scalar("SELECT count(*) FROM user_flags")
+            .fetch_one(self.db.pool())
+            .await?;
+        let actions_taken: i64 = sqlx::query_scalar("SELECT count(*) FROM moderation_actions")
+            .fetch_one(self.db.pool())
+            .await?;
+        let pending_queue_depth: i64 = sqlx::query_scalar(
+            "SELECT count(DISTINCT target_id) FROM user_flags WHERE resolved_at IS NULL",
+        )
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(ModerationStats {
+            flags_received,
+            actions_taken,
+            pending_queue_depth,
+        })
+    }
+
+    /// A user's profile edit history, most recent first. Restricted to
+    /// moderator/admin callers at the handler layer -- this imports the
+    /// "keep a log of old values when records are edited" idea so edits
+    /// (and, eventually, account deletion) leave a reviewable trail.
+    pub async fn profile_history(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<ProfileHistoryEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, field, old_value, new_value, changed_at \
+             FROM profile_history \
+             WHERE user_id = $1 \
+             ORDER BY changed_at DESC \
+             LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ProfileHistoryEntry {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                field: row.get("field"),
+                old_value: row.get("old_value"),
+                new_value: row.get("new_value"),
+                changed_at: row.get("changed_at"),
+            })
+            .collect())
+    }
 }
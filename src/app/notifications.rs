@@ -1,19 +1,28 @@
 use anyhow::Result;
+use serde_json::Value;
 use sqlx::Row;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::notification::Notification;
-use crate::infra::db::Db;
+use crate::app::bots::BotService;
+use crate::app::push::PushService;
+use crate::app::streaming::StreamingService;
+use crate::domain::notification::{Notification, NotificationKind};
+use crate::infra::cache::RedisCache;
+use crate::infra::db::{Db, UnitOfWork};
+use crate::infra::queue::QueueClient;
+use crate::jobs::bot_webhook_dispatcher::BotWebhookJob;
 
 #[derive(Clone)]
 pub struct NotificationService {
     db: Db,
+    cache: RedisCache,
+    queue: QueueClient,
 }
 
 impl NotificationService {
-    pub fn new(db: Db) -> Self {
-        Self { db }
+    pub fn new(db: Db, cache: RedisCache, queue: QueueClient) -> Self {
+        Self { db, cache, queue }
     }
 
     pub async fn list(
@@ -82,4 +91,417 @@ impl NotificationService {
 
         Ok(result.rows_affected() > 0)
     }
+
+    /// Mark every unread notification belonging to `user_id` as read,
+    /// returning how many were updated.
+    pub async fn mark_all_read(&self, user_id: Uuid) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = now() WHERE user_id = $1 AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// How many of `user_id`'s notifications are still unread -- the badge
+    /// count for an inbox/bell UI.
+    pub async fn unread_count(&self, user_id: Uuid) -> Result<i64> {
+        Ok(sqlx::query_scalar(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?)
+    }
+
+    /// Delete every notification belonging to `user_id`, e.g. as part of
+    /// account deletion. Accepts an optional `UnitOfWork` so the caller can
+    /// run it in the same transaction as the rest of account deletion,
+    /// rather than as an independent statement that could succeed or fail
+    /// on its own.
+    pub async fn delete_all_for_user(&self, tx: Option<&mut UnitOfWork>, user_id: Uuid) -> Result<()> {
+        let query = sqlx::query("DELETE FROM notifications WHERE user_id = $1").bind(user_id);
+
+        match tx {
+            Some(uow) => query.execute(uow.conn()).await?,
+            None => query.execute(self.db.pool()).await?,
+        };
+
+        Ok(())
+    }
+
+    /// Persists a notification row, publishes it to its recipient's
+    /// streaming channel (see `StreamingService::publish_notification`) so
+    /// `GET /v1/notifications/stream` subscribers see it without polling,
+    /// and enqueues a Web Push delivery to each of their registered devices
+    /// (see `PushService::notify`). Both the publish and the push enqueue are
+    /// best-effort -- a Redis or queue hiccup is logged, not propagated,
+    /// since the row itself is already durably committed.
+    async fn create(&self, user_id: Uuid, notification_type: &str, payload: Value) -> Result<()> {
+        let row = sqlx::query(
+            "INSERT INTO notifications (user_id, notification_type, payload) \
+             VALUES ($1, $2, $3) \
+             RETURNING id, user_id, notification_type, payload, read_at, created_at",
+        )
+        .bind(user_id)
+        .bind(notification_type)
+        .bind(payload)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let notification = Notification {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            notification_type: row.get("notification_type"),
+            payload: row.get("payload"),
+            read_at: row.get("read_at"),
+            created_at: row.get("created_at"),
+        };
+
+        let streaming = StreamingService::new(self.db.clone(), self.cache.clone());
+        if let Err(err) = streaming.publish_notification(&notification).await {
+            tracing::warn!(error = ?err, notification_id = %notification.id, "failed to publish notification");
+        }
+
+        let (title, body) = push_copy(notification_type);
+        let push = PushService::new(self.db.clone(), self.queue.clone());
+        if let Err(err) = push
+            .notify(user_id, notification.id, notification_type, title, body)
+            .await
+        {
+            tracing::warn!(error = ?err, notification_id = %notification.id, "failed to enqueue push delivery");
+        }
+
+        if matches!(notification_type, "mention" | "follow") {
+            if let Err(err) = self.dispatch_bot_webhooks(&notification).await {
+                tracing::warn!(error = ?err, notification_id = %notification.id, "failed to dispatch bot webhooks");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enqueues a signed webhook delivery (see `jobs::bot_webhook_dispatcher`)
+    /// for every bot `notification.user_id` owns with an `interactions_url`
+    /// on file -- the bot-account equivalent of the Web Push enqueue above,
+    /// for integrations that want to react to being mentioned or followed
+    /// without polling `GET /v1/notifications`.
+    async fn dispatch_bot_webhooks(&self, notification: &Notification) -> Result<()> {
+        let bots = BotService::new(self.db.clone())
+            .bots_with_webhook(notification.user_id)
+            .await?;
+
+        for bot in bots {
+            let Some(interactions_url) = bot.interactions_url else {
+                continue;
+            };
+            let job = BotWebhookJob {
+                bot_id: bot.id,
+                interactions_url,
+                event: serde_json::json!({
+                    "notification_type": notification.notification_type,
+                    "payload": notification.payload,
+                }),
+            };
+            self.queue.enqueue_bot_webhook_job(&job).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Notifications for `user_id` created after `after_id`, oldest first --
+    /// the backfill query `GET /v1/notifications/stream` runs against a
+    /// `Last-Event-ID` before switching over to the live Redis subscription,
+    /// so a reconnecting client doesn't miss anything published while it was
+    /// offline.
+    pub async fn list_since(&self, user_id: Uuid, after_id: Uuid) -> Result<Vec<Notification>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, notification_type, payload, read_at, created_at \
+             FROM notifications \
+             WHERE user_id = $1 \
+               AND created_at > (SELECT created_at FROM notifications WHERE id = $2) \
+             ORDER BY created_at ASC, id ASC",
+        )
+        .bind(user_id)
+        .bind(after_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut notifications = Vec::with_capacity(rows.len());
+        for row in rows {
+            notifications.push(Notification {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                notification_type: row.get("notification_type"),
+                payload: row.get("payload"),
+                read_at: row.get("read_at"),
+                created_at: row.get("created_at"),
+            });
+        }
+
+        Ok(notifications)
+    }
+
+    /// Notify `followee_id` that `follower_id` has followed them. A no-op on
+    /// a self-follow (which should never reach here but is cheap to guard),
+    /// or when `followee_id` has muted `follower_id` -- a blocked follower
+    /// can never reach this point, since `SocialService::follow` already
+    /// refuses the INSERT.
+    pub async fn notify_follow(&self, followee_id: Uuid, follower_id: Uuid) -> Result<()> {
+        if followee_id == follower_id || self.is_muted(followee_id, follower_id).await? {
+            return Ok(());
+        }
+        self.create(
+            followee_id,
+            "follow",
+            serde_json::json!({ "actor_id": follower_id }),
+        )
+        .await
+    }
+
+    /// Notify `follower_id` that `followee_id` accepted their pending follow
+    /// request. A no-op on a self-follow, or when `follower_id` has muted
+    /// `followee_id`.
+    pub async fn notify_follow_accepted(&self, follower_id: Uuid, followee_id: Uuid) -> Result<()> {
+        if follower_id == followee_id || self.is_muted(follower_id, followee_id).await? {
+            return Ok(());
+        }
+        self.create(
+            follower_id,
+            "follow_accepted",
+            serde_json::json!({ "actor_id": followee_id }),
+        )
+        .await
+    }
+
+    /// Notify `recipient_id` (the post owner) that `actor_id` liked
+    /// `post_id`. Returns whether a notification was actually created, so
+    /// the caller can decide whether to also fan it out to real-time
+    /// clients -- `false` on a no-op: liking your own post, either side
+    /// having blocked the other, or the recipient having muted `actor_id`.
+    /// Compatibility shim over `notify_reaction` for the original
+    /// binary-like notification path.
+    pub async fn notify_like(&self, recipient_id: Uuid, actor_id: Uuid, post_id: Uuid) -> Result<bool> {
+        self.notify_reaction(recipient_id, actor_id, post_id, "like").await
+    }
+
+    /// Notify `recipient_id` (the post owner) that `actor_id` reacted to
+    /// `post_id` with `reaction_type`. Returns whether a notification was
+    /// actually created, so callers can skip a secondary side-effect (e.g.
+    /// a streaming publish) on a guarded no-op.
+    pub async fn notify_reaction(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        post_id: Uuid,
+        reaction_type: &str,
+    ) -> Result<bool> {
+        if recipient_id == actor_id {
+            return Ok(false);
+        }
+        if self.is_blocked(recipient_id, actor_id).await? || self.is_muted(recipient_id, actor_id).await? {
+            return Ok(false);
+        }
+        self.create(
+            recipient_id,
+            "post_liked",
+            serde_json::json!({ "actor_id": actor_id, "post_id": post_id, "reaction_type": reaction_type }),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Notify `recipient_id` (the post owner) that `actor_id` commented on
+    /// `post_id` with `comment_id`. Returns whether a notification was
+    /// actually created, mirroring `notify_like`'s guards and `bool`
+    /// result exactly, since a comment is the same kind of direct reaction
+    /// to one post.
+    pub async fn notify_comment(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        post_id: Uuid,
+        comment_id: Uuid,
+    ) -> Result<bool> {
+        if recipient_id == actor_id {
+            return Ok(false);
+        }
+        if self.is_blocked(recipient_id, actor_id).await? || self.is_muted(recipient_id, actor_id).await? {
+            return Ok(false);
+        }
+        self.create(
+            recipient_id,
+            "post_commented",
+            serde_json::json!({ "actor_id": actor_id, "post_id": post_id, "comment_id": comment_id }),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Notify `recipient_id` (the owner of the post being replied to) that
+    /// `actor_id` replied to it with `post_id`. A no-op when replying to your
+    /// own post.
+    pub async fn notify_reply(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        post_id: Uuid,
+    ) -> Result<()> {
+        if recipient_id == actor_id {
+            return Ok(());
+        }
+        self.create(
+            recipient_id,
+            NotificationKind::Reply.as_str(),
+            serde_json::json!({ "actor_id": actor_id, "post_id": post_id }),
+        )
+        .await
+    }
+
+    /// Notify `recipient_id` (the owner of the post being reposted) that
+    /// `actor_id` reposted it as `post_id`. A no-op when reposting your own
+    /// post.
+    pub async fn notify_repost(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        post_id: Uuid,
+    ) -> Result<()> {
+        if recipient_id == actor_id {
+            return Ok(());
+        }
+        self.create(
+            recipient_id,
+            NotificationKind::Repost.as_str(),
+            serde_json::json!({ "actor_id": actor_id, "post_id": post_id }),
+        )
+        .await
+    }
+
+    /// Notify `recipient_id` that `actor_id` mentioned them in `post_id`. A
+    /// no-op when mentioning yourself. No separate block check here --
+    /// `PostService::create_post`'s mention resolution already excludes any
+    /// handle with a block in either direction, so a blocked recipient never
+    /// makes it into `Post::mentions` in the first place.
+    pub async fn notify_mention(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        post_id: Uuid,
+    ) -> Result<()> {
+        if recipient_id == actor_id {
+            return Ok(());
+        }
+        self.create(
+            recipient_id,
+            NotificationKind::Mention.as_str(),
+            serde_json::json!({ "actor_id": actor_id, "post_id": post_id }),
+        )
+        .await
+    }
+
+    /// Notify `recipient_id` (the story owner) that `actor_id` reacted to
+    /// `story_id`, but only when `is_new_reaction` is true -- the caller
+    /// passes through `StoryService::add_reaction`'s `xmax = 0` signal, so
+    /// swapping an existing reaction's emoji doesn't notify a second time. A
+    /// no-op when reacting to your own story, when either side has blocked
+    /// the other, or when the recipient has muted `actor_id`.
+    pub async fn notify_story_reaction(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        story_id: Uuid,
+        is_new_reaction: bool,
+    ) -> Result<()> {
+        if recipient_id == actor_id || !is_new_reaction {
+            return Ok(());
+        }
+        if self.is_blocked(recipient_id, actor_id).await? || self.is_muted(recipient_id, actor_id).await? {
+            return Ok(());
+        }
+        self.create(
+            recipient_id,
+            "story_reaction",
+            serde_json::json!({ "actor_id": actor_id, "story_id": story_id }),
+        )
+        .await
+    }
+
+    /// Notify `recipient_id` (the story owner) that `actor_id` viewed
+    /// `story_id` for the first time. The caller passes through
+    /// `StoryService::mark_seen`'s `rows_affected > 0` signal, so repeat
+    /// views of the same story don't notify again. A no-op when viewing
+    /// your own story, when either side has blocked the other, or when the
+    /// recipient has muted `actor_id`.
+    pub async fn notify_story_first_view(
+        &self,
+        recipient_id: Uuid,
+        actor_id: Uuid,
+        story_id: Uuid,
+    ) -> Result<()> {
+        if recipient_id == actor_id {
+            return Ok(());
+        }
+        if self.is_blocked(recipient_id, actor_id).await? || self.is_muted(recipient_id, actor_id).await? {
+            return Ok(());
+        }
+        self.create(
+            recipient_id,
+            "story_first_view",
+            serde_json::json!({ "actor_id": actor_id, "story_id": story_id }),
+        )
+        .await
+    }
+
+    async fn is_blocked(&self, a: Uuid, b: Uuid) -> Result<bool> {
+        let blocked: bool = sqlx::query_scalar(
+            "SELECT EXISTS ( \
+                SELECT 1 FROM blocks \
+                WHERE (blocker_id = $1 AND blocked_id = $2) \
+                   OR (blocker_id = $2 AND blocked_id = $1) \
+             )",
+        )
+        .bind(a)
+        .bind(b)
+        .fetch_one(self.db.pool())
+        .await?;
+        Ok(blocked)
+    }
+
+    /// Unlike `is_blocked`, this is one-directional: only whether
+    /// `recipient_id` has muted `actor_id`, not the reverse.
+    async fn is_muted(&self, recipient_id: Uuid, actor_id: Uuid) -> Result<bool> {
+        let muted: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = $2)",
+        )
+        .bind(recipient_id)
+        .bind(actor_id)
+        .fetch_one(self.db.pool())
+        .await?;
+        Ok(muted)
+    }
+}
+
+/// Generic title/body copy for a push notification, keyed by
+/// `notification_type`. Deliberately actor-agnostic -- resolving a display
+/// name would mean threading a `UserService` lookup through every
+/// `notify_*` call just for push copy, so the push payload points the user
+/// at the app rather than naming who triggered it; `GET /v1/notifications`
+/// already returns the full `payload` (including `actor_id`) for the
+/// in-app view.
+fn push_copy(notification_type: &str) -> (&'static str, &'static str) {
+    match notification_type {
+        "follow" => ("New follower", "Someone started following you."),
+        "follow_accepted" => ("Follow request accepted", "Your follow request was accepted."),
+        "post_liked" => ("New reaction", "Someone reacted to your post."),
+        "post_commented" => ("New comment", "Someone commented on your post."),
+        "reply" => ("New reply", "Someone replied to your post."),
+        "repost" => ("New repost", "Someone reposted your post."),
+        "mention" => ("New mention", "Someone mentioned you in a post."),
+        "story_reaction" => ("Story reaction", "Someone reacted to your story."),
+        "story_first_view" => ("Story view", "Someone viewed your story."),
+        _ => ("New notification", "You have a new notification."),
+    }
 }
@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::app::auth::{hash_token, Scope};
+use crate::infra::db::Db;
+
+/// How long a minted authorization code is redeemable for. Short on purpose --
+/// it only has to survive the redirect back from the client's authorize page
+/// to its own token-exchange request, not an interactive login window like
+/// `PasswordResetService`'s tokens.
+const AUTHORIZATION_CODE_TTL_SECONDS: i64 = 120;
+
+/// A registered OAuth2 client (third-party or first-party). Distinct from
+/// `app::oauth::OAuthService`, which is the other direction: us consuming
+/// an *external* provider (Google, GitHub) for social login. This is us
+/// acting as the authorization server for clients that want to obtain a
+/// scoped `TokenPair` without ever seeing a user's password.
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Vec<Scope>,
+}
+
+#[derive(Clone)]
+pub struct OAuthServerService {
+    db: Db,
+}
+
+impl OAuthServerService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Register a new client. Returns it alongside the plaintext
+    /// `client_secret`, shown to the caller exactly once -- only
+    /// `hash_token(client_secret)` is persisted, the same discipline as a
+    /// refresh token.
+    pub async fn register_client(
+        &self,
+        name: &str,
+        redirect_uris: Vec<String>,
+        allowed_scopes: Vec<Scope>,
+    ) -> Result<(OAuthClient, String)> {
+        if redirect_uris.is_empty() {
+            return Err(anyhow!("at least one redirect_uri is required"));
+        }
+        if allowed_scopes.is_empty() {
+            return Err(anyhow!("at least one allowed scope is required"));
+        }
+
+        let client_id = Uuid::new_v4().to_string();
+        let mut secret_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret_bytes);
+        let client_secret = URL_SAFE_NO_PAD.encode(secret_bytes);
+        let secret_hash = hash_token(&client_secret);
+        let allowed_scope_strs: Vec<&str> = allowed_scopes.iter().map(|s| s.as_str()).collect();
+
+        let row = sqlx::query(
+            "INSERT INTO oauth_clients \
+                (client_id, client_secret_hash, name, redirect_uris, allowed_scopes) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id, client_id, name, redirect_uris, allowed_scopes",
+        )
+        .bind(&client_id)
+        .bind(&secret_hash)
+        .bind(name)
+        .bind(&redirect_uris)
+        .bind(&allowed_scope_strs)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok((row_to_client(&row), client_secret))
+    }
+
+    /// First leg of the authorization-code grant: mint a short-lived,
+    /// single-use code bound to `user_id`, the negotiated `scopes`, the
+    /// `redirect_uri` the client will redeem it at, and its PKCE
+    /// `code_challenge` (S256, mirroring `app::oauth`'s use of the same
+    /// scheme against external providers). The code is returned once and
+    /// stored only as `hash_token(code)`.
+    pub async fn issue_authorization_code(
+        &self,
+        client_id: &str,
+        user_id: Uuid,
+        redirect_uri: &str,
+        scopes: &[Scope],
+        code_challenge: &str,
+    ) -> Result<String> {
+        let client = self
+            .find_client(client_id)
+            .await?
+            .ok_or_else(|| anyhow!("unknown oauth client"))?;
+
+        if !client.redirect_uris.iter().any(|uri| uri == redirect_uri) {
+            return Err(anyhow!("redirect_uri does not match a registered uri"));
+        }
+        for scope in scopes {
+            if !client.allowed_scopes.contains(scope) {
+                return Err(anyhow!(
+                    "client is not permitted to request scope: {}",
+                    scope.as_str()
+                ));
+            }
+        }
+
+        let mut code_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut code_bytes);
+        let code = URL_SAFE_NO_PAD.encode(code_bytes);
+        let code_hash = hash_token(&code);
+        let expires_at =
+            OffsetDateTime::now_utc() + Duration::seconds(AUTHORIZATION_CODE_TTL_SECONDS);
+        let scope_strs: Vec<&str> = scopes.iter().map(|s| s.as_str()).collect();
+
+        sqlx::query(
+            "INSERT INTO oauth_authorization_codes \
+                (code_hash, client_id, user_id, redirect_uri, scopes, code_challenge, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(&code_hash)
+        .bind(client.id)
+        .bind(user_id)
+        .bind(redirect_uri)
+        .bind(&scope_strs)
+        .bind(code_challenge)
+        .bind(expires_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(code)
+    }
+
+    /// Second leg: authenticate the client, consume `code`, and verify
+    /// `code_verifier` against the S256 challenge it was issued with.
+    /// Returns the user and scope set the caller should mint a `TokenPair`
+    /// for via `AuthService::issue_token_pair` -- this service only owns
+    /// client/code bookkeeping, not PASETO issuance, the same split as
+    /// `app::oauth::OAuthService::complete_login` handing off to
+    /// `AuthService` rather than minting tokens itself.
+    pub async fn exchange_code(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+    ) -> Result<(Uuid, Vec<Scope>)> {
+        let client_row_id: Uuid = sqlx::query(
+            "SELECT id FROM oauth_clients WHERE client_id = $1 AND client_secret_hash = $2",
+        )
+        .bind(client_id)
+        .bind(hash_token(client_secret))
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| anyhow!("invalid client credentials"))?
+        .get("id");
+
+        let code_hash = hash_token(code);
+        let mut tx = self.db.pool().begin().await?;
+
+        let row = sqlx::query(
+            "SELECT client_id, user_id, redirect_uri, scopes, code_challenge, \
+                    consumed_at, expires_at \
+             FROM oauth_authorization_codes WHERE code_hash = $1 FOR UPDATE",
+        )
+        .bind(&code_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow!("invalid authorization code"))?;
+
+        let consumed_at: Option<OffsetDateTime> = row.get("consumed_at");
+        if consumed_at.is_some() {
+            return Err(anyhow!("authorization code already used"));
+        }
+
+        let expires_at: OffsetDateTime = row.get("expires_at");
+        if expires_at < OffsetDateTime::now_utc() {
+            return Err(anyhow!("authorization code has expired"));
+        }
+
+        let bound_client_id: Uuid = row.get("client_id");
+        if bound_client_id != client_row_id {
+            return Err(anyhow!("authorization code was not issued to this client"));
+        }
+
+        let bound_redirect_uri: String = row.get("redirect_uri");
+        if bound_redirect_uri != redirect_uri {
+            return Err(anyhow!(
+                "redirect_uri does not match the one used to request this code"
+            ));
+        }
+
+        let code_challenge: String = row.get("code_challenge");
+        let computed_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        if computed_challenge != code_challenge {
+            return Err(anyhow!("code_verifier does not match code_challenge"));
+        }
+
+        sqlx::query("UPDATE oauth_authorization_codes SET consumed_at = now() WHERE code_hash = $1")
+            .bind(&code_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        let user_id: Uuid = row.get("user_id");
+        let scope_strs: Vec<String> = row.get("scopes");
+        let scopes = scope_strs
+            .iter()
+            .filter_map(|raw| Scope::from_str(raw))
+            .collect();
+
+        Ok((user_id, scopes))
+    }
+
+    async fn find_client(&self, client_id: &str) -> Result<Option<OAuthClient>> {
+        let row = sqlx::query(
+            "SELECT id, client_id, name, redirect_uris, allowed_scopes \
+             FROM oauth_clients WHERE client_id = $1",
+        )
+        .bind(client_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|row| row_to_client(&row)))
+    }
+}
+
+fn row_to_client(row: &PgRow) -> OAuthClient {
+    let allowed_scope_strs: Vec<String> = row.get("allowed_scopes");
+    let allowed_scopes = allowed_scope_strs
+        .iter()
+        .filter_map(|raw| Scope::from_str(raw))
+        .collect();
+
+    OAuthClient {
+        id: row.get("id"),
+        client_id: row.get("client_id"),
+        name: row.get("name"),
+        redirect_uris: row.get("redirect_uris"),
+        allowed_scopes,
+    }
+}
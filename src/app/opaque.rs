@@ -0,0 +1,294 @@
+use anyhow::{anyhow, Result};
+use opaque_ke::{
+    CredentialFinalization, CredentialRequest, RegistrationRequest, RegistrationUpload,
+    ServerLogin, ServerLoginStartParameters, ServerRegistration, ServerSetup,
+};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use time::Duration;
+use uuid::Uuid;
+
+use crate::app::auth::{AuthService, DeviceContext, TokenPair};
+use crate::domain::opaque::{decode, encode};
+use crate::infra::cache::RedisCache;
+use crate::infra::db::Db;
+
+/// ristretto255 OPRF + tripleDH key exchange + argon2 slow hash -- the
+/// combination the `opaque-ke` README recommends as a sane default.
+pub struct DefaultCipherSuite;
+
+impl opaque_ke::CipherSuite for DefaultCipherSuite {
+    type OprfCs = opaque_ke::Ristretto255;
+    type KeGroup = opaque_ke::Ristretto255;
+    type KeyExchange = opaque_ke::key_exchange::tripledh::TripleDh;
+    type Ksf = argon2::Argon2<'static>;
+}
+
+/// How long a client has to complete the second round-trip of either
+/// handshake before the server-side state expires and must be restarted.
+const REGISTER_HANDLE_TTL_SECONDS: i64 = 600;
+const LOGIN_HANDLE_TTL_SECONDS: i64 = 120;
+
+/// State carried between `register_start` and `register_finish`, keyed by a
+/// short-lived handle in Redis so the two round-trips can land on different
+/// app instances behind a load balancer.
+#[derive(Serialize, Deserialize)]
+struct RegisterHandleState {
+    user_id: Uuid,
+    oprf_seed: String,
+    server_keypair: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LoginHandleState {
+    user_id: Uuid,
+    server_login_state: String,
+}
+
+#[derive(Clone)]
+pub struct OpaqueService {
+    db: Db,
+    cache: RedisCache,
+}
+
+impl OpaqueService {
+    pub fn new(db: Db, cache: RedisCache) -> Self {
+        Self { db, cache }
+    }
+
+    /// First registration round-trip: evaluate the client's blinded OPRF
+    /// element under a freshly generated per-user server setup, and park
+    /// that setup in Redis until the matching `register_finish` arrives.
+    pub async fn register_start(
+        &self,
+        user_id: Uuid,
+        identifier: &str,
+        registration_request_b64: &str,
+    ) -> Result<(String, String)> {
+        let registration_request_bytes = decode("registration_request", registration_request_b64)?;
+        let registration_request = RegistrationRequest::<DefaultCipherSuite>::deserialize(
+            &registration_request_bytes,
+        )
+        .map_err(|_| anyhow!("malformed registration_request"))?;
+
+        let server_setup = ServerSetup::<DefaultCipherSuite>::new(&mut OsRng);
+        let response = ServerRegistration::<DefaultCipherSuite>::start(
+            &server_setup,
+            registration_request,
+            identifier.as_bytes(),
+        )
+        .map_err(|_| anyhow!("failed to evaluate registration request"))?;
+
+        let handle = Uuid::new_v4().to_string();
+        let state = RegisterHandleState {
+            user_id,
+            oprf_seed: encode(&server_setup.oprf_seed.serialize()),
+            server_keypair: encode(&server_setup.keypair().private().serialize()),
+        };
+        self.put_handle(
+            &register_handle_key(&handle),
+            &state,
+            REGISTER_HANDLE_TTL_SECONDS,
+        )
+        .await?;
+
+        Ok((handle, encode(&response.message.serialize())))
+    }
+
+    /// Second registration round-trip: persist the client's encrypted
+    /// envelope. The server learns nothing about the password itself.
+    pub async fn register_finish(
+        &self,
+        handle: &str,
+        registration_upload_b64: &str,
+    ) -> Result<()> {
+        let state: RegisterHandleState = self
+            .take_handle(&register_handle_key(handle))
+            .await?
+            .ok_or_else(|| anyhow!("registration handle not found or expired"))?;
+
+        let registration_upload_bytes = decode("registration_upload", registration_upload_b64)?;
+        let registration_upload = RegistrationUpload::<DefaultCipherSuite>::deserialize(
+            &registration_upload_bytes,
+        )
+        .map_err(|_| anyhow!("malformed registration_upload"))?;
+
+        let password_file = ServerRegistration::<DefaultCipherSuite>::finish(registration_upload);
+
+        sqlx::query(
+            "INSERT INTO opaque_registrations (user_id, oprf_seed, server_keypair, envelope) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (user_id) DO UPDATE SET \
+                oprf_seed = EXCLUDED.oprf_seed, \
+                server_keypair = EXCLUDED.server_keypair, \
+                envelope = EXCLUDED.envelope, \
+                updated_at = now()",
+        )
+        .bind(state.user_id)
+        .bind(&state.oprf_seed)
+        .bind(&state.server_keypair)
+        .bind(encode(&password_file.serialize()))
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// First login round-trip: reconstruct the stored server setup and
+    /// envelope for `identifier`, and evaluate the client's credential
+    /// request against them.
+    ///
+    /// When `identifier` has no OPAQUE registration, this must not short
+    /// -circuit before the handshake -- that would give a nonexistent
+    /// account a distinguishable (faster, no crypto work, differently
+    /// shaped) response from a real one, defeating the whole point of
+    /// running OPAQUE instead of a plain password check. Instead it runs
+    /// `ServerLogin::start` against a freshly generated, never-persisted
+    /// `ServerSetup` with no password file; `opaque-ke`'s `password_file:
+    /// None` path synthesizes a fake-but-well-formed `CredentialResponse`,
+    /// so the wire response and cost are indistinguishable from a genuine
+    /// account, and `login_finish` simply fails mutual authentication for it
+    /// like any other wrong-password attempt. Mirrors `AuthService::login`,
+    /// which collapses "no such user" and "wrong password" into the same
+    /// `Ok(None)`.
+    pub async fn login_start(
+        &self,
+        identifier: &str,
+        credential_request_b64: &str,
+    ) -> Result<(String, String)> {
+        let row = sqlx::query(
+            "SELECT r.user_id, r.oprf_seed, r.server_keypair, r.envelope \
+             FROM opaque_registrations r \
+             JOIN users u ON u.id = r.user_id \
+             WHERE u.email = $1 OR u.handle = $1",
+        )
+        .bind(identifier)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let (user_id, server_setup, password_file) = match row {
+            Some(row) => {
+                let user_id: Uuid = row.get("user_id");
+                let oprf_seed: String = row.get("oprf_seed");
+                let server_keypair: String = row.get("server_keypair");
+                let envelope: String = row.get("envelope");
+
+                let server_setup = rebuild_server_setup(&oprf_seed, &server_keypair)?;
+                let password_file_bytes = decode("envelope", &envelope)?;
+                let password_file = ServerRegistration::<DefaultCipherSuite>::deserialize(
+                    &password_file_bytes,
+                )
+                .map_err(|_| anyhow!("corrupt stored envelope"))?;
+
+                (user_id, server_setup, Some(password_file))
+            }
+            None => (
+                Uuid::nil(),
+                ServerSetup::<DefaultCipherSuite>::new(&mut OsRng),
+                None,
+            ),
+        };
+
+        let credential_request_bytes = decode("credential_request", credential_request_b64)?;
+        let credential_request = CredentialRequest::<DefaultCipherSuite>::deserialize(
+            &credential_request_bytes,
+        )
+        .map_err(|_| anyhow!("malformed credential_request"))?;
+
+        let login_start = ServerLogin::<DefaultCipherSuite>::start(
+            &mut OsRng,
+            &server_setup,
+            password_file,
+            credential_request,
+            identifier.as_bytes(),
+            ServerLoginStartParameters::default(),
+        )
+        .map_err(|_| anyhow!("failed to start login handshake"))?;
+
+        let handle = Uuid::new_v4().to_string();
+        let state = LoginHandleState {
+            user_id,
+            server_login_state: encode(&login_start.state.serialize()),
+        };
+        self.put_handle(&login_handle_key(&handle), &state, LOGIN_HANDLE_TTL_SECONDS)
+            .await?;
+
+        Ok((handle, encode(&login_start.message.serialize())))
+    }
+
+    /// Second login round-trip: complete the mutually-authenticated key
+    /// exchange and, on success, mint the usual PASETO token pair.
+    pub async fn login_finish(
+        &self,
+        handle: &str,
+        credential_finalization_b64: &str,
+        auth_service: &AuthService,
+    ) -> Result<TokenPair> {
+        let state: LoginHandleState = self
+            .take_handle(&login_handle_key(handle))
+            .await?
+            .ok_or_else(|| anyhow!("login handshake not found or expired"))?;
+
+        let server_login_state_bytes = decode("server_login_state", &state.server_login_state)?;
+        let server_login = ServerLogin::<DefaultCipherSuite>::deserialize(&server_login_state_bytes)
+            .map_err(|_| anyhow!("corrupt login handshake state"))?;
+
+        let credential_finalization_bytes =
+            decode("credential_finalization", credential_finalization_b64)?;
+        let credential_finalization = CredentialFinalization::<DefaultCipherSuite>::deserialize(
+            &credential_finalization_bytes,
+        )
+        .map_err(|_| anyhow!("malformed credential_finalization"))?;
+
+        server_login
+            .finish(credential_finalization)
+            .map_err(|_| anyhow!("mutual authentication failed"))?;
+
+        auth_service
+            .issue_tokens_for_user(state.user_id, DeviceContext::default())
+            .await
+    }
+
+    async fn put_handle<T: Serialize>(&self, key: &str, value: &T, ttl_seconds: i64) -> Result<()> {
+        use redis::AsyncCommands;
+        let payload = serde_json::to_string(value)?;
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        conn.set_ex(key, payload, ttl_seconds as u64).await?;
+        Ok(())
+    }
+
+    async fn take_handle<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
+        use redis::AsyncCommands;
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let payload: Option<String> = conn.get(key).await?;
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        // Single-use: a captured handle can't be replayed against the same
+        // in-progress handshake.
+        let _: () = conn.del(key).await?;
+        Ok(Some(serde_json::from_str(&payload)?))
+    }
+}
+
+fn rebuild_server_setup(
+    oprf_seed_b64: &str,
+    server_keypair_b64: &str,
+) -> Result<ServerSetup<DefaultCipherSuite>> {
+    let oprf_seed_bytes = decode("oprf_seed", oprf_seed_b64)?;
+    let server_keypair_bytes = decode("server_keypair", server_keypair_b64)?;
+    ServerSetup::<DefaultCipherSuite>::deserialize(&[server_keypair_bytes, oprf_seed_bytes].concat())
+        .map_err(|_| anyhow!("corrupt stored OPAQUE server setup"))
+}
+
+fn register_handle_key(handle: &str) -> String {
+    format!("opaque:register:{}", handle)
+}
+
+fn login_handle_key(handle: &str) -> String {
+    format!("opaque:login:{}", handle)
+}
+
+#[allow(dead_code)]
+const _HANDLE_EXPIRY_NOTE: Duration = Duration::seconds(0);
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::app::auth::{hash_password, hash_token};
+use crate::app::rate_limiter::RateLimiter;
+use crate::app::trust::TrustService;
+use crate::config::rate_limits::{LimitType, TrustLevel};
+use crate::infra::cache::RedisCache;
+use crate::infra::db::Db;
+use crate::infra::mailer::Mailer;
+
+/// Issues and consumes single-use password-reset tokens. Shaped like
+/// `EmailVerificationService`, but the token is stored hashed (like a
+/// refresh token, see `app::auth::hash_token`) rather than in plaintext,
+/// since possessing it is enough to take over the account.
+#[derive(Clone)]
+pub struct PasswordResetService {
+    db: Db,
+    cache: RedisCache,
+    mailer: Arc<dyn Mailer>,
+    ttl_hours: i64,
+}
+
+impl PasswordResetService {
+    pub fn new(db: Db, cache: RedisCache, mailer: Arc<dyn Mailer>, ttl_hours: i64) -> Self {
+        Self {
+            db,
+            cache,
+            mailer,
+            ttl_hours,
+        }
+    }
+
+    /// Issue a reset token for the account owning `email` and send it, or do
+    /// nothing if no such account exists or the account has already requested
+    /// too many resets this hour. Callers must return the same response
+    /// either way -- mirrors the existing "no such user" handling in
+    /// `AuthService::login` so this endpoint can't be used to enumerate
+    /// registered emails or probe how close an account is to its limit.
+    pub async fn request_reset(&self, email: &str) -> Result<()> {
+        let user_id: Option<Uuid> = sqlx::query("SELECT id FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_optional(self.db.pool())
+            .await?
+            .map(|row| row.get("id"));
+
+        let user_id = match user_id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let trust_level = TrustService::new(self.db.clone())
+            .get_trust_score(user_id)
+            .await?
+            .map(|score| score.trust_level)
+            .unwrap_or(TrustLevel::New);
+        let info = RateLimiter::new(self.cache.clone())
+            .consume(user_id, LimitType::PasswordReset, trust_level)
+            .await?;
+        if info.limited {
+            return Ok(());
+        }
+
+        let mut token_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut token_bytes);
+        let token = URL_SAFE_NO_PAD.encode(token_bytes);
+        let token_hash = hash_token(&token);
+
+        let expires_at = time::OffsetDateTime::now_utc() + time::Duration::hours(self.ttl_hours);
+
+        sqlx::query(
+            "DELETE FROM password_reset_tokens WHERE user_id = $1 AND consumed_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO password_reset_tokens (token_hash, user_id, expires_at) \
+             VALUES ($1, $2, $3)",
+        )
+        .bind(&token_hash)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(self.db.pool())
+        .await?;
+
+        self.mailer
+            .send(
+                email,
+                "Reset your password",
+                &format!(
+                    "Reset your password by submitting this token: {}",
+                    token
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Consume a reset token, setting its owning user's password to
+    /// `new_password`. Errors if the token is unknown, already used, or
+    /// expired. Does *not* revoke existing sessions itself -- callers pair
+    /// this with `AuthService::revoke_sessions` so a stolen refresh token
+    /// can't outlive the password it replaces.
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<Uuid> {
+        let token_hash = hash_token(token);
+        let mut tx = self.db.pool().begin().await?;
+
+        let row = sqlx::query(
+            "SELECT user_id, expires_at, consumed_at FROM password_reset_tokens \
+             WHERE token_hash = $1 FOR UPDATE",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow!("invalid reset token"))?;
+
+        let consumed_at: Option<time::OffsetDateTime> = row.get("consumed_at");
+        if consumed_at.is_some() {
+            return Err(anyhow!("reset token already used"));
+        }
+
+        let expires_at: time::OffsetDateTime = row.get("expires_at");
+        if expires_at < time::OffsetDateTime::now_utc() {
+            return Err(anyhow!("reset token has expired"));
+        }
+
+        let user_id: Uuid = row.get("user_id");
+        let password_hash = hash_password(new_password)?;
+
+        sqlx::query("UPDATE password_reset_tokens SET consumed_at = now() WHERE token_hash = $1")
+            .bind(&token_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(password_hash)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+}
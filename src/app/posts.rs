@@ -3,7 +3,9 @@ use sqlx::Row;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::post::{Post, PostVisibility};
+use crate::app::media::{list_attachments_for_posts, release_blobs};
+use crate::domain::media::DeletionQueue;
+use crate::domain::post::{Post, PostMention, PostVisibility};
 use crate::infra::db::Db;
 
 #[derive(Clone)]
@@ -16,73 +18,237 @@ impl PostService {
         Self { db }
     }
 
+    /// Creates a post and claims `media_ids` as its attachments in one
+    /// statement, so a post can carry a carousel/gallery of several
+    /// attachments rather than just one. `get_post` and `list_by_user`
+    /// return the claimed set ordered by upload time via
+    /// `list_attachments_for_posts`.
     pub async fn create_post(
         &self,
         owner_id: Uuid,
-        media_id: Uuid,
+        media_ids: Vec<Uuid>,
         caption: Option<String>,
+        visibility: PostVisibility,
+        in_reply_to_id: Option<Uuid>,
+        repost_of_id: Option<Uuid>,
     ) -> Result<Post> {
-        // C3: Verify media ownership before creating post
-        let media_owner: Option<Uuid> = sqlx::query_scalar(
-            "SELECT owner_id FROM media WHERE id = $1",
-        )
-        .bind(media_id)
-        .fetch_optional(self.db.pool())
-        .await?;
-
-        match media_owner {
-            Some(owner) if owner == owner_id => {}
-            Some(_) => return Err(anyhow::anyhow!("media not found or not owned by user")),
-            None => return Err(anyhow::anyhow!("media not found or not owned by user")),
+        if repost_of_id.is_some() && caption.is_some() {
+            return Err(anyhow::anyhow!("reposts cannot carry a caption"));
         }
 
+        let mut tx = self.db.pool().begin().await?;
+
+        // Guards are pushed into the INSERT itself (mirrors `SocialService::follow`'s
+        // conditional-insert pattern) rather than checked beforehand, so the
+        // target-post checks and the (owner, repost_of_id) uniqueness check
+        // all resolve atomically against the same insert attempt.
         let row = sqlx::query(
             "WITH inserted_post AS ( \
-                INSERT INTO posts (owner_id, media_id, caption, visibility) \
-                VALUES ($1, $2, $3, $4::post_visibility) \
-                RETURNING id, owner_id, media_id, caption, visibility::text AS visibility, created_at \
+                INSERT INTO posts (owner_id, caption, visibility, in_reply_to_id, repost_of_id) \
+                SELECT $1, $2, $3::post_visibility, $4, $5 \
+                WHERE ($4::uuid IS NULL OR NOT EXISTS ( \
+                    SELECT 1 FROM posts WHERE id = $4 AND repost_of_id IS NOT NULL \
+                )) \
+                AND ($5::uuid IS NULL OR NOT EXISTS ( \
+                    SELECT 1 FROM posts \
+                    WHERE id = $5 AND (repost_of_id IS NOT NULL OR visibility <> 'public') \
+                )) \
+                ON CONFLICT (owner_id, repost_of_id) DO NOTHING \
+                RETURNING id, owner_id, caption, visibility::text AS visibility, \
+                          created_at, in_reply_to_id, repost_of_id \
              ) \
              SELECT p.*, u.handle AS owner_handle, u.display_name AS owner_display_name \
              FROM inserted_post p \
              JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL",
         )
         .bind(owner_id)
-        .bind(media_id)
-        .bind(caption)
-        .bind(PostVisibility::Public.as_db())
-        .fetch_one(self.db.pool())
+        .bind(&caption)
+        .bind(visibility.as_db())
+        .bind(in_reply_to_id)
+        .bind(repost_of_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => {
+                tx.rollback().await?;
+                return Err(anyhow::anyhow!("reply target is a repost, repost target is a repost or non-public, or this repost already exists"));
+            }
+        };
+
+        let post_id: Uuid = row.get("id");
+
+        // Claiming media by UPDATE (rather than checking ownership up front)
+        // folds the ownership check and the claim into one atomic statement:
+        // a media row only comes back in `RETURNING` if it existed, belonged
+        // to this user, and wasn't already claimed by another post.
+        let claimed_ids: Vec<Uuid> = sqlx::query_scalar(
+            "UPDATE media SET post_id = $1 WHERE owner_id = $2 AND id = ANY($3) RETURNING id",
+        )
+        .bind(post_id)
+        .bind(owner_id)
+        .bind(&media_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if claimed_ids.len() != media_ids.len() {
+            tx.rollback().await?;
+            return Err(anyhow::anyhow!("invalid media_id"));
+        }
+
+        let attachment_rows = sqlx::query(
+            "SELECT id, owner_id, post_id, kind, original_key, thumb_key, medium_key, \
+                    original_width, original_height, original_bytes, \
+                    thumb_width, thumb_height, thumb_bytes, \
+                    medium_width, medium_height, medium_bytes, duration_ms, blurhash, created_at \
+             FROM media WHERE post_id = $1 ORDER BY created_at ASC",
+        )
+        .bind(post_id)
+        .fetch_all(&mut *tx)
         .await?;
 
+        // Resolve @handle mentions against the users table, excluding the
+        // author and anyone with a block in either direction, then persist
+        // the resolved edges. Unresolved or excluded handles are silently
+        // dropped rather than failing the post.
+        let mentioned_handles = caption
+            .as_deref()
+            .map(extract_mentioned_handles)
+            .unwrap_or_default();
+        let mentions = if mentioned_handles.is_empty() {
+            Vec::new()
+        } else {
+            let mention_rows = sqlx::query(
+                "SELECT id AS user_id, handle FROM users \
+                 WHERE lower(handle) = ANY($1) \
+                   AND deleted_at IS NULL \
+                   AND id <> $2 \
+                   AND NOT EXISTS ( \
+                       SELECT 1 FROM blocks \
+                       WHERE (blocker_id = $2 AND blocked_id = users.id) \
+                          OR (blocker_id = users.id AND blocked_id = $2) \
+                   )",
+            )
+            .bind(&mentioned_handles)
+            .bind(owner_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let mentioned_user_ids: Vec<Uuid> =
+                mention_rows.iter().map(|row| row.get("user_id")).collect();
+
+            if !mentioned_user_ids.is_empty() {
+                sqlx::query(
+                    "INSERT INTO post_mentions (post_id, mentioned_user_id) \
+                     SELECT $1, unnest($2::uuid[])",
+                )
+                .bind(post_id)
+                .bind(&mentioned_user_ids)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            mention_rows
+                .into_iter()
+                .map(|row| PostMention {
+                    user_id: row.get("user_id"),
+                    handle: row.get("handle"),
+                })
+                .collect()
+        };
+
+        tx.commit().await?;
+
+        let attachments = attachment_rows
+            .into_iter()
+            .map(|row| crate::domain::media::Media {
+                id: row.get("id"),
+                owner_id: row.get("owner_id"),
+                post_id: row.get("post_id"),
+                kind: crate::domain::media::MediaKind::from_db(&row.get::<String, _>("kind"))
+                    .unwrap_or(crate::domain::media::MediaKind::Image),
+                original_key: row.get("original_key"),
+                thumb_key: row.get("thumb_key"),
+                medium_key: row.get("medium_key"),
+                original_width: row.get("original_width"),
+                original_height: row.get("original_height"),
+                original_bytes: row.get("original_bytes"),
+                thumb_width: row.get("thumb_width"),
+                thumb_height: row.get("thumb_height"),
+                thumb_bytes: row.get("thumb_bytes"),
+                medium_width: row.get("medium_width"),
+                medium_height: row.get("medium_height"),
+                medium_bytes: row.get("medium_bytes"),
+                duration_ms: row.get("duration_ms"),
+                thumb_blurhash: row.get("blurhash"),
+                created_at: row.get("created_at"),
+                original_url: None,
+                thumb_url: None,
+                medium_url: None,
+            })
+            .collect();
+
         let visibility: String = row.get("visibility");
         let visibility = PostVisibility::from_db(&visibility).ok_or_else(|| {
             anyhow::anyhow!("unknown post visibility: {}", visibility)
         })?;
 
+        let repost_of_id: Option<Uuid> = row.get("repost_of_id");
+        let reposted_post = match repost_of_id {
+            Some(repost_of_id) => list_reposted_posts(self.db.pool(), &[repost_of_id])
+                .await?
+                .remove(&repost_of_id)
+                .map(Box::new),
+            None => None,
+        };
+
         Ok(Post {
-            id: row.get("id"),
+            id: post_id,
             owner_id: row.get("owner_id"),
             owner_handle: Some(row.get("owner_handle")),
             owner_display_name: Some(row.get("owner_display_name")),
-            media_id: row.get("media_id"),
+            attachments,
+            mentions,
             caption: row.get("caption"),
             visibility,
+            in_reply_to_id: row.get("in_reply_to_id"),
+            repost_of_id,
+            reposted_post,
             created_at: row.get("created_at"),
+            owner_avatar_key: None,
+            owner_avatar_url: None,
         })
     }
 
+    /// Looks up a post's owner without applying visibility rules. Used by
+    /// notification producers to find who a reply/repost should notify.
+    pub async fn get_owner_id(&self, post_id: Uuid) -> Result<Option<Uuid>> {
+        sqlx::query_scalar("SELECT owner_id FROM posts WHERE id = $1")
+            .bind(post_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn get_post(&self, post_id: Uuid, viewer_id: Option<Uuid>) -> Result<Option<Post>> {
         let row = match viewer_id {
             Some(viewer_id) => {
                 sqlx::query(
                     "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                            p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                      FROM posts p \
-                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
                      WHERE p.id = $1 \
+                       AND p.removed_at IS NULL \
                        AND (p.visibility = 'public' \
                             OR p.owner_id = $2 \
-                            OR (p.visibility = 'followers_only' AND EXISTS ( \
+                            OR (p.visibility = 'followers' AND EXISTS ( \
                                 SELECT 1 FROM follows WHERE follower_id = $2 AND followee_id = p.owner_id \
+                            )) \
+                            OR (p.visibility = 'mentioned' AND EXISTS ( \
+                                SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $2 \
                             ))) \
                        AND NOT EXISTS ( \
                            SELECT 1 FROM blocks \
@@ -98,10 +264,11 @@ impl PostService {
             None => {
                 sqlx::query(
                     "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                            p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                      FROM posts p \
-                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
-                     WHERE p.id = $1 AND p.visibility = 'public'",
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                     WHERE p.id = $1 AND p.visibility = 'public' AND p.removed_at IS NULL",
                 )
                 .bind(post_id)
                 .fetch_optional(self.db.pool())
@@ -114,15 +281,33 @@ impl PostService {
                 let visibility: String = row.get("visibility");
                 let visibility = PostVisibility::from_db(&visibility)
                     .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+                let mut attachments_by_post =
+                    list_attachments_for_posts(self.db.pool(), &[post_id]).await?;
+                let mut mentions_by_post =
+                    list_mentions_for_posts(self.db.pool(), &[post_id]).await?;
+                let repost_of_id: Option<Uuid> = row.get("repost_of_id");
+                let reposted_post = match repost_of_id {
+                    Some(repost_of_id) => list_reposted_posts(self.db.pool(), &[repost_of_id])
+                        .await?
+                        .remove(&repost_of_id)
+                        .map(Box::new),
+                    None => None,
+                };
                 Some(Post {
                     id: row.get("id"),
                     owner_id: row.get("owner_id"),
                     owner_handle: Some(row.get("owner_handle")),
                     owner_display_name: Some(row.get("owner_display_name")),
-                    media_id: row.get("media_id"),
+                    attachments: attachments_by_post.remove(&post_id).unwrap_or_default(),
+                    mentions: mentions_by_post.remove(&post_id).unwrap_or_default(),
                     caption: row.get("caption"),
                     visibility,
+                    in_reply_to_id: row.get("in_reply_to_id"),
+                    repost_of_id,
+                    reposted_post,
                     created_at: row.get("created_at"),
+                    owner_avatar_key: None,
+                    owner_avatar_url: None,
                 })
             }
             None => None,
@@ -131,6 +316,11 @@ impl PostService {
         Ok(post)
     }
 
+    /// Updates the caption, first snapshotting the pre-edit caption and
+    /// visibility into `post_history` (action `'edit'`) in the same
+    /// statement, so moderators can later see what a post said before it
+    /// was changed. Mirrors `UserService::update_profile`'s
+    /// snapshot-then-mutate shape for `profile_history`.
     pub async fn update_caption(
         &self,
         post_id: Uuid,
@@ -138,11 +328,17 @@ impl PostService {
         caption: Option<String>,
     ) -> Result<Option<Post>> {
         let row = sqlx::query(
-            "WITH updated_post AS ( \
+            "WITH old_post AS ( \
+                SELECT caption, visibility FROM posts WHERE id = $1 AND owner_id = $2 \
+             ), logged AS ( \
+                INSERT INTO post_history (post_id, owner_id, action, caption, visibility) \
+                SELECT $1, $2, 'edit', caption, visibility FROM old_post \
+             ), updated_post AS ( \
                 UPDATE posts \
                 SET caption = $3 \
                 WHERE id = $1 AND owner_id = $2 \
-                RETURNING id, owner_id, media_id, caption, visibility::text AS visibility, created_at \
+                RETURNING id, owner_id, caption, visibility::text AS visibility, created_at, \
+                          in_reply_to_id, repost_of_id \
              ) \
              SELECT p.*, u.handle AS owner_handle, u.display_name AS owner_display_name \
              FROM updated_post p \
@@ -159,15 +355,25 @@ impl PostService {
                 let visibility: String = row.get("visibility");
                 let visibility = PostVisibility::from_db(&visibility)
                     .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+                let mut attachments_by_post =
+                    list_attachments_for_posts(self.db.pool(), &[post_id]).await?;
+                let mut mentions_by_post =
+                    list_mentions_for_posts(self.db.pool(), &[post_id]).await?;
                 Some(Post {
                     id: row.get("id"),
                     owner_id: row.get("owner_id"),
                     owner_handle: Some(row.get("owner_handle")),
                     owner_display_name: Some(row.get("owner_display_name")),
-                    media_id: row.get("media_id"),
+                    attachments: attachments_by_post.remove(&post_id).unwrap_or_default(),
+                    mentions: mentions_by_post.remove(&post_id).unwrap_or_default(),
                     caption: row.get("caption"),
                     visibility,
+                    in_reply_to_id: row.get("in_reply_to_id"),
+                    repost_of_id: row.get("repost_of_id"),
+                    reposted_post: None,
                     created_at: row.get("created_at"),
+                    owner_avatar_key: None,
+                    owner_avatar_url: None,
                 })
             }
             None => None,
@@ -176,14 +382,121 @@ impl PostService {
         Ok(post)
     }
 
-    pub async fn delete_post(&self, post_id: Uuid, owner_id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM posts WHERE id = $1 AND owner_id = $2")
+    /// Deletes the post, first snapshotting its caption and visibility into
+    /// `post_history` (action `'delete'`) so the content is still visible
+    /// to moderators after it's gone. Each attachment the post claimed is
+    /// released: if a story still references it, it's just unclaimed
+    /// (`post_id = NULL`); otherwise, since a media row can now only ever
+    /// be attached to one post, it's deleted outright along with its
+    /// upload record. Freed storage keys across all released attachments
+    /// are merged into one `DeletionQueue` for the caller to hand to the
+    /// deletion sweeper. Returns `None` if there was no such post to
+    /// delete.
+    ///
+    /// `owner_id` of `None` skips the ownership check entirely -- only the
+    /// moderator-override path in `delete_post`'s handler should pass that,
+    /// after `ModerationService::authorize_post_force_delete` has confirmed
+    /// and logged it.
+    pub async fn delete_post(
+        &self,
+        post_id: Uuid,
+        owner_id: Option<Uuid>,
+    ) -> Result<Option<DeletionQueue>> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let deleted = match owner_id {
+            Some(owner_id) => {
+                sqlx::query(
+                    "DELETE FROM posts WHERE id = $1 AND owner_id = $2 \
+                     RETURNING id, owner_id, caption, visibility::text AS visibility",
+                )
+                .bind(post_id)
+                .bind(owner_id)
+                .fetch_optional(&mut *tx)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "DELETE FROM posts WHERE id = $1 \
+                     RETURNING id, owner_id, caption, visibility::text AS visibility",
+                )
+                .bind(post_id)
+                .fetch_optional(&mut *tx)
+                .await?
+            }
+        };
+
+        let deleted = match deleted {
+            Some(row) => row,
+            None => {
+                tx.rollback().await?;
+                return Ok(None);
+            }
+        };
+
+        // Snapshot the full content into post_history before it's gone, so
+        // a moderator can still see what a removed post said.
+        sqlx::query(
+            "INSERT INTO post_history (post_id, owner_id, action, caption, visibility) \
+             VALUES ($1, $2, 'delete', $3, $4::post_visibility)",
+        )
+        .bind(post_id)
+        .bind(deleted.get::<Uuid, _>("owner_id"))
+        .bind(deleted.get::<Option<String>, _>("caption"))
+        .bind(deleted.get::<String, _>("visibility"))
+        .execute(&mut *tx)
+        .await?;
+
+        let media_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM media WHERE post_id = $1")
             .bind(post_id)
-            .bind(owner_id)
-            .execute(self.db.pool())
+            .fetch_all(&mut *tx)
             .await?;
 
-        Ok(result.rows_affected() > 0)
+        let mut freed_objects = Vec::new();
+        for media_id in media_ids {
+            let still_used: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM stories WHERE media_id = $1)")
+                    .bind(media_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+            if still_used {
+                sqlx::query("UPDATE media SET post_id = NULL WHERE id = $1")
+                    .bind(media_id)
+                    .execute(&mut *tx)
+                    .await?;
+                continue;
+            }
+
+            let media_row = sqlx::query(
+                "SELECT original_key, thumb_key, medium_key FROM media WHERE id = $1 FOR UPDATE",
+            )
+            .bind(media_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let Some(media_row) = media_row {
+                let keys = [
+                    media_row.get::<String, _>("original_key"),
+                    media_row.get::<String, _>("thumb_key"),
+                    media_row.get::<String, _>("medium_key"),
+                ];
+
+                sqlx::query("DELETE FROM media_uploads WHERE processed_media_id = $1")
+                    .bind(media_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("DELETE FROM media WHERE id = $1")
+                    .bind(media_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                freed_objects.extend(release_blobs(&mut tx, &keys).await?.objects);
+            }
+        }
+
+        tx.commit().await?;
+        Ok(Some(DeletionQueue { objects: freed_objects }))
     }
 
     pub async fn list_by_user(
@@ -198,14 +511,19 @@ impl PostService {
                 Some((created_at, post_id)) => {
                     sqlx::query(
                         "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                                p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                                p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                          FROM posts p \
-                         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
+                         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
                          WHERE p.owner_id = $1 \
+                           AND p.removed_at IS NULL \
                            AND (p.visibility = 'public' \
                                 OR p.owner_id = $2 \
-                                OR (p.visibility = 'followers_only' AND EXISTS ( \
+                                OR (p.visibility = 'followers' AND EXISTS ( \
                                     SELECT 1 FROM follows WHERE follower_id = $2 AND followee_id = p.owner_id \
+                                )) \
+                                OR (p.visibility = 'mentioned' AND EXISTS ( \
+                                    SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $2 \
                                 ))) \
                            AND NOT EXISTS ( \
                                SELECT 1 FROM blocks \
@@ -227,14 +545,19 @@ impl PostService {
                 None => {
                     sqlx::query(
                     "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                            p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                      FROM posts p \
-                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
                      WHERE p.owner_id = $1 \
+                       AND p.removed_at IS NULL \
                        AND (p.visibility = 'public' \
                             OR p.owner_id = $2 \
-                            OR (p.visibility = 'followers_only' AND EXISTS ( \
+                            OR (p.visibility = 'followers' AND EXISTS ( \
                                 SELECT 1 FROM follows WHERE follower_id = $2 AND followee_id = p.owner_id \
+                            )) \
+                            OR (p.visibility = 'mentioned' AND EXISTS ( \
+                                SELECT 1 FROM post_mentions WHERE post_id = p.id AND mentioned_user_id = $2 \
                             ))) \
                        AND NOT EXISTS ( \
                            SELECT 1 FROM blocks \
@@ -255,11 +578,13 @@ impl PostService {
                 Some((created_at, post_id)) => {
                     sqlx::query(
                         "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                                p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                                p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                          FROM posts p \
-                         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
+                         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
                          WHERE p.owner_id = $1 \
                            AND p.visibility = 'public' \
+                           AND p.removed_at IS NULL \
                            AND (p.created_at < $2 OR (p.created_at = $2 AND p.id < $3)) \
                          ORDER BY p.created_at DESC, p.id DESC \
                          LIMIT $4",
@@ -274,10 +599,11 @@ impl PostService {
                 None => {
                     sqlx::query(
                         "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                                p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
+                                p.caption, p.visibility::text AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
                          FROM posts p \
-                         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
-                         WHERE p.owner_id = $1 AND p.visibility = 'public' \
+                         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                         WHERE p.owner_id = $1 AND p.visibility = 'public' AND p.removed_at IS NULL \
                          ORDER BY p.created_at DESC, p.id DESC \
                          LIMIT $2",
                     )
@@ -289,23 +615,321 @@ impl PostService {
             },
         };
 
+        let post_ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+        let mut attachments_by_post = list_attachments_for_posts(self.db.pool(), &post_ids).await?;
+        let mut mentions_by_post = list_mentions_for_posts(self.db.pool(), &post_ids).await?;
+        let repost_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|row| row.get::<Option<Uuid>, _>("repost_of_id"))
+            .collect();
+        let reposted_by_id = list_reposted_posts(self.db.pool(), &repost_ids).await?;
+
         let mut posts = Vec::with_capacity(rows.len());
         for row in rows {
+            let id: Uuid = row.get("id");
             let visibility: String = row.get("visibility");
             let visibility = PostVisibility::from_db(&visibility)
                 .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+            let repost_of_id: Option<Uuid> = row.get("repost_of_id");
+            let reposted_post = repost_of_id
+                .and_then(|id| reposted_by_id.get(&id).cloned())
+                .map(Box::new);
             posts.push(Post {
-                id: row.get("id"),
+                id,
                 owner_id: row.get("owner_id"),
                 owner_handle: Some(row.get("owner_handle")),
                 owner_display_name: Some(row.get("owner_display_name")),
-                media_id: row.get("media_id"),
+                attachments: attachments_by_post.remove(&id).unwrap_or_default(),
+                mentions: mentions_by_post.remove(&id).unwrap_or_default(),
                 caption: row.get("caption"),
                 visibility,
+                in_reply_to_id: row.get("in_reply_to_id"),
+                repost_of_id,
+                reposted_post,
                 created_at: row.get("created_at"),
+                owner_avatar_key: None,
+                owner_avatar_url: None,
             });
         }
 
         Ok(posts)
     }
+
+    /// Returns `post_id`'s `post_history` log (edits, then the delete entry
+    /// if it's gone), most recent first. Gated to the post's owner or a
+    /// moderator, the same split `delete_post`'s force-delete fallback
+    /// uses -- an `"insufficient role"` error, by convention, maps to 403
+    /// at the handler layer.
+    pub async fn list_history(
+        &self,
+        post_id: Uuid,
+        caller_id: Uuid,
+    ) -> Result<Vec<crate::domain::post::PostHistoryEntry>> {
+        let owner_id: Option<Uuid> = sqlx::query_scalar(
+            "SELECT owner_id FROM posts WHERE id = $1 \
+             UNION ALL \
+             SELECT owner_id FROM post_history WHERE post_id = $1 \
+             LIMIT 1",
+        )
+        .bind(post_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        if owner_id != Some(caller_id) {
+            let role = crate::app::moderation::ModerationService::new(self.db.clone())
+                .role_of(caller_id)
+                .await?;
+            if !matches!(
+                role,
+                crate::domain::moderation::UserRole::Moderator | crate::domain::moderation::UserRole::Admin
+            ) {
+                return Err(anyhow::anyhow!("insufficient role: requires moderator"));
+            }
+        }
+
+        let rows = sqlx::query(
+            "SELECT id, post_id, owner_id, action, caption, visibility::text AS visibility, created_at \
+             FROM post_history \
+             WHERE post_id = $1 \
+             ORDER BY created_at DESC",
+        )
+        .bind(post_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let visibility: String = row.get("visibility");
+            let visibility = PostVisibility::from_db(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+            entries.push(crate::domain::post::PostHistoryEntry {
+                id: row.get("id"),
+                post_id: row.get("post_id"),
+                owner_id: row.get("owner_id"),
+                action: row.get("action"),
+                caption: row.get("caption"),
+                visibility,
+                created_at: row.get("created_at"),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Hard-deletes every post `user_id` owns, as the post/media half of
+    /// `jobs::account_purge`'s expired-account cleanup. Mirrors
+    /// `delete_post`'s per-post cleanup (`post_history` snapshot, attachment
+    /// release) but runs it across the whole set in one transaction and
+    /// merges every freed blob key into a single `DeletionQueue`, so the
+    /// caller enqueues one deletion job per purged account instead of one
+    /// per post.
+    pub async fn purge_posts_for_user(&self, user_id: Uuid) -> Result<DeletionQueue> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let post_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM posts WHERE owner_id = $1")
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut freed_objects = Vec::new();
+        for post_id in post_ids {
+            let deleted = sqlx::query(
+                "DELETE FROM posts WHERE id = $1 \
+                 RETURNING caption, visibility::text AS visibility",
+            )
+            .bind(post_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO post_history (post_id, owner_id, action, caption, visibility) \
+                 VALUES ($1, $2, 'delete', $3, $4::post_visibility)",
+            )
+            .bind(post_id)
+            .bind(user_id)
+            .bind(deleted.get::<Option<String>, _>("caption"))
+            .bind(deleted.get::<String, _>("visibility"))
+            .execute(&mut *tx)
+            .await?;
+
+            let media_ids: Vec<Uuid> =
+                sqlx::query_scalar("SELECT id FROM media WHERE post_id = $1")
+                    .bind(post_id)
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+            for media_id in media_ids {
+                let still_used: bool =
+                    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM stories WHERE media_id = $1)")
+                        .bind(media_id)
+                        .fetch_one(&mut *tx)
+                        .await?;
+
+                if still_used {
+                    sqlx::query("UPDATE media SET post_id = NULL WHERE id = $1")
+                        .bind(media_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    continue;
+                }
+
+                let media_row = sqlx::query(
+                    "SELECT original_key, thumb_key, medium_key FROM media WHERE id = $1 FOR UPDATE",
+                )
+                .bind(media_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if let Some(media_row) = media_row {
+                    let keys = [
+                        media_row.get::<String, _>("original_key"),
+                        media_row.get::<String, _>("thumb_key"),
+                        media_row.get::<String, _>("medium_key"),
+                    ];
+
+                    sqlx::query("DELETE FROM media_uploads WHERE processed_media_id = $1")
+                        .bind(media_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    sqlx::query("DELETE FROM media WHERE id = $1")
+                        .bind(media_id)
+                        .execute(&mut *tx)
+                        .await?;
+
+                    freed_objects.extend(release_blobs(&mut tx, &keys).await?.objects);
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(DeletionQueue {
+            objects: freed_objects,
+        })
+    }
+}
+
+/// Extracts the distinct, lowercased `@handle` tokens referenced in a
+/// caption. A token starts at an `@` not itself preceded by a handle
+/// character (so emails like `a@b.com` aren't mistaken for a mention) and
+/// runs through ASCII alphanumerics and underscores.
+fn extract_mentioned_handles(caption: &str) -> Vec<String> {
+    let chars: Vec<char> = caption.chars().collect();
+    let mut seen = std::collections::HashSet::new();
+    let mut handles = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '@' && (i == 0 || !is_handle_char(chars[i - 1])) {
+            let start = i + 1;
+            let mut end = start;
+            while end < chars.len() && is_handle_char(chars[end]) {
+                end += 1;
+            }
+            if end > start {
+                let handle: String = chars[start..end].iter().collect::<String>().to_lowercase();
+                if seen.insert(handle.clone()) {
+                    handles.push(handle);
+                }
+                i = end;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    handles
+}
+
+fn is_handle_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Loads the resolved mentions for a batch of posts in one query and groups
+/// them by `post_id`. Mirrors `list_attachments_for_posts` in `app::media`
+/// to keep the read paths (`PostService`, `FeedService`, `SearchService`)
+/// free of N+1 queries.
+pub(crate) async fn list_mentions_for_posts(
+    pool: &sqlx::PgPool,
+    post_ids: &[Uuid],
+) -> Result<std::collections::HashMap<Uuid, Vec<PostMention>>> {
+    let rows = sqlx::query(
+        "SELECT pm.post_id, u.id AS user_id, u.handle \
+         FROM post_mentions pm \
+         JOIN users u ON u.id = pm.mentioned_user_id AND u.deleted_at IS NULL \
+         WHERE pm.post_id = ANY($1)",
+    )
+    .bind(post_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_post: std::collections::HashMap<Uuid, Vec<PostMention>> =
+        std::collections::HashMap::with_capacity(post_ids.len());
+    for row in rows {
+        let post_id: Uuid = row.get("post_id");
+        by_post.entry(post_id).or_default().push(PostMention {
+            user_id: row.get("user_id"),
+            handle: row.get("handle"),
+        });
+    }
+
+    Ok(by_post)
+}
+
+/// Loads the original posts referenced by a batch of `repost_of_id`s, for
+/// embedding as `Post::reposted_post`, keyed by their own id. Not recursive
+/// -- `create_post`'s insert guard already refuses to let a repost target
+/// another repost, so an original post fetched here never itself carries a
+/// `reposted_post`.
+async fn list_reposted_posts(
+    pool: &sqlx::PgPool,
+    repost_ids: &[Uuid],
+) -> Result<std::collections::HashMap<Uuid, Post>> {
+    if repost_ids.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    let rows = sqlx::query(
+        "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                p.caption, p.visibility::text AS visibility, p.created_at, \
+                p.in_reply_to_id, p.repost_of_id \
+         FROM posts p \
+         JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL \
+         WHERE p.id = ANY($1) AND p.removed_at IS NULL",
+    )
+    .bind(repost_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+    let mut attachments_by_post = list_attachments_for_posts(pool, &ids).await?;
+    let mut mentions_by_post = list_mentions_for_posts(pool, &ids).await?;
+
+    let mut by_id = std::collections::HashMap::with_capacity(rows.len());
+    for row in rows {
+        let id: Uuid = row.get("id");
+        let visibility: String = row.get("visibility");
+        let visibility = PostVisibility::from_db(&visibility)
+            .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+        by_id.insert(
+            id,
+            Post {
+                id,
+                owner_id: row.get("owner_id"),
+                owner_handle: Some(row.get("owner_handle")),
+                owner_display_name: Some(row.get("owner_display_name")),
+                attachments: attachments_by_post.remove(&id).unwrap_or_default(),
+                mentions: mentions_by_post.remove(&id).unwrap_or_default(),
+                caption: row.get("caption"),
+                visibility,
+                in_reply_to_id: row.get("in_reply_to_id"),
+                repost_of_id: row.get("repost_of_id"),
+                reposted_post: None,
+                created_at: row.get("created_at"),
+                owner_avatar_key: None,
+                owner_avatar_url: None,
+            },
+        );
+    }
+
+    Ok(by_id)
 }
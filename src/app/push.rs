@@ -0,0 +1,134 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::infra::db::Db;
+use crate::infra::queue::QueueClient;
+
+/// Queued unit of work for the push dispatcher: carries just enough to look
+/// the subscription back up at delivery time rather than ferrying its keys
+/// through the queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushJob {
+    pub subscription_id: Uuid,
+    pub notification_id: Uuid,
+    pub notification_type: String,
+    pub title: String,
+    pub body: String,
+}
+
+pub struct PushSubscriptionRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Clone)]
+pub struct PushService {
+    db: Db,
+    queue: QueueClient,
+}
+
+impl PushService {
+    pub fn new(db: Db, queue: QueueClient) -> Self {
+        Self { db, queue }
+    }
+
+    /// Register (or refresh) a browser's Web Push subscription for `user_id`.
+    pub async fn subscribe(
+        &self,
+        user_id: Uuid,
+        endpoint: &str,
+        p256dh: &str,
+        auth: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO push_subscriptions (user_id, endpoint, p256dh, auth) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (endpoint) DO UPDATE SET \
+                user_id = EXCLUDED.user_id, p256dh = EXCLUDED.p256dh, auth = EXCLUDED.auth",
+        )
+        .bind(user_id)
+        .bind(endpoint)
+        .bind(p256dh)
+        .bind(auth)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn unsubscribe(&self, user_id: Uuid, endpoint: &str) -> Result<()> {
+        sqlx::query("DELETE FROM push_subscriptions WHERE user_id = $1 AND endpoint = $2")
+            .bind(user_id)
+            .bind(endpoint)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Enqueue `title`/`body` for delivery to every device `user_id` has
+    /// subscribed from. Dispatch happens out-of-band in the push worker, not
+    /// on this request's critical path. `notification_id`/`notification_type`
+    /// ride along so the dispatcher can fall back to an id-only payload (see
+    /// `jobs::push_dispatcher::build_payload`) when the full body doesn't fit
+    /// a push service's size limit.
+    pub async fn notify(
+        &self,
+        user_id: Uuid,
+        notification_id: Uuid,
+        notification_type: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<()> {
+        let subscription_ids: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM push_subscriptions WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_all(self.db.pool())
+                .await?;
+
+        for subscription_id in subscription_ids {
+            self.queue
+                .enqueue_push_job(&PushJob {
+                    subscription_id,
+                    notification_id,
+                    notification_type: notification_type.to_string(),
+                    title: title.to_string(),
+                    body: body.to_string(),
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_subscription(&self, subscription_id: Uuid) -> Result<Option<PushSubscriptionRow>> {
+        let row = sqlx::query(
+            "SELECT id, user_id, endpoint, p256dh, auth FROM push_subscriptions WHERE id = $1",
+        )
+        .bind(subscription_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|row| PushSubscriptionRow {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            endpoint: row.get("endpoint"),
+            p256dh: row.get("p256dh"),
+            auth: row.get("auth"),
+        }))
+    }
+
+    pub async fn retire_subscription(&self, subscription_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM push_subscriptions WHERE id = $1")
+            .bind(subscription_id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+}
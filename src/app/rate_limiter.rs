@@ -1,14 +1,345 @@
 use anyhow::Result;
+use dashmap::DashMap;
 use redis::AsyncCommands;
+use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::sync::OnceLock;
 use uuid::Uuid;
 
-use crate::config::rate_limits::{current_window, RateLimits, RateWindow, TrustLevel};
+use crate::config::rate_limits::{
+    current_window, elapsed_fraction, seconds_until_window_reset, LimitType, RateLimits,
+    RateWindow, TrustLevel,
+};
 use crate::infra::cache::RedisCache;
 
+/// Rolls a sliding-window-counter key forward to `window_id` and atomically
+/// admits `delta` more requests if doing so wouldn't push the weighted
+/// estimate past `limit`, in one `EVAL` round trip. Replaces the old
+/// read-then-write pair (`HGETALL` followed by a separate `HSET`/`EXPIRE`),
+/// which raced under concurrency: two requests could both read the
+/// not-yet-incremented count, both decide they were under the limit, and
+/// both write, letting a burst at the boundary admit more than `limit`.
+/// Passing `limit = u32::MAX` turns this into an unconditional roll-and-add,
+/// for callers (like the periodic flush) that aren't making an admission
+/// decision at all. See `atomic_consume` for the return shape.
+const CONSUME_SCRIPT_SRC: &str = r#"
+local key = KEYS[1]
+local window_id = tonumber(ARGV[1])
+local window_seconds = tonumber(ARGV[2])
+local limit = tonumber(ARGV[3])
+local elapsed_fraction = tonumber(ARGV[4])
+local delta = tonumber(ARGV[5])
+
+local stored_window_id = tonumber(redis.call('HGET', key, 'window_id')) or 0
+local curr_count = tonumber(redis.call('HGET', key, 'curr_count')) or 0
+local prev_count = tonumber(redis.call('HGET', key, 'prev_count')) or 0
+
+if stored_window_id == window_id then
+    -- same window, nothing to roll
+elseif stored_window_id + 1 == window_id then
+    prev_count = curr_count
+    curr_count = 0
+else
+    prev_count = 0
+    curr_count = 0
+end
+
+local estimate = prev_count * (1 - elapsed_fraction) + curr_count
+local allowed = 0
+if estimate + delta <= limit then
+    allowed = 1
+    curr_count = curr_count + delta
+end
+
+redis.call('HSET', key, 'window_id', window_id, 'curr_count', curr_count, 'prev_count', prev_count)
+redis.call('EXPIRE', key, window_seconds * 2)
+
+local new_estimate = prev_count * (1 - elapsed_fraction) + curr_count
+local new_count = math.ceil(new_estimate)
+local remaining = limit - new_count
+if remaining < 0 then
+    remaining = 0
+end
+
+return {allowed, remaining, new_count}
+"#;
+
+fn consume_script() -> &'static redis::Script {
+    static SCRIPT: OnceLock<redis::Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| redis::Script::new(CONSUME_SCRIPT_SRC))
+}
+
+/// Like `CONSUME_SCRIPT_SRC`, but checks and commits every window a
+/// `LimitType` uses in one `EVAL` round trip: every window's estimate is
+/// compared against its limit *before* any of them are rolled/incremented,
+/// so a rejection on one window can never leave an earlier window's
+/// increment stranded. Without this, checking windows one at a time (as
+/// `RateLimiter::consume` used to) commits each window's increment as soon
+/// as it individually passes -- a user already at their Day limit but still
+/// under their Hour limit would burn an Hour-quota unit on a request that
+/// was never actually admitted, since the Hour window had already been
+/// written by the time the Day window rejected.
+const CONSUME_MULTI_SCRIPT_SRC: &str = r#"
+local delta = tonumber(ARGV[1])
+local n = #KEYS
+
+local window_ids = {}
+local window_seconds_list = {}
+local limits = {}
+local elapsed_fractions = {}
+local curr_counts = {}
+local prev_counts = {}
+local estimates = {}
+
+for i = 1, n do
+    local base = 1 + (i - 1) * 4
+    window_ids[i] = tonumber(ARGV[base + 1])
+    window_seconds_list[i] = tonumber(ARGV[base + 2])
+    limits[i] = tonumber(ARGV[base + 3])
+    elapsed_fractions[i] = tonumber(ARGV[base + 4])
+
+    local key = KEYS[i]
+    local stored_window_id = tonumber(redis.call('HGET', key, 'window_id')) or 0
+    local curr_count = tonumber(redis.call('HGET', key, 'curr_count')) or 0
+    local prev_count = tonumber(redis.call('HGET', key, 'prev_count')) or 0
+
+    if stored_window_id == window_ids[i] then
+        -- same window, nothing to roll
+    elseif stored_window_id + 1 == window_ids[i] then
+        prev_count = curr_count
+        curr_count = 0
+    else
+        prev_count = 0
+        curr_count = 0
+    end
+
+    curr_counts[i] = curr_count
+    prev_counts[i] = prev_count
+    estimates[i] = prev_count * (1 - elapsed_fractions[i]) + curr_count
+end
+
+local allowed = 1
+local failed_index = 0
+for i = 1, n do
+    if estimates[i] + delta > limits[i] then
+        allowed = 0
+        failed_index = i
+        break
+    end
+end
+
+local remainings = {}
+local new_counts = {}
+for i = 1, n do
+    local curr_count = curr_counts[i]
+    if allowed == 1 then
+        curr_count = curr_count + delta
+    end
+
+    redis.call('HSET', KEYS[i], 'window_id', window_ids[i], 'curr_count', curr_count, 'prev_count', prev_counts[i])
+    redis.call('EXPIRE', KEYS[i], window_seconds_list[i] * 2)
+
+    local new_estimate = prev_counts[i] * (1 - elapsed_fractions[i]) + curr_count
+    local new_count = math.ceil(new_estimate)
+    new_counts[i] = new_count
+    local remaining = limits[i] - new_count
+    if remaining < 0 then
+        remaining = 0
+    end
+    remainings[i] = remaining
+end
+
+return {allowed, failed_index, remainings, new_counts}
+"#;
+
+fn consume_multi_script() -> &'static redis::Script {
+    static SCRIPT: OnceLock<redis::Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| redis::Script::new(CONSUME_MULTI_SCRIPT_SRC))
+}
+
+/// One window's admission parameters for `atomic_consume_multi`.
+struct WindowSpec<'a> {
+    key: &'a str,
+    window_seconds: u64,
+    window_id: u64,
+    limit: u32,
+    elapsed_fraction: f64,
+}
+
+/// Check-and-admit `delta` against every window in `specs` in a single
+/// atomic step: either all windows' increments are committed, or none are.
+/// Returns whether the batch was admitted, the index of the window that
+/// rejected it (if any), and each window's post-admission `(remaining,
+/// new_count)` in the same order as `specs`.
+async fn atomic_consume_multi(
+    conn: &mut redis::aio::MultiplexedConnection,
+    specs: &[WindowSpec<'_>],
+    delta: u32,
+) -> Result<(bool, Option<usize>, Vec<(u32, u32)>)> {
+    let mut invocation = consume_multi_script().key(specs[0].key);
+    for spec in &specs[1..] {
+        invocation = invocation.key(spec.key);
+    }
+    invocation = invocation.arg(delta);
+    for spec in specs {
+        invocation = invocation
+            .arg(spec.window_id)
+            .arg(spec.window_seconds)
+            .arg(spec.limit)
+            .arg(spec.elapsed_fraction);
+    }
+
+    let (allowed, failed_index, remainings, new_counts): (i64, i64, Vec<i64>, Vec<i64>) =
+        invocation.invoke_async(conn).await?;
+
+    let per_window = remainings
+        .into_iter()
+        .zip(new_counts)
+        .map(|(remaining, new_count)| (remaining.max(0) as u32, new_count.max(0) as u32))
+        .collect();
+
+    let failed = if allowed == 0 {
+        Some((failed_index - 1).max(0) as usize)
+    } else {
+        None
+    };
+
+    Ok((allowed != 0, failed, per_window))
+}
+
+/// `(allowed, remaining, new_count)`: whether `delta` was admitted, the
+/// post-admission remaining quota, and the rolled weighted-estimate count
+/// (ceiled) regardless of whether it was admitted -- callers that are just
+/// flushing a local tally (not making a decision) only care about the last.
+async fn atomic_consume(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key: &str,
+    window_seconds: u64,
+    window_id: u64,
+    limit: u32,
+    elapsed_fraction: f64,
+    delta: u32,
+) -> Result<(bool, u32, u32)> {
+    let (allowed, remaining, new_count): (i64, i64, i64) = consume_script()
+        .key(key)
+        .arg(window_id)
+        .arg(window_seconds)
+        .arg(limit)
+        .arg(elapsed_fraction)
+        .arg(delta)
+        .invoke_async(conn)
+        .await?;
+
+    Ok((allowed != 0, remaining as u32, new_count.max(0) as u32))
+}
+
+/// Seconds until `key` expires, derived from its Redis TTL. Falls back to the
+/// arithmetic window boundary if the key hasn't been written yet (no TTL set).
+async fn key_reset_seconds(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key: &str,
+    window_seconds: u64,
+) -> Result<u64> {
+    let ttl: i64 = conn.ttl(key).await.unwrap_or(-1);
+    if ttl > 0 {
+        Ok(ttl as u64)
+    } else {
+        Ok(seconds_until_window_reset(window_seconds))
+    }
+}
+
+/// Sliding-window-counter state for one `(user, limit_type, window)` key:
+/// the window the counts were last rolled to, plus that window's count and
+/// the one before it. Stored as a Redis hash so the three fields move
+/// together -- if they lived in separate keys they could drift out of sync
+/// under concurrent readers.
+struct SlidingWindow {
+    window_id: u64,
+    curr_count: u32,
+    prev_count: u32,
+}
+
+impl SlidingWindow {
+    /// Estimated rolling count as of `elapsed_fraction` into the current
+    /// window: the previous window's count decays linearly to zero over the
+    /// course of the current one, while the current window's count is taken
+    /// at face value.
+    fn weighted_estimate(&self, elapsed_fraction: f64) -> f64 {
+        self.prev_count as f64 * (1.0 - elapsed_fraction) + self.curr_count as f64
+    }
+}
+
+/// Project `state` forward to `window_id` without persisting anything, for
+/// read-only callers (`get_remaining`) that must still see an accurate
+/// estimate even if nothing has rolled the stored hash over yet this window.
+fn projected(state: &SlidingWindow, window_id: u64) -> SlidingWindow {
+    if state.window_id == window_id {
+        SlidingWindow {
+            window_id,
+            curr_count: state.curr_count,
+            prev_count: state.prev_count,
+        }
+    } else if state.window_id + 1 == window_id {
+        SlidingWindow {
+            window_id,
+            curr_count: 0,
+            prev_count: state.curr_count,
+        }
+    } else {
+        SlidingWindow {
+            window_id,
+            curr_count: 0,
+            prev_count: 0,
+        }
+    }
+}
+
+async fn read_sliding_window(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key: &str,
+) -> Result<SlidingWindow> {
+    let fields: std::collections::HashMap<String, u64> = conn.hgetall(key).await.unwrap_or_default();
+    Ok(SlidingWindow {
+        window_id: fields.get("window_id").copied().unwrap_or(0),
+        curr_count: fields.get("curr_count").copied().unwrap_or(0) as u32,
+        prev_count: fields.get("prev_count").copied().unwrap_or(0) as u32,
+    })
+}
+
+/// Unconditionally roll `key`'s stored counters forward to `window_id`,
+/// adding `delta` to the current window's count, and return the resulting
+/// weighted-estimate count. Used by callers that have already made their own
+/// admission decision (the deferred limiter's local fast path) and are just
+/// flushing a tally, not asking whether `delta` should be admitted -- backed
+/// by `atomic_consume` with `limit = u32::MAX` so it's still a single,
+/// race-free round trip rather than the old `HGETALL` + `HSET` + `EXPIRE`.
+async fn roll_sliding_window(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key: &str,
+    window_seconds: u64,
+    window_id: u64,
+    delta: u32,
+) -> Result<u32> {
+    let (_, _, new_count) = atomic_consume(
+        conn,
+        key,
+        window_seconds,
+        window_id,
+        u32::MAX,
+        elapsed_fraction(window_seconds),
+        delta,
+    )
+    .await?;
+
+    Ok(new_count)
+}
+
 pub struct RateLimitInfo {
     pub limited: bool,
     pub limit: u32,
     pub remaining: u32,
+    /// Seconds until the window backing this decision resets.
+    pub reset_seconds: u64,
 }
 
 #[derive(Clone)]
@@ -21,71 +352,109 @@ impl RateLimiter {
         Self { cache }
     }
 
-    /// Rate limit check result with quota information for response headers.
-    pub async fn check_rate_limit(
+    /// Check-and-admit one request against every window a `LimitType`
+    /// checks, in a single atomic step covering *all* of them. This replaces
+    /// what used to be a separate `check_rate_limit` read followed by an
+    /// `increment` write: two requests racing between those calls could both
+    /// read the same not-yet-incremented estimate, both pass the check, and
+    /// both write, letting a burst admit more than `limit`. It's also not
+    /// enough to run `atomic_consume`'s single-window script once per window
+    /// in a loop: that closes the race within a window, but a multi-window
+    /// `LimitType` like `PostCreate` (Hour + Day) would still commit the
+    /// Hour window's increment before finding out the Day window rejects,
+    /// burning an Hour-quota unit on a request that was never admitted.
+    /// `atomic_consume_multi` checks every window's estimate against its
+    /// limit before committing any of them, so either the whole request is
+    /// admitted across all windows or none of them are touched.
+    pub async fn consume(
         &self,
         user_id: Uuid,
-        action: &str,
+        limit_type: LimitType,
         trust_level: TrustLevel,
     ) -> Result<RateLimitInfo> {
         let limits = RateLimits::for_trust_level(trust_level);
+        let windows = limits.windows_for(limit_type);
+        if windows.is_empty() {
+            return Ok(RateLimitInfo {
+                limited: false,
+                limit: 0,
+                remaining: 0,
+                reset_seconds: 0,
+            });
+        }
 
-        // Check both hourly and daily limits where applicable
-        let checks = match action {
-            "post" => vec![
-                (limits.posts_per_hour, RateWindow::Hour),
-                (limits.posts_per_day, RateWindow::Day),
-            ],
-            "follow" => vec![
-                (limits.follows_per_hour, RateWindow::Hour),
-                (limits.follows_per_day, RateWindow::Day),
-            ],
-            "unfollow" => vec![(limits.unfollows_per_day, RateWindow::Day)],
-            "like" => vec![(limits.likes_per_hour, RateWindow::Hour)],
-            "comment" => vec![(limits.comments_per_hour, RateWindow::Hour)],
-            "login" => vec![(limits.login_attempts_per_hour, RateWindow::Hour)],
-            "feed" => vec![(limits.feed_requests_per_hour, RateWindow::Hour)],
-            "notifications" => vec![(limits.notifications_per_hour, RateWindow::Hour)],
-            "search" => vec![(limits.search_requests_per_hour, RateWindow::Hour)],
-            "media" => vec![(limits.media_requests_per_hour, RateWindow::Hour)],
-            "moderation" => vec![(limits.moderation_actions_per_hour, RateWindow::Hour)],
-            _ => return Ok(RateLimitInfo { limited: false, limit: 0, remaining: 0 }),
-        };
+        struct WindowCheck {
+            key: String,
+            window_seconds: u64,
+            window_id: u64,
+            limit: u32,
+            reset_seconds: u64,
+        }
 
-        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let mut checks = Vec::with_capacity(windows.len());
+        for window in windows {
+            let Some(bucket) = limits.limit(limit_type, window) else {
+                continue;
+            };
+            let window_seconds = window.seconds();
+            checks.push(WindowCheck {
+                key: format!("ratelimit:{}:{}:{}", user_id, limit_type.as_key(), window_seconds),
+                window_seconds,
+                window_id: current_window(window_seconds),
+                limit: bucket.max,
+                reset_seconds: seconds_until_window_reset(window_seconds),
+            });
+        }
 
-        // Track the tightest (most constrained) window for response headers
-        let mut min_remaining = u32::MAX;
-        let mut effective_limit: u32 = 0;
+        if checks.is_empty() {
+            return Ok(RateLimitInfo {
+                limited: false,
+                limit: 0,
+                remaining: 0,
+                reset_seconds: 0,
+            });
+        }
 
-        // Check all applicable windows
-        for (limit, window) in checks {
-            let window_seconds = window.seconds();
-            let key = format!(
-                "ratelimit:{}:{}:{}",
-                user_id,
-                action,
-                current_window(window_seconds)
-            );
+        let specs: Vec<WindowSpec> = checks
+            .iter()
+            .map(|check| WindowSpec {
+                key: &check.key,
+                window_seconds: check.window_seconds,
+                window_id: check.window_id,
+                limit: check.limit,
+                elapsed_fraction: elapsed_fraction(check.window_seconds),
+            })
+            .collect();
 
-            let count: u32 = conn.get(&key).await.unwrap_or(0);
-            let remaining = limit.saturating_sub(count);
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let (allowed, failed_index, per_window) = atomic_consume_multi(&mut conn, &specs, 1).await?;
 
-            if remaining < min_remaining {
-                min_remaining = remaining;
-                effective_limit = limit;
-            }
+        if !allowed {
+            let failed = &checks[failed_index.unwrap_or(0)];
+            tracing::debug!(
+                user_id = %user_id,
+                limit_type = limit_type.as_key(),
+                window_seconds = failed.window_seconds,
+                limit = failed.limit,
+                "Rate limit exceeded"
+            );
+            return Ok(RateLimitInfo {
+                limited: true,
+                limit: failed.limit,
+                remaining: 0,
+                reset_seconds: failed.reset_seconds,
+            });
+        }
 
-            if count >= limit {
-                tracing::debug!(
-                    user_id = %user_id,
-                    action = action,
-                    window = ?window,
-                    count = count,
-                    limit = limit,
-                    "Rate limit exceeded"
-                );
-                return Ok(RateLimitInfo { limited: true, limit, remaining: 0 });
+        // Track the tightest (most constrained) window for response headers
+        let mut min_remaining = u32::MAX;
+        let mut effective_limit: u32 = 0;
+        let mut effective_reset_seconds: u64 = 0;
+        for (check, (remaining, _new_count)) in checks.iter().zip(per_window.iter()) {
+            if *remaining < min_remaining {
+                min_remaining = *remaining;
+                effective_limit = check.limit;
+                effective_reset_seconds = check.reset_seconds;
             }
         }
 
@@ -93,42 +462,130 @@ impl RateLimiter {
             limited: false,
             limit: effective_limit,
             remaining: min_remaining,
+            reset_seconds: effective_reset_seconds,
         })
     }
 
-    /// Increment rate limit counter for an action
-    pub async fn increment(
+    /// Get remaining quota for a limit type's shortest window, per the same
+    /// sliding-window estimate `consume` uses. Read-only -- doesn't admit or
+    /// roll anything, so it's fine for this one to stay a plain `HGETALL`
+    /// rather than going through `atomic_consume`.
+    pub async fn get_remaining(
         &self,
         user_id: Uuid,
-        action: &str,
-    ) -> Result<()> {
-        let windows = match action {
-            "post" => vec![RateWindow::Hour, RateWindow::Day],
-            "follow" => vec![RateWindow::Hour, RateWindow::Day],
-            "unfollow" => vec![RateWindow::Day],
-            "like" | "comment" | "login" => vec![RateWindow::Hour],
-            "feed" | "notifications" | "search" | "media" | "moderation" => vec![RateWindow::Hour],
-            _ => return Ok(()), // Unknown action, skip
+        limit_type: LimitType,
+        trust_level: TrustLevel,
+    ) -> Result<u32> {
+        let limits = RateLimits::for_trust_level(trust_level);
+
+        let Some(window) = limits.windows_for(limit_type).into_iter().next() else {
+            return Ok(0);
+        };
+        let Some(bucket) = limits.limit(limit_type, window) else {
+            return Ok(0);
         };
 
+        let window_seconds = window.seconds();
+        let key = format!("ratelimit:{}:{}:{}", user_id, limit_type.as_key(), window_seconds);
+        let window_id = current_window(window_seconds);
+
         let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let state = read_sliding_window(&mut conn, &key).await?;
+        let estimate = projected(&state, window_id).weighted_estimate(elapsed_fraction(window_seconds));
 
-        for window in windows {
-            let window_seconds = window.seconds();
-            let key = format!(
-                "ratelimit:{}:{}:{}",
-                user_id,
-                action,
-                current_window(window_seconds)
+        Ok(bucket.max.saturating_sub(estimate.ceil() as u32))
+    }
+
+    /// Check rate limit by IP address (for unauthenticated requests), plus a
+    /// coarser limit on `subnet` (e.g. the containing /64 or /24) so a
+    /// rotating block of addresses from a single allocation can't bypass the
+    /// per-IP limit. Whichever bucket is tighter determines the result.
+    pub async fn check_ip_rate_limit(
+        &self,
+        ip: &str,
+        subnet: &str,
+        limit_type: LimitType,
+        limit: u32,
+        subnet_limit: u32,
+        window: RateWindow,
+    ) -> Result<RateLimitInfo> {
+        let action = limit_type.as_key();
+        let window_seconds = window.seconds();
+        let ip_key = format!("ratelimit:ip:{}:{}:{}", ip, action, current_window(window_seconds));
+        let subnet_key = format!(
+            "ratelimit:ipsubnet:{}:{}:{}",
+            subnet,
+            action,
+            current_window(window_seconds)
+        );
+
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+
+        let ip_count: u32 = conn.get(&ip_key).await.unwrap_or(0);
+        let subnet_count: u32 = conn.get(&subnet_key).await.unwrap_or(0);
+
+        if ip_count >= limit {
+            let reset_seconds = key_reset_seconds(&mut conn, &ip_key, window_seconds).await?;
+            tracing::debug!(ip = ip, action = action, count = ip_count, limit, "IP rate limit exceeded");
+            return Ok(RateLimitInfo {
+                limited: true,
+                limit,
+                remaining: 0,
+                reset_seconds,
+            });
+        }
+
+        if subnet_count >= subnet_limit {
+            let reset_seconds = key_reset_seconds(&mut conn, &subnet_key, window_seconds).await?;
+            tracing::debug!(
+                subnet = subnet,
+                action = action,
+                count = subnet_count,
+                limit = subnet_limit,
+                "IP subnet rate limit exceeded"
             );
+            return Ok(RateLimitInfo {
+                limited: true,
+                limit: subnet_limit,
+                remaining: 0,
+                reset_seconds,
+            });
+        }
 
-            // Get current count
-            let count: u32 = conn.get(&key).await.unwrap_or(0);
+        let ip_remaining = limit.saturating_sub(ip_count);
+        let subnet_remaining = subnet_limit.saturating_sub(subnet_count);
+
+        if subnet_remaining < ip_remaining {
+            let reset_seconds = key_reset_seconds(&mut conn, &subnet_key, window_seconds).await?;
+            Ok(RateLimitInfo {
+                limited: false,
+                limit: subnet_limit,
+                remaining: subnet_remaining,
+                reset_seconds,
+            })
+        } else {
+            let reset_seconds = key_reset_seconds(&mut conn, &ip_key, window_seconds).await?;
+            Ok(RateLimitInfo {
+                limited: false,
+                limit,
+                remaining: ip_remaining,
+                reset_seconds,
+            })
+        }
+    }
 
-            // Increment
-            let _: () = conn.incr(&key, 1).await?;
+    /// Increment both the exact-IP and containing-subnet counters.
+    pub async fn increment_ip(&self, ip: &str, subnet: &str, limit_type: LimitType, window: RateWindow) -> Result<()> {
+        let action = limit_type.as_key();
+        let window_seconds = window.seconds();
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
 
-            // Set expiration on first increment
+        for key in [
+            format!("ratelimit:ip:{}:{}:{}", ip, action, current_window(window_seconds)),
+            format!("ratelimit:ipsubnet:{}:{}:{}", subnet, action, current_window(window_seconds)),
+        ] {
+            let count: u32 = conn.get(&key).await.unwrap_or(0);
+            let _: () = conn.incr(&key, 1).await?;
             if count == 0 {
                 let _: () = conn.expire(&key, window_seconds as i64).await?;
             }
@@ -136,81 +593,272 @@ impl RateLimiter {
 
         Ok(())
     }
+}
 
-    /// Get remaining quota for an action
-    pub async fn get_remaining(
+/// Per-(user, limit type, window) local counter state, shared between the
+/// request path and the background flush task via `Arc`.
+struct LocalCounter {
+    /// Requests admitted locally (via fast path or confirmed via Redis) since
+    /// the counter was last synced with Redis for the current window.
+    local_count: AtomicU32,
+    /// Local admissions not yet flushed to Redis.
+    pending_delta: AtomicU32,
+    /// The `current_window(window_seconds)` bucket this state belongs to.
+    /// A mismatch on access means the window has rolled over and the
+    /// counter must reset rather than carry stale state across the boundary.
+    window_id: AtomicU64,
+}
+
+impl LocalCounter {
+    fn new(window_id: u64) -> Self {
+        Self {
+            local_count: AtomicU32::new(0),
+            pending_delta: AtomicU32::new(0),
+            window_id: AtomicU64::new(window_id),
+        }
+    }
+
+    /// Reset to a fresh window, carrying no state across the boundary.
+    fn reset_for_window(&self, window_id: u64, authoritative_count: u32) {
+        self.local_count.store(authoritative_count, Ordering::SeqCst);
+        self.pending_delta.store(0, Ordering::SeqCst);
+        self.window_id.store(window_id, Ordering::SeqCst);
+    }
+}
+
+type CounterKey = (Uuid, LimitType, u64);
+
+/// Approximate rate limiter that admits most requests from local,
+/// per-process counters and only round-trips to Redis when a window's local
+/// count approaches its limit or on a periodic background flush.
+///
+/// This trades small, bounded over-admission (bounded by `safe_fraction` and
+/// the number of app instances) for cutting Redis round-trips on the hot
+/// path down from two-per-request to near zero for most traffic.
+#[derive(Clone)]
+pub struct DeferredRateLimiter {
+    cache: RedisCache,
+    counters: Arc<DashMap<CounterKey, Arc<LocalCounter>>>,
+    safe_fraction: f64,
+}
+
+impl DeferredRateLimiter {
+    /// `safe_fraction` bounds how much of a window's limit can be admitted
+    /// from local state alone before falling back to Redis (e.g. `0.5` means
+    /// local admission stops once half the limit has been seen locally).
+    /// Spawns a background flush loop on the current Tokio runtime.
+    pub fn new(cache: RedisCache, safe_fraction: f64) -> Self {
+        let limiter = Self {
+            cache,
+            counters: Arc::new(DashMap::new()),
+            safe_fraction,
+        };
+        limiter.spawn_flush_loop();
+        limiter
+    }
+
+    fn spawn_flush_loop(&self) {
+        let cache = self.cache.clone();
+        let counters = self.counters.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+            loop {
+                interval.tick().await;
+                if let Err(err) = flush_all(&cache, &counters).await {
+                    tracing::warn!(error = ?err, "failed to flush deferred rate limit counters");
+                }
+            }
+        });
+    }
+
+    /// Check-and-increment in one call: admits locally when comfortably under
+    /// budget, otherwise reconciles with Redis and decides from the
+    /// authoritative count. Mirrors `RateLimiter::consume` but adds a local
+    /// fast path so most requests never round-trip to Redis at all.
+    pub async fn check_and_increment(
         &self,
         user_id: Uuid,
-        action: &str,
+        limit_type: LimitType,
         trust_level: TrustLevel,
-    ) -> Result<u32> {
+    ) -> Result<RateLimitInfo> {
         let limits = RateLimits::for_trust_level(trust_level);
+        let windows = limits.windows_for(limit_type);
+        if windows.is_empty() {
+            return Ok(RateLimitInfo {
+                limited: false,
+                limit: 0,
+                remaining: 0,
+                reset_seconds: 0,
+            });
+        }
 
-        let (limit, window) = match action {
-            "post" => (limits.posts_per_hour, RateWindow::Hour),
-            "follow" => (limits.follows_per_hour, RateWindow::Hour),
-            "like" => (limits.likes_per_hour, RateWindow::Hour),
-            "comment" => (limits.comments_per_hour, RateWindow::Hour),
-            _ => return Ok(0),
-        };
+        let mut min_remaining = u32::MAX;
+        let mut effective_limit: u32 = 0;
+        let mut effective_reset_seconds: u64 = 0;
+        let mut granted: Vec<RateWindow> = Vec::with_capacity(windows.len());
 
-        let window_seconds = window.seconds();
-        let key = format!(
-            "ratelimit:{}:{}:{}",
-            user_id,
-            action,
-            current_window(window_seconds)
-        );
+        for window in windows {
+            let Some(bucket) = limits.limit(limit_type, window) else {
+                continue;
+            };
+
+            let info = self
+                .check_and_increment_window(user_id, limit_type, window, bucket.max)
+                .await?;
+            if info.limited {
+                // This window rejected the request, but earlier windows in
+                // this same call already admitted and incremented it -- undo
+                // those local increments so a user pinned at one window's
+                // limit doesn't burn quota from a window they were still
+                // under, for a request that was never actually admitted.
+                for granted_window in &granted {
+                    self.undo_local_increment(user_id, limit_type, *granted_window);
+                }
+                return Ok(info);
+            }
+            granted.push(window);
+            if info.remaining < min_remaining {
+                min_remaining = info.remaining;
+                effective_limit = info.limit;
+                effective_reset_seconds = info.reset_seconds;
+            }
+        }
 
-        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
-        let count: u32 = conn.get(&key).await.unwrap_or(0);
+        Ok(RateLimitInfo {
+            limited: false,
+            limit: effective_limit,
+            remaining: min_remaining,
+            reset_seconds: effective_reset_seconds,
+        })
+    }
 
-        Ok(limit.saturating_sub(count))
+    /// Undo the local admission `check_and_increment_window` just granted
+    /// for `window`, because a later window in the same `check_and_increment`
+    /// call rejected the request. Only unwinds the local counters
+    /// (`local_count`/`pending_delta`) -- if the grant had already been
+    /// flushed to Redis by a concurrent `reconcile`/`flush_all`, the rolled
+    /// Redis count is a harmless, bounded overcount of the kind this
+    /// approximate limiter already tolerates, not a correctness bug.
+    fn undo_local_increment(&self, user_id: Uuid, limit_type: LimitType, window: RateWindow) {
+        let key: CounterKey = (user_id, limit_type, window.seconds());
+        if let Some(counter) = self.counters.get(&key) {
+            counter.local_count.fetch_sub(1, Ordering::SeqCst);
+            counter.pending_delta.fetch_sub(1, Ordering::SeqCst);
+        }
     }
 
-    /// Check rate limit by IP address (for unauthenticated requests)
-    pub async fn check_ip_rate_limit(
+    async fn check_and_increment_window(
         &self,
-        ip: &str,
-        action: &str,
-        limit: u32,
+        user_id: Uuid,
+        limit_type: LimitType,
         window: RateWindow,
-    ) -> Result<bool> {
+        limit: u32,
+    ) -> Result<RateLimitInfo> {
         let window_seconds = window.seconds();
-        let key = format!("ratelimit:ip:{}:{}:{}", ip, action, current_window(window_seconds));
-
-        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let window_id = current_window(window_seconds);
+        let key: CounterKey = (user_id, limit_type, window_seconds);
+
+        let counter = self
+            .counters
+            .entry(key)
+            .or_insert_with(|| Arc::new(LocalCounter::new(window_id)))
+            .clone();
+
+        // Window rolled over since we last touched this counter: the Redis
+        // TTL already expired the old key, so start clean rather than carry
+        // a stale local count across the boundary.
+        if counter.window_id.load(Ordering::SeqCst) != window_id {
+            counter.reset_for_window(window_id, 0);
+        }
 
-        let count: u32 = conn.get(&key).await.unwrap_or(0);
+        let reset_seconds = seconds_until_window_reset(window_seconds);
+        let safe_budget = ((limit as f64) * self.safe_fraction).floor() as u32;
+        let local_count = counter.local_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if local_count <= safe_budget {
+            counter.pending_delta.fetch_add(1, Ordering::SeqCst);
+            return Ok(RateLimitInfo {
+                limited: false,
+                limit,
+                remaining: limit.saturating_sub(local_count),
+                reset_seconds,
+            });
+        }
 
-        if count >= limit {
-            tracing::debug!(
-                ip = ip,
-                action = action,
-                count = count,
-                limit = limit,
-                "IP rate limit exceeded"
-            );
-            return Ok(true); // Rate limited
+        // Approaching the limit: reconcile with Redis for an authoritative answer.
+        self.reconcile(user_id, limit_type, window, &counter).await?;
+        let authoritative = counter.local_count.load(Ordering::SeqCst);
+
+        if authoritative >= limit {
+            return Ok(RateLimitInfo {
+                limited: true,
+                limit,
+                remaining: 0,
+                reset_seconds,
+            });
         }
 
-        Ok(false)
+        counter.pending_delta.fetch_add(1, Ordering::SeqCst);
+        let new_count = counter.local_count.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(RateLimitInfo {
+            limited: false,
+            limit,
+            remaining: limit.saturating_sub(new_count),
+            reset_seconds,
+        })
     }
 
-    /// Increment IP-based rate limit counter
-    pub async fn increment_ip(&self, ip: &str, action: &str, window: RateWindow) -> Result<()> {
+    /// Flush this counter's pending delta to Redis and reset local state to
+    /// the authoritative sliding-window estimate, rounded up so the local
+    /// fast path stays at least as conservative as the real count.
+    async fn reconcile(
+        &self,
+        user_id: Uuid,
+        limit_type: LimitType,
+        window: RateWindow,
+        counter: &LocalCounter,
+    ) -> Result<()> {
         let window_seconds = window.seconds();
-        let key = format!("ratelimit:ip:{}:{}:{}", ip, action, current_window(window_seconds));
+        let redis_key = format!("ratelimit:{}:{}:{}", user_id, limit_type.as_key(), window_seconds);
+        let window_id = current_window(window_seconds);
+        let delta = counter.pending_delta.swap(0, Ordering::SeqCst);
 
         let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let new_count = roll_sliding_window(&mut conn, &redis_key, window_seconds, window_id, delta).await?;
+
+        counter.local_count.store(new_count, Ordering::SeqCst);
+        Ok(())
+    }
+}
 
-        let count: u32 = conn.get(&key).await.unwrap_or(0);
-        let _: () = conn.incr(&key, 1).await?;
+/// Flush every tracked counter's pending delta to Redis. Run periodically so
+/// admitted-but-unflushed requests aren't lost if the process restarts
+/// between a counter crossing its safe budget and the next reconcile.
+async fn flush_all(
+    cache: &RedisCache,
+    counters: &DashMap<CounterKey, Arc<LocalCounter>>,
+) -> Result<()> {
+    let mut conn = cache.client().get_multiplexed_async_connection().await?;
+
+    for entry in counters.iter() {
+        let (user_id, limit_type, window_seconds) = *entry.key();
+        let counter = entry.value();
+        let delta = counter.pending_delta.swap(0, Ordering::SeqCst);
+        if delta == 0 {
+            continue;
+        }
 
-        if count == 0 {
-            let _: () = conn.expire(&key, window_seconds as i64).await?;
+        let window_id = current_window(window_seconds);
+        if counter.window_id.load(Ordering::SeqCst) != window_id {
+            // Rolled over between ticks; the delta belongs to an expired
+            // window, so drop it rather than bleed it into the new one.
+            continue;
         }
 
-        Ok(())
+        let redis_key = format!("ratelimit:{}:{}:{}", user_id, limit_type.as_key(), window_seconds);
+        let new_count = roll_sliding_window(&mut conn, &redis_key, window_seconds, window_id, delta).await?;
+        counter.local_count.store(new_count, Ordering::SeqCst);
     }
+
+    Ok(())
 }
@@ -1,152 +1,112 @@
 use anyhow::Result;
-use sqlx::Row;
+use futures::future::try_join_all;
+use std::sync::Arc;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::post::{Post, PostVisibility};
-use crate::domain::user::User;
-use crate::infra::db::Db;
+use crate::app::search_store::{RankedPost, RankedUser, SearchStore};
 
+/// Upper bound on how many requests a single `search_all` call can batch, so
+/// one HTTP request can't fan out into an unbounded number of concurrent
+/// store queries.
+const MAX_BATCH_SIZE: usize = 20;
+
+/// Which entity kind(s) a [`SearchRequest`] wants back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchEntity {
+    Users,
+    Posts,
+    Both,
+}
+
+/// One query within a [`SearchService::search_all`] batch.
+#[derive(Clone, Debug)]
+pub struct SearchRequest {
+    pub entity: SearchEntity,
+    pub query: String,
+    pub cursor: Option<(f64, OffsetDateTime, Uuid)>,
+    pub limit: i64,
+}
+
+/// The result of one [`SearchRequest`]. Whichever half wasn't requested
+/// (per `entity`) comes back as an empty `Vec` rather than `Option::None`,
+/// so callers can treat every response uniformly.
+#[derive(Debug, Default)]
+pub struct SearchResponse {
+    pub users: Vec<RankedUser>,
+    pub posts: Vec<RankedPost>,
+}
+
+/// Ranked user/post search. Delegates to whichever `SearchStore` backend was
+/// selected at startup (`Postgres`/`Sqlite`, see `AppConfig::db_backend`) so
+/// this service stays agnostic to the underlying SQL dialect.
 #[derive(Clone)]
 pub struct SearchService {
-    db: Db,
+    store: Arc<dyn SearchStore>,
 }
 
 impl SearchService {
-    pub fn new(db: Db) -> Self {
-        Self { db }
+    pub fn new(store: Arc<dyn SearchStore>) -> Self {
+        Self { store }
     }
 
     pub async fn search_users(
         &self,
         query: &str,
-        cursor: Option<(OffsetDateTime, Uuid)>,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
         limit: i64,
-    ) -> Result<Vec<User>> {
-        let pattern = format!("%{}%", escape_like_pattern(query));
-        let rows = match cursor {
-            Some((created_at, user_id)) => {
-                sqlx::query(
-                    "SELECT id, handle, email, display_name, bio, avatar_key, created_at \
-                     FROM users \
-                     WHERE (handle ILIKE $1 ESCAPE '\\' OR display_name ILIKE $1 ESCAPE '\\') \
-                       AND (created_at < $2 OR (created_at = $2 AND id < $3)) \
-                     ORDER BY created_at DESC, id DESC \
-                     LIMIT $4",
-                )
-                .bind(&pattern)
-                .bind(created_at)
-                .bind(user_id)
-                .bind(limit)
-                .fetch_all(self.db.pool())
-                .await?
-            }
-            None => {
-                sqlx::query(
-                    "SELECT id, handle, email, display_name, bio, avatar_key, created_at \
-                     FROM users \
-                     WHERE handle ILIKE $1 ESCAPE '\\' OR display_name ILIKE $1 ESCAPE '\\' \
-                     ORDER BY created_at DESC, id DESC \
-                     LIMIT $2",
-                )
-                .bind(&pattern)
-                .bind(limit)
-                .fetch_all(self.db.pool())
-                .await?
-            }
-        };
-
-        let mut users = Vec::with_capacity(rows.len());
-        for row in rows {
-            users.push(User {
-                id: row.get("id"),
-                handle: row.get("handle"),
-                email: row.get("email"),
-                display_name: row.get("display_name"),
-                bio: row.get("bio"),
-                avatar_key: row.get("avatar_key"),
-                created_at: row.get("created_at"),
-            });
-        }
-
-        Ok(users)
+    ) -> Result<Vec<RankedUser>> {
+        self.store.search_users(query, cursor, limit).await
     }
 
     pub async fn search_posts(
         &self,
         query: &str,
-        cursor: Option<(OffsetDateTime, Uuid)>,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
         limit: i64,
-    ) -> Result<Vec<Post>> {
-        let pattern = format!("%{}%", escape_like_pattern(query));
-        let rows = match cursor {
-            Some((created_at, post_id)) => {
-                sqlx::query(
-                    "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
-                     FROM posts p \
-                     JOIN users u ON p.owner_id = u.id \
-                     WHERE p.visibility = 'public' \
-                       AND p.caption ILIKE $1 ESCAPE '\\' \
-                       AND (p.created_at < $2 OR (p.created_at = $2 AND p.id < $3)) \
-                     ORDER BY p.created_at DESC, p.id DESC \
-                     LIMIT $4",
-                )
-                .bind(&pattern)
-                .bind(created_at)
-                .bind(post_id)
-                .bind(limit)
-                .fetch_all(self.db.pool())
-                .await?
-            }
-            None => {
-                sqlx::query(
-                    "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
-                            p.media_id, p.caption, p.visibility::text AS visibility, p.created_at \
-                     FROM posts p \
-                     JOIN users u ON p.owner_id = u.id \
-                     WHERE p.visibility = 'public' AND p.caption ILIKE $1 ESCAPE '\\' \
-                     ORDER BY p.created_at DESC, p.id DESC \
-                     LIMIT $2",
-                )
-                .bind(&pattern)
-                .bind(limit)
-                .fetch_all(self.db.pool())
-                .await?
-            }
-        };
+    ) -> Result<Vec<RankedPost>> {
+        self.store.search_posts(query, cursor, limit).await
+    }
 
-        let mut posts = Vec::with_capacity(rows.len());
-        for row in rows {
-            let visibility: String = row.get("visibility");
-            let visibility = PostVisibility::from_db(&visibility)
-                .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
-            posts.push(Post {
-                id: row.get("id"),
-                owner_id: row.get("owner_id"),
-                owner_handle: Some(row.get("owner_handle")),
-                owner_display_name: Some(row.get("owner_display_name")),
-                media_id: row.get("media_id"),
-                caption: row.get("caption"),
-                visibility,
-                created_at: row.get("created_at"),
-            });
+    /// Runs a batch of independent search requests concurrently against the
+    /// store (e.g. a combined search UI asking for users and posts across
+    /// several terms in one round trip), returning one [`SearchResponse`]
+    /// per request in the same order. Each request's user/post lookups also
+    /// run concurrently with each other.
+    pub async fn search_all(&self, queries: Vec<SearchRequest>) -> Result<Vec<SearchResponse>> {
+        if queries.len() > MAX_BATCH_SIZE {
+            return Err(anyhow::anyhow!(
+                "batch of {} queries exceeds max batch size of {}",
+                queries.len(),
+                MAX_BATCH_SIZE
+            ));
         }
 
-        Ok(posts)
-    }
-}
+        try_join_all(queries.into_iter().map(|request| async move {
+            let (users, posts) = futures::future::try_join(
+                async {
+                    if matches!(request.entity, SearchEntity::Users | SearchEntity::Both) {
+                        self.store
+                            .search_users(&request.query, request.cursor, request.limit)
+                            .await
+                    } else {
+                        Ok(Vec::new())
+                    }
+                },
+                async {
+                    if matches!(request.entity, SearchEntity::Posts | SearchEntity::Both) {
+                        self.store
+                            .search_posts(&request.query, request.cursor, request.limit)
+                            .await
+                    } else {
+                        Ok(Vec::new())
+                    }
+                },
+            )
+            .await?;
 
-fn escape_like_pattern(input: &str) -> String {
-    let mut escaped = String::with_capacity(input.len());
-    for ch in input.chars() {
-        match ch {
-            '%' | '_' | '\\' => {
-                escaped.push('\\');
-                escaped.push(ch);
-            }
-            _ => escaped.push(ch),
-        }
+            Ok::<_, anyhow::Error>(SearchResponse { users, posts })
+        }))
+        .await
     }
-    escaped
 }
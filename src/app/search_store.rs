@@ -0,0 +1,874 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration as StdDuration, Instant};
+
+use anyhow::Result;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Row, SqlitePool};
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, Value, FAST, STORED, TEXT};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, Term};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::app::media::list_attachments_for_posts;
+use crate::app::posts::list_mentions_for_posts;
+use crate::domain::post::{Post, PostVisibility};
+use crate::domain::user::User;
+use crate::infra::db::Db;
+
+/// A typo-tolerant relevance threshold below which a row is considered
+/// unrelated to the query and excluded entirely.
+const SIMILARITY_THRESHOLD: f64 = 0.2;
+
+/// A user search hit paired with the relevance score it matched at, so
+/// callers can thread the score into the next page's cursor.
+pub struct RankedUser {
+    pub user: User,
+    pub rank: f64,
+}
+
+/// A post search hit paired with the relevance score it matched at, so
+/// callers can thread the score into the next page's cursor.
+pub struct RankedPost {
+    pub post: Post,
+    pub rank: f64,
+}
+
+/// Backend for `SearchService`'s queries. Each implementation owns its own
+/// SQL dialect (Postgres full-text/trigram vs. SQLite substring matching) so
+/// `SearchService` itself stays dialect-agnostic -- mirrors how
+/// `infra::mailer::Mailer`/`infra::push_sender::PushSender` keep a single
+/// network-bound concern behind a trait.
+#[axum::async_trait]
+pub trait SearchStore: Send + Sync {
+    async fn search_users(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedUser>>;
+
+    async fn search_posts(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedPost>>;
+
+    /// Pushes a created/edited post into the store's index. The SQL-backed
+    /// stores read `search_vector`/the caption column live off `posts`
+    /// itself, so they have nothing to do here; only an out-of-band index
+    /// (`TantivySearchStore`) needs this pushed explicitly on every write.
+    async fn index_post(&self, _post: &Post) -> Result<()> {
+        Ok(())
+    }
+
+    /// Removes a post from the store's index (deletion or moderation
+    /// takedown). See `index_post` for why the SQL-backed stores no-op here.
+    async fn remove_post(&self, _post_id: Uuid) -> Result<()> {
+        Ok(())
+    }
+
+    /// Pushes a created/edited user into the store's index. See `index_post`.
+    async fn index_user(&self, _user: &User) -> Result<()> {
+        Ok(())
+    }
+
+    /// Removes a user from the store's index. See `index_post`.
+    async fn remove_user(&self, _user_id: Uuid) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Production backend: ranked full-text (`websearch_to_tsquery`/`ts_rank_cd`)
+/// with a `pg_trgm` `similarity` fallback for typo/prefix tolerance.
+pub struct PostgresSearchStore {
+    db: Db,
+}
+
+impl PostgresSearchStore {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+#[axum::async_trait]
+impl SearchStore for PostgresSearchStore {
+    async fn search_users(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedUser>> {
+        let rows = match cursor {
+            Some((rank, created_at, user_id)) => {
+                sqlx::query(
+                    "WITH scored AS ( \
+                        SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private, \
+                               CASE \
+                                   WHEN search_vector @@ websearch_to_tsquery('simple', $1) \
+                                       THEN ts_rank_cd(search_vector, websearch_to_tsquery('simple', $1)) + 1.0 \
+                                   ELSE similarity(handle || ' ' || display_name, $1) \
+                               END AS rank \
+                        FROM users \
+                        WHERE deleted_at IS NULL \
+                          AND suspended_at IS NULL \
+                          AND (search_vector @@ websearch_to_tsquery('simple', $1) \
+                               OR similarity(handle || ' ' || display_name, $1) > $2) \
+                     ) \
+                     SELECT * FROM scored \
+                     WHERE rank < $3 OR (rank = $3 AND (created_at < $4 OR (created_at = $4 AND id < $5))) \
+                     ORDER BY rank DESC, created_at DESC, id DESC \
+                     LIMIT $6",
+                )
+                .bind(query)
+                .bind(SIMILARITY_THRESHOLD)
+                .bind(rank)
+                .bind(created_at)
+                .bind(user_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "WITH scored AS ( \
+                        SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private, \
+                               CASE \
+                                   WHEN search_vector @@ websearch_to_tsquery('simple', $1) \
+                                       THEN ts_rank_cd(search_vector, websearch_to_tsquery('simple', $1)) + 1.0 \
+                                   ELSE similarity(handle || ' ' || display_name, $1) \
+                               END AS rank \
+                        FROM users \
+                        WHERE deleted_at IS NULL \
+                          AND suspended_at IS NULL \
+                          AND (search_vector @@ websearch_to_tsquery('simple', $1) \
+                               OR similarity(handle || ' ' || display_name, $1) > $2) \
+                     ) \
+                     SELECT * FROM scored \
+                     ORDER BY rank DESC, created_at DESC, id DESC \
+                     LIMIT $3",
+                )
+                .bind(query)
+                .bind(SIMILARITY_THRESHOLD)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        let mut users = Vec::with_capacity(rows.len());
+        for row in rows {
+            users.push(RankedUser {
+                user: User {
+                    id: row.get("id"),
+                    handle: row.get("handle"),
+                    email: row.get("email"),
+                    display_name: row.get("display_name"),
+                    bio: row.get("bio"),
+                    avatar_key: row.get("avatar_key"),
+                    avatar_url: None,
+                    created_at: row.get("created_at"),
+                    is_locked: row.get("is_private"),
+                },
+                rank: row.get("rank"),
+            });
+        }
+
+        Ok(users)
+    }
+
+    async fn search_posts(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedPost>> {
+        let rows = match cursor {
+            Some((rank, created_at, post_id)) => {
+                sqlx::query(
+                    "WITH scored AS ( \
+                        SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                               p.caption, p.visibility::text AS visibility, p.created_at, \
+                               p.in_reply_to_id, p.repost_of_id, \
+                               CASE \
+                                   WHEN p.search_vector @@ websearch_to_tsquery('simple', $1) \
+                                       THEN ts_rank_cd(p.search_vector, websearch_to_tsquery('simple', $1)) + 1.0 \
+                                   ELSE similarity(coalesce(p.caption, ''), $1) \
+                               END AS rank \
+                        FROM posts p \
+                        JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                        WHERE p.visibility = 'public' \
+                          AND p.removed_at IS NULL \
+                          AND (p.search_vector @@ websearch_to_tsquery('simple', $1) \
+                               OR similarity(coalesce(p.caption, ''), $1) > $2) \
+                     ) \
+                     SELECT * FROM scored \
+                     WHERE rank < $3 OR (rank = $3 AND (created_at < $4 OR (created_at = $4 AND id < $5))) \
+                     ORDER BY rank DESC, created_at DESC, id DESC \
+                     LIMIT $6",
+                )
+                .bind(query)
+                .bind(SIMILARITY_THRESHOLD)
+                .bind(rank)
+                .bind(created_at)
+                .bind(post_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "WITH scored AS ( \
+                        SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                               p.caption, p.visibility::text AS visibility, p.created_at, \
+                               p.in_reply_to_id, p.repost_of_id, \
+                               CASE \
+                                   WHEN p.search_vector @@ websearch_to_tsquery('simple', $1) \
+                                       THEN ts_rank_cd(p.search_vector, websearch_to_tsquery('simple', $1)) + 1.0 \
+                                   ELSE similarity(coalesce(p.caption, ''), $1) \
+                               END AS rank \
+                        FROM posts p \
+                        JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                        WHERE p.visibility = 'public' \
+                          AND p.removed_at IS NULL \
+                          AND (p.search_vector @@ websearch_to_tsquery('simple', $1) \
+                               OR similarity(coalesce(p.caption, ''), $1) > $2) \
+                     ) \
+                     SELECT * FROM scored \
+                     ORDER BY rank DESC, created_at DESC, id DESC \
+                     LIMIT $3",
+                )
+                .bind(query)
+                .bind(SIMILARITY_THRESHOLD)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        let post_ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+        let mut attachments_by_post = list_attachments_for_posts(self.db.pool(), &post_ids).await?;
+        let mut mentions_by_post = list_mentions_for_posts(self.db.pool(), &post_ids).await?;
+
+        let mut posts = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: Uuid = row.get("id");
+            let visibility: String = row.get("visibility");
+            let visibility = PostVisibility::from_db(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+            posts.push(RankedPost {
+                post: Post {
+                    id,
+                    owner_id: row.get("owner_id"),
+                    owner_handle: Some(row.get("owner_handle")),
+                    owner_display_name: Some(row.get("owner_display_name")),
+                    attachments: attachments_by_post.remove(&id).unwrap_or_default(),
+                    mentions: mentions_by_post.remove(&id).unwrap_or_default(),
+                    caption: row.get("caption"),
+                    visibility,
+                    in_reply_to_id: row.get("in_reply_to_id"),
+                    repost_of_id: row.get("repost_of_id"),
+                    reposted_post: None,
+                    created_at: row.get("created_at"),
+                    owner_avatar_key: None,
+                    owner_avatar_url: None,
+                },
+                rank: row.get("rank"),
+            });
+        }
+
+        Ok(posts)
+    }
+}
+
+/// Single-binary backend: plain `LIKE ... ESCAPE` substring matching, no
+/// full-text/trigram extensions available. Every match ranks equally (`rank`
+/// is always `0.0`), so pages order on `created_at`/`id` alone -- good enough
+/// for a lightweight deployment that doesn't need relevance ranking.
+/// Attachments and mentions aren't hydrated here: those batch helpers in
+/// `app::media`/`app::posts` are themselves Postgres-only, so a post search
+/// result from this backend always has empty `attachments`/`mentions`.
+pub struct SqliteSearchStore {
+    pool: SqlitePool,
+}
+
+impl SqliteSearchStore {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let pool = SqlitePoolOptions::new().connect(database_url).await?;
+        Ok(Self { pool })
+    }
+}
+
+#[axum::async_trait]
+impl SearchStore for SqliteSearchStore {
+    async fn search_users(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedUser>> {
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        let rows = match cursor {
+            Some((_rank, created_at, user_id)) => {
+                sqlx::query(
+                    "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private \
+                     FROM users \
+                     WHERE deleted_at IS NULL \
+                       AND suspended_at IS NULL \
+                       AND (handle LIKE ? ESCAPE '\\' OR display_name LIKE ? ESCAPE '\\') \
+                       AND (created_at < ? OR (created_at = ? AND id < ?)) \
+                     ORDER BY created_at DESC, id DESC \
+                     LIMIT ?",
+                )
+                .bind(&pattern)
+                .bind(&pattern)
+                .bind(created_at)
+                .bind(created_at)
+                .bind(user_id.to_string())
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private \
+                     FROM users \
+                     WHERE deleted_at IS NULL \
+                       AND suspended_at IS NULL \
+                       AND (handle LIKE ? ESCAPE '\\' OR display_name LIKE ? ESCAPE '\\') \
+                     ORDER BY created_at DESC, id DESC \
+                     LIMIT ?",
+                )
+                .bind(&pattern)
+                .bind(&pattern)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut users = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: String = row.get("id");
+            users.push(RankedUser {
+                user: User {
+                    id: Uuid::parse_str(&id)?,
+                    handle: row.get("handle"),
+                    email: row.get("email"),
+                    display_name: row.get("display_name"),
+                    bio: row.get("bio"),
+                    avatar_key: row.get("avatar_key"),
+                    avatar_url: None,
+                    created_at: row.get("created_at"),
+                    is_locked: row.get("is_private"),
+                },
+                rank: 0.0,
+            });
+        }
+
+        Ok(users)
+    }
+
+    async fn search_posts(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedPost>> {
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        let rows = match cursor {
+            Some((_rank, created_at, post_id)) => {
+                sqlx::query(
+                    "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                            p.caption, p.visibility AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
+                     FROM posts p \
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                     WHERE p.visibility = 'public' \
+                       AND p.removed_at IS NULL \
+                       AND p.caption LIKE ? ESCAPE '\\' \
+                       AND (p.created_at < ? OR (p.created_at = ? AND p.id < ?)) \
+                     ORDER BY p.created_at DESC, p.id DESC \
+                     LIMIT ?",
+                )
+                .bind(&pattern)
+                .bind(created_at)
+                .bind(created_at)
+                .bind(post_id.to_string())
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                            p.caption, p.visibility AS visibility, p.created_at, \
+                            p.in_reply_to_id, p.repost_of_id \
+                     FROM posts p \
+                     JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+                     WHERE p.visibility = 'public' AND p.removed_at IS NULL AND p.caption LIKE ? ESCAPE '\\' \
+                     ORDER BY p.created_at DESC, p.id DESC \
+                     LIMIT ?",
+                )
+                .bind(&pattern)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut posts = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: String = row.get("id");
+            let owner_id: String = row.get("owner_id");
+            let visibility: String = row.get("visibility");
+            let visibility = PostVisibility::from_db(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+            let in_reply_to_id: Option<String> = row.get("in_reply_to_id");
+            let repost_of_id: Option<String> = row.get("repost_of_id");
+            posts.push(RankedPost {
+                post: Post {
+                    id: Uuid::parse_str(&id)?,
+                    owner_id: Uuid::parse_str(&owner_id)?,
+                    owner_handle: Some(row.get("owner_handle")),
+                    owner_display_name: Some(row.get("owner_display_name")),
+                    attachments: Vec::new(),
+                    mentions: Vec::new(),
+                    caption: row.get("caption"),
+                    visibility,
+                    in_reply_to_id: in_reply_to_id.map(|v| Uuid::parse_str(&v)).transpose()?,
+                    repost_of_id: repost_of_id.map(|v| Uuid::parse_str(&v)).transpose()?,
+                    reposted_post: None,
+                    created_at: row.get("created_at"),
+                    owner_avatar_key: None,
+                    owner_avatar_url: None,
+                },
+                rank: 0.0,
+            });
+        }
+
+        Ok(posts)
+    }
+}
+
+/// How long a collection lets inserts/deletes pile up before `commit_if_due`
+/// actually flushes them to the reader's segment -- avoids paying
+/// `IndexWriter::commit`'s cost on every single post/user write in exchange
+/// for a short window where a just-written document isn't searchable yet.
+const COMMIT_DEBOUNCE: StdDuration = StdDuration::from_millis(750);
+
+/// Soft cap on how many top-ranked hits a single query pulls from Tantivy
+/// before the keyset cursor filter below is applied. Tantivy's `TopDocs`
+/// collector has no "resume after (score, id)" the way a SQL keyset query
+/// does, so pagination works by over-fetching a window of the highest-ranked
+/// hits and discarding everything at or before the cursor -- a query with
+/// more than this many hits tied at the same top score could in principle
+/// skip a result past the window, the same relevance-over-exhaustiveness
+/// trade-off `SIMILARITY_THRESHOLD` already makes for the SQL backends.
+const SEARCH_WINDOW: usize = 500;
+
+/// One Tantivy index plus the bookkeeping `TantivySearchStore` needs to
+/// write to and page through it: every collection (users, posts) is shaped
+/// the same way, just with a different schema and text fields.
+struct TantivyCollection {
+    index: Index,
+    reader: IndexReader,
+    writer: StdMutex<IndexWriter>,
+    id_field: Field,
+    created_at_field: Field,
+    dirty: AtomicBool,
+    last_commit: StdMutex<Instant>,
+}
+
+impl TantivyCollection {
+    fn open(schema: Schema, id_field: Field, created_at_field: Field) -> Result<Self> {
+        let index = Index::create_in_ram(schema);
+        let writer = index.writer(15_000_000)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        Ok(Self {
+            index,
+            reader,
+            writer: StdMutex::new(writer),
+            id_field,
+            created_at_field,
+            dirty: AtomicBool::new(false),
+            last_commit: StdMutex::new(Instant::now()),
+        })
+    }
+
+    /// Tantivy has no in-place update; "updating" a document already in the
+    /// index is a `delete_term` of its old `id` posting followed by
+    /// re-adding it under the same id.
+    fn replace(&self, id: Uuid, document: tantivy::Document) {
+        let writer = self.writer.lock().unwrap();
+        writer.delete_term(Term::from_field_bytes(self.id_field, id.as_bytes()));
+        let _ = writer.add_document(document);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    fn delete(&self, id: Uuid) {
+        let writer = self.writer.lock().unwrap();
+        writer.delete_term(Term::from_field_bytes(self.id_field, id.as_bytes()));
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    fn commit_if_due(&self) -> Result<()> {
+        if !self.dirty.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut last_commit = self.last_commit.lock().unwrap();
+        if last_commit.elapsed() < COMMIT_DEBOUNCE {
+            return Ok(());
+        }
+        self.writer.lock().unwrap().commit()?;
+        self.dirty.store(false, Ordering::Relaxed);
+        *last_commit = Instant::now();
+        Ok(())
+    }
+
+    /// An index nothing has ever been written to -- `HybridSearchStore`'s
+    /// signal to fall back to the SQL-backed store instead of returning an
+    /// empty page for every query.
+    fn is_cold(&self) -> bool {
+        self.reader.searcher().num_docs() == 0
+    }
+
+    /// Runs `query` over `text_fields`, ranked by BM25, and returns
+    /// `(id, created_at, rank)` triples in descending-rank order with the
+    /// `cursor` keyset already applied -- mirrors the
+    /// `rank < $3 OR (rank = $3 AND (created_at < $4 OR ...))` predicate the
+    /// SQL-backed stores use for the same page boundary.
+    fn search(
+        &self,
+        query: &str,
+        text_fields: &[Field],
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<(Uuid, OffsetDateTime, f64)>> {
+        let searcher = self.reader.searcher();
+        let query_parser = QueryParser::for_index(&self.index, text_fields.to_vec());
+        let parsed_query = query_parser.parse_query(query)?;
+        let top_docs = searcher.search(&parsed_query, &TopDocs::with_limit(SEARCH_WINDOW))?;
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, doc_address) in top_docs {
+            let retrieved: tantivy::Document = searcher.doc(doc_address)?;
+            let id_bytes = retrieved
+                .get_first(self.id_field)
+                .and_then(Value::as_bytes)
+                .ok_or_else(|| anyhow::anyhow!("indexed document missing id field"))?;
+            let id = Uuid::from_slice(id_bytes)?;
+            let created_at_secs = retrieved
+                .get_first(self.created_at_field)
+                .and_then(Value::as_i64)
+                .ok_or_else(|| anyhow::anyhow!("indexed document missing created_at field"))?;
+            let created_at = OffsetDateTime::from_unix_timestamp(created_at_secs)?;
+            hits.push((id, created_at, score as f64));
+        }
+
+        let hits: Vec<_> = match cursor {
+            Some((cursor_rank, cursor_created_at, cursor_id)) => hits
+                .into_iter()
+                .filter(|(id, created_at, rank)| {
+                    *rank < cursor_rank
+                        || (*rank == cursor_rank
+                            && (*created_at < cursor_created_at
+                                || (*created_at == cursor_created_at && *id < cursor_id)))
+                })
+                .collect(),
+            None => hits,
+        };
+
+        Ok(hits.into_iter().take(limit.max(0) as usize).collect())
+    }
+}
+
+/// Real inverted-index search: a `tantivy` index per entity kind, ranked by
+/// BM25 instead of the SQL backends' `ts_rank_cd`/substring matching. Held
+/// behind `HybridSearchStore` rather than used directly, so a cold index
+/// (nothing indexed yet on a freshly-started process -- there's no on-disk
+/// persistence to warm from) doesn't return empty search results.
+pub struct TantivySearchStore {
+    db: Db,
+    users: TantivyCollection,
+    user_handle_field: Field,
+    user_display_name_field: Field,
+    posts: TantivyCollection,
+    post_body_field: Field,
+}
+
+impl TantivySearchStore {
+    pub fn new(db: Db) -> Result<Self> {
+        let mut user_schema = Schema::builder();
+        let user_id_field = user_schema.add_bytes_field("id", STORED);
+        let user_created_at_field = user_schema.add_i64_field("created_at", STORED | FAST);
+        let user_handle_field = user_schema.add_text_field("handle", TEXT);
+        let user_display_name_field = user_schema.add_text_field("display_name", TEXT);
+        let users =
+            TantivyCollection::open(user_schema.build(), user_id_field, user_created_at_field)?;
+
+        let mut post_schema = Schema::builder();
+        let post_id_field = post_schema.add_bytes_field("id", STORED);
+        let post_created_at_field = post_schema.add_i64_field("created_at", STORED | FAST);
+        let post_body_field = post_schema.add_text_field("body", TEXT);
+        let posts =
+            TantivyCollection::open(post_schema.build(), post_id_field, post_created_at_field)?;
+
+        Ok(Self {
+            db,
+            users,
+            user_handle_field,
+            user_display_name_field,
+            posts,
+            post_body_field,
+        })
+    }
+
+    /// Flushes any collection that's had writes pending for at least
+    /// `COMMIT_DEBOUNCE`. Called on a timer from
+    /// `jobs::search_index_committer::run` rather than after every single
+    /// `index_post`/`index_user` call.
+    pub fn commit_if_due(&self) -> Result<()> {
+        self.users.commit_if_due()?;
+        self.posts.commit_if_due()?;
+        Ok(())
+    }
+}
+
+#[axum::async_trait]
+impl SearchStore for TantivySearchStore {
+    async fn search_users(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedUser>> {
+        let hits = self.users.search(
+            query,
+            &[self.user_handle_field, self.user_display_name_field],
+            cursor,
+            limit,
+        )?;
+        if hits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<Uuid> = hits.iter().map(|(id, _, _)| *id).collect();
+        let rows = sqlx::query(
+            "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private FROM users \
+             WHERE id = ANY($1) AND deleted_at IS NULL AND suspended_at IS NULL",
+        )
+        .bind(&ids)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut by_id: HashMap<Uuid, User> = rows
+            .into_iter()
+            .map(|row| {
+                let user = User {
+                    id: row.get("id"),
+                    handle: row.get("handle"),
+                    email: row.get("email"),
+                    display_name: row.get("display_name"),
+                    bio: row.get("bio"),
+                    avatar_key: row.get("avatar_key"),
+                    avatar_url: None,
+                    created_at: row.get("created_at"),
+                    is_locked: row.get("is_private"),
+                };
+                (user.id, user)
+            })
+            .collect();
+
+        // Tantivy's rank order is preserved here -- the SQL lookup above
+        // just hydrates full rows, it doesn't re-rank. A hit whose row
+        // didn't come back (deleted/suspended since the index last saw it)
+        // is dropped rather than surfaced as a stale result.
+        Ok(hits
+            .into_iter()
+            .filter_map(|(id, _, rank)| by_id.remove(&id).map(|user| RankedUser { user, rank }))
+            .collect())
+    }
+
+    async fn search_posts(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedPost>> {
+        let hits = self.posts.search(query, &[self.post_body_field], cursor, limit)?;
+        if hits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<Uuid> = hits.iter().map(|(id, _, _)| *id).collect();
+        let rows = sqlx::query(
+            "SELECT p.id, p.owner_id, u.handle AS owner_handle, u.display_name AS owner_display_name, \
+                    p.caption, p.visibility::text AS visibility, p.created_at, \
+                    p.in_reply_to_id, p.repost_of_id \
+             FROM posts p \
+             JOIN users u ON p.owner_id = u.id AND u.deleted_at IS NULL AND u.suspended_at IS NULL \
+             WHERE p.id = ANY($1) AND p.visibility = 'public' AND p.removed_at IS NULL",
+        )
+        .bind(&ids)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut attachments_by_post = list_attachments_for_posts(self.db.pool(), &ids).await?;
+        let mut mentions_by_post = list_mentions_for_posts(self.db.pool(), &ids).await?;
+
+        let mut by_id: HashMap<Uuid, Post> = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let id: Uuid = row.get("id");
+            let visibility: String = row.get("visibility");
+            let visibility = PostVisibility::from_db(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("unknown post visibility: {}", visibility))?;
+            by_id.insert(
+                id,
+                Post {
+                    id,
+                    owner_id: row.get("owner_id"),
+                    owner_handle: Some(row.get("owner_handle")),
+                    owner_display_name: Some(row.get("owner_display_name")),
+                    attachments: attachments_by_post.remove(&id).unwrap_or_default(),
+                    mentions: mentions_by_post.remove(&id).unwrap_or_default(),
+                    caption: row.get("caption"),
+                    visibility,
+                    in_reply_to_id: row.get("in_reply_to_id"),
+                    repost_of_id: row.get("repost_of_id"),
+                    reposted_post: None,
+                    created_at: row.get("created_at"),
+                    owner_avatar_key: None,
+                    owner_avatar_url: None,
+                },
+            );
+        }
+
+        Ok(hits
+            .into_iter()
+            .filter_map(|(id, _, rank)| by_id.remove(&id).map(|post| RankedPost { post, rank }))
+            .collect())
+    }
+
+    async fn index_post(&self, post: &Post) -> Result<()> {
+        // Only public posts are ever returned by `search_posts`'s SQL
+        // hydration above, so indexing anything else is dead weight --
+        // worse, a post edited *to* a narrower visibility would otherwise
+        // linger in the index under its old, now-stale `Public` state.
+        if !matches!(post.visibility, PostVisibility::Public) {
+            self.posts.delete(post.id);
+            return Ok(());
+        }
+
+        let mut document = tantivy::Document::default();
+        document.add_bytes(self.posts.id_field, post.id.as_bytes().to_vec());
+        document.add_i64(self.posts.created_at_field, post.created_at.unix_timestamp());
+        document.add_text(self.post_body_field, post.caption.as_deref().unwrap_or_default());
+        self.posts.replace(post.id, document);
+        Ok(())
+    }
+
+    async fn remove_post(&self, post_id: Uuid) -> Result<()> {
+        self.posts.delete(post_id);
+        Ok(())
+    }
+
+    async fn index_user(&self, user: &User) -> Result<()> {
+        let mut document = tantivy::Document::default();
+        document.add_bytes(self.users.id_field, user.id.as_bytes().to_vec());
+        document.add_i64(self.users.created_at_field, user.created_at.unix_timestamp());
+        document.add_text(self.user_handle_field, &user.handle);
+        document.add_text(self.user_display_name_field, &user.display_name);
+        self.users.replace(user.id, document);
+        Ok(())
+    }
+
+    async fn remove_user(&self, user_id: Uuid) -> Result<()> {
+        self.users.delete(user_id);
+        Ok(())
+    }
+}
+
+/// Fronts the real Tantivy index (`TantivySearchStore`) in front of whichever
+/// SQL-backed store was selected at startup. Every query tries the index
+/// first; while it's still cold (a freshly-started process -- the index is
+/// in-memory only, so there's nothing to warm it from on restart) queries
+/// fall back to the SQL backend's own relevance ranking until the index
+/// catches up via `index_post`/`index_user`.
+pub struct HybridSearchStore {
+    tantivy: std::sync::Arc<TantivySearchStore>,
+    fallback: std::sync::Arc<dyn SearchStore>,
+}
+
+impl HybridSearchStore {
+    pub fn new(
+        tantivy: std::sync::Arc<TantivySearchStore>,
+        fallback: std::sync::Arc<dyn SearchStore>,
+    ) -> Self {
+        Self { tantivy, fallback }
+    }
+}
+
+#[axum::async_trait]
+impl SearchStore for HybridSearchStore {
+    async fn search_users(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedUser>> {
+        if self.tantivy.users.is_cold() {
+            return self.fallback.search_users(query, cursor, limit).await;
+        }
+        self.tantivy.search_users(query, cursor, limit).await
+    }
+
+    async fn search_posts(
+        &self,
+        query: &str,
+        cursor: Option<(f64, OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<RankedPost>> {
+        if self.tantivy.posts.is_cold() {
+            return self.fallback.search_posts(query, cursor, limit).await;
+        }
+        self.tantivy.search_posts(query, cursor, limit).await
+    }
+
+    async fn index_post(&self, post: &Post) -> Result<()> {
+        self.tantivy.index_post(post).await
+    }
+
+    async fn remove_post(&self, post_id: Uuid) -> Result<()> {
+        self.tantivy.remove_post(post_id).await
+    }
+
+    async fn index_user(&self, user: &User) -> Result<()> {
+        self.tantivy.index_user(user).await
+    }
+
+    async fn remove_user(&self, user_id: Uuid) -> Result<()> {
+        self.tantivy.remove_user(user_id).await
+    }
+}
+
+fn escape_like_pattern(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '%' | '_' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
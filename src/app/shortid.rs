@@ -0,0 +1,76 @@
+//! Short, URL-friendly public identifiers that stand in for the internal
+//! `Uuid` primary keys used in path parameters and response bodies (post,
+//! comment, and media ids today).
+//!
+//! Encoding XORs the id's 128 bits against a deployment-specific salt (so
+//! two deployments never mint the same public id for the same row) and
+//! writes the result out in a 62-symbol alphabet that's itself shuffled by
+//! the salt. This is obfuscation, not encryption -- like hashids, it keeps
+//! raw database keys off of shareable links and makes sequential
+//! enumeration impractical, but anyone who knows the salt can still invert
+//! it. `AppState::shortid_salt` is the single configured salt so ids are
+//! stable for the lifetime of a deployment.
+
+use uuid::Uuid;
+
+const ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+fn shuffled_alphabet(salt: &[u8; 32]) -> [u8; 62] {
+    let mut alphabet = *ALPHABET;
+    let len = alphabet.len();
+    for i in (1..len).rev() {
+        let j = (salt[i % salt.len()] as usize + i) % (i + 1);
+        alphabet.swap(i, j);
+    }
+    alphabet
+}
+
+fn salt_to_u128(salt: &[u8; 32]) -> u128 {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&salt[0..16]);
+    u128::from_be_bytes(bytes)
+}
+
+/// Encodes `id` into its short public form.
+pub fn encode(id: Uuid, salt: &[u8; 32]) -> String {
+    let alphabet = shuffled_alphabet(salt);
+    let mut value = id.as_u128() ^ salt_to_u128(salt);
+    if value == 0 {
+        return (alphabet[0] as char).to_string();
+    }
+
+    let base = alphabet.len() as u128;
+    let mut chars = Vec::new();
+    while value > 0 {
+        chars.push(alphabet[(value % base) as usize]);
+        value /= base;
+    }
+    chars.reverse();
+    String::from_utf8(chars).expect("alphabet is ascii")
+}
+
+/// Decodes a short public id minted by [`encode`] back into the `Uuid` it
+/// represents. Returns `None` when `encoded` contains a byte outside this
+/// salt's alphabet or its value overflows 128 bits (which includes,
+/// harmlessly, a raw hyphenated UUID string); other strings decode to some
+/// `Uuid` whether or not `encode` produced them -- callers on the `Uuid`/short-id transition should fall back to
+/// `Uuid::parse_str` themselves when this returns `None`).
+pub fn decode(encoded: &str, salt: &[u8; 32]) -> Option<Uuid> {
+    let alphabet = shuffled_alphabet(salt);
+    let base = alphabet.len() as u128;
+
+    let mut value: u128 = 0;
+    for byte in encoded.bytes() {
+        let digit = alphabet.iter().position(|&c| c == byte)? as u128;
+        value = value.checked_mul(base)?.checked_add(digit)?;
+    }
+    Some(Uuid::from_u128(value ^ salt_to_u128(salt)))
+}
+
+/// Decodes `raw` as a short id first, falling back to a raw UUID string --
+/// the transition path so existing links/clients that still pass a `Uuid`
+/// keep working while new ones get the short form.
+pub fn decode_either(raw: &str, salt: &[u8; 32]) -> Option<Uuid> {
+    decode(raw, salt).or_else(|| Uuid::parse_str(raw).ok())
+}
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use time::OffsetDateTime;
+
+/// The fields of an EIP-4361 "Sign-In with Ethereum" message that we care
+/// about. Fields we don't act on (statement, uri, version, chain id,
+/// resources, ...) are parsed loosely and discarded.
+pub(crate) struct SiweMessage {
+    pub domain: String,
+    pub address: String,
+    pub nonce: String,
+    pub expiration_time: Option<OffsetDateTime>,
+}
+
+/// Parse the subset of an EIP-4361 message we need. Expected shape:
+///
+/// ```text
+/// {domain} wants you to sign in with your Ethereum account:
+/// {address}
+///
+/// {statement}
+///
+/// URI: {uri}
+/// Version: {version}
+/// Chain ID: {chain_id}
+/// Nonce: {nonce}
+/// Issued At: {issued_at}
+/// Expiration Time: {expiration_time}
+/// ```
+pub(crate) fn parse_siwe_message(message: &str) -> Result<SiweMessage> {
+    let mut lines = message.lines();
+
+    let header = lines
+        .next()
+        .ok_or_else(|| anyhow!("empty SIWE message"))?;
+    let domain = header
+        .strip_suffix(" wants you to sign in with your Ethereum account:")
+        .ok_or_else(|| anyhow!("malformed SIWE header"))?
+        .to_string();
+
+    let address = lines
+        .next()
+        .ok_or_else(|| anyhow!("missing address line"))?
+        .trim()
+        .to_string();
+    if !is_plausible_address(&address) {
+        return Err(anyhow!("malformed wallet address"));
+    }
+
+    let mut nonce = None;
+    let mut expiration_time = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Nonce: ") {
+            nonce = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Expiration Time: ") {
+            expiration_time = Some(
+                OffsetDateTime::parse(value.trim(), &time::format_description::well_known::Rfc3339)
+                    .map_err(|_| anyhow!("malformed Expiration Time"))?,
+            );
+        }
+    }
+
+    Ok(SiweMessage {
+        domain,
+        address,
+        nonce: nonce.ok_or_else(|| anyhow!("missing Nonce field"))?,
+        expiration_time,
+    })
+}
+
+pub(crate) fn is_plausible_address(address: &str) -> bool {
+    address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Recover the signer's lowercase `0x...` address from a secp256k1 signature over
+/// the EIP-191 `personal_sign` digest of `message`.
+pub(crate) fn recover_address(message: &str, signature_hex: &str) -> Result<String> {
+    let signature_hex = signature_hex.strip_prefix("0x").unwrap_or(signature_hex);
+    let signature_bytes = hex::decode(signature_hex)
+        .map_err(|_| anyhow!("signature is not valid hex"))?;
+    if signature_bytes.len() != 65 {
+        return Err(anyhow!("signature must be 65 bytes (r, s, v)"));
+    }
+
+    let signature = Signature::from_slice(&signature_bytes[..64])
+        .map_err(|_| anyhow!("invalid signature"))?;
+    let recovery_byte = signature_bytes[64];
+    let recovery_id = RecoveryId::from_byte(normalize_recovery_byte(recovery_byte))
+        .ok_or_else(|| anyhow!("invalid recovery id"))?;
+
+    let digest = eip191_digest(message);
+    let verifying_key = VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id)
+        .map_err(|_| anyhow!("failed to recover signer"))?;
+
+    let uncompressed = verifying_key.to_encoded_point(false);
+    let public_key_bytes = &uncompressed.as_bytes()[1..]; // drop the 0x04 prefix
+
+    let mut hasher = Keccak256::new();
+    hasher.update(public_key_bytes);
+    let hash = hasher.finalize();
+
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+/// EIP-191 `personal_sign` digest: `keccak256("\x19Ethereum Signed Message:\n" + len(message) + message)`.
+pub(crate) fn eip191_digest(message: &str) -> [u8; 32] {
+    let prefix = format!("\x19Ethereum Signed Message:\n{}", message.len());
+    let mut hasher = Keccak256::new();
+    hasher.update(prefix.as_bytes());
+    hasher.update(message.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Wallets commonly send `v` as 27/28 (legacy Ethereum) rather than 0/1.
+pub(crate) fn normalize_recovery_byte(v: u8) -> u8 {
+    match v {
+        27 | 28 => v - 27,
+        other => other,
+    }
+}
+
+/// Render a lowercase `0x...` address in EIP-55 mixed-case checksum form.
+pub(crate) fn to_checksum_address(address_lower: &str) -> String {
+    let hex_part = address_lower.trim_start_matches("0x").to_lowercase();
+
+    let mut hasher = Keccak256::new();
+    hasher.update(hex_part.as_bytes());
+    let hash = hasher.finalize();
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in hex_part.chars().enumerate() {
+        if c.is_ascii_digit() {
+            checksummed.push(c);
+            continue;
+        }
+        // Each hex character of the address is upper-cased if the
+        // corresponding nibble of keccak256(address) is >= 8.
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+
+    checksummed
+}
@@ -1,9 +1,10 @@
 use anyhow::Result;
 use sqlx::Row;
+use std::collections::HashMap;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::user::User;
+use crate::domain::user::{PublicUser, PublicUserRelationship, User};
 use crate::infra::db::Db;
 
 #[derive(Clone)]
@@ -23,6 +24,38 @@ pub struct RelationshipStatus {
     pub is_followed_by: bool,
     pub is_blocking: bool,
     pub is_blocked_by: bool,
+    /// True when the viewer has a `follows` row on `other_id` still sitting
+    /// in `state = 'pending'` (a private account, or a remote actor that
+    /// hasn't sent back an `Accept` yet -- see `FollowState::Pending`).
+    pub is_pending: bool,
+    pub is_muting: bool,
+    pub is_muted_by: bool,
+}
+
+/// Where a follow relationship stands: `Pending` on a private account until
+/// the followee calls `accept_follow`, `Accepted` everywhere else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowState {
+    Pending,
+    Accepted,
+}
+
+impl FollowState {
+    fn as_str(self) -> &'static str {
+        match self {
+            FollowState::Pending => "pending",
+            FollowState::Accepted => "accepted",
+        }
+    }
+}
+
+/// The two counters a follow/unfollow mutation actually changes, so
+/// callers don't have to re-run `UserService::get_public_user_with_counts`
+/// just to learn the new numbers.
+#[derive(Debug, Clone, Copy)]
+pub struct FollowCounts {
+    pub followers_count: i64,
+    pub following_count: i64,
 }
 
 impl SocialService {
@@ -30,18 +63,28 @@ impl SocialService {
         Self { db }
     }
 
-    pub async fn follow(&self, follower_id: Uuid, followee_id: Uuid) -> Result<bool> {
+    /// Follow `followee_id`. Lands as `Accepted` immediately on a public
+    /// account, or `Pending` (awaiting `accept_follow`) on a private one.
+    /// A no-op (returns `inserted: false`) on a self-follow, a duplicate
+    /// follow/request, or while either side has blocked the other.
+    pub async fn follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<(bool, FollowState, FollowCounts)> {
         const MAX_FOLLOWERS: i64 = 5000;
 
         let mut tx = self.db.pool().begin().await?;
 
-        sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+        let followee_row = sqlx::query("SELECT is_private FROM users WHERE id = $1 FOR UPDATE")
             .bind(followee_id)
             .fetch_one(&mut *tx)
             .await?;
+        let is_private: bool = followee_row.get("is_private");
+        let state = if is_private { FollowState::Pending } else { FollowState::Accepted };
 
         let follower_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM follows WHERE followee_id = $1",
+            "SELECT COUNT(*) FROM follows WHERE followee_id = $1 AND state = 'accepted'",
         )
         .bind(followee_id)
         .fetch_one(&mut *tx)
@@ -53,8 +96,8 @@ impl SocialService {
         }
 
         let result = sqlx::query(
-            "INSERT INTO follows (follower_id, followee_id) \
-             SELECT $1, $2 \
+            "INSERT INTO follows (follower_id, followee_id, state) \
+             SELECT $1, $2, $3 \
              WHERE $1 <> $2 \
                AND NOT EXISTS ( \
                    SELECT 1 FROM blocks \
@@ -65,17 +108,51 @@ impl SocialService {
         )
         .bind(follower_id)
         .bind(followee_id)
+        .bind(state.as_str())
         .execute(&mut *tx)
         .await?;
 
+        let inserted = result.rows_affected() > 0;
+        let counts = fetch_counts(&mut tx, follower_id, followee_id).await?;
+
         tx.commit().await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok((inserted, state, counts))
     }
 
-    pub async fn unfollow(&self, follower_id: Uuid, followee_id: Uuid) -> Result<bool> {
+    /// Approve a pending follow request. Returns `false` if there was no
+    /// pending request from `follower_id` to `followee_id`.
+    pub async fn accept_follow(
+        &self,
+        followee_id: Uuid,
+        follower_id: Uuid,
+    ) -> Result<(bool, FollowCounts)> {
+        let mut tx = self.db.pool().begin().await?;
+
         let result = sqlx::query(
-            "DELETE FROM follows WHERE follower_id = $1 AND followee_id = $2",
+            "UPDATE follows SET state = 'accepted' \
+             WHERE follower_id = $1 AND followee_id = $2 AND state = 'pending'",
+        )
+        .bind(follower_id)
+        .bind(followee_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let accepted = result.rows_affected() > 0;
+        let counts = fetch_counts(&mut tx, follower_id, followee_id).await?;
+
+        tx.commit().await?;
+
+        Ok((accepted, counts))
+    }
+
+    /// Reject a pending follow request, deleting it outright rather than
+    /// leaving a tombstone -- the requester is free to send a new request
+    /// later, same as if they'd never asked. Returns `false` if there was no
+    /// pending request from `follower_id` to `followee_id`.
+    pub async fn reject_follow(&self, followee_id: Uuid, follower_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM follows WHERE follower_id = $1 AND followee_id = $2 AND state = 'pending'",
         )
         .bind(follower_id)
         .bind(followee_id)
@@ -85,6 +162,142 @@ impl SocialService {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Whether `follower_id` has a still-pending (not yet accepted) follow
+    /// request to `followee_id`.
+    pub async fn is_follow_pending(&self, follower_id: Uuid, followee_id: Uuid) -> Result<bool> {
+        let pending: bool = sqlx::query_scalar(
+            "SELECT EXISTS ( \
+                SELECT 1 FROM follows \
+                WHERE follower_id = $1 AND followee_id = $2 AND state = 'pending' \
+             )",
+        )
+        .bind(follower_id)
+        .bind(followee_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(pending)
+    }
+
+    /// Pending follow requests awaiting `followee_id`'s approval, oldest first
+    /// so the earliest request is approved or rejected first.
+    pub async fn list_follow_requests(
+        &self,
+        followee_id: Uuid,
+        cursor: Option<(OffsetDateTime, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<SocialUserEdge>> {
+        let rows = match cursor {
+            Some((created_at, follower_id)) => {
+                sqlx::query(
+                    "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, \
+                            u.created_at, u.is_private, f.created_at AS followed_at \
+                     FROM follows f \
+                     JOIN users u ON u.id = f.follower_id \
+                     WHERE f.followee_id = $1 AND f.state = 'pending' \
+                       AND (f.created_at > $2 OR (f.created_at = $2 AND f.follower_id > $3)) \
+                     ORDER BY f.created_at ASC, f.follower_id ASC \
+                     LIMIT $4",
+                )
+                .bind(followee_id)
+                .bind(created_at)
+                .bind(follower_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, \
+                            u.created_at, u.is_private, f.created_at AS followed_at \
+                     FROM follows f \
+                     JOIN users u ON u.id = f.follower_id \
+                     WHERE f.followee_id = $1 AND f.state = 'pending' \
+                     ORDER BY f.created_at ASC, f.follower_id ASC \
+                     LIMIT $2",
+                )
+                .bind(followee_id)
+                .bind(limit)
+                .fetch_all(self.db.pool())
+                .await?
+            }
+        };
+
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            let user = User {
+                id: row.get("id"),
+                handle: row.get("handle"),
+                email: row.get("email"),
+                display_name: row.get("display_name"),
+                bio: row.get("bio"),
+                avatar_key: row.get("avatar_key"),
+                avatar_url: None,
+                created_at: row.get("created_at"),
+                is_locked: row.get("is_private"),
+            };
+            items.push(SocialUserEdge {
+                user,
+                followed_at: row.get("followed_at"),
+            });
+        }
+
+        Ok(items)
+    }
+
+    /// Count of pending follow requests awaiting `followee_id`'s approval, for
+    /// badging a client's requests inbox without paging through the list.
+    pub async fn pending_follow_request_count(&self, followee_id: Uuid) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM follows WHERE followee_id = $1 AND state = 'pending'",
+        )
+        .bind(followee_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn unfollow(&self, follower_id: Uuid, followee_id: Uuid) -> Result<(bool, FollowCounts)> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let result = sqlx::query(
+            "DELETE FROM follows WHERE follower_id = $1 AND followee_id = $2",
+        )
+        .bind(follower_id)
+        .bind(followee_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let unfollowed = result.rows_affected() > 0;
+        let counts = fetch_counts(&mut tx, follower_id, followee_id).await?;
+
+        tx.commit().await?;
+
+        Ok((unfollowed, counts))
+    }
+
+    /// Whether `follower_id` has an *accepted* follow of `followee_id`; a
+    /// still-pending request does not count as following.
+    pub async fn is_following(&self, follower_id: Uuid, followee_id: Uuid) -> Result<bool> {
+        let following: bool = sqlx::query_scalar(
+            "SELECT EXISTS ( \
+                SELECT 1 FROM follows \
+                WHERE follower_id = $1 AND followee_id = $2 AND state = 'accepted' \
+             )",
+        )
+        .bind(follower_id)
+        .bind(followee_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(following)
+    }
+
+    /// Block `blocked_id`: atomically removes any existing follow between
+    /// the two (in either direction, any state) and records the block, so
+    /// a pending or accepted follow can't linger once blocked and neither
+    /// side can re-follow while the block stands.
     pub async fn block(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool> {
         let mut tx = self.db.pool().begin().await?;
 
@@ -126,6 +339,50 @@ impl SocialService {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Mute `muted_id`: one-directional and silent, unlike `block` -- it
+    /// doesn't touch `follows` either way, so `muted_id` keeps following
+    /// (and being followed by) `muter_id` and is never told. Feed and
+    /// notification queries filter out muted authors for `muter_id` only.
+    pub async fn mute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let inserted = sqlx::query(
+            "INSERT INTO mutes (muter_id, muted_id) \
+             SELECT $1, $2 \
+             WHERE $1 <> $2 \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(muter_id)
+        .bind(muted_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(inserted.rows_affected() > 0)
+    }
+
+    pub async fn unmute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM mutes WHERE muter_id = $1 AND muted_id = $2")
+            .bind(muter_id)
+            .bind(muted_id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// `true` when `muter_id` has muted `muted_id` -- the check feed and
+    /// notification queries apply to suppress that author without touching
+    /// the follow graph.
+    pub async fn is_muting(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let muting: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = $2)",
+        )
+        .bind(muter_id)
+        .bind(muted_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(muting)
+    }
+
     pub async fn list_followers(
         &self,
         user_id: Uuid,
@@ -136,10 +393,10 @@ impl SocialService {
             Some((created_at, follower_id)) => {
                 sqlx::query(
                     "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, \
-                            u.created_at, f.created_at AS followed_at \
+                            u.created_at, u.is_private, f.created_at AS followed_at \
                      FROM follows f \
                      JOIN users u ON u.id = f.follower_id \
-                     WHERE f.followee_id = $1 \
+                     WHERE f.followee_id = $1 AND f.state = 'accepted' \
                        AND (f.created_at < $2 OR (f.created_at = $2 AND f.follower_id < $3)) \
                      ORDER BY f.created_at DESC, f.follower_id DESC \
                      LIMIT $4",
@@ -154,10 +411,10 @@ impl SocialService {
             None => {
                 sqlx::query(
                     "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, \
-                            u.created_at, f.created_at AS followed_at \
+                            u.created_at, u.is_private, f.created_at AS followed_at \
                      FROM follows f \
                      JOIN users u ON u.id = f.follower_id \
-                     WHERE f.followee_id = $1 \
+                     WHERE f.followee_id = $1 AND f.state = 'accepted' \
                      ORDER BY f.created_at DESC, f.follower_id DESC \
                      LIMIT $2",
                 )
@@ -177,7 +434,9 @@ impl SocialService {
                 display_name: row.get("display_name"),
                 bio: row.get("bio"),
                 avatar_key: row.get("avatar_key"),
+                avatar_url: None,
                 created_at: row.get("created_at"),
+                is_locked: row.get("is_private"),
             };
             items.push(SocialUserEdge {
                 user,
@@ -198,10 +457,10 @@ impl SocialService {
             Some((created_at, followee_id)) => {
                 sqlx::query(
                     "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, \
-                            u.created_at, f.created_at AS followed_at \
+                            u.created_at, u.is_private, f.created_at AS followed_at \
                      FROM follows f \
                      JOIN users u ON u.id = f.followee_id \
-                     WHERE f.follower_id = $1 \
+                     WHERE f.follower_id = $1 AND f.state = 'accepted' \
                        AND (f.created_at < $2 OR (f.created_at = $2 AND f.followee_id < $3)) \
                      ORDER BY f.created_at DESC, f.followee_id DESC \
                      LIMIT $4",
@@ -216,10 +475,10 @@ impl SocialService {
             None => {
                 sqlx::query(
                     "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, \
-                            u.created_at, f.created_at AS followed_at \
+                            u.created_at, u.is_private, f.created_at AS followed_at \
                      FROM follows f \
                      JOIN users u ON u.id = f.followee_id \
-                     WHERE f.follower_id = $1 \
+                     WHERE f.follower_id = $1 AND f.state = 'accepted' \
                      ORDER BY f.created_at DESC, f.followee_id DESC \
                      LIMIT $2",
                 )
@@ -239,7 +498,9 @@ impl SocialService {
                 display_name: row.get("display_name"),
                 bio: row.get("bio"),
                 avatar_key: row.get("avatar_key"),
+                avatar_url: None,
                 created_at: row.get("created_at"),
+                is_locked: row.get("is_private"),
             };
             items.push(SocialUserEdge {
                 user,
@@ -257,10 +518,13 @@ impl SocialService {
     ) -> Result<RelationshipStatus> {
         let row = sqlx::query(
             "SELECT \
-                EXISTS (SELECT 1 FROM follows WHERE follower_id = $1 AND followee_id = $2) AS is_following, \
-                EXISTS (SELECT 1 FROM follows WHERE follower_id = $2 AND followee_id = $1) AS is_followed_by, \
+                EXISTS (SELECT 1 FROM follows WHERE follower_id = $1 AND followee_id = $2 AND state = 'accepted') AS is_following, \
+                EXISTS (SELECT 1 FROM follows WHERE follower_id = $2 AND followee_id = $1 AND state = 'accepted') AS is_followed_by, \
                 EXISTS (SELECT 1 FROM blocks WHERE blocker_id = $1 AND blocked_id = $2) AS is_blocking, \
-                EXISTS (SELECT 1 FROM blocks WHERE blocker_id = $2 AND blocked_id = $1) AS is_blocked_by",
+                EXISTS (SELECT 1 FROM blocks WHERE blocker_id = $2 AND blocked_id = $1) AS is_blocked_by, \
+                EXISTS (SELECT 1 FROM follows WHERE follower_id = $1 AND followee_id = $2 AND state = 'pending') AS is_pending, \
+                EXISTS (SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = $2) AS is_muting, \
+                EXISTS (SELECT 1 FROM mutes WHERE muter_id = $2 AND muted_id = $1) AS is_muted_by",
         )
         .bind(viewer_id)
         .bind(other_id)
@@ -272,6 +536,119 @@ impl SocialService {
             is_followed_by: row.get("is_followed_by"),
             is_blocking: row.get("is_blocking"),
             is_blocked_by: row.get("is_blocked_by"),
+            is_pending: row.get("is_pending"),
+            is_muting: row.get("is_muting"),
+            is_muted_by: row.get("is_muted_by"),
         })
     }
+
+    /// Batches `relationship_status` and the three `PublicUser` counts for a
+    /// whole page of results into two queries total, instead of the 2N
+    /// queries a per-row `relationship_status`/counts lookup would cost.
+    /// Used by `list_followers`/`list_following`/`search_users` so clients
+    /// don't have to N+1 `/v1/users/{id}/relationship` for every row.
+    pub async fn add_user_relationships(
+        &self,
+        viewer_id: Uuid,
+        users: &mut [PublicUser],
+    ) -> Result<()> {
+        if users.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = users.iter().map(|user| user.id).collect();
+
+        let count_rows = sqlx::query(
+            "SELECT t.id AS target_id, \
+                    (SELECT COUNT(*) FROM follows WHERE followee_id = t.id AND state = 'accepted') AS followers_count, \
+                    (SELECT COUNT(*) FROM follows WHERE follower_id = t.id AND state = 'accepted') AS following_count, \
+                    (SELECT COUNT(*) FROM posts WHERE owner_id = t.id) AS posts_count \
+             FROM unnest($1::uuid[]) AS t(id)",
+        )
+        .bind(&ids)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut counts: HashMap<Uuid, (i64, i64, i64)> = HashMap::with_capacity(count_rows.len());
+        for row in count_rows {
+            counts.insert(
+                row.get("target_id"),
+                (
+                    row.get("followers_count"),
+                    row.get("following_count"),
+                    row.get("posts_count"),
+                ),
+            );
+        }
+
+        let relationship_rows = sqlx::query(
+            "SELECT t.id AS target_id, \
+                    EXISTS (SELECT 1 FROM follows WHERE follower_id = $1 AND followee_id = t.id AND state = 'accepted') AS is_following, \
+                    EXISTS (SELECT 1 FROM follows WHERE follower_id = t.id AND followee_id = $1 AND state = 'accepted') AS is_followed_by, \
+                    EXISTS (SELECT 1 FROM blocks WHERE blocker_id = $1 AND blocked_id = t.id) AS is_blocking, \
+                    EXISTS (SELECT 1 FROM blocks WHERE blocker_id = t.id AND blocked_id = $1) AS is_blocked_by, \
+                    EXISTS (SELECT 1 FROM follows WHERE follower_id = $1 AND followee_id = t.id AND state = 'pending') AS is_pending, \
+                    EXISTS (SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = t.id) AS is_muting, \
+                    EXISTS (SELECT 1 FROM mutes WHERE muter_id = t.id AND muted_id = $1) AS is_muted_by \
+             FROM unnest($2::uuid[]) AS t(id)",
+        )
+        .bind(viewer_id)
+        .bind(&ids)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut relationships: HashMap<Uuid, PublicUserRelationship> =
+            HashMap::with_capacity(relationship_rows.len());
+        for row in relationship_rows {
+            relationships.insert(
+                row.get("target_id"),
+                PublicUserRelationship {
+                    is_following: row.get("is_following"),
+                    is_followed_by: row.get("is_followed_by"),
+                    is_blocking: row.get("is_blocking"),
+                    is_blocked_by: row.get("is_blocked_by"),
+                    is_pending: row.get("is_pending"),
+                    is_muting: row.get("is_muting"),
+                    is_muted_by: row.get("is_muted_by"),
+                },
+            );
+        }
+
+        for user in users.iter_mut() {
+            if let Some((followers_count, following_count, posts_count)) = counts.get(&user.id) {
+                user.followers_count = *followers_count;
+                user.following_count = *following_count;
+                user.posts_count = *posts_count;
+            }
+            if user.id != viewer_id {
+                user.relationship = relationships.remove(&user.id);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `followee_id`'s followers_count and `follower_id`'s following_count --
+/// the two counters a follow/unfollow mutation between them can change.
+/// Only `state = 'accepted'` rows count; a pending request isn't a follow
+/// yet.
+async fn fetch_counts(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    follower_id: Uuid,
+    followee_id: Uuid,
+) -> Result<FollowCounts> {
+    let row = sqlx::query(
+        "SELECT \
+            (SELECT COUNT(*) FROM follows WHERE followee_id = $1 AND state = 'accepted') AS followers_count, \
+            (SELECT COUNT(*) FROM follows WHERE follower_id = $2 AND state = 'accepted') AS following_count",
+    )
+    .bind(followee_id)
+    .bind(follower_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(FollowCounts {
+        followers_count: row.get("followers_count"),
+        following_count: row.get("following_count"),
+    })
 }
@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use dashmap::DashMap;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::db::Db;
+use crate::infra::queue::QueueClient;
+use crate::jobs::social_federation_dispatcher::SocialEdgeJob;
+
+/// How long a fetched peer server's published ed25519 key is trusted before
+/// `ServerKeyClient` re-fetches it -- mirrors `signature::SignatureService`'s
+/// `ACTOR_KEY_CACHE_TTL`, same tradeoff between inbox throughput and picking
+/// up a rotated key.
+const SERVER_KEY_CACHE_TTL: time::Duration = time::Duration::minutes(30);
+
+/// A Follow/Block edge change pushed to a peer Lumine server, signed with
+/// the origin server's ed25519 key rather than a per-actor RSA keypair.
+/// This is Lumine-to-Lumine specific -- distinct from the ActivityPub
+/// `Follow`/`Undo(Follow)` handling in `federation::FederationService`,
+/// which lets any ActivityPub implementation interoperate. `actor` and
+/// `target` are `handle@host`, since one side of the edge may not have a
+/// row in this server's `users` table at all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialEdgeEvent {
+    pub event_type: EdgeEventType,
+    pub actor: String,
+    pub target: String,
+    pub origin_server: String,
+    pub issued_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeEventType {
+    FollowCreated,
+    FollowRemoved,
+    BlockCreated,
+    BlockRemoved,
+}
+
+/// Signs `event` with this server's ed25519 key and returns the exact bytes
+/// signed alongside the base64 signature -- the receiver verifies against
+/// these same bytes (see `ServerKeyClient::verify`), so the caller must
+/// deliver them verbatim rather than re-serializing the event.
+pub fn sign_edge_event(signing_key_seed: &[u8; 32], event: &SocialEdgeEvent) -> Result<(Vec<u8>, String)> {
+    let signing_key = SigningKey::from_bytes(signing_key_seed);
+    let body = serde_json::to_vec(event)
+        .map_err(|err| anyhow!("failed to serialize edge event: {}", err))?;
+    let signature = signing_key.sign(&body);
+    Ok((body, BASE64.encode(signature.to_bytes())))
+}
+
+/// This server's published ed25519 public key, served from
+/// `GET /federation/v1/server-key` (see `http::handlers::server_key`) for
+/// peers to verify our outbound pushes against.
+pub fn server_public_key(signing_key_seed: &[u8; 32]) -> VerifyingKey {
+    SigningKey::from_bytes(signing_key_seed).verifying_key()
+}
+
+#[derive(Clone)]
+struct CachedServerKey {
+    public_key: VerifyingKey,
+    fetched_at: OffsetDateTime,
+}
+
+/// Fetches and caches peer Lumine servers' published ed25519 public keys,
+/// the way `SignatureService` caches `publicKeyPem` for ActivityPub actors.
+#[derive(Clone)]
+pub struct ServerKeyClient {
+    http: reqwest::Client,
+    key_cache: Arc<DashMap<String, CachedServerKey>>,
+}
+
+impl ServerKeyClient {
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            key_cache: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Verifies `body`'s signature came from `origin_server`'s published
+    /// key, then parses and returns the event. Also rejects an event whose
+    /// own `origin_server` field differs from the `origin_server` whose key
+    /// verified it, so a peer can't sign an edge on another server's behalf.
+    pub async fn verify(&self, origin_server: &str, body: &[u8], signature_b64: &str) -> Result<SocialEdgeEvent> {
+        let public_key = self.fetch_public_key(origin_server).await?;
+
+        let signature_bytes = BASE64
+            .decode(signature_b64)
+            .map_err(|_| anyhow!("signature is not valid base64"))?;
+        let signature_bytes: [u8; 64] = signature_bytes
+            .try_into()
+            .map_err(|_| anyhow!("signature must be 64 bytes"))?;
+        let signature = Signature::from_bytes(&signature_bytes);
+
+        public_key
+            .verify(body, &signature)
+            .map_err(|_| anyhow!("server signature verification failed"))?;
+
+        let event: SocialEdgeEvent = serde_json::from_slice(body)
+            .map_err(|_| anyhow!("edge event body is malformed"))?;
+        if event.origin_server != origin_server {
+            return Err(anyhow!("edge event origin_server does not match the signing server"));
+        }
+
+        Ok(event)
+    }
+
+    async fn fetch_public_key(&self, host: &str) -> Result<VerifyingKey> {
+        if let Some(cached) = self.key_cache.get(host) {
+            if OffsetDateTime::now_utc() - cached.fetched_at < SERVER_KEY_CACHE_TTL {
+                return Ok(cached.public_key);
+            }
+        }
+
+        let response: serde_json::Value = self
+            .http
+            .get(format!("https://{}/federation/v1/server-key", host))
+            .send()
+            .await?
+            .json()
+            .await?;
+        let public_key_b64 = response["public_key"]
+            .as_str()
+            .ok_or_else(|| anyhow!("peer server-key response is missing public_key"))?;
+        let bytes = BASE64
+            .decode(public_key_b64)
+            .map_err(|_| anyhow!("peer public key is not valid base64"))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("peer public key must be 32 bytes"))?;
+        let public_key =
+            VerifyingKey::from_bytes(&bytes).map_err(|_| anyhow!("invalid peer ed25519 public key"))?;
+
+        self.key_cache.insert(
+            host.to_string(),
+            CachedServerKey {
+                public_key,
+                fetched_at: OffsetDateTime::now_utc(),
+            },
+        );
+
+        Ok(public_key)
+    }
+}
+
+/// Persists and dispatches the `user_id@host` side of the social graph --
+/// the part `app::social::SocialService` can't reach, since its `follows`/
+/// `blocks` tables only ever relate two local `Uuid`s. Kept as a separate
+/// service (rather than folded into `SocialService`) because it has its own
+/// signing/delivery concerns and only ever touches the `remote_*` tables.
+#[derive(Clone)]
+pub struct SocialFederationService {
+    db: Db,
+    base_url: String,
+}
+
+impl SocialFederationService {
+    pub fn new(db: Db, base_url: String) -> Self {
+        Self { db, base_url }
+    }
+
+    /// This server's bare host, as it appears on the `@host` side of a
+    /// `handle@host` address and in `SocialEdgeEvent::origin_server` --
+    /// derived from `base_url` rather than a separate setting, the same way
+    /// `FederationService::actor_uri` builds every ActivityPub id off it.
+    fn host(&self) -> Result<String> {
+        url::Url::parse(&self.base_url)?
+            .host_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("federation_base_url has no host"))
+    }
+
+    /// Signs `event` and hands it to `queue` for delivery by
+    /// `jobs::social_federation_dispatcher`, which is the only place that
+    /// actually makes the outbound HTTP call -- this keeps delivery
+    /// retries off the request path, same as `FederationService`'s
+    /// `Create`/`Delete` activities going through `federation_sweeper`.
+    pub async fn push_edge_event(
+        &self,
+        queue: &QueueClient,
+        signing_key_seed: &[u8; 32],
+        event: &SocialEdgeEvent,
+    ) -> Result<()> {
+        let target_server = event
+            .target
+            .split_once('@')
+            .map(|(_, host)| host.to_string())
+            .ok_or_else(|| anyhow!("target must be handle@host"))?;
+
+        let (body, signature) = sign_edge_event(signing_key_seed, event)?;
+        let job = SocialEdgeJob {
+            target_server,
+            body: String::from_utf8(body)
+                .map_err(|err| anyhow!("edge event body was not valid UTF-8: {}", err))?,
+            signature,
+        };
+        queue.enqueue_social_edge_job(&job).await?;
+        Ok(())
+    }
+
+    /// Builds the `FollowCreated`/`FollowRemoved`/`BlockCreated`/
+    /// `BlockRemoved` event for a local user acting on a remote
+    /// `handle@host` target, stamping `origin_server` to this instance's
+    /// own host.
+    pub fn build_event(
+        &self,
+        event_type: EdgeEventType,
+        local_handle: &str,
+        remote_actor: &str,
+        issued_at: i64,
+    ) -> Result<SocialEdgeEvent> {
+        let origin_server = self.host()?;
+        Ok(SocialEdgeEvent {
+            event_type,
+            actor: format!("{}@{}", local_handle, origin_server),
+            target: remote_actor.to_string(),
+            origin_server,
+            issued_at,
+        })
+    }
+
+    /// Records that `blocker_id` has blocked `remote_actor` (`handle@host`)
+    /// -- the outbound half of "block semantics are authoritative on the
+    /// blocker's home server": we keep our own row so unblocking and
+    /// relationship lookups work without depending on the peer ever
+    /// acknowledging the push, and a separate table from `remote_blocks`
+    /// since that one instead holds the *inbound* direction (a remote actor
+    /// blocking one of our local users).
+    pub async fn record_outbound_block(&self, blocker_id: Uuid, remote_actor: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO outbound_remote_blocks (blocker_id, remote_actor) VALUES ($1, $2) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(blocker_id)
+        .bind(remote_actor)
+        .execute(self.db.pool())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_outbound_block(&self, blocker_id: Uuid, remote_actor: &str) -> Result<()> {
+        sqlx::query("DELETE FROM outbound_remote_blocks WHERE blocker_id = $1 AND remote_actor = $2")
+            .bind(blocker_id)
+            .bind(remote_actor)
+            .execute(self.db.pool())
+            .await?;
+        Ok(())
+    }
+
+    /// Looks up the `remote_actors` row for `actor`, inserting one if this
+    /// is the first edge event we've ever seen involving it -- same
+    /// upsert-by-uri shape as `FederationService::upsert_remote_actor`,
+    /// just keyed by a synthetic `lumine://host/handle` uri instead of an
+    /// ActivityPub actor URL, so both federation mechanisms can point at
+    /// distinct rows for what is otherwise the same remote person.
+    async fn upsert_remote_actor(&self, actor: &str) -> Result<Uuid> {
+        let actor_uri = format!("lumine://{}", actor);
+        let row = sqlx::query(
+            "INSERT INTO remote_actors (actor_uri) VALUES ($1) \
+             ON CONFLICT (actor_uri) DO UPDATE SET actor_uri = EXCLUDED.actor_uri \
+             RETURNING id",
+        )
+        .bind(&actor_uri)
+        .fetch_one(self.db.pool())
+        .await?;
+        Ok(row.get("id"))
+    }
+
+    /// Applies a verified inbound edge event to the local `remote_*` tables.
+    /// `event.target` must resolve to a local user -- an event about a
+    /// third server's actor isn't ours to record.
+    pub async fn apply_remote_edge(&self, event: &SocialEdgeEvent, local_user_id: Uuid) -> Result<()> {
+        let remote_actor_id = self.upsert_remote_actor(&event.actor).await?;
+
+        match event.event_type {
+            EdgeEventType::FollowCreated => {
+                sqlx::query(
+                    "INSERT INTO remote_follows (remote_actor_id, followee_id, origin_server) \
+                     VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+                )
+                .bind(remote_actor_id)
+                .bind(local_user_id)
+                .bind(&event.origin_server)
+                .execute(self.db.pool())
+                .await?;
+            }
+            EdgeEventType::FollowRemoved => {
+                sqlx::query("DELETE FROM remote_follows WHERE remote_actor_id = $1 AND followee_id = $2")
+                    .bind(remote_actor_id)
+                    .bind(local_user_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+            EdgeEventType::BlockCreated => {
+                sqlx::query(
+                    "INSERT INTO remote_blocks (remote_actor_id, blocked_id, origin_server) \
+                     VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+                )
+                .bind(remote_actor_id)
+                .bind(local_user_id)
+                .bind(&event.origin_server)
+                .execute(self.db.pool())
+                .await?;
+                sqlx::query("DELETE FROM remote_follows WHERE remote_actor_id = $1 AND followee_id = $2")
+                    .bind(remote_actor_id)
+                    .bind(local_user_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+            EdgeEventType::BlockRemoved => {
+                sqlx::query("DELETE FROM remote_blocks WHERE remote_actor_id = $1 AND blocked_id = $2")
+                    .bind(remote_actor_id)
+                    .bind(local_user_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// True if `remote_actor` (`actor@host`) has blocked the local user
+    /// `blocked_id`, as recorded in `remote_blocks` from the remote
+    /// server's pushed `BlockCreated` event -- home-server-authoritative
+    /// block semantics mean the blocked side enforces it purely from that
+    /// pushed event.
+    pub async fn is_remote_actor_blocked(&self, blocked_id: Uuid, remote_actor: &str) -> Result<bool> {
+        let actor_uri = format!("lumine://{}", remote_actor);
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(\
+                SELECT 1 FROM remote_blocks b \
+                JOIN remote_actors a ON a.id = b.remote_actor_id \
+                WHERE a.actor_uri = $1 AND b.blocked_id = $2\
+             )",
+        )
+        .bind(&actor_uri)
+        .bind(blocked_id)
+        .fetch_one(self.db.pool())
+        .await?;
+        Ok(exists)
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::Result;
+use redis::AsyncCommands;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::domain::event::Event;
+use crate::domain::post::{Post, PostVisibility};
+use crate::domain::story::Story;
+use crate::infra::{cache::RedisCache, db::Db};
+
+/// Firehose channel every client may subscribe to: public posts only.
+pub const PUBLIC_CHANNEL: &str = "timeline:public";
+
+/// Per-user channel a client subscribes to for their own home feed.
+pub fn user_channel(user_id: Uuid) -> String {
+    format!("timeline:user:{}", user_id)
+}
+
+/// Per-author channel stories are published to. Unlike `user_channel`
+/// (one channel per *recipient*, fanned out to on publish), this is one
+/// channel per *author* -- a viewer subscribes directly to each of their
+/// followees' channels (see `followee_story_channels`) rather than having
+/// every story fanned out to a per-viewer channel, since a story has far
+/// fewer active viewers-at-once than a post's home-feed fan-out.
+pub fn story_channel(user_id: Uuid) -> String {
+    format!("stories.feed.{}", user_id)
+}
+
+/// Per-recipient channel `NotificationService`-produced notifications are
+/// published to, for an inbox/bell UI to subscribe to directly rather than
+/// polling `GET /v1/notifications`.
+pub fn notification_channel(user_id: Uuid) -> String {
+    format!("notifications:user:{}", user_id)
+}
+
+/// Publishes live feed events to Redis pub/sub so `GET /v1/feed/stream`
+/// subscribers see new posts without polling. Fan-out mirrors
+/// `FeedService::fan_out_post`'s visibility gating: a `Private`/`Mentioned`
+/// post is never published to a channel a non-owner could be listening on.
+///
+/// Because every method here publishes to the shared Redis deployment
+/// rather than to an in-process channel, a subscriber on any backend
+/// replica sees events published from any other replica for free -- there
+/// is no per-instance broadcast map to fan out across instances, and so
+/// no need for a Postgres `LISTEN`/`NOTIFY` bridge to reconcile them.
+#[derive(Clone)]
+pub struct StreamingService {
+    db: Db,
+    cache: RedisCache,
+}
+
+impl StreamingService {
+    pub fn new(db: Db, cache: RedisCache) -> Self {
+        Self { db, cache }
+    }
+
+    /// Best-effort: a Redis hiccup here must never fail the post-creation
+    /// request, so every Redis failure is logged and swallowed rather than
+    /// propagated (mirrors `FeedService::fan_out_post`). A failed follower
+    /// lookup in Postgres is returned as an `Err` for the caller to log.
+    pub async fn publish_post_created(&self, post: &Post) -> Result<()> {
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for feed streaming");
+                return Ok(());
+            }
+        };
+
+        let event = Event::PostCreated { post: post.clone() };
+        let payload = serde_json::to_string(&event)?;
+
+        if matches!(post.visibility, PostVisibility::Public) {
+            if let Err(err) = conn.publish::<_, _, ()>(PUBLIC_CHANNEL, &payload).await {
+                warn!(error = ?err, post_id = %post.id, "failed to publish post to public timeline channel");
+            }
+        }
+
+        let follower_ids: Vec<Uuid> = match post.visibility {
+            PostVisibility::Private | PostVisibility::Mentioned => Vec::new(),
+            PostVisibility::Public | PostVisibility::Followers => sqlx::query_scalar(
+                "SELECT f.follower_id FROM follows f \
+                 WHERE f.followee_id = $1 \
+                   AND NOT EXISTS ( \
+                       SELECT 1 FROM blocks \
+                       WHERE (blocker_id = f.followee_id AND blocked_id = f.follower_id) \
+                          OR (blocker_id = f.follower_id AND blocked_id = f.followee_id) \
+                   )",
+            )
+            .bind(post.owner_id)
+            .fetch_all(self.db.pool())
+            .await?,
+        };
+
+        for recipient_id in follower_ids.into_iter().chain(std::iter::once(post.owner_id)) {
+            let channel = user_channel(recipient_id);
+            if let Err(err) = conn.publish::<_, _, ()>(&channel, &payload).await {
+                warn!(error = ?err, recipient_id = %recipient_id, "failed to publish post to timeline channel");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publish a newly created story to its author's channel. Best-effort,
+    /// same rationale as `publish_post_created`: a Redis hiccup must never
+    /// fail story creation.
+    pub async fn publish_story_created(&self, story: &Story) -> Result<()> {
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for story streaming");
+                return Ok(());
+            }
+        };
+
+        let event = Event::StoryCreated { story: story.clone() };
+        let payload = serde_json::to_string(&event)?;
+        let channel = story_channel(story.user_id);
+        if let Err(err) = conn.publish::<_, _, ()>(&channel, &payload).await {
+            warn!(error = ?err, story_id = %story.id, "failed to publish story to stories feed channel");
+        }
+
+        Ok(())
+    }
+
+    /// Publish a story removal (manual delete or expiry) to its author's
+    /// channel so subscribed followers drop it from a live story ring
+    /// without having to re-poll.
+    pub async fn publish_story_deleted(&self, story_id: Uuid, user_id: Uuid) -> Result<()> {
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for story streaming");
+                return Ok(());
+            }
+        };
+
+        let event = Event::StoryDeleted { story_id };
+        let payload = serde_json::to_string(&event)?;
+        let channel = story_channel(user_id);
+        if let Err(err) = conn.publish::<_, _, ()>(&channel, &payload).await {
+            warn!(error = ?err, story_id = %story_id, "failed to publish story deletion to stories feed channel");
+        }
+
+        Ok(())
+    }
+
+    /// Publish a freshly created notification to its recipient's channel.
+    /// Best-effort, same rationale as `publish_post_created`: a Redis
+    /// hiccup here must never fail the like/comment/follow/etc. request
+    /// that triggered it. Called by `NotificationService::create` right
+    /// after the row is persisted.
+    pub async fn publish_notification(&self, notification: &crate::domain::notification::Notification) -> Result<()> {
+        let mut conn = match self.cache.client().get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = ?err, "failed to connect to Redis for notification streaming");
+                return Ok(());
+            }
+        };
+
+        let event = Event::NotificationCreated {
+            notification: notification.clone(),
+        };
+        let payload = serde_json::to_string(&event)?;
+        let channel = notification_channel(notification.user_id);
+        if let Err(err) = conn.publish::<_, _, ()>(&channel, &payload).await {
+            warn!(error = ?err, notification_id = %notification.id, "failed to publish notification to channel");
+        }
+
+        Ok(())
+    }
+
+    /// The set of story channels `viewer_id` should subscribe to for a live
+    /// stories feed: one per followee, excluding either direction of a
+    /// block -- mirrors `publish_post_created`'s block exclusion.
+    pub async fn followee_story_channels(&self, viewer_id: Uuid) -> Result<Vec<String>> {
+        let followee_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT f.followee_id FROM follows f \
+             WHERE f.follower_id = $1 \
+               AND NOT EXISTS ( \
+                   SELECT 1 FROM blocks \
+                   WHERE (blocker_id = f.followee_id AND blocked_id = f.follower_id) \
+                      OR (blocker_id = f.follower_id AND blocked_id = f.followee_id) \
+               )",
+        )
+        .bind(viewer_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(followee_ids.into_iter().map(story_channel).collect())
+    }
+}
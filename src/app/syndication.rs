@@ -0,0 +1,149 @@
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+
+use crate::domain::post::Post;
+use crate::domain::user::User;
+
+/// Minimal hand-rolled escaping for the plain-text fields (captions, handles)
+/// that end up inside feed XML -- there's no markup in a caption, so pulling
+/// in a full XML writer crate for this would be overkill.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn entry_title(post: &Post) -> String {
+    match &post.caption {
+        Some(caption) if !caption.is_empty() => {
+            let mut title: String = caption.chars().take(80).collect();
+            if title.len() < caption.len() {
+                title.push_str("...");
+            }
+            title
+        }
+        _ => format!("Post {}", post.id),
+    }
+}
+
+/// The playable/viewable URL for a post's first attachment, if any -- linked
+/// from each feed entry so a reader can open the media without the JSON API.
+fn media_url(post: &Post) -> Option<&str> {
+    post.attachments
+        .first()
+        .map(|media| media.original_url.as_deref().unwrap_or(&media.original_key))
+}
+
+/// Renders `user`'s public post timeline as an Atom feed (RFC 4287) for
+/// `GET /users/:id/feed.atom`. `posts` is expected newest-first, as returned
+/// by `PostService::list_by_user`.
+pub fn render_atom_feed(base_url: &str, user: &User, posts: &[Post]) -> String {
+    let self_url = format!("{}/users/{}/feed.atom", base_url, user.id);
+    let alternate_url = format!("{}/users/{}", base_url, user.id);
+    let updated = posts
+        .first()
+        .map(|post| post.created_at)
+        .unwrap_or(user.created_at)
+        .format(&Rfc3339)
+        .unwrap_or_default();
+
+    let mut entries = String::new();
+    for post in posts {
+        let post_url = format!("{}/posts/{}", base_url, post.id);
+        let published = post.created_at.format(&Rfc3339).unwrap_or_default();
+        let media_link = media_url(post)
+            .map(|url| format!("    <link rel=\"enclosure\" href=\"{}\"/>\n", escape_xml(url)))
+            .unwrap_or_default();
+
+        entries.push_str(&format!(
+            "  <entry>\n    \
+               <id>{post_url}</id>\n    \
+               <title>{title}</title>\n    \
+               <link href=\"{post_url}\"/>\n    \
+               <published>{published}</published>\n    \
+               <updated>{published}</updated>\n    \
+               <summary>{summary}</summary>\n\
+             {media_link}  </entry>\n",
+            post_url = post_url,
+            title = escape_xml(&entry_title(post)),
+            published = published,
+            summary = escape_xml(post.caption.as_deref().unwrap_or("")),
+            media_link = media_link,
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\">\n  \
+           <id>{alternate_url}</id>\n  \
+           <title>{display_name}</title>\n  \
+           <updated>{updated}</updated>\n  \
+           <link rel=\"self\" href=\"{self_url}\"/>\n  \
+           <link rel=\"alternate\" href=\"{alternate_url}\"/>\n\
+         {entries}</feed>\n",
+        alternate_url = alternate_url,
+        display_name = escape_xml(&user.display_name),
+        updated = updated,
+        self_url = self_url,
+        entries = entries,
+    )
+}
+
+/// Renders `user`'s public post timeline as an RSS 2.0 feed for
+/// `GET /users/:id/feed.rss`. See `render_atom_feed` for the Atom
+/// equivalent; the two are built side by side so a client can pick whichever
+/// format it already speaks.
+pub fn render_rss_feed(base_url: &str, user: &User, posts: &[Post]) -> String {
+    let self_url = format!("{}/users/{}/feed.rss", base_url, user.id);
+    let alternate_url = format!("{}/users/{}", base_url, user.id);
+    let last_build_date = posts
+        .first()
+        .map(|post| post.created_at)
+        .unwrap_or(user.created_at)
+        .format(&Rfc2822)
+        .unwrap_or_default();
+
+    let mut items = String::new();
+    for post in posts {
+        let post_url = format!("{}/posts/{}", base_url, post.id);
+        let pub_date = post.created_at.format(&Rfc2822).unwrap_or_default();
+        let media_link = media_url(post)
+            .map(|url| format!("      <enclosure url=\"{}\"/>\n", escape_xml(url)))
+            .unwrap_or_default();
+
+        items.push_str(&format!(
+            "    <item>\n      \
+               <guid>{post_url}</guid>\n      \
+               <title>{title}</title>\n      \
+               <link>{post_url}</link>\n      \
+               <description>{description}</description>\n      \
+               <pubDate>{pub_date}</pubDate>\n\
+             {media_link}    </item>\n",
+            post_url = post_url,
+            title = escape_xml(&entry_title(post)),
+            description = escape_xml(post.caption.as_deref().unwrap_or("")),
+            pub_date = pub_date,
+            media_link = media_link,
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n  \
+           <channel>\n    \
+             <title>{display_name}</title>\n    \
+             <link>{alternate_url}</link>\n    \
+             <description>Posts by {display_name}</description>\n    \
+             <atom:link rel=\"self\" href=\"{self_url}\"/>\n    \
+             <lastBuildDate>{last_build_date}</lastBuildDate>\n\
+           {items}  </channel>\n\
+         </rss>\n",
+        display_name = escape_xml(&user.display_name),
+        alternate_url = alternate_url,
+        self_url = self_url,
+        last_build_date = last_build_date,
+        items = items,
+    )
+}
@@ -0,0 +1,363 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sqlx::Row;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::app::auth::{hash_password, AuthService, DeviceContext, Scope, TokenPair};
+use crate::infra::cache::RedisCache;
+use crate::infra::db::Db;
+
+/// Brand shown in an authenticator app next to the account label.
+const TOTP_ISSUER: &str = "Lumine";
+/// RFC 6238 default: a new code every 30 seconds.
+const TOTP_STEP_SECONDS: i64 = 30;
+/// Tolerate the client's clock running one step fast or slow.
+const TOTP_STEP_TOLERANCE: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+/// How long a `login`-issued TOTP challenge stays valid before the client
+/// has to restart the login.
+const LOGIN_CHALLENGE_TTL_SECONDS: i64 = 300;
+
+#[derive(Serialize, Deserialize)]
+struct LoginChallengeState {
+    user_id: Uuid,
+    scopes: Vec<String>,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    device_label: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct TotpService {
+    db: Db,
+    cache: RedisCache,
+}
+
+impl TotpService {
+    pub fn new(db: Db, cache: RedisCache) -> Self {
+        Self { db, cache }
+    }
+
+    /// Starts (or restarts) enrollment: generates a fresh secret, stores it
+    /// unconfirmed, and returns it alongside the `otpauth://` URI an
+    /// authenticator app can scan as a QR code. The secret only takes effect
+    /// once `confirm_totp` proves the caller's app is actually in sync with
+    /// it -- until then, `login` has nothing to gate on.
+    pub async fn enroll_totp(&self, user_id: Uuid) -> Result<(String, String)> {
+        let mut secret_bytes = [0u8; 20];
+        thread_rng().fill_bytes(&mut secret_bytes);
+        let secret_base32 = base32_encode(&secret_bytes);
+
+        sqlx::query(
+            "INSERT INTO user_totp_secrets (user_id, secret_base32, confirmed_at) \
+             VALUES ($1, $2, NULL) \
+             ON CONFLICT (user_id) DO UPDATE SET secret_base32 = EXCLUDED.secret_base32, confirmed_at = NULL",
+        )
+        .bind(user_id)
+        .bind(&secret_base32)
+        .execute(self.db.pool())
+        .await?;
+
+        let label: String = sqlx::query_scalar("SELECT handle FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(self.db.pool())
+            .await?;
+
+        let otpauth_uri = format!(
+            "otpauth://totp/{issuer}:{label}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits=6&period={period}",
+            issuer = TOTP_ISSUER,
+            label = label,
+            secret = secret_base32,
+            period = TOTP_STEP_SECONDS,
+        );
+
+        Ok((secret_base32, otpauth_uri))
+    }
+
+    /// Confirms enrollment by checking `code` against the pending secret,
+    /// flips it to confirmed (the state `login` gates on), and mints a fresh
+    /// batch of one-time recovery codes -- replacing any from a prior
+    /// enrollment, since only one secret is live per account.
+    pub async fn confirm_totp(&self, user_id: Uuid, code: &str) -> Result<Vec<String>> {
+        let secret_base32: String = sqlx::query_scalar(
+            "SELECT secret_base32 FROM user_totp_secrets WHERE user_id = $1 AND confirmed_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| anyhow!("no pending totp enrollment for this account"))?;
+
+        let secret = base32_decode(&secret_base32)?;
+        if !code_matches_secret(&secret, code)? {
+            return Err(anyhow!("invalid totp code"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+
+        sqlx::query("UPDATE user_totp_secrets SET confirmed_at = now() WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut recovery_codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+        for _ in 0..RECOVERY_CODE_COUNT {
+            let recovery_code = random_recovery_code();
+            let code_hash = hash_password(&recovery_code)?;
+            sqlx::query(
+                "INSERT INTO totp_recovery_codes (user_id, code_hash) VALUES ($1, $2)",
+            )
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&mut *tx)
+            .await?;
+            recovery_codes.push(recovery_code);
+        }
+
+        tx.commit().await?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Checks `code` against a confirmed secret's current step (plus one
+    /// step of clock-skew tolerance on either side), falling back to
+    /// consuming a matching recovery code. Used both for `login`'s gated
+    /// second factor and for any other caller that just wants a yes/no.
+    pub async fn verify_totp(&self, user_id: Uuid, code: &str) -> Result<bool> {
+        let secret_base32: Option<String> = sqlx::query_scalar(
+            "SELECT secret_base32 FROM user_totp_secrets WHERE user_id = $1 AND confirmed_at IS NOT NULL",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        if let Some(secret_base32) = secret_base32 {
+            let secret = base32_decode(&secret_base32)?;
+            if code_matches_secret(&secret, code)? {
+                return Ok(true);
+            }
+        }
+
+        self.consume_recovery_code(user_id, code).await
+    }
+
+    /// True if a confirmed secret exists for `user_id`, the gate `login`
+    /// checks before deciding whether to issue tokens outright or park a
+    /// challenge for `complete_login` instead.
+    pub(crate) async fn has_confirmed_secret(&self, user_id: Uuid) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM user_totp_secrets WHERE user_id = $1 AND confirmed_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(exists)
+    }
+
+    /// Parks the scopes/device a gated `login` would otherwise have issued
+    /// tokens for, keyed by a handle the client echoes back to
+    /// `complete_login` along with the TOTP (or recovery) code.
+    pub(crate) async fn begin_login(
+        &self,
+        user_id: Uuid,
+        scopes: &[Scope],
+        device: &DeviceContext,
+    ) -> Result<String> {
+        let handle = Uuid::new_v4().to_string();
+        self.put_handle(
+            &login_handle_key(&handle),
+            &LoginChallengeState {
+                user_id,
+                scopes: scopes.iter().map(|scope| scope.as_str().to_string()).collect(),
+                user_agent: device.user_agent.clone(),
+                ip: device.ip.clone(),
+                device_label: device.device_label.clone(),
+            },
+            LOGIN_CHALLENGE_TTL_SECONDS,
+        )
+        .await?;
+
+        Ok(handle)
+    }
+
+    /// Second half of a TOTP-gated login: verify `code` against the
+    /// challenged account and, on success, mint the token pair `login`
+    /// withheld.
+    pub async fn complete_login(
+        &self,
+        handle: &str,
+        code: &str,
+        auth_service: &AuthService,
+    ) -> Result<TokenPair> {
+        let state: LoginChallengeState = self
+            .take_handle(&login_handle_key(handle))
+            .await?
+            .ok_or_else(|| anyhow!("totp login handle not found or expired"))?;
+
+        if !self.verify_totp(state.user_id, code).await? {
+            return Err(anyhow!("invalid totp code"));
+        }
+
+        let scopes: Vec<Scope> = state
+            .scopes
+            .iter()
+            .filter_map(|scope| Scope::from_str(scope))
+            .collect();
+        let device = DeviceContext {
+            user_agent: state.user_agent,
+            ip: state.ip,
+            device_label: state.device_label,
+        };
+
+        auth_service
+            .issue_token_pair(state.user_id, &scopes, &device)
+            .await
+    }
+
+    async fn consume_recovery_code(&self, user_id: Uuid, code: &str) -> Result<bool> {
+        let rows = sqlx::query("SELECT id, code_hash FROM totp_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(self.db.pool())
+            .await?;
+
+        for row in rows {
+            let id: Uuid = row.get("id");
+            let code_hash: String = row.get("code_hash");
+            if crate::app::auth::verify_password(code, &code_hash).unwrap_or(false) {
+                sqlx::query("DELETE FROM totp_recovery_codes WHERE id = $1")
+                    .bind(id)
+                    .execute(self.db.pool())
+                    .await?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn put_handle<T: Serialize>(&self, key: &str, value: &T, ttl_seconds: i64) -> Result<()> {
+        use redis::AsyncCommands;
+        let payload = serde_json::to_string(value)?;
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        conn.set_ex(key, payload, ttl_seconds as u64).await?;
+        Ok(())
+    }
+
+    async fn take_handle<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
+        use redis::AsyncCommands;
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let payload: Option<String> = conn.get(key).await?;
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        // Single-use: a captured handle can't be replayed to mint a second
+        // token pair for the same login attempt.
+        let _: () = conn.del(key).await?;
+        Ok(Some(serde_json::from_str(&payload)?))
+    }
+}
+
+fn login_handle_key(handle: &str) -> String {
+    format!("totp:login:{}", handle)
+}
+
+fn random_recovery_code() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(char::from)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// RFC 6238: true if `code` matches `secret`'s current 30-second step, or
+/// the step immediately before/after it to tolerate clock skew.
+fn code_matches_secret(secret: &[u8], code: &str) -> Result<bool> {
+    let unix_time = OffsetDateTime::now_utc().unix_timestamp();
+    let current_step = unix_time / TOTP_STEP_SECONDS;
+
+    for step in (current_step - TOTP_STEP_TOLERANCE)..=(current_step + TOTP_STEP_TOLERANCE) {
+        if generate_totp(secret, step)? == code {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// RFC 6238 HOTP-with-a-time-derived-counter: `HMAC-SHA1(secret, step)`,
+/// dynamic truncation per RFC 4226 section 5.3, then `mod 10^6` zero-padded
+/// to 6 digits.
+fn generate_totp(secret: &[u8], step: i64) -> Result<String> {
+    let counter = (step as u64).to_be_bytes();
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret)
+        .map_err(|_| anyhow!("totp secret has an invalid length for hmac-sha1"))?;
+    mac.update(&counter);
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[19] & 0x0f) as usize;
+    let truncated = ((u32::from(digest[offset]) & 0x7f) << 24)
+        | (u32::from(digest[offset + 1]) << 16)
+        | (u32::from(digest[offset + 2]) << 8)
+        | u32::from(digest[offset + 3]);
+
+    Ok(format!("{:06}", truncated % 1_000_000))
+}
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits_in_buffer = 0u32;
+
+    for &byte in data {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits_in_buffer += 8;
+        while bits_in_buffer >= 5 {
+            bits_in_buffer -= 5;
+            let index = (buffer >> bits_in_buffer) & 0x1f;
+            output.push(BASE32_ALPHABET[index as usize] as char);
+        }
+    }
+
+    if bits_in_buffer > 0 {
+        let index = (buffer << (5 - bits_in_buffer)) & 0x1f;
+        output.push(BASE32_ALPHABET[index as usize] as char);
+    }
+
+    output
+}
+
+fn base32_decode(input: &str) -> Result<Vec<u8>> {
+    let mut buffer: u32 = 0;
+    let mut bits_in_buffer = 0u32;
+    let mut output = Vec::new();
+
+    for ch in input.trim_end_matches('=').chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&b| b as char == ch.to_ascii_uppercase())
+            .ok_or_else(|| anyhow!("invalid base32 character in totp secret"))?
+            as u32;
+        buffer = (buffer << 5) | value;
+        bits_in_buffer += 5;
+        if bits_in_buffer >= 8 {
+            bits_in_buffer -= 8;
+            output.push((buffer >> bits_in_buffer) as u8);
+        }
+    }
+
+    Ok(output)
+}
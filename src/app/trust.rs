@@ -265,9 +265,12 @@ impl TrustService {
         Ok(())
     }
 
-    /// Manually set trust level (admin action)
-    pub async fn set_trust_level(&self, user_id: Uuid, level: TrustLevel) -> Result<()> {
-        sqlx::query(
+    /// Manually set trust level (admin action). Returns whether a
+    /// `user_trust_scores` row existed to update -- see
+    /// `http::handlers::set_user_trust_level`, the manual-promotion
+    /// endpoint this backs.
+    pub async fn set_trust_level(&self, user_id: Uuid, level: TrustLevel) -> Result<bool> {
+        let result = sqlx::query(
             "UPDATE user_trust_scores \
              SET trust_level = $1, updated_at = NOW() \
              WHERE user_id = $2",
@@ -277,7 +280,75 @@ impl TrustService {
         .execute(self.db.pool())
         .await?;
 
-        Ok(())
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Nightly maintenance sweep: advances every account's `account_age_days`
+    /// from `users.created_at`, decays `trust_points` for accounts that have
+    /// gone quiet, and clears any `banned_until` that has already elapsed --
+    /// all in one transaction, since each is a small update over the whole
+    /// `user_trust_scores` table and none should partially apply. Returns
+    /// the number of accounts decayed.
+    ///
+    /// Decay multiplies `trust_points` by a per-day factor derived from
+    /// `decay_half_life_days` (so, applied nightly, points fall off
+    /// exponentially rather than all at once), and only touches accounts
+    /// whose `last_activity_at` is older than `activity_staleness_days` --
+    /// an account that's still active keeps the points `record_activity`
+    /// gave it. `recalculate_trust_level` then re-runs for every account
+    /// decay touched, since a points drop can demote a trust level on its
+    /// own even without a new strike or flag.
+    pub async fn run_daily_maintenance(
+        &self,
+        decay_half_life_days: f64,
+        activity_staleness_days: i64,
+    ) -> Result<u64> {
+        let decay_factor = 0.5_f64.powf(1.0 / decay_half_life_days.max(1.0));
+
+        let mut tx = self.db.pool().begin().await?;
+
+        sqlx::query(
+            "UPDATE user_trust_scores AS uts \
+             SET account_age_days = GREATEST(0, EXTRACT(DAY FROM NOW() - u.created_at))::int, \
+                 updated_at = NOW() \
+             FROM users AS u \
+             WHERE uts.user_id = u.id",
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE user_trust_scores \
+             SET banned_until = NULL, updated_at = NOW() \
+             WHERE banned_until IS NOT NULL AND banned_until <= NOW()",
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let decayed: Vec<Uuid> = sqlx::query(
+            "UPDATE user_trust_scores \
+             SET trust_points = GREATEST(0, FLOOR(trust_points * $1))::int, \
+                 updated_at = NOW() \
+             WHERE last_activity_at IS NOT NULL \
+               AND last_activity_at < NOW() - make_interval(days => $2::int) \
+               AND trust_points > 0 \
+             RETURNING user_id",
+        )
+        .bind(decay_factor)
+        .bind(activity_staleness_days as i32)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| row.get("user_id"))
+        .collect();
+
+        tx.commit().await?;
+
+        for user_id in &decayed {
+            self.recalculate_trust_level(*user_id).await?;
+        }
+
+        Ok(decayed.len() as u64)
     }
 
     /// Get trust level statistics (for admin dashboard)
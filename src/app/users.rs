@@ -1,127 +1,365 @@
 use anyhow::Result;
+use dashmap::DashMap;
+use serde::Serialize;
 use sqlx::Row;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::domain::user::User;
-use crate::infra::db::Db;
+use crate::infra::crud::Crud;
+use crate::infra::db::{Db, UnitOfWork};
+
+const USER_CACHE_TTL_SECONDS: i64 = 300;
+const USER_CACHE_GC_INTERVAL: Duration = Duration::from_secs(60);
+
+struct CachedUser {
+    user: Arc<User>,
+    cached_at_unix: AtomicI64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Write-through/write-invalidate cache for `User` rows, owned by
+/// `UserService`. `get_user` consults this map before hitting Postgres;
+/// `update_profile` and `deactivate_account` reconcile it in the same call
+/// that writes the row, so a cached entry is never staler than the last
+/// successful transaction on that user -- no mutating method returns
+/// without having done so. Entries older than `USER_CACHE_TTL_SECONDS` are
+/// swept by a background GC loop, mirroring `ConcurrencyLimiter`'s
+/// idle-eviction loop.
+#[derive(Clone)]
+pub struct UserCache {
+    entries: Arc<DashMap<Uuid, CachedUser>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl UserCache {
+    pub fn new() -> Self {
+        let cache = Self {
+            entries: Arc::new(DashMap::new()),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        };
+        cache.spawn_gc_loop();
+        cache
+    }
+
+    fn spawn_gc_loop(&self) {
+        let entries = self.entries.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(USER_CACHE_GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = now_unix();
+                entries.retain(|_, cached| {
+                    now - cached.cached_at_unix.load(Ordering::Relaxed) < USER_CACHE_TTL_SECONDS
+                });
+            }
+        });
+    }
+
+    fn get(&self, user_id: Uuid) -> Option<Arc<User>> {
+        let hit = self.entries.get(&user_id).and_then(|cached| {
+            let age = now_unix() - cached.cached_at_unix.load(Ordering::Relaxed);
+            (age < USER_CACHE_TTL_SECONDS).then(|| cached.user.clone())
+        });
+
+        match &hit {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        hit
+    }
+
+    fn put(&self, user: User) -> Arc<User> {
+        let user = Arc::new(user);
+        self.entries.insert(
+            user.id,
+            CachedUser {
+                user: user.clone(),
+                cached_at_unix: AtomicI64::new(now_unix()),
+            },
+        );
+        user
+    }
+
+    /// Evict `user_id`'s entry, e.g. after an out-of-band write this
+    /// service didn't make (a moderation tool writing directly to Postgres).
+    pub fn invalidate(&self, user_id: Uuid) {
+        self.entries.remove(&user_id);
+    }
+
+    pub fn cache_stats(&self) -> UserCacheStats {
+        UserCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.len(),
+        }
+    }
+}
+
+fn now_unix() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
 
 #[derive(Clone)]
 pub struct UserService {
     db: Db,
+    cache: UserCache,
+    recovery_grace_days: i64,
 }
 
 impl UserService {
-    pub fn new(db: Db) -> Self {
-        Self { db }
+    pub fn new(db: Db, cache: UserCache, recovery_grace_days: i64) -> Self {
+        Self {
+            db,
+            cache,
+            recovery_grace_days,
+        }
     }
 
-    pub async fn get_user(&self, _user_id: Uuid) -> Result<Option<User>> {
-        let row = sqlx::query(
-            "SELECT id, handle, email, display_name, bio, avatar_key, created_at \
-             FROM users WHERE id = $1 AND deleted_at IS NULL",
-        )
-        .bind(_user_id)
-        .fetch_optional(self.db.pool())
-        .await?;
+    /// `tx: None` runs the lookup against the pool directly (the common
+    /// case); `tx: Some(uow)` joins an in-flight `UnitOfWork` so the read
+    /// sees uncommitted writes made earlier in the same unit of work.
+    pub async fn get_user(&self, tx: Option<&mut UnitOfWork>, user_id: Uuid) -> Result<Option<User>> {
+        if let Some(user) = self.cache.get(user_id) {
+            return Ok(Some((*user).clone()));
+        }
 
-        let user = row.map(|row| User {
-            id: row.get("id"),
-            handle: row.get("handle"),
-            email: row.get("email"),
-            display_name: row.get("display_name"),
-            bio: row.get("bio"),
-            avatar_key: row.get("avatar_key"),
-            avatar_url: None,
-            created_at: row.get("created_at"),
-        });
+        let user = User::read(&self.db, tx, user_id).await?;
+
+        if let Some(user) = &user {
+            self.cache.put(user.clone());
+        }
 
         Ok(user)
     }
 
-
-    pub async fn get_public_user_with_counts(&self, user_id: Uuid) -> Result<Option<(User, i64, i64, i64)>> {
+    /// Exact, case-sensitive handle lookup, bypassing `get_user`'s
+    /// id-keyed cache since federation's WebFinger resolution and the
+    /// social-edge inbox are the only callers and don't repeat lookups often
+    /// enough to be worth caching.
+    pub async fn get_user_by_handle(&self, handle: &str) -> Result<Option<User>> {
         let row = sqlx::query(
-            "SELECT u.id, u.handle, u.email, u.display_name, u.bio, u.avatar_key, u.created_at, \
-                    (SELECT COUNT(*) FROM follows WHERE followee_id = u.id) AS followers_count, \
-                    (SELECT COUNT(*) FROM follows WHERE follower_id = u.id) AS following_count, \
-                    (SELECT COUNT(*) FROM posts WHERE owner_id = u.id) AS posts_count \
-             FROM users u WHERE u.id = $1 AND u.deleted_at IS NULL",
+            "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private \
+             FROM users WHERE handle = $1 AND deleted_at IS NULL \
+               AND NOT EXISTS ( \
+                 SELECT 1 FROM bans \
+                 WHERE target_id = users.id AND scope IS NULL AND lifted_at IS NULL \
+                   AND (expires_at IS NULL OR expires_at > now()) \
+               )",
         )
-        .bind(user_id)
+        .bind(handle)
         .fetch_optional(self.db.pool())
         .await?;
 
-        let result = row.map(|row| {
-            let user = User {
-                id: row.get("id"),
-                handle: row.get("handle"),
-                email: row.get("email"),
-                display_name: row.get("display_name"),
-                bio: row.get("bio"),
-                avatar_key: row.get("avatar_key"),
-                avatar_url: None,
-                created_at: row.get("created_at"),
-            };
-            let followers_count: i64 = row.get("followers_count");
-            let following_count: i64 = row.get("following_count");
-            let posts_count: i64 = row.get("posts_count");
-            (user, followers_count, following_count, posts_count)
-        });
+        Ok(row.map(|row| row_to_user(&row)))
+    }
+
+    /// Counts are always read live (they change on every follow/post), but
+    /// the `User` half reuses `get_user`'s cache rather than re-selecting
+    /// columns that haven't changed.
+    pub async fn get_public_user_with_counts(
+        &self,
+        mut tx: Option<&mut UnitOfWork>,
+        user_id: Uuid,
+    ) -> Result<Option<(User, i64, i64, i64)>> {
+        let user = match tx.as_mut() {
+            Some(uow) => self.get_user(Some(&mut **uow), user_id).await?,
+            None => self.get_user(None, user_id).await?,
+        };
+        let user = match user {
+            Some(user) => user,
+            None => return Ok(None),
+        };
 
-        Ok(result)
+        let counts_query = sqlx::query(
+            "SELECT (SELECT COUNT(*) FROM follows WHERE followee_id = $1) AS followers_count, \
+                    (SELECT COUNT(*) FROM follows WHERE follower_id = $1) AS following_count, \
+                    (SELECT COUNT(*) FROM posts WHERE owner_id = $1) AS posts_count",
+        )
+        .bind(user_id);
+
+        let row = match tx {
+            Some(uow) => counts_query.fetch_one(uow.conn()).await?,
+            None => counts_query.fetch_one(self.db.pool()).await?,
+        };
+
+        let followers_count: i64 = row.get("followers_count");
+        let following_count: i64 = row.get("following_count");
+        let posts_count: i64 = row.get("posts_count");
+
+        Ok(Some((user, followers_count, following_count, posts_count)))
     }
 
+    /// Updates the given fields and records each actual change in
+    /// `profile_history` in the same transaction, so the write and its
+    /// audit trail always succeed or fail together.
     pub async fn update_profile(
         &self,
+        tx: Option<&mut UnitOfWork>,
         user_id: Uuid,
         display_name: Option<String>,
         bio: Option<String>,
         avatar_key: Option<String>,
     ) -> Result<Option<User>> {
-        let row = sqlx::query(
-            "UPDATE users \
-             SET display_name = COALESCE($2, display_name), \
-                 bio = COALESCE($3, bio), \
-                 avatar_key = COALESCE($4, avatar_key) \
-             WHERE id = $1 AND deleted_at IS NULL \
-             RETURNING id, handle, email, display_name, bio, avatar_key, created_at",
-        )
-        .bind(user_id)
-        .bind(display_name)
-        .bind(bio)
-        .bind(avatar_key)
-        .fetch_optional(self.db.pool())
-        .await?;
+        let user = match tx {
+            Some(uow) => {
+                self.update_profile_in(uow, user_id, display_name, bio, avatar_key)
+                    .await?
+            }
+            None => {
+                let mut uow = UnitOfWork::begin(&self.db).await?;
+                let user = self
+                    .update_profile_in(&mut uow, user_id, display_name, bio, avatar_key)
+                    .await?;
+                if user.is_some() {
+                    uow.commit().await?;
+                } else {
+                    uow.rollback().await?;
+                }
+                user
+            }
+        };
 
-        let user = row.map(|row| User {
-            id: row.get("id"),
-            handle: row.get("handle"),
-            email: row.get("email"),
-            display_name: row.get("display_name"),
-            bio: row.get("bio"),
-            avatar_key: row.get("avatar_key"),
-            avatar_url: None,
-            created_at: row.get("created_at"),
-        });
+        if let Some(user) = &user {
+            self.cache.put(user.clone());
+        }
 
         Ok(user)
     }
 
-    /// Soft-delete user account (GDPR/CCPA compliance)
-    /// Sets deleted_at timestamp and cleans up related data that previously relied on CASCADE.
-    pub async fn delete_account(&self, user_id: Uuid) -> Result<bool> {
-        let mut tx = self.db.pool().begin().await?;
-
-        // Soft-delete the user
-        let result = sqlx::query(
-            "UPDATE users SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+    async fn update_profile_in(
+        &self,
+        uow: &mut UnitOfWork,
+        user_id: Uuid,
+        display_name: Option<String>,
+        bio: Option<String>,
+        avatar_key: Option<String>,
+    ) -> Result<Option<User>> {
+        let old_row = sqlx::query(
+            "SELECT display_name, bio, avatar_key FROM users \
+             WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
         )
         .bind(user_id)
-        .execute(&mut *tx)
+        .fetch_optional(uow.conn())
         .await?;
 
-        if result.rows_affected() == 0 {
-            tx.rollback().await?;
+        let old_row = match old_row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let old_display_name: Option<String> = old_row.get("display_name");
+        let old_bio: Option<String> = old_row.get("bio");
+        let old_avatar_key: Option<String> = old_row.get("avatar_key");
+
+        let form = UpdateUserForm {
+            display_name: display_name.clone(),
+            bio: bio.clone(),
+            avatar_key: avatar_key.clone(),
+        };
+        let user = match User::update(&self.db, Some(&mut *uow), user_id, form).await? {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        for (field, old_value, new_value) in [
+            ("display_name", old_display_name, display_name),
+            ("bio", old_bio, bio),
+            ("avatar_key", old_avatar_key, avatar_key),
+        ] {
+            if let Some(new_value) = new_value {
+                if old_value.as_deref() != Some(new_value.as_str()) {
+                    sqlx::query(
+                        "INSERT INTO profile_history (user_id, field, old_value, new_value) \
+                         VALUES ($1, $2, $3, $4)",
+                    )
+                    .bind(user_id)
+                    .bind(field)
+                    .bind(old_value)
+                    .bind(new_value)
+                    .execute(uow.conn())
+                    .await?;
+                }
+            }
+        }
+
+        Ok(Some(user))
+    }
+
+    /// Toggle the "locked account" flag -- doesn't touch any existing
+    /// `follows` rows, so switching it on only changes how *future* follow
+    /// attempts land (see `SocialService::follow`), and switching it off
+    /// doesn't retroactively accept anything still `pending`.
+    pub async fn set_locked(&self, user_id: Uuid, locked: bool) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE users SET is_private = $2 WHERE id = $1 AND deleted_at IS NULL")
+                .bind(user_id)
+                .bind(locked)
+                .execute(self.db.pool())
+                .await?;
+
+        let updated = result.rows_affected() > 0;
+        if updated {
+            self.cache.invalidate(user_id);
+        }
+
+        Ok(updated)
+    }
+
+    /// Deactivate a user account (GDPR/CCPA "delete my account" entry
+    /// point). Sets `deleted_at` and revokes refresh tokens, but -- unlike
+    /// the old `delete_account` this replaces -- leaves `follows`/`blocks`
+    /// untouched, so `restore_account` can bring the account back with its
+    /// social graph intact. The hard cleanup of that graph only happens in
+    /// `jobs::account_purge`, once `recovery_grace_days` has elapsed.
+    ///
+    /// `tx: None` begins and commits its own `UnitOfWork`, matching the
+    /// previous standalone-transaction behavior. `tx: Some(uow)` joins the
+    /// caller's unit of work instead -- the caller commits or rolls back,
+    /// so e.g. a downstream notifications cleanup can share the same
+    /// transaction and the whole request succeeds or fails atomically.
+    pub async fn deactivate_account(
+        &self,
+        tx: Option<&mut UnitOfWork>,
+        user_id: Uuid,
+    ) -> Result<bool> {
+        let deactivated = match tx {
+            Some(uow) => self.deactivate_account_in(uow, user_id).await?,
+            None => {
+                let mut uow = UnitOfWork::begin(&self.db).await?;
+                let deactivated = self.deactivate_account_in(&mut uow, user_id).await?;
+                if deactivated {
+                    uow.commit().await?;
+                } else {
+                    uow.rollback().await?;
+                }
+                deactivated
+            }
+        };
+
+        if deactivated {
+            self.cache.invalidate(user_id);
+        }
+
+        Ok(deactivated)
+    }
+
+    async fn deactivate_account_in(&self, uow: &mut UnitOfWork, user_id: Uuid) -> Result<bool> {
+        if !User::delete(&self.db, Some(&mut *uow), user_id).await? {
             return Ok(false);
         }
 
@@ -130,24 +368,199 @@ impl UserService {
             "UPDATE refresh_tokens SET revoked_at = now() WHERE user_id = $1 AND revoked_at IS NULL",
         )
         .bind(user_id)
-        .execute(&mut *tx)
+        .execute(uow.conn())
         .await?;
 
-        // Remove all follow relationships (both directions)
-        sqlx::query("DELETE FROM follows WHERE follower_id = $1 OR followee_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
+        Ok(true)
+    }
 
-        // Remove all block relationships (both directions)
-        sqlx::query("DELETE FROM blocks WHERE blocker_id = $1 OR blocked_id = $1")
+    /// Clears `deleted_at` if, and only if, `user_id` is currently
+    /// deactivated and still inside its `recovery_grace_days` window.
+    /// Returns `false` for an active account, a nonexistent one, or one
+    /// whose window has already elapsed (at that point only
+    /// `jobs::account_purge` still has a say).
+    pub async fn restore_account(&self, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = NULL \
+             WHERE id = $1 AND deleted_at IS NOT NULL \
+               AND deleted_at > now() - make_interval(days => $2::int)",
+        )
+        .bind(user_id)
+        .bind(self.recovery_grace_days as i32)
+        .execute(self.db.pool())
+        .await?;
+
+        let restored = result.rows_affected() > 0;
+        if restored {
+            self.cache.invalidate(user_id);
+        }
+
+        Ok(restored)
+    }
+
+    /// Whether `user_id` is deactivated and, if so, whether it's still
+    /// within its recovery window -- the same cutoff `AuthService::login`
+    /// applies inline when deciding whether to point the client at
+    /// `restore_account`, for a "reactivate your account" UI. Bypasses `User::read`'s
+    /// `deleted_at IS NULL` filter, since a deactivated row is exactly what
+    /// this is looking for.
+    pub async fn account_recovery_status(&self, user_id: Uuid) -> Result<AccountRecoveryStatus> {
+        let row = sqlx::query("SELECT deleted_at FROM users WHERE id = $1")
             .bind(user_id)
-            .execute(&mut *tx)
+            .fetch_optional(self.db.pool())
             .await?;
 
-        tx.commit().await?;
+        let deleted_at: Option<OffsetDateTime> = match row {
+            Some(row) => row.get("deleted_at"),
+            None => return Ok(AccountRecoveryStatus::NotDeactivated),
+        };
 
-        Ok(true)
+        match deleted_at {
+            None => Ok(AccountRecoveryStatus::NotDeactivated),
+            Some(deleted_at) => {
+                let cutoff = OffsetDateTime::now_utc() - time::Duration::days(self.recovery_grace_days);
+                if deleted_at > cutoff {
+                    Ok(AccountRecoveryStatus::Recoverable { deleted_at })
+                } else {
+                    Ok(AccountRecoveryStatus::Expired { deleted_at })
+                }
+            }
+        }
     }
 }
 
+/// Outcome of `UserService::account_recovery_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountRecoveryStatus {
+    NotDeactivated,
+    Recoverable { deleted_at: OffsetDateTime },
+    Expired { deleted_at: OffsetDateTime },
+}
+
+/// Fields needed to insert a brand-new `users` row. Deliberately narrower
+/// than the signup flow: `AuthService::signup` owns invite redemption and
+/// password hashing and inserts its own `users` row rather than going
+/// through this form. The form takes an already-hashed password and only
+/// knows how to put a row in the table.
+pub struct NewUserForm {
+    pub handle: String,
+    pub email: String,
+    pub display_name: String,
+    pub bio: Option<String>,
+    pub avatar_key: Option<String>,
+    pub password_hash: String,
+}
+
+/// Fields `UserService::update_profile` lets a caller change. `None` means
+/// "leave as-is", matching the `COALESCE` semantics of the underlying
+/// `UPDATE`.
+pub struct UpdateUserForm {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_key: Option<String>,
+}
+
+#[axum::async_trait]
+impl Crud for User {
+    type Id = Uuid;
+    type InsertForm = NewUserForm;
+    type UpdateForm = UpdateUserForm;
+
+    async fn create(db: &Db, tx: Option<&mut UnitOfWork>, form: Self::InsertForm) -> Result<Self> {
+        let query = sqlx::query(
+            "INSERT INTO users (handle, email, display_name, bio, avatar_key, password_hash) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             RETURNING id, handle, email, display_name, bio, avatar_key, created_at, is_private",
+        )
+        .bind(form.handle)
+        .bind(form.email)
+        .bind(form.display_name)
+        .bind(form.bio)
+        .bind(form.avatar_key)
+        .bind(form.password_hash);
+
+        let row = match tx {
+            Some(uow) => query.fetch_one(uow.conn()).await?,
+            None => query.fetch_one(db.pool()).await?,
+        };
+
+        Ok(row_to_user(&row))
+    }
+
+    /// A globally-banned user is hidden the same way a deleted one is --
+    /// scoped bans (e.g. "posting") don't affect whether the profile itself
+    /// is visible, only a global ban does.
+    async fn read(db: &Db, tx: Option<&mut UnitOfWork>, id: Self::Id) -> Result<Option<Self>> {
+        let query = sqlx::query(
+            "SELECT id, handle, email, display_name, bio, avatar_key, created_at, is_private \
+             FROM users WHERE id = $1 AND deleted_at IS NULL \
+               AND NOT EXISTS ( \
+                 SELECT 1 FROM bans \
+                 WHERE target_id = users.id AND scope IS NULL AND lifted_at IS NULL \
+                   AND (expires_at IS NULL OR expires_at > now()) \
+               )",
+        )
+        .bind(id);
+
+        let row = match tx {
+            Some(uow) => query.fetch_optional(uow.conn()).await?,
+            None => query.fetch_optional(db.pool()).await?,
+        };
+
+        Ok(row.map(|row| row_to_user(&row)))
+    }
+
+    async fn update(
+        db: &Db,
+        tx: Option<&mut UnitOfWork>,
+        id: Self::Id,
+        form: Self::UpdateForm,
+    ) -> Result<Option<Self>> {
+        let query = sqlx::query(
+            "UPDATE users \
+             SET display_name = COALESCE($2, display_name), \
+                 bio = COALESCE($3, bio), \
+                 avatar_key = COALESCE($4, avatar_key) \
+             WHERE id = $1 AND deleted_at IS NULL \
+             RETURNING id, handle, email, display_name, bio, avatar_key, created_at, is_private",
+        )
+        .bind(id)
+        .bind(form.display_name)
+        .bind(form.bio)
+        .bind(form.avatar_key);
+
+        let row = match tx {
+            Some(uow) => query.fetch_optional(uow.conn()).await?,
+            None => query.fetch_optional(db.pool()).await?,
+        };
+
+        Ok(row.map(|row| row_to_user(&row)))
+    }
+
+    async fn delete(db: &Db, tx: Option<&mut UnitOfWork>, id: Self::Id) -> Result<bool> {
+        let query =
+            sqlx::query("UPDATE users SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL")
+                .bind(id);
+
+        let result = match tx {
+            Some(uow) => query.execute(uow.conn()).await?,
+            None => query.execute(db.pool()).await?,
+        };
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_user(row: &sqlx::postgres::PgRow) -> User {
+    User {
+        id: row.get("id"),
+        handle: row.get("handle"),
+        email: row.get("email"),
+        display_name: row.get("display_name"),
+        bio: row.get("bio"),
+        avatar_key: row.get("avatar_key"),
+        avatar_url: None,
+        created_at: row.get("created_at"),
+        is_locked: row.get("is_private"),
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use sqlx::Row;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::app::siwe::{parse_siwe_message, recover_address, to_checksum_address};
+use crate::domain::wallet::WalletLink;
+use crate::infra::db::Db;
+
+/// How long an issued nonce may be redeemed before it must be reissued.
+const NONCE_TTL_MINUTES: i64 = 10;
+
+#[derive(Clone)]
+pub struct WalletService {
+    db: Db,
+}
+
+impl WalletService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    /// Issue a single-use nonce bound to `user_id`, to be embedded in the
+    /// `Nonce:` field of the SIWE message the client signs.
+    pub async fn issue_nonce(&self, user_id: Uuid) -> Result<String> {
+        let nonce: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        let expires_at = OffsetDateTime::now_utc() + Duration::minutes(NONCE_TTL_MINUTES);
+
+        sqlx::query(
+            "INSERT INTO wallet_nonces (user_id, nonce, expires_at) VALUES ($1, $2, $3)",
+        )
+        .bind(user_id)
+        .bind(&nonce)
+        .bind(expires_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(nonce)
+    }
+
+    /// Verify a signed SIWE message and link the recovered wallet address to
+    /// `user_id`. Returns `Ok(None)` if the signature does not recover to the
+    /// claimed address (a bad proof, as opposed to a malformed request).
+    pub async fn verify_and_link(
+        &self,
+        user_id: Uuid,
+        message: &str,
+        signature_hex: &str,
+        expected_domain: &str,
+    ) -> Result<Option<WalletLink>> {
+        let parsed = parse_siwe_message(message)?;
+
+        if !parsed.domain.eq_ignore_ascii_case(expected_domain) {
+            return Err(anyhow!("domain mismatch"));
+        }
+
+        if let Some(expiration_time) = parsed.expiration_time {
+            if expiration_time <= OffsetDateTime::now_utc() {
+                return Err(anyhow!("message has expired"));
+            }
+        }
+
+        let recovered = recover_address(message, signature_hex)?;
+        if !recovered.eq_ignore_ascii_case(&parsed.address) {
+            return Ok(None);
+        }
+        let address = to_checksum_address(&recovered);
+
+        // Consume the nonce atomically so a captured message can't be replayed.
+        let mut tx = self.db.pool().begin().await?;
+        let consumed = sqlx::query(
+            "UPDATE wallet_nonces SET consumed_at = now() \
+             WHERE user_id = $1 AND nonce = $2 AND consumed_at IS NULL AND expires_at > now()",
+        )
+        .bind(user_id)
+        .bind(&parsed.nonce)
+        .execute(&mut *tx)
+        .await?;
+
+        if consumed.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Err(anyhow!("nonce is invalid, expired, or already used"));
+        }
+
+        let row = sqlx::query(
+            "INSERT INTO wallet_links (user_id, address) VALUES ($1, $2) \
+             ON CONFLICT (user_id) DO UPDATE SET address = EXCLUDED.address, linked_at = now() \
+             RETURNING user_id, address, linked_at",
+        )
+        .bind(user_id)
+        .bind(&address)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO moderation_actions (actor_id, target_type, target_id, reason) \
+             VALUES ($1, 'wallet_link', $1, $2)",
+        )
+        .bind(user_id)
+        .bind(format!("linked wallet {}", address))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(WalletLink {
+            user_id: row.get("user_id"),
+            address: row.get("address"),
+            linked_at: row.get("linked_at"),
+        }))
+    }
+
+    /// The wallet currently linked to `user_id`, if any.
+    pub async fn get_linked_wallet(&self, user_id: Uuid) -> Result<Option<WalletLink>> {
+        let row = sqlx::query(
+            "SELECT user_id, address, linked_at FROM wallet_links WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|row| WalletLink {
+            user_id: row.get("user_id"),
+            address: row.get("address"),
+            linked_at: row.get("linked_at"),
+        }))
+    }
+}
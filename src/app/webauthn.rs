@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use base64::Engine;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::app::auth::{AuthService, DeviceContext, TokenPair};
+use crate::infra::cache::RedisCache;
+use crate::infra::db::Db;
+
+/// How long a client has to complete the second round-trip of either
+/// ceremony before the parked challenge expires and must be restarted.
+const REGISTRATION_HANDLE_TTL_SECONDS: i64 = 300;
+const AUTHENTICATION_HANDLE_TTL_SECONDS: i64 = 120;
+
+#[derive(Serialize, Deserialize)]
+struct RegistrationHandleState {
+    user_id: Uuid,
+    challenge: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AuthenticationHandleState {
+    user_id: Uuid,
+    challenge: String,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony_type: String,
+    challenge: String,
+}
+
+#[derive(Clone)]
+pub struct WebauthnService {
+    db: Db,
+    cache: RedisCache,
+}
+
+impl WebauthnService {
+    pub fn new(db: Db, cache: RedisCache) -> Self {
+        Self { db, cache }
+    }
+
+    /// First registration round-trip: park a fresh challenge for `user_id`
+    /// so `finish_registration` can confirm the client signed this exact one.
+    pub async fn begin_registration(&self, user_id: Uuid) -> Result<(String, String)> {
+        let challenge = random_challenge();
+        let handle = Uuid::new_v4().to_string();
+        self.put_handle(
+            &registration_handle_key(&handle),
+            &RegistrationHandleState {
+                user_id,
+                challenge: challenge.clone(),
+            },
+            REGISTRATION_HANDLE_TTL_SECONDS,
+        )
+        .await?;
+
+        Ok((handle, challenge))
+    }
+
+    /// Second registration round-trip: confirm the client's `client_data_json`
+    /// covers the challenge we parked, then store the asserted credential.
+    ///
+    /// This deliberately does not verify an attestation statement -- doing so
+    /// would mean maintaining a trusted-root store per authenticator vendor,
+    /// which is out of scope here. The client still has to prove it holds the
+    /// matching private key on every subsequent `finish_authentication` call,
+    /// so a credential registered this way is only as trustworthy as the
+    /// device that later authenticates with it.
+    pub async fn finish_registration(
+        &self,
+        handle: &str,
+        credential_id: &str,
+        public_key_b64: &str,
+        client_data_json: &str,
+    ) -> Result<()> {
+        let state: RegistrationHandleState = self
+            .take_handle(&registration_handle_key(handle))
+            .await?
+            .ok_or_else(|| anyhow!("registration handle not found or expired"))?;
+
+        verify_client_data(client_data_json, &state.challenge, "webauthn.create")?;
+
+        let public_key_bytes = BASE64
+            .decode(public_key_b64)
+            .map_err(|_| anyhow!("public_key is not valid base64"))?;
+        VerifyingKey::from_sec1_bytes(&public_key_bytes)
+            .map_err(|_| anyhow!("malformed public_key"))?;
+
+        sqlx::query(
+            "INSERT INTO credentials (credential_id, public_key, sign_count, user_id) \
+             VALUES ($1, $2, 0, $3)",
+        )
+        .bind(credential_id)
+        .bind(public_key_b64)
+        .bind(state.user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// First authentication round-trip: park a fresh challenge against the
+    /// account matching `identifier`, so any of its registered credentials
+    /// may complete it.
+    pub async fn begin_authentication(&self, identifier: &str) -> Result<(String, String)> {
+        let user_id: Uuid = sqlx::query_scalar(
+            "SELECT id FROM users WHERE email = $1 OR handle = $1",
+        )
+        .bind(identifier)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| anyhow!("no account for that identifier"))?;
+
+        let challenge = random_challenge();
+        let handle = Uuid::new_v4().to_string();
+        self.put_handle(
+            &authentication_handle_key(&handle),
+            &AuthenticationHandleState {
+                user_id,
+                challenge: challenge.clone(),
+            },
+            AUTHENTICATION_HANDLE_TTL_SECONDS,
+        )
+        .await?;
+
+        Ok((handle, challenge))
+    }
+
+    /// Second authentication round-trip: verify the assertion signature
+    /// against the stored public key, reject a signature counter that
+    /// didn't strictly increase (a strong signal the credential was cloned)
+    /// unless both the stored and the new counter are 0, and on success mint
+    /// the usual PASETO token pair.
+    pub async fn finish_authentication(
+        &self,
+        handle: &str,
+        credential_id: &str,
+        authenticator_data_b64: &str,
+        client_data_json: &str,
+        signature_b64: &str,
+        auth_service: &AuthService,
+    ) -> Result<TokenPair> {
+        let state: AuthenticationHandleState = self
+            .take_handle(&authentication_handle_key(handle))
+            .await?
+            .ok_or_else(|| anyhow!("authentication handle not found or expired"))?;
+
+        verify_client_data(client_data_json, &state.challenge, "webauthn.get")?;
+
+        let row = sqlx::query(
+            "SELECT public_key, sign_count FROM credentials \
+             WHERE credential_id = $1 AND user_id = $2",
+        )
+        .bind(credential_id)
+        .bind(state.user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| anyhow!("unknown credential for that account"))?;
+
+        let public_key_b64: String = row.get("public_key");
+        let stored_sign_count: i64 = row.get("sign_count");
+
+        let public_key_bytes = BASE64
+            .decode(&public_key_b64)
+            .map_err(|_| anyhow!("corrupt stored public_key"))?;
+        let verifying_key = VerifyingKey::from_sec1_bytes(&public_key_bytes)
+            .map_err(|_| anyhow!("corrupt stored public_key"))?;
+
+        let authenticator_data = BASE64
+            .decode(authenticator_data_b64)
+            .map_err(|_| anyhow!("authenticator_data is not valid base64"))?;
+        if authenticator_data.len() < 37 {
+            return Err(anyhow!("malformed authenticator_data"));
+        }
+        let new_sign_count =
+            u32::from_be_bytes(authenticator_data[33..37].try_into().unwrap()) as i64;
+
+        let signature_bytes = BASE64
+            .decode(signature_b64)
+            .map_err(|_| anyhow!("signature is not valid base64"))?;
+        let signature = Signature::from_der(&signature_bytes)
+            .or_else(|_| Signature::from_slice(&signature_bytes))
+            .map_err(|_| anyhow!("malformed signature"))?;
+
+        let client_data_hash = Sha256::digest(client_data_json.as_bytes());
+        let mut signed_data = authenticator_data.clone();
+        signed_data.extend_from_slice(&client_data_hash);
+
+        verifying_key
+            .verify(&signed_data, &signature)
+            .map_err(|_| anyhow!("assertion signature verification failed"))?;
+
+        // Clone detection: a genuine authenticator's counter only moves
+        // forward. A counter that fails to increase means either a replayed
+        // assertion or a cloned credential -- either way, reject it. A
+        // counter that stays at 0 on both sides is let through.
+        if new_sign_count <= stored_sign_count && !(new_sign_count == 0 && stored_sign_count == 0) {
+            return Err(anyhow!("signature counter did not increase, possible cloned credential"));
+        }
+
+        sqlx::query("UPDATE credentials SET sign_count = $1 WHERE credential_id = $2")
+            .bind(new_sign_count)
+            .bind(credential_id)
+            .execute(self.db.pool())
+            .await?;
+
+        auth_service
+            .issue_tokens_for_user(state.user_id, DeviceContext::default())
+            .await
+    }
+
+    async fn put_handle<T: Serialize>(&self, key: &str, value: &T, ttl_seconds: i64) -> Result<()> {
+        use redis::AsyncCommands;
+        let payload = serde_json::to_string(value)?;
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        conn.set_ex(key, payload, ttl_seconds as u64).await?;
+        Ok(())
+    }
+
+    async fn take_handle<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
+        use redis::AsyncCommands;
+        let mut conn = self.cache.client().get_multiplexed_async_connection().await?;
+        let payload: Option<String> = conn.get(key).await?;
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        // Single-use: a captured handle can't be replayed against the same
+        // in-progress ceremony.
+        let _: () = conn.del(key).await?;
+        Ok(Some(serde_json::from_str(&payload)?))
+    }
+}
+
+fn verify_client_data(client_data_json: &str, expected_challenge: &str, expected_type: &str) -> Result<()> {
+    let client_data: ClientData = serde_json::from_str(client_data_json)
+        .map_err(|_| anyhow!("malformed client_data_json"))?;
+
+    if client_data.ceremony_type != expected_type {
+        return Err(anyhow!("unexpected client_data_json type"));
+    }
+    if client_data.challenge != expected_challenge {
+        return Err(anyhow!("client_data_json does not match the expected challenge"));
+    }
+
+    Ok(())
+}
+
+fn random_challenge() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn registration_handle_key(handle: &str) -> String {
+    format!("webauthn:register:{}", handle)
+}
+
+fn authentication_handle_key(handle: &str) -> String {
+    format!("webauthn:authenticate:{}", handle)
+}
@@ -3,14 +3,175 @@ pub mod rate_limits;
 use anyhow::{anyhow, Result};
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
-use std::net::SocketAddr;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Which SQL dialect `DATABASE_URL` points at. Derived once at startup from
+/// the URL scheme so callers don't re-parse it; see
+/// `app::search_store::SearchStore` for the backend this currently gates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DbBackend {
+    Postgres,
+    Sqlite,
+}
+
+/// Deployment environment, read from `APP_ENV` (defaults to `development`).
+/// Picks both the `.env` file `from_env` merges and the tracing output
+/// format `init_tracing` installs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppEnv {
+    Development,
+    Production,
+}
+
+impl FromStr for AppEnv {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "development" | "dev" => Ok(AppEnv::Development),
+            "production" | "prod" => Ok(AppEnv::Production),
+            other => Err(anyhow!("invalid APP_ENV: {}", other)),
+        }
+    }
+}
+
+/// Minimum `tracing` event level to emit, read from `LOG_LEVEL` (defaults to
+/// `info`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            other => Err(anyhow!("invalid LOG_LEVEL: {}", other)),
+        }
+    }
+}
+
+impl LogLevel {
+    fn as_filter_directive(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+/// When `MediaService` produces a derivative (resize/thumbnail/crop) of a
+/// `media` row, read from `MEDIA_DERIVATIVE_MODE` (defaults to `lazy`).
+/// Mirrors the three strategies image hosts commonly choose between:
+/// generate-on-first-request, generate-eagerly-at-upload-time, or never
+/// persist a derivative at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerivativeMode {
+    /// Generate a variant the first time it's requested and cache it --
+    /// the original behavior, and still the right default for workloads
+    /// where most derivative sizes are never actually requested.
+    Lazy,
+    /// Pre-render every size in the derivative ladder as soon as an upload
+    /// finishes processing, so every later request is a cache hit.
+    Eager,
+    /// Never persist a derivative: resize on the fly and stream the result
+    /// on every request, trading CPU for zero extra storage.
+    Realtime,
+}
+
+impl FromStr for DerivativeMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "lazy" => Ok(DerivativeMode::Lazy),
+            "eager" => Ok(DerivativeMode::Eager),
+            "realtime" => Ok(DerivativeMode::Realtime),
+            other => Err(anyhow!("invalid MEDIA_DERIVATIVE_MODE: {}", other)),
+        }
+    }
+}
+
+/// How `ContentFilter::enforce` reacts to a banned-term match, read per-field
+/// from `COMMENT_FILTER_MODE`/`PROFILE_FILTER_MODE` (both default to
+/// `reject`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Refuse the submission, the same way a too-long `bio` is refused.
+    Reject,
+    /// Let the submission through with each matched word replaced by
+    /// asterisks of equal length.
+    Censor,
+}
+
+impl FromStr for FilterMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "reject" => Ok(FilterMode::Reject),
+            "censor" => Ok(FilterMode::Censor),
+            other => Err(anyhow!("invalid filter mode: {}", other)),
+        }
+    }
+}
+
+/// Static client configuration for one OAuth/OIDC identity provider, loaded
+/// from `OAUTH_{NAME}_*` env vars named after the provider key in
+/// `OAUTH_PROVIDERS`.
+#[derive(Clone, Debug)]
+pub struct OAuthProviderConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub authorize_url: String,
+    pub token_url: String,
+    pub redirect_uri: String,
+}
+
+/// Config for authenticating `AuthService::login` against a corporate LDAP
+/// directory instead of (or alongside) local password hashes. Present only
+/// when `LDAP_URL` is set -- most deployments have no directory, so this
+/// whole auth source is opt-in.
+#[derive(Clone, Debug)]
+pub struct LdapConfig {
+    pub url: String,
+    /// Bind DN with `{username}` substituted for the login identifier, e.g.
+    /// `uid={username},ou=people,dc=example,dc=com`.
+    pub bind_dn_template: String,
+    /// Base DN to search for the bound user's entry, to read back the
+    /// attributes `handle`/`email`/`display_name` map to.
+    pub search_base: String,
+    pub username_attr: String,
+    pub handle_attr: String,
+    pub email_attr: String,
+    pub display_name_attr: String,
+}
 
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub http_addr: String,
     pub app_mode: String,
+    pub env: AppEnv,
+    pub log_level: LogLevel,
     pub database_url: String,
+    pub db_backend: DbBackend,
     pub redis_url: String,
     pub s3_endpoint: String,
     pub s3_public_endpoint: Option<String>,
@@ -26,18 +187,146 @@ pub struct AppConfig {
     pub admin_token: Option<String>,
     pub upload_url_ttl_seconds: u64,
     pub upload_max_bytes: i64,
-    pub paseto_access_key: [u8; 32],
-    pub paseto_refresh_key: [u8; 32],
+    /// Ordered newest-first; entry 0 signs new access tokens, every entry
+    /// can still verify one. See `PASETO_ACCESS_KEYS`/`env_keyring` for the
+    /// rotation-friendly format and `PASETO_ACCESS_KEY` for the single-key
+    /// fallback.
+    pub paseto_access_keys: Vec<(String, [u8; 32])>,
+    pub paseto_refresh_keys: Vec<(String, [u8; 32])>,
     pub access_ttl_minutes: u64,
     pub refresh_ttl_days: u64,
+    /// HMAC-SHA256 key used to seal pagination cursors (`handlers::encode_cursor`
+    /// / `parse_cursor`) so they're opaque and tamper-evident to clients.
+    pub cursor_hmac_key: [u8; 32],
+    /// Salt `app::shortid` mixes into post/comment/media ids before
+    /// shuffling them through its alphabet, so short ids are stable for
+    /// this deployment but unpredictable to anyone who doesn't have it.
+    pub shortid_salt: [u8; 32],
+    pub siwe_domain: String,
+    /// CIDR blocks (e.g. load balancers) allowed to supply the real client
+    /// IP via `X-Forwarded-For`/`Forwarded`. Requests from any other peer
+    /// are rate-limited on their own socket address.
+    pub trusted_proxy_cidrs: Vec<(IpAddr, u8)>,
+    pub ip_subnet_prefix_v4: u8,
+    pub ip_subnet_prefix_v6: u8,
+    pub oauth_providers: HashMap<String, OAuthProviderConfig>,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    pub smtp_password: String,
+    pub smtp_from: String,
+    pub email_verification_ttl_hours: i64,
+    pub password_reset_ttl_hours: i64,
+    /// How long a deactivated account can be restored via
+    /// `UserService::restore_account` before `jobs::account_purge` hard-
+    /// deletes its follows/blocks/tokens.
+    pub account_recovery_grace_days: i64,
+    /// Half-life, in days, of the exponential decay `jobs::trust_maintenance`
+    /// applies nightly to `trust_points` for accounts past
+    /// `trust_activity_staleness_days` of inactivity -- see
+    /// `TrustService::run_daily_maintenance`.
+    pub trust_decay_half_life_days: f64,
+    /// An account stops earning decay-exempt "active" status this many days
+    /// after its `last_activity_at`.
+    pub trust_activity_staleness_days: i64,
+    pub push_queue_name: String,
+    pub vapid_private_key: [u8; 32],
+    pub vapid_subject: String,
+    pub deletion_queue_name: String,
+    pub video_max_duration_seconds: u64,
+    pub media_derivative_mode: DerivativeMode,
+    /// Caps simultaneous completion hashing / format sniffing / derivative
+    /// rendering across *all* requests, independent of the per-user
+    /// `ConcurrencyLimiter`. `None` (the default, unset) means unlimited.
+    pub media_max_concurrency: Option<usize>,
+    pub media_concurrency_acquire_timeout_ms: u64,
+    pub federation_queue_name: String,
+    /// The instance's own public origin, used to mint ActivityPub actor and
+    /// object URIs (`{base}/users/{id}/actor`, `{base}/stories/{id}`) for outbound
+    /// federation -- there's no separate "instance domain" setting yet, so
+    /// this doubles as that until one's needed for something else.
+    pub federation_base_url: String,
+    /// Local address `GossipClient` binds its UDP socket to.
+    pub gossip_bind_addr: String,
+    /// Other instances' gossip addresses, e.g. `10.0.0.2:7946,10.0.0.3:7946`.
+    /// Empty (the default) disables gossip: broadcasts become no-ops.
+    pub gossip_peers: Vec<SocketAddr>,
+    /// Corporate directory `AuthService::login` binds against before
+    /// falling back to the local password hash. `None` (the default, no
+    /// `LDAP_URL`) disables LDAP entirely.
+    pub ldap: Option<LdapConfig>,
+    /// Ed25519 seed `InviteService::issue_signed_invite` signs stateless
+    /// invite tokens with (see `app::invites::signed`). Rotating this value
+    /// invalidates every outstanding signed token at once -- the bulk-revoke
+    /// mechanism for that token type, since there's no per-token DB row to
+    /// flip `is_valid` on.
+    pub invite_signing_key: [u8; 32],
+    /// Ed25519 seed this instance signs outbound Lumine-to-Lumine
+    /// Follow/Block edge pushes with (see `app::social_federation`) --
+    /// distinct from the per-user RSA actor keys ActivityPub federation
+    /// uses, since peers verify this one as a server identity, not a
+    /// specific actor's.
+    pub server_signing_key: [u8; 32],
+    pub social_edge_queue_name: String,
+    /// Banned terms `app::content_filter::ContentFilter` is compiled from at
+    /// startup, read from `BANNED_TERMS` (comma-separated). Empty (the
+    /// default) disables the filter: nothing ever matches.
+    pub banned_terms: Vec<String>,
+    /// `FilterMode` applied to `EngagementService::comment_post` bodies.
+    pub comment_filter_mode: FilterMode,
+    /// `FilterMode` applied to signup/`update_profile`'s `display_name` and
+    /// `bio` fields.
+    pub profile_filter_mode: FilterMode,
+    /// Deepest a comment reply chain may nest, enforced by
+    /// `EngagementService::comment_post` at insert time. `0` means only
+    /// top-level comments (no replies) are accepted.
+    pub max_comment_depth: i32,
+    /// Reaction kinds the `react_post` handler accepts, read from
+    /// `REACTION_KINDS` (comma-separated). The `/like` endpoints, a
+    /// compatibility shim over reactions, call `EngagementService::like_post`
+    /// without consulting this list, so they keep working even if an
+    /// operator trims `like` from it.
+    pub allowed_reaction_kinds: Vec<String>,
+    /// Queue `jobs::bot_webhook_dispatcher` drains to deliver signed
+    /// interaction webhooks (see `app::bots::BotService`) -- same
+    /// per-purpose-queue shape as `federation_queue_name`.
+    pub bot_webhook_queue_name: String,
+    /// Dead-letter queue `QueueClient::new` points the main media queue's
+    /// `RedrivePolicy` at -- a parse failure or a job that exhausts
+    /// `media_queue_max_receive_count` lands here instead of being dropped.
+    pub media_dlq_name: String,
+    /// How many times SQS will redeliver a media job before routing it to
+    /// `media_dlq_name` automatically, per the queue's `RedrivePolicy`.
+    pub media_queue_max_receive_count: i32,
 }
 
 impl AppConfig {
     pub fn from_env() -> Result<Self> {
+        // `APP_ENV` picks which dotenv file to merge, so it is read from the
+        // process env before that merge runs -- an `APP_ENV` set only inside
+        // a dotenv file has no effect on which file gets picked.
+        let env: AppEnv = env_or("APP_ENV", "development").parse()?;
+        let dotenv_file = match env {
+            AppEnv::Production => ".env.production",
+            AppEnv::Development => ".env",
+        };
+        dotenvy::from_filename(dotenv_file).ok();
+
+        let log_level: LogLevel = env_or("LOG_LEVEL", "info").parse()?;
+
         let http_addr = env_or("HTTP_ADDR", "0.0.0.0:8080");
         let _parsed_http_addr = SocketAddr::from_str(&http_addr)
             .map_err(|err| anyhow!("invalid HTTP_ADDR: {}", err))?;
         let app_mode = env_or("APP_MODE", "api");
+        if !matches!(app_mode.as_str(), "api" | "serverless-worker") {
+            return Err(anyhow!(
+                "invalid APP_MODE: {} (expected \"api\" or \"serverless-worker\")",
+                app_mode
+            ));
+        }
+
+        let database_url = env_or_err("DATABASE_URL")?;
+        let db_backend = parse_db_backend(&database_url)?;
 
         let s3_region = env_or("S3_REGION", "fr-par");
         let queue_region = std::env::var("QUEUE_REGION").unwrap_or_else(|_| s3_region.clone());
@@ -45,7 +334,10 @@ impl AppConfig {
         Ok(Self {
             http_addr,
             app_mode,
-            database_url: env_or_err("DATABASE_URL")?,
+            env,
+            log_level,
+            database_url,
+            db_backend,
             redis_url: env_or("REDIS_URL", "redis://127.0.0.1/"),
             s3_endpoint: env_or_err("S3_ENDPOINT")?,
             s3_public_endpoint: std::env::var("S3_PUBLIC_ENDPOINT").ok(),
@@ -61,12 +353,99 @@ impl AppConfig {
             admin_token: std::env::var("ADMIN_TOKEN").ok(),
             upload_url_ttl_seconds: env_or_parse("UPLOAD_URL_TTL_SECONDS", "900")?,
             upload_max_bytes: env_or_parse("UPLOAD_MAX_BYTES", "10485760")?,
-            paseto_access_key: env_key_32("PASETO_ACCESS_KEY")?,
-            paseto_refresh_key: env_key_32("PASETO_REFRESH_KEY")?,
+            paseto_access_keys: env_keyring("PASETO_ACCESS_KEYS", "PASETO_ACCESS_KEY")?,
+            paseto_refresh_keys: env_keyring("PASETO_REFRESH_KEYS", "PASETO_REFRESH_KEY")?,
             access_ttl_minutes: env_or_parse("ACCESS_TTL_MINUTES", "15")?,
             refresh_ttl_days: env_or_parse("REFRESH_TTL_DAYS", "30")?,
+            cursor_hmac_key: env_key_32("CURSOR_HMAC_KEY")?,
+            shortid_salt: env_key_32("SHORTID_SALT")?,
+            siwe_domain: env_or("SIWE_DOMAIN", "lumine.app"),
+            trusted_proxy_cidrs: parse_trusted_proxies(&env_or("TRUSTED_PROXY_CIDRS", ""))?,
+            ip_subnet_prefix_v4: env_or_parse("IP_SUBNET_PREFIX_V4", "24")?,
+            ip_subnet_prefix_v6: env_or_parse("IP_SUBNET_PREFIX_V6", "64")?,
+            oauth_providers: parse_oauth_providers(&env_or("OAUTH_PROVIDERS", ""))?,
+            smtp_host: env_or("SMTP_HOST", "localhost"),
+            smtp_port: env_or_parse("SMTP_PORT", "587")?,
+            smtp_username: env_or("SMTP_USERNAME", ""),
+            smtp_password: env_or("SMTP_PASSWORD", ""),
+            smtp_from: env_or("SMTP_FROM", "no-reply@lumine.app"),
+            email_verification_ttl_hours: env_or_parse("EMAIL_VERIFICATION_TTL_HOURS", "24")?,
+            password_reset_ttl_hours: env_or_parse("PASSWORD_RESET_TTL_HOURS", "1")?,
+            account_recovery_grace_days: env_or_parse("ACCOUNT_RECOVERY_GRACE_DAYS", "30")?,
+            trust_decay_half_life_days: env_or_parse("TRUST_DECAY_HALF_LIFE_DAYS", "30")?,
+            trust_activity_staleness_days: env_or_parse("TRUST_ACTIVITY_STALENESS_DAYS", "14")?,
+            push_queue_name: env_or("PUSH_QUEUE_NAME", "ciel-push-jobs"),
+            vapid_private_key: env_key_32("VAPID_PRIVATE_KEY")?,
+            vapid_subject: env_or("VAPID_SUBJECT", "mailto:support@lumine.app"),
+            deletion_queue_name: env_or("DELETION_QUEUE_NAME", "ciel-deletion-jobs"),
+            video_max_duration_seconds: env_or_parse("VIDEO_MAX_DURATION_SECONDS", "120")?,
+            media_derivative_mode: env_or("MEDIA_DERIVATIVE_MODE", "lazy").parse()?,
+            media_max_concurrency: match std::env::var("MEDIA_MAX_CONCURRENCY") {
+                Ok(value) if !value.is_empty() => Some(value.parse()?),
+                _ => None,
+            },
+            media_concurrency_acquire_timeout_ms: env_or_parse(
+                "MEDIA_CONCURRENCY_ACQUIRE_TIMEOUT_MS",
+                "2000",
+            )?,
+            federation_queue_name: env_or("FEDERATION_QUEUE_NAME", "ciel-federation-jobs"),
+            federation_base_url: env_or("FEDERATION_BASE_URL", "https://lumine.app"),
+            gossip_bind_addr: env_or("GOSSIP_BIND_ADDR", "0.0.0.0:7946"),
+            gossip_peers: parse_gossip_peers(&env_or("GOSSIP_PEERS", ""))?,
+            ldap: parse_ldap_config()?,
+            invite_signing_key: env_key_32("INVITE_SIGNING_KEY")?,
+            server_signing_key: env_key_32("SERVER_SIGNING_KEY")?,
+            social_edge_queue_name: env_or("SOCIAL_EDGE_QUEUE_NAME", "ciel-social-edge-jobs"),
+            banned_terms: env_or("BANNED_TERMS", "")
+                .split(',')
+                .map(str::trim)
+                .filter(|term| !term.is_empty())
+                .map(str::to_string)
+                .collect(),
+            comment_filter_mode: env_or("COMMENT_FILTER_MODE", "reject").parse()?,
+            profile_filter_mode: env_or("PROFILE_FILTER_MODE", "reject").parse()?,
+            max_comment_depth: env_or_parse("MAX_COMMENT_DEPTH", "5")?,
+            allowed_reaction_kinds: env_or("REACTION_KINDS", "like,love,laugh,wow,sad,angry")
+                .split(',')
+                .map(str::trim)
+                .filter(|kind| !kind.is_empty())
+                .map(str::to_string)
+                .collect(),
+            bot_webhook_queue_name: env_or("BOT_WEBHOOK_QUEUE_NAME", "ciel-bot-webhook-jobs"),
+            media_dlq_name: env_or("MEDIA_DLQ_NAME", "ciel-media-jobs-dlq"),
+            media_queue_max_receive_count: env_or_parse("MEDIA_QUEUE_MAX_RECEIVE_COUNT", "5")?,
         })
     }
+
+    /// Installs the global `tracing` subscriber from `log_level`/`env`.
+    /// `Production` emits JSON (for log aggregators); `Development` emits
+    /// pretty-printed, human-readable output. Must be called once, before
+    /// any other `tracing::*!` call.
+    pub fn init_tracing(&self) {
+        let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(self.log_level.as_filter_directive()));
+        let registry = tracing_subscriber::registry().with(filter);
+
+        match self.env {
+            AppEnv::Production => registry.with(tracing_subscriber::fmt::layer().json()).init(),
+            AppEnv::Development => registry.with(tracing_subscriber::fmt::layer().pretty()).init(),
+        }
+    }
+}
+
+/// Picks the SQL dialect from `DATABASE_URL`'s scheme, e.g.
+/// `postgres://...`/`postgresql://...` for Postgres or `sqlite://...` for
+/// SQLite.
+fn parse_db_backend(database_url: &str) -> Result<DbBackend> {
+    if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+        Ok(DbBackend::Postgres)
+    } else if database_url.starts_with("sqlite://") {
+        Ok(DbBackend::Sqlite)
+    } else {
+        Err(anyhow!(
+            "unsupported DATABASE_URL scheme (expected postgres://, postgresql://, or sqlite://)"
+        ))
+    }
 }
 
 fn env_or(key: &str, default: &str) -> String {
@@ -88,6 +467,80 @@ where
         .map_err(|err| anyhow!("invalid {}: {}", key, err))
 }
 
+fn parse_trusted_proxies(raw: &str) -> Result<Vec<(IpAddr, u8)>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (ip_str, prefix_str) = entry
+                .split_once('/')
+                .ok_or_else(|| anyhow!("invalid CIDR (expected ip/prefix): {}", entry))?;
+            let ip: IpAddr = ip_str
+                .parse()
+                .map_err(|_| anyhow!("invalid CIDR address: {}", entry))?;
+            let prefix: u8 = prefix_str
+                .parse()
+                .map_err(|_| anyhow!("invalid CIDR prefix: {}", entry))?;
+            Ok((ip, prefix))
+        })
+        .collect()
+}
+
+/// Parse `GOSSIP_PEERS` (a comma-separated list of `host:port` addresses)
+/// into the socket addresses `GossipClient` broadcasts invalidations to.
+fn parse_gossip_peers(raw: &str) -> Result<Vec<SocketAddr>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse()
+                .map_err(|_| anyhow!("invalid GOSSIP_PEERS address: {}", entry))
+        })
+        .collect()
+}
+
+/// Parse `OAUTH_PROVIDERS` (a comma-separated list of provider keys, e.g.
+/// `google,github`) and load each one's client config from
+/// `OAUTH_{KEY}_CLIENT_ID` / `_CLIENT_SECRET` / `_AUTHORIZE_URL` / `_TOKEN_URL`
+/// / `_REDIRECT_URI`, upper-casing the key for the env var names.
+fn parse_oauth_providers(raw: &str) -> Result<HashMap<String, OAuthProviderConfig>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|name| {
+            let prefix = format!("OAUTH_{}", name.to_uppercase());
+            let config = OAuthProviderConfig {
+                client_id: env_or_err(&format!("{}_CLIENT_ID", prefix))?,
+                client_secret: env_or_err(&format!("{}_CLIENT_SECRET", prefix))?,
+                authorize_url: env_or_err(&format!("{}_AUTHORIZE_URL", prefix))?,
+                token_url: env_or_err(&format!("{}_TOKEN_URL", prefix))?,
+                redirect_uri: env_or_err(&format!("{}_REDIRECT_URI", prefix))?,
+            };
+            Ok((name.to_string(), config))
+        })
+        .collect()
+}
+
+/// Loads `LdapConfig` from `LDAP_*` env vars, or returns `None` if `LDAP_URL`
+/// is unset -- the rest of the `LDAP_*` vars are only required once a
+/// deployment opts in by setting it.
+fn parse_ldap_config() -> Result<Option<LdapConfig>> {
+    let url = match std::env::var("LDAP_URL") {
+        Ok(url) => url,
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(LdapConfig {
+        url,
+        bind_dn_template: env_or_err("LDAP_BIND_DN_TEMPLATE")?,
+        search_base: env_or_err("LDAP_SEARCH_BASE")?,
+        username_attr: env_or("LDAP_USERNAME_ATTR", "uid"),
+        handle_attr: env_or("LDAP_HANDLE_ATTR", "uid"),
+        email_attr: env_or("LDAP_EMAIL_ATTR", "mail"),
+        display_name_attr: env_or("LDAP_DISPLAY_NAME_ATTR", "cn"),
+    }))
+}
+
 fn env_key_32(key: &str) -> Result<[u8; 32]> {
     let value = env_or_err(key)?;
     let decoded = STANDARD
@@ -101,3 +554,42 @@ fn env_key_32(key: &str) -> Result<[u8; 32]> {
     Ok(key_bytes)
 }
 
+/// Loads a `KeyRing`-shaped key list from `multi_key`, a comma-separated
+/// list of `kid:base64key` entries ordered newest (current) first -- e.g.
+/// `PASETO_ACCESS_KEYS=v2:AAAA...,v1:BBBB...` to rotate `v1` out as the
+/// signing key while it still verifies tokens minted under it. Falls back
+/// to `single_key_env` (the pre-rotation single-key format) as a ring of
+/// one key named "v1" when `multi_key` isn't set, so existing deployments
+/// don't need to change anything until they actually want to rotate.
+fn env_keyring(multi_key: &str, single_key_env: &str) -> Result<Vec<(String, [u8; 32])>> {
+    let Ok(value) = std::env::var(multi_key) else {
+        return Ok(vec![("v1".to_string(), env_key_32(single_key_env)?)]);
+    };
+
+    let mut keys = Vec::new();
+    for entry in value.split(',') {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let (kid, encoded) = entry
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid {}: expected \"kid:base64key\" entries", multi_key))?;
+        let decoded = STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(|err| anyhow!("invalid {}: {}", multi_key, err))?;
+        if decoded.len() != 32 {
+            return Err(anyhow!("invalid {}: expected 32 bytes per key", multi_key));
+        }
+        let mut key_bytes = [0u8; 32];
+        key_bytes.copy_from_slice(&decoded);
+        keys.push((kid.to_string(), key_bytes));
+    }
+
+    if keys.is_empty() {
+        return Err(anyhow!("{} is set but contains no keys", multi_key));
+    }
+
+    Ok(keys)
+}
+
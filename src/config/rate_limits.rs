@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Trust levels for users, determining their rate limits and privileges
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -26,91 +27,167 @@ impl TrustLevel {
     }
 }
 
-/// Rate limits for different user actions based on trust level
-#[derive(Debug, Clone, Copy)]
-pub struct RateLimits {
-    // Posts
-    pub posts_per_hour: u32,
-    pub posts_per_day: u32,
+/// A distinct, independently-tracked rate-limit bucket. Each variant gets
+/// its own Redis/local-counter key and its own `Limit` per `TrustLevel` --
+/// no two `LimitType`s ever share a counter, even if they happen to use the
+/// same window and max-count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitType {
+    /// Coarse per-user ceiling across every rate-limited action, independent
+    /// of which specific action tripped it. Not yet wired to a middleware
+    /// path -- defined here so a future catch-all check has a bucket to use.
+    Global,
+    /// Per-IP-address bucket for unauthenticated endpoints.
+    Ip,
+    /// Login attempts.
+    Auth,
+    PostCreate,
+    MediaUpload,
+    Signup,
+    Follow,
+    Unfollow,
+    Like,
+    Comment,
+    Feed,
+    Notifications,
+    Search,
+    Moderation,
+    /// Password-reset issuance, per account (not per IP -- see
+    /// `ip_rate_limit_middleware` for the unauthenticated `/auth/login`-style
+    /// IP bucket, which still applies to this endpoint).
+    PasswordReset,
+}
 
-    // Social actions
-    pub follows_per_hour: u32,
-    pub follows_per_day: u32,
-    pub unfollows_per_day: u32,
+impl LimitType {
+    /// Stable key fragment used when building Redis/local-counter keys, e.g.
+    /// `ratelimit:<user_id>:post_create:<window>`.
+    pub fn as_key(&self) -> &'static str {
+        match self {
+            LimitType::Global => "global",
+            LimitType::Ip => "ip",
+            LimitType::Auth => "auth",
+            LimitType::PostCreate => "post_create",
+            LimitType::MediaUpload => "media_upload",
+            LimitType::Signup => "signup",
+            LimitType::Follow => "follow",
+            LimitType::Unfollow => "unfollow",
+            LimitType::Like => "like",
+            LimitType::Comment => "comment",
+            LimitType::Feed => "feed",
+            LimitType::Notifications => "notifications",
+            LimitType::Search => "search",
+            LimitType::Moderation => "moderation",
+            LimitType::PasswordReset => "password_reset",
+        }
+    }
+}
 
-    // Engagement
-    pub likes_per_hour: u32,
-    pub comments_per_hour: u32,
+/// A single window + max-count pair backing one `LimitType`.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub window: RateWindow,
+    pub max: u32,
+}
 
-    // Authentication
-    pub login_attempts_per_hour: u32,
+/// Rate limits for different user actions based on trust level. Actions that
+/// check more than one window (e.g. `PostCreate` checks both hourly and
+/// daily) get one `Limit` entry per `(LimitType, RateWindow)` pair.
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    buckets: HashMap<(LimitType, RateWindow), Limit>,
+    // Concurrency (simultaneous in-flight requests for costly actions) isn't
+    // a windowed count, so it lives outside `buckets` alongside it.
+    pub concurrent_request_slots: u32,
 }
 
 impl RateLimits {
     /// Get rate limits for a specific trust level
     pub fn for_trust_level(level: TrustLevel) -> Self {
-        match level {
-            TrustLevel::New => RateLimits {
-                posts_per_hour: 1,
-                posts_per_day: 5,
-                follows_per_hour: 5,
-                follows_per_day: 20,
-                unfollows_per_day: 10,
-                likes_per_hour: 30,
-                comments_per_hour: 10,
-                login_attempts_per_hour: 5,
-            },
-            TrustLevel::Basic => RateLimits {
-                posts_per_hour: 5,
-                posts_per_day: 20,
-                follows_per_hour: 20,
-                follows_per_day: 100,
-                unfollows_per_day: 50,
-                likes_per_hour: 100,
-                comments_per_hour: 30,
-                login_attempts_per_hour: 10,
-            },
-            TrustLevel::Trusted => RateLimits {
-                posts_per_hour: 20,
-                posts_per_day: 100,
-                follows_per_hour: 100,
-                follows_per_day: 500,
-                unfollows_per_day: 200,
-                likes_per_hour: 500,
-                comments_per_hour: 100,
-                login_attempts_per_hour: 20,
-            },
-            TrustLevel::Verified => RateLimits {
-                posts_per_hour: 50,
-                posts_per_day: 200,
-                follows_per_hour: 200,
-                follows_per_day: 1000,
-                unfollows_per_day: 500,
-                likes_per_hour: 1000,
-                comments_per_hour: 200,
-                login_attempts_per_hour: 30,
-            },
+        let (posts_per_hour, posts_per_day, media_per_hour, signups_per_day, concurrent_request_slots) =
+            match level {
+                TrustLevel::New => (1, 5, 3, 1, 2),
+                TrustLevel::Basic => (5, 20, 10, 3, 4),
+                TrustLevel::Trusted => (20, 100, 40, 5, 8),
+                TrustLevel::Verified => (50, 200, 100, 10, 16),
+            };
+        let (follows_per_hour, follows_per_day, unfollows_per_day) = match level {
+            TrustLevel::New => (5, 20, 10),
+            TrustLevel::Basic => (20, 100, 50),
+            TrustLevel::Trusted => (100, 500, 200),
+            TrustLevel::Verified => (200, 1000, 500),
+        };
+        let (likes_per_hour, comments_per_hour, login_attempts_per_hour) = match level {
+            TrustLevel::New => (30, 10, 5),
+            TrustLevel::Basic => (100, 30, 10),
+            TrustLevel::Trusted => (500, 100, 20),
+            TrustLevel::Verified => (1000, 200, 30),
+        };
+        // Deliberately low and nearly flat across trust tiers -- a forgotten
+        // password isn't something a long-tenured account needs to do more
+        // often than a brand-new one, unlike posting/following volume.
+        let password_resets_per_hour = match level {
+            TrustLevel::New => 3,
+            TrustLevel::Basic => 3,
+            TrustLevel::Trusted => 5,
+            TrustLevel::Verified => 5,
+        };
+        let (feed_per_hour, notifications_per_hour, search_per_hour, moderation_per_hour) = match level
+        {
+            TrustLevel::New => (60, 120, 30, 10),
+            TrustLevel::Basic => (180, 300, 90, 30),
+            TrustLevel::Trusted => (600, 900, 300, 100),
+            TrustLevel::Verified => (1200, 1800, 600, 200),
+        };
+
+        let mut buckets = HashMap::new();
+        let mut bucket = |t: LimitType, window: RateWindow, max: u32| {
+            buckets.insert((t, window), Limit { window, max });
+        };
+
+        bucket(LimitType::Global, RateWindow::Hour, posts_per_hour.max(feed_per_hour) * 10);
+        bucket(LimitType::PostCreate, RateWindow::Hour, posts_per_hour);
+        bucket(LimitType::PostCreate, RateWindow::Day, posts_per_day);
+        bucket(LimitType::MediaUpload, RateWindow::Hour, media_per_hour);
+        bucket(LimitType::Signup, RateWindow::Day, signups_per_day);
+        bucket(LimitType::Follow, RateWindow::Hour, follows_per_hour);
+        bucket(LimitType::Follow, RateWindow::Day, follows_per_day);
+        bucket(LimitType::Unfollow, RateWindow::Day, unfollows_per_day);
+        bucket(LimitType::Like, RateWindow::Hour, likes_per_hour);
+        bucket(LimitType::Comment, RateWindow::Hour, comments_per_hour);
+        bucket(LimitType::Auth, RateWindow::Hour, login_attempts_per_hour);
+        bucket(LimitType::Feed, RateWindow::Hour, feed_per_hour);
+        bucket(LimitType::Notifications, RateWindow::Hour, notifications_per_hour);
+        bucket(LimitType::Search, RateWindow::Hour, search_per_hour);
+        bucket(LimitType::Moderation, RateWindow::Hour, moderation_per_hour);
+        bucket(LimitType::PasswordReset, RateWindow::Hour, password_resets_per_hour);
+
+        RateLimits {
+            buckets,
+            concurrent_request_slots,
         }
     }
 
-    /// Get the limit for a specific action type
-    pub fn limit_for_action(&self, action: &str, window: RateWindow) -> Option<u32> {
-        match (action, window) {
-            ("post", RateWindow::Hour) => Some(self.posts_per_hour),
-            ("post", RateWindow::Day) => Some(self.posts_per_day),
-            ("follow", RateWindow::Hour) => Some(self.follows_per_hour),
-            ("follow", RateWindow::Day) => Some(self.follows_per_day),
-            ("unfollow", RateWindow::Day) => Some(self.unfollows_per_day),
-            ("like", RateWindow::Hour) => Some(self.likes_per_hour),
-            ("comment", RateWindow::Hour) => Some(self.comments_per_hour),
-            ("login", RateWindow::Hour) => Some(self.login_attempts_per_hour),
-            _ => None,
-        }
+    /// The windows this limit type is checked against, in ascending order
+    /// (e.g. `PostCreate` -> `[Hour, Day]`). Most types check exactly one.
+    pub fn windows_for(&self, limit_type: LimitType) -> Vec<RateWindow> {
+        let mut windows: Vec<RateWindow> = self
+            .buckets
+            .keys()
+            .filter(|(t, _)| *t == limit_type)
+            .map(|(_, w)| *w)
+            .collect();
+        windows.sort_by_key(RateWindow::seconds);
+        windows
+    }
+
+    /// The bucket for `(limit_type, window)`, if one is configured.
+    pub fn limit(&self, limit_type: LimitType, window: RateWindow) -> Option<Limit> {
+        self.buckets.get(&(limit_type, window)).copied()
     }
 }
 
 /// Time window for rate limiting
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RateWindow {
     Hour,
     Day,
@@ -127,10 +204,27 @@ impl RateWindow {
 
 /// Calculate current window timestamp for rate limiting
 pub fn current_window(window_seconds: u64) -> u64 {
+    now_secs() / window_seconds
+}
+
+/// Seconds remaining until the current window rolls over, for clients
+/// without a live Redis TTL to read (e.g. a purely local fast-path decision).
+pub fn seconds_until_window_reset(window_seconds: u64) -> u64 {
+    window_seconds - (now_secs() % window_seconds)
+}
+
+/// How far into the current window `now` is, as a fraction in `[0, 1)`.
+/// The sliding-window-counter rate limiter uses this to weight down the
+/// previous window's count as the current window fills in, so a counter
+/// reset at the window boundary can't be used to burst past the limit.
+pub fn elapsed_fraction(window_seconds: u64) -> f64 {
+    (now_secs() % window_seconds) as f64 / window_seconds as f64
+}
+
+fn now_secs() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs();
-    now / window_seconds
+        .as_secs()
 }
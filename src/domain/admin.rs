@@ -0,0 +1,19 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// One row of the admin user search results -- a superset of `User`/`PublicUser`
+/// exposing the operator-facing signals (trust, verification, device risk)
+/// those client-facing views deliberately omit.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminUserSummary {
+    pub id: Uuid,
+    pub handle: String,
+    pub email: String,
+    pub display_name: String,
+    pub trust_level: i32,
+    pub is_verified: bool,
+    pub device_risk_score: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
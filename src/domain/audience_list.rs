@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A user-curated list of accounts that can be targeted as a story's
+/// audience via `StoryVisibility::List`, instead of the blanket
+/// public/friends_only/close_friends_only tiers. See `StoryService`'s
+/// `create_audience_list`/`add_list_member`/`remove_list_member`/
+/// `delete_audience_list`/`get_user_audience_lists`.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AudienceList {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// One membership rule within an `AudienceList`. `User` names an explicit
+/// account; `Prefix`/`Word` auto-include any account whose handle matches
+/// `pattern` (a literal prefix, or a whole-handle match), so a list can stay
+/// current as new accounts adopt a naming convention without the owner
+/// re-curating it by hand.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AudienceListMember {
+    pub id: Uuid,
+    pub list_id: Uuid,
+    pub kind: AudienceListMemberKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Stored as a plain text column (`as_db`/`from_db`), like `PostVisibility`,
+/// rather than a Postgres enum type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AudienceListMemberKind {
+    User,
+    Prefix,
+    Word,
+}
+
+impl AudienceListMemberKind {
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(Self::User),
+            "prefix" => Some(Self::Prefix),
+            "word" => Some(Self::Word),
+            _ => None,
+        }
+    }
+
+    pub fn as_db(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Prefix => "prefix",
+            Self::Word => "word",
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A bot/application account: a long-lived bearer credential scoped to one
+/// owning user, for integrations that need to call the API without an
+/// interactive login flow. See `app::bots::BotService`.
+///
+/// Deliberately has no `token`/`token_hash` field -- like
+/// `app::oauth_server::OAuthClient`'s `client_secret`, the bearer token is
+/// only ever handed back once, from `BotService::create_bot`/`rotate_token`,
+/// and persisted as `hash_token(token)`.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Bot {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    /// Whether this bot is listed to other users, or only to its own owner.
+    pub public: bool,
+    /// Webhook endpoint POSTed a signed event when the owning account is
+    /// mentioned or followed -- see
+    /// `app::bots::BotService::bots_with_webhook`.
+    pub interactions_url: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
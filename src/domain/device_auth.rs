@@ -0,0 +1,28 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A pending "approve this login from a new device" request, driven by an
+/// already-trusted device approving or denying on behalf of the account.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceAuthRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub fingerprint_hash: String,
+    pub request_ip: String,
+    #[serde(skip_serializing)]
+    pub access_code: String,
+    pub approved: Option<bool>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub response_date: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+impl DeviceAuthRequest {
+    pub fn is_pending(&self) -> bool {
+        self.approved.is_none() && self.expires_at > OffsetDateTime::now_utc()
+    }
+}
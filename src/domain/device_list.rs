@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The signed payload: an ordered device list where `devices[0]` is the
+/// primary device. This exact JSON encoding is what gets ed25519-signed, so
+/// its field order and shape must stay stable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawDeviceList {
+    pub devices: Vec<String>,
+    pub timestamp: i64,
+}
+
+/// A device-list update as submitted by a client. `cur_primary_signature`
+/// and `last_primary_signature` are base64-encoded ed25519 signatures over
+/// the JSON encoding of `raw`. Identity-generated updates (e.g. an admin
+/// forcibly removing a device) carry neither signature.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignedDeviceList {
+    pub raw: RawDeviceList,
+    pub cur_primary_signature: Option<String>,
+    pub last_primary_signature: Option<String>,
+}
+
+/// The device list as stored for a user.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredDeviceList {
+    pub user_id: Uuid,
+    pub devices: Vec<String>,
+    pub timestamp: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl StoredDeviceList {
+    pub fn primary_device(&self) -> Option<&str> {
+        self.devices.first().map(String::as_str)
+    }
+
+    pub fn contains(&self, device_id: &str) -> bool {
+        self.devices.iter().any(|d| d == device_id)
+    }
+}
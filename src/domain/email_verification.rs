@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A single-use, expiring token sent to a user's email address so they can
+/// prove they own it.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailVerification {
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub consumed_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmEmailVerificationRequest {
+    pub token: String,
+}
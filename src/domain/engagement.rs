@@ -2,20 +2,33 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Like {
     pub id: Uuid,
     pub user_id: Uuid,
     pub post_id: Uuid,
+    /// The reaction kind, e.g. `"like"`, `"love"`, `"laugh"` -- see
+    /// `EngagementService::react_post`. The `/like` endpoints are a
+    /// compatibility shim that always reacts with `"like"`.
+    pub reaction_type: String,
     pub created_at: OffsetDateTime,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ReactionCount {
+    pub reaction_type: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Comment {
     pub id: Uuid,
     pub user_id: Uuid,
     pub post_id: Uuid,
     pub body: String,
+    /// The comment this one replies to, or `None` for a top-level comment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_comment_id: Option<Uuid>,
     pub created_at: OffsetDateTime,
 }
 
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::notification::Notification;
+use crate::domain::post::Post;
+use crate::domain::story::Story;
+
+/// A live update published to a timeline's Redis pub/sub channel, forwarded
+/// verbatim to SSE subscribers of `GET /v1/feed/stream`. Internally tagged
+/// (a `type` field beside the variant's own fields) so new kinds can be
+/// added later without breaking the wire format older subscribers already
+/// parse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    PostCreated { post: Post },
+    StoryCreated { story: Story },
+    StoryDeleted { story_id: Uuid },
+    /// Published to `app::streaming::notification_channel(recipient_id)`
+    /// right after `NotificationService` inserts the row, so an inbox/bell
+    /// UI subscribed to its own channel (see `GET /v1/notifications/stream`)
+    /// updates without polling `GET /v1/notifications`. Carries the full row
+    /// (including `id`) so a subscriber can backfill from it on reconnect.
+    NotificationCreated { notification: Notification },
+}
@@ -0,0 +1,31 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The external identity providers a Lumine account can be linked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalIdentityKind {
+    Farcaster,
+    Ethereum,
+}
+
+impl ExternalIdentityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExternalIdentityKind::Farcaster => "farcaster",
+            ExternalIdentityKind::Ethereum => "ethereum",
+        }
+    }
+}
+
+/// A verified external identity bound to exactly one account.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalIdentityLink {
+    pub user_id: Uuid,
+    pub kind: ExternalIdentityKind,
+    /// The Farcaster FID (as a string) or EIP-55 checksummed address.
+    pub external_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub linked_at: OffsetDateTime,
+}
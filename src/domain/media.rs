@@ -2,16 +2,84 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Whether a `media` row is a still image or a video (including animated
+/// GIFs, which are normalized to video the same way an mp4/webm upload is).
+/// Stored as a plain text column (`as_db`/`from_db`), like `PostVisibility`,
+/// rather than a Postgres enum type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Image,
+    Video,
+}
+
+impl MediaKind {
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "image" => Some(Self::Image),
+            "video" => Some(Self::Video),
+            _ => None,
+        }
+    }
+
+    pub fn as_db(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Media {
     pub id: Uuid,
     pub owner_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<Uuid>,
+    pub kind: MediaKind,
+    /// For `kind == Video`, the normalized web-friendly transcode; for
+    /// `kind == Image`, the original upload re-encoded to strip metadata.
+    /// Either way, `original_url` below is what a client plays/opens.
     pub original_key: String,
+    /// Poster frame for video (the first keyframe, extracted via ffmpeg) or
+    /// the resized image itself.
     pub thumb_key: String,
     pub medium_key: String,
-    pub width: i32,
-    pub height: i32,
-    pub bytes: i64,
+    pub original_width: i32,
+    pub original_height: i32,
+    pub original_bytes: i64,
+    pub thumb_width: i32,
+    pub thumb_height: i32,
+    pub thumb_bytes: i64,
+    pub medium_width: i32,
+    pub medium_height: i32,
+    pub medium_bytes: i64,
+    /// Duration in milliseconds, probed via ffprobe. `None` for images.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
     pub created_at: OffsetDateTime,
+    /// BlurHash of the thumbnail/poster frame, so clients can render a
+    /// blurred color placeholder instantly instead of waiting on the
+    /// presigned thumbnail URL to load. `None` for media processed before
+    /// this field existed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumb_blurhash: Option<String>,
+    /// The playable video URL when `kind == Video`; the full-size image
+    /// otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumb_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medium_url: Option<String>,
 }
 
+/// Storage keys freed up by a delete that, after checking inside the same
+/// transaction, are no longer referenced by any `media`/`media_uploads` row.
+/// Callers hand these off to the deletion sweeper rather than deleting the
+/// S3 objects inline, so blob cleanup stays eventually consistent with the
+/// (authoritative) database state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeletionQueue {
+    pub objects: Vec<String>,
+}
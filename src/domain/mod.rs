@@ -0,0 +1,21 @@
+pub mod admin;
+pub mod audience_list;
+pub mod bot;
+pub mod device_auth;
+pub mod device_list;
+pub mod email_verification;
+pub mod engagement;
+pub mod event;
+pub mod external_identity;
+pub mod media;
+pub mod moderation;
+pub mod notification;
+pub mod oauth;
+pub mod opaque;
+pub mod post;
+pub mod push;
+pub mod session;
+pub mod social_graph;
+pub mod user;
+pub mod wallet;
+pub mod webauthn;
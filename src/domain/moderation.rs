@@ -22,3 +22,91 @@ pub struct UserFlag {
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
 }
+
+/// Moderation load snapshot for operators: how many flags have come in, how
+/// many actions moderators have taken in response, and how many distinct
+/// targets are still sitting in the review queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationStats {
+    pub flags_received: i64,
+    pub actions_taken: i64,
+    pub pending_queue_depth: i64,
+}
+
+/// Unresolved flags against a single target, aggregated for the moderation
+/// review queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingFlagGroup {
+    pub target_id: Uuid,
+    pub report_count: i64,
+    pub latest_reason: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub first_reported_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_reported_at: OffsetDateTime,
+}
+
+/// A user's position in the moderator hierarchy. Ordinary users can't
+/// sanction anyone; moderators can flag/takedown/suspend/ban but can't
+/// change anyone's role; only admins can promote or demote moderators.
+///
+/// This, `ModerationService::suspend_user`/`unsuspend_user`, and
+/// `app::blocklist::BlocklistService` (a signup-time email blocklist, like
+/// Plume's `BlocklistedEmail`) already cover the staff-role and
+/// global-suspension halves of the moderation surface -- only the manual
+/// trust-level promotion endpoint (`http::handlers::set_user_trust_level`)
+/// needed adding on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserRole::User => "user",
+            UserRole::Moderator => "moderator",
+            UserRole::Admin => "admin",
+        }
+    }
+
+    pub fn from_str(role: &str) -> Self {
+        match role {
+            "admin" => UserRole::Admin,
+            "moderator" => UserRole::Moderator,
+            _ => UserRole::User,
+        }
+    }
+}
+
+/// A single ban record. `scope: None` means a global ban (the account is
+/// fully sanctioned); `scope: Some("posting")` etc. sanctions just that
+/// capability. `expires_at: None` means indefinite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserBan {
+    pub id: Uuid,
+    pub target_id: Uuid,
+    pub banned_by: Uuid,
+    pub reason: Option<String>,
+    pub scope: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// `effective_status`'s answer: whichever active, non-expired ban is most
+/// restrictive for `user_id` right now (global takes precedence over a
+/// scoped ban), or `banned: false` with every other field `None` if the
+/// user isn't currently banned at all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveBanStatus {
+    pub banned: bool,
+    pub scope: Option<String>,
+    pub reason: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
@@ -3,12 +3,35 @@ use serde_json::Value;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Notification {
     pub id: Uuid,
     pub user_id: Uuid,
     pub notification_type: String,
+    #[schema(value_type = Object)]
     pub payload: Value,
     pub read_at: Option<OffsetDateTime>,
     pub created_at: OffsetDateTime,
 }
+
+/// The notifications produced directly from post authorship --
+/// `NotificationService::notify_mention`/`notify_reply`/`notify_repost` take
+/// this instead of a bare string so callers can't typo a `notification_type`.
+/// Other notification producers (follow, like, story reactions) don't go
+/// through a post and so aren't represented here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Mention,
+    Reply,
+    Repost,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Mention => "mention",
+            NotificationKind::Reply => "reply",
+            NotificationKind::Repost => "repost",
+        }
+    }
+}
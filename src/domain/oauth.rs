@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A third-party identity (Google, GitHub, ...) linked to a local account.
+/// `provider` is the configured provider key (e.g. `"google"`); `provider_subject`
+/// is that provider's stable `sub` claim for the end user.
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthIdentity {
+    pub user_id: Uuid,
+    pub provider: String,
+    pub provider_subject: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub linked_at: OffsetDateTime,
+}
+
+/// Response to starting the authorization-code + PKCE flow: the client
+/// redirects the user-agent to `authorize_url` and must echo `state` back
+/// unchanged on the callback.
+#[derive(Debug, Serialize)]
+pub struct OAuthAuthorizeResponse {
+    pub authorize_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: String,
+    pub state: String,
+    /// Required the first time a given provider subject is seen, since new
+    /// account provisioning is invite-gated the same as password signup.
+    pub invite_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkOAuthRequest {
+    pub code: String,
+    pub state: String,
+}
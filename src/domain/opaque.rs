@@ -0,0 +1,54 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Wire message for the first registration round-trip: the client's blinded
+/// OPRF element, base64-encoded.
+#[derive(Debug, Deserialize)]
+pub struct OpaqueRegisterStartRequest {
+    pub identifier: String,
+    pub registration_request: String,
+}
+
+/// Server's OPRF evaluation plus the short-lived `handle` the client must
+/// echo back with its envelope upload.
+#[derive(Debug, Serialize)]
+pub struct OpaqueRegisterStartResponse {
+    pub handle: String,
+    pub registration_response: String,
+}
+
+/// Second registration round-trip: the client's encrypted envelope.
+#[derive(Debug, Deserialize)]
+pub struct OpaqueRegisterFinishRequest {
+    pub handle: String,
+    pub registration_upload: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpaqueLoginStartRequest {
+    pub identifier: String,
+    pub credential_request: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpaqueLoginStartResponse {
+    pub handle: String,
+    pub credential_response: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpaqueLoginFinishRequest {
+    pub handle: String,
+    pub credential_finalization: String,
+}
+
+pub(crate) fn decode(field: &str, value: &str) -> anyhow::Result<Vec<u8>> {
+    BASE64
+        .decode(value)
+        .map_err(|_| anyhow::anyhow!("{} is not valid base64", field))
+}
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    BASE64.encode(bytes)
+}
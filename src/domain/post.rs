@@ -2,34 +2,78 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::domain::media::Media;
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Post {
     pub id: Uuid,
     pub owner_id: Uuid,
     pub owner_handle: Option<String>,
     pub owner_display_name: Option<String>,
-    pub media_id: Uuid,
+    #[serde(default)]
+    pub attachments: Vec<Media>,
+    #[serde(default)]
+    pub mentions: Vec<PostMention>,
     pub caption: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     pub visibility: PostVisibility,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_reply_to_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repost_of_id: Option<Uuid>,
+    /// The original post this one reposts, embedded one level deep. Never
+    /// itself carries a `reposted_post` -- `PostService::create_post`
+    /// already refuses to let a repost target another repost, so there's
+    /// nothing further to embed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reposted_post: Option<Box<Post>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub owner_avatar_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner_avatar_url: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A snapshot of a post's caption and visibility taken just before an edit
+/// or a delete, recorded by `PostService::update_caption`/`delete_post` so
+/// moderators have an audit trail of content that's since changed or is
+/// gone. Mirrors `domain::user::ProfileHistoryEntry`'s role for profiles.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PostHistoryEntry {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub owner_id: Uuid,
+    pub action: String,
+    pub caption: Option<String>,
+    pub visibility: PostVisibility,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// A resolved `@handle` mention in a post's caption, kept alongside the
+/// `handle` it was resolved from so clients can render a link without an
+/// extra user lookup.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PostMention {
+    pub user_id: Uuid,
+    pub handle: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub enum PostVisibility {
     Public,
-    FollowersOnly,
+    Followers,
+    Mentioned,
+    Private,
 }
 
 impl PostVisibility {
     pub fn from_db(value: &str) -> Option<Self> {
         match value {
             "public" => Some(Self::Public),
-            "followers_only" => Some(Self::FollowersOnly),
+            "followers" => Some(Self::Followers),
+            "mentioned" => Some(Self::Mentioned),
+            "private" => Some(Self::Private),
             _ => None,
         }
     }
@@ -37,7 +81,9 @@ impl PostVisibility {
     pub fn as_db(&self) -> &'static str {
         match self {
             Self::Public => "public",
-            Self::FollowersOnly => "followers_only",
+            Self::Followers => "followers",
+            Self::Mentioned => "mentioned",
+            Self::Private => "private",
         }
     }
 }
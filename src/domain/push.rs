@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+/// A browser's Web Push subscription, as handed to `PushManager.subscribe()`
+/// on the client and echoed back to us verbatim.
+#[derive(Debug, Deserialize)]
+pub struct SubscribePushRequest {
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnsubscribePushRequest {
+    pub endpoint: String,
+}
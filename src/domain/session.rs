@@ -0,0 +1,19 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// One active refresh-token "session" for a user, as surfaced by
+/// `GET /v1/auth/sessions` -- lets a user see and individually revoke the
+/// devices currently able to mint fresh access tokens.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_used_at: OffsetDateTime,
+    pub is_current: bool,
+}
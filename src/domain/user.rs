@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct User {
     pub id: Uuid,
     pub handle: String,
@@ -16,6 +16,24 @@ pub struct User {
     pub avatar_url: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    /// Mastodon-style "locked account": follows land `pending` instead of
+    /// `accepted` until approved, per `SocialService::follow`. Backed by
+    /// the `users.is_private` column.
+    pub is_locked: bool,
+}
+
+/// A single field change recorded by `UserService::update_profile`, kept
+/// around as a reviewable trail for moderators investigating abuse
+/// (impersonation, a bio edited away right before a report, etc.).
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileHistoryEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub changed_at: OffsetDateTime,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -31,6 +49,34 @@ pub struct PublicUser {
     pub followers_count: i64,
     pub following_count: i64,
     pub posts_count: i64,
+    /// This user's ActivityPub actor id (`{base}/users/{id}/actor`), for
+    /// clients that want to cross-reference or federate with them. `None`
+    /// unless a caller opts in with `with_ap_id` -- the domain layer has no
+    /// `federation_base_url` to build it from, so it's never set by the
+    /// `From` impls below.
+    pub ap_id: Option<String>,
+    /// See `User::is_locked`.
+    pub is_locked: bool,
+    /// Viewer-specific follow/block/mute state, batched in by
+    /// `app::social::SocialService::add_user_relationships` for a whole
+    /// page of results at once. `None` until a handler opts in -- plain
+    /// `From<User>`/`from_user_with_url` leave it unset, same as the
+    /// counts above.
+    pub relationship: Option<PublicUserRelationship>,
+}
+
+/// Mirrors `http::handlers::RelationshipResponse`, but reusable as an
+/// inline field on `PublicUser` so list endpoints can batch it in per item
+/// instead of clients hitting `/v1/users/{id}/relationship` once per row.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicUserRelationship {
+    pub is_following: bool,
+    pub is_followed_by: bool,
+    pub is_blocking: bool,
+    pub is_blocked_by: bool,
+    pub is_pending: bool,
+    pub is_muting: bool,
+    pub is_muted_by: bool,
 }
 
 impl PublicUser {
@@ -46,8 +92,17 @@ impl PublicUser {
             followers_count: 0,
             following_count: 0,
             posts_count: 0,
+            ap_id: None,
+            is_locked: user.is_locked,
+            relationship: None,
         }
     }
+
+    /// Attaches the caller-resolved ActivityPub actor id -- see `ap_id`.
+    pub fn with_ap_id(mut self, ap_id: Option<String>) -> Self {
+        self.ap_id = ap_id;
+        self
+    }
 }
 
 impl From<User> for PublicUser {
@@ -62,6 +117,9 @@ impl From<User> for PublicUser {
             followers_count: 0,
             following_count: 0,
             posts_count: 0,
+            ap_id: None,
+            is_locked: user.is_locked,
+            relationship: None,
         }
     }
 }
@@ -78,6 +136,9 @@ impl From<&User> for PublicUser {
             followers_count: 0,
             following_count: 0,
             posts_count: 0,
+            ap_id: None,
+            is_locked: user.is_locked,
+            relationship: None,
         }
     }
 }
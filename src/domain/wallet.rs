@@ -0,0 +1,14 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// An Ethereum wallet linked to an account after a successful Sign-In with
+/// Ethereum (EIP-4361) verification.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletLink {
+    pub user_id: Uuid,
+    /// EIP-55 checksummed address, e.g. `0x5aAe...`.
+    pub address: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub linked_at: OffsetDateTime,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Server challenge plus the short-lived `handle` the client must echo back
+/// with the completed ceremony.
+#[derive(Debug, Serialize)]
+pub struct WebauthnChallengeResponse {
+    pub handle: String,
+    pub challenge: String,
+}
+
+/// Finish a passkey registration: the new credential's id, its P-256 public
+/// key (SEC1 uncompressed point, base64), and the signed client data.
+#[derive(Debug, Deserialize)]
+pub struct RegisterPasskeyRequest {
+    pub handle: String,
+    pub credential_id: String,
+    pub public_key: String,
+    pub client_data_json: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthenticatePasskeyStartRequest {
+    pub identifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthenticatePasskeyFinishRequest {
+    pub handle: String,
+    pub credential_id: String,
+    pub authenticator_data: String,
+    pub client_data_json: String,
+    pub signature: String,
+}
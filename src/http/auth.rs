@@ -1,22 +1,189 @@
-use axum::extract::FromRequestParts;
+use std::marker::PhantomData;
+
+use axum::extract::{FromRequest, FromRequestParts, Request};
 use axum::http::header;
 use axum::http::HeaderName;
 use axum::http::request::Parts;
 
-use crate::app::auth::AuthService;
+use crate::app::auth::{AuthService, Scope};
+use crate::app::bots::BotService;
+use crate::app::moderation::ModerationService;
+use crate::domain::moderation::UserRole;
 use crate::http::AppError;
 use crate::AppState;
 
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: uuid::Uuid,
+    pub scopes: Vec<Scope>,
+    pub session_id: uuid::Uuid,
+    /// Set when this request authenticated with a bot bearer token (see
+    /// `app::bots::BotService::authenticate`) rather than a PASETO access
+    /// token -- `user_id` is the bot's owner in that case, and
+    /// `http::middleware::rate_limit::rate_limit_middleware` keys off this
+    /// instead to avoid attributing a bot's traffic to its owner's quota.
+    pub bot_id: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AdminToken;
 
+/// Authenticates like `AuthUser`, then additionally rejects with 403 unless
+/// the caller's `users.role` (the DB role from the moderator hierarchy, not
+/// an access-token scope) is `Moderator` or `Admin`. Use this on moderation
+/// routes that should be delegable to community moderators, in place of
+/// `RequireScope<AdminModeration>` (a token grant, not tied to who the
+/// account actually is) or `AdminToken` (a single shared operator secret).
+#[derive(Debug, Clone)]
+pub struct ModeratorUser {
+    pub user_id: uuid::Uuid,
+    pub role: UserRole,
+}
+
+/// A compile-time tag for the scope a `RequireScope<S>` extractor checks.
+/// Kept as marker types (rather than a runtime `Scope` parameter) so each
+/// route declares its required scope in its handler signature, the same
+/// way `AuthUser`/`AdminToken` are declared today.
+pub trait ScopeMarker {
+    const SCOPE: Scope;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReadFeed;
+impl ScopeMarker for ReadFeed {
+    const SCOPE: Scope = Scope::ReadFeed;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WritePosts;
+impl ScopeMarker for WritePosts {
+    const SCOPE: Scope = Scope::WritePosts;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ManageAccount;
+impl ScopeMarker for ManageAccount {
+    const SCOPE: Scope = Scope::ManageAccount;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AdminModeration;
+impl ScopeMarker for AdminModeration {
+    const SCOPE: Scope = Scope::AdminModeration;
+}
+
+/// Authenticates the caller like `AuthUser`, then additionally rejects with
+/// 403 unless the access token's scope set includes `S::SCOPE`. Use this in
+/// place of `AuthUser` on routes that should only work for tokens granted a
+/// specific permission, e.g. `RequireScope<AdminModeration>` for moderation
+/// actions instead of a separate operator-facing admin-token check.
+#[derive(Debug, Clone)]
+pub struct RequireScope<S> {
+    pub user_id: uuid::Uuid,
+    _marker: PhantomData<S>,
+}
+
 const ADMIN_TOKEN_HEADER: HeaderName = HeaderName::from_static("x-admin-token");
 
+/// A remote ActivityPub activity whose HTTP Signature has already been
+/// verified against its signer's public key. Use this in place of manually
+/// reading the body and calling `SignatureService::verify_signed_request` --
+/// it needs `FromRequest` rather than `FromRequestParts` (like `AuthUser`),
+/// since verifying the signature requires the raw request body for the
+/// `Digest` check.
+#[derive(Debug, Clone)]
+pub struct VerifiedActivity {
+    pub actor_uri: String,
+    pub activity: serde_json::Value,
+}
+
+#[axum::async_trait]
+impl FromRequest<AppState> for VerifiedActivity {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let method = parts.method.as_str().to_string();
+        let path = parts.uri.path().to_string();
+        let header_map: std::collections::HashMap<String, String> = parts
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.as_str().to_ascii_lowercase(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect();
+
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .map_err(|_| AppError::bad_request("failed to read request body"))?;
+
+        let actor_uri = state
+            .signature
+            .verify_signed_request(&method, &path, &header_map, &body)
+            .await
+            .map_err(|err| {
+                tracing::warn!(error = ?err, "rejected inbound activity: signature verification failed");
+                AppError::unauthorized("invalid HTTP signature")
+            })?;
+
+        let activity: serde_json::Value = serde_json::from_slice(&body)
+            .map_err(|_| AppError::bad_request("activity body is not valid JSON"))?;
+
+        Ok(VerifiedActivity { actor_uri, activity })
+    }
+}
+
+/// A remote Lumine server's Follow/Block edge push whose ed25519 server
+/// signature has already been verified against its origin's published key
+/// (see `app::social_federation::ServerKeyClient`). Use this in place of
+/// `AuthUser` on the `/federation/v1/social-edges` inbox, the Lumine
+/// equivalent of `VerifiedActivity` for ActivityPub's `/inbox`.
+#[derive(Debug, Clone)]
+pub struct VerifiedSocialEdge {
+    pub event: crate::app::social_federation::SocialEdgeEvent,
+}
+
+#[axum::async_trait]
+impl FromRequest<AppState> for VerifiedSocialEdge {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let signature = parts
+            .headers
+            .get("x-lumine-server-signature")
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| AppError::unauthorized("missing X-Lumine-Server-Signature header"))?
+            .to_string();
+
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .map_err(|_| AppError::bad_request("failed to read request body"))?;
+
+        // The event's own `origin_server` field names which peer's key to
+        // verify against -- there's no separate claimed-origin header, so
+        // peek at it before verifying, the same way `ServerKeyClient::verify`
+        // re-checks it against the key that actually signed the payload.
+        let unverified: crate::app::social_federation::SocialEdgeEvent =
+            serde_json::from_slice(&body)
+                .map_err(|_| AppError::bad_request("edge event body is malformed"))?;
+
+        let event = state
+            .social_federation_keys
+            .verify(&unverified.origin_server, &body, &signature)
+            .await
+            .map_err(|err| {
+                tracing::warn!(error = ?err, origin_server = %unverified.origin_server, "rejected inbound social edge: signature verification failed");
+                AppError::unauthorized("invalid server signature")
+            })?;
+
+        Ok(VerifiedSocialEdge { event })
+    }
+}
+
 #[axum::async_trait]
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = AppError;
@@ -39,19 +206,95 @@ impl FromRequestParts<AppState> for AuthUser {
 
         let service = AuthService::new(
             app_state.db.clone(),
-            app_state.paseto_access_key,
-            app_state.paseto_refresh_key,
+            app_state.cache.clone(),
+            app_state.paseto_access_keys.clone(),
+            app_state.paseto_refresh_keys.clone(),
             app_state.access_ttl_minutes,
             app_state.refresh_ttl_days,
+            app_state.account_recovery_grace_days,
+            app_state.ldap.clone(),
         );
         let session = service
             .authenticate_access_token(token)
             .await
             .map_err(|_| AppError::internal("failed to authenticate"))?;
 
-        let session = session.ok_or_else(|| AppError::unauthorized("invalid token"))?;
+        if let Some(session) = session {
+            return Ok(AuthUser {
+                user_id: session.user_id,
+                scopes: session.scopes,
+                session_id: session.session_id,
+                bot_id: None,
+            });
+        }
+
+        // Not a PASETO access token -- try it as a bot's non-expiring
+        // bearer token before giving up. `BotService::authenticate` returns
+        // `Ok(None)` just as cleanly for a token that's neither, so this
+        // still reports a single "invalid token" 401 on a genuine miss.
+        let bot = BotService::new(app_state.db.clone())
+            .authenticate(token)
+            .await
+            .map_err(|_| AppError::internal("failed to authenticate"))?
+            .ok_or_else(|| AppError::unauthorized("invalid token"))?;
+
         Ok(AuthUser {
-            user_id: session.user_id,
+            user_id: bot.owner_id,
+            scopes: Scope::all(),
+            session_id: bot.bot_id,
+            bot_id: Some(bot.bot_id),
+        })
+    }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<AppState> for RequireScope<S>
+where
+    S: ScopeMarker + Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = AuthUser::from_request_parts(parts, state).await?;
+        if !auth.scopes.contains(&S::SCOPE) {
+            return Err(AppError::forbidden(format!(
+                "token is missing required scope: {}",
+                S::SCOPE.as_str()
+            )));
+        }
+        Ok(RequireScope {
+            user_id: auth.user_id,
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for ModeratorUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = AuthUser::from_request_parts(parts, state).await?;
+
+        let service = ModerationService::new(state.db.clone());
+        let role = service
+            .role_of(auth.user_id)
+            .await
+            .map_err(|_| AppError::internal("failed to resolve role"))?;
+
+        if !matches!(role, UserRole::Moderator | UserRole::Admin) {
+            return Err(AppError::forbidden("requires moderator role or higher"));
+        }
+
+        Ok(ModeratorUser {
+            user_id: auth.user_id,
+            role,
         })
     }
 }
@@ -82,3 +325,28 @@ impl FromRequestParts<AppState> for AdminToken {
         Ok(AdminToken)
     }
 }
+
+/// A single `Uuid` path parameter that accepts either the raw id or its
+/// `app::shortid`-encoded short form -- drop-in for `Path<uuid::Uuid>` on
+/// routes that want to start handing out (and accepting) short public ids
+/// without breaking existing links built from the raw `Uuid`.
+#[derive(Debug, Clone, Copy)]
+pub struct ShortUuid(pub uuid::Uuid);
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for ShortUuid {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let axum::extract::Path(raw) = axum::extract::Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| AppError::bad_request("missing id"))?;
+
+        crate::app::shortid::decode_either(&raw, &state.shortid_salt)
+            .map(ShortUuid)
+            .ok_or_else(|| AppError::bad_request("invalid id"))
+    }
+}
@@ -3,16 +3,69 @@ use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Serialize;
 
+/// Stable, machine-readable error codes returned as `errcode` in the JSON
+/// body, mirroring the Matrix `errcode`/`error` convention -- clients branch
+/// on `errcode` instead of string-matching `error`. The "already taken"
+/// conflict variants (`HandleTaken` through `OauthIdentityTaken`) and
+/// `EmailNotVerified` use a snake_case wire value; everything else follows
+/// the `M_SCREAMING_CASE` convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    Forbidden,
+    NotFound,
+    LimitExceeded,
+    InviteRevoked,
+    InviteExpired,
+    InviteConsumed,
+    TrustQuota,
+    Banned,
+    HandleTaken,
+    EmailTaken,
+    InviteCodeCollision,
+    FidTaken,
+    AddressTaken,
+    OauthIdentityTaken,
+    EmailNotVerified,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Forbidden => "M_FORBIDDEN",
+            Self::NotFound => "M_NOT_FOUND",
+            Self::LimitExceeded => "M_LIMIT_EXCEEDED",
+            Self::InviteRevoked => "M_INVITE_REVOKED",
+            Self::InviteExpired => "M_INVITE_EXPIRED",
+            Self::InviteConsumed => "M_INVITE_CONSUMED",
+            Self::TrustQuota => "M_TRUST_QUOTA",
+            Self::Banned => "M_BANNED",
+            Self::HandleTaken => "handle_taken",
+            Self::EmailTaken => "email_taken",
+            Self::InviteCodeCollision => "invite_code_collision",
+            Self::FidTaken => "fid_taken",
+            Self::AddressTaken => "address_taken",
+            Self::OauthIdentityTaken => "oauth_identity_taken",
+            Self::EmailNotVerified => "email_not_verified",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AppError {
     status: StatusCode,
     message: String,
     headers: Vec<(String, String)>,
+    code: Option<ErrorCode>,
+    retry_after_ms: Option<u64>,
 }
 
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errcode: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_ms: Option<u64>,
 }
 
 impl AppError {
@@ -21,6 +74,8 @@ impl AppError {
             status: StatusCode::BAD_REQUEST,
             message: message.into(),
             headers: vec![],
+            code: None,
+            retry_after_ms: None,
         }
     }
 
@@ -29,6 +84,8 @@ impl AppError {
             status: StatusCode::NOT_FOUND,
             message: message.into(),
             headers: vec![],
+            code: Some(ErrorCode::NotFound),
+            retry_after_ms: None,
         }
     }
 
@@ -37,6 +94,8 @@ impl AppError {
             status: StatusCode::NOT_IMPLEMENTED,
             message: message.into(),
             headers: vec![],
+            code: None,
+            retry_after_ms: None,
         }
     }
 
@@ -45,6 +104,8 @@ impl AppError {
             status: StatusCode::UNAUTHORIZED,
             message: message.into(),
             headers: vec![],
+            code: None,
+            retry_after_ms: None,
         }
     }
 
@@ -53,6 +114,8 @@ impl AppError {
             status: StatusCode::CONFLICT,
             message: message.into(),
             headers: vec![],
+            code: None,
+            retry_after_ms: None,
         }
     }
 
@@ -61,26 +124,54 @@ impl AppError {
             status: StatusCode::INTERNAL_SERVER_ERROR,
             message: message.into(),
             headers: vec![],
+            code: None,
+            retry_after_ms: None,
         }
     }
 
+    /// 429 with no quota information -- prefer `rate_limited_with_headers`
+    /// when `RateLimitInfo` is available, since it also carries `retry_after_ms`.
     pub fn rate_limited(message: impl Into<String>) -> Self {
         Self {
             status: StatusCode::TOO_MANY_REQUESTS,
             message: message.into(),
             headers: vec![],
+            code: Some(ErrorCode::LimitExceeded),
+            retry_after_ms: None,
+        }
+    }
+
+    /// Distinct from `rate_limited`: this fires when a user has too many
+    /// requests simultaneously in flight, not too many over a time window.
+    pub fn concurrency_limited(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            message: message.into(),
+            headers: vec![],
+            code: Some(ErrorCode::LimitExceeded),
+            retry_after_ms: None,
         }
     }
 
-    pub fn rate_limited_with_headers(message: impl Into<String>, limit: u32, remaining: u32) -> Self {
+    /// 429 with the standard `RateLimit-*` + `Retry-After` headers, plus
+    /// `retry_after_ms` in the body for clients that don't read headers.
+    pub fn rate_limited_with_headers(
+        message: impl Into<String>,
+        limit: u32,
+        remaining: u32,
+        reset_seconds: u64,
+    ) -> Self {
         Self {
             status: StatusCode::TOO_MANY_REQUESTS,
             message: message.into(),
             headers: vec![
-                ("X-RateLimit-Limit".to_string(), limit.to_string()),
-                ("X-RateLimit-Remaining".to_string(), remaining.to_string()),
-                ("Retry-After".to_string(), "60".to_string()),
+                ("RateLimit-Limit".to_string(), limit.to_string()),
+                ("RateLimit-Remaining".to_string(), remaining.to_string()),
+                ("RateLimit-Reset".to_string(), reset_seconds.to_string()),
+                ("Retry-After".to_string(), reset_seconds.to_string()),
             ],
+            code: Some(ErrorCode::LimitExceeded),
+            retry_after_ms: Some(reset_seconds * 1000),
         }
     }
 
@@ -89,6 +180,137 @@ impl AppError {
             status: StatusCode::FORBIDDEN,
             message: message.into(),
             headers: vec![],
+            code: Some(ErrorCode::Forbidden),
+            retry_after_ms: None,
+        }
+    }
+
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::PAYLOAD_TOO_LARGE,
+            message: message.into(),
+            headers: vec![],
+            code: None,
+            retry_after_ms: None,
+        }
+    }
+
+    /// Distinct from `concurrency_limited` (429, per-user): this fires when
+    /// the process-wide `MediaConcurrencyLimiter` couldn't hand out a permit
+    /// within its acquire timeout, meaning the server itself is overloaded
+    /// rather than this one account making too many requests.
+    pub fn server_busy(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message: message.into(),
+            headers: vec![],
+            code: None,
+            retry_after_ms: None,
+        }
+    }
+
+    /// The account making the request has been banned -- distinct from a
+    /// generic `forbidden` so clients can stop retrying and show a ban
+    /// screen instead of treating it as a transient permission error.
+    pub fn banned(message: impl Into<String>) -> Self {
+        AppError::forbidden(message).with_code(ErrorCode::Banned)
+    }
+
+    /// `InviteService::consume_invite` rejected a code that's been revoked.
+    pub fn invite_revoked(message: impl Into<String>) -> Self {
+        AppError::bad_request(message).with_code(ErrorCode::InviteRevoked)
+    }
+
+    /// `InviteService::consume_invite` rejected a code past its `expires_at`.
+    pub fn invite_expired(message: impl Into<String>) -> Self {
+        AppError::bad_request(message).with_code(ErrorCode::InviteExpired)
+    }
+
+    /// `InviteService::consume_invite` rejected a code that already has
+    /// `use_count >= max_uses`.
+    pub fn invite_consumed(message: impl Into<String>) -> Self {
+        AppError::bad_request(message).with_code(ErrorCode::InviteConsumed)
+    }
+
+    /// `InviteService::create_invite` rejected a request over the caller's
+    /// trust-level invite quota.
+    pub fn trust_quota(message: impl Into<String>) -> Self {
+        AppError::forbidden(message).with_code(ErrorCode::TrustQuota)
+    }
+
+    /// Attach a stable machine-readable error code that clients can switch
+    /// on instead of parsing the prose `message`.
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Inspect an `anyhow::Error` for a wrapped Postgres unique-constraint
+    /// violation and translate it into the matching domain conflict, if the
+    /// constraint is one we recognize. Returns `None` for errors that aren't
+    /// unique violations (or whose constraint we don't special-case), so
+    /// callers can fall back to a generic `internal` error for anything
+    /// else. Centralizes the `sqlx::Error` downcast so individual handlers
+    /// don't each re-implement the same constraint-name matching.
+    pub fn from_db_conflict(err: &anyhow::Error) -> Option<AppError> {
+        let db_err = err.downcast_ref::<sqlx::Error>()?.as_database_error()?;
+        if !db_err.is_unique_violation() {
+            return None;
+        }
+        DbConflict::from_constraint(db_err.constraint().unwrap_or_default())
+            .map(|conflict| AppError::conflict(conflict.message()).with_code(conflict.code()))
+    }
+
+    /// Recognizes a `MediaConcurrencyLimiter::acquire` timeout surfaced as a
+    /// generic `anyhow::Error` from deep inside `MediaService`, and
+    /// translates it into 503 rather than a generic 500 -- the caller should
+    /// retry shortly, this isn't a bug.
+    pub fn from_media_busy(err: &anyhow::Error) -> Option<AppError> {
+        if err.to_string().contains("media concurrency limit exceeded") {
+            Some(AppError::server_busy("server busy"))
+        } else {
+            None
+        }
+    }
+}
+
+/// Unique-constraint violations we can translate into a specific, stable
+/// `error`/`code` pair instead of a generic 500. Matched by constraint name
+/// rather than table name, since Postgres names unique indexes after the
+/// column(s) they cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DbConflict {
+    HandleTaken,
+    EmailTaken,
+    InviteCodeCollision,
+}
+
+impl DbConflict {
+    fn from_constraint(constraint: &str) -> Option<Self> {
+        if constraint.contains("users_handle_key") {
+            Some(Self::HandleTaken)
+        } else if constraint.contains("users_email_key") {
+            Some(Self::EmailTaken)
+        } else if constraint.contains("invite_codes") {
+            Some(Self::InviteCodeCollision)
+        } else {
+            None
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Self::HandleTaken => "Handle already taken",
+            Self::EmailTaken => "Email already taken",
+            Self::InviteCodeCollision => "Invite code already exists, please retry",
+        }
+    }
+
+    fn code(self) -> ErrorCode {
+        match self {
+            Self::HandleTaken => ErrorCode::HandleTaken,
+            Self::EmailTaken => ErrorCode::EmailTaken,
+            Self::InviteCodeCollision => ErrorCode::InviteCodeCollision,
         }
     }
 }
@@ -97,6 +319,8 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let body = Json(ErrorResponse {
             error: self.message,
+            errcode: self.code.map(ErrorCode::as_str),
+            retry_after_ms: self.retry_after_ms,
         });
         let mut response = (self.status, body).into_response();
         for (name, value) in &self.headers {
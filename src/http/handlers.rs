@@ -1,24 +1,40 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{ConnectInfo, Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
+    response::{IntoResponse, Response},
     Json,
 };
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use futures::StreamExt;
+use hmac::Mac;
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::app::auth::AuthService;
+use crate::app::device_list::DeviceListService;
 use crate::app::engagement::EngagementService;
 use crate::app::feed::FeedService;
-use crate::app::media::{MediaService, UploadIntent, UploadStatus};
+use crate::app::media::{
+    DirectUploadResult, MediaService, ProcessSpec, UploadIntent, UploadStatus, VariantFormat,
+    VariantOp, DERIVATIVE_SIZES,
+};
 use crate::app::moderation::ModerationService;
 use crate::app::notifications::NotificationService;
 use crate::app::posts::PostService;
 use crate::app::search::SearchService;
 use crate::app::social::SocialService;
+use crate::app::streaming::StreamingService;
 use crate::app::users::UserService;
-use crate::http::{AdminToken, AppError, AuthUser};
+use crate::http::middleware::client_ip::{resolve_client_ip, subnet_bucket};
+use crate::http::{
+    AdminModeration, AdminToken, AppError, AuthUser, ModeratorUser, ReadFeed, RequireScope,
+    ShortUuid,
+};
 use crate::AppState;
 
 #[derive(Serialize)]
@@ -26,42 +42,225 @@ pub(crate) struct HealthResponse {
     status: &'static str,
 }
 
-#[derive(Deserialize)]
+/// Cursor pagination convention used across every list endpoint in this
+/// module: `cursor` is the opaque `next_cursor` from the previous page's
+/// `ListResponse` (omit it for the first page), and `limit` caps the page
+/// size (each handler documents its own allowed range; callers that pass
+/// more than the max get a 400, not a silently-capped page).
+#[derive(Deserialize, utoipa::IntoParams)]
 pub struct PaginationQuery {
     pub limit: Option<i64>,
     pub cursor: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct CommentListQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    /// When true, only root comments (no `parent_comment_id`) are
+    /// returned; a client lazy-loads each thread's replies separately via
+    /// `GET /v1/comments/{id}/replies`.
+    #[serde(default)]
+    pub top_level_only: bool,
+}
+
+#[derive(Deserialize)]
+pub struct StoriesFeedQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    /// Feed-version token the client last saw (see
+    /// `VersionedListResponse::version` below). When it matches the server's current version, the response
+    /// comes back with an empty `items` list instead of re-running the
+    /// feed query.
+    pub since_version: Option<i64>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+#[aliases(
+    PostListResponse = ListResponse<crate::domain::post::Post>,
+    CommentListResponse = ListResponse<crate::domain::engagement::Comment>,
+    NotificationListResponse = ListResponse<crate::domain::notification::Notification>,
+)]
 pub struct ListResponse<T> {
+    pub items: Vec<T>,
+    /// Pass back as `?cursor=` to fetch the next page; `None` means this was
+    /// the last one.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct VersionedListResponse<T> {
     pub items: Vec<T>,
     pub next_cursor: Option<String>,
+    /// Current `stories:feedver:{user_id}` value -- pass this back as
+    /// `since_version` on the next poll to turn a no-op request into a
+    /// single Redis read.
+    pub version: i64,
 }
 
-fn parse_cursor(cursor: Option<String>) -> Result<Option<(OffsetDateTime, Uuid)>, AppError> {
+type CursorHmac = hmac::Hmac<sha2::Sha256>;
+
+/// Wire format for every `(OffsetDateTime, Uuid)` pagination cursor in this
+/// module: unix-millis (i64, big-endian) + the 16 raw UUID bytes, HMAC-
+/// SHA256-tagged with `AppState::cursor_hmac_key` and base64url-encoded.
+/// Opaque and tamper-evident -- a client can't forge a cursor for a window
+/// it hasn't legitimately paged through, and doesn't get to see the raw
+/// timestamp/id it encodes.
+fn parse_cursor(
+    cursor: Option<String>,
+    hmac_key: &[u8; 32],
+) -> Result<Option<(OffsetDateTime, Uuid)>, AppError> {
     let Some(cursor) = cursor else {
         return Ok(None);
     };
 
-    let mut parts = cursor.splitn(2, '/');
-    let timestamp = parts
-        .next()
-        .ok_or_else(|| AppError::bad_request("invalid cursor"))?;
-    let id = parts
-        .next()
-        .ok_or_else(|| AppError::bad_request("invalid cursor"))?;
+    let sealed = URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| AppError::bad_request("invalid cursor"))?;
 
-    let timestamp = OffsetDateTime::parse(timestamp, &Rfc3339)
+    if sealed.len() != 24 + 32 {
+        return Err(AppError::bad_request("invalid cursor"));
+    }
+    let (payload, tag) = sealed.split_at(24);
+
+    let mut mac =
+        CursorHmac::new_from_slice(hmac_key).expect("HMAC-SHA256 accepts any key length");
+    mac.update(payload);
+    mac.verify_slice(tag)
+        .map_err(|_| AppError::bad_request("invalid cursor"))?;
+
+    let millis = i64::from_be_bytes(payload[0..8].try_into().expect("checked length"));
+    let id = Uuid::from_slice(&payload[8..24]).map_err(|_| AppError::bad_request("invalid cursor"))?;
+    let timestamp = OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
         .map_err(|_| AppError::bad_request("invalid cursor"))?;
-    let id = Uuid::parse_str(id).map_err(|_| AppError::bad_request("invalid cursor"))?;
 
     Ok(Some((timestamp, id)))
 }
 
-fn encode_cursor(cursor: Option<(OffsetDateTime, Uuid)>) -> Option<String> {
+fn encode_cursor(cursor: Option<(OffsetDateTime, Uuid)>, hmac_key: &[u8; 32]) -> Option<String> {
     let (timestamp, id) = cursor?;
-    let timestamp = timestamp.format(&Rfc3339).ok()?;
-    Some(format!("{}/{}", timestamp, id))
+    let millis = (timestamp.unix_timestamp_nanos() / 1_000_000) as i64;
+
+    let mut payload = Vec::with_capacity(24);
+    payload.extend_from_slice(&millis.to_be_bytes());
+    payload.extend_from_slice(id.as_bytes());
+
+    let mut mac =
+        CursorHmac::new_from_slice(hmac_key).expect("HMAC-SHA256 accepts any key length");
+    mac.update(&payload);
+    let tag = mac.finalize().into_bytes();
+
+    let mut sealed = payload;
+    sealed.extend_from_slice(&tag);
+    Some(URL_SAFE_NO_PAD.encode(sealed))
+}
+
+/// Builds an RFC 5988 `Link` header value for a cursor-paginated list
+/// response, so generic HTTP clients and proxies can follow pagination
+/// without parsing the JSON body's `next_cursor` field. Emits `rel="next"`
+/// when there's another page, and `rel="first"` pointing back at the
+/// unpaginated route when the caller supplied a cursor (there's no stored
+/// "previous cursor" to offer a true `rel="prev"`). Returns `None` when
+/// neither applies, so callers can skip adding the header entirely.
+fn build_link_header(path: &str, next_cursor: Option<&str>, limit: i64, had_cursor: bool) -> Option<HeaderValue> {
+    let mut links = Vec::new();
+    if let Some(cursor) = next_cursor {
+        links.push(format!(
+            "<{path}?cursor={}&limit={limit}>; rel=\"next\"",
+            percent_encode_cursor(cursor)
+        ));
+    }
+    if had_cursor {
+        links.push(format!("<{path}?limit={limit}>; rel=\"first\""));
+    }
+    if links.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(&links.join(", ")).ok()
+}
+
+/// Cursors are base64url (`A-Za-z0-9-_`), already URL-safe, but this
+/// percent-encodes defensively in case the cursor format ever changes.
+fn percent_encode_cursor(cursor: &str) -> String {
+    let mut out = String::with_capacity(cursor.len());
+    for byte in cursor.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Gate a story mutation (reaction, view) on the viewer actually being able
+/// to see the story. `StoryService::get_story` already applies the
+/// public/friends_only/close_friends_only/list visibility rules, so any
+/// `None` here is either a nonexistent story or one the viewer can't see --
+/// in the private-account case specifically because their follow request to
+/// the author is still pending, which is surfaced as `forbidden` rather than
+/// `not_found` so a client can tell "wait for approval" apart from "gone".
+async fn ensure_story_viewable(
+    service: &crate::app::stories::StoryService,
+    state: &AppState,
+    story_id: Uuid,
+    viewer_id: Uuid,
+) -> Result<(), AppError> {
+    let visible = service.get_story(story_id, viewer_id).await.map_err(|err| {
+        tracing::error!(error = ?err, story_id = %story_id, viewer_id = %viewer_id, "failed to check story visibility");
+        AppError::internal("failed to check story visibility")
+    })?;
+
+    if visible.is_some() {
+        return Ok(());
+    }
+
+    let Some(owner_id) = service.get_story_owner(story_id).await.map_err(|err| {
+        tracing::error!(error = ?err, story_id = %story_id, "failed to look up story owner");
+        AppError::internal("failed to check story visibility")
+    })?
+    else {
+        return Err(AppError::not_found("story not found"));
+    };
+
+    let social = SocialService::new(state.db.clone());
+    let pending = social
+        .is_follow_pending(viewer_id, owner_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, story_id = %story_id, viewer_id = %viewer_id, "failed to check follow request status");
+            AppError::internal("failed to check story visibility")
+        })?;
+
+    if pending {
+        return Err(AppError::forbidden("follow request still pending approval"));
+    }
+
+    Err(AppError::not_found("story not found"))
+}
+
+/// Build the device metadata recorded alongside a refresh token at
+/// login/refresh time. The IP is stored coarsened to its subnet (the same
+/// bucketing the IP rate limiter uses) rather than the literal address.
+fn device_context(
+    state: &AppState,
+    addr: SocketAddr,
+    headers: &HeaderMap,
+    device_label: Option<String>,
+) -> crate::app::auth::DeviceContext {
+    let client_ip = resolve_client_ip(addr.ip(), headers, &state.trusted_proxy_cidrs);
+    let ip = subnet_bucket(client_ip, state.ip_subnet_prefix_v4, state.ip_subnet_prefix_v6);
+    let user_agent = headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    crate::app::auth::DeviceContext {
+        user_agent,
+        ip: Some(ip),
+        device_label,
+    }
 }
 
 pub(crate) async fn health(State(state): State<AppState>) -> Json<HealthResponse> {
@@ -72,17 +271,38 @@ pub(crate) async fn health(State(state): State<AppState>) -> Json<HealthResponse
     Json(HealthResponse { status })
 }
 
-pub async fn metrics() -> Result<StatusCode, AppError> {
-    Err(AppError::not_implemented("metrics not yet available"))
+#[derive(Serialize)]
+pub struct MetricsResponse {
+    pub media_concurrency: crate::app::media::MediaConcurrencyStats,
+    pub user_cache: crate::app::users::UserCacheStats,
 }
 
-#[derive(Deserialize)]
+/// Lightweight operator-facing counters, distinct from the per-user
+/// `ConcurrencyLimiter` (which has no process-wide view to report). Reports
+/// `media_concurrency`, the limiter operators tune `MEDIA_MAX_CONCURRENCY`
+/// against, and `user_cache`; add fields here as more subsystems need the
+/// same visibility.
+pub async fn metrics(State(state): State<AppState>) -> Json<MetricsResponse> {
+    Json(MetricsResponse {
+        media_concurrency: state.media_concurrency_limiter.stats(),
+        user_cache: state.user_cache.cache_stats(),
+    })
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
+    /// Requested scopes, e.g. `["read:feed"]` for a third-party integration
+    /// that only needs read access. Omit to receive the full scope set.
+    pub scope: Option<Vec<String>>,
+    /// Caller-supplied label for this device (e.g. "Sarah's iPhone"), shown
+    /// back in `GET /v1/auth/sessions`. Falls back to the User-Agent header
+    /// when omitted.
+    pub device_label: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, utoipa::ToSchema)]
 pub struct AuthTokenResponse {
     pub access_token: String,
     pub refresh_token: String,
@@ -92,10 +312,33 @@ pub struct AuthTokenResponse {
     pub refresh_expires_at: OffsetDateTime,
 }
 
+/// `login`'s response: either `tokens` (the usual case) or `totp_handle`
+/// when the account has a confirmed TOTP secret and the client still needs
+/// to call `POST /auth/totp/login` with the second factor.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct LoginResponse {
+    pub tokens: Option<AuthTokenResponse>,
+    pub totp_handle: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login succeeded, or a totp_handle if a second factor is required", body = LoginResponse),
+        (status = 400, description = "Missing/too-long email or password"),
+        (status = 401, description = "Invalid credentials"),
+        (status = 403, description = "Account is deactivated but still recoverable"),
+    ),
+    tag = "auth",
+)]
 pub async fn login(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthTokenResponse>, AppError> {
+) -> Result<Json<LoginResponse>, AppError> {
     const MAX_PASSWORD_LEN: usize = 128;
 
     if payload.email.trim().is_empty() || payload.password.trim().is_empty() {
@@ -105,54 +348,108 @@ pub async fn login(
         return Err(AppError::bad_request("password must be at most 128 characters"));
     }
 
+    let device = device_context(&state, addr, &headers, payload.device_label.clone());
+
+    let requested_scopes = payload
+        .scope
+        .map(|requested| {
+            requested
+                .iter()
+                .map(|raw| {
+                    crate::app::auth::Scope::from_str(raw)
+                        .ok_or_else(|| AppError::bad_request(format!("unknown scope: {}", raw)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
     let service = AuthService::new(
         state.db.clone(),
-        state.paseto_access_key,
-        state.paseto_refresh_key,
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
         state.access_ttl_minutes,
         state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
     );
-    let tokens = service
-        .login(&payload.email, &payload.password)
+    let outcome = service
+        .login(&payload.email, &payload.password, requested_scopes, device)
         .await
         .map_err(|err| {
+            if err.to_string().contains("recoverable") {
+                return AppError::forbidden(
+                    "this account is deactivated; call /account/restore to reactivate it",
+                );
+            }
             tracing::error!(error = ?err, "failed to login");
             AppError::internal("failed to login")
         })?;
 
-    match tokens {
-        Some(tokens) => Ok(Json(AuthTokenResponse {
-            access_token: tokens.access_token,
-            refresh_token: tokens.refresh_token,
-            access_expires_at: tokens.access_expires_at,
-            refresh_expires_at: tokens.refresh_expires_at,
+    match outcome {
+        Some(crate::app::auth::LoginOutcome::Tokens(tokens)) => Ok(Json(LoginResponse {
+            tokens: Some(AuthTokenResponse {
+                access_token: tokens.access_token,
+                refresh_token: tokens.refresh_token,
+                access_expires_at: tokens.access_expires_at,
+                refresh_expires_at: tokens.refresh_expires_at,
+            }),
+            totp_handle: None,
+        })),
+        Some(crate::app::auth::LoginOutcome::TotpRequired { handle }) => Ok(Json(LoginResponse {
+            tokens: None,
+            totp_handle: Some(handle),
         })),
         None => Err(AppError::unauthorized("invalid credentials")),
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct RefreshRequest {
     pub refresh_token: String,
+    /// Caller-supplied label for this device, recorded against the rotated
+    /// refresh token the same way `LoginRequest::device_label` is. Falls
+    /// back to the User-Agent header when omitted, so a client that never
+    /// sends one still shows up as something recognizable in
+    /// `GET /v1/auth/sessions`.
+    pub device_label: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/auth/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "Token refreshed", body = AuthTokenResponse),
+        (status = 400, description = "refresh_token is required"),
+        (status = 401, description = "Refresh token is invalid, expired, or revoked"),
+    ),
+    tag = "auth",
+)]
 pub async fn refresh_token(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<RefreshRequest>,
 ) -> Result<Json<AuthTokenResponse>, AppError> {
     if payload.refresh_token.trim().is_empty() {
         return Err(AppError::bad_request("refresh_token is required"));
     }
 
+    let device = device_context(&state, addr, &headers, payload.device_label.clone());
+
     let service = AuthService::new(
         state.db.clone(),
-        state.paseto_access_key,
-        state.paseto_refresh_key,
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
         state.access_ttl_minutes,
         state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
     );
     let tokens = service
-        .refresh(&payload.refresh_token)
+        .refresh(&payload.refresh_token, device)
         .await
         .map_err(|err| {
             tracing::error!(error = ?err, "failed to refresh token");
@@ -185,10 +482,13 @@ pub async fn revoke_token(
 
     let service = AuthService::new(
         state.db.clone(),
-        state.paseto_access_key,
-        state.paseto_refresh_key,
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
         state.access_ttl_minutes,
         state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
     );
     let revoked = service
         .revoke_refresh_token(&payload.refresh_token)
@@ -202,1835 +502,6922 @@ pub async fn revoke_token(
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn get_current_user(
+/// Log out every session for the authenticated user by bumping their
+/// session epoch -- every access/refresh token issued before this call
+/// stops validating, not just the one presented here.
+pub async fn logout_all_sessions(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<crate::domain::user::User>, AppError> {
+) -> Result<StatusCode, AppError> {
     let service = AuthService::new(
         state.db.clone(),
-        state.paseto_access_key,
-        state.paseto_refresh_key,
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
         state.access_ttl_minutes,
         state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
     );
-    let user = service
-        .get_current_user(auth.user_id)
+    service
+        .revoke_all_sessions(auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch current user");
-            AppError::internal("failed to fetch current user")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to revoke sessions");
+            AppError::internal("failed to revoke sessions")
         })?;
 
-    match user {
-        Some(user) => Ok(Json(user)),
-        None => Err(AppError::not_found("user not found")),
-    }
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn get_user(
-    Path(id): Path<Uuid>,
+/// List the authenticated user's active sessions (one per still-valid,
+/// unrevoked refresh token), most recently used first.
+pub async fn list_sessions(
+    auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<crate::domain::user::PublicUser>, AppError> {
-    let service = UserService::new(state.db.clone());
-    let user = service.get_user(id).await.map_err(|err| {
-        tracing::error!(error = ?err, user_id = %id, "failed to fetch user");
-        AppError::internal("failed to fetch user")
-    })?;
+) -> Result<Json<Vec<crate::domain::session::SessionSummary>>, AppError> {
+    let service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let sessions = service
+        .list_sessions(auth.user_id, Some(auth.session_id))
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to list sessions");
+            AppError::internal("failed to list sessions")
+        })?;
 
-    match user {
-        Some(user) => Ok(Json(user.into())),
-        None => Err(AppError::not_found("user not found")),
+    Ok(Json(sessions))
+}
+
+/// Revoke a single session (refresh token) by id, e.g. to sign out a lost
+/// or stolen device without affecting any other session.
+pub async fn revoke_session(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let revoked = service
+        .revoke_session(auth.user_id, session_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to revoke session");
+            AppError::internal("failed to revoke session")
+        })?;
+
+    if !revoked {
+        return Err(AppError::not_found("session not found"));
     }
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 #[derive(Deserialize)]
-pub struct CreateUserRequest {
-    pub handle: String,
-    pub email: String,
-    pub display_name: String,
-    pub bio: Option<String>,
-    pub avatar_key: Option<String>,
-    pub password: String,
-    pub invite_code: String,
+pub struct RevokeAllSessionsRequest {
+    /// When `true`, keep the session making this request alive and only
+    /// revoke every other one -- "log out all other devices".
+    #[serde(default)]
+    pub exclude_current: bool,
 }
 
-pub async fn create_user(
+/// Revoke every refresh token for the authenticated user, optionally
+/// keeping the current session alive. Unlike `/auth/logout-all`, this marks
+/// the `refresh_tokens` rows themselves revoked (and bumps the session
+/// epoch only when no session is excluded; see
+/// `AuthService::revoke_sessions`), so revoked devices disappear from
+/// `GET /v1/auth/sessions` immediately.
+pub async fn revoke_all_sessions_endpoint(
+    auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<CreateUserRequest>,
-) -> Result<Json<crate::domain::user::User>, AppError> {
-    const MAX_PASSWORD_LEN: usize = 128;
-
-    if payload.handle.trim().is_empty() {
-        return Err(AppError::bad_request("handle cannot be empty"));
-    }
-    if payload.email.trim().is_empty() {
-        return Err(AppError::bad_request("email cannot be empty"));
-    }
-    if payload.display_name.trim().is_empty() {
-        return Err(AppError::bad_request("display_name cannot be empty"));
-    }
-    if payload.password.trim().len() < 8 {
-        return Err(AppError::bad_request("password must be at least 8 characters"));
-    }
-    if payload.password.len() > MAX_PASSWORD_LEN {
-        return Err(AppError::bad_request("password must be at most 128 characters"));
-    }
-    if payload.invite_code.trim().is_empty() {
-        return Err(AppError::bad_request("invite_code is required"));
-    }
-
+    Json(payload): Json<RevokeAllSessionsRequest>,
+) -> Result<StatusCode, AppError> {
     let service = AuthService::new(
         state.db.clone(),
-        state.paseto_access_key,
-        state.paseto_refresh_key,
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
         state.access_ttl_minutes,
         state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
     );
-    let user = service
-        .signup(
-            payload.handle,
-            payload.email,
-            payload.display_name,
-            payload.bio,
-            payload.avatar_key,
-            payload.password,
-            payload.invite_code,
-        )
+    let except = payload.exclude_current.then_some(auth.session_id);
+    service
+        .revoke_sessions(auth.user_id, except)
         .await
         .map_err(|err| {
-            if let Some(sqlx_err) = err.downcast_ref::<sqlx::Error>() {
-                if let Some(db_err) = sqlx_err.as_database_error() {
-                    if let Some(code) = db_err.code() {
-                        if code == "23505" {
-                            let constraint = db_err.constraint().unwrap_or_default();
-                            if constraint.contains("users_handle_key") {
-                                return AppError::conflict("Handle already taken");
-                            }
-                            if constraint.contains("users_email_key") {
-                                return AppError::conflict("Email already taken");
-                            }
-                        }
-                    }
-                }
-            }
-            let message = err.to_string();
-            if message.contains("invite code") || message.contains("Invite code") {
-                return AppError::bad_request(message);
-            }
-            tracing::error!(error = ?err, "failed to create user");
-            AppError::internal("failed to create user")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to revoke sessions");
+            AppError::internal("failed to revoke sessions")
         })?;
 
-    Ok(Json(user))
+    Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Deserialize)]
-pub struct UpdateProfileRequest {
-    pub display_name: Option<String>,
-    pub bio: Option<String>,
-    pub avatar_key: Option<String>,
+/// `get_current_user`'s response: the account plus its position in the
+/// moderator hierarchy, so clients know whether to show moderation tools
+/// without a second round-trip to a `/moderation/*` endpoint.
+#[derive(Serialize)]
+pub struct CurrentUserResponse {
+    #[serde(flatten)]
+    pub user: crate::domain::user::User,
+    pub role: crate::domain::moderation::UserRole,
 }
 
-pub async fn update_profile(
-    Path(id): Path<Uuid>,
+pub async fn get_current_user(
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<UpdateProfileRequest>,
-) -> Result<Json<crate::domain::user::User>, AppError> {
-    if auth.user_id != id {
-        return Err(AppError::forbidden("cannot update other users"));
-    }
-
-    if let Some(display_name) = &payload.display_name {
-        if display_name.trim().is_empty() {
-            return Err(AppError::bad_request("display_name cannot be empty"));
-        }
-    }
-
-    let service = UserService::new(state.db.clone());
+) -> Result<Json<CurrentUserResponse>, AppError> {
+    let service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
     let user = service
-        .update_profile(id, payload.display_name, payload.bio, payload.avatar_key)
+        .get_current_user(auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %id, "failed to update profile");
-            AppError::internal("failed to update profile")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch current user");
+            AppError::internal("failed to fetch current user")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
+
+    let role = ModerationService::new(state.db.clone())
+        .role_of(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to resolve role");
+            AppError::internal("failed to fetch current user")
         })?;
 
-    match user {
-        Some(user) => Ok(Json(user)),
-        None => Err(AppError::not_found("user not found")),
-    }
+    Ok(Json(CurrentUserResponse { user, role }))
 }
 
-/// Delete user account and all associated data (GDPR/CCPA compliance)
-pub async fn delete_account(
+/// Request (or resend) an email verification token for the authenticated
+/// user. Always reissues a fresh token, invalidating any prior one.
+pub async fn request_email_verification(
     auth: AuthUser,
     State(state): State<AppState>,
 ) -> Result<StatusCode, AppError> {
-    let service = UserService::new(state.db.clone());
-    let deleted = service
-        .delete_account(auth.user_id)
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let user = auth_service
+        .get_current_user(auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to delete account");
-            AppError::internal("failed to delete account")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch current user");
+            AppError::internal("failed to fetch current user")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
+
+    let service = crate::app::email_verification::EmailVerificationService::new(
+        state.db.clone(),
+        state.mailer.clone(),
+        state.email_verification_ttl_hours,
+    );
+    service
+        .request_verification(auth.user_id, &user.email)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to send verification email");
+            AppError::internal("failed to send verification email")
         })?;
 
-    if deleted {
-        tracing::info!(user_id = %auth.user_id, "account deleted");
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("user not found"))
-    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Consume an email verification token, flipping `email_verified_at` for
+/// the owning account.
+pub async fn confirm_email_verification(
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::email_verification::ConfirmEmailVerificationRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::email_verification::EmailVerificationService::new(
+        state.db.clone(),
+        state.mailer.clone(),
+        state.email_verification_ttl_hours,
+    );
+    service.confirm(&payload.token).await.map_err(|err| {
+        let message = err.to_string();
+        if message.contains("invalid verification token")
+            || message.contains("already used")
+            || message.contains("has expired")
+        {
+            return AppError::bad_request(message);
+        }
+        tracing::error!(error = ?err, "failed to confirm email verification");
+        AppError::internal("failed to confirm email verification")
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+/// Request a password-reset token for `email`. Always returns 204 whether
+/// or not the address belongs to an account -- mirrors the login endpoint's
+/// "no user enumeration" behavior.
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::password_reset::PasswordResetService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.mailer.clone(),
+        state.password_reset_ttl_hours,
+    );
+    service
+        .request_reset(&payload.email)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to issue password reset token");
+            AppError::internal("failed to issue password reset token")
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Consume a password-reset token, set a new password, and revoke every
+/// session for the owning account.
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    const MAX_PASSWORD_LEN: usize = 128;
+
+    if payload.new_password.trim().len() < 8 {
+        return Err(AppError::bad_request("password must be at least 8 characters"));
+    }
+    if payload.new_password.len() > MAX_PASSWORD_LEN {
+        return Err(AppError::bad_request("password must be at most 128 characters"));
+    }
+
+    let service = crate::app::password_reset::PasswordResetService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.mailer.clone(),
+        state.password_reset_ttl_hours,
+    );
+    let user_id = service
+        .reset_password(&payload.token, &payload.new_password)
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("invalid reset token")
+                || message.contains("already used")
+                || message.contains("has expired")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to reset password");
+            AppError::internal("failed to reset password")
+        })?;
+
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    auth_service
+        .revoke_sessions(user_id, None)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %user_id, "failed to revoke sessions after password reset");
+            AppError::internal("failed to revoke sessions")
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Change the authenticated user's password, verifying their current one
+/// first, then revoke every other session now that it's out of date.
+pub async fn change_password(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    const MAX_PASSWORD_LEN: usize = 128;
+
+    if payload.new_password.trim().len() < 8 {
+        return Err(AppError::bad_request("password must be at least 8 characters"));
+    }
+    if payload.new_password.len() > MAX_PASSWORD_LEN {
+        return Err(AppError::bad_request("password must be at most 128 characters"));
+    }
+
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let changed = auth_service
+        .change_password(auth.user_id, &payload.current_password, &payload.new_password)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to change password");
+            AppError::internal("failed to change password")
+        })?;
+
+    if !changed {
+        return Err(AppError::bad_request("current password is incorrect"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_user(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::user::PublicUser>, AppError> {
+    let service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let user = service.get_user(None, id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %id, "failed to fetch user");
+        AppError::internal("failed to fetch user")
+    })?;
+
+    match user {
+        Some(user) => {
+            let federation = crate::app::federation::FederationService::new(
+                state.db.clone(),
+                state.federation_base_url.clone(),
+            );
+            let ap_id = federation.actor_uri(id);
+            let public: crate::domain::user::PublicUser = user.into();
+            Ok(Json(public.with_ap_id(Some(ap_id))))
+        }
+        None => Err(AppError::not_found("user not found")),
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateUserRequest {
+    pub handle: String,
+    pub email: String,
+    pub display_name: String,
+    pub bio: Option<String>,
+    pub avatar_key: Option<String>,
+    pub password: String,
+    pub invite_code: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "Account created", body = crate::domain::user::User),
+        (status = 400, description = "A required field was missing, or the password/invite_code was invalid"),
+    ),
+    tag = "users",
+)]
+pub async fn create_user(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateUserRequest>,
+) -> Result<Json<crate::domain::user::User>, AppError> {
+    const MAX_PASSWORD_LEN: usize = 128;
+
+    if payload.handle.trim().is_empty() {
+        return Err(AppError::bad_request("handle cannot be empty"));
+    }
+    if payload.email.trim().is_empty() {
+        return Err(AppError::bad_request("email cannot be empty"));
+    }
+    if payload.display_name.trim().is_empty() {
+        return Err(AppError::bad_request("display_name cannot be empty"));
+    }
+    if payload.password.trim().len() < 8 {
+        return Err(AppError::bad_request("password must be at least 8 characters"));
+    }
+    if payload.password.len() > MAX_PASSWORD_LEN {
+        return Err(AppError::bad_request("password must be at most 128 characters"));
+    }
+    if payload.invite_code.trim().is_empty() {
+        return Err(AppError::bad_request("invite_code is required"));
+    }
+
+    let display_name = state
+        .content_filter
+        .enforce("display_name", &payload.display_name, state.profile_filter_mode)
+        .map_err(|err| AppError::bad_request(err.to_string()))?;
+    let bio = payload
+        .bio
+        .as_deref()
+        .map(|bio| state.content_filter.enforce("bio", bio, state.profile_filter_mode))
+        .transpose()
+        .map_err(|err| AppError::bad_request(err.to_string()))?;
+
+    let service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let user = service
+        .signup(
+            payload.handle,
+            payload.email,
+            display_name,
+            bio,
+            payload.avatar_key,
+            payload.password,
+            payload.invite_code,
+            &state.invite_signing_key,
+        )
+        .await
+        .map_err(|err| {
+            if let Some(conflict) = AppError::from_db_conflict(&err) {
+                return conflict;
+            }
+            let message = err.to_string();
+            if message.contains("revoked") {
+                return AppError::invite_revoked(message);
+            }
+            if message.contains("expired") {
+                return AppError::invite_expired(message);
+            }
+            if message.contains("fully used") {
+                return AppError::invite_consumed(message);
+            }
+            if message.contains("invite code") || message.contains("Invite code") {
+                return AppError::bad_request(message);
+            }
+            if message.contains("not allowed to sign up") {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to create user");
+            AppError::internal("failed to create user")
+        })?;
+
+    if let Err(err) = state.search_store.index_user(&user).await {
+        tracing::warn!(error = ?err, user_id = %user.id, "failed to index user for search");
+    }
+
+    Ok(Json(user))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProfileRequest {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    /// Id of a previously-uploaded, already-processed `Media` row (see
+    /// `MediaService::complete_upload`) to adopt as the caller's avatar.
+    /// Resolved and ownership-checked in the handler below rather than
+    /// accepting a raw storage key, so a caller can't point their avatar at
+    /// an object they never uploaded.
+    pub avatar_media_id: Option<Uuid>,
+}
+
+pub async fn update_profile(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<Json<crate::domain::user::User>, AppError> {
+    if auth.user_id != id {
+        return Err(AppError::forbidden("cannot update other users"));
+    }
+
+    if let Some(display_name) = &payload.display_name {
+        if display_name.trim().is_empty() {
+            return Err(AppError::bad_request("display_name cannot be empty"));
+        }
+    }
+
+    let display_name = payload
+        .display_name
+        .as_deref()
+        .map(|display_name| {
+            state
+                .content_filter
+                .enforce("display_name", display_name, state.profile_filter_mode)
+        })
+        .transpose()
+        .map_err(|err| AppError::bad_request(err.to_string()))?;
+    let bio = payload
+        .bio
+        .as_deref()
+        .map(|bio| state.content_filter.enforce("bio", bio, state.profile_filter_mode))
+        .transpose()
+        .map_err(|err| AppError::bad_request(err.to_string()))?;
+
+    let media_service = MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+    let avatar_key = match payload.avatar_media_id {
+        Some(media_id) => {
+            let media = media_service
+                .get_media(media_id)
+                .await
+                .map_err(|err| {
+                    tracing::error!(error = ?err, media_id = %media_id, "failed to look up avatar media");
+                    AppError::internal("failed to update profile")
+                })?
+                .ok_or_else(|| AppError::bad_request("avatar media not found"))?;
+            if media.owner_id != auth.user_id {
+                return Err(AppError::forbidden("cannot use media you do not own as an avatar"));
+            }
+            if media.kind != crate::domain::media::MediaKind::Image {
+                return Err(AppError::bad_request("avatar media must be an image"));
+            }
+            Some(media.thumb_key)
+        }
+        None => None,
+    };
+
+    let service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let user = service
+        .update_profile(None, id, display_name, bio, avatar_key)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to update profile");
+            AppError::internal("failed to update profile")
+        })?;
+
+    match user {
+        Some(mut user) => {
+            media_service.populate_user_avatar_url(&mut user).await;
+            if let Err(err) = state.search_store.index_user(&user).await {
+                tracing::warn!(error = ?err, user_id = %user.id, "failed to re-index edited profile for search");
+            }
+            Ok(Json(user))
+        }
+        None => Err(AppError::not_found("user not found")),
+    }
+}
+
+/// Mastodon-style "locked account": subsequent follows from anyone land
+/// `pending` instead of `accepted` (see `SocialService::follow`). Doesn't
+/// touch any already-`accepted` follow.
+pub async fn lock_account(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::user::User>, AppError> {
+    if auth.user_id != id {
+        return Err(AppError::forbidden("cannot update other users"));
+    }
+
+    let service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    set_account_locked(&service, id, true).await
+}
+
+pub async fn unlock_account(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::user::User>, AppError> {
+    if auth.user_id != id {
+        return Err(AppError::forbidden("cannot update other users"));
+    }
+
+    let service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    set_account_locked(&service, id, false).await
+}
+
+async fn set_account_locked(
+    service: &UserService,
+    user_id: Uuid,
+    locked: bool,
+) -> Result<Json<crate::domain::user::User>, AppError> {
+    let updated = service.set_locked(user_id, locked).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %user_id, "failed to update account lock state");
+        AppError::internal("failed to update account lock state")
+    })?;
+
+    if !updated {
+        return Err(AppError::not_found("user not found"));
+    }
+
+    let user = service
+        .get_user(None, user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %user_id, "failed to fetch user after lock update");
+            AppError::internal("failed to fetch user")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
+
+    Ok(Json(user))
+}
+
+/// Deactivate user account (GDPR/CCPA "delete my account" entry point).
+/// Soft-deletes the user and revokes refresh tokens, but -- unlike the old
+/// immediate purge this replaces -- leaves follows/blocks intact so
+/// `restore_account` can undo this within `account_recovery_grace_days`.
+/// Runs on a single shared `UnitOfWork` so the user deactivation and the
+/// notifications cleanup commit or roll back together.
+pub async fn delete_account(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+
+    let mut uow = crate::infra::db::UnitOfWork::begin(&state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to begin account deletion transaction");
+            AppError::internal("failed to delete account")
+        })?;
+
+    let result: anyhow::Result<bool> = async {
+        let deleted = service.deactivate_account(Some(&mut uow), auth.user_id).await?;
+        if deleted {
+            notification_service
+                .delete_all_for_user(Some(&mut uow), auth.user_id)
+                .await?;
+        }
+        Ok(deleted)
+    }
+    .await;
+
+    let deleted = match result {
+        Ok(deleted) => {
+            if deleted {
+                uow.commit().await
+            } else {
+                uow.rollback().await
+            }
+            .map_err(|err| {
+                tracing::error!(error = ?err, user_id = %auth.user_id, "failed to finalize account deletion");
+                AppError::internal("failed to delete account")
+            })?;
+            deleted
+        }
+        Err(err) => {
+            let _ = uow.rollback().await;
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to delete account");
+            return Err(AppError::internal("failed to delete account"));
+        }
+    };
+
+    if deleted {
+        if let Err(err) = state.search_store.remove_user(auth.user_id).await {
+            tracing::warn!(error = ?err, user_id = %auth.user_id, "failed to remove deleted account from search index");
+        }
+        tracing::info!(user_id = %auth.user_id, "account deleted");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("user not found"))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RestoreAccountRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// Reactivates a deactivated account that's still inside its recovery
+/// window. A deactivated user can't hold a valid access token (`login`
+/// refuses to issue one while `deleted_at` is set), so this re-verifies
+/// the password directly rather than going through `AuthUser`, the same
+/// way `login` does.
+pub async fn restore_account(
+    State(state): State<AppState>,
+    Json(payload): Json<RestoreAccountRequest>,
+) -> Result<StatusCode, AppError> {
+    if payload.email.trim().is_empty() || payload.password.trim().is_empty() {
+        return Err(AppError::bad_request("email and password are required"));
+    }
+
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let user_id = auth_service
+        .verify_credentials(&payload.email, &payload.password)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to verify credentials for account restore");
+            AppError::internal("failed to restore account")
+        })?
+        .ok_or_else(|| AppError::unauthorized("invalid credentials"))?;
+
+    let service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let restored = service.restore_account(user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %user_id, "failed to restore account");
+        AppError::internal("failed to restore account")
+    })?;
+
+    if restored {
+        tracing::info!(user_id = %user_id, "account restored");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::forbidden(
+            "account is not within its recovery window",
+        ))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/users/{id}/posts",
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+        PaginationQuery,
+    ),
+    responses(
+        (status = 200, description = "Page of the user's visible posts, newest first", body = PostListResponse),
+        (status = 400, description = "limit outside 1..=200"),
+    ),
+    tag = "posts",
+)]
+pub async fn list_user_posts(
+    Path(id): Path<Uuid>,
+    auth: Option<AuthUser>,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<crate::domain::post::Post>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+    let viewer_id = auth.map(|user| user.user_id);
+
+    let service = PostService::new(state.db.clone());
+    let mut posts = service
+        .list_by_user(id, viewer_id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to list user posts");
+            AppError::internal("failed to list user posts")
+        })?;
+
+    let next_cursor = if posts.len() > limit as usize {
+        let last = posts.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: posts,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+/// Newest public posts a feed reader fetches without following pagination --
+/// mirrors the default page size `list_user_posts` gives an unauthenticated
+/// caller, which is also the one this reuses (`viewer_id: None`).
+const SYNDICATION_FEED_LIMIT: i64 = 30;
+
+async fn user_feed_posts(id: Uuid, state: &AppState) -> Result<(crate::domain::user::User, Vec<crate::domain::post::Post>), AppError> {
+    let user_service = UserService::new(state.db.clone(), state.user_cache.clone(), state.account_recovery_grace_days);
+    let user = user_service
+        .get_user(None, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to load user for syndication feed");
+            AppError::internal("failed to load user")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
+
+    let post_service = PostService::new(state.db.clone());
+    let posts = post_service
+        .list_by_user(id, None, None, SYNDICATION_FEED_LIMIT)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to list user posts for syndication feed");
+            AppError::internal("failed to list user posts")
+        })?;
+
+    Ok((user, posts))
+}
+
+/// `GET /users/:id/feed.atom` -- the user's public post timeline as an Atom
+/// feed (see `app::syndication::render_atom_feed`), for following without
+/// the JSON API or an account.
+pub async fn user_feed_atom(Path(id): Path<Uuid>, State(state): State<AppState>) -> Result<Response, AppError> {
+    let (user, posts) = user_feed_posts(id, &state).await?;
+    let body = crate::app::syndication::render_atom_feed(&state.federation_base_url, &user, &posts);
+    Ok((StatusCode::OK, [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")], body).into_response())
+}
+
+/// `GET /users/:id/feed.rss` -- the RSS 2.0 equivalent of `user_feed_atom`.
+pub async fn user_feed_rss(Path(id): Path<Uuid>, State(state): State<AppState>) -> Result<Response, AppError> {
+    let (user, posts) = user_feed_posts(id, &state).await?;
+    let body = crate::app::syndication::render_rss_feed(&state.federation_base_url, &user, &posts);
+    Ok((StatusCode::OK, [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")], body).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/users/{id}/follow",
+    params(("id" = String, Path, description = "Local user id to follow, or a remote `acct:handle@domain`")),
+    responses(
+        (status = 200, description = "Follow created (or left pending, for a private account or a remote actor awaiting Accept)", body = FollowResponse),
+        (status = 400, description = "Cannot follow yourself, or the remote actor could not be resolved"),
+        (status = 403, description = "Followee has reached the follower limit"),
+    ),
+    tag = "social",
+    security(("bearer_token" = [])),
+)]
+pub async fn follow_user(
+    Path(target): Path<String>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<FollowResponse>, AppError> {
+    let Ok(id) = Uuid::parse_str(&target) else {
+        return follow_remote_user_by_acct(auth, state, &target).await;
+    };
+
+    if auth.user_id == id {
+        return Err(AppError::bad_request("cannot follow yourself"));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let (followed, follow_state, counts) =
+        service.follow(auth.user_id, id).await.map_err(|err| {
+            if err.to_string().contains("follower limit") {
+                return AppError::forbidden("user has reached the follower limit");
+            }
+            tracing::error!(error = ?err, follower_id = %auth.user_id, followee_id = %id, "failed to follow user");
+            AppError::internal("failed to follow user")
+        })?;
+
+    if followed {
+        let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+        if let Err(err) = notification_service.notify_follow(id, auth.user_id).await {
+            tracing::warn!(error = ?err, follower_id = %auth.user_id, followee_id = %id, "failed to create follow notification");
+        }
+
+        // A pending follow request isn't a follow yet, so it shouldn't
+        // change what shows up in the followee's stories feed.
+        if follow_state == crate::app::social::FollowState::Accepted {
+            let story_service =
+                crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+            if let Err(err) = story_service.bump_feed_version(auth.user_id).await {
+                tracing::warn!(error = ?err, follower_id = %auth.user_id, "failed to bump stories feed version after follow");
+            } else {
+                state
+                    .gossip
+                    .broadcast_story_feed_changed(vec![auth.user_id])
+                    .await;
+            }
+        }
+    }
+
+    Ok(Json(FollowResponse {
+        followed,
+        requested: follow_state == crate::app::social::FollowState::Pending,
+        followers_count: counts.followers_count,
+        following_count: counts.following_count,
+    }))
+}
+
+/// The ActivityPub branch of `follow_user`, reached when `target` doesn't
+/// parse as a local `Uuid`: resolves `acct:handle@domain` via WebFinger and
+/// sends a signed `Follow`, leaving the request `pending` until the remote
+/// actor's `Accept` reaches `post_inbox`. There's no local `follows` row
+/// for a remote followee, so `followers_count`/`following_count` below are
+/// the caller's own counts, unaffected by this follow (see
+/// `app::federation::FederationService::follow_remote_actor`).
+async fn follow_remote_user_by_acct(
+    auth: AuthUser,
+    state: AppState,
+    target: &str,
+) -> Result<Json<FollowResponse>, AppError> {
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    let remote = federation
+        .follow_remote_actor(auth.user_id, target)
+        .await
+        .map_err(|err| {
+            tracing::warn!(error = ?err, target = %target, "failed to initiate remote follow");
+            AppError::bad_request(err.to_string())
+        })?;
+
+    let job = crate::jobs::federation_sweeper::FederationJob {
+        activity: remote.activity,
+        inbox: Some(remote.inbox),
+    };
+    if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+        tracing::warn!(error = ?err, actor_url = %remote.actor_url, "failed to enqueue outbound Follow activity");
+    }
+
+    let user_service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let (_, followers_count, following_count, _) = user_service
+        .get_public_user_with_counts(None, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch follow counts after remote follow");
+            AppError::internal("failed to fetch follow counts")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
+
+    Ok(Json(FollowResponse {
+        followed: false,
+        requested: true,
+        followers_count,
+        following_count,
+    }))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct FollowResponse {
+    pub followed: bool,
+    /// True when `followed` landed as a pending request awaiting
+    /// `accept_follow_request` (the followee has a locked/private account,
+    /// or is a remote actor yet to send back an `Accept`).
+    pub requested: bool,
+    pub followers_count: i64,
+    pub following_count: i64,
+}
+
+pub async fn accept_follow_request(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<FollowResponse>, AppError> {
+    let service = SocialService::new(state.db.clone());
+    let (accepted, counts) = service
+        .accept_follow(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, follower_id = %id, followee_id = %auth.user_id, "failed to accept follow request");
+            AppError::internal("failed to accept follow request")
+        })?;
+
+    if !accepted {
+        return Err(AppError::not_found("no pending follow request from this user"));
+    }
+
+    let story_service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    if let Err(err) = story_service.bump_feed_version(id).await {
+        tracing::warn!(error = ?err, follower_id = %id, "failed to bump stories feed version after accepting follow request");
+    } else {
+        state.gossip.broadcast_story_feed_changed(vec![id]).await;
+    }
+
+    let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+    if let Err(err) = notification_service
+        .notify_follow_accepted(id, auth.user_id)
+        .await
+    {
+        tracing::warn!(error = ?err, follower_id = %id, followee_id = %auth.user_id, "failed to create follow-accepted notification");
+    }
+
+    Ok(Json(FollowResponse {
+        followed: true,
+        requested: false,
+        followers_count: counts.followers_count,
+        following_count: counts.following_count,
+    }))
+}
+
+pub async fn reject_follow_request(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = SocialService::new(state.db.clone());
+    let rejected = service
+        .reject_follow(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, follower_id = %id, followee_id = %auth.user_id, "failed to reject follow request");
+            AppError::internal("failed to reject follow request")
+        })?;
+
+    if rejected {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("no pending follow request from this user"))
+    }
+}
+
+pub async fn list_follow_requests(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<SocialUserItem>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = SocialService::new(state.db.clone());
+    let mut requests = service
+        .list_follow_requests(auth.user_id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to list follow requests");
+            AppError::internal("failed to list follow requests")
+        })?;
+
+    let next_cursor = if requests.len() > limit as usize {
+        let last = requests.pop().expect("checked len");
+        Some((last.followed_at, last.user.id))
+    } else {
+        None
+    };
+
+    let items = requests
+        .into_iter()
+        .map(|edge| SocialUserItem {
+            user: edge.user.into(),
+            followed_at: edge.followed_at,
+        })
+        .collect();
+
+    Ok(Json(ListResponse {
+        items,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct FollowRequestSummary {
+    pub pending_count: i64,
+}
+
+pub async fn get_follow_request_summary(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<FollowRequestSummary>, AppError> {
+    let service = SocialService::new(state.db.clone());
+    let pending_count = service
+        .pending_follow_request_count(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to count pending follow requests");
+            AppError::internal("failed to count pending follow requests")
+        })?;
+
+    Ok(Json(FollowRequestSummary { pending_count }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/users/{id}/unfollow",
+    params(("id" = Uuid, Path, description = "User id to unfollow")),
+    responses(
+        (status = 200, description = "Unfollowed (or already wasn't following)", body = UnfollowResponse),
+        (status = 400, description = "Cannot unfollow yourself"),
+    ),
+    tag = "social",
+    security(("bearer_token" = [])),
+)]
+pub async fn unfollow_user(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<UnfollowResponse>, AppError> {
+    if auth.user_id == id {
+        return Err(AppError::bad_request("cannot unfollow yourself"));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let (unfollowed, counts) = service.unfollow(auth.user_id, id).await.map_err(|err| {
+        tracing::error!(error = ?err, follower_id = %auth.user_id, followee_id = %id, "failed to unfollow user");
+        AppError::internal("failed to unfollow user")
+    })?;
+
+    if unfollowed {
+        let story_service =
+            crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+        if let Err(err) = story_service.bump_feed_version(auth.user_id).await {
+            tracing::warn!(error = ?err, follower_id = %auth.user_id, "failed to bump stories feed version after unfollow");
+        } else {
+            state
+                .gossip
+                .broadcast_story_feed_changed(vec![auth.user_id])
+                .await;
+        }
+    }
+
+    Ok(Json(UnfollowResponse {
+        unfollowed,
+        followers_count: counts.followers_count,
+        following_count: counts.following_count,
+    }))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct UnfollowResponse {
+    pub unfollowed: bool,
+    pub followers_count: i64,
+    pub following_count: i64,
+}
+
+pub async fn block_user(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<BlockResponse>, AppError> {
+    if auth.user_id == id {
+        return Err(AppError::bad_request("cannot block yourself"));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let blocked = service.block(auth.user_id, id).await.map_err(|err| {
+        tracing::error!(error = ?err, blocker_id = %auth.user_id, blocked_id = %id, "failed to block user");
+        AppError::internal("failed to block user")
+    })?;
+
+    if blocked {
+        let story_service =
+            crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+        if let Err(err) = story_service.bump_feed_version(auth.user_id).await {
+            tracing::warn!(error = ?err, blocker_id = %auth.user_id, "failed to bump stories feed version after block");
+        } else {
+            state
+                .gossip
+                .broadcast_story_feed_changed(vec![auth.user_id])
+                .await;
+        }
+    }
+
+    Ok(Json(BlockResponse { blocked }))
+}
+
+#[derive(Serialize)]
+pub struct BlockResponse {
+    pub blocked: bool,
+}
+
+pub async fn unblock_user(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<UnblockResponse>, AppError> {
+    if auth.user_id == id {
+        return Err(AppError::bad_request("cannot unblock yourself"));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let unblocked = service.unblock(auth.user_id, id).await.map_err(|err| {
+        tracing::error!(error = ?err, blocker_id = %auth.user_id, blocked_id = %id, "failed to unblock user");
+        AppError::internal("failed to unblock user")
+    })?;
+
+    if unblocked {
+        let story_service =
+            crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+        if let Err(err) = story_service.bump_feed_version(auth.user_id).await {
+            tracing::warn!(error = ?err, blocker_id = %auth.user_id, "failed to bump stories feed version after unblock");
+        } else {
+            state
+                .gossip
+                .broadcast_story_feed_changed(vec![auth.user_id])
+                .await;
+        }
+    }
+
+    Ok(Json(UnblockResponse { unblocked }))
+}
+
+#[derive(Serialize)]
+pub struct UnblockResponse {
+    pub unblocked: bool,
+}
+
+/// Unlike `block_user`, muting doesn't touch the follow graph or stories
+/// feed version -- the muted user is still followed (and can still follow
+/// back) and still sees everything; only the muter's own feed/notification
+/// queries filter them out.
+pub async fn mute_user(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<MuteResponse>, AppError> {
+    if auth.user_id == id {
+        return Err(AppError::bad_request("cannot mute yourself"));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let muted = service.mute(auth.user_id, id).await.map_err(|err| {
+        tracing::error!(error = ?err, muter_id = %auth.user_id, muted_id = %id, "failed to mute user");
+        AppError::internal("failed to mute user")
+    })?;
+
+    Ok(Json(MuteResponse { muted }))
+}
+
+#[derive(Serialize)]
+pub struct MuteResponse {
+    pub muted: bool,
+}
+
+pub async fn unmute_user(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<UnmuteResponse>, AppError> {
+    if auth.user_id == id {
+        return Err(AppError::bad_request("cannot unmute yourself"));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let unmuted = service.unmute(auth.user_id, id).await.map_err(|err| {
+        tracing::error!(error = ?err, muter_id = %auth.user_id, muted_id = %id, "failed to unmute user");
+        AppError::internal("failed to unmute user")
+    })?;
+
+    Ok(Json(UnmuteResponse { unmuted }))
+}
+
+#[derive(Serialize)]
+pub struct UnmuteResponse {
+    pub unmuted: bool,
+}
+
+#[derive(Serialize)]
+pub struct SocialUserItem {
+    pub user: crate::domain::user::PublicUser,
+    #[serde(with = "time::serde::rfc3339")]
+    pub followed_at: OffsetDateTime,
+}
+
+pub async fn list_followers(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<SocialUserItem>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = SocialService::new(state.db.clone());
+    let mut followers = service
+        .list_followers(id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to list followers");
+            AppError::internal("failed to list followers")
+        })?;
+
+    let next_cursor = if followers.len() > limit as usize {
+        let last = followers.pop().expect("checked len");
+        Some((last.followed_at, last.user.id))
+    } else {
+        None
+    };
+
+    let followed_at: Vec<OffsetDateTime> = followers.iter().map(|edge| edge.followed_at).collect();
+    let mut users: Vec<crate::domain::user::PublicUser> =
+        followers.into_iter().map(|edge| edge.user.into()).collect();
+    service
+        .add_user_relationships(auth.user_id, &mut users)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to batch follower relationships");
+            AppError::internal("failed to list followers")
+        })?;
+
+    let items = users
+        .into_iter()
+        .zip(followed_at)
+        .map(|(user, followed_at)| SocialUserItem { user, followed_at })
+        .collect();
+
+    Ok(Json(ListResponse {
+        items,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn list_following(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<SocialUserItem>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = SocialService::new(state.db.clone());
+    let mut following = service
+        .list_following(id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to list following");
+            AppError::internal("failed to list following")
+        })?;
+
+    let next_cursor = if following.len() > limit as usize {
+        let last = following.pop().expect("checked len");
+        Some((last.followed_at, last.user.id))
+    } else {
+        None
+    };
+
+    let followed_at: Vec<OffsetDateTime> = following.iter().map(|edge| edge.followed_at).collect();
+    let mut users: Vec<crate::domain::user::PublicUser> =
+        following.into_iter().map(|edge| edge.user.into()).collect();
+    service
+        .add_user_relationships(auth.user_id, &mut users)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to batch following relationships");
+            AppError::internal("failed to list following")
+        })?;
+
+    let items = users
+        .into_iter()
+        .zip(followed_at)
+        .map(|(user, followed_at)| SocialUserItem { user, followed_at })
+        .collect();
+
+    Ok(Json(ListResponse {
+        items,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct RelationshipResponse {
+    pub is_following: bool,
+    pub is_followed_by: bool,
+    pub is_blocking: bool,
+    pub is_blocked_by: bool,
+    /// True while a `Follow` the caller sent is still awaiting acceptance --
+    /// a private local account, or a remote actor that hasn't sent back an
+    /// `Accept` yet.
+    pub is_pending: bool,
+    pub is_muting: bool,
+    pub is_muted_by: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/users/{id}/relationship",
+    params(("id" = String, Path, description = "Other user's id, or a remote `acct:handle@domain`")),
+    responses(
+        (status = 200, description = "Follow/block relationship between the caller and user :id", body = RelationshipResponse),
+    ),
+    tag = "social",
+    security(("bearer_token" = [])),
+)]
+pub async fn relationship_status(
+    Path(target): Path<String>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<RelationshipResponse>, AppError> {
+    let Ok(id) = Uuid::parse_str(&target) else {
+        return remote_relationship_status(auth, state, &target).await;
+    };
+
+    if auth.user_id == id {
+        return Ok(Json(RelationshipResponse {
+            is_following: false,
+            is_followed_by: false,
+            is_blocking: false,
+            is_blocked_by: false,
+            is_pending: false,
+            is_muting: false,
+            is_muted_by: false,
+        }));
+    }
+
+    let service = SocialService::new(state.db.clone());
+    let status = service
+        .relationship_status(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, viewer_id = %auth.user_id, other_id = %id, "failed to fetch relationship status");
+            AppError::internal("failed to fetch relationship status")
+        })?;
+
+    Ok(Json(RelationshipResponse {
+        is_following: status.is_following,
+        is_followed_by: status.is_followed_by,
+        is_blocking: status.is_blocking,
+        is_blocked_by: status.is_blocked_by,
+        is_pending: status.is_pending,
+        is_muting: status.is_muting,
+        is_muted_by: status.is_muted_by,
+    }))
+}
+
+/// The ActivityPub branch of `relationship_status`, reached when `target`
+/// doesn't parse as a local `Uuid`: there's no local `blocks`/`follows` row
+/// for a remote actor, so everything but `is_following` and `is_pending`
+/// (both read from the remote follow's state) is trivially `false`.
+async fn remote_relationship_status(
+    auth: AuthUser,
+    state: AppState,
+    target: &str,
+) -> Result<Json<RelationshipResponse>, AppError> {
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    let follow_state = federation
+        .remote_follow_state(auth.user_id, target)
+        .await
+        .map_err(|err| {
+            tracing::warn!(error = ?err, target = %target, "failed to resolve remote relationship status");
+            AppError::bad_request(err.to_string())
+        })?;
+
+    Ok(Json(RelationshipResponse {
+        is_following: follow_state.as_deref() == Some("accepted"),
+        is_followed_by: false,
+        is_blocking: false,
+        is_blocked_by: false,
+        is_pending: follow_state.as_deref() == Some("pending"),
+        is_muting: false,
+        is_muted_by: false,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct CreatePostRequest {
+    pub media_ids: Vec<Uuid>,
+    pub caption: Option<String>,
+    pub visibility: crate::domain::post::PostVisibility,
+    #[serde(default)]
+    pub in_reply_to_id: Option<Uuid>,
+    #[serde(default)]
+    pub repost_of_id: Option<Uuid>,
+}
+
+pub async fn create_post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreatePostRequest>,
+) -> Result<Json<crate::domain::post::Post>, AppError> {
+    let service = PostService::new(state.db.clone());
+    let post = service
+        .create_post(
+            auth.user_id,
+            payload.media_ids,
+            payload.caption,
+            payload.visibility,
+            payload.in_reply_to_id,
+            payload.repost_of_id,
+        )
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("reposts cannot carry a caption") {
+                return AppError::bad_request(message);
+            }
+            if message.contains("invalid media_id") {
+                return AppError::bad_request("one or more media_id values are invalid or not owned by you");
+            }
+            if message.contains("reply target is a repost") {
+                return AppError::conflict(message);
+            }
+            tracing::error!(error = ?err, owner_id = %auth.user_id, "failed to create post");
+            AppError::internal("failed to create post")
+        })?;
+
+    let feed_service = FeedService::new(state.db.clone(), state.cache.clone());
+    if let Err(err) = feed_service.fan_out_post(&post).await {
+        tracing::warn!(error = ?err, post_id = %post.id, "failed to fan out post to follower timelines");
+    }
+
+    let streaming_service = StreamingService::new(state.db.clone(), state.cache.clone());
+    if let Err(err) = streaming_service.publish_post_created(&post).await {
+        tracing::warn!(error = ?err, post_id = %post.id, "failed to publish post to timeline channels");
+    }
+
+    let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+    if let Some(in_reply_to_id) = post.in_reply_to_id {
+        match service.get_owner_id(in_reply_to_id).await {
+            Ok(Some(recipient_id)) => {
+                if let Err(err) = notification_service
+                    .notify_reply(recipient_id, post.owner_id, post.id)
+                    .await
+                {
+                    tracing::warn!(error = ?err, post_id = %post.id, "failed to create reply notification");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, post_id = %post.id, "failed to look up reply target owner")
+            }
+        }
+    }
+    if let Some(repost_of_id) = post.repost_of_id {
+        match service.get_owner_id(repost_of_id).await {
+            Ok(Some(recipient_id)) => {
+                if let Err(err) = notification_service
+                    .notify_repost(recipient_id, post.owner_id, post.id)
+                    .await
+                {
+                    tracing::warn!(error = ?err, post_id = %post.id, "failed to create repost notification");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, post_id = %post.id, "failed to look up repost target owner")
+            }
+        }
+    }
+    for mention in &post.mentions {
+        if let Err(err) = notification_service
+            .notify_mention(mention.user_id, post.owner_id, post.id)
+            .await
+        {
+            tracing::warn!(error = ?err, post_id = %post.id, mentioned_user_id = %mention.user_id, "failed to create mention notification");
+        }
+    }
+
+    if let Err(err) = state.search_store.index_post(&post).await {
+        tracing::warn!(error = ?err, post_id = %post.id, "failed to index post for search");
+    }
+
+    Ok(Json(post))
+}
+
+pub async fn get_post(
+    Path(id): Path<Uuid>,
+    auth: Option<AuthUser>,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::post::Post>, AppError> {
+    let viewer_id = auth.map(|user| user.user_id);
+    let service = PostService::new(state.db.clone());
+    let post = service.get_post(id, viewer_id).await.map_err(|err| {
+        tracing::error!(error = ?err, post_id = %id, "failed to fetch post");
+        AppError::internal("failed to fetch post")
+    })?;
+
+    match post {
+        Some(post) => Ok(Json(post)),
+        None => Err(AppError::not_found("post not found")),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCaptionRequest {
+    pub caption: Option<String>,
+}
+
+pub async fn update_post_caption(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateCaptionRequest>,
+) -> Result<Json<crate::domain::post::Post>, AppError> {
+    let service = PostService::new(state.db.clone());
+    let post = service
+        .update_caption(id, auth.user_id, payload.caption)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, post_id = %id, "failed to update post");
+            AppError::internal("failed to update post")
+        })?;
+
+    match post {
+        Some(post) => {
+            if let Err(err) = state.search_store.index_post(&post).await {
+                tracing::warn!(error = ?err, post_id = %post.id, "failed to re-index edited post for search");
+            }
+            Ok(Json(post))
+        }
+        None => Err(AppError::not_found("post not found")),
+    }
+}
+
+pub async fn delete_post(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = PostService::new(state.db.clone());
+    let deletion_queue = service.delete_post(id, Some(auth.user_id)).await.map_err(|err| {
+        tracing::error!(error = ?err, post_id = %id, "failed to delete post");
+        AppError::internal("failed to delete post")
+    })?;
+
+    // Not the owner (or no such post) -- if the caller is at least a
+    // moderator, fall back to a force-delete rather than a 404, so mods can
+    // take down abusive posts without waiting on the owner.
+    let deletion_queue = match deletion_queue {
+        Some(queue) => Some(queue),
+        None => {
+            let moderation = ModerationService::new(state.db.clone());
+            if moderation
+                .authorize_post_force_delete(auth.user_id, id)
+                .await
+                .is_ok()
+            {
+                service.delete_post(id, None).await.map_err(|err| {
+                    tracing::error!(error = ?err, post_id = %id, actor_id = %auth.user_id, "failed to force-delete post");
+                    AppError::internal("failed to delete post")
+                })?
+            } else {
+                None
+            }
+        }
+    };
+
+    match deletion_queue {
+        Some(deletion_queue) => {
+            if !deletion_queue.objects.is_empty() {
+                let job = crate::jobs::deletion_sweeper::DeletionJob {
+                    keys: deletion_queue.objects,
+                };
+                if let Err(err) = state.queue.enqueue_deletion_job(&job).await {
+                    tracing::warn!(error = ?err, post_id = %id, "failed to enqueue orphaned media for deletion");
+                }
+            }
+            if let Err(err) = state.search_store.remove_post(id).await {
+                tracing::warn!(error = ?err, post_id = %id, "failed to remove post from search index");
+            }
+            Ok(StatusCode::NO_CONTENT)
+        }
+        None => Err(AppError::not_found("post not found")),
+    }
+}
+
+/// The edit/delete audit trail for a post: see `PostService::list_history`.
+/// Restricted to the post's owner or a moderator.
+pub async fn post_history(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<crate::domain::post::PostHistoryEntry>>, AppError> {
+    let service = PostService::new(state.db.clone());
+    let history = service.list_history(id, auth.user_id).await.map_err(|err| {
+        if err.to_string().contains("insufficient role") {
+            return AppError::forbidden("only the post's owner or a moderator may view its history");
+        }
+        tracing::error!(error = ?err, post_id = %id, "failed to fetch post history");
+        AppError::internal("failed to fetch post history")
+    })?;
+
+    Ok(Json(history))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/posts/{id}/like",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 200, description = "Liked (or was already liked)", body = LikeResponse),
+    ),
+    tag = "engagement",
+    security(("bearer_token" = [])),
+)]
+pub async fn like_post(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<LikeResponse>, AppError> {
+    let service = EngagementService::new(state.db.clone());
+    let like = service
+        .like_post(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to like post");
+            AppError::internal("failed to like post")
+        })?;
+
+    if like.is_some() {
+        let post_service = PostService::new(state.db.clone());
+        let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+        match post_service.get_owner_id(id).await {
+            Ok(Some(recipient_id)) => {
+                if let Err(err) = notification_service
+                    .notify_like(recipient_id, auth.user_id, id)
+                    .await
+                {
+                    tracing::warn!(error = ?err, post_id = %id, "failed to create like notification");
+                }
+
+                let federation = crate::app::federation::FederationService::new(
+                    state.db.clone(),
+                    state.federation_base_url.clone(),
+                );
+                let activity = federation.build_like_activity(auth.user_id, id, recipient_id);
+                let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+                if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+                    tracing::warn!(error = ?err, post_id = %id, "failed to enqueue Like activity");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, post_id = %id, "failed to look up liked post owner for notification")
+            }
+        }
+    }
+
+    Ok(Json(LikeResponse {
+        created: like.is_some(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/posts/{id}/like",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 204, description = "Like removed"),
+        (status = 404, description = "Post was not liked by the caller"),
+    ),
+    tag = "engagement",
+    security(("bearer_token" = [])),
+)]
+pub async fn unlike_post(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = EngagementService::new(state.db.clone());
+    let deleted = service
+        .unlike_post(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to unlike post");
+            AppError::internal("failed to unlike post")
+        })?;
+
+    if deleted {
+        let post_service = PostService::new(state.db.clone());
+        if let Ok(Some(recipient_id)) = post_service.get_owner_id(id).await {
+            let federation = crate::app::federation::FederationService::new(
+                state.db.clone(),
+                state.federation_base_url.clone(),
+            );
+            let activity = federation.build_undo_like_activity(auth.user_id, id, recipient_id);
+            let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+            if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+                tracing::warn!(error = ?err, post_id = %id, "failed to enqueue Undo{{Like}} activity");
+            }
+        }
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("like not found"))
+    }
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ReactionListQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    /// When set, only reactions of this kind are returned.
+    pub kind: Option<String>,
+}
+
+pub async fn list_post_likes(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Query(query): Query<ReactionListQuery>,
+) -> Result<Json<ListResponse<crate::domain::engagement::Like>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = EngagementService::new(state.db.clone());
+    let mut likes = service
+        .list_likes(id, query.kind.as_deref(), cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, post_id = %id, "failed to list likes");
+            AppError::internal("failed to list likes")
+        })?;
+
+    let next_cursor = if likes.len() > limit as usize {
+        let last = likes.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: likes,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn list_post_reaction_counts(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<crate::domain::engagement::ReactionCount>>, AppError> {
+    let service = EngagementService::new(state.db.clone());
+    let counts = service.reaction_counts(id).await.map_err(|err| {
+        tracing::error!(error = ?err, post_id = %id, "failed to compute reaction counts");
+        AppError::internal("failed to compute reaction counts")
+    })?;
+
+    Ok(Json(counts))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ReactRequest {
+    pub kind: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/posts/{id}/react",
+    params(("id" = Uuid, Path, description = "Post id")),
+    request_body = ReactRequest,
+    responses(
+        (status = 200, description = "Reacted (or reaction swapped/already recorded)", body = LikeResponse),
+        (status = 400, description = "Reaction kind is not in this instance's configured palette"),
+    ),
+    tag = "engagement",
+    security(("bearer_token" = [])),
+)]
+pub async fn react_post(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<ReactRequest>,
+) -> Result<Json<LikeResponse>, AppError> {
+    if !state.allowed_reaction_kinds.iter().any(|kind| kind == &payload.kind) {
+        return Err(AppError::bad_request("unsupported reaction kind"));
+    }
+
+    let service = EngagementService::new(state.db.clone());
+    let like = service
+        .react_post(auth.user_id, id, &payload.kind)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to react to post");
+            AppError::internal("failed to react to post")
+        })?;
+
+    if like.is_some() {
+        let post_service = PostService::new(state.db.clone());
+        let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+        match post_service.get_owner_id(id).await {
+            Ok(Some(recipient_id)) => {
+                if let Err(err) = notification_service
+                    .notify_reaction(recipient_id, auth.user_id, id, &payload.kind)
+                    .await
+                {
+                    tracing::warn!(error = ?err, post_id = %id, "failed to create reaction notification");
+                }
+
+                // Federation only speaks the ActivityPub `Like` verb, so only
+                // the `like`-kind reaction is relayed outward -- other
+                // reaction kinds stay local to this instance.
+                if payload.kind == "like" {
+                    let federation = crate::app::federation::FederationService::new(
+                        state.db.clone(),
+                        state.federation_base_url.clone(),
+                    );
+                    let activity = federation.build_like_activity(auth.user_id, id, recipient_id);
+                    let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+                    if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+                        tracing::warn!(error = ?err, post_id = %id, "failed to enqueue Like activity");
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, post_id = %id, "failed to look up reacted post owner for notification")
+            }
+        }
+    }
+
+    Ok(Json(LikeResponse {
+        created: like.is_some(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/posts/{id}/react",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 204, description = "Reaction removed"),
+        (status = 404, description = "Post had no reaction from the caller"),
+    ),
+    tag = "engagement",
+    security(("bearer_token" = [])),
+)]
+pub async fn unreact_post(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = EngagementService::new(state.db.clone());
+    let removed_kind = service
+        .unreact_post(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to remove reaction");
+            AppError::internal("failed to remove reaction")
+        })?;
+
+    match removed_kind {
+        Some(kind) => {
+            if kind == "like" {
+                let post_service = PostService::new(state.db.clone());
+                if let Ok(Some(recipient_id)) = post_service.get_owner_id(id).await {
+                    let federation = crate::app::federation::FederationService::new(
+                        state.db.clone(),
+                        state.federation_base_url.clone(),
+                    );
+                    let activity = federation.build_undo_like_activity(auth.user_id, id, recipient_id);
+                    let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+                    if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+                        tracing::warn!(error = ?err, post_id = %id, "failed to enqueue Undo{{Like}} activity");
+                    }
+                }
+            }
+            Ok(StatusCode::NO_CONTENT)
+        }
+        None => Err(AppError::not_found("reaction not found")),
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct LikeResponse {
+    pub created: bool,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CommentRequest {
+    pub body: String,
+    /// Comment this one replies to, for threaded replies. Must belong to
+    /// the same post.
+    #[serde(default)]
+    pub parent_comment_id: Option<Uuid>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/posts/{id}/comment",
+    params(("id" = String, Path, description = "Post id, as a short id or raw UUID")),
+    request_body = CommentRequest,
+    responses(
+        (status = 200, description = "Comment created", body = crate::domain::engagement::Comment),
+        (status = 400, description = "Comment body is empty or exceeds 1000 characters"),
+    ),
+    tag = "engagement",
+    security(("bearer_token" = [])),
+)]
+pub async fn comment_post(
+    ShortUuid(id): ShortUuid,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CommentRequest>,
+) -> Result<Json<crate::domain::engagement::Comment>, AppError> {
+    const MAX_COMMENT_LEN: usize = 1000;
+
+    if payload.body.trim().is_empty() {
+        return Err(AppError::bad_request("comment body cannot be empty"));
+    }
+    if payload.body.chars().count() > MAX_COMMENT_LEN {
+        return Err(AppError::bad_request("comment body exceeds 1000 characters"));
+    }
+
+    let body = state
+        .content_filter
+        .enforce("comment", &payload.body, state.comment_filter_mode)
+        .map_err(|err| AppError::bad_request(err.to_string()))?;
+
+    let service = EngagementService::new(state.db.clone());
+    let comment = service
+        .comment_post(auth.user_id, id, body, payload.parent_comment_id, state.max_comment_depth)
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("parent comment does not exist")
+                || message.contains("parent comment belongs to a different post")
+                || message.contains("comment nesting exceeds max depth")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to comment");
+            AppError::internal("failed to comment")
+        })?;
+
+    let post_service = PostService::new(state.db.clone());
+    if let Ok(Some(recipient_id)) = post_service.get_owner_id(id).await {
+        let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+        if let Err(err) = notification_service
+            .notify_comment(recipient_id, auth.user_id, id, comment.id)
+            .await
+        {
+            tracing::warn!(error = ?err, post_id = %id, "failed to create comment notification");
+        }
+
+        let federation = crate::app::federation::FederationService::new(
+            state.db.clone(),
+            state.federation_base_url.clone(),
+        );
+        let activity = federation.build_comment_activity(auth.user_id, id, recipient_id, &comment);
+        let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+        if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+            tracing::warn!(error = ?err, post_id = %id, "failed to enqueue comment Create activity");
+        }
+    }
+
+    Ok(Json(comment))
+}
+
+pub async fn list_post_comments(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Query(query): Query<CommentListQuery>,
+) -> Result<Json<ListResponse<crate::domain::engagement::Comment>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = EngagementService::new(state.db.clone());
+    let mut comments = service
+        .list_comments(id, query.top_level_only, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, post_id = %id, "failed to list comments");
+            AppError::internal("failed to list comments")
+        })?;
+
+    let next_cursor = if comments.len() > limit as usize {
+        let last = comments.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: comments,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn list_comment_replies(
+    Path((_post_id_raw, comment_id_raw)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<crate::domain::engagement::Comment>>, AppError> {
+    let comment_id = crate::app::shortid::decode_either(&comment_id_raw, &state.shortid_salt)
+        .ok_or_else(|| AppError::bad_request("invalid comment id"))?;
+
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = EngagementService::new(state.db.clone());
+    let mut replies = service
+        .list_comment_replies(comment_id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, comment_id = %comment_id, "failed to list comment replies");
+            AppError::internal("failed to list comment replies")
+        })?;
+
+    let next_cursor = if replies.len() > limit as usize {
+        let last = replies.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: replies,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn delete_comment(
+    Path((post_id_raw, comment_id_raw)): Path<(String, String)>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let post_id = crate::app::shortid::decode_either(&post_id_raw, &state.shortid_salt)
+        .ok_or_else(|| AppError::bad_request("invalid post id"))?;
+    let comment_id = crate::app::shortid::decode_either(&comment_id_raw, &state.shortid_salt)
+        .ok_or_else(|| AppError::bad_request("invalid comment id"))?;
+
+    let service = EngagementService::new(state.db.clone());
+    let deleted = service
+        .delete_comment(comment_id, post_id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, comment_id = %comment_id, user_id = %auth.user_id, "failed to delete comment");
+            AppError::internal("failed to delete comment")
+        })?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("comment not found"))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/feed",
+    params(PaginationQuery),
+    responses(
+        (status = 200, description = "Page of the caller's home timeline, newest first", body = PostListResponse),
+        (status = 400, description = "limit outside 1..=200"),
+    ),
+    tag = "feed",
+    security(("bearer_token" = [])),
+)]
+pub async fn home_feed(
+    auth: RequireScope<ReadFeed>,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<crate::domain::post::Post>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = FeedService::new(state.db.clone(), state.cache.clone());
+    let (posts, next_cursor) = service
+        .get_home_feed(auth.user_id, cursor, limit)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch home feed");
+            AppError::internal("failed to fetch home feed")
+        })?;
+
+    Ok(Json(ListResponse {
+        items: posts,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn refresh_feed(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = FeedService::new(state.db.clone(), state.cache.clone());
+    service
+        .refresh_home_feed(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to refresh feed");
+            AppError::internal("failed to refresh feed")
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Live-tails the caller's home feed: subscribes to their personal timeline
+/// channel plus the public firehose and forwards each `Event` published by
+/// `StreamingService::publish_post_created` as an SSE message, so clients
+/// can get new posts without re-polling `GET /feed`.
+pub async fn stream_feed(
+    auth: RequireScope<ReadFeed>,
+    State(state): State<AppState>,
+) -> Result<Sse<impl futures::Stream<Item = Result<SseEvent, std::convert::Infallible>>>, AppError> {
+    let conn = state
+        .cache
+        .client()
+        .get_async_connection()
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to open Redis connection for feed stream");
+            AppError::internal("failed to start feed stream")
+        })?;
+
+    let mut pubsub = conn.into_pubsub();
+    for channel in [crate::app::streaming::user_channel(auth.user_id), crate::app::streaming::PUBLIC_CHANNEL.to_string()] {
+        pubsub.subscribe(channel).await.map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to subscribe to feed stream channel");
+            AppError::internal("failed to start feed stream")
+        })?;
+    }
+
+    let stream = pubsub.into_on_message().filter_map(|msg| async move {
+        let payload: String = msg.get_payload().ok()?;
+        Some(Ok(SseEvent::default().data(payload)))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+pub async fn stream_stories_feed(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Sse<impl futures::Stream<Item = Result<SseEvent, std::convert::Infallible>>>, AppError> {
+    let streaming = StreamingService::new(state.db.clone(), state.cache.clone());
+    let mut channels = streaming.followee_story_channels(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to resolve followee story channels");
+        AppError::internal("failed to start stories stream")
+    })?;
+    channels.push(crate::app::streaming::story_channel(auth.user_id));
+
+    let conn = state
+        .cache
+        .client()
+        .get_async_connection()
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to open Redis connection for stories stream");
+            AppError::internal("failed to start stories stream")
+        })?;
+
+    let mut pubsub = conn.into_pubsub();
+    for channel in channels {
+        pubsub.subscribe(channel).await.map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to subscribe to stories stream channel");
+            AppError::internal("failed to start stories stream")
+        })?;
+    }
+
+    let stream = pubsub.into_on_message().filter_map(|msg| async move {
+        let payload: String = msg.get_payload().ok()?;
+        Some(Ok(SseEvent::default().data(payload)))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[derive(Deserialize)]
+pub struct UploadRequest {
+    pub content_type: String,
+    pub bytes: i64,
+}
+
+pub async fn create_upload(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<UploadRequest>,
+) -> Result<Json<UploadIntent>, AppError> {
+    if payload.bytes <= 0 {
+        return Err(AppError::bad_request("bytes must be greater than 0"));
+    }
+    if payload.bytes > state.upload_max_bytes {
+        return Err(AppError::bad_request("upload exceeds max size"));
+    }
+
+    let service = crate::app::media::MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    let intent = service
+        .create_upload(
+            auth.user_id,
+            payload.content_type,
+            payload.bytes,
+            state.upload_url_ttl_seconds,
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create upload");
+            AppError::bad_request("invalid upload request")
+        })?;
+
+    Ok(Json(intent))
+}
+
+#[derive(Deserialize)]
+pub struct MultipartUploadRequest {
+    pub content_type: String,
+    pub bytes: i64,
+    pub part_count: i32,
+}
+
+pub async fn create_multipart_upload(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<MultipartUploadRequest>,
+) -> Result<Json<crate::app::media::MultipartUploadIntent>, AppError> {
+    if payload.bytes <= 0 {
+        return Err(AppError::bad_request("bytes must be greater than 0"));
+    }
+    if payload.bytes > state.upload_max_bytes {
+        return Err(AppError::bad_request("upload exceeds max size"));
+    }
+    if payload.part_count < 1 {
+        return Err(AppError::bad_request("part_count must be at least 1"));
+    }
+
+    let service = crate::app::media::MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    let intent = service
+        .create_multipart_upload(
+            auth.user_id,
+            payload.content_type,
+            payload.bytes,
+            payload.part_count,
+            state.upload_url_ttl_seconds,
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create multipart upload");
+            AppError::bad_request("invalid upload request")
+        })?;
+
+    Ok(Json(intent))
+}
+
+#[derive(Deserialize)]
+pub struct CompleteMultipartUploadRequest {
+    pub parts: Vec<crate::app::media::CompletedPart>,
+}
+
+pub async fn complete_multipart_upload(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<CompleteMultipartUploadRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::media::MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    let queued = service
+        .complete_multipart_upload(id, auth.user_id, payload.parts)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, upload_id = %id, user_id = %auth.user_id, "failed to complete multipart upload");
+            AppError::internal("failed to complete multipart upload")
+        })?;
+
+    if queued {
+        Ok(StatusCode::ACCEPTED)
+    } else {
+        Err(AppError::not_found("upload not found"))
+    }
+}
+
+pub async fn abort_multipart_upload(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::media::MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    let aborted = service
+        .abort_multipart_upload(id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, upload_id = %id, user_id = %auth.user_id, "failed to abort multipart upload");
+            AppError::internal("failed to abort multipart upload")
+        })?;
+
+    if aborted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("upload not found"))
+    }
+}
+
+/// Alternative to the `create_upload` + PUT-to-presigned-URL + `complete_upload`
+/// flow for clients that can't (or don't want to) do a presigned PUT -- CLIs,
+/// server-to-server callers, anything that already has the whole file in
+/// memory. Takes a `multipart/form-data` body with a single `file` field and
+/// uploads it to storage directly instead of handing back a presigned URL.
+///
+/// The declared `Content-Length` isn't trusted: bytes are counted as they
+/// stream in and the upload is rejected with 413 the moment the running
+/// total exceeds `upload_max_bytes`, the same limit `create_upload` enforces.
+pub async fn create_upload_direct(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<DirectUploadResult>, AppError> {
+    let mut content_type = None;
+    let mut data: Vec<u8> = Vec::new();
+
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| AppError::bad_request("invalid multipart body"))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        content_type = field.content_type().map(|ct| ct.to_string());
+
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|_| AppError::bad_request("invalid multipart body"))?
+        {
+            if data.len() as i64 + chunk.len() as i64 > state.upload_max_bytes {
+                return Err(AppError::payload_too_large("upload exceeds max size"));
+            }
+            data.extend_from_slice(&chunk);
+        }
+    }
+
+    if data.is_empty() {
+        return Err(AppError::bad_request("file field is required"));
+    }
+    let content_type = content_type.ok_or_else(|| AppError::bad_request("file field is required"))?;
+
+    let service = MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    let result = service
+        .create_upload_direct(auth.user_id, content_type, data)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create direct upload");
+            AppError::bad_request("invalid upload request")
+        })?;
+
+    match result {
+        Some(result) => Ok(Json(result)),
+        None => Err(AppError::bad_request("content type mismatch")),
+    }
+}
+
+/// Response body for a successfully queued `complete_upload`. `derivative_mode`
+/// tells the client what to expect: `lazy` means it must request a derivative
+/// before one exists, `eager` means the ladder is being pre-rendered as part
+/// of processing (so it's worth a client polling `/media/:id/derivative`
+/// once the upload's status turns `completed`), and `realtime` means
+/// derivatives are never persisted at all. `available_derivatives` is always
+/// empty at this point -- processing is asynchronous, so nothing can exist
+/// yet -- but is included so eager-mode clients don't need a second request
+/// just to learn the mode.
+#[derive(Serialize)]
+pub struct CompleteUploadResponse {
+    pub derivative_mode: &'static str,
+    pub available_derivatives: Vec<String>,
+}
+
+pub async fn complete_upload(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<CompleteUploadResponse>), AppError> {
+    let service = crate::app::media::MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    let outcome = service
+        .complete_upload(id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, upload_id = %id, user_id = %auth.user_id, "failed to complete upload");
+            AppError::internal("failed to complete upload")
+        })?;
+
+    let derivative_mode = match state.media_derivative_mode {
+        crate::config::DerivativeMode::Lazy => "lazy",
+        crate::config::DerivativeMode::Eager => "eager",
+        crate::config::DerivativeMode::Realtime => "realtime",
+    };
+
+    match outcome {
+        crate::app::media::CompleteUploadOutcome::Queued => Ok((
+            StatusCode::ACCEPTED,
+            Json(CompleteUploadResponse {
+                derivative_mode,
+                available_derivatives: Vec::new(),
+            }),
+        )),
+        crate::app::media::CompleteUploadOutcome::NotFound => {
+            Err(AppError::not_found("upload not found"))
+        }
+        crate::app::media::CompleteUploadOutcome::ContentTypeMismatch => {
+            Err(AppError::bad_request("content type mismatch"))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/{id}",
+    params(("id" = String, Path, description = "Media id, as a short id or raw UUID")),
+    responses(
+        (status = 200, description = "Media record, with presigned URLs for its variants", body = crate::domain::media::Media),
+        (status = 404, description = "Media not found, or not visible to the caller"),
+    ),
+    tag = "media",
+    security(("bearer_token" = [])),
+)]
+pub async fn get_media(
+    auth: AuthUser,
+    ShortUuid(id): ShortUuid,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::media::Media>, AppError> {
+    let service = MediaService::new(state.db.clone(), state.cache.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone(), state.variant_guard.clone(), state.media_derivative_mode, state.media_concurrency_limiter.clone());
+    let media = service.get_media_for_user(id, auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, media_id = %id, "failed to fetch media");
+        AppError::internal("failed to fetch media")
+    })?;
+
+    match media {
+        Some(media) => Ok(Json(media)),
+        None => Err(AppError::not_found("media not found")),
+    }
+}
+
+#[derive(Serialize)]
+pub struct MediaVariantResponse {
+    pub url: String,
+}
+
+/// Generates (or reuses an already-generated) on-demand image variant --
+/// resize/crop/thumbnail chain plus output format -- and returns a presigned
+/// URL for it. See `MediaService::get_media_variant` for the caching and
+/// single-flight behavior.
+pub async fn get_media_variant(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(spec): Json<ProcessSpec>,
+) -> Result<Json<MediaVariantResponse>, AppError> {
+    let service = MediaService::new(state.db.clone(), state.cache.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone(), state.variant_guard.clone(), state.media_derivative_mode, state.media_concurrency_limiter.clone());
+    let url = service
+        .get_media_variant(id, auth.user_id, &spec)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, media_id = %id, "failed to generate media variant");
+            AppError::from_media_busy(&err)
+                .unwrap_or_else(|| AppError::internal("failed to generate media variant"))
+        })?;
+
+    match url {
+        Some(url) => Ok(Json(MediaVariantResponse { url })),
+        None => Err(AppError::not_found("media not found")),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MediaDerivativeQuery {
+    pub size: u32,
+    pub format: Option<String>,
+}
+
+/// Thin, size-ladder-restricted convenience wrapper over
+/// `get_media_variant`'s free-form `ProcessSpec`: `GET
+/// /media/:id/derivative?size=320&format=webp` resizes (preserving aspect
+/// ratio, via `VariantOp::Thumbnail`) to one of a fixed set of sizes instead
+/// of requiring the caller to construct a full processing spec.
+///
+/// Behavior depends on `AppState::media_derivative_mode`: `Lazy`/`Eager` both
+/// serve a presigned URL to a cached variant (the only difference between
+/// them is *when* that variant was generated -- see
+/// `MediaService::prerender_derivatives`); `Realtime` never persists
+/// anything and streams the resized bytes directly in the response body.
+pub async fn get_media_derivative(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<MediaDerivativeQuery>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    if !DERIVATIVE_SIZES.contains(&query.size) {
+        return Err(AppError::bad_request(
+            "size must be one of 80, 160, 320, 640, 1080, 2160",
+        ));
+    }
+
+    let format = match query.format.as_deref() {
+        Some("png") => VariantFormat::Png,
+        Some("webp") => VariantFormat::Webp,
+        Some("jpeg") | Some("jpg") | None => VariantFormat::Jpeg,
+        Some(_) => return Err(AppError::bad_request("unsupported derivative format")),
+    };
+
+    let spec = ProcessSpec {
+        ops: vec![VariantOp::Thumbnail {
+            width: query.size,
+            height: query.size,
+        }],
+        format,
+    };
+
+    let service = MediaService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.storage.clone(),
+        state.queue.clone(),
+        state.s3_public_endpoint.clone(),
+        state.variant_guard.clone(),
+        state.media_derivative_mode,
+        state.media_concurrency_limiter.clone(),
+    );
+
+    if matches!(state.media_derivative_mode, crate::config::DerivativeMode::Realtime) {
+        let rendered = service
+            .render_variant_bytes(id, auth.user_id, &spec)
+            .await
+            .map_err(|err| {
+                tracing::error!(error = ?err, media_id = %id, "failed to render media derivative");
+                AppError::from_media_busy(&err)
+                    .unwrap_or_else(|| AppError::internal("failed to render media derivative"))
+            })?;
+
+        return match rendered {
+            Some((bytes, content_type)) => {
+                Ok((StatusCode::OK, [(header::CONTENT_TYPE, content_type)], bytes).into_response())
+            }
+            None => Err(AppError::not_found("media not found")),
+        };
+    }
+
+    let url = service
+        .get_media_variant(id, auth.user_id, &spec)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, media_id = %id, "failed to generate media derivative");
+            AppError::from_media_busy(&err)
+                .unwrap_or_else(|| AppError::internal("failed to generate media derivative"))
+        })?;
+
+    match url {
+        Some(url) => Ok(Json(MediaVariantResponse { url }).into_response()),
+        None => Err(AppError::not_found("media not found")),
+    }
+}
+
+pub async fn get_upload_status(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<UploadStatus>, AppError> {
+    let service = MediaService::new(state.db.clone(), state.cache.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone(), state.variant_guard.clone(), state.media_derivative_mode, state.media_concurrency_limiter.clone());
+    let status = service
+        .get_upload_status(id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, upload_id = %id, user_id = %auth.user_id, "failed to fetch upload status");
+            AppError::internal("failed to fetch upload status")
+        })?;
+
+    match status {
+        Some(status) => Ok(Json(status)),
+        None => Err(AppError::not_found("upload not found")),
+    }
+}
+
+pub async fn delete_media(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = MediaService::new(state.db.clone(), state.cache.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone(), state.variant_guard.clone(), state.media_derivative_mode, state.media_concurrency_limiter.clone());
+    let deleted = service
+        .delete_media(id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, media_id = %id, user_id = %auth.user_id, "failed to delete media");
+            AppError::internal("failed to delete media")
+        })?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("media not found"))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/notifications",
+    params(PaginationQuery),
+    responses(
+        (status = 200, description = "Page of the caller's notifications, newest first", body = NotificationListResponse),
+        (status = 400, description = "limit outside 1..=200"),
+    ),
+    tag = "notifications",
+    security(("bearer_token" = [])),
+)]
+pub async fn list_notifications(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<crate::domain::notification::Notification>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+    let mut notifications = service
+        .list(auth.user_id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to list notifications");
+            AppError::internal("failed to list notifications")
+        })?;
+
+    let next_cursor = if notifications.len() > limit as usize {
+        let last = notifications.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: notifications,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn mark_notification_read(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+    let updated = service
+        .mark_read(id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, notification_id = %id, user_id = %auth.user_id, "failed to mark notification read");
+            AppError::internal("failed to mark notification read")
+        })?;
+
+    if updated {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("notification not found"))
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MarkAllReadResponse {
+    /// How many notifications were newly marked read.
+    pub updated: u64,
+}
+
+pub async fn mark_all_notifications_read(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<MarkAllReadResponse>, AppError> {
+    let service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+    let updated = service.mark_all_read(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to mark all notifications read");
+        AppError::internal("failed to mark all notifications read")
+    })?;
+
+    Ok(Json(MarkAllReadResponse { updated }))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct UnreadCountResponse {
+    pub count: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/notifications/unread-count",
+    responses(
+        (status = 200, description = "Number of unread notifications for the caller", body = UnreadCountResponse),
+    ),
+    tag = "notifications",
+    security(("bearer_token" = [])),
+)]
+pub async fn unread_notification_count(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<UnreadCountResponse>, AppError> {
+    let service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+    let count = service.unread_count(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to count unread notifications");
+        AppError::internal("failed to count unread notifications")
+    })?;
+
+    Ok(Json(UnreadCountResponse { count }))
+}
+
+/// Live-tails the caller's notifications: subscribes to their personal
+/// channel and forwards each `Event::NotificationCreated` published by
+/// `NotificationService::create` as an SSE message, using the
+/// notification's own id as the SSE event id. On reconnect, a client
+/// sends back the last id it saw via `Last-Event-ID`; this backfills
+/// anything created since then (`NotificationService::list_since`) before
+/// switching over to the live Redis subscription, so nothing published
+/// while the connection was down is missed.
+pub async fn stream_notifications(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Sse<impl futures::Stream<Item = Result<SseEvent, std::convert::Infallible>>>, AppError> {
+    let service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+
+    let mut backfill = Vec::new();
+    if let Some(after_id) = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Uuid>().ok())
+    {
+        backfill = service.list_since(auth.user_id, after_id).await.map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to backfill notification stream");
+            AppError::internal("failed to start notification stream")
+        })?;
+    }
+
+    let conn = state
+        .cache
+        .client()
+        .get_async_connection()
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to open Redis connection for notification stream");
+            AppError::internal("failed to start notification stream")
+        })?;
+
+    let mut pubsub = conn.into_pubsub();
+    pubsub
+        .subscribe(crate::app::streaming::notification_channel(auth.user_id))
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to subscribe to notification stream channel");
+            AppError::internal("failed to start notification stream")
+        })?;
+
+    let backfill_stream = futures::stream::iter(backfill.into_iter().filter_map(|notification| {
+        let id = notification.id;
+        let event = crate::domain::event::Event::NotificationCreated { notification };
+        let payload = serde_json::to_string(&event).ok()?;
+        Some(Ok(SseEvent::default().id(id.to_string()).data(payload)))
+    }));
+
+    let live_stream = pubsub.into_on_message().filter_map(|msg| async move {
+        let payload: String = msg.get_payload().ok()?;
+        let event: crate::domain::event::Event = serde_json::from_str(&payload).ok()?;
+        let id = match &event {
+            crate::domain::event::Event::NotificationCreated { notification } => notification.id,
+            _ => return None,
+        };
+        Some(Ok(SseEvent::default().id(id.to_string()).data(payload)))
+    });
+
+    Ok(Sse::new(backfill_stream.chain(live_stream)).keep_alive(KeepAlive::default()))
+}
+
+/// Register a browser's Web Push subscription (as produced by
+/// `PushManager.subscribe()`) so future notifications can be dispatched to
+/// it. Upserts on `endpoint`, so re-subscribing just refreshes the keys.
+pub async fn subscribe_push(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::push::SubscribePushRequest>,
+) -> Result<StatusCode, AppError> {
+    state
+        .push
+        .subscribe(auth.user_id, &payload.endpoint, &payload.p256dh, &payload.auth)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to subscribe to push notifications");
+            AppError::internal("failed to subscribe to push notifications")
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn unsubscribe_push(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::push::UnsubscribePushRequest>,
+) -> Result<StatusCode, AppError> {
+    state
+        .push
+        .unsubscribe(auth.user_id, &payload.endpoint)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to unsubscribe from push notifications");
+            AppError::internal("failed to unsubscribe from push notifications")
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ModerationRequest {
+    pub reason: Option<String>,
+}
+
+pub async fn flag_user(
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<ModerationRequest>,
+) -> Result<Json<crate::domain::moderation::UserFlag>, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let flag = service
+        .flag_user(auth.user_id, id, payload.reason)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, reporter_id = %auth.user_id, target_id = %id, "failed to flag user");
+            AppError::internal("failed to flag user")
+        })?;
+
+    Ok(Json(flag))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/moderation/posts/{id}/takedown",
+    params(("id" = String, Path, description = "Post id, as a short id or raw UUID")),
+    request_body = ModerationRequest,
+    responses(
+        (status = 204, description = "Post removed"),
+        (status = 404, description = "Post not found"),
+    ),
+    tag = "moderation",
+    security(("bearer_token" = [])),
+)]
+pub async fn takedown_post(
+    moderator: ModeratorUser,
+    ShortUuid(id): ShortUuid,
+    State(state): State<AppState>,
+    Json(payload): Json<ModerationRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let removed = service
+        .takedown_post(moderator.user_id, id, payload.reason)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %moderator.user_id, post_id = %id, "failed to takedown post");
+            AppError::internal("failed to takedown post")
+        })?;
+
+    if removed {
+        if let Err(err) = state.search_store.remove_post(id).await {
+            tracing::warn!(error = ?err, post_id = %id, "failed to remove taken-down post from search index");
+        }
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("post not found"))
+    }
+}
+
+pub async fn takedown_comment(
+    moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<ModerationRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let removed = service
+        .takedown_comment(moderator.user_id, id, payload.reason)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %moderator.user_id, comment_id = %id, "failed to takedown comment");
+            AppError::internal("failed to takedown comment")
+        })?;
+
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("comment not found"))
+    }
+}
+
+/// Suspends a user account: see `ModerationService::suspend_user` for what
+/// that hides.
+pub async fn suspend_user(
+    moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<ModerationRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let suspended = service
+        .suspend_user(moderator.user_id, id, payload.reason)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %moderator.user_id, target_id = %id, "failed to suspend user");
+            AppError::internal("failed to suspend user")
+        })?;
+
+    if suspended {
+        if let Err(err) = state.search_store.remove_user(id).await {
+            tracing::warn!(error = ?err, target_id = %id, "failed to remove suspended user from search index");
+        }
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("user not found or already suspended"))
+    }
+}
+
+pub async fn unsuspend_user(
+    auth: AuthUser,
+    _admin: AdminToken,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let restored = service
+        .unsuspend_user(auth.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %auth.user_id, target_id = %id, "failed to unsuspend user");
+            AppError::internal("failed to unsuspend user")
+        })?;
+
+    if restored {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("user not found or not suspended"))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BanRequest {
+    pub reason: Option<String>,
+    pub scope: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<time::OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct UnbanRequest {
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SetRoleRequest {
+    pub role: crate::domain::moderation::UserRole,
+}
+
+/// Bans a user, globally or for one capability. See
+/// `ModerationService::ban_user` for the moderator/admin split.
+pub async fn ban_user(
+    auth: RequireScope<AdminModeration>,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<BanRequest>,
+) -> Result<Json<crate::domain::moderation::UserBan>, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let ban = service
+        .ban_user(auth.user_id, id, payload.reason, payload.scope, payload.expires_at)
+        .await
+        .map_err(|err| {
+            if err.to_string().contains("insufficient role") {
+                return AppError::forbidden("only admins may issue a global ban");
+            }
+            tracing::error!(error = ?err, actor_id = %auth.user_id, target_id = %id, "failed to ban user");
+            AppError::internal("failed to ban user")
+        })?;
+
+    Ok(Json(ban))
+}
+
+pub async fn unban_user(
+    auth: RequireScope<AdminModeration>,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<UnbanRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let lifted = service
+        .unban_user(auth.user_id, id, payload.scope)
+        .await
+        .map_err(|err| {
+            if err.to_string().contains("insufficient role") {
+                return AppError::forbidden("only admins may lift a global ban");
+            }
+            tracing::error!(error = ?err, actor_id = %auth.user_id, target_id = %id, "failed to unban user");
+            AppError::internal("failed to unban user")
+        })?;
+
+    if lifted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("user not found or not banned"))
+    }
+}
+
+/// Coalesces every active ban against a user into one answer. Requires the
+/// shared `AdminToken`, so a banned user can't call it to check their own
+/// status.
+pub async fn user_ban_status(
+    _admin: AdminToken,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::moderation::EffectiveBanStatus>, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let status = service.effective_status(id).await.map_err(|err| {
+        tracing::error!(error = ?err, target_id = %id, "failed to load ban status");
+        AppError::internal("failed to load ban status")
+    })?;
+
+    Ok(Json(status))
+}
+
+/// Promotes or demotes a user's role. Admin-only: see
+/// `ModerationService::set_role`.
+pub async fn set_user_role(
+    auth: RequireScope<AdminModeration>,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<SetRoleRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let updated = service
+        .set_role(auth.user_id, id, payload.role)
+        .await
+        .map_err(|err| {
+            if err.to_string().contains("insufficient role") {
+                return AppError::forbidden("only admins may change a user's role");
+            }
+            tracing::error!(error = ?err, actor_id = %auth.user_id, target_id = %id, "failed to set user role");
+            AppError::internal("failed to set user role")
+        })?;
+
+    if updated {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("user not found"))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetTrustLevelRequest {
+    pub level: crate::config::rate_limits::TrustLevel,
+}
+
+/// Manually promotes (or demotes) a user's trust level, bypassing the
+/// `posts_count`/`followers_count`/etc. thresholds
+/// `TrustService::recalculate_trust_level` would otherwise require -- e.g.
+/// fast-tracking a known-good user to `Verified` without waiting for them
+/// to rack up activity. See `TrustService::set_trust_level`.
+pub async fn set_user_trust_level(
+    moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<SetTrustLevelRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::trust::TrustService::new(state.db.clone());
+    let updated = service
+        .set_trust_level(id, payload.level)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %moderator.user_id, target_id = %id, "failed to set trust level");
+            AppError::internal("failed to set trust level")
+        })?;
+
+    if updated {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("user not found"))
+    }
+}
+
+/// A user's profile edit history: see `ModerationService::profile_history`.
+pub async fn user_profile_history(
+    _moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<Vec<crate::domain::user::ProfileHistoryEntry>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+
+    let service = ModerationService::new(state.db.clone());
+    let history = service.profile_history(id, limit).await.map_err(|err| {
+        tracing::error!(error = ?err, target_id = %id, "failed to load profile history");
+        AppError::internal("failed to load profile history")
+    })?;
+
+    Ok(Json(history))
+}
+
+/// Moderation-load snapshot for operators: flags received, actions taken,
+/// and the pending review queue depth.
+pub async fn moderation_stats(
+    _auth: AuthUser,
+    _admin: AdminToken,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::moderation::ModerationStats>, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let stats = service.stats().await.map_err(|err| {
+        tracing::error!(error = ?err, "failed to load moderation stats");
+        AppError::internal("failed to load moderation stats")
+    })?;
+
+    Ok(Json(stats))
+}
+
+#[derive(Deserialize)]
+pub struct AdminUserSearchQuery {
+    pub handle_prefix: Option<String>,
+    pub email_contains: Option<String>,
+    pub trust_level_min: Option<i32>,
+    pub trust_level_max: Option<i32>,
+    pub is_verified: Option<bool>,
+    pub min_device_risk_score: Option<i32>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+/// Search users by handle prefix, email substring, trust level range,
+/// verification status, and/or device-fingerprint risk. Criteria are
+/// AND-combined; omitting all of them matches every (non-deleted) user.
+pub async fn admin_search_users(
+    _admin: AdminToken,
+    State(state): State<AppState>,
+    Query(query): Query<AdminUserSearchQuery>,
+) -> Result<Json<ListResponse<crate::domain::admin::AdminUserSummary>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let mut predicates = Vec::new();
+    if let Some(prefix) = query.handle_prefix {
+        predicates.push(crate::app::admin::UserFilter::HandlePrefix(prefix));
+    }
+    if let Some(substring) = query.email_contains {
+        predicates.push(crate::app::admin::UserFilter::EmailContains(substring));
+    }
+    if query.trust_level_min.is_some() || query.trust_level_max.is_some() {
+        predicates.push(crate::app::admin::UserFilter::TrustLevelRange {
+            min: query.trust_level_min.unwrap_or(0),
+            max: query.trust_level_max.unwrap_or(i32::MAX),
+        });
+    }
+    if let Some(verified) = query.is_verified {
+        predicates.push(crate::app::admin::UserFilter::IsVerified(verified));
+    }
+    if let Some(min_risk) = query.min_device_risk_score {
+        predicates.push(crate::app::admin::UserFilter::MinDeviceRiskScore(min_risk));
+    }
+    let filter = crate::app::admin::UserFilter::And(predicates);
+
+    let service = crate::app::admin::AdminUserService::new(state.db.clone());
+    let mut users = service
+        .search(&filter, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to search admin users");
+            AppError::internal("failed to search users")
+        })?;
+
+    let next_cursor = if users.len() > limit as usize {
+        let last = users.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: users,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn list_moderation_audit(
+    _auth: AuthUser,
+    _admin: AdminToken,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<crate::domain::moderation::ModerationAction>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = ModerationService::new(state.db.clone());
+    let mut actions = service
+        .list_audit(cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to list moderation audit");
+            AppError::internal("failed to list moderation audit")
+        })?;
+
+    let next_cursor = if actions.len() > limit as usize {
+        let last = actions.pop().expect("checked len");
+        Some((last.created_at, last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: actions,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+pub async fn restore_post(
+    moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let restored = service
+        .restore_post(moderator.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %moderator.user_id, post_id = %id, "failed to restore post");
+            AppError::internal("failed to restore post")
+        })?;
+
+    if restored {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("post not found"))
+    }
+}
+
+pub async fn restore_comment(
+    moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let restored = service
+        .restore_comment(moderator.user_id, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, actor_id = %moderator.user_id, comment_id = %id, "failed to restore comment");
+            AppError::internal("failed to restore comment")
+        })?;
+
+    if restored {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("comment not found"))
+    }
+}
+
+pub async fn list_pending_flags(
+    _moderator: ModeratorUser,
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ListResponse<crate::domain::moderation::PendingFlagGroup>>, AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service = ModerationService::new(state.db.clone());
+    let mut groups = service
+        .list_pending_flags(cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to list pending flags");
+            AppError::internal("failed to list pending flags")
+        })?;
+
+    let next_cursor = if groups.len() > limit as usize {
+        let last = groups.pop().expect("checked len");
+        Some((last.last_reported_at, last.target_id))
+    } else {
+        None
+    };
+
+    Ok(Json(ListResponse {
+        items: groups,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ResolveFlagRequest {
+    pub outcome: String,
+}
+
+pub async fn resolve_flag(
+    moderator: ModeratorUser,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(payload): Json<ResolveFlagRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = ModerationService::new(state.db.clone());
+    let resolved = service
+        .resolve_flag(moderator.user_id, id, payload.outcome)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, moderator_id = %moderator.user_id, target_id = %id, "failed to resolve flag");
+            AppError::internal("failed to resolve flag")
+        })?;
+
+    if resolved > 0 {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("no pending flags for target"))
+    }
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+/// Decodes a ranked-search cursor of the form `"{rank}/{rfc3339_timestamp}/{uuid}"`.
+/// Distinct from [`parse_cursor`] because ranked search pages on
+/// `(rank, created_at, id)` rather than the `(created_at, id)` pair every
+/// other paginated endpoint uses.
+fn parse_rank_cursor(cursor: Option<String>) -> Result<Option<(f64, OffsetDateTime, Uuid)>, AppError> {
+    let Some(cursor) = cursor else {
+        return Ok(None);
+    };
+
+    let mut parts = cursor.splitn(3, '/');
+    let rank = parts
+        .next()
+        .ok_or_else(|| AppError::bad_request("invalid cursor"))?;
+    let timestamp = parts
+        .next()
+        .ok_or_else(|| AppError::bad_request("invalid cursor"))?;
+    let id = parts
+        .next()
+        .ok_or_else(|| AppError::bad_request("invalid cursor"))?;
+
+    let rank: f64 = rank.parse().map_err(|_| AppError::bad_request("invalid cursor"))?;
+    let timestamp = OffsetDateTime::parse(timestamp, &Rfc3339)
+        .map_err(|_| AppError::bad_request("invalid cursor"))?;
+    let id = Uuid::parse_str(id).map_err(|_| AppError::bad_request("invalid cursor"))?;
+
+    Ok(Some((rank, timestamp, id)))
+}
+
+fn encode_rank_cursor(cursor: Option<(f64, OffsetDateTime, Uuid)>) -> Option<String> {
+    let (rank, timestamp, id) = cursor?;
+    let timestamp = timestamp.format(&Rfc3339).ok()?;
+    Some(format!("{}/{}/{}", rank, timestamp, id))
+}
+
+pub async fn search_users(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ListResponse<crate::domain::user::PublicUser>>, AppError> {
+    let term = query.q.trim();
+    if term.len() < 2 {
+        return Err(AppError::bad_request("q must be at least 2 characters"));
+    }
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_rank_cursor(query.cursor)?;
+
+    let service = SearchService::new(state.search_store.clone());
+    let mut users = service
+        .search_users(term, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to search users");
+            AppError::internal("failed to search users")
+        })?;
+
+    let next_cursor = if users.len() > limit as usize {
+        let last = users.pop().expect("checked len");
+        Some((last.rank, last.user.created_at, last.user.id))
+    } else {
+        None
+    };
+
+    let mut items: Vec<crate::domain::user::PublicUser> = users
+        .into_iter()
+        .map(|ranked| crate::domain::user::PublicUser::from(ranked.user))
+        .collect();
+    SocialService::new(state.db.clone())
+        .add_user_relationships(auth.user_id, &mut items)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to batch user search relationships");
+            AppError::internal("failed to search users")
+        })?;
+
+    Ok(Json(ListResponse {
+        items,
+        next_cursor: encode_rank_cursor(next_cursor),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/search/posts",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Posts ranked by relevance to `q`", body = PostListResponse),
+        (status = 400, description = "q shorter than 2 characters, or limit outside 1..=200"),
+    ),
+    tag = "search",
+    security(("bearer_token" = [])),
+)]
+pub async fn search_posts(
+    _auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ListResponse<crate::domain::post::Post>>, AppError> {
+    let term = query.q.trim();
+    if term.len() < 2 {
+        return Err(AppError::bad_request("q must be at least 2 characters"));
+    }
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let cursor = parse_rank_cursor(query.cursor)?;
+
+    let service = SearchService::new(state.search_store.clone());
+    let mut posts = service
+        .search_posts(term, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to search posts");
+            AppError::internal("failed to search posts")
+        })?;
+
+    let next_cursor = if posts.len() > limit as usize {
+        let last = posts.pop().expect("checked len");
+        Some((last.rank, last.post.created_at, last.post.id))
+    } else {
+        None
+    };
+
+    let items = posts.into_iter().map(|ranked| ranked.post).collect();
+
+    Ok(Json(ListResponse {
+        items,
+        next_cursor: encode_rank_cursor(next_cursor),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchSearchEntity {
+    Users,
+    Posts,
+    Both,
+}
+
+#[derive(Deserialize)]
+pub struct BatchSearchQuery {
+    pub entity: BatchSearchEntity,
+    pub q: String,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BatchSearchRequest {
+    pub queries: Vec<BatchSearchQuery>,
+}
+
+#[derive(Serialize)]
+pub struct BatchSearchResult {
+    pub users: ListResponse<crate::domain::user::PublicUser>,
+    pub posts: ListResponse<crate::domain::post::Post>,
+}
+
+/// Satisfies a combined search UI (users and posts for several terms) in
+/// one round trip instead of `N` calls to [`search_users`]/[`search_posts`],
+/// sharing the same query validation and running every lookup concurrently
+/// via `SearchService::search_all`.
+pub async fn search_all(
+    _auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<BatchSearchRequest>,
+) -> Result<Json<Vec<BatchSearchResult>>, AppError> {
+    let mut requests = Vec::with_capacity(payload.queries.len());
+    let mut limits = Vec::with_capacity(payload.queries.len());
+    for item in payload.queries {
+        let term = item.q.trim();
+        if term.len() < 2 {
+            return Err(AppError::bad_request("q must be at least 2 characters"));
+        }
+        let limit = item.limit.unwrap_or(30);
+        if !(1..=200).contains(&limit) {
+            return Err(AppError::bad_request("limit must be between 1 and 200"));
+        }
+        let cursor = parse_rank_cursor(item.cursor)?;
+        let entity = match item.entity {
+            BatchSearchEntity::Users => crate::app::search::SearchEntity::Users,
+            BatchSearchEntity::Posts => crate::app::search::SearchEntity::Posts,
+            BatchSearchEntity::Both => crate::app::search::SearchEntity::Both,
+        };
+
+        limits.push(limit);
+        requests.push(crate::app::search::SearchRequest {
+            entity,
+            query: term.to_string(),
+            cursor,
+            limit: limit + 1,
+        });
+    }
+
+    let service = SearchService::new(state.search_store.clone());
+    let results = service.search_all(requests).await.map_err(|err| {
+        if err.to_string().contains("exceeds max batch size") {
+            return AppError::bad_request(err.to_string());
+        }
+        tracing::error!(error = ?err, "failed to run batch search");
+        AppError::internal("failed to run batch search")
+    })?;
+
+    let items = results
+        .into_iter()
+        .zip(limits)
+        .map(|(mut response, limit)| {
+            let users_next_cursor = if response.users.len() > limit as usize {
+                let last = response.users.pop().expect("checked len");
+                encode_rank_cursor(Some((last.rank, last.user.created_at, last.user.id)))
+            } else {
+                None
+            };
+            let posts_next_cursor = if response.posts.len() > limit as usize {
+                let last = response.posts.pop().expect("checked len");
+                encode_rank_cursor(Some((last.rank, last.post.created_at, last.post.id)))
+            } else {
+                None
+            };
+
+            BatchSearchResult {
+                users: ListResponse {
+                    items: response
+                        .users
+                        .into_iter()
+                        .map(|ranked| crate::domain::user::PublicUser::from(ranked.user))
+                        .collect(),
+                    next_cursor: users_next_cursor,
+                },
+                posts: ListResponse {
+                    items: response.posts.into_iter().map(|ranked| ranked.post).collect(),
+                    next_cursor: posts_next_cursor,
+                },
+            }
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+
+// ============================================================================
+// Safety & Anti-Abuse Handlers
+// ============================================================================
+
+// Trust Score Handlers
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TrustScoreResponse {
+    pub user_id: String,
+    pub trust_level: i32,
+    pub trust_level_name: String,
+    pub trust_points: i32,
+    pub account_age_days: i32,
+    pub posts_count: i32,
+    pub followers_count: i32,
+    pub strikes: i32,
+    pub is_banned: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/account/trust-score",
+    responses(
+        (status = 200, description = "Caller's current trust level and standing", body = TrustScoreResponse),
+        (status = 404, description = "Trust score not found"),
+    ),
+    tag = "trust",
+    security(("bearer_token" = [])),
+)]
+pub async fn get_trust_score(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<TrustScoreResponse>, AppError> {
+    let trust_service = crate::app::trust::TrustService::new(state.db.clone());
+    let score = trust_service
+        .get_trust_score(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch trust score");
+            AppError::internal("failed to fetch trust score")
+        })?
+        .ok_or_else(|| AppError::not_found("trust score not found"))?;
+
+    let trust_level_name = match score.trust_level {
+        crate::config::rate_limits::TrustLevel::New => "New",
+        crate::config::rate_limits::TrustLevel::Basic => "Basic",
+        crate::config::rate_limits::TrustLevel::Trusted => "Trusted",
+        crate::config::rate_limits::TrustLevel::Verified => "Verified",
+    };
+
+    Ok(Json(TrustScoreResponse {
+        user_id: score.user_id.to_string(),
+        trust_level: score.trust_level as i32,
+        trust_level_name: trust_level_name.to_string(),
+        trust_points: score.trust_points,
+        account_age_days: score.account_age_days,
+        posts_count: score.posts_count,
+        followers_count: score.followers_count,
+        strikes: score.strikes,
+        is_banned: score.banned_until.map(|until| until > time::OffsetDateTime::now_utc()).unwrap_or(false),
+    }))
+}
+
+// Rate Limit Handlers
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct RateLimitsResponse {
+    pub trust_level: String,
+    pub posts_per_hour: u32,
+    pub posts_per_day: u32,
+    pub follows_per_hour: u32,
+    pub follows_per_day: u32,
+    pub likes_per_hour: u32,
+    pub comments_per_hour: u32,
+    pub remaining: RemainingQuotas,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct RemainingQuotas {
+    pub posts: u32,
+    pub follows: u32,
+    pub likes: u32,
+    pub comments: u32,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/account/rate-limits",
+    responses(
+        (status = 200, description = "Caller's per-action limits and remaining quota for their trust level", body = RateLimitsResponse),
+        (status = 404, description = "Trust score not found"),
+    ),
+    tag = "trust",
+    security(("bearer_token" = [])),
+)]
+pub async fn get_rate_limits(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<RateLimitsResponse>, AppError> {
+    let trust_service = crate::app::trust::TrustService::new(state.db.clone());
+    let score = trust_service
+        .get_trust_score(auth.user_id)
+        .await
+        .map_err(|_| AppError::internal("failed to fetch trust score"))?
+        .ok_or_else(|| AppError::not_found("trust score not found"))?;
+
+    let trust_level = score.trust_level;
+    let limits = crate::config::rate_limits::RateLimits::for_trust_level(trust_level);
+    let limit_max = |t: crate::config::rate_limits::LimitType, w: crate::config::rate_limits::RateWindow| {
+        limits.limit(t, w).map(|l| l.max).unwrap_or(0)
+    };
+
+    let rate_limiter = crate::app::rate_limiter::RateLimiter::new(state.cache.clone());
+
+    let remaining_posts = rate_limiter
+        .get_remaining(auth.user_id, crate::config::rate_limits::LimitType::PostCreate, trust_level)
+        .await
+        .unwrap_or(0);
+    let remaining_follows = rate_limiter
+        .get_remaining(auth.user_id, crate::config::rate_limits::LimitType::Follow, trust_level)
+        .await
+        .unwrap_or(0);
+    let remaining_likes = rate_limiter
+        .get_remaining(auth.user_id, crate::config::rate_limits::LimitType::Like, trust_level)
+        .await
+        .unwrap_or(0);
+    let remaining_comments = rate_limiter
+        .get_remaining(auth.user_id, crate::config::rate_limits::LimitType::Comment, trust_level)
+        .await
+        .unwrap_or(0);
+
+    use crate::config::rate_limits::{LimitType, RateWindow};
+    Ok(Json(RateLimitsResponse {
+        trust_level: format!("{:?}", trust_level),
+        posts_per_hour: limit_max(LimitType::PostCreate, RateWindow::Hour),
+        posts_per_day: limit_max(LimitType::PostCreate, RateWindow::Day),
+        follows_per_hour: limit_max(LimitType::Follow, RateWindow::Hour),
+        follows_per_day: limit_max(LimitType::Follow, RateWindow::Day),
+        likes_per_hour: limit_max(LimitType::Like, RateWindow::Hour),
+        comments_per_hour: limit_max(LimitType::Comment, RateWindow::Hour),
+        remaining: RemainingQuotas {
+            posts: remaining_posts,
+            follows: remaining_follows,
+            likes: remaining_likes,
+            comments: remaining_comments,
+        },
+    }))
+}
+
+// Device Fingerprint Handlers
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RegisterFingerprintRequest {
+    pub fingerprint: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/account/device/register",
+    request_body = RegisterFingerprintRequest,
+    responses(
+        (status = 204, description = "Fingerprint recorded"),
+        (status = 403, description = "Device has been blocked"),
+    ),
+    tag = "fingerprint",
+)]
+pub async fn register_device_fingerprint(
+    auth: Option<AuthUser>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<RegisterFingerprintRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::fingerprint::FingerprintService::new(state.db.clone());
+
+    let user_agent = headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    let fingerprint_hash = crate::app::fingerprint::FingerprintService::hash_fingerprint(&payload.fingerprint);
+
+    // Check if device is blocked
+    let (risk_score, is_blocked) = service
+        .check_device_risk(&fingerprint_hash)
+        .await
+        .map_err(|_| AppError::internal("failed to check device risk"))?;
+
+    if is_blocked {
+        return Err(AppError::forbidden("This device has been blocked"));
+    }
+
+    if risk_score > 80 {
+        let user_id_log = match &auth {
+            Some(auth_user) => format!("user_id={}", auth_user.user_id),
+            None => "unauthenticated".to_string(),
+        };
+        tracing::warn!(
+            user_id = user_id_log,
+            fingerprint_hash = &fingerprint_hash[..8],
+            risk_score = risk_score,
+            "High-risk device detected"
+        );
+    }
+
+    // Register fingerprint - user_id is optional for unauthenticated registration
+    let user_id = auth.map(|a| a.user_id);
+    service
+        .register_fingerprint(fingerprint_hash, user_id, user_agent)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to register fingerprint");
+            AppError::internal("failed to register device")
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn list_user_posts(
+#[derive(Serialize)]
+pub struct DeviceResponse {
+    pub fingerprint_hash: String,
+    pub account_count: i32,
+    pub risk_score: i32,
+    pub is_blocked: bool,
+}
+
+pub async fn list_user_devices(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DeviceResponse>>, AppError> {
+    let service = crate::app::fingerprint::FingerprintService::new(state.db.clone());
+
+    let devices = service
+        .get_user_devices(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to fetch devices");
+            AppError::internal("failed to fetch devices")
+        })?;
+
+    let response = devices
+        .into_iter()
+        .map(|device| DeviceResponse {
+            fingerprint_hash: device.fingerprint_hash[..16].to_string(), // Truncate for privacy
+            account_count: device.account_count,
+            risk_score: device.risk_score,
+            is_blocked: device.is_blocked,
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+// Signed Device List Handlers
+
+#[derive(Deserialize)]
+pub struct RegisterDeviceKeyRequest {
+    pub device_id: String,
+    /// base64-encoded ed25519 public key for this device.
+    pub public_key: String,
+}
+
+pub async fn register_device_key(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<RegisterDeviceKeyRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = DeviceListService::new(state.db.clone());
+    service
+        .register_device_key(auth.user_id, &payload.device_id, &payload.public_key)
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("base64") || message.contains("bytes") || message.contains("invalid ed25519") {
+                AppError::bad_request(message)
+            } else {
+                tracing::error!(error = ?err, user_id = %auth.user_id, "failed to register device key");
+                AppError::internal("failed to register device key")
+            }
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_device_list(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::device_list::StoredDeviceList>, AppError> {
+    let service = DeviceListService::new(state.db.clone());
+    let list = service
+        .get_device_list(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch device list");
+            AppError::internal("failed to fetch device list")
+        })?
+        .ok_or_else(|| AppError::not_found("no device list on file for this account"))?;
+
+    Ok(Json(list))
+}
+
+pub async fn update_device_list(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::device_list::SignedDeviceList>,
+) -> Result<Json<crate::domain::device_list::StoredDeviceList>, AppError> {
+    let service = DeviceListService::new(state.db.clone());
+    let updated = service
+        .update_device_list(auth.user_id, payload)
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("timestamp")
+                || message.contains("signature")
+                || message.contains("device list must name")
+            {
+                AppError::bad_request(message)
+            } else {
+                tracing::error!(error = ?err, user_id = %auth.user_id, "failed to update device list");
+                AppError::internal("failed to update device list")
+            }
+        })?;
+
+    Ok(Json(updated))
+}
+
+// Device Auth Request Handlers (passwordless "approve new login" flow)
+
+#[derive(Deserialize)]
+pub struct CreateDeviceAuthRequest {
+    pub identifier: String,
+    pub fingerprint: String,
+}
+
+#[derive(Serialize)]
+pub struct DeviceAuthRequestCreatedResponse {
+    pub request_id: Uuid,
+    pub access_code: String,
+}
+
+/// Called by an unrecognized device attempting a passwordless login. Creates
+/// a pending request for an already-trusted device to approve or deny.
+pub async fn create_device_auth_request(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<CreateDeviceAuthRequest>,
+) -> Result<Json<DeviceAuthRequestCreatedResponse>, AppError> {
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let user = auth_service
+        .get_current_user_by_identifier(&payload.identifier)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to look up user for device auth request");
+            AppError::internal("failed to create device auth request")
+        })?
+        .ok_or_else(|| AppError::not_found("no account matches that identifier"))?;
+
+    let fingerprint_service = crate::app::fingerprint::FingerprintService::new(state.db.clone());
+    let fingerprint_hash =
+        crate::app::fingerprint::FingerprintService::hash_fingerprint(&payload.fingerprint);
+
+    let (_, is_blocked) = fingerprint_service
+        .check_device_risk(&fingerprint_hash)
+        .await
+        .map_err(|_| AppError::internal("failed to check device risk"))?;
+    if is_blocked {
+        return Err(AppError::forbidden("This device has been blocked"));
+    }
+
+    let request_ip = headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let service = crate::app::device_auth::DeviceAuthService::new(state.db.clone());
+    let (request, access_code) = service
+        .create_request(user.id, fingerprint_hash, request_ip)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to create device auth request");
+            AppError::internal("failed to create device auth request")
+        })?;
+
+    Ok(Json(DeviceAuthRequestCreatedResponse {
+        request_id: request.id,
+        access_code,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct PollDeviceAuthRequestQuery {
+    pub access_code: String,
+}
+
+#[derive(Serialize)]
+pub struct DeviceAuthPollResponse {
+    pub approved: Option<bool>,
+    pub tokens: Option<AuthTokenResponse>,
+}
+
+/// Polled by the requesting device to learn whether it has been approved,
+/// and to pick up tokens once it has.
+pub async fn poll_device_auth_request(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path(id): Path<Uuid>,
-    auth: Option<AuthUser>,
+    Query(query): Query<PollDeviceAuthRequestQuery>,
+) -> Result<Json<DeviceAuthPollResponse>, AppError> {
+    let service = crate::app::device_auth::DeviceAuthService::new(state.db.clone());
+    let request = service
+        .get_by_access_code(id, &query.access_code)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to poll device auth request");
+            AppError::internal("failed to poll device auth request")
+        })?
+        .ok_or_else(|| AppError::not_found("device auth request not found"))?;
+
+    let tokens = if request.approved == Some(true) {
+        let auth_service = AuthService::new(
+            state.db.clone(),
+            state.cache.clone(),
+            state.paseto_access_keys.clone(),
+            state.paseto_refresh_keys.clone(),
+            state.access_ttl_minutes,
+            state.refresh_ttl_days,
+            state.account_recovery_grace_days,
+            state.ldap.clone(),
+        );
+        let device = device_context(&state, addr, &headers, None);
+        let tokens = auth_service
+            .issue_tokens_for_user(request.user_id, device)
+            .await
+            .map_err(|err| {
+                tracing::error!(error = ?err, "failed to issue tokens for approved device");
+                AppError::internal("failed to issue tokens")
+            })?;
+        Some(AuthTokenResponse {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            access_expires_at: tokens.access_expires_at,
+            refresh_expires_at: tokens.refresh_expires_at,
+        })
+    } else {
+        None
+    };
+
+    Ok(Json(DeviceAuthPollResponse {
+        approved: request.approved,
+        tokens,
+    }))
+}
+
+/// Pending device auth requests for the current (already-trusted) account,
+/// for a trusted device to review.
+pub async fn list_pending_device_auth_requests(
+    auth: AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::post::Post>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
+) -> Result<Json<Vec<crate::domain::device_auth::DeviceAuthRequest>>, AppError> {
+    let service = crate::app::device_auth::DeviceAuthService::new(state.db.clone());
+    let requests = service.list_pending(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, "failed to list device auth requests");
+        AppError::internal("failed to list device auth requests")
+    })?;
+
+    Ok(Json(requests))
+}
+
+#[derive(Deserialize)]
+pub struct RespondDeviceAuthRequest {
+    pub approved: bool,
+}
+
+pub async fn respond_device_auth_request(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RespondDeviceAuthRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::device_auth::DeviceAuthService::new(state.db.clone());
+    let updated = service
+        .respond(id, auth.user_id, payload.approved)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to respond to device auth request");
+            AppError::internal("failed to respond to device auth request")
+        })?;
+
+    if updated.is_none() {
+        return Err(AppError::not_found(
+            "device auth request not found or already resolved",
+        ));
     }
-    let cursor = parse_cursor(query.cursor)?;
-    let viewer_id = auth.map(|user| user.user_id);
 
-    let service = PostService::new(state.db.clone());
-    let mut posts = service
-        .list_by_user(id, viewer_id, cursor, limit + 1)
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Wallet Linking Handlers (Sign-In with Ethereum)
+
+#[derive(Serialize)]
+pub struct WalletNonceResponse {
+    pub nonce: String,
+}
+
+/// Issue a single-use nonce the client embeds in the `Nonce:` field of the
+/// SIWE message it asks the wallet to sign.
+pub async fn create_wallet_nonce(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<WalletNonceResponse>, AppError> {
+    let service = crate::app::wallet::WalletService::new(state.db.clone());
+    let nonce = service.issue_nonce(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to issue wallet nonce");
+        AppError::internal("failed to issue wallet nonce")
+    })?;
+
+    Ok(Json(WalletNonceResponse { nonce }))
+}
+
+#[derive(Deserialize)]
+pub struct VerifyWalletRequest {
+    pub message: String,
+    pub signature: String,
+}
+
+pub async fn verify_wallet(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyWalletRequest>,
+) -> Result<Json<crate::domain::wallet::WalletLink>, AppError> {
+    let service = crate::app::wallet::WalletService::new(state.db.clone());
+    let link = service
+        .verify_and_link(
+            auth.user_id,
+            &payload.message,
+            &payload.signature,
+            &state.siwe_domain,
+        )
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("nonce is invalid") {
+                AppError::bad_request(message)
+            } else if message.contains("domain mismatch")
+                || message.contains("expired")
+                || message.contains("malformed")
+                || message.contains("missing")
+            {
+                AppError::bad_request(message)
+            } else {
+                tracing::error!(error = ?err, user_id = %auth.user_id, "failed to verify wallet signature");
+                AppError::internal("failed to verify wallet signature")
+            }
+        })?;
+
+    match link {
+        Some(link) => Ok(Json(link)),
+        None => Err(AppError::unauthorized("signature does not match claimed address")),
+    }
+}
+
+pub async fn get_wallet(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::wallet::WalletLink>, AppError> {
+    let service = crate::app::wallet::WalletService::new(state.db.clone());
+    let link = service
+        .get_linked_wallet(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch linked wallet");
+            AppError::internal("failed to fetch linked wallet")
+        })?
+        .ok_or_else(|| AppError::not_found("no wallet linked to this account"))?;
+
+    Ok(Json(link))
+}
+
+// Invite Handlers
+
+pub async fn list_invites(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<crate::app::invites::InviteCode>>, AppError> {
+    let service = crate::app::invites::InviteService::new(state.db.clone());
+
+    let invites = service
+        .list_user_invites(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to list invites");
+            AppError::internal("failed to list invites")
+        })?;
+
+    Ok(Json(invites))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateInviteRequest {
+    #[serde(default = "default_days_valid")]
+    pub days_valid: i64,
+}
+
+fn default_days_valid() -> i64 {
+    7
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/invites",
+    request_body = CreateInviteRequest,
+    responses(
+        (status = 200, description = "Invite code created", body = crate::app::invites::InviteCode),
+        (status = 400, description = "days_valid outside 1..=30"),
+        (status = 403, description = "Email not verified, or invite limit reached"),
+    ),
+    tag = "invites",
+    security(("bearer_token" = [])),
+)]
+pub async fn create_invite(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateInviteRequest>,
+) -> Result<Json<crate::app::invites::InviteCode>, AppError> {
+    if payload.days_valid < 1 || payload.days_valid > 30 {
+        return Err(AppError::bad_request("days_valid must be between 1 and 30"));
+    }
+
+    let verification_service = crate::app::email_verification::EmailVerificationService::new(
+        state.db.clone(),
+        state.mailer.clone(),
+        state.email_verification_ttl_hours,
+    );
+    let is_verified = verification_service
+        .is_verified(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to check email verification");
+            AppError::internal("failed to check email verification")
+        })?;
+    if !is_verified {
+        return Err(AppError::forbidden("email must be verified before issuing invite codes")
+            .with_code(crate::http::ErrorCode::EmailNotVerified));
+    }
+
+    let service = crate::app::invites::InviteService::new(state.db.clone());
+
+    let invite = service
+        .create_invite(auth.user_id, payload.days_valid)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %id, "failed to list user posts");
-            AppError::internal("failed to list user posts")
+            if let Some(conflict) = AppError::from_db_conflict(&err) {
+                return conflict;
+            }
+            if err.to_string().contains("Maximum invite limit") {
+                return AppError::trust_quota(&err.to_string());
+            }
+            tracing::error!(error = ?err, "failed to create invite");
+            AppError::internal("failed to create invite")
         })?;
 
-    let next_cursor = if posts.len() > limit as usize {
-        let last = posts.pop().expect("checked len");
-        Some((last.created_at, last.id))
-    } else {
-        None
-    };
-
-    Ok(Json(ListResponse {
-        items: posts,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+    Ok(Json(invite))
 }
 
-pub async fn follow_user(
-    Path(id): Path<Uuid>,
+pub async fn get_invite_stats(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<FollowResponse>, AppError> {
-    if auth.user_id == id {
-        return Err(AppError::bad_request("cannot follow yourself"));
-    }
-
-    let service = SocialService::new(state.db.clone());
-    let followed = service.follow(auth.user_id, id).await.map_err(|err| {
-        if err.to_string().contains("follower limit") {
-            return AppError::forbidden("user has reached the follower limit");
-        }
-        tracing::error!(error = ?err, follower_id = %auth.user_id, followee_id = %id, "failed to follow user");
-        AppError::internal("failed to follow user")
-    })?;
+) -> Result<Json<crate::app::invites::InviteStats>, AppError> {
+    let service = crate::app::invites::InviteService::new(state.db.clone());
 
-    Ok(Json(FollowResponse { followed }))
-}
+    let stats = service
+        .get_invite_stats(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to fetch invite stats");
+            AppError::internal("failed to fetch invite stats")
+        })?;
 
-#[derive(Serialize)]
-pub struct FollowResponse {
-    pub followed: bool,
+    Ok(Json(stats))
 }
 
-pub async fn unfollow_user(
-    Path(id): Path<Uuid>,
+pub async fn revoke_invite(
     auth: AuthUser,
+    Path(code): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<UnfollowResponse>, AppError> {
-    if auth.user_id == id {
-        return Err(AppError::bad_request("cannot unfollow yourself"));
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::invites::InviteService::new(state.db.clone());
+
+    let revoked = service
+        .revoke_invite(&code, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to revoke invite");
+            AppError::internal("failed to revoke invite")
+        })?;
+
+    if revoked {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("invite code not found or already used"))
     }
+}
 
-    let service = SocialService::new(state.db.clone());
-    let unfollowed = service.unfollow(auth.user_id, id).await.map_err(|err| {
-        tracing::error!(error = ?err, follower_id = %auth.user_id, followee_id = %id, "failed to unfollow user");
-        AppError::internal("failed to unfollow user")
-    })?;
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateSignedInviteRequest {
+    #[serde(default = "default_days_valid")]
+    pub days_valid: i64,
+    #[serde(default = "default_signed_invite_max_uses")]
+    pub max_uses: i32,
+}
 
-    Ok(Json(UnfollowResponse { unfollowed }))
+fn default_signed_invite_max_uses() -> i32 {
+    1
 }
 
-#[derive(Serialize)]
-pub struct UnfollowResponse {
-    pub unfollowed: bool,
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct SignedInviteResponse {
+    pub token: String,
 }
 
-pub async fn block_user(
-    Path(id): Path<Uuid>,
+/// Stateless alternative to `create_invite`: the returned token is a signed,
+/// self-contained credential rather than a row in `invite_codes`, so it can
+/// be verified offline and handed out at volume (e.g. a referral link)
+/// without a uniqueness round trip per code. See `app::invites::signed`.
+#[utoipa::path(
+    post,
+    path = "/v1/invites/signed",
+    request_body = CreateSignedInviteRequest,
+    responses(
+        (status = 200, description = "Signed invite token issued", body = SignedInviteResponse),
+        (status = 400, description = "days_valid outside 1..=30, or max_uses outside 1..=1000"),
+        (status = 403, description = "Email not verified, or invite limit reached"),
+    ),
+    tag = "invites",
+    security(("bearer_token" = [])),
+)]
+pub async fn create_signed_invite(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<BlockResponse>, AppError> {
-    if auth.user_id == id {
-        return Err(AppError::bad_request("cannot block yourself"));
+    Json(payload): Json<CreateSignedInviteRequest>,
+) -> Result<Json<SignedInviteResponse>, AppError> {
+    if payload.days_valid < 1 || payload.days_valid > 30 {
+        return Err(AppError::bad_request("days_valid must be between 1 and 30"));
+    }
+    if payload.max_uses < 1 || payload.max_uses > 1000 {
+        return Err(AppError::bad_request("max_uses must be between 1 and 1000"));
     }
 
-    let service = SocialService::new(state.db.clone());
-    let blocked = service.block(auth.user_id, id).await.map_err(|err| {
-        tracing::error!(error = ?err, blocker_id = %auth.user_id, blocked_id = %id, "failed to block user");
-        AppError::internal("failed to block user")
-    })?;
+    let verification_service = crate::app::email_verification::EmailVerificationService::new(
+        state.db.clone(),
+        state.mailer.clone(),
+        state.email_verification_ttl_hours,
+    );
+    let is_verified = verification_service
+        .is_verified(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to check email verification");
+            AppError::internal("failed to check email verification")
+        })?;
+    if !is_verified {
+        return Err(AppError::forbidden("email must be verified before issuing invite codes")
+            .with_code(crate::http::ErrorCode::EmailNotVerified));
+    }
 
-    Ok(Json(BlockResponse { blocked }))
-}
+    let service = crate::app::invites::InviteService::new(state.db.clone());
 
-#[derive(Serialize)]
-pub struct BlockResponse {
-    pub blocked: bool,
+    let token = service
+        .issue_signed_invite(
+            &state.invite_signing_key,
+            auth.user_id,
+            payload.days_valid,
+            payload.max_uses,
+        )
+        .await
+        .map_err(|err| {
+            if err.to_string().contains("Maximum invite limit") {
+                return AppError::trust_quota(&err.to_string());
+            }
+            tracing::error!(error = ?err, "failed to issue signed invite");
+            AppError::internal("failed to issue signed invite")
+        })?;
+
+    Ok(Json(SignedInviteResponse { token }))
 }
 
-pub async fn unblock_user(
-    Path(id): Path<Uuid>,
+pub async fn revoke_signed_invite(
     auth: AuthUser,
+    Path(nonce): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<UnblockResponse>, AppError> {
-    if auth.user_id == id {
-        return Err(AppError::bad_request("cannot unblock yourself"));
-    }
-
-    let service = SocialService::new(state.db.clone());
-    let unblocked = service.unblock(auth.user_id, id).await.map_err(|err| {
-        tracing::error!(error = ?err, blocker_id = %auth.user_id, blocked_id = %id, "failed to unblock user");
-        AppError::internal("failed to unblock user")
-    })?;
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::invites::InviteService::new(state.db.clone());
 
-    Ok(Json(UnblockResponse { unblocked }))
-}
+    service
+        .revoke_signed_invite(&nonce, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to revoke signed invite");
+            AppError::internal("failed to revoke signed invite")
+        })?;
 
-#[derive(Serialize)]
-pub struct UnblockResponse {
-    pub unblocked: bool,
+    Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Serialize)]
-pub struct SocialUserItem {
-    pub user: crate::domain::user::PublicUser,
-    #[serde(with = "time::serde::rfc3339")]
-    pub followed_at: OffsetDateTime,
+#[derive(Deserialize)]
+pub struct CreateAdminInviteRequest {
+    #[serde(default = "default_days_valid")]
+    pub days_valid: i64,
+    pub bound_email: Option<String>,
+    #[serde(default = "default_signed_invite_max_uses")]
+    pub max_uses: i32,
+    pub note: Option<String>,
 }
 
-pub async fn list_followers(
-    Path(id): Path<Uuid>,
-    _auth: AuthUser,
+/// Issue an invite code on an admin's behalf, bypassing the per-user quota
+/// in `create_invite`. Used to run a closed beta without burning a real
+/// user's invite allowance.
+pub async fn admin_create_invite(
+    _admin: AdminToken,
     State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<SocialUserItem>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    Json(payload): Json<CreateAdminInviteRequest>,
+) -> Result<Json<crate::app::invites::InviteCode>, AppError> {
+    if payload.days_valid < 1 || payload.days_valid > 365 {
+        return Err(AppError::bad_request("days_valid must be between 1 and 365"));
+    }
+    if payload.max_uses < 1 || payload.max_uses > 1000 {
+        return Err(AppError::bad_request("max_uses must be between 1 and 1000"));
     }
-    let cursor = parse_cursor(query.cursor)?;
 
-    let service = SocialService::new(state.db.clone());
-    let mut followers = service
-        .list_followers(id, cursor, limit + 1)
+    let service = crate::app::invites::InviteService::new(state.db.clone());
+
+    let invite = service
+        .create_admin_invite(payload.days_valid, payload.bound_email, payload.max_uses, payload.note)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %id, "failed to list followers");
-            AppError::internal("failed to list followers")
+            if let Some(conflict) = AppError::from_db_conflict(&err) {
+                return conflict;
+            }
+            tracing::error!(error = ?err, "failed to create admin invite");
+            AppError::internal("failed to create admin invite")
         })?;
 
-    let next_cursor = if followers.len() > limit as usize {
-        let last = followers.pop().expect("checked len");
-        Some((last.followed_at, last.user.id))
-    } else {
-        None
-    };
+    Ok(Json(invite))
+}
 
-    let items = followers
-        .into_iter()
-        .map(|edge| SocialUserItem {
-            user: edge.user.into(),
-            followed_at: edge.followed_at,
-        })
-        .collect();
+#[derive(Deserialize)]
+pub struct AdminListInvitesQuery {
+    #[serde(default = "default_admin_invite_limit")]
+    pub limit: i64,
+}
 
-    Ok(Json(ListResponse {
-        items,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+fn default_admin_invite_limit() -> i64 {
+    50
 }
 
-pub async fn list_following(
-    Path(id): Path<Uuid>,
-    _auth: AuthUser,
+pub async fn admin_list_invites(
+    _admin: AdminToken,
     State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<SocialUserItem>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
+    Query(query): Query<AdminListInvitesQuery>,
+) -> Result<Json<Vec<crate::app::invites::InviteCode>>, AppError> {
+    if !(1..=200).contains(&query.limit) {
         return Err(AppError::bad_request("limit must be between 1 and 200"));
     }
-    let cursor = parse_cursor(query.cursor)?;
 
-    let service = SocialService::new(state.db.clone());
-    let mut following = service
-        .list_following(id, cursor, limit + 1)
+    let service = crate::app::invites::InviteService::new(state.db.clone());
+
+    let invites = service
+        .list_all_invites(query.limit)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %id, "failed to list following");
-            AppError::internal("failed to list following")
+            tracing::error!(error = ?err, "failed to list admin invites");
+            AppError::internal("failed to list invites")
         })?;
 
-    let next_cursor = if following.len() > limit as usize {
-        let last = following.pop().expect("checked len");
-        Some((last.followed_at, last.user.id))
-    } else {
-        None
-    };
-
-    let items = following
-        .into_iter()
-        .map(|edge| SocialUserItem {
-            user: edge.user.into(),
-            followed_at: edge.followed_at,
-        })
-        .collect();
-
-    Ok(Json(ListResponse {
-        items,
-        next_cursor: encode_cursor(next_cursor),
-    }))
-}
-
-#[derive(Serialize)]
-pub struct RelationshipResponse {
-    pub is_following: bool,
-    pub is_followed_by: bool,
-    pub is_blocking: bool,
-    pub is_blocked_by: bool,
+    Ok(Json(invites))
 }
 
-pub async fn relationship_status(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
+pub async fn admin_revoke_invite(
+    _admin: AdminToken,
+    Path(code): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<RelationshipResponse>, AppError> {
-    if auth.user_id == id {
-        return Ok(Json(RelationshipResponse {
-            is_following: false,
-            is_followed_by: false,
-            is_blocking: false,
-            is_blocked_by: false,
-        }));
-    }
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::invites::InviteService::new(state.db.clone());
 
-    let service = SocialService::new(state.db.clone());
-    let status = service
-        .relationship_status(auth.user_id, id)
+    let revoked = service
+        .revoke_invite_as_admin(&code)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, viewer_id = %auth.user_id, other_id = %id, "failed to fetch relationship status");
-            AppError::internal("failed to fetch relationship status")
-        })?;
-
-    Ok(Json(RelationshipResponse {
-        is_following: status.is_following,
-        is_followed_by: status.is_followed_by,
-        is_blocking: status.is_blocking,
-        is_blocked_by: status.is_blocked_by,
-    }))
+            tracing::error!(error = ?err, "failed to revoke invite");
+            AppError::internal("failed to revoke invite")
+        })?;
+
+    if revoked {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("invite code not found or already used"))
+    }
 }
 
 #[derive(Deserialize)]
-pub struct CreatePostRequest {
-    pub media_id: Uuid,
-    pub caption: Option<String>,
+pub struct AddBlocklistEntryRequest {
+    // An exact address ("abuse@example.com") or a wildcard domain pattern
+    // ("*@tempmail.com").
+    pub pattern: String,
+    pub reason: Option<String>,
 }
 
-pub async fn create_post(
-    auth: AuthUser,
+/// Add an entry to the signup email blocklist, checked by `create_user`.
+pub async fn admin_add_blocklist_entry(
+    _admin: AdminToken,
     State(state): State<AppState>,
-    Json(payload): Json<CreatePostRequest>,
-) -> Result<Json<crate::domain::post::Post>, AppError> {
-    let service = PostService::new(state.db.clone());
-    let post = service
-        .create_post(auth.user_id, payload.media_id, payload.caption)
+    Json(payload): Json<AddBlocklistEntryRequest>,
+) -> Result<Json<crate::app::blocklist::BlocklistedEmail>, AppError> {
+    let service = crate::app::blocklist::BlocklistService::new(state.db.clone());
+
+    let entry = service
+        .add(&payload.pattern, payload.reason)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, owner_id = %auth.user_id, "failed to create post");
-            AppError::internal("failed to create post")
+            if let Some(conflict) = AppError::from_db_conflict(&err) {
+                return conflict;
+            }
+            let message = err.to_string();
+            if message.contains("cannot be empty") {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to add blocklist entry");
+            AppError::internal("failed to add blocklist entry")
         })?;
 
-    Ok(Json(post))
+    Ok(Json(entry))
 }
 
-pub async fn get_post(
+pub async fn admin_list_blocklist(
+    _admin: AdminToken,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<crate::app::blocklist::BlocklistedEmail>>, AppError> {
+    let service = crate::app::blocklist::BlocklistService::new(state.db.clone());
+
+    let entries = service.list().await.map_err(|err| {
+        tracing::error!(error = ?err, "failed to list blocklist entries");
+        AppError::internal("failed to list blocklist entries")
+    })?;
+
+    Ok(Json(entries))
+}
+
+pub async fn admin_remove_blocklist_entry(
+    _admin: AdminToken,
     Path(id): Path<Uuid>,
-    auth: Option<AuthUser>,
     State(state): State<AppState>,
-) -> Result<Json<crate::domain::post::Post>, AppError> {
-    let viewer_id = auth.map(|user| user.user_id);
-    let service = PostService::new(state.db.clone());
-    let post = service.get_post(id, viewer_id).await.map_err(|err| {
-        tracing::error!(error = ?err, post_id = %id, "failed to fetch post");
-        AppError::internal("failed to fetch post")
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::blocklist::BlocklistService::new(state.db.clone());
+
+    let removed = service.remove(id).await.map_err(|err| {
+        tracing::error!(error = ?err, "failed to remove blocklist entry");
+        AppError::internal("failed to remove blocklist entry")
     })?;
 
-    match post {
-        Some(post) => Ok(Json(post)),
-        None => Err(AppError::not_found("post not found")),
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("blocklist entry not found"))
     }
 }
 
+// ============================================================================
+// Story Handlers
+// ============================================================================
+
 #[derive(Deserialize)]
-pub struct UpdateCaptionRequest {
+pub struct CreateStoryRequest {
+    pub media_id: Uuid,
     pub caption: Option<String>,
+    pub visibility: crate::domain::story::StoryVisibility,
+    /// Required, and only meaningful, when `visibility` is `List`; must name
+    /// a `story_audience_lists` row owned by the caller (see
+    /// `StoryService::create_story`).
+    pub audience_list_id: Option<Uuid>,
+    /// Present only for an end-to-end encrypted close-friends story; see
+    /// `app::stories::StoryEncryption`.
+    pub encryption: Option<CreateStoryEncryptionRequest>,
 }
 
-pub async fn update_post_caption(
-    Path(id): Path<Uuid>,
+#[derive(Deserialize)]
+pub struct CreateStoryEncryptionRequest {
+    pub ciphertext: String,
+    pub iv: String,
+    pub key_envelopes: Vec<CreateStoryKeyEnvelopeRequest>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateStoryKeyEnvelopeRequest {
+    pub recipient_id: Uuid,
+    pub wrapped_key: String,
+}
+
+pub async fn create_story(
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<UpdateCaptionRequest>,
-) -> Result<Json<crate::domain::post::Post>, AppError> {
-    let service = PostService::new(state.db.clone());
-    let post = service
-        .update_caption(id, auth.user_id, payload.caption)
+    Json(payload): Json<CreateStoryRequest>,
+) -> Result<Json<crate::domain::story::Story>, AppError> {
+    let encryption = payload.encryption.map(|encryption| crate::app::stories::StoryEncryption {
+        ciphertext: encryption.ciphertext,
+        iv: encryption.iv,
+        key_envelopes: encryption
+            .key_envelopes
+            .into_iter()
+            .map(|envelope| crate::app::stories::StoryKeyEnvelope {
+                recipient_id: envelope.recipient_id,
+                wrapped_key: envelope.wrapped_key,
+            })
+            .collect(),
+    });
+
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let story = service
+        .create_story(
+            auth.user_id,
+            payload.media_id,
+            payload.caption,
+            payload.visibility,
+            payload.audience_list_id,
+            encryption,
+        )
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, post_id = %id, "failed to update post");
-            AppError::internal("failed to update post")
+            if err.to_string().contains("media not found") {
+                return AppError::not_found("media not found");
+            }
+            if err.to_string().contains("media does not belong") {
+                return AppError::forbidden("media does not belong to you");
+            }
+            if err.to_string().contains("audience_list_id is required") {
+                return AppError::bad_request("audience_list_id is required for list visibility");
+            }
+            if err.to_string().contains("audience list not found") {
+                return AppError::not_found("audience list not found");
+            }
+            if err.to_string().contains("audience list does not belong") {
+                return AppError::forbidden("audience list does not belong to you");
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create story");
+            AppError::internal("failed to create story")
         })?;
 
-    match post {
-        Some(post) => Ok(Json(post)),
-        None => Err(AppError::not_found("post not found")),
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    match federation.build_create_activity(&story).await {
+        Ok(activity) => {
+            let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+            if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+                tracing::warn!(error = ?err, story_id = %story.id, "failed to enqueue story Create activity");
+            }
+        }
+        Err(err) => {
+            tracing::warn!(error = ?err, story_id = %story.id, "failed to build story Create activity");
+        }
     }
-}
 
-pub async fn delete_post(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
-    State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service = PostService::new(state.db.clone());
-    let deleted = service.delete_post(id, auth.user_id).await.map_err(|err| {
-        tracing::error!(error = ?err, post_id = %id, "failed to delete post");
-        AppError::internal("failed to delete post")
-    })?;
+    let streaming = StreamingService::new(state.db.clone(), state.cache.clone());
+    if let Err(err) = streaming.publish_story_created(&story).await {
+        tracing::warn!(error = ?err, story_id = %story.id, "failed to publish story to stories feed channel");
+    }
 
-    if deleted {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("post not found"))
+    match service.bump_feed_version_for_followers(story.user_id).await {
+        Ok(follower_ids) => {
+            state.gossip.broadcast_story_feed_changed(follower_ids).await;
+        }
+        Err(err) => {
+            tracing::warn!(error = ?err, story_id = %story.id, "failed to bump followers' stories feed version");
+        }
     }
+
+    Ok(Json(story))
 }
 
-pub async fn like_post(
-    Path(id): Path<Uuid>,
+#[derive(Deserialize)]
+pub struct RegisterStoryEncryptionKeyRequest {
+    pub public_key: String,
+}
+
+/// Publishes the caller's x25519 public key so other clients can wrap a
+/// content key for them when posting an encrypted close-friends story (see
+/// `CreateStoryRequest::encryption`).
+pub async fn register_story_encryption_key(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<LikeResponse>, AppError> {
-    let service = EngagementService::new(state.db.clone());
-    let like = service
-        .like_post(auth.user_id, id)
+    Json(payload): Json<RegisterStoryEncryptionKeyRequest>,
+) -> Result<StatusCode, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    service
+        .register_encryption_key(auth.user_id, payload.public_key)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to like post");
-            AppError::internal("failed to like post")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to register story encryption key");
+            AppError::internal("failed to register encryption key")
         })?;
 
-    Ok(Json(LikeResponse {
-        created: like.is_some(),
-    }))
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn unlike_post(
+#[derive(Serialize)]
+pub struct StoryEncryptionKeyResponse {
+    pub public_key: Option<String>,
+}
+
+pub async fn get_story_encryption_key(
     Path(id): Path<Uuid>,
-    auth: AuthUser,
+    _auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service = EngagementService::new(state.db.clone());
-    let deleted = service
-        .unlike_post(auth.user_id, id)
-        .await
-        .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to unlike post");
-            AppError::internal("failed to unlike post")
-        })?;
+) -> Result<Json<StoryEncryptionKeyResponse>, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let public_key = service.get_encryption_key(id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %id, "failed to look up story encryption key");
+        AppError::internal("failed to look up encryption key")
+    })?;
 
-    if deleted {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("like not found"))
-    }
+    Ok(Json(StoryEncryptionKeyResponse { public_key }))
 }
 
-pub async fn list_post_likes(
+pub async fn get_user_stories(
     Path(id): Path<Uuid>,
+    auth: AuthUser,
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::engagement::Like>>, AppError> {
+) -> Result<(HeaderMap, Json<ListResponse<crate::domain::story::Story>>), AppError> {
     let limit = query.limit.unwrap_or(30);
     if !(1..=200).contains(&limit) {
         return Err(AppError::bad_request("limit must be between 1 and 200"));
     }
-    let cursor = parse_cursor(query.cursor)?;
+    let had_cursor = query.cursor.is_some();
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
 
-    let service = EngagementService::new(state.db.clone());
-    let mut likes = service
-        .list_likes(id, cursor, limit + 1)
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let mut stories = service
+        .get_user_stories(id, auth.user_id, cursor, limit + 1)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, post_id = %id, "failed to list likes");
-            AppError::internal("failed to list likes")
+            tracing::error!(error = ?err, user_id = %id, "failed to get user stories");
+            AppError::internal("failed to get user stories")
         })?;
 
-    let next_cursor = if likes.len() > limit as usize {
-        let last = likes.pop().expect("checked len");
+    let next_cursor = if stories.len() > limit as usize {
+        let last = stories.pop().expect("checked len");
         Some((last.created_at, last.id))
     } else {
         None
     };
+    let next_cursor = encode_cursor(next_cursor, &state.cursor_hmac_key);
 
-    Ok(Json(ListResponse {
-        items: likes,
-        next_cursor: encode_cursor(next_cursor),
-    }))
-}
-
-#[derive(Serialize)]
-pub struct LikeResponse {
-    pub created: bool,
-}
+    let mut headers = HeaderMap::new();
+    let path = format!("/v1/users/{id}/stories");
+    if let Some(link) = build_link_header(&path, next_cursor.as_deref(), limit, had_cursor) {
+        headers.insert(header::LINK, link);
+    }
 
-#[derive(Deserialize)]
-pub struct CommentRequest {
-    pub body: String,
+    Ok((
+        headers,
+        Json(ListResponse {
+            items: stories,
+            next_cursor,
+        }),
+    ))
 }
 
-pub async fn comment_post(
+pub async fn get_story(
     Path(id): Path<Uuid>,
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<CommentRequest>,
-) -> Result<Json<crate::domain::engagement::Comment>, AppError> {
-    const MAX_COMMENT_LEN: usize = 1000;
-
-    if payload.body.trim().is_empty() {
-        return Err(AppError::bad_request("comment body cannot be empty"));
-    }
-    if payload.body.chars().count() > MAX_COMMENT_LEN {
-        return Err(AppError::bad_request("comment body exceeds 1000 characters"));
-    }
-
-    let service = EngagementService::new(state.db.clone());
-    let comment = service
-        .comment_post(auth.user_id, id, payload.body)
+) -> Result<Json<crate::domain::story::Story>, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let story = service
+        .get_story(id, auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, post_id = %id, "failed to comment");
-            AppError::internal("failed to comment")
+            tracing::error!(error = ?err, story_id = %id, "failed to get story");
+            AppError::internal("failed to get story")
         })?;
 
-    Ok(Json(comment))
-}
-
-pub async fn list_post_comments(
-    Path(id): Path<Uuid>,
-    State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::engagement::Comment>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    match story {
+        Some(story) => Ok(Json(story)),
+        None => Err(AppError::not_found("story not found")),
     }
-    let cursor = parse_cursor(query.cursor)?;
-
-    let service = EngagementService::new(state.db.clone());
-    let mut comments = service
-        .list_comments(id, cursor, limit + 1)
-        .await
-        .map_err(|err| {
-            tracing::error!(error = ?err, post_id = %id, "failed to list comments");
-            AppError::internal("failed to list comments")
-        })?;
-
-    let next_cursor = if comments.len() > limit as usize {
-        let last = comments.pop().expect("checked len");
-        Some((last.created_at, last.id))
-    } else {
-        None
-    };
-
-    Ok(Json(ListResponse {
-        items: comments,
-        next_cursor: encode_cursor(next_cursor),
-    }))
 }
 
-pub async fn delete_comment(
-    Path((post_id, comment_id)): Path<(Uuid, Uuid)>,
+pub async fn delete_story(
+    Path(id): Path<Uuid>,
     auth: AuthUser,
     State(state): State<AppState>,
 ) -> Result<StatusCode, AppError> {
-    let service = EngagementService::new(state.db.clone());
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    let delete_activity = federation.build_delete_activity(id, auth.user_id).await.ok().flatten();
+
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
     let deleted = service
-        .delete_comment(comment_id, post_id, auth.user_id)
+        .delete_story(id, auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, comment_id = %comment_id, user_id = %auth.user_id, "failed to delete comment");
-            AppError::internal("failed to delete comment")
+            tracing::error!(error = ?err, story_id = %id, "failed to delete story");
+            AppError::internal("failed to delete story")
         })?;
 
     if deleted {
+        if let Some(activity) = delete_activity {
+            let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+            if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+                tracing::warn!(error = ?err, story_id = %id, "failed to enqueue story Delete activity");
+            }
+        }
+
+        let streaming = StreamingService::new(state.db.clone(), state.cache.clone());
+        if let Err(err) = streaming.publish_story_deleted(id, auth.user_id).await {
+            tracing::warn!(error = ?err, story_id = %id, "failed to publish story deletion to stories feed channel");
+        }
+
+        match service.bump_feed_version_for_followers(auth.user_id).await {
+            Ok(follower_ids) => {
+                state.gossip.broadcast_story_feed_changed(follower_ids).await;
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, story_id = %id, "failed to bump followers' stories feed version");
+            }
+        }
+
         Ok(StatusCode::NO_CONTENT)
     } else {
-        Err(AppError::not_found("comment not found"))
+        Err(AppError::not_found("story not found"))
     }
 }
 
-pub async fn home_feed(
+pub async fn get_story_viewers(
+    Path(id): Path<Uuid>,
     auth: AuthUser,
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::post::Post>>, AppError> {
+) -> Result<(HeaderMap, Json<ListResponse<crate::domain::story::StoryView>>), AppError> {
     let limit = query.limit.unwrap_or(30);
     if !(1..=200).contains(&limit) {
         return Err(AppError::bad_request("limit must be between 1 and 200"));
     }
-    let cursor = parse_cursor(query.cursor)?;
+    let had_cursor = query.cursor.is_some();
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
 
-    let service = FeedService::new(state.db.clone(), state.cache.clone());
-    let (posts, next_cursor) = service
-        .get_home_feed(auth.user_id, cursor, limit)
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+
+    let owner = service
+        .get_story_owner(id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch home feed");
-            AppError::internal("failed to fetch home feed")
+            tracing::error!(error = ?err, story_id = %id, "failed to check story ownership");
+            AppError::internal("failed to get story viewers")
         })?;
 
-    Ok(Json(ListResponse {
-        items: posts,
-        next_cursor: encode_cursor(next_cursor),
-    }))
-}
+    match owner {
+        Some(owner_id) if owner_id == auth.user_id => {}
+        Some(_) => return Err(AppError::forbidden("only the story owner can view viewers")),
+        None => return Err(AppError::not_found("story not found")),
+    }
 
-pub async fn refresh_feed(
-    auth: AuthUser,
-    State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service = FeedService::new(state.db.clone(), state.cache.clone());
-    service
-        .refresh_home_feed(auth.user_id)
+    let mut viewers = service
+        .list_viewers(id, cursor, limit + 1)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to refresh feed");
-            AppError::internal("failed to refresh feed")
+            tracing::error!(error = ?err, story_id = %id, "failed to list story viewers");
+            AppError::internal("failed to list story viewers")
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    let next_cursor = if viewers.len() > limit as usize {
+        let last = viewers.pop().expect("checked len");
+        Some((last.viewed_at, last.viewer_id))
+    } else {
+        None
+    };
+    let next_cursor = encode_cursor(next_cursor, &state.cursor_hmac_key);
+
+    let mut headers = HeaderMap::new();
+    let path = format!("/v1/stories/{id}/viewers");
+    if let Some(link) = build_link_header(&path, next_cursor.as_deref(), limit, had_cursor) {
+        headers.insert(header::LINK, link);
+    }
+
+    Ok((
+        headers,
+        Json(ListResponse {
+            items: viewers,
+            next_cursor,
+        }),
+    ))
 }
 
 #[derive(Deserialize)]
-pub struct UploadRequest {
-    pub content_type: String,
-    pub bytes: i64,
+pub struct AddReactionRequest {
+    pub emoji: String,
 }
 
-pub async fn create_upload(
+pub async fn add_story_reaction(
+    Path(id): Path<Uuid>,
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<UploadRequest>,
-) -> Result<Json<UploadIntent>, AppError> {
-    if payload.bytes <= 0 {
-        return Err(AppError::bad_request("bytes must be greater than 0"));
+    Json(payload): Json<AddReactionRequest>,
+) -> Result<Json<crate::domain::story::StoryReaction>, AppError> {
+    let emoji = payload.emoji.trim();
+    if emoji.is_empty() {
+        return Err(AppError::bad_request("emoji cannot be empty"));
     }
-    if payload.bytes > state.upload_max_bytes {
-        return Err(AppError::bad_request("upload exceeds max size"));
+    if emoji.chars().count() > 7 {
+        return Err(AppError::bad_request("emoji must be at most 7 characters"));
     }
 
-    let service = crate::app::media::MediaService::new(
-        state.db.clone(),
-        state.storage.clone(),
-        state.queue.clone(),
-        state.s3_public_endpoint.clone(),
-    );
-
-    let intent = service
-        .create_upload(
-            auth.user_id,
-            payload.content_type,
-            payload.bytes,
-            state.upload_url_ttl_seconds,
-        )
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    ensure_story_viewable(&service, &state, id, auth.user_id).await?;
+    let reaction = service
+        .add_reaction(id, auth.user_id, emoji.to_string())
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create upload");
-            AppError::bad_request("invalid upload request")
+            if let Some(sqlx_err) = err.downcast_ref::<sqlx::Error>() {
+                if let Some(db_err) = sqlx_err.as_database_error() {
+                    if let Some(code) = db_err.code() {
+                        if code == "23503" {
+                            return AppError::not_found("story not found");
+                        }
+                    }
+                }
+            }
+            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to add reaction");
+            AppError::internal("failed to add reaction")
         })?;
 
-    Ok(Json(intent))
+    match service.get_story_owner(id).await {
+        Ok(Some(recipient_id)) => {
+            let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+            if let Err(err) = notification_service
+                .notify_story_reaction(recipient_id, auth.user_id, id, reaction.is_new)
+                .await
+            {
+                tracing::warn!(error = ?err, story_id = %id, "failed to create story reaction notification");
+            }
+        }
+        Ok(None) => {}
+        Err(err) => {
+            tracing::warn!(error = ?err, story_id = %id, "failed to look up story owner for notification")
+        }
+    }
+
+    Ok(Json(reaction))
 }
 
-pub async fn complete_upload(
-    auth: AuthUser,
+pub async fn list_story_reactions(
     Path(id): Path<Uuid>,
+    auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service = crate::app::media::MediaService::new(
-        state.db.clone(),
-        state.storage.clone(),
-        state.queue.clone(),
-        state.s3_public_endpoint.clone(),
-    );
+    Query(query): Query<PaginationQuery>,
+) -> Result<(HeaderMap, Json<ListResponse<crate::domain::story::StoryReaction>>), AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let had_cursor = query.cursor.is_some();
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
 
-    let queued = service
-        .complete_upload(id, auth.user_id)
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let story = service
+        .get_story(id, auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, upload_id = %id, user_id = %auth.user_id, "failed to complete upload");
-            AppError::internal("failed to complete upload")
+            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to check story visibility");
+            AppError::internal("failed to list story reactions")
         })?;
 
-    if queued {
-        Ok(StatusCode::ACCEPTED)
+    if story.is_none() {
+        return Err(AppError::not_found("story not found"));
+    }
+
+    let mut reactions = service
+        .list_reactions(id, cursor, limit + 1)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, story_id = %id, "failed to list story reactions");
+            AppError::internal("failed to list story reactions")
+        })?;
+
+    let next_cursor = if reactions.len() > limit as usize {
+        let last = reactions.pop().expect("checked len");
+        Some((last.created_at, last.id))
     } else {
-        Err(AppError::not_found("upload not found"))
+        None
+    };
+    let next_cursor = encode_cursor(next_cursor, &state.cursor_hmac_key);
+
+    let mut headers = HeaderMap::new();
+    let path = format!("/v1/stories/{id}/reactions");
+    if let Some(link) = build_link_header(&path, next_cursor.as_deref(), limit, had_cursor) {
+        headers.insert(header::LINK, link);
     }
+
+    Ok((
+        headers,
+        Json(ListResponse {
+            items: reactions,
+            next_cursor,
+        }),
+    ))
 }
 
-pub async fn get_media(
-    auth: AuthUser,
+pub async fn remove_story_reaction(
     Path(id): Path<Uuid>,
+    auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<crate::domain::media::Media>, AppError> {
-    let service = MediaService::new(state.db.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone());
-    let media = service.get_media_for_user(id, auth.user_id).await.map_err(|err| {
-        tracing::error!(error = ?err, media_id = %id, "failed to fetch media");
-        AppError::internal("failed to fetch media")
-    })?;
+) -> Result<StatusCode, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let removed = service
+        .remove_reaction(id, auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to remove reaction");
+            AppError::internal("failed to remove reaction")
+        })?;
 
-    match media {
-        Some(media) => Ok(Json(media)),
-        None => Err(AppError::not_found("media not found")),
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("reaction not found"))
     }
 }
 
-pub async fn get_upload_status(
-    auth: AuthUser,
+pub async fn mark_story_seen(
     Path(id): Path<Uuid>,
+    auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<UploadStatus>, AppError> {
-    let service = MediaService::new(state.db.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone());
-    let status = service
-        .get_upload_status(id, auth.user_id)
+) -> Result<StatusCode, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    ensure_story_viewable(&service, &state, id, auth.user_id).await?;
+    let first_view = service
+        .mark_seen(id, auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, upload_id = %id, user_id = %auth.user_id, "failed to fetch upload status");
-            AppError::internal("failed to fetch upload status")
+            if let Some(sqlx_err) = err.downcast_ref::<sqlx::Error>() {
+                if let Some(db_err) = sqlx_err.as_database_error() {
+                    if let Some(code) = db_err.code() {
+                        if code == "23503" {
+                            return AppError::not_found("story not found");
+                        }
+                    }
+                }
+            }
+            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to mark story seen");
+            AppError::internal("failed to mark story seen")
         })?;
 
-    match status {
-        Some(status) => Ok(Json(status)),
-        None => Err(AppError::not_found("upload not found")),
+    if first_view {
+        match service.get_story_owner(id).await {
+            Ok(Some(recipient_id)) => {
+                let notification_service = NotificationService::new(state.db.clone(), state.cache.clone(), state.queue.clone());
+                if let Err(err) = notification_service
+                    .notify_story_first_view(recipient_id, auth.user_id, id)
+                    .await
+                {
+                    tracing::warn!(error = ?err, story_id = %id, "failed to create story view notification");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, story_id = %id, "failed to look up story owner for notification")
+            }
+        }
     }
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn delete_media(
+pub async fn get_stories_feed(
     auth: AuthUser,
-    Path(id): Path<Uuid>,
     State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service = MediaService::new(state.db.clone(), state.storage.clone(), state.queue.clone(), state.s3_public_endpoint.clone());
-    let deleted = service
-        .delete_media(id, auth.user_id)
+    Query(query): Query<StoriesFeedQuery>,
+) -> Result<(HeaderMap, Json<VersionedListResponse<crate::domain::story::Story>>), AppError> {
+    let limit = query.limit.unwrap_or(30);
+    if !(1..=200).contains(&limit) {
+        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    }
+    let had_cursor = query.cursor.is_some();
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
+
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let (mut stories, version) = service
+        .get_stories_feed(auth.user_id, query.since_version, cursor, limit + 1)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, media_id = %id, user_id = %auth.user_id, "failed to delete media");
-            AppError::internal("failed to delete media")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to get stories feed");
+            AppError::internal("failed to get stories feed")
         })?;
 
-    if deleted {
-        Ok(StatusCode::NO_CONTENT)
+    let next_cursor = if stories.len() > limit as usize {
+        let last = stories.pop().expect("checked len");
+        Some((last.created_at, last.id))
     } else {
-        Err(AppError::not_found("media not found"))
+        None
+    };
+    let next_cursor = encode_cursor(next_cursor, &state.cursor_hmac_key);
+
+    let mut headers = HeaderMap::new();
+    if let Some(link) = build_link_header("/v1/feed/stories", next_cursor.as_deref(), limit, had_cursor) {
+        headers.insert(header::LINK, link);
     }
+
+    Ok((
+        headers,
+        Json(VersionedListResponse {
+            items: stories,
+            next_cursor,
+            version,
+        }),
+    ))
 }
 
-pub async fn list_notifications(
+pub async fn get_hashtag_stories_feed(
+    Path(tag): Path<String>,
     auth: AuthUser,
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::notification::Notification>>, AppError> {
+) -> Result<Json<ListResponse<crate::domain::story::Story>>, AppError> {
     let limit = query.limit.unwrap_or(30);
     if !(1..=200).contains(&limit) {
         return Err(AppError::bad_request("limit must be between 1 and 200"));
     }
-    let cursor = parse_cursor(query.cursor)?;
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
 
-    let service = NotificationService::new(state.db.clone());
-    let mut notifications = service
-        .list(auth.user_id, cursor, limit + 1)
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let mut stories = service
+        .get_hashtag_feed(&tag, auth.user_id, cursor, limit + 1)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to list notifications");
-            AppError::internal("failed to list notifications")
+            tracing::error!(error = ?err, tag = %tag, "failed to get hashtag stories feed");
+            AppError::internal("failed to get hashtag stories feed")
         })?;
 
-    let next_cursor = if notifications.len() > limit as usize {
-        let last = notifications.pop().expect("checked len");
+    let next_cursor = if stories.len() > limit as usize {
+        let last = stories.pop().expect("checked len");
         Some((last.created_at, last.id))
     } else {
         None
     };
 
     Ok(Json(ListResponse {
-        items: notifications,
-        next_cursor: encode_cursor(next_cursor),
+        items: stories,
+        next_cursor: encode_cursor(next_cursor, &state.cursor_hmac_key),
     }))
 }
 
-pub async fn mark_notification_read(
-    auth: AuthUser,
-    Path(id): Path<Uuid>,
+pub async fn get_trending_hashtags(
     State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service = NotificationService::new(state.db.clone());
-    let updated = service
-        .mark_read(id, auth.user_id)
+) -> Result<Json<Vec<crate::domain::story::TrendingHashtag>>, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let trending = service
+        .trending_hashtags(std::time::Duration::from_secs(24 * 3600))
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, notification_id = %id, user_id = %auth.user_id, "failed to mark notification read");
-            AppError::internal("failed to mark notification read")
+            tracing::error!(error = ?err, "failed to get trending hashtags");
+            AppError::internal("failed to get trending hashtags")
         })?;
 
-    if updated {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("notification not found"))
-    }
-}
-
-#[derive(Deserialize)]
-pub struct ModerationRequest {
-    pub reason: Option<String>,
+    Ok(Json(trending))
 }
 
-pub async fn flag_user(
-    auth: AuthUser,
+pub async fn get_story_metrics(
     Path(id): Path<Uuid>,
-    State(state): State<AppState>,
-    Json(payload): Json<ModerationRequest>,
-) -> Result<Json<crate::domain::moderation::UserFlag>, AppError> {
-    let service = ModerationService::new(state.db.clone());
-    let flag = service
-        .flag_user(auth.user_id, id, payload.reason)
-        .await
-        .map_err(|err| {
-            tracing::error!(error = ?err, reporter_id = %auth.user_id, target_id = %id, "failed to flag user");
-            AppError::internal("failed to flag user")
-        })?;
-
-    Ok(Json(flag))
-}
-
-pub async fn takedown_post(
     auth: AuthUser,
-    _admin: AdminToken,
-    Path(id): Path<Uuid>,
     State(state): State<AppState>,
-    Json(payload): Json<ModerationRequest>,
-) -> Result<StatusCode, AppError> {
-    let service = ModerationService::new(state.db.clone());
-    let removed = service
-        .takedown_post(auth.user_id, id, payload.reason)
+) -> Result<Json<crate::domain::story::StoryMetrics>, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let metrics = service
+        .get_metrics(id, auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, actor_id = %auth.user_id, post_id = %id, "failed to takedown post");
-            AppError::internal("failed to takedown post")
+            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to get story metrics");
+            AppError::internal("failed to get story metrics")
         })?;
 
-    if removed {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("post not found"))
+    match metrics {
+        Some(metrics) => Ok(Json(metrics)),
+        None => Err(AppError::not_found("story not found")),
     }
 }
 
-pub async fn takedown_comment(
-    auth: AuthUser,
-    _admin: AdminToken,
+#[derive(Deserialize)]
+pub struct AddToHighlightRequest {
+    pub highlight_name: String,
+}
+
+pub async fn add_story_to_highlight(
     Path(id): Path<Uuid>,
+    auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<ModerationRequest>,
-) -> Result<StatusCode, AppError> {
-    let service = ModerationService::new(state.db.clone());
-    let removed = service
-        .takedown_comment(auth.user_id, id, payload.reason)
+    Json(payload): Json<AddToHighlightRequest>,
+) -> Result<Json<crate::domain::story::StoryHighlight>, AppError> {
+    if payload.highlight_name.trim().is_empty() {
+        return Err(AppError::bad_request("highlight_name cannot be empty"));
+    }
+
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let highlight = service
+        .add_to_highlight(auth.user_id, id, payload.highlight_name)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, actor_id = %auth.user_id, comment_id = %id, "failed to takedown comment");
-            AppError::internal("failed to takedown comment")
+            if err.to_string().contains("story not found") {
+                return AppError::not_found("story not found");
+            }
+            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to add to highlight");
+            AppError::internal("failed to add to highlight")
         })?;
 
-    if removed {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("comment not found"))
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    let job = crate::jobs::federation_sweeper::FederationJob {
+        activity: federation.build_add_to_highlight_activity(&highlight, id),
+        inbox: None,
+    };
+    if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+        tracing::warn!(error = ?err, story_id = %id, highlight_id = %highlight.id, "failed to enqueue highlight Add activity");
     }
+
+    Ok(Json(highlight))
 }
 
-pub async fn list_moderation_audit(
+pub async fn get_user_highlights(
+    Path(id): Path<Uuid>,
     _auth: AuthUser,
-    _admin: AdminToken,
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::moderation::ModerationAction>>, AppError> {
+) -> Result<(HeaderMap, Json<ListResponse<crate::domain::story::StoryHighlight>>), AppError> {
     let limit = query.limit.unwrap_or(30);
     if !(1..=200).contains(&limit) {
         return Err(AppError::bad_request("limit must be between 1 and 200"));
     }
-    let cursor = parse_cursor(query.cursor)?;
+    let had_cursor = query.cursor.is_some();
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
 
-    let service = ModerationService::new(state.db.clone());
-    let mut actions = service
-        .list_audit(cursor, limit + 1)
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let mut highlights = service
+        .get_user_highlights(id, cursor, limit + 1)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to list moderation audit");
-            AppError::internal("failed to list moderation audit")
+            tracing::error!(error = ?err, user_id = %id, "failed to get user highlights");
+            AppError::internal("failed to get user highlights")
         })?;
 
-    let next_cursor = if actions.len() > limit as usize {
-        let last = actions.pop().expect("checked len");
+    let next_cursor = if highlights.len() > limit as usize {
+        let last = highlights.pop().expect("checked len");
         Some((last.created_at, last.id))
     } else {
         None
     };
+    let next_cursor = encode_cursor(next_cursor, &state.cursor_hmac_key);
 
-    Ok(Json(ListResponse {
-        items: actions,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+    let mut headers = HeaderMap::new();
+    let path = format!("/v1/users/{id}/highlights");
+    if let Some(link) = build_link_header(&path, next_cursor.as_deref(), limit, had_cursor) {
+        headers.insert(header::LINK, link);
+    }
+
+    Ok((
+        headers,
+        Json(ListResponse {
+            items: highlights,
+            next_cursor,
+        }),
+    ))
 }
 
 #[derive(Deserialize)]
-pub struct SearchQuery {
-    pub q: String,
-    pub limit: Option<i64>,
-    pub cursor: Option<String>,
+pub struct CreateAudienceListRequest {
+    pub name: String,
 }
 
-pub async fn search_users(
-    _auth: AuthUser,
+pub async fn create_audience_list(
+    auth: AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<SearchQuery>,
-) -> Result<Json<ListResponse<crate::domain::user::PublicUser>>, AppError> {
-    let term = query.q.trim();
-    if term.len() < 2 {
-        return Err(AppError::bad_request("q must be at least 2 characters"));
-    }
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
+    Json(payload): Json<CreateAudienceListRequest>,
+) -> Result<Json<crate::domain::audience_list::AudienceList>, AppError> {
+    if payload.name.trim().is_empty() {
+        return Err(AppError::bad_request("name cannot be empty"));
     }
-    let cursor = parse_cursor(query.cursor)?;
 
-    let service = SearchService::new(state.db.clone());
-    let mut users = service
-        .search_users(term, cursor, limit + 1)
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let list = service
+        .create_audience_list(auth.user_id, payload.name)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to search users");
-            AppError::internal("failed to search users")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create audience list");
+            AppError::internal("failed to create audience list")
         })?;
 
-    let next_cursor = if users.len() > limit as usize {
-        let last = users.pop().expect("checked len");
-        Some((last.created_at, last.id))
-    } else {
-        None
-    };
+    Ok(Json(list))
+}
 
-    let items = users.into_iter().map(crate::domain::user::PublicUser::from).collect();
+pub async fn get_user_audience_lists(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<crate::domain::audience_list::AudienceList>>, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let lists = service
+        .get_user_audience_lists(auth.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to list audience lists");
+            AppError::internal("failed to list audience lists")
+        })?;
 
-    Ok(Json(ListResponse {
-        items,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+    Ok(Json(lists))
 }
 
-pub async fn search_posts(
-    _auth: AuthUser,
+pub async fn delete_audience_list(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<SearchQuery>,
-) -> Result<Json<ListResponse<crate::domain::post::Post>>, AppError> {
-    let term = query.q.trim();
-    if term.len() < 2 {
-        return Err(AppError::bad_request("q must be at least 2 characters"));
-    }
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
-    }
-    let cursor = parse_cursor(query.cursor)?;
-
-    let service = SearchService::new(state.db.clone());
-    let mut posts = service
-        .search_posts(term, cursor, limit + 1)
+) -> Result<StatusCode, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let deleted = service
+        .delete_audience_list(auth.user_id, id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to search posts");
-            AppError::internal("failed to search posts")
+            tracing::error!(error = ?err, list_id = %id, user_id = %auth.user_id, "failed to delete audience list");
+            AppError::internal("failed to delete audience list")
         })?;
 
-    let next_cursor = if posts.len() > limit as usize {
-        let last = posts.pop().expect("checked len");
-        Some((last.created_at, last.id))
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
     } else {
-        None
-    };
-
-    Ok(Json(ListResponse {
-        items: posts,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+        Err(AppError::not_found("audience list not found"))
+    }
 }
 
+#[derive(Deserialize)]
+pub struct AddListMemberRequest {
+    pub kind: crate::domain::audience_list::AudienceListMemberKind,
+    pub user_id: Option<Uuid>,
+    pub pattern: Option<String>,
+}
 
-// ============================================================================
-// Safety & Anti-Abuse Handlers
-// ============================================================================
+pub async fn add_list_member(
+    Path(id): Path<Uuid>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<AddListMemberRequest>,
+) -> Result<Json<crate::domain::audience_list::AudienceListMember>, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let member = service
+        .add_list_member(auth.user_id, id, payload.kind, payload.user_id, payload.pattern)
+        .await
+        .map_err(|err| {
+            if err.to_string().contains("audience list not found") {
+                return AppError::not_found("audience list not found");
+            }
+            if err.to_string().contains("does not belong") {
+                return AppError::forbidden("audience list does not belong to you");
+            }
+            if err.to_string().contains("is required") {
+                return AppError::bad_request(err.to_string());
+            }
+            tracing::error!(error = ?err, list_id = %id, user_id = %auth.user_id, "failed to add list member");
+            AppError::internal("failed to add list member")
+        })?;
 
-// Trust Score Handlers
+    Ok(Json(member))
+}
 
-#[derive(Serialize)]
-pub struct TrustScoreResponse {
-    pub user_id: String,
-    pub trust_level: i32,
-    pub trust_level_name: String,
-    pub trust_points: i32,
-    pub account_age_days: i32,
-    pub posts_count: i32,
-    pub followers_count: i32,
-    pub strikes: i32,
-    pub is_banned: bool,
+pub async fn remove_list_member(
+    Path((id, member_id)): Path<(Uuid, Uuid)>,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let service =
+        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+    let removed = service
+        .remove_list_member(auth.user_id, id, member_id)
+        .await
+        .map_err(|err| {
+            if err.to_string().contains("audience list not found") {
+                return AppError::not_found("audience list not found");
+            }
+            if err.to_string().contains("does not belong") {
+                return AppError::forbidden("audience list does not belong to you");
+            }
+            tracing::error!(error = ?err, list_id = %id, member_id = %member_id, user_id = %auth.user_id, "failed to remove list member");
+            AppError::internal("failed to remove list member")
+        })?;
+
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found("list member not found"))
+    }
 }
 
-pub async fn get_trust_score(
+pub async fn opaque_register_start(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<TrustScoreResponse>, AppError> {
-    let trust_service = crate::app::trust::TrustService::new(state.db.clone());
-    let score = trust_service
-        .get_trust_score(auth.user_id)
+    Json(payload): Json<crate::domain::opaque::OpaqueRegisterStartRequest>,
+) -> Result<Json<crate::domain::opaque::OpaqueRegisterStartResponse>, AppError> {
+    let service = crate::app::opaque::OpaqueService::new(state.db.clone(), state.cache.clone());
+    let (handle, registration_response) = service
+        .register_start(
+            auth.user_id,
+            &payload.identifier,
+            &payload.registration_request,
+        )
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to fetch trust score");
-            AppError::internal("failed to fetch trust score")
-        })?
-        .ok_or_else(|| AppError::not_found("trust score not found"))?;
-
-    let trust_level_name = match score.trust_level {
-        crate::config::rate_limits::TrustLevel::New => "New",
-        crate::config::rate_limits::TrustLevel::Basic => "Basic",
-        crate::config::rate_limits::TrustLevel::Trusted => "Trusted",
-        crate::config::rate_limits::TrustLevel::Verified => "Verified",
-    };
+            if err.to_string().contains("not valid base64")
+                || err.to_string().contains("malformed registration_request")
+            {
+                return AppError::bad_request(err.to_string());
+            }
+            tracing::error!(error = ?err, "failed to start opaque registration");
+            AppError::internal("failed to start registration")
+        })?;
 
-    Ok(Json(TrustScoreResponse {
-        user_id: score.user_id.to_string(),
-        trust_level: score.trust_level as i32,
-        trust_level_name: trust_level_name.to_string(),
-        trust_points: score.trust_points,
-        account_age_days: score.account_age_days,
-        posts_count: score.posts_count,
-        followers_count: score.followers_count,
-        strikes: score.strikes,
-        is_banned: score.banned_until.map(|until| until > time::OffsetDateTime::now_utc()).unwrap_or(false),
+    Ok(Json(crate::domain::opaque::OpaqueRegisterStartResponse {
+        handle,
+        registration_response,
     }))
 }
 
-// Rate Limit Handlers
-
-#[derive(Serialize)]
-pub struct RateLimitsResponse {
-    pub trust_level: String,
-    pub posts_per_hour: u32,
-    pub posts_per_day: u32,
-    pub follows_per_hour: u32,
-    pub follows_per_day: u32,
-    pub likes_per_hour: u32,
-    pub comments_per_hour: u32,
-    pub remaining: RemainingQuotas,
-}
+pub async fn opaque_register_finish(
+    _auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::opaque::OpaqueRegisterFinishRequest>,
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::opaque::OpaqueService::new(state.db.clone(), state.cache.clone());
+    service
+        .register_finish(&payload.handle, &payload.registration_upload)
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("handle not found or expired") {
+                return AppError::not_found(message);
+            }
+            if message.contains("not valid base64") || message.contains("malformed registration_upload")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to finish opaque registration");
+            AppError::internal("failed to finish registration")
+        })?;
 
-#[derive(Serialize)]
-pub struct RemainingQuotas {
-    pub posts: u32,
-    pub follows: u32,
-    pub likes: u32,
-    pub comments: u32,
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn get_rate_limits(
-    auth: AuthUser,
+pub async fn opaque_login_start(
     State(state): State<AppState>,
-) -> Result<Json<RateLimitsResponse>, AppError> {
-    let trust_service = crate::app::trust::TrustService::new(state.db.clone());
-    let score = trust_service
-        .get_trust_score(auth.user_id)
+    Json(payload): Json<crate::domain::opaque::OpaqueLoginStartRequest>,
+) -> Result<Json<crate::domain::opaque::OpaqueLoginStartResponse>, AppError> {
+    let service = crate::app::opaque::OpaqueService::new(state.db.clone(), state.cache.clone());
+    let (handle, credential_response) = service
+        .login_start(&payload.identifier, &payload.credential_request)
         .await
-        .map_err(|_| AppError::internal("failed to fetch trust score"))?
-        .ok_or_else(|| AppError::not_found("trust score not found"))?;
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("no OPAQUE registration for that identifier") {
+                return AppError::unauthorized("invalid credentials");
+            }
+            if message.contains("not valid base64") || message.contains("malformed credential_request")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to start opaque login");
+            AppError::internal("failed to start login")
+        })?;
 
-    let trust_level = score.trust_level;
-    let limits = crate::config::rate_limits::RateLimits::for_trust_level(trust_level);
+    Ok(Json(crate::domain::opaque::OpaqueLoginStartResponse {
+        handle,
+        credential_response,
+    }))
+}
 
-    let rate_limiter = crate::app::rate_limiter::RateLimiter::new(state.cache.clone());
+pub async fn opaque_login_finish(
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::opaque::OpaqueLoginFinishRequest>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let opaque_service =
+        crate::app::opaque::OpaqueService::new(state.db.clone(), state.cache.clone());
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
 
-    let remaining_posts = rate_limiter
-        .get_remaining(auth.user_id, "post", trust_level)
-        .await
-        .unwrap_or(0);
-    let remaining_follows = rate_limiter
-        .get_remaining(auth.user_id, "follow", trust_level)
-        .await
-        .unwrap_or(0);
-    let remaining_likes = rate_limiter
-        .get_remaining(auth.user_id, "like", trust_level)
-        .await
-        .unwrap_or(0);
-    let remaining_comments = rate_limiter
-        .get_remaining(auth.user_id, "comment", trust_level)
+    let tokens = opaque_service
+        .login_finish(
+            &payload.handle,
+            &payload.credential_finalization,
+            &auth_service,
+        )
         .await
-        .unwrap_or(0);
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("handshake not found or expired") {
+                return AppError::not_found(message);
+            }
+            if message.contains("mutual authentication failed") {
+                return AppError::unauthorized("invalid credentials");
+            }
+            if message.contains("not valid base64")
+                || message.contains("malformed credential_finalization")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to finish opaque login");
+            AppError::internal("failed to finish login")
+        })?;
 
-    Ok(Json(RateLimitsResponse {
-        trust_level: format!("{:?}", trust_level),
-        posts_per_hour: limits.posts_per_hour,
-        posts_per_day: limits.posts_per_day,
-        follows_per_hour: limits.follows_per_hour,
-        follows_per_day: limits.follows_per_day,
-        likes_per_hour: limits.likes_per_hour,
-        comments_per_hour: limits.comments_per_hour,
-        remaining: RemainingQuotas {
-            posts: remaining_posts,
-            follows: remaining_follows,
-            likes: remaining_likes,
-            comments: remaining_comments,
-        },
+    Ok(Json(AuthTokenResponse {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        access_expires_at: tokens.access_expires_at,
+        refresh_expires_at: tokens.refresh_expires_at,
     }))
 }
 
-// Device Fingerprint Handlers
+pub async fn webauthn_registration_begin(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::webauthn::WebauthnChallengeResponse>, AppError> {
+    let service = crate::app::webauthn::WebauthnService::new(state.db.clone(), state.cache.clone());
+    let (handle, challenge) = service.begin_registration(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to start passkey registration");
+        AppError::internal("failed to start passkey registration")
+    })?;
 
-#[derive(Deserialize)]
-pub struct RegisterFingerprintRequest {
-    pub fingerprint: String,
+    Ok(Json(crate::domain::webauthn::WebauthnChallengeResponse {
+        handle,
+        challenge,
+    }))
 }
 
-pub async fn register_device_fingerprint(
-    auth: Option<AuthUser>,
+pub async fn webauthn_registration_finish(
+    _auth: AuthUser,
     State(state): State<AppState>,
-    headers: axum::http::HeaderMap,
-    Json(payload): Json<RegisterFingerprintRequest>,
+    Json(payload): Json<crate::domain::webauthn::RegisterPasskeyRequest>,
 ) -> Result<StatusCode, AppError> {
-    let service = crate::app::fingerprint::FingerprintService::new(state.db.clone());
-
-    let user_agent = headers
-        .get(axum::http::header::USER_AGENT)
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+    let service = crate::app::webauthn::WebauthnService::new(state.db.clone(), state.cache.clone());
+    service
+        .finish_registration(
+            &payload.handle,
+            &payload.credential_id,
+            &payload.public_key,
+            &payload.client_data_json,
+        )
+        .await
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("handle not found or expired") {
+                return AppError::not_found(message);
+            }
+            if message.contains("not valid base64")
+                || message.contains("malformed public_key")
+                || message.contains("malformed client_data_json")
+                || message.contains("does not match the expected challenge")
+                || message.contains("unexpected client_data_json type")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to finish passkey registration");
+            AppError::internal("failed to finish passkey registration")
+        })?;
 
-    let fingerprint_hash = crate::app::fingerprint::FingerprintService::hash_fingerprint(&payload.fingerprint);
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // Check if device is blocked
-    let (risk_score, is_blocked) = service
-        .check_device_risk(&fingerprint_hash)
+pub async fn webauthn_authentication_begin(
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::webauthn::AuthenticatePasskeyStartRequest>,
+) -> Result<Json<crate::domain::webauthn::WebauthnChallengeResponse>, AppError> {
+    let service = crate::app::webauthn::WebauthnService::new(state.db.clone(), state.cache.clone());
+    let (handle, challenge) = service
+        .begin_authentication(&payload.identifier)
         .await
-        .map_err(|_| AppError::internal("failed to check device risk"))?;
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("no account for that identifier") {
+                return AppError::unauthorized("invalid credentials");
+            }
+            tracing::error!(error = ?err, "failed to start passkey authentication");
+            AppError::internal("failed to start passkey authentication")
+        })?;
 
-    if is_blocked {
-        return Err(AppError::forbidden("This device has been blocked"));
-    }
+    Ok(Json(crate::domain::webauthn::WebauthnChallengeResponse {
+        handle,
+        challenge,
+    }))
+}
 
-    if risk_score > 80 {
-        let user_id_log = match &auth {
-            Some(auth_user) => format!("user_id={}", auth_user.user_id),
-            None => "unauthenticated".to_string(),
-        };
-        tracing::warn!(
-            user_id = user_id_log,
-            fingerprint_hash = &fingerprint_hash[..8],
-            risk_score = risk_score,
-            "High-risk device detected"
-        );
-    }
+pub async fn webauthn_authentication_finish(
+    State(state): State<AppState>,
+    Json(payload): Json<crate::domain::webauthn::AuthenticatePasskeyFinishRequest>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let webauthn_service =
+        crate::app::webauthn::WebauthnService::new(state.db.clone(), state.cache.clone());
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
 
-    // Register fingerprint - user_id is optional for unauthenticated registration
-    let user_id = auth.map(|a| a.user_id);
-    service
-        .register_fingerprint(fingerprint_hash, user_id, user_agent)
+    let tokens = webauthn_service
+        .finish_authentication(
+            &payload.handle,
+            &payload.credential_id,
+            &payload.authenticator_data,
+            &payload.client_data_json,
+            &payload.signature,
+            &auth_service,
+        )
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to register fingerprint");
-            AppError::internal("failed to register device")
+            let message = err.to_string();
+            if message.contains("handle not found or expired") {
+                return AppError::not_found(message);
+            }
+            if message.contains("possible cloned credential")
+                || message.contains("assertion signature verification failed")
+                || message.contains("unknown credential for that account")
+            {
+                return AppError::unauthorized("invalid credentials");
+            }
+            if message.contains("not valid base64")
+                || message.contains("malformed")
+                || message.contains("does not match the expected challenge")
+                || message.contains("unexpected client_data_json type")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to finish passkey authentication");
+            AppError::internal("failed to finish passkey authentication")
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(AuthTokenResponse {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        access_expires_at: tokens.access_expires_at,
+        refresh_expires_at: tokens.refresh_expires_at,
+    }))
 }
 
-#[derive(Serialize)]
-pub struct DeviceResponse {
-    pub fingerprint_hash: String,
-    pub account_count: i32,
-    pub risk_score: i32,
-    pub is_blocked: bool,
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TotpEnrollResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
 }
 
-pub async fn list_user_devices(
+/// Starts (or restarts) TOTP enrollment for the caller. The secret isn't
+/// live until `totp_confirm` proves the authenticator app is in sync with
+/// it, so calling this again before confirming just replaces the pending
+/// secret.
+pub async fn totp_enroll(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<Vec<DeviceResponse>>, AppError> {
-    let service = crate::app::fingerprint::FingerprintService::new(state.db.clone());
+) -> Result<Json<TotpEnrollResponse>, AppError> {
+    let service = crate::app::totp::TotpService::new(state.db.clone(), state.cache.clone());
+    let (secret, otpauth_uri) = service.enroll_totp(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to start totp enrollment");
+        AppError::internal("failed to start totp enrollment")
+    })?;
 
-    let devices = service
-        .get_user_devices(auth.user_id)
+    Ok(Json(TotpEnrollResponse {
+        secret,
+        otpauth_uri,
+    }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TotpConfirmRequest {
+    pub code: String,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TotpConfirmResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Confirms a pending TOTP enrollment, which from here on gates `login` for
+/// this account, and returns the one-time recovery codes -- shown to the
+/// user exactly once, since only their argon2 hash is kept afterward.
+pub async fn totp_confirm(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<TotpConfirmRequest>,
+) -> Result<Json<TotpConfirmResponse>, AppError> {
+    let service = crate::app::totp::TotpService::new(state.db.clone(), state.cache.clone());
+    let recovery_codes = service
+        .confirm_totp(auth.user_id, &payload.code)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to fetch devices");
-            AppError::internal("failed to fetch devices")
+            let message = err.to_string();
+            if message.contains("no pending totp enrollment") {
+                return AppError::bad_request(message);
+            }
+            if message.contains("invalid totp code") {
+                return AppError::unauthorized(message);
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to confirm totp enrollment");
+            AppError::internal("failed to confirm totp enrollment")
         })?;
 
-    let response = devices
-        .into_iter()
-        .map(|device| DeviceResponse {
-            fingerprint_hash: device.fingerprint_hash[..16].to_string(), // Truncate for privacy
-            account_count: device.account_count,
-            risk_score: device.risk_score,
-            is_blocked: device.is_blocked,
-        })
-        .collect();
-
-    Ok(Json(response))
+    Ok(Json(TotpConfirmResponse { recovery_codes }))
 }
 
-// Invite Handlers
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TotpLoginRequest {
+    pub handle: String,
+    pub code: String,
+}
 
-pub async fn list_invites(
-    auth: AuthUser,
+/// Second half of a TOTP-gated login (see `AuthService::login`'s
+/// `LoginOutcome::TotpRequired`): verify the code against the challenged
+/// account and mint the token pair that `login` withheld.
+pub async fn totp_login(
     State(state): State<AppState>,
-) -> Result<Json<Vec<crate::app::invites::InviteCode>>, AppError> {
-    let service = crate::app::invites::InviteService::new(state.db.clone());
+    Json(payload): Json<TotpLoginRequest>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let totp_service = crate::app::totp::TotpService::new(state.db.clone(), state.cache.clone());
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
 
-    let invites = service
-        .list_user_invites(auth.user_id)
+    let tokens = totp_service
+        .complete_login(&payload.handle, &payload.code, &auth_service)
         .await
-        .map_err(|err| {
-            tracing::error!(error = ?err, "failed to list invites");
-            AppError::internal("failed to list invites")
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("handle not found or expired") {
+                return AppError::not_found(message);
+            }
+            if message.contains("invalid totp code") {
+                return AppError::unauthorized("invalid credentials");
+            }
+            tracing::error!(error = ?err, "failed to complete totp login");
+            AppError::internal("failed to complete totp login")
         })?;
 
-    Ok(Json(invites))
+    Ok(Json(AuthTokenResponse {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        access_expires_at: tokens.access_expires_at,
+        refresh_expires_at: tokens.refresh_expires_at,
+    }))
 }
 
 #[derive(Deserialize)]
-pub struct CreateInviteRequest {
-    #[serde(default = "default_days_valid")]
-    pub days_valid: i64,
-}
-
-fn default_days_valid() -> i64 {
-    7
+pub struct LinkFarcasterRequest {
+    pub fid: i64,
+    pub custody_address: String,
+    pub message: String,
+    pub signature: String,
 }
 
-pub async fn create_invite(
+pub async fn link_farcaster_identity(
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<CreateInviteRequest>,
-) -> Result<Json<crate::app::invites::InviteCode>, AppError> {
-    if payload.days_valid < 1 || payload.days_valid > 30 {
-        return Err(AppError::bad_request("days_valid must be between 1 and 30"));
-    }
-
-    let service = crate::app::invites::InviteService::new(state.db.clone());
-
-    let invite = service
-        .create_invite(auth.user_id, payload.days_valid)
+    Json(payload): Json<LinkFarcasterRequest>,
+) -> Result<Json<crate::domain::external_identity::ExternalIdentityLink>, AppError> {
+    let service = crate::app::external_identity::ExternalIdentityService::new(state.db.clone());
+    let link = service
+        .link_farcaster(
+            auth.user_id,
+            crate::app::external_identity::FarcasterProof {
+                fid: payload.fid,
+                custody_address: &payload.custody_address,
+                message: &payload.message,
+                signature_hex: &payload.signature,
+            },
+        )
         .await
         .map_err(|err| {
-            if err.to_string().contains("Maximum invite limit") {
-                AppError::forbidden(&err.to_string())
-            } else {
-                tracing::error!(error = ?err, "failed to create invite");
-                AppError::internal("failed to create invite")
+            let message = err.to_string();
+            if message == "fid_taken" {
+                return AppError::conflict("that Farcaster FID is already linked to another account")
+                    .with_code(crate::http::ErrorCode::FidTaken);
+            }
+            if message.contains("does not match")
+                || message.contains("not valid base64")
+                || message.contains("not valid hex")
+                || message.contains("signature must be")
+                || message.contains("does not reference")
+            {
+                return AppError::bad_request(message);
             }
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to link farcaster identity");
+            AppError::internal("failed to link farcaster identity")
         })?;
 
-    Ok(Json(invite))
+    Ok(Json(link))
 }
 
-pub async fn get_invite_stats(
+pub async fn unlink_farcaster_identity(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<crate::app::invites::InviteStats>, AppError> {
-    let service = crate::app::invites::InviteService::new(state.db.clone());
+) -> Result<StatusCode, AppError> {
+    let service = crate::app::external_identity::ExternalIdentityService::new(state.db.clone());
+    service
+        .unlink_external_account(
+            auth.user_id,
+            crate::domain::external_identity::ExternalIdentityKind::Farcaster,
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to unlink farcaster identity");
+            AppError::internal("failed to unlink farcaster identity")
+        })?;
 
-    let stats = service
-        .get_invite_stats(auth.user_id)
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct LinkEthereumIdentityRequest {
+    pub message: String,
+    pub signature: String,
+}
+
+pub async fn link_ethereum_identity(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<LinkEthereumIdentityRequest>,
+) -> Result<Json<crate::domain::external_identity::ExternalIdentityLink>, AppError> {
+    let service = crate::app::external_identity::ExternalIdentityService::new(state.db.clone());
+    let link = service
+        .link_ethereum(auth.user_id, &payload.message, &payload.signature)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to fetch invite stats");
-            AppError::internal("failed to fetch invite stats")
+            let message = err.to_string();
+            if message == "address_taken" {
+                return AppError::conflict("that Ethereum address is already linked to another account")
+                    .with_code(crate::http::ErrorCode::AddressTaken);
+            }
+            if message.contains("does not match")
+                || message.contains("malformed")
+                || message.contains("missing")
+                || message.contains("expired")
+                || message.contains("not valid hex")
+                || message.contains("signature must be")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to link ethereum identity");
+            AppError::internal("failed to link ethereum identity")
         })?;
 
-    Ok(Json(stats))
+    Ok(Json(link))
 }
 
-pub async fn revoke_invite(
+pub async fn unlink_ethereum_identity(
     auth: AuthUser,
-    Path(code): Path<String>,
     State(state): State<AppState>,
 ) -> Result<StatusCode, AppError> {
-    let service = crate::app::invites::InviteService::new(state.db.clone());
-
-    let revoked = service
-        .revoke_invite(&code, auth.user_id)
+    let service = crate::app::external_identity::ExternalIdentityService::new(state.db.clone());
+    service
+        .unlink_external_account(
+            auth.user_id,
+            crate::domain::external_identity::ExternalIdentityKind::Ethereum,
+        )
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, "failed to revoke invite");
-            AppError::internal("failed to revoke invite")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to unlink ethereum identity");
+            AppError::internal("failed to unlink ethereum identity")
         })?;
 
-    if revoked {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("invite code not found or already used"))
-    }
+    Ok(StatusCode::NO_CONTENT)
 }
 
-// ============================================================================
-// Story Handlers
-// ============================================================================
+/// First leg of OAuth/OIDC login: hand the client an authorize URL + PKCE
+/// `state` to redirect the user-agent to.
+pub async fn start_oauth_login(
+    Path(provider): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<crate::domain::oauth::OAuthAuthorizeResponse>, AppError> {
+    let response = state.oauth.start(&provider).await.map_err(|err| {
+        let message = err.to_string();
+        if message.contains("unknown oauth provider") {
+            return AppError::not_found(message);
+        }
+        tracing::error!(error = ?err, provider = %provider, "failed to start oauth login");
+        AppError::internal("failed to start oauth login")
+    })?;
 
-#[derive(Deserialize)]
-pub struct CreateStoryRequest {
-    pub media_id: Uuid,
-    pub caption: Option<String>,
-    pub visibility: crate::domain::story::StoryVisibility,
+    Ok(Json(response))
 }
 
-pub async fn create_story(
-    auth: AuthUser,
+/// Second leg: exchange the authorization code and either log the user into
+/// their existing linked account or provision a new one (consuming an
+/// invite, same as password signup).
+pub async fn oauth_callback(
+    Path(provider): Path<String>,
     State(state): State<AppState>,
-    Json(payload): Json<CreateStoryRequest>,
-) -> Result<Json<crate::domain::story::Story>, AppError> {
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let story = service
-        .create_story(auth.user_id, payload.media_id, payload.caption, payload.visibility)
+    Query(query): Query<crate::domain::oauth::OAuthCallbackQuery>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+
+    let tokens = state
+        .oauth
+        .complete_login(
+            &provider,
+            &query.code,
+            &query.state,
+            query.invite_code,
+            &state.invite_signing_key,
+            &auth_service,
+        )
         .await
         .map_err(|err| {
-            if err.to_string().contains("media not found") {
-                return AppError::not_found("media not found");
+            let message = err.to_string();
+            if message.contains("unknown oauth provider")
+                || message.contains("state not found or expired")
+                || message.contains("state does not match provider")
+            {
+                return AppError::bad_request(message);
             }
-            if err.to_string().contains("does not belong") {
-                return AppError::forbidden("media does not belong to you");
+            if message.contains("revoked") {
+                return AppError::invite_revoked(message);
             }
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create story");
-            AppError::internal("failed to create story")
+            if message.contains("expired") {
+                return AppError::invite_expired(message);
+            }
+            if message.contains("fully used") {
+                return AppError::invite_consumed(message);
+            }
+            if message.contains("invite_code is required")
+                || message.contains("invite code")
+                || message.contains("Invite code")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, provider = %provider, "failed to complete oauth login");
+            AppError::internal("failed to complete oauth login")
         })?;
 
-    Ok(Json(story))
+    Ok(Json(AuthTokenResponse {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        access_expires_at: tokens.access_expires_at,
+        refresh_expires_at: tokens.refresh_expires_at,
+    }))
 }
 
-pub async fn get_user_stories(
-    Path(id): Path<Uuid>,
+pub async fn link_oauth_identity(
     auth: AuthUser,
+    Path(provider): Path<String>,
     State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::story::Story>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
-    }
-    let cursor = parse_cursor(query.cursor)?;
-
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let mut stories = service
-        .get_user_stories(id, auth.user_id, cursor, limit + 1)
+    Json(payload): Json<crate::domain::oauth::LinkOAuthRequest>,
+) -> Result<Json<crate::domain::oauth::OAuthIdentity>, AppError> {
+    let identity = state
+        .oauth
+        .link_identity(auth.user_id, &provider, &payload.code, &payload.state)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %id, "failed to get user stories");
-            AppError::internal("failed to get user stories")
+            let message = err.to_string();
+            if message.contains("already linked to a different account") {
+                return AppError::conflict(message).with_code(crate::http::ErrorCode::OauthIdentityTaken);
+            }
+            if message.contains("unknown oauth provider")
+                || message.contains("state not found or expired")
+                || message.contains("state does not match provider")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, provider = %provider, "failed to link oauth identity");
+            AppError::internal("failed to link oauth identity")
         })?;
 
-    let next_cursor = if stories.len() > limit as usize {
-        let last = stories.pop().expect("checked len");
-        Some((last.created_at, last.id))
-    } else {
-        None
-    };
+    Ok(Json(identity))
+}
 
-    Ok(Json(ListResponse {
-        items: stories,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+// --- OAuth2 authorization server ---
+//
+// Distinct from the social-login flow above (`start_oauth_login`/
+// `oauth_callback`, where *we* are the client of an external provider):
+// these let a registered third-party/first-party client obtain a scoped
+// `TokenPair` for one of our users via the standard authorization-code +
+// PKCE grant, without ever seeing that user's password.
+
+#[derive(Deserialize)]
+pub struct RegisterOauthClientRequest {
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Vec<String>,
 }
 
-pub async fn get_story(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
+#[derive(Serialize)]
+pub struct RegisterOauthClientResponse {
+    pub client_id: String,
+    /// Shown exactly once -- only its hash is stored, the same discipline
+    /// as a refresh token.
+    pub client_secret: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Vec<String>,
+}
+
+/// Register a new OAuth2 client. Operator-facing, same gate as the other
+/// `/admin/*` resource-creation endpoints.
+pub async fn admin_register_oauth_client(
+    _admin: AdminToken,
     State(state): State<AppState>,
-) -> Result<Json<crate::domain::story::Story>, AppError> {
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let story = service
-        .get_story(id, auth.user_id)
+    Json(payload): Json<RegisterOauthClientRequest>,
+) -> Result<Json<RegisterOauthClientResponse>, AppError> {
+    let allowed_scopes = payload
+        .allowed_scopes
+        .iter()
+        .map(|raw| {
+            crate::app::auth::Scope::from_str(raw)
+                .ok_or_else(|| AppError::bad_request(format!("unknown scope: {}", raw)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let service = crate::app::oauth_server::OAuthServerService::new(state.db.clone());
+    let (client, client_secret) = service
+        .register_client(&payload.name, payload.redirect_uris, allowed_scopes)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, "failed to get story");
-            AppError::internal("failed to get story")
+            let message = err.to_string();
+            if message.contains("is required") {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to register oauth client");
+            AppError::internal("failed to register oauth client")
         })?;
 
-    match story {
-        Some(story) => Ok(Json(story)),
-        None => Err(AppError::not_found("story not found")),
-    }
+    Ok(Json(RegisterOauthClientResponse {
+        client_id: client.client_id,
+        client_secret,
+        name: client.name,
+        redirect_uris: client.redirect_uris,
+        allowed_scopes: client
+            .allowed_scopes
+            .iter()
+            .map(|scope| scope.as_str().to_string())
+            .collect(),
+    }))
 }
 
-pub async fn delete_story(
-    Path(id): Path<Uuid>,
+#[derive(Deserialize)]
+pub struct OauthAuthorizeRequest {
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: Vec<String>,
+    /// S256 PKCE challenge the client derived from its `code_verifier`.
+    pub code_challenge: String,
+}
+
+#[derive(Serialize)]
+pub struct OauthAuthorizeResponse {
+    pub code: String,
+}
+
+/// Mint an authorization code for the logged-in caller, granting `client_id`
+/// the requested (client-permitted) scopes. In a browser-based consent flow
+/// this would follow a UI where the user approves the client; this endpoint
+/// is the API equivalent -- the bearer token in `Authorization` *is* the
+/// user's approval.
+pub async fn oauth_authorize(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<StatusCode, AppError> {
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let deleted = service
-        .delete_story(id, auth.user_id)
+    Json(payload): Json<OauthAuthorizeRequest>,
+) -> Result<Json<OauthAuthorizeResponse>, AppError> {
+    let scopes = payload
+        .scope
+        .iter()
+        .map(|raw| {
+            crate::app::auth::Scope::from_str(raw)
+                .ok_or_else(|| AppError::bad_request(format!("unknown scope: {}", raw)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let service = crate::app::oauth_server::OAuthServerService::new(state.db.clone());
+    let code = service
+        .issue_authorization_code(
+            &payload.client_id,
+            auth.user_id,
+            &payload.redirect_uri,
+            &scopes,
+            &payload.code_challenge,
+        )
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, "failed to delete story");
-            AppError::internal("failed to delete story")
+            let message = err.to_string();
+            if message.contains("unknown oauth client")
+                || message.contains("redirect_uri does not match")
+                || message.contains("not permitted to request scope")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to issue oauth authorization code");
+            AppError::internal("failed to issue oauth authorization code")
         })?;
 
-    if deleted {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("story not found"))
-    }
+    Ok(Json(OauthAuthorizeResponse { code }))
 }
 
-pub async fn get_story_viewers(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
-    State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::story::StoryView>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
-    }
-    let cursor = parse_cursor(query.cursor)?;
-
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
+#[derive(Deserialize)]
+pub struct OauthTokenRequest {
+    pub client_id: String,
+    pub client_secret: String,
+    pub code: String,
+    pub redirect_uri: String,
+    pub code_verifier: String,
+}
 
-    let owner = service
-        .get_story_owner(id)
+/// Exchange an authorization code for a `TokenPair`, the token endpoint of
+/// the authorization-code + PKCE grant. Unauthenticated like `/auth/login`
+/// and `/auth/refresh` -- the client credentials and PKCE verifier are the
+/// proof of authorization here, not a bearer token.
+pub async fn oauth_token(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<OauthTokenRequest>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let oauth_server = crate::app::oauth_server::OAuthServerService::new(state.db.clone());
+    let (user_id, scopes) = oauth_server
+        .exchange_code(
+            &payload.client_id,
+            &payload.client_secret,
+            &payload.code,
+            &payload.redirect_uri,
+            &payload.code_verifier,
+        )
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, "failed to check story ownership");
-            AppError::internal("failed to get story viewers")
+            let message = err.to_string();
+            if message.contains("invalid client credentials")
+                || message.contains("invalid authorization code")
+                || message.contains("authorization code already used")
+                || message.contains("authorization code has expired")
+                || message.contains("authorization code was not issued to this client")
+                || message.contains("redirect_uri does not match")
+                || message.contains("code_verifier does not match")
+            {
+                return AppError::bad_request(message);
+            }
+            tracing::error!(error = ?err, "failed to exchange oauth authorization code");
+            AppError::internal("failed to exchange oauth authorization code")
         })?;
 
-    match owner {
-        Some(owner_id) if owner_id == auth.user_id => {}
-        Some(_) => return Err(AppError::forbidden("only the story owner can view viewers")),
-        None => return Err(AppError::not_found("story not found")),
-    }
-
-    let mut viewers = service
-        .list_viewers(id, cursor, limit + 1)
+    let device = device_context(
+        &state,
+        addr,
+        &headers,
+        Some(format!("oauth-client:{}", payload.client_id)),
+    );
+    let auth_service = AuthService::new(
+        state.db.clone(),
+        state.cache.clone(),
+        state.paseto_access_keys.clone(),
+        state.paseto_refresh_keys.clone(),
+        state.access_ttl_minutes,
+        state.refresh_ttl_days,
+        state.account_recovery_grace_days,
+        state.ldap.clone(),
+    );
+    let tokens = auth_service
+        .issue_token_pair(user_id, &scopes, &device)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, "failed to list story viewers");
-            AppError::internal("failed to list story viewers")
+            tracing::error!(error = ?err, user_id = %user_id, "failed to issue tokens for oauth code exchange");
+            AppError::internal("failed to issue tokens")
         })?;
 
-    let next_cursor = if viewers.len() > limit as usize {
-        let last = viewers.pop().expect("checked len");
-        Some((last.viewed_at, last.viewer_id))
-    } else {
-        None
-    };
+    Ok(Json(AuthTokenResponse {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        access_expires_at: tokens.access_expires_at,
+        refresh_expires_at: tokens.refresh_expires_at,
+    }))
+}
+
+// --- Federation (ActivityPub) ---
+//
+// Unauthenticated, server-to-server endpoints: WebFinger handle resolution,
+// actor documents, and the signed inbox. Unlike the rest of the API these
+// are mounted unversioned at their fixed, spec-required paths (see
+// `http::routes::federation`).
+
+#[derive(Deserialize)]
+pub struct WebfingerQuery {
+    pub resource: String,
+}
+
+pub async fn webfinger(
+    State(state): State<AppState>,
+    Query(query): Query<WebfingerQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let acct = query
+        .resource
+        .strip_prefix("acct:")
+        .ok_or_else(|| AppError::bad_request("resource must be an acct: URI"))?;
+    let (handle, _domain) = acct
+        .split_once('@')
+        .ok_or_else(|| AppError::bad_request("resource must be acct:handle@domain"))?;
+
+    let user_service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let user = user_service
+        .get_user_by_handle(handle)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, handle = %handle, "failed to resolve handle for webfinger");
+            AppError::internal("failed to resolve handle")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
 
-    Ok(Json(ListResponse {
-        items: viewers,
-        next_cursor: encode_cursor(next_cursor),
-    }))
-}
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    let document = federation
+        .webfinger(&query.resource, &user)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, resource = %query.resource, "failed to build webfinger response");
+            AppError::internal("failed to build webfinger response")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
 
-#[derive(Deserialize)]
-pub struct AddReactionRequest {
-    pub emoji: String,
+    Ok(Json(document))
 }
 
-pub async fn add_story_reaction(
+pub async fn get_actor(
     Path(id): Path<Uuid>,
-    auth: AuthUser,
     State(state): State<AppState>,
-    Json(payload): Json<AddReactionRequest>,
-) -> Result<Json<crate::domain::story::StoryReaction>, AppError> {
-    let emoji = payload.emoji.trim();
-    if emoji.is_empty() {
-        return Err(AppError::bad_request("emoji cannot be empty"));
-    }
-    if emoji.chars().count() > 7 {
-        return Err(AppError::bad_request("emoji must be at most 7 characters"));
-    }
+) -> Result<Json<serde_json::Value>, AppError> {
+    let user_service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let user = user_service
+        .get_user(None, id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %id, "failed to fetch user for actor document");
+            AppError::internal("failed to fetch user")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
 
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let reaction = service
-        .add_reaction(id, auth.user_id, emoji.to_string())
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    let public_key_pem = federation
+        .actor_public_key_pem(id)
         .await
         .map_err(|err| {
-            if let Some(sqlx_err) = err.downcast_ref::<sqlx::Error>() {
-                if let Some(db_err) = sqlx_err.as_database_error() {
-                    if let Some(code) = db_err.code() {
-                        if code == "23503" {
-                            return AppError::not_found("story not found");
-                        }
-                    }
-                }
-            }
-            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to add reaction");
-            AppError::internal("failed to add reaction")
-        })?;
+            tracing::error!(error = ?err, user_id = %id, "failed to fetch actor public key");
+            AppError::internal("failed to fetch actor public key")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
 
-    Ok(Json(reaction))
+    Ok(Json(federation.actor_document(&user, &public_key_pem)))
 }
 
-pub async fn list_story_reactions(
+pub async fn get_outbox(
     Path(id): Path<Uuid>,
-    auth: AuthUser,
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::story::StoryReaction>>, AppError> {
+) -> Result<Json<serde_json::Value>, AppError> {
     let limit = query.limit.unwrap_or(30);
     if !(1..=200).contains(&limit) {
         return Err(AppError::bad_request("limit must be between 1 and 200"));
     }
-    let cursor = parse_cursor(query.cursor)?;
-
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let story = service
-        .get_story(id, auth.user_id)
-        .await
-        .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to check story visibility");
-            AppError::internal("failed to list story reactions")
-        })?;
-
-    if story.is_none() {
-        return Err(AppError::not_found("story not found"));
-    }
+    let cursor = parse_cursor(query.cursor, &state.cursor_hmac_key)?;
 
-    let mut reactions = service
-        .list_reactions(id, cursor, limit + 1)
+    let post_service = PostService::new(state.db.clone());
+    let mut posts = post_service
+        .list_by_user(id, None, cursor, limit + 1)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, "failed to list story reactions");
-            AppError::internal("failed to list story reactions")
+            tracing::error!(error = ?err, user_id = %id, "failed to list posts for outbox");
+            AppError::internal("failed to list posts")
         })?;
 
-    let next_cursor = if reactions.len() > limit as usize {
-        let last = reactions.pop().expect("checked len");
+    let next_cursor = if posts.len() > limit as usize {
+        let last = posts.pop().expect("checked len");
         Some((last.created_at, last.id))
     } else {
         None
     };
 
-    Ok(Json(ListResponse {
-        items: reactions,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+    Ok(Json(federation.outbox(
+        id,
+        &posts,
+        encode_cursor(next_cursor, &state.cursor_hmac_key),
+    )))
 }
 
-pub async fn remove_story_reaction(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
+/// `POST /users/:id/inbox`. `id` identifies which local actor the activity
+/// was addressed to, but verification and dispatch don't otherwise depend
+/// on it -- `ingest_activity` resolves everything it needs (the followee,
+/// the post owner) from the activity body itself once the signature checks
+/// out.
+pub async fn post_inbox(
+    Path(_id): Path<Uuid>,
     State(state): State<AppState>,
+    verified: crate::http::auth::VerifiedActivity,
 ) -> Result<StatusCode, AppError> {
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let removed = service
-        .remove_reaction(id, auth.user_id)
+    let federation = crate::app::federation::FederationService::new(
+        state.db.clone(),
+        state.federation_base_url.clone(),
+    );
+
+    let reply = federation
+        .ingest_activity(&verified.actor_uri, &verified.activity)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to remove reaction");
-            AppError::internal("failed to remove reaction")
+            tracing::warn!(error = ?err, actor_uri = %verified.actor_uri, "failed to ingest inbound activity");
+            AppError::bad_request(err.to_string())
         })?;
 
-    if removed {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(AppError::not_found("reaction not found"))
+    // e.g. the `Accept` owed back for a `Follow` -- queued the same way
+    // `create_story` queues its own outbound activities, addressed
+    // directly at the inbox `ingest_activity` resolved it owes the reply
+    // to.
+    if let Some(reply) = reply {
+        let job = crate::jobs::federation_sweeper::FederationJob {
+            activity: reply.activity,
+            inbox: Some(reply.inbox),
+        };
+        if let Err(err) = state.queue.enqueue_federation_job(&job).await {
+            tracing::warn!(error = ?err, actor_uri = %verified.actor_uri, "failed to enqueue federation reply activity");
+        }
     }
+
+    Ok(StatusCode::ACCEPTED)
 }
 
-pub async fn mark_story_seen(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
+// --- Federation (Lumine-to-Lumine social edges) ---
+//
+// A second, unauthenticated server-to-server surface alongside the
+// ActivityPub one above: Lumine instances address each other's users as
+// `handle@host` and exchange signed Follow/Block edge events directly,
+// rather than through ActivityPub's actor/inbox model. See
+// `app::social_federation`.
+
+#[derive(Serialize)]
+pub struct ServerKeyResponse {
+    pub host: String,
+    pub public_key: String,
+}
+
+/// `GET /federation/v1/server-key`. Peers fetch and cache this to verify
+/// the `X-Lumine-Server-Signature` header on our outbound edge pushes (see
+/// `app::social_federation::ServerKeyClient::fetch_public_key`).
+pub async fn server_key(State(state): State<AppState>) -> Result<Json<ServerKeyResponse>, AppError> {
+    let host = url::Url::parse(&state.federation_base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .ok_or_else(|| AppError::internal("federation_base_url has no host"))?;
+    let public_key = crate::app::social_federation::server_public_key(&state.server_signing_key);
+    Ok(Json(ServerKeyResponse {
+        host,
+        public_key: base64::engine::general_purpose::STANDARD.encode(public_key.to_bytes()),
+    }))
+}
+
+/// `POST /federation/v1/social-edges`. `VerifiedSocialEdge` has already
+/// checked the ed25519 signature against the claimed origin server's
+/// published key before this runs.
+pub async fn receive_social_edge(
     State(state): State<AppState>,
+    verified: crate::http::auth::VerifiedSocialEdge,
 ) -> Result<StatusCode, AppError> {
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    service
-        .mark_seen(id, auth.user_id)
+    let (handle, host) = verified
+        .event
+        .target
+        .split_once('@')
+        .ok_or_else(|| AppError::bad_request("target must be handle@host"))?;
+    let our_host = url::Url::parse(&state.federation_base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string));
+    if our_host.as_deref() != Some(host) {
+        return Err(AppError::bad_request("edge event is not addressed to this server"));
+    }
+
+    let user_service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let local_user = user_service
+        .get_user_by_handle(handle)
         .await
         .map_err(|err| {
-            if let Some(sqlx_err) = err.downcast_ref::<sqlx::Error>() {
-                if let Some(db_err) = sqlx_err.as_database_error() {
-                    if let Some(code) = db_err.code() {
-                        if code == "23503" {
-                            return AppError::not_found("story not found");
-                        }
-                    }
-                }
-            }
-            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to mark story seen");
-            AppError::internal("failed to mark story seen")
+            tracing::error!(error = ?err, handle = %handle, "failed to resolve social edge target handle");
+            AppError::internal("failed to resolve target handle")
+        })?
+        .ok_or_else(|| AppError::not_found("target handle not found"))?;
+
+    let social_federation =
+        crate::app::social_federation::SocialFederationService::new(state.db.clone(), state.federation_base_url.clone());
+    social_federation
+        .apply_remote_edge(&verified.event, local_user.id)
+        .await
+        .map_err(|err| {
+            tracing::warn!(error = ?err, actor = %verified.event.actor, "failed to apply inbound social edge");
+            AppError::internal("failed to apply social edge")
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::ACCEPTED)
 }
 
-pub async fn get_stories_feed(
+#[derive(Deserialize)]
+pub struct RemoteEdgeRequest {
+    /// The remote actor this edge is directed at, as `handle@host`.
+    pub remote_actor: String,
+}
+
+#[derive(Serialize)]
+pub struct RemoteEdgeResponse {
+    pub queued: bool,
+}
+
+async fn push_remote_edge(
+    state: &AppState,
+    auth_user_id: Uuid,
+    event_type: crate::app::social_federation::EdgeEventType,
+    remote_actor: &str,
+) -> Result<(), AppError> {
+    let user_service = UserService::new(
+        state.db.clone(),
+        state.user_cache.clone(),
+        state.account_recovery_grace_days,
+    );
+    let local_user = user_service
+        .get_user(None, auth_user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, user_id = %auth_user_id, "failed to fetch user for remote edge push");
+            AppError::internal("failed to fetch user")
+        })?
+        .ok_or_else(|| AppError::not_found("user not found"))?;
+
+    let social_federation =
+        crate::app::social_federation::SocialFederationService::new(state.db.clone(), state.federation_base_url.clone());
+    let event = social_federation
+        .build_event(event_type, &local_user.handle, remote_actor, OffsetDateTime::now_utc().unix_timestamp())
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to build outbound social edge event");
+            AppError::internal("failed to build social edge event")
+        })?;
+    social_federation
+        .push_edge_event(&state.queue, &state.server_signing_key, &event)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, remote_actor = %remote_actor, "failed to queue outbound social edge push");
+            AppError::internal("failed to queue social edge push")
+        })
+}
+
+/// `POST /v1/users/remote-follow`. Fires the Lumine-to-Lumine signed push
+/// notifying `remote_actor`'s home server that the caller now follows it.
+/// Unlike `follow_user`, there's no local row recording "I follow this
+/// remote actor" yet -- that bookkeeping, plus WebFinger discovery of
+/// `remote_actor` in the first place, belongs to the ActivityPub follow
+/// federation work building on this.
+pub async fn follow_remote_user(
     auth: AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::story::Story>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
-    }
-    let cursor = parse_cursor(query.cursor)?;
+    Json(body): Json<RemoteEdgeRequest>,
+) -> Result<Json<RemoteEdgeResponse>, AppError> {
+    push_remote_edge(
+        &state,
+        auth.user_id,
+        crate::app::social_federation::EdgeEventType::FollowCreated,
+        &body.remote_actor,
+    )
+    .await?;
+    Ok(Json(RemoteEdgeResponse { queued: true }))
+}
 
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let mut stories = service
-        .get_stories_feed(auth.user_id, cursor, limit + 1)
+pub async fn unfollow_remote_user(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(body): Json<RemoteEdgeRequest>,
+) -> Result<Json<RemoteEdgeResponse>, AppError> {
+    push_remote_edge(
+        &state,
+        auth.user_id,
+        crate::app::social_federation::EdgeEventType::FollowRemoved,
+        &body.remote_actor,
+    )
+    .await?;
+    Ok(Json(RemoteEdgeResponse { queued: true }))
+}
+
+/// `POST /v1/users/remote-block`. Records the block locally (so it survives
+/// independent of peer acknowledgment) and pushes `BlockCreated` to the
+/// remote actor's home server -- the blocker's home server is authoritative
+/// per `app::social_federation`'s doc comment, so the remote side enforces
+/// this purely from the pushed event.
+pub async fn block_remote_user(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(body): Json<RemoteEdgeRequest>,
+) -> Result<Json<RemoteEdgeResponse>, AppError> {
+    let social_federation =
+        crate::app::social_federation::SocialFederationService::new(state.db.clone(), state.federation_base_url.clone());
+    social_federation
+        .record_outbound_block(auth.user_id, &body.remote_actor)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to get stories feed");
-            AppError::internal("failed to get stories feed")
+            tracing::error!(error = ?err, "failed to record outbound remote block");
+            AppError::internal("failed to record remote block")
         })?;
 
-    let next_cursor = if stories.len() > limit as usize {
-        let last = stories.pop().expect("checked len");
-        Some((last.created_at, last.id))
-    } else {
-        None
-    };
-
-    Ok(Json(ListResponse {
-        items: stories,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+    push_remote_edge(
+        &state,
+        auth.user_id,
+        crate::app::social_federation::EdgeEventType::BlockCreated,
+        &body.remote_actor,
+    )
+    .await?;
+    Ok(Json(RemoteEdgeResponse { queued: true }))
 }
 
-pub async fn get_story_metrics(
-    Path(id): Path<Uuid>,
+pub async fn unblock_remote_user(
     auth: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<crate::domain::story::StoryMetrics>, AppError> {
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let metrics = service
-        .get_metrics(id, auth.user_id)
+    Json(body): Json<RemoteEdgeRequest>,
+) -> Result<Json<RemoteEdgeResponse>, AppError> {
+    let social_federation =
+        crate::app::social_federation::SocialFederationService::new(state.db.clone(), state.federation_base_url.clone());
+    social_federation
+        .remove_outbound_block(auth.user_id, &body.remote_actor)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to get story metrics");
-            AppError::internal("failed to get story metrics")
+            tracing::error!(error = ?err, "failed to remove outbound remote block");
+            AppError::internal("failed to remove remote block")
         })?;
 
-    match metrics {
-        Some(metrics) => Ok(Json(metrics)),
-        None => Err(AppError::not_found("story not found")),
-    }
+    push_remote_edge(
+        &state,
+        auth.user_id,
+        crate::app::social_federation::EdgeEventType::BlockRemoved,
+        &body.remote_actor,
+    )
+    .await?;
+    Ok(Json(RemoteEdgeResponse { queued: true }))
 }
 
+// --- Bot/application accounts -----------------------------------------
+//
+// Long-lived bearer credentials an integration authenticates with instead
+// of a human login flow (see `app::bots::BotService` and
+// `http::AuthUser::from_request_parts`'s fallback onto it).
+
 #[derive(Deserialize)]
-pub struct AddToHighlightRequest {
-    pub highlight_name: String,
+pub struct CreateBotRequest {
+    #[serde(default)]
+    pub public: bool,
+    pub interactions_url: Option<String>,
 }
 
-pub async fn add_story_to_highlight(
-    Path(id): Path<Uuid>,
-    auth: AuthUser,
-    State(state): State<AppState>,
-    Json(payload): Json<AddToHighlightRequest>,
-) -> Result<Json<crate::domain::story::StoryHighlight>, AppError> {
-    if payload.highlight_name.trim().is_empty() {
-        return Err(AppError::bad_request("highlight_name cannot be empty"));
+#[derive(Serialize)]
+pub struct BotResponse {
+    pub id: Uuid,
+    pub public: bool,
+    pub interactions_url: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl From<crate::domain::bot::Bot> for BotResponse {
+    fn from(bot: crate::domain::bot::Bot) -> Self {
+        Self {
+            id: bot.id,
+            public: bot.public,
+            interactions_url: bot.interactions_url,
+            created_at: bot.created_at,
+        }
     }
+}
 
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let highlight = service
-        .add_to_highlight(auth.user_id, id, payload.highlight_name)
+#[derive(Serialize)]
+pub struct BotCreatedResponse {
+    #[serde(flatten)]
+    pub bot: BotResponse,
+    /// Shown exactly once -- only its hash is stored, the same discipline
+    /// as an OAuth client secret (`RegisterOauthClientResponse`).
+    pub token: String,
+}
+
+/// Create a bot owned by the caller.
+pub async fn create_bot(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateBotRequest>,
+) -> Result<Json<BotCreatedResponse>, AppError> {
+    let service = crate::app::bots::BotService::new(state.db.clone());
+    let (bot, token) = service
+        .create_bot(auth.user_id, payload.public, payload.interactions_url)
         .await
         .map_err(|err| {
-            if err.to_string().contains("story not found") {
-                return AppError::not_found("story not found");
-            }
-            tracing::error!(error = ?err, story_id = %id, user_id = %auth.user_id, "failed to add to highlight");
-            AppError::internal("failed to add to highlight")
+            tracing::error!(error = ?err, user_id = %auth.user_id, "failed to create bot");
+            AppError::internal("failed to create bot")
         })?;
 
-    Ok(Json(highlight))
+    Ok(Json(BotCreatedResponse {
+        bot: bot.into(),
+        token,
+    }))
 }
 
-pub async fn get_user_highlights(
-    Path(id): Path<Uuid>,
-    _auth: AuthUser,
+/// List the bots the caller owns.
+pub async fn list_bots(
+    auth: AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<PaginationQuery>,
-) -> Result<Json<ListResponse<crate::domain::story::StoryHighlight>>, AppError> {
-    let limit = query.limit.unwrap_or(30);
-    if !(1..=200).contains(&limit) {
-        return Err(AppError::bad_request("limit must be between 1 and 200"));
-    }
-    let cursor = parse_cursor(query.cursor)?;
+) -> Result<Json<Vec<BotResponse>>, AppError> {
+    let service = crate::app::bots::BotService::new(state.db.clone());
+    let bots = service.list_bots(auth.user_id).await.map_err(|err| {
+        tracing::error!(error = ?err, user_id = %auth.user_id, "failed to list bots");
+        AppError::internal("failed to list bots")
+    })?;
 
-    let service =
-        crate::app::stories::StoryService::new(state.db.clone(), state.cache.clone());
-    let mut highlights = service
-        .get_user_highlights(id, cursor, limit + 1)
+    Ok(Json(bots.into_iter().map(BotResponse::from).collect()))
+}
+
+#[derive(Serialize)]
+pub struct RotateBotTokenResponse {
+    pub token: String,
+}
+
+/// Mint a fresh bearer token for one of the caller's bots, invalidating the
+/// old one.
+pub async fn rotate_bot_token(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<RotateBotTokenResponse>, AppError> {
+    let service = crate::app::bots::BotService::new(state.db.clone());
+    let token = service
+        .rotate_token(bot_id, auth.user_id)
         .await
         .map_err(|err| {
-            tracing::error!(error = ?err, user_id = %id, "failed to get user highlights");
-            AppError::internal("failed to get user highlights")
+            let message = err.to_string();
+            if message.contains("not found") {
+                return AppError::not_found(message);
+            }
+            tracing::error!(error = ?err, user_id = %auth.user_id, bot_id = %bot_id, "failed to rotate bot token");
+            AppError::internal("failed to rotate bot token")
         })?;
 
-    let next_cursor = if highlights.len() > limit as usize {
-        let last = highlights.pop().expect("checked len");
-        Some((last.created_at, last.id))
-    } else {
-        None
-    };
-
-    Ok(Json(ListResponse {
-        items: highlights,
-        next_cursor: encode_cursor(next_cursor),
-    }))
+    Ok(Json(RotateBotTokenResponse { token }))
 }
@@ -2,10 +2,13 @@ use axum::extract::{Request, State};
 use axum::middleware::Next;
 use axum::response::Response;
 
+use crate::app::device_list::DeviceListService;
 use crate::app::trust::TrustService;
 use crate::http::{AppError, AuthUser};
 use crate::AppState;
 
+const DEVICE_ID_HEADER: &str = "x-device-id";
+
 /// Global ban check for authenticated requests.
 pub async fn ban_check_middleware(
     State(state): State<AppState>,
@@ -13,7 +16,7 @@ pub async fn ban_check_middleware(
     request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    if let Some(auth_user) = auth {
+    if let Some(auth_user) = &auth {
         let trust_service = TrustService::new(state.db.clone());
         let is_banned = trust_service
             .is_banned(auth_user.user_id)
@@ -24,11 +27,38 @@ pub async fn ban_check_middleware(
             })?;
 
         if is_banned {
-            return Err(AppError::forbidden(
+            return Err(AppError::banned(
                 "Your account has been temporarily suspended",
             ));
         }
     }
 
+    if let (Some(auth_user), Some(device_id)) = (
+        &auth,
+        request
+            .headers()
+            .get(DEVICE_ID_HEADER)
+            .and_then(|v| v.to_str().ok()),
+    ) {
+        let device_list_service = DeviceListService::new(state.db.clone());
+        let device_list = device_list_service
+            .get_device_list(auth_user.user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!(error = ?err, "failed to check device list");
+                AppError::internal("failed to check device list")
+            })?;
+
+        // No device list on file yet means the account hasn't opted into
+        // signed device management; don't penalize it for that.
+        if let Some(device_list) = device_list {
+            if !device_list.contains(device_id) {
+                return Err(AppError::forbidden(
+                    "This device has been removed from the account",
+                ));
+            }
+        }
+    }
+
     Ok(next.run(request).await)
 }
@@ -0,0 +1,132 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use axum::http::HeaderMap;
+
+/// Resolve the real client address for `peer` (the socket's immediate peer).
+///
+/// If `peer` is not one of `trusted_proxies`, it's trusted as-is: there's no
+/// proxy in front of us to have rewritten it. Otherwise we walk the
+/// `X-Forwarded-For`/`Forwarded` chain from the right (the hop closest to
+/// us) leftward, skipping entries that are themselves trusted proxies, and
+/// return the first untrusted hop -- the same "first untrusted IP, counted
+/// from the proxy side" resolution used by nginx's `realip` and AWS's ALB.
+pub fn resolve_client_ip(
+    peer: IpAddr,
+    headers: &HeaderMap,
+    trusted_proxies: &[(IpAddr, u8)],
+) -> IpAddr {
+    if !is_trusted(peer, trusted_proxies) {
+        return peer;
+    }
+
+    let Some(chain) = extract_forwarded_chain(headers) else {
+        return peer;
+    };
+
+    for hop in chain.iter().rev() {
+        if !is_trusted(*hop, trusted_proxies) {
+            return *hop;
+        }
+    }
+
+    // Every hop (including the claimed origin) is a trusted proxy, e.g. an
+    // internal health check. Fall back to the left-most (originating) entry.
+    chain.first().copied().unwrap_or(peer)
+}
+
+/// The subnet `ip` belongs to, as a stable bucket key, using `prefix_v4` for
+/// IPv4 addresses and `prefix_v6` for IPv6 addresses.
+pub fn subnet_bucket(ip: IpAddr, prefix_v4: u8, prefix_v6: u8) -> String {
+    match ip {
+        IpAddr::V4(addr) => {
+            let prefix = prefix_v4.min(32);
+            let bits = u32::from(addr);
+            let mask: u32 = if prefix == 0 { 0 } else { !0u32 << (32 - prefix) };
+            let network = Ipv4Addr::from(bits & mask);
+            format!("{}/{}", network, prefix)
+        }
+        IpAddr::V6(addr) => {
+            let prefix = prefix_v6.min(128);
+            let bits = u128::from(addr);
+            let mask: u128 = if prefix == 0 { 0 } else { !0u128 << (128 - prefix) };
+            let network = Ipv6Addr::from(bits & mask);
+            format!("{}/{}", network, prefix)
+        }
+    }
+}
+
+fn is_trusted(ip: IpAddr, trusted_proxies: &[(IpAddr, u8)]) -> bool {
+    trusted_proxies
+        .iter()
+        .any(|(network, prefix)| cidr_contains(*network, *prefix, ip))
+}
+
+fn cidr_contains(network: IpAddr, prefix: u8, ip: IpAddr) -> bool {
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let prefix = prefix.min(32);
+            let mask: u32 = if prefix == 0 { 0 } else { !0u32 << (32 - prefix) };
+            (u32::from(network) & mask) == (u32::from(ip) & mask)
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let prefix = prefix.min(128);
+            let mask: u128 = if prefix == 0 { 0 } else { !0u128 << (128 - prefix) };
+            (u128::from(network) & mask) == (u128::from(ip) & mask)
+        }
+        _ => false,
+    }
+}
+
+/// The forwarding chain in left-to-right (original client first) order, read
+/// from `X-Forwarded-For` if present, else the standardized `Forwarded`
+/// header. Unparsable entries are dropped rather than failing the whole
+/// header, since a single malformed hop shouldn't defeat the rest.
+fn extract_forwarded_chain(headers: &HeaderMap) -> Option<Vec<IpAddr>> {
+    if let Some(value) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+        let chain: Vec<IpAddr> = value.split(',').filter_map(parse_address_token).collect();
+        if !chain.is_empty() {
+            return Some(chain);
+        }
+    }
+
+    if let Some(value) = headers.get("forwarded").and_then(|v| v.to_str().ok()) {
+        let chain: Vec<IpAddr> = value
+            .split(',')
+            .filter_map(|segment| {
+                segment
+                    .split(';')
+                    .find_map(|part| part.trim().strip_prefix("for="))
+            })
+            .filter_map(parse_address_token)
+            .collect();
+        if !chain.is_empty() {
+            return Some(chain);
+        }
+    }
+
+    None
+}
+
+/// Parse one forwarded-for token, which may be a bare address, an
+/// address:port pair, a bracketed IPv6 literal, or a quoted RFC 7239 value.
+fn parse_address_token(token: &str) -> Option<IpAddr> {
+    let token = token.trim().trim_matches('"');
+
+    if let Some(rest) = token.strip_prefix('[') {
+        let addr_part = rest.split(']').next()?;
+        return addr_part.parse().ok();
+    }
+
+    if let Ok(ip) = token.parse::<IpAddr>() {
+        return Some(ip);
+    }
+
+    // "1.2.3.4:5678" -- an IPv4 address with a port.
+    if let Some((ip_part, _port)) = token.rsplit_once(':') {
+        if let Ok(ip) = ip_part.parse::<IpAddr>() {
+            return Some(ip);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,148 @@
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+use crate::app::trust::TrustService;
+use crate::config::rate_limits::{RateLimits, TrustLevel};
+use crate::http::{AppError, AuthUser};
+use crate::AppState;
+
+const ACQUIRE_TIMEOUT: Duration = Duration::from_millis(500);
+const IDLE_GC_AFTER_SECONDS: i64 = 600;
+const GC_INTERVAL: Duration = Duration::from_secs(120);
+
+struct UserSlots {
+    semaphore: Arc<Semaphore>,
+    last_used_unix: AtomicI64,
+}
+
+/// Per-user concurrency limiter bounding simultaneous in-flight requests for
+/// costly actions (`media`, `search`, `feed/refresh`), independent of the
+/// frequency-based `RateLimiter`/`DeferredRateLimiter`.
+#[derive(Clone)]
+pub struct ConcurrencyLimiter {
+    slots: Arc<DashMap<Uuid, Arc<UserSlots>>>,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new() -> Self {
+        let limiter = Self {
+            slots: Arc::new(DashMap::new()),
+        };
+        limiter.spawn_gc_loop();
+        limiter
+    }
+
+    /// Periodically drop semaphores for users with no recent activity and no
+    /// permits currently held, so the map doesn't grow without bound.
+    fn spawn_gc_loop(&self) {
+        let slots = self.slots.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = now_unix();
+                slots.retain(|_, user_slots| {
+                    let idle_seconds = now - user_slots.last_used_unix.load(Ordering::Relaxed);
+                    idle_seconds < IDLE_GC_AFTER_SECONDS
+                        || Arc::strong_count(&user_slots.semaphore) > 1
+                });
+            }
+        });
+    }
+
+    /// Acquire a permit for `user_id`, sized by `trust_level`. Returns `None`
+    /// if no permit became free within `ACQUIRE_TIMEOUT`.
+    async fn acquire(&self, user_id: Uuid, trust_level: TrustLevel) -> Option<OwnedSemaphorePermit> {
+        let max_permits = RateLimits::for_trust_level(trust_level).concurrent_request_slots as usize;
+
+        let user_slots = self
+            .slots
+            .entry(user_id)
+            .or_insert_with(|| {
+                Arc::new(UserSlots {
+                    semaphore: Arc::new(Semaphore::new(max_permits.max(1))),
+                    last_used_unix: AtomicI64::new(now_unix()),
+                })
+            })
+            .clone();
+        user_slots.last_used_unix.store(now_unix(), Ordering::Relaxed);
+
+        tokio::time::timeout(ACQUIRE_TIMEOUT, user_slots.semaphore.clone().acquire_owned())
+            .await
+            .ok()
+            .and_then(|res| res.ok())
+    }
+}
+
+fn now_unix() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Bound simultaneous in-flight requests per user for costly actions, on top
+/// of the frequency-based rate limiting in `rate_limit_middleware`. The
+/// permit is held for the lifetime of the request and released (dropped)
+/// once the handler returns, on every path including errors.
+pub async fn concurrency_limit_middleware(
+    State(state): State<AppState>,
+    auth: Option<AuthUser>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let path = request.uri().path();
+    let method = request.method();
+
+    let action = match (path, method.as_str()) {
+        (p, _) if p.starts_with("/media") => Some("media"),
+        (p, _) if p.starts_with("/search/") => Some("search"),
+        ("/feed/refresh", "POST") => Some("feed/refresh"),
+        _ => None,
+    };
+
+    let Some(action) = action else {
+        return Ok(next.run(request).await);
+    };
+    let Some(auth_user) = auth else {
+        return Ok(next.run(request).await);
+    };
+
+    let trust_service = TrustService::new(state.db.clone());
+    let trust_score = trust_service
+        .get_trust_score(auth_user.user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = ?err, "failed to check trust score");
+            AppError::internal("failed to check trust score")
+        })?;
+    let trust_level = trust_score.map(|s| s.trust_level).unwrap_or(TrustLevel::New);
+
+    let permit = state
+        .concurrency_limiter
+        .acquire(auth_user.user_id, trust_level)
+        .await;
+
+    let Some(permit) = permit else {
+        tracing::debug!(
+            user_id = %auth_user.user_id,
+            action = action,
+            "concurrency limit exceeded"
+        );
+        return Err(AppError::concurrency_limited(
+            "Too many concurrent requests in flight for your account. Please retry shortly.",
+        ));
+    };
+
+    let response = next.run(request).await;
+    drop(permit);
+    Ok(response)
+}
@@ -0,0 +1,4 @@
+pub mod ban;
+pub mod client_ip;
+pub mod concurrency;
+pub mod rate_limit;
@@ -5,7 +5,8 @@ use std::net::SocketAddr;
 
 use crate::app::rate_limiter::RateLimiter;
 use crate::app::trust::TrustService;
-use crate::config::rate_limits::{RateWindow, TrustLevel};
+use crate::config::rate_limits::{LimitType, RateWindow, TrustLevel};
+use crate::http::middleware::client_ip::{resolve_client_ip, subnet_bucket};
 use crate::http::{AppError, AuthUser};
 use crate::AppState;
 
@@ -19,60 +20,71 @@ pub async fn rate_limit_middleware(
     let path = request.uri().path();
     let method = request.method();
 
-    // Determine action type from path and method
-    let action = match (path, method.as_str()) {
+    // Determine the limit type from path and method
+    let limit_type = match (path, method.as_str()) {
         (p, "POST") if p.starts_with("/posts") && !p.contains("/comment") && !p.contains("/like") => {
-            Some("post")
+            Some(LimitType::PostCreate)
         }
-        (p, "POST") if p.contains("/follow") && !p.contains("/unfollow") => Some("follow"),
-        (p, "POST") if p.contains("/unfollow") => Some("unfollow"),
-        (p, "POST") if p.contains("/like") => Some("like"),
-        (p, "POST") if p.contains("/comment") => Some("comment"),
-        ("/feed", "GET") | ("/feed/stories", "GET") => Some("feed"),
-        ("/feed/refresh", "POST") => Some("feed"),
-        (p, _) if p.starts_with("/notifications") => Some("notifications"),
-        (p, _) if p.starts_with("/search/") => Some("search"),
-        (p, _) if p.starts_with("/media") => Some("media"),
-        (p, _) if p.starts_with("/moderation/") => Some("moderation"),
+        (p, "POST") if p.contains("/follow") && !p.contains("/unfollow") => Some(LimitType::Follow),
+        (p, "POST") if p.contains("/unfollow") => Some(LimitType::Unfollow),
+        (p, "POST") if p.contains("/like") => Some(LimitType::Like),
+        (p, "POST") if p.contains("/comment") => Some(LimitType::Comment),
+        ("/feed", "GET") | ("/feed/stories", "GET") => Some(LimitType::Feed),
+        ("/feed/refresh", "POST") => Some(LimitType::Feed),
+        (p, _) if p.starts_with("/notifications") => Some(LimitType::Notifications),
+        (p, _) if p.starts_with("/search/") => Some(LimitType::Search),
+        (p, _) if p.starts_with("/media") => Some(LimitType::MediaUpload),
+        (p, _) if p.starts_with("/moderation/") => Some(LimitType::Moderation),
         _ => None,
     };
 
-    if let Some(action) = action {
+    if let Some(limit_type) = limit_type {
         if let Some(auth_user) = auth {
-            // Get trust level
-            let trust_service = TrustService::new(state.db.clone());
-            let trust_score = trust_service
-                .get_trust_score(auth_user.user_id)
-                .await
-                .map_err(|err| {
-                    tracing::error!(error = ?err, "failed to check trust score");
-                    AppError::internal("failed to check trust score")
-                })?;
-
-            let trust_level = trust_score
-                .map(|s| s.trust_level)
-                .unwrap_or(TrustLevel::New);
-
-            // Check rate limit
-            let rate_limiter = RateLimiter::new(state.cache.clone());
-            let is_limited = rate_limiter
-                .check_rate_limit(auth_user.user_id, action, trust_level)
+            // A bot's traffic is keyed on its own id, not its owner's, so a
+            // bot and its owner's interactive sessions don't share one
+            // quota -- and on a fixed `TrustLevel::Basic` rather than a
+            // trust score, since trust is only ever computed from a real
+            // account's own history (see `TrustService::get_trust_score`).
+            let (limit_key, trust_level) = match auth_user.bot_id {
+                Some(bot_id) => (bot_id, TrustLevel::Basic),
+                None => {
+                    let trust_service = TrustService::new(state.db.clone());
+                    let trust_score = trust_service
+                        .get_trust_score(auth_user.user_id)
+                        .await
+                        .map_err(|err| {
+                            tracing::error!(error = ?err, "failed to check trust score");
+                            AppError::internal("failed to check trust score")
+                        })?;
+
+                    let trust_level = trust_score
+                        .map(|s| s.trust_level)
+                        .unwrap_or(TrustLevel::New);
+                    (auth_user.user_id, trust_level)
+                }
+            };
+
+            // Check-and-increment against the deferred, mostly-local limiter so
+            // the common case never round-trips to Redis.
+            let info = state
+                .deferred_rate_limiter
+                .check_and_increment(limit_key, limit_type, trust_level)
                 .await
                 .map_err(|err| {
                     tracing::error!(error = ?err, "failed to check rate limit");
                     AppError::internal("failed to check rate limit")
                 })?;
 
-            if is_limited {
-                return Err(AppError::rate_limited(&format!(
-                    "Rate limit exceeded for action: {}. Please try again later.",
-                    action
-                )));
-            }
-
-            // Increment counter after successful check
-            if let Err(err) = rate_limiter.increment(auth_user.user_id, action).await {
-                tracing::warn!(error = ?err, "failed to increment rate limit counter");
+            if info.limited {
+                return Err(AppError::rate_limited_with_headers(
+                    format!(
+                        "Rate limit exceeded for action: {}. Please try again later.",
+                        limit_type.as_key()
+                    ),
+                    info.limit,
+                    info.remaining,
+                    info.reset_seconds,
+                ));
             }
         }
     }
@@ -90,44 +102,53 @@ pub async fn ip_rate_limit_middleware(
     let path = request.uri().path();
     let method = request.method();
 
-    // Determine if this is a sensitive unauthenticated endpoint
+    // Determine if this is a sensitive unauthenticated endpoint. The second
+    // limit applies to the containing subnet, so a rotating block of
+    // addresses from one allocation can't bypass the per-IP limit.
     let rate_limit_config = match (path, method.as_str()) {
-        ("/auth/login", "POST") => Some(("login", 10, RateWindow::Hour)),
-        ("/users", "POST") => Some(("signup", 3, RateWindow::Day)),
+        ("/auth/login", "POST") => Some((LimitType::Auth, 10, 50, RateWindow::Hour)),
+        ("/oauth2/token", "POST") => Some((LimitType::Auth, 10, 50, RateWindow::Hour)),
+        ("/users", "POST") => Some((LimitType::Signup, 3, 15, RateWindow::Day)),
         _ => None,
     };
 
     // Skip rate limiting if not a sensitive endpoint
-    let (action, limit, window) = match rate_limit_config {
+    let (limit_type, limit, subnet_limit, window) = match rate_limit_config {
         Some(config) => config,
         None => return Ok(next.run(request).await),
     };
 
-    let ip = addr.ip().to_string();
+    let client_ip = resolve_client_ip(addr.ip(), request.headers(), &state.trusted_proxy_cidrs);
+    let ip = client_ip.to_string();
+    let subnet = subnet_bucket(client_ip, state.ip_subnet_prefix_v4, state.ip_subnet_prefix_v6);
     let rate_limiter = RateLimiter::new(state.cache.clone());
 
     // Check IP-based rate limit
-    let is_limited = rate_limiter
-        .check_ip_rate_limit(&ip, action, limit, window)
+    let info = rate_limiter
+        .check_ip_rate_limit(&ip, &subnet, limit_type, limit, subnet_limit, window)
         .await
         .map_err(|err| {
             tracing::error!(error = ?err, "failed to check IP rate limit");
             AppError::internal("failed to check rate limit")
         })?;
 
-    if is_limited {
+    if info.limited {
         tracing::warn!(
             ip = ip,
-            action = action,
+            subnet = subnet,
+            limit_type = limit_type.as_key(),
             "IP rate limit exceeded"
         );
-        return Err(AppError::rate_limited(
+        return Err(AppError::rate_limited_with_headers(
             "Too many attempts from your IP address. Please try again later.",
+            info.limit,
+            info.remaining,
+            info.reset_seconds,
         ));
     }
 
-    // Increment counter
-    if let Err(err) = rate_limiter.increment_ip(&ip, action, window).await {
+    // Increment counters
+    if let Err(err) = rate_limiter.increment_ip(&ip, &subnet, limit_type, window).await {
         tracing::warn!(error = ?err, "failed to increment IP rate limit counter");
     }
 
@@ -7,6 +7,8 @@ use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 use crate::AppState;
 
@@ -14,10 +16,15 @@ mod auth;
 mod error;
 mod handlers;
 pub mod middleware;
+mod openapi;
 mod routes;
 
-pub use auth::{AdminToken, AuthUser};
-pub use error::AppError;
+pub use auth::{
+    AdminModeration, AdminToken, AuthUser, ManageAccount, ModeratorUser, ReadFeed, RequireScope,
+    ScopeMarker, ShortUuid, WritePosts,
+};
+pub use error::{AppError, ErrorCode};
+pub use openapi::ApiDoc;
 
 pub fn router(state: AppState) -> Router {
     // M8: Versioned API routes under /v1
@@ -60,6 +67,10 @@ pub fn router(state: AppState) -> Router {
         )
         .merge(
             routes::feed()
+                .layer(axum_middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::concurrency::concurrency_limit_middleware,
+                ))
                 .layer(axum_middleware::from_fn_with_state(
                     state.clone(),
                     middleware::rate_limit::rate_limit_middleware,
@@ -71,6 +82,10 @@ pub fn router(state: AppState) -> Router {
         )
         .merge(
             routes::media()
+                .layer(axum_middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::concurrency::concurrency_limit_middleware,
+                ))
                 .layer(axum_middleware::from_fn_with_state(
                     state.clone(),
                     middleware::rate_limit::rate_limit_middleware,
@@ -104,6 +119,10 @@ pub fn router(state: AppState) -> Router {
         )
         .merge(
             routes::search()
+                .layer(axum_middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::concurrency::concurrency_limit_middleware,
+                ))
                 .layer(axum_middleware::from_fn_with_state(
                     state.clone(),
                     middleware::rate_limit::rate_limit_middleware,
@@ -143,8 +162,21 @@ pub fn router(state: AppState) -> Router {
                     middleware::rate_limit::ip_rate_limit_middleware,
                 ))
         )
+        // ActivityPub federation endpoints -- also unversioned, since their
+        // paths are fixed by the protocol rather than this API's own
+        // convention (see `routes::federation`).
+        .merge(
+            routes::federation()
+                .layer(axum_middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::rate_limit::ip_rate_limit_middleware,
+                ))
+        )
         // All API routes under /v1
         .nest("/v1", v1_routes)
+        // Machine-readable API contract + interactive docs, both unversioned
+        // like the health/federation routes above.
+        .merge(SwaggerUi::new("/api-docs/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .with_state(state)
         // Global middleware layers (applied to all routes)
         // CORS â€” no web origins allowed (mobile-only API)
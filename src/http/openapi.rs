@@ -0,0 +1,106 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::http::handlers;
+
+/// Aggregates the `#[utoipa::path]`-annotated handlers into a single spec,
+/// served as JSON from `/api-docs/openapi.json` (see `http::router`) and
+/// rendered by the Swagger UI mounted alongside it. Not every handler in
+/// `handlers` is listed here yet -- this module grows as more of them pick
+/// up annotations, the same way `routes.rs` grows as endpoints are added.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::login,
+        handlers::refresh_token,
+        handlers::create_user,
+        handlers::list_user_posts,
+        handlers::follow_user,
+        handlers::unfollow_user,
+        handlers::relationship_status,
+        handlers::like_post,
+        handlers::unlike_post,
+        handlers::react_post,
+        handlers::unreact_post,
+        handlers::comment_post,
+        handlers::home_feed,
+        handlers::get_media,
+        handlers::list_notifications,
+        handlers::unread_notification_count,
+        handlers::takedown_post,
+        handlers::search_posts,
+        handlers::get_trust_score,
+        handlers::get_rate_limits,
+        handlers::register_device_fingerprint,
+        handlers::create_invite,
+        handlers::create_signed_invite,
+    ),
+    components(schemas(
+        handlers::LoginRequest,
+        handlers::LoginResponse,
+        handlers::AuthTokenResponse,
+        handlers::RefreshRequest,
+        handlers::CreateUserRequest,
+        handlers::FollowResponse,
+        handlers::UnfollowResponse,
+        handlers::RelationshipResponse,
+        handlers::LikeResponse,
+        handlers::ReactRequest,
+        handlers::PostListResponse,
+        handlers::CommentListResponse,
+        handlers::NotificationListResponse,
+        handlers::UnreadCountResponse,
+        handlers::CommentRequest,
+        handlers::ModerationRequest,
+        handlers::TrustScoreResponse,
+        handlers::RateLimitsResponse,
+        handlers::RemainingQuotas,
+        handlers::RegisterFingerprintRequest,
+        handlers::CreateInviteRequest,
+        handlers::CreateSignedInviteRequest,
+        handlers::SignedInviteResponse,
+        crate::domain::user::User,
+        crate::domain::post::Post,
+        crate::domain::post::PostMention,
+        crate::domain::post::PostVisibility,
+        crate::domain::media::Media,
+        crate::domain::media::MediaKind,
+        crate::domain::engagement::Comment,
+        crate::domain::notification::Notification,
+        crate::app::invites::InviteCode,
+    )),
+    tags(
+        (name = "auth", description = "Login, token refresh, and session bootstrap"),
+        (name = "users", description = "Account creation and profile lookup"),
+        (name = "posts", description = "Post timelines"),
+        (name = "social", description = "Follow/unfollow/block relationships"),
+        (name = "engagement", description = "Likes and comments"),
+        (name = "feed", description = "Home timeline"),
+        (name = "media", description = "Uploaded images, video, and their variants"),
+        (name = "notifications", description = "In-app and push notifications"),
+        (name = "moderation", description = "Admin/moderator actions on users and content"),
+        (name = "search", description = "Full-text search over users and posts"),
+        (name = "trust", description = "Trust scoring and per-action rate limits"),
+        (name = "fingerprint", description = "Device fingerprinting for abuse detection"),
+        (name = "invites", description = "Invite-gated signup codes"),
+    ),
+    modifiers(&SecurityAddon),
+)]
+pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.as_mut().expect("components registered above");
+        components.add_security_scheme(
+            "bearer_token",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
@@ -12,9 +12,89 @@ pub fn health() -> Router<AppState> {
 pub fn auth() -> Router<AppState> {
     Router::new()
         .route("/auth/login", post(handlers::login))
+        .route("/auth/account/restore", post(handlers::restore_account))
         .route("/auth/refresh", post(handlers::refresh_token))
         .route("/auth/revoke", post(handlers::revoke_token))
+        .route("/auth/logout-all", post(handlers::logout_all_sessions))
+        .route("/auth/sessions", get(handlers::list_sessions))
+        .route("/auth/sessions/:id", delete(handlers::revoke_session))
+        .route(
+            "/auth/sessions/revoke-all",
+            post(handlers::revoke_all_sessions_endpoint),
+        )
         .route("/auth/me", get(handlers::get_current_user))
+        .route(
+            "/auth/email-verification",
+            post(handlers::request_email_verification),
+        )
+        .route(
+            "/auth/email-verification/confirm",
+            post(handlers::confirm_email_verification),
+        )
+        .route("/auth/password/forgot", post(handlers::forgot_password))
+        .route("/auth/password/reset", post(handlers::reset_password))
+        .route("/auth/password/change", post(handlers::change_password))
+        .route(
+            "/auth/device/requests",
+            post(handlers::create_device_auth_request),
+        )
+        .route(
+            "/auth/device/requests/:id/poll",
+            get(handlers::poll_device_auth_request),
+        )
+        // OPAQUE (augmented PAKE) -- passwordless registration/login
+        .route(
+            "/auth/opaque/register/start",
+            post(handlers::opaque_register_start),
+        )
+        .route(
+            "/auth/opaque/register/finish",
+            post(handlers::opaque_register_finish),
+        )
+        .route(
+            "/auth/opaque/login/start",
+            post(handlers::opaque_login_start),
+        )
+        .route(
+            "/auth/opaque/login/finish",
+            post(handlers::opaque_login_finish),
+        )
+        // WebAuthn/FIDO2 passkeys -- second factor / passwordless login
+        .route(
+            "/auth/webauthn/registration/begin",
+            post(handlers::webauthn_registration_begin),
+        )
+        .route(
+            "/auth/webauthn/registration/finish",
+            post(handlers::webauthn_registration_finish),
+        )
+        .route(
+            "/auth/webauthn/authentication/begin",
+            post(handlers::webauthn_authentication_begin),
+        )
+        .route(
+            "/auth/webauthn/authentication/finish",
+            post(handlers::webauthn_authentication_finish),
+        )
+        // TOTP -- optional second factor gating password login
+        .route("/auth/totp/enroll", post(handlers::totp_enroll))
+        .route("/auth/totp/confirm", post(handlers::totp_confirm))
+        .route("/auth/totp/login", post(handlers::totp_login))
+        // OAuth/OIDC (authorization-code + PKCE) third-party login
+        .route(
+            "/auth/oauth/:provider/start",
+            post(handlers::start_oauth_login),
+        )
+        .route(
+            "/auth/oauth/:provider/callback",
+            get(handlers::oauth_callback),
+        )
+        // OAuth2 authorization server (authorization-code + PKCE grant) --
+        // lets a registered client (see `moderation()`'s
+        // `/admin/oauth/clients`) obtain a scoped token pair for a user
+        // without handling their password.
+        .route("/oauth2/authorize", post(handlers::oauth_authorize))
+        .route("/oauth2/token", post(handlers::oauth_token))
 }
 
 pub fn users() -> Router<AppState> {
@@ -23,17 +103,53 @@ pub fn users() -> Router<AppState> {
         .route("/users/:id", get(handlers::get_user))
         .route("/users/:id", patch(handlers::update_profile))
         .route("/users/:id/posts", get(handlers::list_user_posts))
+        .route("/users/:id/feed.atom", get(handlers::user_feed_atom))
+        .route("/users/:id/feed.rss", get(handlers::user_feed_rss))
         .route("/users/:id/stories", get(handlers::get_user_stories))
         .route("/users/:id/highlights", get(handlers::get_user_highlights))
         .route("/users/:id/follow", post(handlers::follow_user))
         .route("/users/:id/unfollow", post(handlers::unfollow_user))
+        .route(
+            "/users/:id/follow-requests/accept",
+            post(handlers::accept_follow_request),
+        )
+        .route(
+            "/users/:id/follow-requests/reject",
+            post(handlers::reject_follow_request),
+        )
         .route("/users/:id/block", post(handlers::block_user))
         .route("/users/:id/unblock", post(handlers::unblock_user))
+        .route("/users/:id/mute", post(handlers::mute_user))
+        .route("/users/:id/unmute", post(handlers::unmute_user))
+        .route("/users/:id/lock", post(handlers::lock_account))
+        .route("/users/:id/unlock", post(handlers::unlock_account))
         .route("/users/:id/followers", get(handlers::list_followers))
         .route("/users/:id/following", get(handlers::list_following))
         .route("/users/:id/relationship", get(handlers::relationship_status))
+        // Lumine-to-Lumine `handle@host` follow/block pushes (see
+        // `app::social_federation`) -- kept under `/account` rather than
+        // `/users/:id/...` since the target isn't a local user id.
+        .route("/account/remote-follow", post(handlers::follow_remote_user))
+        .route("/account/remote-unfollow", post(handlers::unfollow_remote_user))
+        .route("/account/remote-block", post(handlers::block_remote_user))
+        .route("/account/remote-unblock", post(handlers::unblock_remote_user))
         // Account management (authenticated user's own account)
         .route("/account", delete(handlers::delete_account))
+        .route(
+            "/account/follow-requests",
+            get(handlers::list_follow_requests),
+        )
+        .route(
+            "/account/follow-requests/summary",
+            get(handlers::get_follow_request_summary),
+        )
+        // Bot/application accounts -- see `app::bots::BotService`.
+        .route("/account/bots", post(handlers::create_bot))
+        .route("/account/bots", get(handlers::list_bots))
+        .route(
+            "/account/bots/:id/rotate-token",
+            post(handlers::rotate_bot_token),
+        )
 }
 
 pub fn posts() -> Router<AppState> {
@@ -42,29 +158,59 @@ pub fn posts() -> Router<AppState> {
         .route("/posts/:id", get(handlers::get_post))
         .route("/posts/:id", patch(handlers::update_post_caption))
         .route("/posts/:id", delete(handlers::delete_post))
+        .route("/posts/:id/history", get(handlers::post_history))
         .route("/posts/:id/like", post(handlers::like_post))
         .route("/posts/:id/like", delete(handlers::unlike_post))
         .route("/posts/:id/likes", get(handlers::list_post_likes))
+        .route("/posts/:id/react", post(handlers::react_post))
+        .route("/posts/:id/react", delete(handlers::unreact_post))
+        .route(
+            "/posts/:id/reactions",
+            get(handlers::list_post_reaction_counts),
+        )
         .route("/posts/:id/comment", post(handlers::comment_post))
         .route("/posts/:id/comments", get(handlers::list_post_comments))
         .route(
             "/posts/:id/comments/:comment_id",
             delete(handlers::delete_comment),
         )
+        .route(
+            "/posts/:id/comments/:comment_id/replies",
+            get(handlers::list_comment_replies),
+        )
 }
 
 pub fn feed() -> Router<AppState> {
     Router::new()
         .route("/feed", get(handlers::home_feed))
         .route("/feed/refresh", post(handlers::refresh_feed))
+        .route("/feed/stream", get(handlers::stream_feed))
 }
 
 pub fn media() -> Router<AppState> {
     Router::new()
+        .route("/media", post(handlers::create_upload_direct))
         .route("/media/upload", post(handlers::create_upload))
         .route("/media/upload/:id/complete", post(handlers::complete_upload))
+        .route(
+            "/media/upload/multipart",
+            post(handlers::create_multipart_upload),
+        )
+        .route(
+            "/media/upload/:id/multipart/complete",
+            post(handlers::complete_multipart_upload),
+        )
+        .route(
+            "/media/upload/:id/multipart/abort",
+            post(handlers::abort_multipart_upload),
+        )
         .route("/media/:id", get(handlers::get_media))
         .route("/media/:id", delete(handlers::delete_media))
+        .route("/media/:id/variant", post(handlers::get_media_variant))
+        .route(
+            "/media/:id/derivative",
+            get(handlers::get_media_derivative),
+        )
         .route(
             "/media/upload/:id/status",
             get(handlers::get_upload_status),
@@ -74,6 +220,18 @@ pub fn media() -> Router<AppState> {
 pub fn notifications() -> Router<AppState> {
     Router::new()
         .route("/notifications", get(handlers::list_notifications))
+        .route(
+            "/notifications/unread-count",
+            get(handlers::unread_notification_count),
+        )
+        .route(
+            "/notifications/stream",
+            get(handlers::stream_notifications),
+        )
+        .route(
+            "/notifications/read-all",
+            post(handlers::mark_all_notifications_read),
+        )
         .route(
             "/notifications/:id/read",
             post(handlers::mark_notification_read),
@@ -82,6 +240,11 @@ pub fn notifications() -> Router<AppState> {
 
 pub fn moderation() -> Router<AppState> {
     Router::new()
+        .route("/admin/users", get(handlers::admin_search_users))
+        .route(
+            "/admin/users/:id/trust-level",
+            post(handlers::set_user_trust_level),
+        )
         .route(
             "/moderation/users/:id/flag",
             post(handlers::flag_user),
@@ -94,18 +257,78 @@ pub fn moderation() -> Router<AppState> {
             "/moderation/comments/:id/takedown",
             post(handlers::takedown_comment),
         )
+        .route("/moderation/posts/:id/restore", post(handlers::restore_post))
+        .route(
+            "/moderation/comments/:id/restore",
+            post(handlers::restore_comment),
+        )
+        .route(
+            "/moderation/users/:id/suspend",
+            post(handlers::suspend_user),
+        )
+        .route(
+            "/moderation/users/:id/unsuspend",
+            post(handlers::unsuspend_user),
+        )
+        .route("/moderation/users/:id/ban", post(handlers::ban_user))
+        .route("/moderation/users/:id/unban", post(handlers::unban_user))
+        .route(
+            "/moderation/users/:id/ban-status",
+            get(handlers::user_ban_status),
+        )
+        .route("/moderation/users/:id/role", post(handlers::set_user_role))
+        .route(
+            "/moderation/users/:id/profile-history",
+            get(handlers::user_profile_history),
+        )
+        .route("/moderation/stats", get(handlers::moderation_stats))
         .route("/moderation/audit", get(handlers::list_moderation_audit))
+        .route("/moderation/flags", get(handlers::list_pending_flags))
+        .route(
+            "/moderation/flags/:id/resolve",
+            post(handlers::resolve_flag),
+        )
+        // Admin-issued invite codes (closed beta, no per-user quota)
+        .route("/admin/invites", get(handlers::admin_list_invites))
+        .route("/admin/invites", post(handlers::admin_create_invite))
+        .route(
+            "/admin/invites/:code",
+            delete(handlers::admin_revoke_invite),
+        )
+        // Signup email blocklist (exact addresses or `*@domain` wildcards)
+        .route("/admin/blocklist", get(handlers::admin_list_blocklist))
+        .route("/admin/blocklist", post(handlers::admin_add_blocklist_entry))
+        .route(
+            "/admin/blocklist/:id",
+            delete(handlers::admin_remove_blocklist_entry),
+        )
+        // OAuth2 authorization server -- registering clients for the
+        // authorization-code grant (see `auth()` for the authorize/token
+        // endpoints themselves)
+        .route(
+            "/admin/oauth/clients",
+            post(handlers::admin_register_oauth_client),
+        )
 }
 
 pub fn search() -> Router<AppState> {
     Router::new()
         .route("/search/users", get(handlers::search_users))
         .route("/search/posts", get(handlers::search_posts))
+        .route("/search/batch", post(handlers::search_all))
 }
 
 pub fn stories() -> Router<AppState> {
     Router::new()
         .route("/stories", post(handlers::create_story))
+        .route(
+            "/account/story-encryption-key",
+            post(handlers::register_story_encryption_key),
+        )
+        .route(
+            "/users/:id/story-encryption-key",
+            get(handlers::get_story_encryption_key),
+        )
         .route("/stories/:id", get(handlers::get_story))
         .route("/stories/:id", delete(handlers::delete_story))
         .route("/stories/:id/viewers", get(handlers::get_story_viewers))
@@ -127,7 +350,58 @@ pub fn stories() -> Router<AppState> {
             "/stories/:id/highlights",
             post(handlers::add_story_to_highlight),
         )
+        .route(
+            "/account/audience-lists",
+            post(handlers::create_audience_list),
+        )
+        .route(
+            "/account/audience-lists",
+            get(handlers::get_user_audience_lists),
+        )
+        .route(
+            "/account/audience-lists/:id",
+            delete(handlers::delete_audience_list),
+        )
+        .route(
+            "/account/audience-lists/:id/members",
+            post(handlers::add_list_member),
+        )
+        .route(
+            "/account/audience-lists/:id/members/:member_id",
+            delete(handlers::remove_list_member),
+        )
         .route("/feed/stories", get(handlers::get_stories_feed))
+        .route("/feed/stories/stream", get(handlers::stream_stories_feed))
+        .route(
+            "/stories/hashtags/trending",
+            get(handlers::get_trending_hashtags),
+        )
+        .route(
+            "/stories/hashtags/:tag",
+            get(handlers::get_hashtag_stories_feed),
+        )
+}
+
+/// Server-to-server ActivityPub endpoints. These are unversioned (see
+/// `http::router`) since their paths are dictated by the protocol (the
+/// handle in a WebFinger response, the `id`/`inbox` a remote server
+/// discovers from an actor document) rather than by this API's own
+/// `/v1` convention.
+pub fn federation() -> Router<AppState> {
+    Router::new()
+        .route("/.well-known/webfinger", get(handlers::webfinger))
+        .route("/users/:id/actor", get(handlers::get_actor))
+        .route("/users/:id/outbox", get(handlers::get_outbox))
+        .route("/users/:id/inbox", post(handlers::post_inbox))
+        // Lumine-to-Lumine signed Follow/Block edge push protocol (see
+        // `app::social_federation`) -- distinct from the ActivityPub routes
+        // above, so namespaced under its own `/federation/v1` prefix rather
+        // than the bare, spec-fixed paths ActivityPub requires.
+        .route("/federation/v1/server-key", get(handlers::server_key))
+        .route(
+            "/federation/v1/social-edges",
+            post(handlers::receive_social_edge),
+        )
 }
 
 pub fn safety() -> Router<AppState> {
@@ -138,9 +412,63 @@ pub fn safety() -> Router<AppState> {
         // Device Fingerprinting
         .route("/account/device/register", post(handlers::register_device_fingerprint))
         .route("/account/devices", get(handlers::list_user_devices))
+        // Signed device lists (primary-device rotation)
+        .route("/account/device-list", get(handlers::get_device_list))
+        .route("/account/device-list", patch(handlers::update_device_list))
+        .route(
+            "/account/device-list/keys",
+            post(handlers::register_device_key),
+        )
+        .route(
+            "/account/device/requests",
+            get(handlers::list_pending_device_auth_requests),
+        )
+        .route(
+            "/account/device/requests/:id/respond",
+            post(handlers::respond_device_auth_request),
+        )
+        // Wallet Linking (Sign-In with Ethereum)
+        .route("/account/wallet", get(handlers::get_wallet))
+        .route("/account/wallet/nonce", post(handlers::create_wallet_nonce))
+        .route("/account/wallet/verify", post(handlers::verify_wallet))
+        // External identity linking (Farcaster FID / Ethereum address)
+        .route(
+            "/account/identities/farcaster",
+            post(handlers::link_farcaster_identity),
+        )
+        .route(
+            "/account/identities/farcaster",
+            delete(handlers::unlink_farcaster_identity),
+        )
+        .route(
+            "/account/identities/ethereum",
+            post(handlers::link_ethereum_identity),
+        )
+        .route(
+            "/account/identities/ethereum",
+            delete(handlers::unlink_ethereum_identity),
+        )
+        .route(
+            "/account/identities/oauth/:provider",
+            post(handlers::link_oauth_identity),
+        )
+        // Web Push subscriptions
+        .route(
+            "/account/push-subscriptions",
+            post(handlers::subscribe_push),
+        )
+        .route(
+            "/account/push-subscriptions",
+            delete(handlers::unsubscribe_push),
+        )
         // Invite System
         .route("/invites", get(handlers::list_invites))
         .route("/invites", post(handlers::create_invite))
         .route("/invites/stats", get(handlers::get_invite_stats))
         .route("/invites/:code/revoke", post(handlers::revoke_invite))
+        .route("/invites/signed", post(handlers::create_signed_invite))
+        .route(
+            "/invites/signed/:nonce/revoke",
+            post(handlers::revoke_signed_invite),
+        )
 }
@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use crate::infra::db::{Db, UnitOfWork};
+
+/// Generic create/read/update/delete surface for a domain record backed by
+/// a single soft-deletable Postgres table. Every method takes an optional
+/// `UnitOfWork` the same way `UserService` does -- `None` runs against the
+/// pool directly, `Some(uow)` joins the caller's transaction -- so a `Crud`
+/// impl composes with other unit-of-work participants instead of insisting
+/// on its own transaction.
+///
+/// This doesn't replace a service's business methods (validation, caching,
+/// cascades, audit trails all stay on the service); it exists so the plain
+/// "map a row to the struct, filter out soft-deleted rows" boilerplate only
+/// has to be written once per type.
+#[axum::async_trait]
+pub trait Crud: Sized {
+    type Id: Send + Sync;
+    type InsertForm: Send + Sync;
+    type UpdateForm: Send + Sync;
+
+    async fn create(db: &Db, tx: Option<&mut UnitOfWork>, form: Self::InsertForm) -> Result<Self>;
+
+    async fn read(db: &Db, tx: Option<&mut UnitOfWork>, id: Self::Id) -> Result<Option<Self>>;
+
+    async fn update(
+        db: &Db,
+        tx: Option<&mut UnitOfWork>,
+        id: Self::Id,
+        form: Self::UpdateForm,
+    ) -> Result<Option<Self>>;
+
+    /// Soft-delete: sets `deleted_at` rather than removing the row.
+    async fn delete(db: &Db, tx: Option<&mut UnitOfWork>, id: Self::Id) -> Result<bool>;
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
 use std::time::Duration;
 
 use crate::config::AppConfig;
@@ -32,3 +32,45 @@ impl Db {
     }
 }
 
+/// A single Postgres transaction threaded through multiple services so a
+/// request that touches e.g. users + notifications commits or rolls back as
+/// one unit, instead of each service running its own independent
+/// transaction against `Db::pool()`. Services take `Option<&mut UnitOfWork>`:
+/// `None` means "run standalone, manage your own transaction if you need
+/// one"; `Some` means "join the caller's transaction and let the caller
+/// decide when to commit".
+pub struct UnitOfWork {
+    tx: Transaction<'static, Postgres>,
+}
+
+impl UnitOfWork {
+    pub async fn begin(db: &Db) -> Result<Self> {
+        Ok(Self { tx: db.pool().begin().await? })
+    }
+
+    pub fn conn(&mut self) -> &mut PgConnection {
+        &mut self.tx
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn rollback(self) -> Result<()> {
+        self.tx.rollback().await?;
+        Ok(())
+    }
+}
+
+/// Whether `err` is a transient Postgres error worth retrying -- a
+/// serialization failure (`40001`) or a detected deadlock (`40P01`) -- as
+/// opposed to a conflict that will keep failing (bad SQL, a constraint
+/// violation on data that won't change).
+pub fn is_transaction_conflict(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => matches!(db_err.code().as_deref(), Some("40001") | Some("40P01")),
+        _ => false,
+    }
+}
+
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::infra::cache::RedisCache;
+
+const MAX_DATAGRAM_BYTES: usize = 4096;
+const SEEN_ID_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum GossipEvent {
+    StoryFeedChanged { user_ids: Vec<Uuid> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GossipMessage {
+    id: Uuid,
+    event: GossipEvent,
+}
+
+/// UDP fan-out so peer API instances can proactively evict a stale
+/// `feed:stories:*` cache entry the moment a story, follow, or block change
+/// touches it, instead of waiting out `STORIES_FEED_CACHE_TTL`.
+///
+/// This is a latency optimization, not a correctness requirement: the feed
+/// cache lives in a single shared Redis instance, not per-node, and
+/// `StoryService::bump_feed_version` already folds the new version into
+/// every node's cache key the moment it's written, so a peer that never
+/// receives a gossip message still serves correct data -- it just serves a
+/// stale (but no-longer-reachable, since the version moved on) cache entry
+/// until the TTL reclaims it. What gossip buys is freeing that entry's
+/// Redis memory immediately rather than waiting for expiry.
+#[derive(Clone)]
+pub struct GossipClient {
+    socket: Arc<UdpSocket>,
+    peers: Vec<SocketAddr>,
+}
+
+impl GossipClient {
+    pub async fn bind(bind_addr: &str, peers: Vec<SocketAddr>) -> Result<Self> {
+        let socket = UdpSocket::bind(bind_addr).await?;
+        Ok(Self { socket: Arc::new(socket), peers })
+    }
+
+    /// Best-effort: a down peer or a serialization failure must never fail
+    /// the story/follow/block mutation that triggered this broadcast.
+    pub async fn broadcast_story_feed_changed(&self, user_ids: Vec<Uuid>) {
+        if self.peers.is_empty() || user_ids.is_empty() {
+            return;
+        }
+
+        let message = GossipMessage {
+            id: Uuid::new_v4(),
+            event: GossipEvent::StoryFeedChanged { user_ids },
+        };
+        let payload = match serde_json::to_vec(&message) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!(error = ?err, "failed to encode gossip invalidation message");
+                return;
+            }
+        };
+
+        for peer in &self.peers {
+            if let Err(err) = self.socket.send_to(&payload, peer).await {
+                warn!(error = ?err, peer = %peer, "failed to send gossip invalidation message");
+            }
+        }
+    }
+
+    /// Runs until the socket errors; wire up as its own `tokio::spawn` task
+    /// alongside the other background loops in `main.rs`. Duplicate
+    /// messages -- the same change gossiped by more than one peer -- are
+    /// dropped via a short-lived seen-id set so a fan-out storm doesn't
+    /// repeat the same cache eviction work.
+    pub async fn run(self, cache: RedisCache) -> Result<()> {
+        let mut buf = vec![0u8; MAX_DATAGRAM_BYTES];
+        let mut seen_order: VecDeque<Uuid> = VecDeque::with_capacity(SEEN_ID_CAPACITY);
+        let mut seen: HashSet<Uuid> = HashSet::with_capacity(SEEN_ID_CAPACITY);
+
+        loop {
+            let (len, _src) = self.socket.recv_from(&mut buf).await?;
+
+            let message: GossipMessage = match serde_json::from_slice(&buf[..len]) {
+                Ok(message) => message,
+                Err(err) => {
+                    warn!(error = ?err, "failed to decode gossip invalidation message");
+                    continue;
+                }
+            };
+
+            if !seen.insert(message.id) {
+                continue;
+            }
+            seen_order.push_back(message.id);
+            if seen_order.len() > SEEN_ID_CAPACITY {
+                if let Some(oldest) = seen_order.pop_front() {
+                    seen.remove(&oldest);
+                }
+            }
+
+            let GossipEvent::StoryFeedChanged { user_ids } = message.event;
+            for user_id in user_ids {
+                if let Err(err) = evict_stories_feed_cache(&cache, user_id).await {
+                    warn!(error = ?err, user_id = %user_id, "failed to evict gossiped stories feed cache entries");
+                }
+            }
+        }
+    }
+}
+
+/// Deletes every `feed:stories:{user_id}:*` entry (one per distinct page
+/// size a client has requested) for the given user. Uses `SCAN` rather than
+/// `KEYS` since this runs on every gossip receipt, not just at startup, and
+/// must not block Redis while iterating a large keyspace.
+async fn evict_stories_feed_cache(cache: &RedisCache, user_id: Uuid) -> Result<()> {
+    let mut conn = cache.client().get_multiplexed_async_connection().await?;
+    let pattern = format!("feed:stories:{}:*", user_id);
+    let mut cursor: u64 = 0;
+
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(100)
+            .query_async(&mut conn)
+            .await?;
+
+        if !keys.is_empty() {
+            let _: () = conn.del(keys).await?;
+        }
+
+        if next_cursor == 0 {
+            break;
+        }
+        cursor = next_cursor;
+    }
+
+    Ok(())
+}
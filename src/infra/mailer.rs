@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+use crate::config::AppConfig;
+
+/// Sends a single transactional email. Abstracted behind a trait so tests
+/// can substitute an in-memory sink instead of talking to real SMTP --
+/// mirrors `app::oauth::TokenExchanger`'s mockable-network-call shape.
+#[axum::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<()>;
+}
+
+/// Production mailer: submits over SMTP using the provider configured by
+/// `SMTP_*` env vars.
+pub struct SmtpMailer {
+    transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+    from: String,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &AppConfig) -> Result<Self> {
+        let mut builder =
+            lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::relay(&config.smtp_host)?
+                .port(config.smtp_port);
+        if !config.smtp_username.is_empty() {
+            builder = builder.credentials(lettre::transport::smtp::authentication::Credentials::new(
+                config.smtp_username.clone(),
+                config.smtp_password.clone(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.smtp_from.clone(),
+        })
+    }
+}
+
+#[axum::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<()> {
+        use lettre::AsyncTransport;
+
+        let message = lettre::Message::builder()
+            .from(self.from.parse()?)
+            .to(to.parse()?)
+            .subject(subject)
+            .body(body.to_string())?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
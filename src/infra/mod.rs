@@ -0,0 +1,8 @@
+pub mod cache;
+pub mod crud;
+pub mod db;
+pub mod gossip;
+pub mod mailer;
+pub mod push_sender;
+pub mod queue;
+pub mod storage;
@@ -0,0 +1,62 @@
+use anyhow::Result;
+
+/// Delivers an already-encrypted Web Push message (an aes128gcm content-coded
+/// body plus its headers) to a subscription's push service endpoint.
+/// Abstracted behind a trait so tests can substitute an in-memory sink
+/// instead of talking to a real push service -- mirrors `infra::mailer::Mailer`.
+#[axum::async_trait]
+pub trait PushSender: Send + Sync {
+    async fn send(&self, endpoint: &str, headers: Vec<(String, String)>, body: Vec<u8>) -> Result<PushOutcome>;
+}
+
+/// How the push service responded, collapsed to what the dispatcher needs to
+/// decide: keep the subscription, or retire it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    Delivered,
+    /// The push service returned 404/410 -- the subscription is gone and
+    /// should be deleted rather than retried.
+    Gone,
+    Failed,
+}
+
+/// Production sender: POSTs the encrypted payload straight to the push
+/// service endpoint (e.g. `https://fcm.googleapis.com/...`, `https://updates.push.services.mozilla.com/...`).
+pub struct HttpPushSender {
+    client: reqwest::Client,
+}
+
+impl HttpPushSender {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Default for HttpPushSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[axum::async_trait]
+impl PushSender for HttpPushSender {
+    async fn send(&self, endpoint: &str, headers: Vec<(String, String)>, body: Vec<u8>) -> Result<PushOutcome> {
+        let mut request = self.client.post(endpoint);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.body(body).send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            Ok(PushOutcome::Delivered)
+        } else if status.as_u16() == 404 || status.as_u16() == 410 {
+            Ok(PushOutcome::Gone)
+        } else {
+            Ok(PushOutcome::Failed)
+        }
+    }
+}
@@ -3,24 +3,77 @@ use aws_config::meta::region::RegionProviderChain;
 use aws_config::BehaviorVersion;
 use aws_config::Region;
 use aws_sdk_sqs::error::SdkError;
+use aws_sdk_sqs::types::{MessageSystemAttributeName, QueueAttributeName};
 use aws_sdk_sqs::Client;
 use serde_json;
 use tracing::{debug, warn};
 
+use crate::app::push::PushJob;
 use crate::config::AppConfig;
+use crate::jobs::bot_webhook_dispatcher::BotWebhookJob;
+use crate::jobs::deletion_sweeper::DeletionJob;
+use crate::jobs::federation_sweeper::FederationJob;
 use crate::jobs::media_processor::MediaJob;
+use crate::jobs::social_federation_dispatcher::SocialEdgeJob;
 
 #[derive(Clone)]
 pub struct QueueClient {
     client: Client,
     queue_name: String,
     queue_url: String,
+    push_queue_name: String,
+    push_queue_url: String,
+    deletion_queue_name: String,
+    deletion_queue_url: String,
+    federation_queue_name: String,
+    federation_queue_url: String,
+    social_edge_queue_name: String,
+    social_edge_queue_url: String,
+    bot_webhook_queue_name: String,
+    bot_webhook_queue_url: String,
+    media_dlq_name: String,
+    media_dlq_url: String,
 }
 
 #[derive(Debug)]
 pub struct ReceivedJob {
     pub job: MediaJob,
     pub receipt_handle: String,
+    /// `ApproximateReceiveCount` from SQS -- how many times this message has
+    /// been delivered, including this one. Lets `jobs::media_processor` back
+    /// off exponentially on `ProcessingOutcome::RetryLater` instead of
+    /// immediately redelivering at the queue's default visibility timeout.
+    pub approximate_receive_count: i32,
+}
+
+#[derive(Debug)]
+pub struct ReceivedPushJob {
+    pub job: PushJob,
+    pub receipt_handle: String,
+}
+
+#[derive(Debug)]
+pub struct ReceivedDeletionJob {
+    pub job: DeletionJob,
+    pub receipt_handle: String,
+}
+
+#[derive(Debug)]
+pub struct ReceivedFederationJob {
+    pub job: FederationJob,
+    pub receipt_handle: String,
+}
+
+#[derive(Debug)]
+pub struct ReceivedSocialEdgeJob {
+    pub job: SocialEdgeJob,
+    pub receipt_handle: String,
+}
+
+#[derive(Debug)]
+pub struct ReceivedBotWebhookJob {
+    pub job: BotWebhookJob,
+    pub receipt_handle: String,
 }
 
 impl QueueClient {
@@ -40,36 +93,43 @@ impl QueueClient {
         let sqs_config = sqs_builder.build();
 
         let client = Client::from_conf(sqs_config);
-        let queue_url = match client
-            .get_queue_url()
-            .queue_name(&config.queue_name)
+        let queue_url = resolve_queue_url(&client, &config.queue_name).await?;
+        let push_queue_url = resolve_queue_url(&client, &config.push_queue_name).await?;
+        let deletion_queue_url = resolve_queue_url(&client, &config.deletion_queue_name).await?;
+        let federation_queue_url = resolve_queue_url(&client, &config.federation_queue_name).await?;
+        let social_edge_queue_url = resolve_queue_url(&client, &config.social_edge_queue_name).await?;
+        let bot_webhook_queue_url = resolve_queue_url(&client, &config.bot_webhook_queue_name).await?;
+        let media_dlq_url = resolve_queue_url(&client, &config.media_dlq_name).await?;
+
+        let dlq_arn = queue_arn(&client, &media_dlq_url).await?;
+        let redrive_policy = serde_json::json!({
+            "deadLetterTargetArn": dlq_arn,
+            "maxReceiveCount": config.media_queue_max_receive_count,
+        })
+        .to_string();
+        client
+            .set_queue_attributes()
+            .queue_url(&queue_url)
+            .attributes(QueueAttributeName::RedrivePolicy, redrive_policy)
             .send()
-            .await
-        {
-            Ok(response) => response
-                .queue_url()
-                .ok_or_else(|| anyhow!("missing queue url"))?
-                .to_string(),
-            Err(SdkError::ServiceError(service_err))
-                if service_err.err().is_queue_does_not_exist() =>
-            {
-                let created = client
-                    .create_queue()
-                    .queue_name(&config.queue_name)
-                    .send()
-                    .await?;
-                created
-                    .queue_url()
-                    .ok_or_else(|| anyhow!("missing queue url"))?
-                    .to_string()
-            }
-            Err(err) => return Err(anyhow!(err)),
-        };
+            .await?;
 
         Ok(Self {
             client,
             queue_name: config.queue_name.clone(),
             queue_url,
+            push_queue_name: config.push_queue_name.clone(),
+            push_queue_url,
+            deletion_queue_name: config.deletion_queue_name.clone(),
+            deletion_queue_url,
+            federation_queue_name: config.federation_queue_name.clone(),
+            federation_queue_url,
+            social_edge_queue_name: config.social_edge_queue_name.clone(),
+            social_edge_queue_url,
+            bot_webhook_queue_name: config.bot_webhook_queue_name.clone(),
+            bot_webhook_queue_url,
+            media_dlq_name: config.media_dlq_name.clone(),
+            media_dlq_url,
         })
     }
 
@@ -81,6 +141,30 @@ impl QueueClient {
         &self.queue_name
     }
 
+    pub fn push_queue_name(&self) -> &str {
+        &self.push_queue_name
+    }
+
+    pub fn deletion_queue_name(&self) -> &str {
+        &self.deletion_queue_name
+    }
+
+    pub fn federation_queue_name(&self) -> &str {
+        &self.federation_queue_name
+    }
+
+    pub fn social_edge_queue_name(&self) -> &str {
+        &self.social_edge_queue_name
+    }
+
+    pub fn bot_webhook_queue_name(&self) -> &str {
+        &self.bot_webhook_queue_name
+    }
+
+    pub fn media_dlq_name(&self) -> &str {
+        &self.media_dlq_name
+    }
+
     pub async fn enqueue_media_job(&self, job: &MediaJob) -> Result<()> {
         let body = serde_json::to_string(job)?;
         self.client
@@ -100,6 +184,7 @@ impl QueueClient {
             .queue_url(&self.queue_url)
             .max_number_of_messages(1)
             .wait_time_seconds(wait_time_seconds)
+            .attribute_names(QueueAttributeName::ApproximateReceiveCount)
             .send()
             .await?;
 
@@ -116,6 +201,12 @@ impl QueueClient {
             }
         };
 
+        let approximate_receive_count = message
+            .attributes()
+            .and_then(|attrs| attrs.get(&MessageSystemAttributeName::ApproximateReceiveCount))
+            .and_then(|count| count.parse::<i32>().ok())
+            .unwrap_or(1);
+
         let body = match message.body() {
             Some(body) => body,
             None => {
@@ -128,14 +219,19 @@ impl QueueClient {
         let job: MediaJob = match serde_json::from_str(body) {
             Ok(job) => job,
             Err(err) => {
-                warn!(error = ?err, "failed to parse queue message body");
+                warn!(error = ?err, "failed to parse queue message body, moving to dead-letter queue");
+                let _ = self.send_to_dlq(body).await;
                 let _ = self.delete_message(&receipt_handle).await;
                 return Ok(None);
             }
         };
 
-        debug!(upload_id = %job.upload_id, "received media job");
-        Ok(Some(ReceivedJob { job, receipt_handle }))
+        debug!(upload_id = %job.upload_id, receive_count = approximate_receive_count, "received media job");
+        Ok(Some(ReceivedJob {
+            job,
+            receipt_handle,
+            approximate_receive_count,
+        }))
     }
 
     pub async fn delete_message(&self, receipt_handle: &str) -> Result<()> {
@@ -148,5 +244,418 @@ impl QueueClient {
 
         Ok(())
     }
+
+    /// Resets the media queue's visibility timeout for an in-flight message
+    /// so `jobs::media_processor` can back off before it becomes visible for
+    /// redelivery again, instead of relying on the queue's fixed default.
+    pub async fn change_message_visibility(&self, receipt_handle: &str, seconds: i32) -> Result<()> {
+        self.client
+            .change_message_visibility()
+            .queue_url(&self.queue_url)
+            .receipt_handle(receipt_handle)
+            .visibility_timeout(seconds)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sends a raw message body directly to the media dead-letter queue.
+    /// Used for messages that fail to parse, so they're preserved for
+    /// inspection rather than silently dropped.
+    pub async fn send_to_dlq(&self, body: &str) -> Result<()> {
+        self.client
+            .send_message()
+            .queue_url(&self.media_dlq_url)
+            .message_body(body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn enqueue_push_job(&self, job: &PushJob) -> Result<()> {
+        let body = serde_json::to_string(job)?;
+        self.client
+            .send_message()
+            .queue_url(&self.push_queue_url)
+            .message_body(body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn receive_push_job(&self, wait_time_seconds: i32) -> Result<Option<ReceivedPushJob>> {
+        let response = self
+            .client
+            .receive_message()
+            .queue_url(&self.push_queue_url)
+            .max_number_of_messages(1)
+            .wait_time_seconds(wait_time_seconds)
+            .send()
+            .await?;
+
+        let message = match response.messages().first() {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+
+        let receipt_handle = match message.receipt_handle() {
+            Some(handle) => handle.to_string(),
+            None => {
+                warn!("push queue message missing receipt handle");
+                return Ok(None);
+            }
+        };
+
+        let body = match message.body() {
+            Some(body) => body,
+            None => {
+                warn!("push queue message missing body, deleting");
+                let _ = self.delete_push_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        let job: PushJob = match serde_json::from_str(body) {
+            Ok(job) => job,
+            Err(err) => {
+                warn!(error = ?err, "failed to parse push queue message body");
+                let _ = self.delete_push_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        debug!(subscription_id = %job.subscription_id, "received push job");
+        Ok(Some(ReceivedPushJob { job, receipt_handle }))
+    }
+
+    pub async fn delete_push_message(&self, receipt_handle: &str) -> Result<()> {
+        self.client
+            .delete_message()
+            .queue_url(&self.push_queue_url)
+            .receipt_handle(receipt_handle)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn enqueue_deletion_job(&self, job: &DeletionJob) -> Result<()> {
+        let body = serde_json::to_string(job)?;
+        self.client
+            .send_message()
+            .queue_url(&self.deletion_queue_url)
+            .message_body(body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn receive_deletion_job(
+        &self,
+        wait_time_seconds: i32,
+    ) -> Result<Option<ReceivedDeletionJob>> {
+        let response = self
+            .client
+            .receive_message()
+            .queue_url(&self.deletion_queue_url)
+            .max_number_of_messages(1)
+            .wait_time_seconds(wait_time_seconds)
+            .send()
+            .await?;
+
+        let message = match response.messages().first() {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+
+        let receipt_handle = match message.receipt_handle() {
+            Some(handle) => handle.to_string(),
+            None => {
+                warn!("deletion queue message missing receipt handle");
+                return Ok(None);
+            }
+        };
+
+        let body = match message.body() {
+            Some(body) => body,
+            None => {
+                warn!("deletion queue message missing body, deleting");
+                let _ = self.delete_deletion_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        let job: DeletionJob = match serde_json::from_str(body) {
+            Ok(job) => job,
+            Err(err) => {
+                warn!(error = ?err, "failed to parse deletion queue message body");
+                let _ = self.delete_deletion_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        debug!(keys = job.keys.len(), "received deletion job");
+        Ok(Some(ReceivedDeletionJob { job, receipt_handle }))
+    }
+
+    pub async fn delete_deletion_message(&self, receipt_handle: &str) -> Result<()> {
+        self.client
+            .delete_message()
+            .queue_url(&self.deletion_queue_url)
+            .receipt_handle(receipt_handle)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn enqueue_federation_job(&self, job: &FederationJob) -> Result<()> {
+        let body = serde_json::to_string(job)?;
+        self.client
+            .send_message()
+            .queue_url(&self.federation_queue_url)
+            .message_body(body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn receive_federation_job(
+        &self,
+        wait_time_seconds: i32,
+    ) -> Result<Option<ReceivedFederationJob>> {
+        let response = self
+            .client
+            .receive_message()
+            .queue_url(&self.federation_queue_url)
+            .max_number_of_messages(1)
+            .wait_time_seconds(wait_time_seconds)
+            .send()
+            .await?;
+
+        let message = match response.messages().first() {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+
+        let receipt_handle = match message.receipt_handle() {
+            Some(handle) => handle.to_string(),
+            None => {
+                warn!("federation queue message missing receipt handle");
+                return Ok(None);
+            }
+        };
+
+        let body = match message.body() {
+            Some(body) => body,
+            None => {
+                warn!("federation queue message missing body, deleting");
+                let _ = self.delete_federation_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        let job: FederationJob = match serde_json::from_str(body) {
+            Ok(job) => job,
+            Err(err) => {
+                warn!(error = ?err, "failed to parse federation queue message body");
+                let _ = self.delete_federation_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        debug!("received federation job");
+        Ok(Some(ReceivedFederationJob { job, receipt_handle }))
+    }
+
+    pub async fn delete_federation_message(&self, receipt_handle: &str) -> Result<()> {
+        self.client
+            .delete_message()
+            .queue_url(&self.federation_queue_url)
+            .receipt_handle(receipt_handle)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn enqueue_social_edge_job(&self, job: &SocialEdgeJob) -> Result<()> {
+        let body = serde_json::to_string(job)?;
+        self.client
+            .send_message()
+            .queue_url(&self.social_edge_queue_url)
+            .message_body(body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn receive_social_edge_job(
+        &self,
+        wait_time_seconds: i32,
+    ) -> Result<Option<ReceivedSocialEdgeJob>> {
+        let response = self
+            .client
+            .receive_message()
+            .queue_url(&self.social_edge_queue_url)
+            .max_number_of_messages(1)
+            .wait_time_seconds(wait_time_seconds)
+            .send()
+            .await?;
+
+        let message = match response.messages().first() {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+
+        let receipt_handle = match message.receipt_handle() {
+            Some(handle) => handle.to_string(),
+            None => {
+                warn!("social edge queue message missing receipt handle");
+                return Ok(None);
+            }
+        };
+
+        let body = match message.body() {
+            Some(body) => body,
+            None => {
+                warn!("social edge queue message missing body, deleting");
+                let _ = self.delete_social_edge_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        let job: SocialEdgeJob = match serde_json::from_str(body) {
+            Ok(job) => job,
+            Err(err) => {
+                warn!(error = ?err, "failed to parse social edge queue message body");
+                let _ = self.delete_social_edge_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        debug!("received social edge job");
+        Ok(Some(ReceivedSocialEdgeJob { job, receipt_handle }))
+    }
+
+    pub async fn delete_social_edge_message(&self, receipt_handle: &str) -> Result<()> {
+        self.client
+            .delete_message()
+            .queue_url(&self.social_edge_queue_url)
+            .receipt_handle(receipt_handle)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn enqueue_bot_webhook_job(&self, job: &BotWebhookJob) -> Result<()> {
+        let body = serde_json::to_string(job)?;
+        self.client
+            .send_message()
+            .queue_url(&self.bot_webhook_queue_url)
+            .message_body(body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn receive_bot_webhook_job(
+        &self,
+        wait_time_seconds: i32,
+    ) -> Result<Option<ReceivedBotWebhookJob>> {
+        let response = self
+            .client
+            .receive_message()
+            .queue_url(&self.bot_webhook_queue_url)
+            .max_number_of_messages(1)
+            .wait_time_seconds(wait_time_seconds)
+            .send()
+            .await?;
+
+        let message = match response.messages().first() {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+
+        let receipt_handle = match message.receipt_handle() {
+            Some(handle) => handle.to_string(),
+            None => {
+                warn!("bot webhook queue message missing receipt handle");
+                return Ok(None);
+            }
+        };
+
+        let body = match message.body() {
+            Some(body) => body,
+            None => {
+                warn!("bot webhook queue message missing body, deleting");
+                let _ = self.delete_bot_webhook_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        let job: BotWebhookJob = match serde_json::from_str(body) {
+            Ok(job) => job,
+            Err(err) => {
+                warn!(error = ?err, "failed to parse bot webhook queue message body");
+                let _ = self.delete_bot_webhook_message(&receipt_handle).await;
+                return Ok(None);
+            }
+        };
+
+        debug!(bot_id = %job.bot_id, "received bot webhook job");
+        Ok(Some(ReceivedBotWebhookJob { job, receipt_handle }))
+    }
+
+    pub async fn delete_bot_webhook_message(&self, receipt_handle: &str) -> Result<()> {
+        self.client
+            .delete_message()
+            .queue_url(&self.bot_webhook_queue_url)
+            .receipt_handle(receipt_handle)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+async fn resolve_queue_url(client: &Client, queue_name: &str) -> Result<String> {
+    match client.get_queue_url().queue_name(queue_name).send().await {
+        Ok(response) => Ok(response
+            .queue_url()
+            .ok_or_else(|| anyhow!("missing queue url"))?
+            .to_string()),
+        Err(SdkError::ServiceError(service_err)) if service_err.err().is_queue_does_not_exist() => {
+            let created = client.create_queue().queue_name(queue_name).send().await?;
+            Ok(created
+                .queue_url()
+                .ok_or_else(|| anyhow!("missing queue url"))?
+                .to_string())
+        }
+        Err(err) => Err(anyhow!(err)),
+    }
+}
+
+async fn queue_arn(client: &Client, queue_url: &str) -> Result<String> {
+    let response = client
+        .get_queue_attributes()
+        .queue_url(queue_url)
+        .attribute_names(QueueAttributeName::QueueArn)
+        .send()
+        .await?;
+
+    response
+        .attributes()
+        .and_then(|attrs| attrs.get(&QueueAttributeName::QueueArn))
+        .cloned()
+        .ok_or_else(|| anyhow!("queue {queue_url} has no QueueArn attribute"))
 }
 
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::posts::PostService;
+use crate::infra::db::Db;
+use crate::infra::queue::QueueClient;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Hard-deletes the `follows`/`blocks`/`refresh_tokens` rows, and every post
+/// and its media, belonging to accounts whose `UserService::deactivate_account`
+/// grace window (`grace_days`) has elapsed. Unlike the queue-driven jobs in
+/// this module, there's no message to poll -- this just sweeps `users` on a
+/// fixed interval, mirroring `UserCache`'s GC loop rather than
+/// `deletion_sweeper`'s queue loop.
+pub async fn run(db: Db, queue: QueueClient, grace_days: i64) -> Result<()> {
+    info!("account purge sweeper started");
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match purge_expired_accounts(&db, &queue, grace_days).await {
+            Ok(count) => {
+                if count > 0 {
+                    info!(count, "purged expired deactivated accounts");
+                }
+            }
+            Err(err) => {
+                warn!(error = ?err, "failed to purge expired deactivated accounts");
+            }
+        }
+    }
+}
+
+/// Finds every account past its recovery window and hard-deletes the
+/// social-graph rows `UserService::deactivate_account` left in place, along
+/// with its posts and media -- otherwise the account's media blobs would
+/// never have their refcount released and would accumulate in storage
+/// forever. Returns the number of accounts purged.
+async fn purge_expired_accounts(db: &Db, queue: &QueueClient, grace_days: i64) -> Result<u64> {
+    let expired: Vec<uuid::Uuid> = sqlx::query(
+        "SELECT id FROM users \
+         WHERE deleted_at IS NOT NULL \
+           AND deleted_at <= now() - make_interval(days => $1::int)",
+    )
+    .bind(grace_days as i32)
+    .fetch_all(db.pool())
+    .await?
+    .into_iter()
+    .map(|row| sqlx::Row::get(&row, "id"))
+    .collect();
+
+    let posts = PostService::new(db.clone());
+
+    for user_id in &expired {
+        sqlx::query("DELETE FROM follows WHERE follower_id = $1 OR followee_id = $1")
+            .bind(user_id)
+            .execute(db.pool())
+            .await?;
+
+        sqlx::query("DELETE FROM blocks WHERE blocker_id = $1 OR blocked_id = $1")
+            .bind(user_id)
+            .execute(db.pool())
+            .await?;
+
+        sqlx::query("DELETE FROM refresh_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .execute(db.pool())
+            .await?;
+
+        match posts.purge_posts_for_user(*user_id).await {
+            Ok(deletion_queue) if !deletion_queue.objects.is_empty() => {
+                let job = crate::jobs::deletion_sweeper::DeletionJob {
+                    keys: deletion_queue.objects,
+                };
+                if let Err(err) = queue.enqueue_deletion_job(&job).await {
+                    warn!(error = ?err, user_id = %user_id, "failed to enqueue purged account's orphaned media for deletion");
+                }
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!(error = ?err, user_id = %user_id, "failed to purge posts for expired deactivated account");
+            }
+        }
+    }
+
+    Ok(expired.len() as u64)
+}
@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::app::bots::BotService;
+use crate::infra::{db::Db, queue::QueueClient};
+
+/// A single "this bot's owner was mentioned/followed" event queued by
+/// `NotificationService::create`, destined for one bot's `interactions_url`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotWebhookJob {
+    pub bot_id: Uuid,
+    pub interactions_url: String,
+    pub event: serde_json::Value,
+}
+
+const POLL_WAIT_SECONDS: i32 = 10;
+const IDLE_SLEEP_MS: u64 = 200;
+const ERROR_BACKOFF_MS: u64 = 1000;
+
+/// Drains the bot webhook queue and delivers each event, HMAC-SHA256-signed
+/// with the target bot's `webhook_secret` (see `BotService::webhook_secret`).
+/// Mirrors `federation_sweeper`/`social_federation_dispatcher`: a failed
+/// delivery leaves the queue message undeleted so SQS's visibility timeout
+/// redelivers it later, rather than tracking attempt counts here.
+pub async fn run(db: Db, queue: QueueClient) -> Result<()> {
+    info!("bot webhook dispatcher started");
+    let http = reqwest::Client::new();
+    let bots = BotService::new(db);
+    loop {
+        match queue.receive_bot_webhook_job(POLL_WAIT_SECONDS).await {
+            Ok(Some(message)) => match process_job(&bots, &http, &message.job).await {
+                Ok(()) => {
+                    if let Err(err) = queue.delete_bot_webhook_message(&message.receipt_handle).await
+                    {
+                        warn!(error = ?err, "failed to delete bot webhook queue message");
+                    }
+                }
+                Err(err) => {
+                    warn!(error = ?err, "failed to deliver bot webhook, leaving for retry");
+                }
+            },
+            Ok(None) => {
+                tokio::time::sleep(Duration::from_millis(IDLE_SLEEP_MS)).await;
+            }
+            Err(err) => {
+                warn!(error = ?err, "bot webhook queue receive failed, backing off");
+                tokio::time::sleep(Duration::from_millis(ERROR_BACKOFF_MS)).await;
+            }
+        }
+    }
+}
+
+async fn process_job(bots: &BotService, http: &reqwest::Client, job: &BotWebhookJob) -> Result<()> {
+    let secret = bots
+        .webhook_secret(job.bot_id)
+        .await?
+        .ok_or_else(|| anyhow!("bot {} has no webhook secret on file", job.bot_id))?;
+
+    let body = serde_json::to_vec(&job.event)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC-SHA256 accepts any key length");
+    mac.update(&body);
+    let signature = hex::encode(mac.finalize().into_bytes());
+
+    let response = http
+        .post(&job.interactions_url)
+        .header("X-Lumine-Signature", signature)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "webhook endpoint {} rejected delivery with status {}",
+            job.interactions_url,
+            response.status()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::infra::{queue::QueueClient, storage::ObjectStorage};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletionJob {
+    pub keys: Vec<String>,
+}
+
+const POLL_WAIT_SECONDS: i32 = 10;
+const IDLE_SLEEP_MS: u64 = 200;
+const ERROR_BACKOFF_MS: u64 = 1000;
+
+/// Drains the deletion queue and removes orphaned S3 objects. Deliberately
+/// does not delete the queue message when a key fails to delete -- SQS's
+/// visibility timeout redelivers it, giving transient S3 errors free
+/// retry/backoff without any bookkeeping here.
+pub async fn run(storage: ObjectStorage, queue: QueueClient) -> Result<()> {
+    info!("deletion sweeper started");
+    loop {
+        match queue.receive_deletion_job(POLL_WAIT_SECONDS).await {
+            Ok(Some(message)) => {
+                match process_job(&storage, &message.job).await {
+                    Ok(()) => {
+                        if let Err(err) = queue.delete_deletion_message(&message.receipt_handle).await {
+                            warn!(error = ?err, "failed to delete deletion queue message");
+                        }
+                    }
+                    Err(err) => {
+                        error!(error = ?err, "failed to sweep orphaned media objects, leaving for retry");
+                    }
+                }
+            }
+            Ok(None) => {
+                tokio::time::sleep(Duration::from_millis(IDLE_SLEEP_MS)).await;
+            }
+            Err(err) => {
+                warn!(error = ?err, "deletion queue receive failed, backing off");
+                tokio::time::sleep(Duration::from_millis(ERROR_BACKOFF_MS)).await;
+            }
+        }
+    }
+}
+
+async fn process_job(storage: &ObjectStorage, job: &DeletionJob) -> Result<()> {
+    for key in &job.keys {
+        storage
+            .client()
+            .delete_object()
+            .bucket(storage.bucket())
+            .key(key)
+            .send()
+            .await?;
+    }
+
+    info!(count = job.keys.len(), "swept orphaned media objects");
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::app::signature::SignatureService;
+use crate::infra::{db::Db, queue::QueueClient};
+
+/// A single outbound ActivityPub activity (`Create`/`Delete`/`Follow`/
+/// `Accept`) queued by `FederationService`. `activity` is the already-built
+/// JSON-LD payload. `inbox` is the specific remote inbox URL to deliver it
+/// to when the activity is addressed to one actor directly (e.g. a `Follow`
+/// or the `Accept` owed back for one, see
+/// `FederationService::follow_remote_actor`/`ingest_activity`); `None` for
+/// the broadcast-style story activities, which instead fan out to every
+/// remote follower's inbox at delivery time (see `process_job`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationJob {
+    pub activity: serde_json::Value,
+    #[serde(default)]
+    pub inbox: Option<String>,
+}
+
+const POLL_WAIT_SECONDS: i32 = 10;
+const IDLE_SLEEP_MS: u64 = 200;
+const ERROR_BACKOFF_MS: u64 = 1000;
+
+/// Drains the federation queue and delivers each activity to its resolved
+/// inbox(es), signed with the sending local actor's key (see
+/// `SignatureService::sign`). Mirrors `social_federation_dispatcher`: a
+/// failed delivery leaves the queue message undeleted so SQS's visibility
+/// timeout redelivers it later, rather than tracking attempt counts here.
+pub async fn run(db: Db, queue: QueueClient, base_url: String) -> Result<()> {
+    info!("federation sweeper started");
+    let http = reqwest::Client::new();
+    loop {
+        match queue.receive_federation_job(POLL_WAIT_SECONDS).await {
+            Ok(Some(message)) => {
+                match process_job(&db, &http, &base_url, &message.job).await {
+                    Ok(()) => {
+                        if let Err(err) = queue.delete_federation_message(&message.receipt_handle).await {
+                            warn!(error = ?err, "failed to delete federation queue message");
+                        }
+                    }
+                    Err(err) => {
+                        warn!(error = ?err, "failed to deliver federation activity, leaving for retry");
+                    }
+                }
+            }
+            Ok(None) => {
+                tokio::time::sleep(Duration::from_millis(IDLE_SLEEP_MS)).await;
+            }
+            Err(err) => {
+                warn!(error = ?err, "federation queue receive failed, backing off");
+                tokio::time::sleep(Duration::from_millis(ERROR_BACKOFF_MS)).await;
+            }
+        }
+    }
+}
+
+/// Resolves `job`'s target inbox(es), signs the activity with the local
+/// actor's private key, and POSTs it to each. A broadcast-style job (no
+/// single `inbox`) is delivered to every remote follower on file for the
+/// activity's actor; any of them missing a persisted inbox URL (never
+/// resolved via `follow_remote_actor`/`ingest_activity`) is silently
+/// skipped rather than failing the whole batch.
+///
+/// If any individual delivery fails, the whole job is reported as failed so
+/// the queue redelivers it -- this can re-POST to inboxes that already
+/// succeeded, which is an accepted tradeoff here: ActivityPub
+/// implementations are expected to treat `Follow`/`Like`/`Create` delivery
+/// as idempotent.
+async fn process_job(db: &Db, http: &reqwest::Client, base_url: &str, job: &FederationJob) -> Result<()> {
+    let actor_uri = job.activity["actor"]
+        .as_str()
+        .ok_or_else(|| anyhow!("federation job activity is missing actor"))?;
+    let actor_id = local_actor_id_from_uri(base_url, actor_uri)
+        .ok_or_else(|| anyhow!("federation job actor {} is not a local actor URI", actor_uri))?;
+
+    let private_key_pem: Option<String> =
+        sqlx::query_scalar("SELECT actor_private_key_pem FROM users WHERE id = $1")
+            .bind(actor_id)
+            .fetch_optional(db.pool())
+            .await?
+            .flatten();
+    let private_key_pem = private_key_pem
+        .ok_or_else(|| anyhow!("local actor {} has no private key on file", actor_id))?;
+
+    let inboxes = match &job.inbox {
+        Some(inbox) => vec![inbox.clone()],
+        None => remote_follower_inboxes(db, actor_id).await?,
+    };
+    if inboxes.is_empty() {
+        return Ok(());
+    }
+
+    let body = serde_json::to_vec(&job.activity)?;
+    let mut delivery_failed = false;
+    for inbox in inboxes {
+        if let Err(err) = deliver(http, &inbox, actor_uri, &private_key_pem, &body).await {
+            warn!(error = ?err, inbox = %inbox, "failed to deliver activity to inbox");
+            delivery_failed = true;
+        }
+    }
+
+    if delivery_failed {
+        return Err(anyhow!("one or more inbox deliveries failed"));
+    }
+    Ok(())
+}
+
+/// Every remote follower's persisted inbox for `followee_id`, skipping any
+/// we've recorded a `remote_follows` row for but never resolved an inbox
+/// URL for.
+async fn remote_follower_inboxes(db: &Db, followee_id: Uuid) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar(
+        "SELECT a.inbox_url FROM remote_follows f \
+         JOIN remote_actors a ON a.id = f.remote_actor_id \
+         WHERE f.followee_id = $1 AND a.inbox_url IS NOT NULL",
+    )
+    .bind(followee_id)
+    .fetch_all(db.pool())
+    .await?)
+}
+
+/// The inverse of `FederationService::actor_uri`: recovers the local user
+/// id from `{base_url}/users/{id}/actor`, or `None` if `actor_uri` isn't
+/// shaped like one of ours (it always should be -- every job here is built
+/// from a local actor's own action).
+fn local_actor_id_from_uri(base_url: &str, actor_uri: &str) -> Option<Uuid> {
+    let id_str = actor_uri
+        .strip_prefix(&format!("{}/users/", base_url))
+        .and_then(|rest| rest.strip_suffix("/actor"))?;
+    Uuid::parse_str(id_str).ok()
+}
+
+/// Signs `body` with `private_key_pem` per the HTTP Signatures draft
+/// (`(request-target) host date digest`, RSA-SHA256) and POSTs it to
+/// `inbox` -- the same signature shape
+/// `SignatureService::verify_signed_request` checks on the receiving end.
+async fn deliver(
+    http: &reqwest::Client,
+    inbox: &str,
+    actor_uri: &str,
+    private_key_pem: &str,
+    body: &[u8],
+) -> Result<()> {
+    let url = reqwest::Url::parse(inbox)?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("inbox URL is missing a host"))?
+        .to_string();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let date = OffsetDateTime::now_utc()
+        .format(&Rfc2822)
+        .map_err(|err| anyhow!("failed to format Date header: {}", err))?;
+    let digest = format!("SHA-256={}", BASE64.encode(Sha256::digest(body)));
+
+    let signing_string = format!(
+        "(request-target): post {}\nhost: {}\ndate: {}\ndigest: {}",
+        path, host, date, digest
+    );
+    let signature = SignatureService::sign(private_key_pem, &signing_string)?;
+    let signature_header = format!(
+        "keyId=\"{}#main-key\",algorithm=\"rsa-sha256\",headers=\"(request-target) host date digest\",signature=\"{}\"",
+        actor_uri, signature
+    );
+
+    let response = http
+        .post(inbox)
+        .header("Host", host)
+        .header("Date", date)
+        .header("Digest", digest)
+        .header("Signature", signature_header)
+        .header("Content-Type", "application/activity+json")
+        .body(body.to_vec())
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "remote inbox {} rejected delivery with status {}",
+            inbox,
+            response.status()
+        );
+    }
+
+    Ok(())
+}
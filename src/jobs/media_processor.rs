@@ -1,36 +1,97 @@
 use anyhow::{anyhow, Result};
 use aws_sdk_s3::primitives::ByteStream;
-use image::GenericImageView;
+use image::{DynamicImage, GenericImageView, ImageFormat};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::Row;
+use std::io::Cursor;
 use std::time::Duration;
 use uuid::Uuid;
 use tracing::{error, info, warn};
+use webp::Encoder as WebPEncoder;
 
-use crate::infra::{db::Db, queue::QueueClient, storage::ObjectStorage};
+use crate::app::media::{MediaConcurrencyLimiter, MediaService};
+use crate::config::DerivativeMode;
+use crate::domain::media::MediaKind;
+use crate::infra::{cache::RedisCache, db::Db, queue::QueueClient, storage::ObjectStorage};
+use crate::jobs::deletion_sweeper::DeletionJob;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaJob {
     pub upload_id: Uuid,
     pub owner_id: Uuid,
     pub original_key: String,
+    /// Set by `MediaService::enqueue_processing` when `DerivativeMode::Eager`
+    /// is configured, so the worker knows to pre-render the derivative
+    /// ladder right after this job finishes instead of waiting for the
+    /// first `get_media_derivative` request.
+    #[serde(default)]
+    pub eager_derivatives: bool,
+}
+
+/// Worker-side limits for the video/animated-GIF ingest pipeline.
+#[derive(Debug, Clone)]
+pub struct VideoConfig {
+    pub max_duration_seconds: u64,
 }
 
 const POLL_WAIT_SECONDS: i32 = 10;
 const IDLE_SLEEP_MS: u64 = 200;
 const ERROR_BACKOFF_MS: u64 = 1000;
+/// Base delay for the exponential visibility-timeout backoff applied on
+/// `ProcessingOutcome::RetryLater`, doubled per prior delivery attempt and
+/// capped at `RETRY_BACKOFF_MAX_SECONDS`.
+const RETRY_BACKOFF_BASE_SECONDS: i32 = 15;
+const RETRY_BACKOFF_MAX_SECONDS: i32 = 900;
+const THUMB_MAX_EDGE: u32 = 320;
+const MEDIUM_MAX_EDGE: u32 = 1080;
+const BLURHASH_COMPONENTS_X: u32 = 4;
+const BLURHASH_COMPONENTS_Y: u32 = 3;
+const BLURHASH_SAMPLE_EDGE: u32 = 32;
+const BASE83_CHARS: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+/// Lossy WebP quality (0-100) thumb/medium variants are re-encoded at --
+/// chosen for a small file size at a quality where compression artifacts
+/// aren't visible at thumbnail/feed display sizes.
+const VARIANT_WEBP_QUALITY: f32 = 82.0;
+const WEBP_CONTENT_TYPE: &str = "image/webp";
+const WEBP_EXTENSION: &str = "webp";
 
 enum ProcessingOutcome {
     Completed,
     RetryLater,
 }
 
-pub async fn run(db: Db, storage: ObjectStorage, queue: QueueClient) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    db: Db,
+    cache: RedisCache,
+    storage: ObjectStorage,
+    queue: QueueClient,
+    s3_public_endpoint: Option<String>,
+    variant_guard: crate::app::media::VariantGenerationGuard,
+    derivative_mode: DerivativeMode,
+    concurrency_limiter: MediaConcurrencyLimiter,
+    video_config: VideoConfig,
+) -> Result<()> {
     info!("media processor started");
     loop {
         match queue.receive_media_job(POLL_WAIT_SECONDS).await {
             Ok(Some(message)) => {
-                let outcome = match process_job(&db, &storage, &message.job).await {
+                let outcome = match process_job(
+                    &db,
+                    &cache,
+                    &storage,
+                    &queue,
+                    &s3_public_endpoint,
+                    &variant_guard,
+                    derivative_mode,
+                    &concurrency_limiter,
+                    &video_config,
+                    &message.job,
+                )
+                .await
+                {
                     Ok(outcome) => outcome,
                     Err(err) => {
                         error!(
@@ -43,9 +104,20 @@ pub async fn run(db: Db, storage: ObjectStorage, queue: QueueClient) -> Result<(
                     }
                 };
 
-                if matches!(outcome, ProcessingOutcome::Completed) {
-                    if let Err(err) = queue.delete_message(&message.receipt_handle).await {
-                        warn!(error = ?err, "failed to delete queue message");
+                match outcome {
+                    ProcessingOutcome::Completed => {
+                        if let Err(err) = queue.delete_message(&message.receipt_handle).await {
+                            warn!(error = ?err, "failed to delete queue message");
+                        }
+                    }
+                    ProcessingOutcome::RetryLater => {
+                        let backoff_seconds = retry_backoff_seconds(message.approximate_receive_count);
+                        if let Err(err) = queue
+                            .change_message_visibility(&message.receipt_handle, backoff_seconds)
+                            .await
+                        {
+                            warn!(error = ?err, "failed to set retry backoff on media job");
+                        }
                     }
                 }
             }
@@ -60,27 +132,41 @@ pub async fn run(db: Db, storage: ObjectStorage, queue: QueueClient) -> Result<(
     }
 }
 
+fn retry_backoff_seconds(approximate_receive_count: i32) -> i32 {
+    let attempt = approximate_receive_count.max(1) - 1;
+    RETRY_BACKOFF_BASE_SECONDS
+        .saturating_mul(1i32.checked_shl(attempt as u32).unwrap_or(i32::MAX))
+        .min(RETRY_BACKOFF_MAX_SECONDS)
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn process_job(
     db: &Db,
+    cache: &RedisCache,
     storage: &ObjectStorage,
+    queue: &QueueClient,
+    s3_public_endpoint: &Option<String>,
+    variant_guard: &crate::app::media::VariantGenerationGuard,
+    derivative_mode: DerivativeMode,
+    concurrency_limiter: &MediaConcurrencyLimiter,
+    video_config: &VideoConfig,
     job: &MediaJob,
 ) -> Result<ProcessingOutcome> {
     let row = sqlx::query(
         "UPDATE media_uploads \
          SET status = 'processing' \
          WHERE id = $1 AND owner_id = $2 AND status = 'uploaded' \
-         RETURNING original_key, content_type, bytes",
+         RETURNING original_key, content_type",
     )
     .bind(job.upload_id)
     .bind(job.owner_id)
     .fetch_optional(db.pool())
     .await?;
 
-    let (original_key, content_type, bytes) = match row {
+    let (raw_upload_key, content_type) = match row {
         Some(row) => (
             row.get::<String, _>("original_key"),
             row.get::<String, _>("content_type"),
-            row.get::<i64, _>("bytes"),
         ),
         None => {
             let status_row = sqlx::query(
@@ -93,7 +179,7 @@ async fn process_job(
 
             if let Some(status_row) = status_row {
                 let status: String = status_row.get("status");
-                if status == "completed" {
+                if matches!(status.as_str(), "completed" | "failed" | "rejected") {
                     return Ok(ProcessingOutcome::Completed);
                 }
             }
@@ -105,35 +191,149 @@ async fn process_job(
         .client()
         .get_object()
         .bucket(storage.bucket())
-        .key(&original_key)
+        .key(&raw_upload_key)
         .send()
         .await?;
 
     let data = object.body.collect().await?.into_bytes();
-    let image = image::load_from_memory(&data)
+
+    match media_kind_from_content_type(&content_type) {
+        MediaKind::Image => {
+            process_image_job(
+                db,
+                cache,
+                storage,
+                queue,
+                s3_public_endpoint,
+                variant_guard,
+                derivative_mode,
+                concurrency_limiter,
+                job,
+                raw_upload_key,
+                &content_type,
+                &data,
+            )
+            .await
+        }
+        MediaKind::Video => {
+            process_video_job(
+                db,
+                storage,
+                queue,
+                video_config,
+                job,
+                raw_upload_key,
+                &content_type,
+                &data,
+            )
+            .await
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_image_job(
+    db: &Db,
+    cache: &RedisCache,
+    storage: &ObjectStorage,
+    queue: &QueueClient,
+    s3_public_endpoint: &Option<String>,
+    variant_guard: &crate::app::media::VariantGenerationGuard,
+    derivative_mode: DerivativeMode,
+    concurrency_limiter: &MediaConcurrencyLimiter,
+    job: &MediaJob,
+    raw_upload_key: String,
+    content_type: &str,
+    data: &[u8],
+) -> Result<ProcessingOutcome> {
+    // Format sniffing, thumbnail/medium resizing, and content hashing below
+    // are all CPU-bound, so the whole job body runs under one permit rather
+    // than acquiring separately for each step.
+    let Some(_permit) = concurrency_limiter.acquire().await else {
+        warn!(upload_id = %job.upload_id, "media concurrency limit exceeded, retrying job later");
+        return Ok(ProcessingOutcome::RetryLater);
+    };
+
+    let declared_format = image_format_from_extension(extension_from_content_type(content_type)?)?;
+    let sniffed_format = sniff_image_format(data);
+    if sniffed_format != Some(declared_format) {
+        let reason = match sniffed_format {
+            Some(sniffed) => format!(
+                "declared content type {} does not match actual file format ({:?})",
+                content_type, sniffed
+            ),
+            None => "file does not match a supported image format (jpeg, png, webp)".to_string(),
+        };
+        warn!(upload_id = %job.upload_id, reason = %reason, "rejecting media upload");
+        mark_rejected(db, job, &reason).await?;
+        return Ok(ProcessingOutcome::Completed);
+    }
+
+    let image = image::load_from_memory(data)
         .map_err(|err| anyhow!("failed to decode image: {}", err))?;
+    // `image::load_from_memory` decodes raw pixels without consulting the
+    // EXIF orientation tag, so a photo taken on its side would otherwise
+    // stay sideways in every derivative -- normalize upright before
+    // anything is resized or re-encoded.
+    let orientation = read_exif_orientation(data);
+    let image = apply_exif_orientation(image, orientation);
     let (width, height) = image.dimensions();
 
-    let ext = extension_from_content_type(&content_type)?;
-    let thumb_key = format!("media/{}/{}/thumb.{}", job.owner_id, job.upload_id, ext);
-    let medium_key = format!("media/{}/{}/medium.{}", job.owner_id, job.upload_id, ext);
+    let ext = extension_from_content_type(content_type)?;
+    let format = image_format_from_extension(ext)?;
+
+    // Re-encoding through `image` rather than storing the uploaded bytes
+    // as-is is what strips EXIF/ICC/XMP (GPS, camera serial, etc.) from the
+    // original, not just the derived variants -- `DynamicImage` never
+    // carried that metadata in the first place.
+    let original_data = encode_variant(&image, format)?;
+    let original_bytes = original_data.len() as i64;
+
+    let thumb_image = image.thumbnail(THUMB_MAX_EDGE, THUMB_MAX_EDGE);
+    let (thumb_width, thumb_height) = thumb_image.dimensions();
+    let thumb_data = encode_webp_variant(&thumb_image, VARIANT_WEBP_QUALITY)?;
+    let thumb_blurhash = encode_blurhash(&thumb_image, BLURHASH_COMPONENTS_X, BLURHASH_COMPONENTS_Y);
 
-    upload_variant(storage, &thumb_key, &content_type, data.clone()).await?;
-    upload_variant(storage, &medium_key, &content_type, data.clone()).await?;
+    let medium_image = image.thumbnail(MEDIUM_MAX_EDGE, MEDIUM_MAX_EDGE);
+    let (medium_width, medium_height) = medium_image.dimensions();
+    let medium_data = encode_webp_variant(&medium_image, VARIANT_WEBP_QUALITY)?;
+
+    let thumb_bytes = thumb_data.len() as i64;
+    let medium_bytes = medium_data.len() as i64;
+
+    // Every variant is addressed by the hash of its own bytes, so identical
+    // uploads -- or identical derived thumbnails -- from any user land on the
+    // same blob and are only stored once. Thumb/medium are re-encoded to
+    // WebP regardless of the original's format, so their key/content-type
+    // diverge from the original's.
+    let original_key = acquire_blob(db, storage, &original_data, content_type, ext).await?;
+    let thumb_key = acquire_blob(db, storage, &thumb_data, WEBP_CONTENT_TYPE, WEBP_EXTENSION).await?;
+    let medium_key = acquire_blob(db, storage, &medium_data, WEBP_CONTENT_TYPE, WEBP_EXTENSION).await?;
 
     let media_id = Uuid::new_v4();
     sqlx::query(
-        "INSERT INTO media (id, owner_id, original_key, thumb_key, medium_key, width, height, bytes) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        "INSERT INTO media \
+         (id, owner_id, kind, original_key, thumb_key, medium_key, \
+          original_width, original_height, original_bytes, \
+          thumb_width, thumb_height, thumb_bytes, \
+          medium_width, medium_height, medium_bytes, blurhash) \
+         VALUES ($1, $2, 'image', $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
     )
     .bind(media_id)
     .bind(job.owner_id)
-    .bind(original_key)
+    .bind(original_key.clone())
     .bind(thumb_key)
     .bind(medium_key)
     .bind(width as i32)
     .bind(height as i32)
-    .bind(bytes)
+    .bind(original_bytes)
+    .bind(thumb_width as i32)
+    .bind(thumb_height as i32)
+    .bind(thumb_bytes)
+    .bind(medium_width as i32)
+    .bind(medium_height as i32)
+    .bind(medium_bytes)
+    .bind(thumb_blurhash)
     .execute(db.pool())
     .await?;
 
@@ -148,10 +348,221 @@ async fn process_job(
     .execute(db.pool())
     .await?;
 
+    if job.eager_derivatives {
+        let service = MediaService::new(
+            db.clone(),
+            cache.clone(),
+            storage.clone(),
+            queue.clone(),
+            s3_public_endpoint.clone(),
+            variant_guard.clone(),
+            derivative_mode,
+        );
+        if let Err(err) = service.prerender_derivatives(media_id, &original_key).await {
+            warn!(error = ?err, media_id = %media_id, "failed to pre-render derivatives");
+        }
+    }
+
+    enqueue_raw_upload_cleanup(queue, job, raw_upload_key).await;
+
     info!(upload_id = %job.upload_id, media_id = %media_id, "media processing completed");
     Ok(ProcessingOutcome::Completed)
 }
 
+/// Transcodes an uploaded video (or animated GIF, normalized the same way)
+/// to a web-friendly mp4 via ffmpeg, probes its dimensions/duration via
+/// ffprobe, and extracts a still frame to feed into the existing
+/// thumb/medium image pipeline as the poster.
+async fn process_video_job(
+    db: &Db,
+    storage: &ObjectStorage,
+    queue: &QueueClient,
+    video_config: &VideoConfig,
+    job: &MediaJob,
+    raw_upload_key: String,
+    content_type: &str,
+    data: &[u8],
+) -> Result<ProcessingOutcome> {
+    let ext = extension_from_content_type(content_type)?;
+    if !sniff_video_format(data, ext) {
+        let reason =
+            "file does not match a supported video format (mp4, webm, gif)".to_string();
+        warn!(upload_id = %job.upload_id, reason = %reason, "rejecting media upload");
+        mark_rejected(db, job, &reason).await?;
+        return Ok(ProcessingOutcome::Completed);
+    }
+
+    let source_path = write_temp_file(data, ext).await?;
+    let processed = process_video_file(db, storage, job, video_config, &source_path).await;
+    let _ = tokio::fs::remove_file(&source_path).await;
+
+    let processed = match processed? {
+        Some(processed) => processed,
+        None => return Ok(ProcessingOutcome::Completed),
+    };
+
+    let media_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO media \
+         (id, owner_id, kind, original_key, thumb_key, medium_key, \
+          original_width, original_height, original_bytes, \
+          thumb_width, thumb_height, thumb_bytes, \
+          medium_width, medium_height, medium_bytes, duration_ms, blurhash) \
+         VALUES ($1, $2, 'video', $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
+    )
+    .bind(media_id)
+    .bind(job.owner_id)
+    .bind(processed.original_key)
+    .bind(processed.thumb_key)
+    .bind(processed.medium_key)
+    .bind(processed.probe.width as i32)
+    .bind(processed.probe.height as i32)
+    .bind(processed.original_bytes)
+    .bind(processed.thumb_width as i32)
+    .bind(processed.thumb_height as i32)
+    .bind(processed.thumb_bytes)
+    .bind(processed.medium_width as i32)
+    .bind(processed.medium_height as i32)
+    .bind(processed.medium_bytes)
+    .bind(processed.probe.duration_ms)
+    .bind(processed.thumb_blurhash)
+    .execute(db.pool())
+    .await?;
+
+    sqlx::query(
+        "UPDATE media_uploads \
+         SET status = 'completed', processed_media_id = $1 \
+         WHERE id = $2 AND owner_id = $3",
+    )
+    .bind(media_id)
+    .bind(job.upload_id)
+    .bind(job.owner_id)
+    .execute(db.pool())
+    .await?;
+
+    enqueue_raw_upload_cleanup(queue, job, raw_upload_key).await;
+
+    info!(upload_id = %job.upload_id, media_id = %media_id, "video media processing completed");
+    Ok(ProcessingOutcome::Completed)
+}
+
+/// Everything the video pipeline derives from the source file once it's
+/// downloaded to disk: the transcoded original plus its poster thumb/medium.
+struct ProcessedVideo {
+    original_key: String,
+    original_bytes: i64,
+    thumb_key: String,
+    thumb_width: u32,
+    thumb_height: u32,
+    thumb_bytes: i64,
+    medium_key: String,
+    medium_width: u32,
+    medium_height: u32,
+    medium_bytes: i64,
+    thumb_blurhash: String,
+    probe: VideoProbe,
+}
+
+/// The ffmpeg/ffprobe-heavy part of [`process_video_job`], split out so the
+/// caller can clean up the temp file with `?` still in play. Returns `None`
+/// (after rejecting the upload) if the video fails to probe, exceeds the
+/// configured duration limit, or fails to transcode.
+async fn process_video_file(
+    db: &Db,
+    storage: &ObjectStorage,
+    job: &MediaJob,
+    video_config: &VideoConfig,
+    source_path: &std::path::Path,
+) -> Result<Option<ProcessedVideo>> {
+    let probe = match probe_video(source_path).await {
+        Ok(probe) => probe,
+        Err(err) => {
+            let reason = format!("failed to probe video: {}", err);
+            warn!(upload_id = %job.upload_id, reason = %reason, "rejecting media upload");
+            mark_rejected(db, job, &reason).await?;
+            return Ok(None);
+        }
+    };
+
+    let max_duration_ms = video_config.max_duration_seconds as i64 * 1000;
+    if probe.duration_ms > max_duration_ms {
+        let reason = format!(
+            "video duration {}ms exceeds the {}s limit",
+            probe.duration_ms, video_config.max_duration_seconds
+        );
+        warn!(upload_id = %job.upload_id, reason = %reason, "rejecting media upload");
+        mark_rejected(db, job, &reason).await?;
+        return Ok(None);
+    }
+
+    let transcoded = match transcode_video(source_path).await {
+        Ok(transcoded) => transcoded,
+        Err(err) => {
+            let reason = format!("failed to transcode video: {}", err);
+            warn!(upload_id = %job.upload_id, reason = %reason, "rejecting media upload");
+            mark_rejected(db, job, &reason).await?;
+            return Ok(None);
+        }
+    };
+
+    let still_frame_png = match extract_still_frame(source_path).await {
+        Ok(data) => data,
+        Err(err) => {
+            let reason = format!("failed to extract poster frame: {}", err);
+            warn!(upload_id = %job.upload_id, reason = %reason, "rejecting media upload");
+            mark_rejected(db, job, &reason).await?;
+            return Ok(None);
+        }
+    };
+
+    let still_image = image::load_from_memory(&still_frame_png)
+        .map_err(|err| anyhow!("failed to decode extracted poster frame: {}", err))?;
+
+    let thumb_image = still_image.thumbnail(THUMB_MAX_EDGE, THUMB_MAX_EDGE);
+    let (thumb_width, thumb_height) = thumb_image.dimensions();
+    let thumb_data = encode_webp_variant(&thumb_image, VARIANT_WEBP_QUALITY)?;
+    let thumb_blurhash = encode_blurhash(&thumb_image, BLURHASH_COMPONENTS_X, BLURHASH_COMPONENTS_Y);
+
+    let medium_image = still_image.thumbnail(MEDIUM_MAX_EDGE, MEDIUM_MAX_EDGE);
+    let (medium_width, medium_height) = medium_image.dimensions();
+    let medium_data = encode_webp_variant(&medium_image, VARIANT_WEBP_QUALITY)?;
+
+    let thumb_bytes = thumb_data.len() as i64;
+    let medium_bytes = medium_data.len() as i64;
+    let original_bytes = transcoded.data.len() as i64;
+
+    let original_key = acquire_blob(db, storage, &transcoded.data, "video/mp4", "mp4").await?;
+    let thumb_key = acquire_blob(db, storage, &thumb_data, WEBP_CONTENT_TYPE, WEBP_EXTENSION).await?;
+    let medium_key = acquire_blob(db, storage, &medium_data, WEBP_CONTENT_TYPE, WEBP_EXTENSION).await?;
+
+    Ok(Some(ProcessedVideo {
+        original_key,
+        original_bytes,
+        thumb_key,
+        thumb_width,
+        thumb_height,
+        thumb_bytes,
+        medium_key,
+        medium_width,
+        medium_height,
+        medium_bytes,
+        thumb_blurhash,
+        probe,
+    }))
+}
+
+async fn enqueue_raw_upload_cleanup(queue: &QueueClient, job: &MediaJob, raw_upload_key: String) {
+    // The raw presigned-upload object has now been superseded by the
+    // content-addressed original blob; nothing references it anymore, so
+    // hand it to the sweeper instead of leaving it to linger in S3 forever.
+    let cleanup = DeletionJob {
+        keys: vec![raw_upload_key],
+    };
+    if let Err(err) = queue.enqueue_deletion_job(&cleanup).await {
+        warn!(error = ?err, upload_id = %job.upload_id, "failed to enqueue raw upload object for deletion");
+    }
+}
+
 async fn upload_variant(
     storage: &ObjectStorage,
     key: &str,
@@ -170,6 +581,46 @@ async fn upload_variant(
     Ok(())
 }
 
+/// Stores `data` as a content-addressed blob and returns the key to
+/// reference it by. `media_blobs` is keyed by the SHA-256 hash of `data`, so
+/// two uploads (or two derived variants) with identical bytes -- from the
+/// same user or different ones -- share one S3 object and one refcount.
+/// `(xmax = 0) AS inserted` (see `stories::add_reaction` for the same idiom)
+/// tells a fresh insert apart from an ON CONFLICT bump, so the upload to S3
+/// only happens the first time a given hash is seen.
+async fn acquire_blob(
+    db: &Db,
+    storage: &ObjectStorage,
+    data: &[u8],
+    content_type: &str,
+    ext: &str,
+) -> Result<String> {
+    let hash = hex::encode(Sha256::digest(data));
+    let key = format!("media/blobs/{}.{}", hash, ext);
+
+    let row = sqlx::query(
+        "INSERT INTO media_blobs (hash, refcount, key, content_type, bytes) \
+         VALUES ($1, 1, $2, $3, $4) \
+         ON CONFLICT (hash) DO UPDATE SET refcount = media_blobs.refcount + 1 \
+         RETURNING key, (xmax = 0) AS inserted",
+    )
+    .bind(&hash)
+    .bind(&key)
+    .bind(content_type)
+    .bind(data.len() as i64)
+    .fetch_one(db.pool())
+    .await?;
+
+    let stored_key: String = row.get("key");
+    let inserted: bool = row.get("inserted");
+
+    if inserted {
+        upload_variant(storage, &stored_key, content_type, data.to_vec().into()).await?;
+    }
+
+    Ok(stored_key)
+}
+
 async fn mark_failed(db: &Db, job: &MediaJob) -> Result<()> {
     sqlx::query(
         "UPDATE media_uploads \
@@ -183,11 +634,461 @@ async fn mark_failed(db: &Db, job: &MediaJob) -> Result<()> {
     Ok(())
 }
 
+/// Rejects an upload whose bytes don't actually match the declared content
+/// type (or no supported format at all), so the client can show the
+/// `rejection_reason` instead of polling a status that will never reach
+/// `completed`. Terminal, like `mark_failed`.
+async fn mark_rejected(db: &Db, job: &MediaJob, reason: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE media_uploads \
+         SET status = 'rejected', rejection_reason = $1 \
+         WHERE id = $2 AND owner_id = $3 AND status = 'processing'",
+    )
+    .bind(reason)
+    .bind(job.upload_id)
+    .bind(job.owner_id)
+    .execute(db.pool())
+    .await?;
+    Ok(())
+}
+
+/// Identifies the real image format from its leading bytes, ignoring
+/// whatever `content_type` the client declared at upload time. Covers the
+/// three formats `extension_from_content_type` accepts.
+fn sniff_image_format(data: &[u8]) -> Option<ImageFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else if data.starts_with(&[0x89, 0x50, 0x4E, 0x47]) {
+        Some(ImageFormat::Png)
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some(ImageFormat::WebP)
+    } else {
+        None
+    }
+}
+
 fn extension_from_content_type(content_type: &str) -> Result<&'static str> {
     match content_type {
         "image/jpeg" => Ok("jpg"),
         "image/png" => Ok("png"),
         "image/webp" => Ok("webp"),
+        "image/gif" => Ok("gif"),
+        "video/mp4" => Ok("mp4"),
+        "video/webm" => Ok("webm"),
         _ => Err(anyhow!("unsupported content type")),
     }
 }
+
+/// Animated GIFs are normalized into the video pipeline (transcoded to mp4
+/// like any other video) rather than kept as a third `MediaKind`.
+fn media_kind_from_content_type(content_type: &str) -> MediaKind {
+    match content_type {
+        "video/mp4" | "video/webm" | "image/gif" => MediaKind::Video,
+        _ => MediaKind::Image,
+    }
+}
+
+fn image_format_from_extension(ext: &str) -> Result<ImageFormat> {
+    match ext {
+        "jpg" => Ok(ImageFormat::Jpeg),
+        "png" => Ok(ImageFormat::Png),
+        "webp" => Ok(ImageFormat::WebP),
+        _ => Err(anyhow!("unsupported image extension")),
+    }
+}
+
+/// Encodes the (orientation-corrected) original back to its own declared
+/// format. `DynamicImage` carries no EXIF/ICC/XMP metadata from the source
+/// bytes -- decoding through `image::load_from_memory` and re-encoding here
+/// is what strips it, so the stored original never leaks the upload's
+/// GPS/camera metadata.
+fn encode_variant(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    image
+        .write_to(&mut buf, format)
+        .map_err(|err| anyhow!("failed to encode image variant: {}", err))?;
+    Ok(buf.into_inner())
+}
+
+/// Encodes a thumb/medium derivative as lossy WebP at `quality` rather than
+/// the original's own format -- WebP beats JPEG/PNG at the same perceptual
+/// quality for the sizes these variants are actually displayed at, and every
+/// client this API serves already has a WebP decoder.
+fn encode_webp_variant(image: &DynamicImage, quality: f32) -> Result<Vec<u8>> {
+    let rgba = image.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let encoder = WebPEncoder::from_rgba(&rgba, width, height);
+    Ok(encoder.encode(quality).to_vec())
+}
+
+/// Reads the EXIF orientation tag (0x0112) out of a JPEG's APP1 segment, or
+/// `1` (no-op) if the file isn't a JPEG, has no EXIF block, or the block has
+/// no orientation tag -- PNG/WebP uploads carry no EXIF orientation to
+/// normalize in the first place.
+fn read_exif_orientation(data: &[u8]) -> u32 {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return 1;
+    }
+
+    let mut offset = 2;
+    while offset + 4 <= data.len() {
+        if data[offset] != 0xFF {
+            break;
+        }
+        let marker = data[offset + 1];
+        if marker == 0xD8 || marker == 0xD9 || marker == 0xDA {
+            break;
+        }
+        let segment_len = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        if marker == 0xE1 && offset + 10 <= data.len() && &data[offset + 4..offset + 10] == b"Exif\0\0" {
+            let segment_end = (offset + 2 + segment_len).min(data.len());
+            return parse_tiff_orientation(&data[offset + 10..segment_end]).unwrap_or(1);
+        }
+        if segment_len < 2 {
+            break;
+        }
+        offset += 2 + segment_len;
+    }
+    1
+}
+
+/// Walks a TIFF header's 0th IFD (the structure EXIF embeds its tags in)
+/// looking for the orientation tag. Returns `None` if the header is
+/// malformed or the tag isn't present, so the caller can fall back to "no
+/// rotation" rather than erroring the whole upload over unparseable EXIF.
+fn parse_tiff_orientation(tiff: &[u8]) -> Option<u32> {
+    if tiff.len() < 8 {
+        return None;
+    }
+    let little_endian = match &tiff[0..2] {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |b: &[u8]| -> u16 {
+        if little_endian {
+            u16::from_le_bytes([b[0], b[1]])
+        } else {
+            u16::from_be_bytes([b[0], b[1]])
+        }
+    };
+    let read_u32 = |b: &[u8]| -> u32 {
+        if little_endian {
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        } else {
+            u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+        }
+    };
+
+    let ifd_offset = read_u32(tiff.get(4..8)?) as usize;
+    let entry_count = read_u16(tiff.get(ifd_offset..ifd_offset + 2)?) as usize;
+    let mut pos = ifd_offset + 2;
+    for _ in 0..entry_count {
+        let entry = tiff.get(pos..pos + 12)?;
+        let tag = read_u16(&entry[0..2]);
+        if tag == 0x0112 {
+            return Some(read_u16(&entry[8..10]) as u32);
+        }
+        pos += 12;
+    }
+    None
+}
+
+/// Rotates/mirrors a decoded image to "upright" per its EXIF orientation
+/// value (1-8, per the EXIF spec) -- `image::load_from_memory` decodes raw
+/// pixels without applying it, so every derivative generated downstream
+/// would otherwise inherit a sideways or mirrored source.
+fn apply_exif_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// Computes a BlurHash (https://blurha.sh) for `image`, encoded as
+/// `components_x` x `components_y` DCT-like components. Clients decode this
+/// short string into a blurred color placeholder to render instantly while
+/// the real presigned thumbnail is still loading.
+fn encode_blurhash(image: &DynamicImage, components_x: u32, components_y: u32) -> String {
+    // A handful of low-frequency components is all BlurHash needs, so
+    // downscale first rather than summing over every pixel of the thumbnail.
+    let sample = image
+        .thumbnail(BLURHASH_SAMPLE_EDGE, BLURHASH_SAMPLE_EDGE)
+        .to_rgb8();
+    let (width, height) = sample.dimensions();
+
+    let mut factors = Vec::with_capacity((components_x * components_y) as usize);
+    for cy in 0..components_y {
+        for cx in 0..components_x {
+            factors.push(blurhash_component(&sample, width, height, cx, cy));
+        }
+    }
+
+    let dc = factors[0];
+    let ac = &factors[1..];
+
+    let size_flag = (components_x - 1) + (components_y - 1) * 9;
+    let mut result = encode83(size_flag, 1);
+
+    let max_value = if ac.is_empty() {
+        result.push_str(&encode83(0, 1));
+        1.0
+    } else {
+        let actual_max = ac
+            .iter()
+            .flat_map(|&(r, g, b)| [r.abs(), g.abs(), b.abs()])
+            .fold(0.0_f64, f64::max);
+        let quantized = ((actual_max * 166.0 - 0.5).floor() as i64).clamp(0, 82) as u32;
+        result.push_str(&encode83(quantized, 1));
+        (quantized as f64 + 1.0) / 166.0
+    };
+
+    result.push_str(&encode83(encode_blurhash_dc(dc), 4));
+    for &(r, g, b) in ac {
+        result.push_str(&encode83(encode_blurhash_ac(r, g, b, max_value), 2));
+    }
+
+    result
+}
+
+/// One DCT-like basis coefficient: the pixel-weighted average of
+/// `cos(pi*cx*x/W)*cos(pi*cy*y/H)` over every pixel's linear RGB. `(0, 0)` is
+/// the DC (average color) term; everything else is an AC component.
+fn blurhash_component(
+    image: &image::RgbImage,
+    width: u32,
+    height: u32,
+    cx: u32,
+    cy: u32,
+) -> (f64, f64, f64) {
+    let mut r = 0.0;
+    let mut g = 0.0;
+    let mut b = 0.0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let basis = (std::f64::consts::PI * cx as f64 * x as f64 / width as f64).cos()
+                * (std::f64::consts::PI * cy as f64 * y as f64 / height as f64).cos();
+            let pixel = image.get_pixel(x, y);
+            r += basis * srgb_to_linear(pixel[0]);
+            g += basis * srgb_to_linear(pixel[1]);
+            b += basis * srgb_to_linear(pixel[2]);
+        }
+    }
+
+    let scale = if cx == 0 && cy == 0 {
+        1.0 / (width as f64 * height as f64)
+    } else {
+        2.0 / (width as f64 * height as f64)
+    };
+
+    (r * scale, g * scale, b * scale)
+}
+
+fn encode_blurhash_dc(value: (f64, f64, f64)) -> u32 {
+    let (r, g, b) = value;
+    ((linear_to_srgb(r) << 16) | (linear_to_srgb(g) << 8) | linear_to_srgb(b)) as u32
+}
+
+fn encode_blurhash_ac(r: f64, g: f64, b: f64, max_value: f64) -> u32 {
+    let quantize = |value: f64| -> u32 {
+        (sign_pow(value / max_value, 0.5) * 9.0 + 9.5)
+            .floor()
+            .clamp(0.0, 18.0) as u32
+    };
+    quantize(r) * 19 * 19 + quantize(g) * 19 + quantize(b)
+}
+
+fn sign_pow(value: f64, exp: f64) -> f64 {
+    value.signum() * value.abs().powf(exp)
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let value = value as f64 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> i64 {
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.0031308 {
+        value * 12.92 * 255.0 + 0.5
+    } else {
+        (1.055 * value.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5
+    };
+    (encoded.round() as i64).clamp(0, 255)
+}
+
+fn encode83(mut value: u32, length: usize) -> String {
+    let mut chars = vec![0u8; length];
+    for i in (0..length).rev() {
+        chars[i] = BASE83_CHARS[(value % 83) as usize];
+        value /= 83;
+    }
+    String::from_utf8(chars).expect("BASE83_CHARS is ASCII")
+}
+
+/// Identifies the real video format from its leading bytes, ignoring
+/// whatever `content_type` the client declared at upload time -- the same
+/// role `sniff_image_format` plays for images.
+fn sniff_video_format(data: &[u8], ext: &str) -> bool {
+    match ext {
+        "mp4" => data.len() >= 12 && &data[4..8] == b"ftyp",
+        "webm" => data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]),
+        "gif" => data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a"),
+        _ => false,
+    }
+}
+
+/// Writes `data` out to a unique path under the OS temp directory so ffmpeg
+/// and ffprobe, which only operate on real files, have something to read.
+/// The caller is responsible for removing it once processing finishes.
+async fn write_temp_file(data: &[u8], ext: &str) -> Result<std::path::PathBuf> {
+    let path = std::env::temp_dir().join(format!("media-upload-{}.{}", Uuid::new_v4(), ext));
+    tokio::fs::write(&path, data).await?;
+    Ok(path)
+}
+
+/// Dimensions and duration probed via `ffprobe`, ahead of transcoding, so an
+/// over-long upload can be rejected without ever touching ffmpeg.
+struct VideoProbe {
+    width: u32,
+    height: u32,
+    duration_ms: i64,
+}
+
+#[derive(Deserialize)]
+struct FfprobeOutput {
+    format: FfprobeFormat,
+    streams: Vec<FfprobeStream>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeFormat {
+    duration: String,
+}
+
+#[derive(Deserialize)]
+struct FfprobeStream {
+    width: u32,
+    height: u32,
+}
+
+async fn probe_video(path: &std::path::Path) -> Result<VideoProbe> {
+    let output = tokio::process::Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_entries",
+            "format=duration:stream=width,height",
+            "-select_streams",
+            "v:0",
+        ])
+        .arg(path)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffprobe exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let parsed: FfprobeOutput = serde_json::from_slice(&output.stdout)
+        .map_err(|err| anyhow!("failed to parse ffprobe output: {}", err))?;
+    let stream = parsed
+        .streams
+        .first()
+        .ok_or_else(|| anyhow!("ffprobe found no video stream"))?;
+    let duration_seconds: f64 = parsed
+        .format
+        .duration
+        .parse()
+        .map_err(|err| anyhow!("failed to parse ffprobe duration: {}", err))?;
+
+    Ok(VideoProbe {
+        width: stream.width,
+        height: stream.height,
+        duration_ms: (duration_seconds * 1000.0).round() as i64,
+    })
+}
+
+/// An mp4 transcoded via ffmpeg: H.264 video, AAC audio, `+faststart` so
+/// clients can begin playback before the whole file downloads.
+struct Transcoded {
+    data: Vec<u8>,
+}
+
+async fn transcode_video(source: &std::path::Path) -> Result<Transcoded> {
+    let output_path = std::env::temp_dir().join(format!("media-transcode-{}.mp4", Uuid::new_v4()));
+
+    let output = tokio::process::Command::new("ffmpeg")
+        .args(["-y", "-i"])
+        .arg(source)
+        .args([
+            "-movflags",
+            "+faststart",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-crf",
+            "23",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            "-pix_fmt",
+            "yuv420p",
+        ])
+        .arg(&output_path)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let _ = tokio::fs::remove_file(&output_path).await;
+        return Err(anyhow!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let data = tokio::fs::read(&output_path).await?;
+    let _ = tokio::fs::remove_file(&output_path).await;
+    Ok(Transcoded { data })
+}
+
+/// Extracts the first frame as a PNG, to feed into the existing image
+/// thumb/medium pipeline as the video's poster.
+async fn extract_still_frame(source: &std::path::Path) -> Result<Vec<u8>> {
+    let output = tokio::process::Command::new("ffmpeg")
+        .args(["-y", "-i"])
+        .arg(source)
+        .args(["-vframes", "1", "-f", "image2pipe", "-vcodec", "png", "-"])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(output.stdout)
+}
@@ -0,0 +1,10 @@
+pub mod account_purge;
+pub mod bot_webhook_dispatcher;
+pub mod deletion_sweeper;
+pub mod federation_sweeper;
+pub mod media_processor;
+pub mod push_dispatcher;
+pub mod search_index_committer;
+pub mod social_federation_dispatcher;
+pub mod story_sweep;
+pub mod trust_maintenance;
@@ -0,0 +1,274 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes128Gcm, Nonce};
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hkdf::Hkdf;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::{PublicKey, SecretKey};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use url::Url;
+
+use crate::app::push::{PushJob, PushService, PushSubscriptionRow};
+use crate::infra::push_sender::{PushOutcome, PushSender};
+use crate::infra::{db::Db, queue::QueueClient};
+
+const POLL_WAIT_SECONDS: i32 = 10;
+const IDLE_SLEEP_MS: u64 = 200;
+const ERROR_BACKOFF_MS: u64 = 1000;
+const VAPID_TOKEN_TTL_SECONDS: i64 = 12 * 3600;
+
+/// Web Push's de facto size ceiling (most services, including FCM and
+/// Mozilla's autopush, reject a larger `aes128gcm` record). A notification
+/// whose encoded title/body would exceed this is sent as an id + type stub
+/// instead -- the client is expected to fetch the full notification via
+/// `GET /v1/notifications` when it receives one.
+const MAX_PUSH_PAYLOAD_BYTES: usize = 4096;
+
+#[derive(Clone)]
+pub struct VapidConfig {
+    pub private_key: [u8; 32],
+    pub subject: String,
+}
+
+pub async fn run(
+    db: Db,
+    queue: QueueClient,
+    sender: std::sync::Arc<dyn PushSender>,
+    vapid: VapidConfig,
+) -> Result<()> {
+    info!("push dispatcher started");
+    let push_service = PushService::new(db, queue.clone());
+
+    loop {
+        match queue.receive_push_job(POLL_WAIT_SECONDS).await {
+            Ok(Some(message)) => {
+                match process_job(&push_service, sender.as_ref(), &vapid, &message.job).await {
+                    Ok(()) => {}
+                    Err(err) => {
+                        error!(error = ?err, subscription_id = %message.job.subscription_id, "failed to dispatch push notification");
+                    }
+                }
+
+                if let Err(err) = queue.delete_push_message(&message.receipt_handle).await {
+                    warn!(error = ?err, "failed to delete push queue message");
+                }
+            }
+            Ok(None) => {
+                tokio::time::sleep(Duration::from_millis(IDLE_SLEEP_MS)).await;
+            }
+            Err(err) => {
+                warn!(error = ?err, "push queue receive failed, backing off");
+                tokio::time::sleep(Duration::from_millis(ERROR_BACKOFF_MS)).await;
+            }
+        }
+    }
+}
+
+/// Look up the subscription, encrypt the payload per RFC 8291, sign a VAPID
+/// JWT, and deliver it -- retiring the subscription if the push service says
+/// it's gone. Exposed standalone (rather than folded into `run`'s loop body)
+/// so tests can drive one job synchronously without a background worker.
+pub async fn process_job(
+    push_service: &PushService,
+    sender: &dyn PushSender,
+    vapid: &VapidConfig,
+    job: &PushJob,
+) -> Result<()> {
+    let subscription = match push_service.get_subscription(job.subscription_id).await? {
+        Some(subscription) => subscription,
+        None => return Ok(()),
+    };
+
+    let payload = build_payload(job)?;
+    let encrypted = encrypt_payload(&subscription, &payload)?;
+    let headers = build_headers(&subscription.endpoint, &encrypted, vapid)?;
+
+    match sender
+        .send(&subscription.endpoint, headers, encrypted.body)
+        .await?
+    {
+        PushOutcome::Delivered => Ok(()),
+        PushOutcome::Gone => {
+            push_service.retire_subscription(subscription.id).await?;
+            Ok(())
+        }
+        PushOutcome::Failed => Err(anyhow!("push service rejected delivery")),
+    }
+}
+
+#[derive(Serialize)]
+struct NotificationPayload<'a> {
+    title: &'a str,
+    body: &'a str,
+}
+
+#[derive(Serialize)]
+struct NotificationStub {
+    id: uuid::Uuid,
+    #[serde(rename = "type")]
+    notification_type: String,
+}
+
+/// Encode `job`'s title/body, falling back to an id + type stub when the
+/// full payload wouldn't fit `MAX_PUSH_PAYLOAD_BYTES` -- the client treats a
+/// stub as a cue to fetch the real notification via `GET /v1/notifications`
+/// rather than rendering it directly.
+fn build_payload(job: &PushJob) -> Result<Vec<u8>> {
+    let full = serde_json::to_vec(&NotificationPayload {
+        title: &job.title,
+        body: &job.body,
+    })?;
+
+    if full.len() <= MAX_PUSH_PAYLOAD_BYTES {
+        return Ok(full);
+    }
+
+    Ok(serde_json::to_vec(&NotificationStub {
+        id: job.notification_id,
+        notification_type: job.notification_type.clone(),
+    })?)
+}
+
+struct EncryptedPayload {
+    body: Vec<u8>,
+    salt: [u8; 16],
+    as_public_key: Vec<u8>,
+}
+
+/// RFC 8291 `aes128gcm` content coding: derive a shared secret via ECDH
+/// between a fresh "application server" keypair and the subscription's
+/// `p256dh` key, run the two HKDF stages (auth secret, then salt + as_public)
+/// to get the content-encryption key and nonce, and AEAD-seal the payload
+/// with a single empty-final-record pad byte per the spec's minimal framing.
+fn encrypt_payload(subscription: &PushSubscriptionRow, plaintext: &[u8]) -> Result<EncryptedPayload> {
+    let ua_public = STANDARD
+        .decode(subscription.p256dh.as_bytes())
+        .map_err(|_| anyhow!("invalid p256dh"))?;
+    let auth_secret = STANDARD
+        .decode(subscription.auth.as_bytes())
+        .map_err(|_| anyhow!("invalid auth secret"))?;
+    let ua_public_key = PublicKey::from_sec1_bytes(&ua_public).map_err(|_| anyhow!("invalid p256dh key"))?;
+
+    let as_secret = SecretKey::random(&mut OsRng);
+    let as_public_key = as_secret.public_key().to_encoded_point(false).as_bytes().to_vec();
+
+    let shared_secret = p256::ecdh::diffie_hellman(
+        as_secret.to_nonzero_scalar(),
+        ua_public_key.as_affine(),
+    );
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    let prk = Hkdf::<Sha256>::new(Some(&auth_secret), shared_secret.raw_secret_bytes().as_slice());
+    let mut key_info = Vec::new();
+    key_info.extend_from_slice(b"WebPush: info\0");
+    key_info.extend_from_slice(&ua_public);
+    key_info.extend_from_slice(&as_public_key);
+    let mut ikm = [0u8; 32];
+    prk.expand(&key_info, &mut ikm)
+        .map_err(|_| anyhow!("HKDF expand failed"))?;
+
+    let content_prk = Hkdf::<Sha256>::new(Some(&salt), &ikm);
+    let mut content_encryption_key = [0u8; 16];
+    content_prk
+        .expand(b"Content-Encoding: aes128gcm\0", &mut content_encryption_key)
+        .map_err(|_| anyhow!("HKDF expand failed"))?;
+    let mut nonce_bytes = [0u8; 12];
+    content_prk
+        .expand(b"Content-Encoding: nonce\0", &mut nonce_bytes)
+        .map_err(|_| anyhow!("HKDF expand failed"))?;
+
+    let mut padded_plaintext = plaintext.to_vec();
+    padded_plaintext.push(0x02);
+
+    let cipher = Aes128Gcm::new_from_slice(&content_encryption_key)
+        .map_err(|_| anyhow!("invalid content-encryption key"))?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce_bytes),
+            Payload {
+                msg: &padded_plaintext,
+                aad: &[],
+            },
+        )
+        .map_err(|_| anyhow!("aes-gcm encryption failed"))?;
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&4096u32.to_be_bytes());
+    body.push(as_public_key.len() as u8);
+    body.extend_from_slice(&as_public_key);
+    body.extend_from_slice(&ciphertext);
+
+    Ok(EncryptedPayload {
+        body,
+        salt,
+        as_public_key,
+    })
+}
+
+fn build_headers(endpoint: &str, encrypted: &EncryptedPayload, vapid: &VapidConfig) -> Result<Vec<(String, String)>> {
+    let origin = endpoint_origin(endpoint)?;
+    let (jwt, vapid_public_key) = sign_vapid_jwt(&origin, vapid)?;
+    let _ = &encrypted.salt;
+
+    Ok(vec![
+        ("Content-Encoding".to_string(), "aes128gcm".to_string()),
+        ("Content-Type".to_string(), "application/octet-stream".to_string()),
+        ("TTL".to_string(), "86400".to_string()),
+        (
+            "Authorization".to_string(),
+            format!("vapid t={}, k={}", jwt, vapid_public_key),
+        ),
+    ])
+}
+
+fn endpoint_origin(endpoint: &str) -> Result<String> {
+    let url = Url::parse(endpoint)?;
+    url.host_str()
+        .map(|host| match url.port() {
+            Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+            None => format!("{}://{}", url.scheme(), host),
+        })
+        .ok_or_else(|| anyhow!("push endpoint missing host"))
+}
+
+/// Sign a short-lived ES256 JWT identifying this application server to the
+/// push service, per the VAPID spec (RFC 8292).
+fn sign_vapid_jwt(audience: &str, vapid: &VapidConfig) -> Result<(String, String)> {
+    let signing_key = SigningKey::from_bytes(vapid.private_key.as_slice().into())
+        .map_err(|_| anyhow!("invalid VAPID private key"))?;
+    let verifying_key = signing_key.verifying_key();
+    let public_key = URL_SAFE_NO_PAD.encode(verifying_key.to_encoded_point(false).as_bytes());
+
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let header = serde_json::json!({"typ": "JWT", "alg": "ES256"});
+    let claims = serde_json::json!({
+        "aud": audience,
+        "exp": now + VAPID_TOKEN_TTL_SECONDS,
+        "sub": vapid.subject,
+    });
+
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?),
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims)?),
+    );
+    let signature: Signature = signing_key.sign(signing_input.as_bytes());
+    let jwt = format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    );
+
+    Ok((jwt, public_key))
+}
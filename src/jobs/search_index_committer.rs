@@ -0,0 +1,25 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::search_store::TantivySearchStore;
+
+const COMMIT_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Flushes the Tantivy search index on a fixed tick. Unlike the queue-driven
+/// jobs in this module, there's no message to poll -- `TantivySearchStore`
+/// writes from `index_post`/`index_user` just mark a collection dirty, and
+/// this is what actually calls `commit_if_due` often enough that a write
+/// becomes searchable within about a second, mirroring `account_purge`'s
+/// fixed-interval sweep rather than `deletion_sweeper`'s queue loop.
+pub async fn run(search: Arc<TantivySearchStore>) -> Result<()> {
+    info!("search index committer started");
+    let mut interval = tokio::time::interval(COMMIT_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(err) = search.commit_if_due() {
+            warn!(error = ?err, "failed to commit search index");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::infra::queue::QueueClient;
+
+/// A signed Follow/Block edge push queued by `SocialFederationService`
+/// (see `app::social_federation`), addressed to one peer Lumine server.
+/// `body` is the exact byte string `signature` was computed over -- the
+/// receiving server's `VerifiedSocialEdge` extractor re-derives the
+/// signature check from these same bytes, so nothing here is re-derived
+/// from a parsed `SocialEdgeEvent` that could drift from what was signed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialEdgeJob {
+    pub target_server: String,
+    pub body: String,
+    pub signature: String,
+}
+
+const POLL_WAIT_SECONDS: i32 = 10;
+const IDLE_SLEEP_MS: u64 = 200;
+const ERROR_BACKOFF_MS: u64 = 1000;
+
+/// Drains the social-edge queue and delivers each push to its target
+/// server's inbox. Mirrors `deletion_sweeper`: a failed delivery leaves the
+/// queue message undeleted so SQS's visibility timeout redelivers it later,
+/// which is this crate's one retry/backoff mechanism rather than tracking
+/// attempt counts here.
+pub async fn run(queue: QueueClient) -> Result<()> {
+    info!("social federation dispatcher started");
+    let http = reqwest::Client::new();
+    loop {
+        match queue.receive_social_edge_job(POLL_WAIT_SECONDS).await {
+            Ok(Some(message)) => {
+                match deliver(&http, &message.job).await {
+                    Ok(()) => {
+                        if let Err(err) = queue.delete_social_edge_message(&message.receipt_handle).await {
+                            warn!(error = ?err, "failed to delete social edge queue message");
+                        }
+                    }
+                    Err(err) => {
+                        warn!(error = ?err, target_server = %message.job.target_server, "failed to deliver social edge push, leaving for retry");
+                    }
+                }
+            }
+            Ok(None) => {
+                tokio::time::sleep(Duration::from_millis(IDLE_SLEEP_MS)).await;
+            }
+            Err(err) => {
+                warn!(error = ?err, "social edge queue receive failed, backing off");
+                tokio::time::sleep(Duration::from_millis(ERROR_BACKOFF_MS)).await;
+            }
+        }
+    }
+}
+
+async fn deliver(http: &reqwest::Client, job: &SocialEdgeJob) -> Result<()> {
+    let response = http
+        .post(format!(
+            "https://{}/federation/v1/social-edges",
+            job.target_server
+        ))
+        .header("Content-Type", "application/json")
+        .header("X-Lumine-Server-Signature", &job.signature)
+        .body(job.body.clone())
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "peer server {} rejected social edge push with status {}",
+            job.target_server,
+            response.status()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::{federation::FederationService, stories::StoryService, streaming::StreamingService};
+use crate::infra::{cache::RedisCache, db::Db, gossip::GossipClient, queue::QueueClient};
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Periodic sweep of expired stories, mirroring `account_purge`'s fixed-
+/// interval shape rather than `deletion_sweeper`'s queue-poll shape -- there's
+/// no producer enqueuing "a story expired" messages, so this just re-checks
+/// `expires_at` on a timer.
+///
+/// `view_count`/`reaction_count` on `stories` are already maintained as
+/// live counters (`StoryService::record_view`/`react`), not batched from raw
+/// `story_views`/`story_reactions` rows, so `get_metrics` never scans those
+/// tables -- there is no separate rollup job to run here.
+pub async fn run(
+    db: Db,
+    cache: RedisCache,
+    queue: QueueClient,
+    federation_base_url: String,
+    gossip: GossipClient,
+) -> Result<()> {
+    info!("story sweep job started");
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        sweep_once(&db, &cache, &queue, &federation_base_url, &gossip).await;
+    }
+}
+
+async fn sweep_once(
+    db: &Db,
+    cache: &RedisCache,
+    queue: &QueueClient,
+    federation_base_url: &str,
+    gossip: &GossipClient,
+) {
+    let story_service = StoryService::new(db.clone(), cache.clone());
+    let (deletion_queue, expired_stories) = match story_service.sweep_expired().await {
+        Ok(result) => result,
+        Err(err) => {
+            warn!(error = ?err, "failed to sweep expired stories");
+            return;
+        }
+    };
+
+    if !deletion_queue.objects.is_empty() {
+        let job = crate::jobs::deletion_sweeper::DeletionJob {
+            keys: deletion_queue.objects,
+        };
+        if let Err(err) = queue.enqueue_deletion_job(&job).await {
+            warn!(error = ?err, "failed to enqueue orphaned story media for deletion");
+        }
+    }
+
+    let federation = FederationService::new(db.clone(), federation_base_url.to_string());
+    let streaming = StreamingService::new(db.clone(), cache.clone());
+
+    for (story_id, user_id) in expired_stories {
+        match federation.build_delete_activity(story_id, user_id).await {
+            Ok(Some(activity)) => {
+                let job = crate::jobs::federation_sweeper::FederationJob { activity, inbox: None };
+                if let Err(err) = queue.enqueue_federation_job(&job).await {
+                    warn!(error = ?err, story_id = %story_id, "failed to enqueue expired story Delete activity");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!(error = ?err, story_id = %story_id, "failed to build expired story Delete activity");
+            }
+        }
+
+        if let Err(err) = streaming.publish_story_deleted(story_id, user_id).await {
+            warn!(
+                error = ?err,
+                story_id = %story_id,
+                "failed to publish expired story deletion to stories feed channel"
+            );
+        }
+
+        match story_service.bump_feed_version_for_followers(user_id).await {
+            Ok(follower_ids) => {
+                gossip.broadcast_story_feed_changed(follower_ids).await;
+            }
+            Err(err) => {
+                warn!(error = ?err, story_id = %story_id, "failed to bump followers' stories feed version after expiry");
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::trust::TrustService;
+use crate::infra::db::Db;
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// Runs `TrustService::run_daily_maintenance` on a fixed 24-hour timer,
+/// mirroring `account_purge`'s fixed-interval shape -- nothing enqueues a
+/// "maintenance due" message, so this just re-runs on a timer rather than
+/// polling a queue.
+pub async fn run(db: Db, decay_half_life_days: f64, activity_staleness_days: i64) -> Result<()> {
+    info!("trust maintenance job started");
+    let trust_service = TrustService::new(db);
+    let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match trust_service
+            .run_daily_maintenance(decay_half_life_days, activity_staleness_days)
+            .await
+        {
+            Ok(count) => {
+                if count > 0 {
+                    info!(count, "decayed trust points for inactive accounts");
+                }
+            }
+            Err(err) => {
+                warn!(error = ?err, "failed to run trust maintenance");
+            }
+        }
+    }
+}
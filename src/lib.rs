@@ -5,7 +5,18 @@ pub mod http;
 pub mod infra;
 pub mod jobs;
 
-use crate::infra::{cache::RedisCache, db::Db, queue::QueueClient, storage::ObjectStorage};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crate::app::oauth::OAuthService;
+use crate::app::push::PushService;
+use crate::app::rate_limiter::DeferredRateLimiter;
+use crate::app::search_store::SearchStore;
+use crate::http::middleware::concurrency::ConcurrencyLimiter;
+use crate::infra::{
+    cache::RedisCache, db::Db, gossip::GossipClient, mailer::Mailer, queue::QueueClient,
+    storage::ObjectStorage,
+};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -13,13 +24,62 @@ pub struct AppState {
     pub cache: RedisCache,
     pub storage: ObjectStorage,
     pub queue: QueueClient,
+    pub oauth: OAuthService,
+    pub mailer: Arc<dyn Mailer>,
+    pub search_store: Arc<dyn SearchStore>,
+    pub email_verification_ttl_hours: i64,
+    pub password_reset_ttl_hours: i64,
+    pub push: PushService,
     pub upload_url_ttl_seconds: u64,
     pub upload_max_bytes: i64,
     pub admin_token: Option<String>,
-    pub paseto_access_key: [u8; 32],
-    pub paseto_refresh_key: [u8; 32],
+    pub paseto_access_keys: crate::app::auth::KeyRing,
+    pub paseto_refresh_keys: crate::app::auth::KeyRing,
     pub access_ttl_minutes: u64,
     pub refresh_ttl_days: u64,
+    /// HMAC key sealing pagination cursors -- see `http::handlers::parse_cursor`.
+    pub cursor_hmac_key: [u8; 32],
+    /// Salt for `app::shortid`-encoded post/comment/media ids -- see
+    /// `http::ShortUuid`.
+    pub shortid_salt: [u8; 32],
     pub s3_public_endpoint: Option<String>,
     pub ip_signup_rate_limit: u32,
+    pub deferred_rate_limiter: DeferredRateLimiter,
+    pub concurrency_limiter: ConcurrencyLimiter,
+    pub siwe_domain: String,
+    pub federation_base_url: String,
+    pub gossip: GossipClient,
+    pub trusted_proxy_cidrs: Vec<(IpAddr, u8)>,
+    pub ip_subnet_prefix_v4: u8,
+    pub ip_subnet_prefix_v6: u8,
+    pub variant_guard: crate::app::media::VariantGenerationGuard,
+    pub media_derivative_mode: crate::config::DerivativeMode,
+    pub media_concurrency_limiter: crate::app::media::MediaConcurrencyLimiter,
+    pub user_cache: crate::app::users::UserCache,
+    pub account_recovery_grace_days: i64,
+    pub ldap: Option<crate::config::LdapConfig>,
+    pub signature: crate::app::signature::SignatureService,
+    /// Ed25519 seed signing/verifying stateless invite tokens -- see
+    /// `app::invites::signed`.
+    pub invite_signing_key: [u8; 32],
+    /// Ed25519 seed this instance signs outbound Lumine-to-Lumine
+    /// Follow/Block edge pushes with -- see `app::social_federation`.
+    pub server_signing_key: [u8; 32],
+    /// Caches peer servers' published ed25519 keys for verifying inbound
+    /// edge pushes (see `app::social_federation::ServerKeyClient`). Shared
+    /// on `AppState` the same way `signature`'s actor-key cache is, so the
+    /// warm cache survives across requests.
+    pub social_federation_keys: crate::app::social_federation::ServerKeyClient,
+    /// Compiled banned-term list -- see `app::content_filter::ContentFilter`.
+    pub content_filter: crate::app::content_filter::ContentFilter,
+    /// `FilterMode` applied to comment bodies.
+    pub comment_filter_mode: crate::config::FilterMode,
+    /// `FilterMode` applied to `display_name`/`bio`.
+    pub profile_filter_mode: crate::config::FilterMode,
+    /// Deepest a comment reply chain may nest -- see
+    /// `EngagementService::comment_post`.
+    pub max_comment_depth: i32,
+    /// Reaction kinds this instance accepts -- see
+    /// `EngagementService::react_post`.
+    pub allowed_reaction_kinds: Vec<String>,
 }
@@ -2,7 +2,6 @@ use axum::Router;
 use anyhow::anyhow;
 use std::net::SocketAddr;
 use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use ciel::config::AppConfig;
 use ciel::infra::{cache::RedisCache, db::Db, queue::QueueClient, storage::ObjectStorage};
@@ -10,12 +9,8 @@ use ciel::AppState;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     let config = AppConfig::from_env()?;
+    config.init_tracing();
 
     // Serverless worker only needs DB + S3, skip Redis/Queue init
     if config.app_mode == "serverless-worker" {
@@ -36,50 +31,144 @@ async fn main() -> anyhow::Result<()> {
     let cache = RedisCache::connect(&config.redis_url).await?;
     let storage = ObjectStorage::new(&config).await?;
     let queue = QueueClient::new(&config).await?;
+    let gossip = ciel::infra::gossip::GossipClient::bind(
+        &config.gossip_bind_addr,
+        config.gossip_peers.clone(),
+    )
+    .await?;
+
+    let deferred_rate_limiter = ciel::app::rate_limiter::DeferredRateLimiter::new(cache.clone(), 0.5);
+    let concurrency_limiter = ciel::http::middleware::concurrency::ConcurrencyLimiter::new();
+    let oauth = ciel::app::oauth::OAuthService::new(
+        db.clone(),
+        cache.clone(),
+        config.oauth_providers.clone(),
+    );
+    let mailer: std::sync::Arc<dyn ciel::infra::mailer::Mailer> =
+        std::sync::Arc::new(ciel::infra::mailer::SmtpMailer::new(&config)?);
+    let push = ciel::app::push::PushService::new(db.clone(), queue.clone());
+    let variant_guard = ciel::app::media::VariantGenerationGuard::new();
+    let media_concurrency_limiter = ciel::app::media::MediaConcurrencyLimiter::new(
+        config.media_max_concurrency,
+        std::time::Duration::from_millis(config.media_concurrency_acquire_timeout_ms),
+    );
+    let user_cache = ciel::app::users::UserCache::new();
+    let signature = ciel::app::signature::SignatureService::new(db.clone());
+    let social_federation_keys = ciel::app::social_federation::ServerKeyClient::new();
+    let content_filter = ciel::app::content_filter::ContentFilter::new(config.banned_terms.clone());
+    let sql_search_store: std::sync::Arc<dyn ciel::app::search_store::SearchStore> =
+        match config.db_backend {
+            ciel::config::DbBackend::Postgres => std::sync::Arc::new(
+                ciel::app::search_store::PostgresSearchStore::new(db.clone()),
+            ),
+            ciel::config::DbBackend::Sqlite => std::sync::Arc::new(
+                ciel::app::search_store::SqliteSearchStore::connect(&config.database_url).await?,
+            ),
+        };
+    // The Tantivy index is the real backend now (BM25-ranked, not substring/
+    // trigram matching); `sql_search_store` only fronts it until the index
+    // has been populated by `index_post`/`index_user` writes -- see
+    // `HybridSearchStore`.
+    let tantivy_search_store =
+        std::sync::Arc::new(ciel::app::search_store::TantivySearchStore::new(db.clone())?);
+    let search_store: std::sync::Arc<dyn ciel::app::search_store::SearchStore> = std::sync::Arc::new(
+        ciel::app::search_store::HybridSearchStore::new(tantivy_search_store.clone(), sql_search_store),
+    );
 
     let state = AppState {
         db,
         cache,
         storage,
         queue,
+        oauth,
+        mailer,
+        search_store,
+        email_verification_ttl_hours: config.email_verification_ttl_hours,
+        password_reset_ttl_hours: config.password_reset_ttl_hours,
+        push,
+        deferred_rate_limiter,
+        concurrency_limiter,
         upload_url_ttl_seconds: config.upload_url_ttl_seconds,
         upload_max_bytes: config.upload_max_bytes,
         admin_token: config.admin_token.clone(),
-        paseto_access_key: config.paseto_access_key,
-        paseto_refresh_key: config.paseto_refresh_key,
+        paseto_access_keys: ciel::app::auth::KeyRing::new(config.paseto_access_keys)?,
+        paseto_refresh_keys: ciel::app::auth::KeyRing::new(config.paseto_refresh_keys)?,
         access_ttl_minutes: config.access_ttl_minutes,
         refresh_ttl_days: config.refresh_ttl_days,
+        cursor_hmac_key: config.cursor_hmac_key,
+        shortid_salt: config.shortid_salt,
         s3_public_endpoint: config.s3_public_endpoint,
         ip_signup_rate_limit: config.ip_signup_rate_limit,
+        siwe_domain: config.siwe_domain,
+        federation_base_url: config.federation_base_url.clone(),
+        gossip,
+        trusted_proxy_cidrs: config.trusted_proxy_cidrs,
+        ip_subnet_prefix_v4: config.ip_subnet_prefix_v4,
+        ip_subnet_prefix_v6: config.ip_subnet_prefix_v6,
+        variant_guard,
+        media_derivative_mode: config.media_derivative_mode,
+        media_concurrency_limiter,
+        user_cache,
+        account_recovery_grace_days: config.account_recovery_grace_days,
+        ldap: config.ldap.clone(),
+        signature,
+        invite_signing_key: config.invite_signing_key,
+        server_signing_key: config.server_signing_key,
+        social_federation_keys,
+        content_filter,
+        comment_filter_mode: config.comment_filter_mode,
+        profile_filter_mode: config.profile_filter_mode,
+        max_comment_depth: config.max_comment_depth,
+        allowed_reaction_kinds: config.allowed_reaction_kinds,
     };
 
     match config.app_mode.as_str() {
         "api" => {
-            // H7: Spawn background cleanup task for expired data
+            // Receive peer cache-invalidation gossip (see infra::gossip).
+            let gossip_receiver = state.gossip.clone();
+            let gossip_cache = state.cache.clone();
+            tokio::spawn(async move {
+                if let Err(err) = gossip_receiver.run(gossip_cache).await {
+                    tracing::error!(error = ?err, "gossip receive loop exited");
+                }
+            });
+
+            // Flushes pending Tantivy index writes on a short timer -- see
+            // `jobs::search_index_committer`.
+            tokio::spawn(async move {
+                if let Err(err) = ciel::jobs::search_index_committer::run(tantivy_search_store).await {
+                    tracing::error!(error = ?err, "search index committer exited");
+                }
+            });
+
+            // Sweeps expired, non-highlighted stories and fans out the
+            // resulting media deletion, federated Delete, and feed
+            // invalidation -- see jobs::story_sweep.
+            let story_sweep_db = state.db.clone();
+            let story_sweep_cache = state.cache.clone();
+            let story_sweep_queue = state.queue.clone();
+            let story_sweep_federation_base_url = state.federation_base_url.clone();
+            let story_sweep_gossip = state.gossip.clone();
+            tokio::spawn(async move {
+                if let Err(err) = ciel::jobs::story_sweep::run(
+                    story_sweep_db,
+                    story_sweep_cache,
+                    story_sweep_queue,
+                    story_sweep_federation_base_url,
+                    story_sweep_gossip,
+                )
+                .await
+                {
+                    tracing::error!(error = ?err, "story sweep job exited");
+                }
+            });
+
+            // H7: Spawn background cleanup task for other expired data
             let cleanup_db = state.db.clone();
             tokio::spawn(async move {
                 let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
                 loop {
                     interval.tick().await;
-                    // Clean up expired stories (and cascaded views/reactions) older than 48h
-                    match sqlx::query(
-                        "DELETE FROM stories WHERE expires_at < now() - interval '48 hours'",
-                    )
-                    .execute(cleanup_db.pool())
-                    .await
-                    {
-                        Ok(result) => {
-                            if result.rows_affected() > 0 {
-                                tracing::info!(
-                                    count = result.rows_affected(),
-                                    "cleaned up expired stories"
-                                );
-                            }
-                        }
-                        Err(err) => {
-                            tracing::warn!(error = ?err, "failed to clean up expired stories");
-                        }
-                    }
 
                     // Clean up expired invites
                     match sqlx::query("SELECT cleanup_expired_invites()")
@@ -91,6 +180,20 @@ async fn main() -> anyhow::Result<()> {
                             tracing::warn!(error = ?err, "failed to clean up expired invites");
                         }
                     }
+
+                    // Expire stale, never-responded-to device auth requests
+                    let device_auth_service =
+                        ciel::app::device_auth::DeviceAuthService::new(cleanup_db.clone());
+                    match device_auth_service.expire_stale().await {
+                        Ok(count) => {
+                            if count > 0 {
+                                tracing::info!(count = count, "expired stale device auth requests");
+                            }
+                        }
+                        Err(err) => {
+                            tracing::warn!(error = ?err, "failed to expire device auth requests");
+                        }
+                    }
                 }
             });
 
@@ -107,8 +210,56 @@ async fn main() -> anyhow::Result<()> {
         }
         "worker" => {
             tracing::info!("starting worker mode");
+            let push_sender: std::sync::Arc<dyn ciel::infra::push_sender::PushSender> =
+                std::sync::Arc::new(ciel::infra::push_sender::HttpPushSender::new());
+            let vapid = ciel::jobs::push_dispatcher::VapidConfig {
+                private_key: config.vapid_private_key,
+                subject: config.vapid_subject.clone(),
+            };
+            let video_config = ciel::jobs::media_processor::VideoConfig {
+                max_duration_seconds: config.video_max_duration_seconds,
+            };
             tokio::select! {
-                result = ciel::jobs::media_processor::run(state.db.clone(), state.storage.clone(), state.queue.clone()) => {
+                result = ciel::jobs::media_processor::run(
+                    state.db.clone(),
+                    state.cache.clone(),
+                    state.storage.clone(),
+                    state.queue.clone(),
+                    state.s3_public_endpoint.clone(),
+                    state.variant_guard.clone(),
+                    config.media_derivative_mode,
+                    state.media_concurrency_limiter.clone(),
+                    video_config,
+                ) => {
+                    result?;
+                }
+                result = ciel::jobs::push_dispatcher::run(state.db.clone(), state.queue.clone(), push_sender, vapid) => {
+                    result?;
+                }
+                result = ciel::jobs::deletion_sweeper::run(state.storage.clone(), state.queue.clone()) => {
+                    result?;
+                }
+                result = ciel::jobs::federation_sweeper::run(
+                    state.db.clone(),
+                    state.queue.clone(),
+                    state.federation_base_url.clone(),
+                ) => {
+                    result?;
+                }
+                result = ciel::jobs::social_federation_dispatcher::run(state.queue.clone()) => {
+                    result?;
+                }
+                result = ciel::jobs::bot_webhook_dispatcher::run(state.db.clone(), state.queue.clone()) => {
+                    result?;
+                }
+                result = ciel::jobs::account_purge::run(state.db.clone(), state.queue.clone(), state.account_recovery_grace_days) => {
+                    result?;
+                }
+                result = ciel::jobs::trust_maintenance::run(
+                    state.db.clone(),
+                    config.trust_decay_half_life_days,
+                    config.trust_activity_staleness_days,
+                ) => {
                     result?;
                 }
                 _ = shutdown_signal() => {}
@@ -274,6 +274,44 @@ async fn refresh_already_used_token() {
     assert_eq!(resp.error_message(), "invalid refresh token");
 }
 
+#[tokio::test]
+async fn refresh_reuse_revokes_whole_family() {
+    let app = app().await;
+    let user = app.create_user("refresh_reuse").await;
+
+    // Rotate once to get a live, descendant refresh token.
+    let resp = app
+        .post_json(
+            "/v1/auth/refresh",
+            json!({ "refresh_token": user.refresh_token }),
+            None,
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::OK);
+    let rotated_refresh_token = resp.json()["refresh_token"].as_str().unwrap().to_string();
+
+    // Replay the original (now-rotated) token -- this is the reuse signal.
+    let resp = app
+        .post_json(
+            "/v1/auth/refresh",
+            json!({ "refresh_token": user.refresh_token }),
+            None,
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+
+    // The legitimate, currently-live descendant token must be killed too --
+    // a leaked token shouldn't be able to keep a parallel session alive.
+    let resp = app
+        .post_json(
+            "/v1/auth/refresh",
+            json!({ "refresh_token": rotated_refresh_token }),
+            None,
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn revoke_own_token() {
     let app = app().await;
@@ -397,69 +435,68 @@ async fn create_post_no_auth() {
 }
 
 #[tokio::test]
-async fn admin_endpoint_no_admin_token() {
+async fn admin_endpoint_no_auth_token() {
     let app = app().await;
     let fake_post_id = Uuid::new_v4();
 
     let resp = app
-        .post_admin(
+        .post_json(
             &format!("/v1/moderation/posts/{}/takedown", fake_post_id),
             json!({ "reason": "test" }),
             None,
         )
         .await;
 
-    assert!(
-        resp.status == StatusCode::FORBIDDEN || resp.status == StatusCode::UNAUTHORIZED,
-        "expected 403 or 401, got {}",
-        resp.status
-    );
+    assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
 }
 
 #[tokio::test]
-async fn admin_endpoint_wrong_admin_token() {
+async fn admin_endpoint_missing_moderation_scope() {
+    use ciel::app::auth::Scope;
+
     let app = app().await;
     let fake_post_id = Uuid::new_v4();
+    let user = app
+        .create_user_with_scopes("admin_scope_missing", &[Scope::ReadFeed])
+        .await;
 
     let resp = app
-        .post_admin(
+        .post_json(
             &format!("/v1/moderation/posts/{}/takedown", fake_post_id),
             json!({ "reason": "test" }),
-            Some("wrong-admin-token"),
+            Some(&user.access_token),
         )
         .await;
 
-    assert!(
-        resp.status == StatusCode::FORBIDDEN || resp.status == StatusCode::UNAUTHORIZED,
-        "expected 403 or 401, got {}",
-        resp.status
-    );
+    assert_eq!(resp.status, StatusCode::FORBIDDEN);
 }
 
 #[tokio::test]
-async fn admin_endpoint_valid_admin_token() {
+async fn admin_endpoint_with_moderation_scope() {
     let app = app().await;
     let fake_post_id = Uuid::new_v4();
+    // `create_user` grants the full scope set, including `admin:moderation`.
+    let user = app.create_user("admin_scope_present").await;
 
-    // Valid admin token but non-existent post — should NOT get 403
     let resp = app
-        .post_admin(
+        .post_json(
             &format!("/v1/moderation/posts/{}/takedown", fake_post_id),
             json!({ "reason": "test" }),
-            Some(app.admin_token()),
+            Some(&user.access_token),
         )
         .await;
 
-    // Should be 404 or 500 (post not found), NOT 403/401
+    // Should be 404 (post not found), NOT 403/401 -- the token carries the
+    // required scope, so it clears `RequireScope<AdminModeration>`.
     assert_ne!(
         resp.status,
         StatusCode::FORBIDDEN,
-        "valid admin token should not be rejected"
+        "token with admin:moderation scope should not be rejected"
     );
     assert_ne!(
         resp.status,
         StatusCode::UNAUTHORIZED,
-        "valid admin token should not be rejected"
+        "token with admin:moderation scope should not be rejected"
     );
 }
 
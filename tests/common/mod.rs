@@ -17,9 +17,12 @@ use tokio::sync::OnceCell;
 use tower::ServiceExt;
 use uuid::Uuid;
 
-use ciel::app::auth::AuthService;
+use ciel::app::auth::{AuthService, DeviceContext, Scope};
 use ciel::config::AppConfig;
-use ciel::infra::{cache::RedisCache, db::Db, queue::QueueClient, storage::ObjectStorage};
+use ciel::infra::push_sender::{PushOutcome, PushSender};
+use ciel::infra::{
+    cache::RedisCache, db::Db, mailer::Mailer, queue::QueueClient, storage::ObjectStorage,
+};
 use ciel::AppState;
 
 // ---------------------------------------------------------------------------
@@ -32,6 +35,18 @@ const TEST_PASETO_ACCESS_KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZW
 // "fedcba9876543210fedcba9876543210" (32 bytes)
 const TEST_PASETO_REFRESH_KEY: &str = "ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=";
 const TEST_ADMIN_TOKEN: &str = "test-admin-token-12345";
+// 32 bytes base64-encoded (test-only cursor HMAC key — NOT used in production)
+// "abcdef0123456789abcdef0123456789" (32 bytes)
+const TEST_CURSOR_HMAC_KEY: &str = "YWJjZGVmMDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODk=";
+// 32 bytes base64-encoded (test-only VAPID key — NOT used in production)
+const TEST_VAPID_PRIVATE_KEY: &str = "MTIzNDU2Nzg5MGFiY2RlZjEyMzQ1Njc4OTBhYmNkZWY=";
+const TEST_VAPID_PRIVATE_KEY_BYTES: [u8; 32] = [
+    0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x30, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66,
+    0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x30, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66,
+];
+// A valid, throwaway uncompressed P-256 public point (test-only, not tied to any real device).
+const TEST_P256DH: &str = "BJNDXnky/idHFO1cnVaZvjShTUXzCwhGMg1IJx/VheBXi9NMRCkZ841EWWIHyYr7SVCnevnNiYCtvOU8N43ta1M=";
+const TEST_PUSH_AUTH_SECRET: &str = "MDEyMzQ1Njc4OWFiY2RlZg==";
 pub const DEFAULT_PASSWORD: &str = "testpassword123";
 
 // ---------------------------------------------------------------------------
@@ -41,6 +56,66 @@ pub const DEFAULT_PASSWORD: &str = "testpassword123";
 pub struct TestApp {
     router: Router,
     pub state: AppState,
+    mailer: std::sync::Arc<TestMailer>,
+    push_sender: std::sync::Arc<TestPushSender>,
+}
+
+/// A single message handed to `TestMailer::send`.
+#[derive(Debug, Clone)]
+pub struct SentEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// In-memory `Mailer` that records every send instead of talking to real
+/// SMTP, so tests can assert on verification tokens/links directly.
+#[derive(Default)]
+pub struct TestMailer {
+    sent: std::sync::Mutex<Vec<SentEmail>>,
+}
+
+#[axum::async_trait]
+impl Mailer for TestMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push(SentEmail {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+        });
+        Ok(())
+    }
+}
+
+/// A single notification handed to `TestPushSender::send`, recording the
+/// endpoint it was addressed to rather than the encrypted ciphertext since
+/// tests only care about who got notified.
+#[derive(Debug, Clone)]
+pub struct SentPush {
+    pub endpoint: String,
+}
+
+/// In-memory `PushSender` that records every delivery instead of POSTing to
+/// a real push service, so tests can assert which subscriptions got
+/// notified.
+#[derive(Default)]
+pub struct TestPushSender {
+    sent: std::sync::Mutex<Vec<SentPush>>,
+}
+
+#[axum::async_trait]
+impl PushSender for TestPushSender {
+    async fn send(
+        &self,
+        endpoint: &str,
+        _headers: Vec<(String, String)>,
+        _body: Vec<u8>,
+    ) -> anyhow::Result<PushOutcome> {
+        self.sent.lock().unwrap().push(SentPush {
+            endpoint: endpoint.to_string(),
+        });
+        Ok(PushOutcome::Delivered)
+    }
 }
 
 pub struct TestResponse {
@@ -172,6 +247,7 @@ impl TestApp {
         // Ensure the 32-byte keys decode correctly
         assert_eq!(STANDARD.decode(TEST_PASETO_ACCESS_KEY).unwrap().len(), 32);
         assert_eq!(STANDARD.decode(TEST_PASETO_REFRESH_KEY).unwrap().len(), 32);
+        assert_eq!(STANDARD.decode(TEST_CURSOR_HMAC_KEY).unwrap().len(), 32);
 
         std::env::set_var("DATABASE_URL", &database_url);
         std::env::set_var("REDIS_URL", &redis_url);
@@ -183,7 +259,12 @@ impl TestApp {
         std::env::set_var("QUEUE_REGION", "us-east-1");
         std::env::set_var("PASETO_ACCESS_KEY", TEST_PASETO_ACCESS_KEY);
         std::env::set_var("PASETO_REFRESH_KEY", TEST_PASETO_REFRESH_KEY);
+        std::env::set_var("CURSOR_HMAC_KEY", TEST_CURSOR_HMAC_KEY);
         std::env::set_var("ADMIN_TOKEN", TEST_ADMIN_TOKEN);
+        std::env::set_var("PUSH_QUEUE_NAME", "ciel-push-jobs-test");
+        std::env::set_var("DELETION_QUEUE_NAME", "ciel-deletion-jobs-test");
+        std::env::set_var("VAPID_PRIVATE_KEY", TEST_VAPID_PRIVATE_KEY);
+        std::env::set_var("VAPID_SUBJECT", "mailto:test@lumine.app");
         std::env::set_var("APP_MODE", "api");
         std::env::set_var("DB_MAX_CONNECTIONS", "10");
         std::env::set_var("DB_CONNECT_TIMEOUT_SECONDS", "30");
@@ -210,11 +291,41 @@ impl TestApp {
             .await
             .expect("QueueClient::new failed");
 
+        let deferred_rate_limiter =
+            ciel::app::rate_limiter::DeferredRateLimiter::new(cache.clone(), 0.5);
+        let concurrency_limiter = ciel::http::middleware::concurrency::ConcurrencyLimiter::new();
+        let oauth = ciel::app::oauth::OAuthService::new(
+            db.clone(),
+            cache.clone(),
+            config.oauth_providers.clone(),
+        );
+        let test_mailer = std::sync::Arc::new(TestMailer::default());
+        let mailer: std::sync::Arc<dyn Mailer> = test_mailer.clone();
+        let push_sender = std::sync::Arc::new(TestPushSender::default());
+        let push = ciel::app::push::PushService::new(db.clone(), queue.clone());
+        let search_store: std::sync::Arc<dyn ciel::app::search_store::SearchStore> =
+            match config.db_backend {
+                ciel::config::DbBackend::Postgres => std::sync::Arc::new(
+                    ciel::app::search_store::PostgresSearchStore::new(db.clone()),
+                ),
+                ciel::config::DbBackend::Sqlite => std::sync::Arc::new(
+                    ciel::app::search_store::SqliteSearchStore::connect(&config.database_url)
+                        .await
+                        .expect("SqliteSearchStore::connect failed"),
+                ),
+            };
+
         let state = AppState {
             db,
             cache,
             storage,
             queue,
+            oauth,
+            mailer,
+            search_store,
+            email_verification_ttl_hours: config.email_verification_ttl_hours,
+            password_reset_ttl_hours: config.password_reset_ttl_hours,
+            push,
             upload_url_ttl_seconds: config.upload_url_ttl_seconds,
             upload_max_bytes: config.upload_max_bytes,
             admin_token: config.admin_token.clone(),
@@ -222,12 +333,40 @@ impl TestApp {
             paseto_refresh_key: config.paseto_refresh_key,
             access_ttl_minutes: config.access_ttl_minutes,
             refresh_ttl_days: config.refresh_ttl_days,
+            cursor_hmac_key: config.cursor_hmac_key,
             s3_public_endpoint: config.s3_public_endpoint,
+            deferred_rate_limiter,
+            concurrency_limiter,
+            siwe_domain: config.siwe_domain,
+            trusted_proxy_cidrs: config.trusted_proxy_cidrs,
+            ip_subnet_prefix_v4: config.ip_subnet_prefix_v4,
+            ip_subnet_prefix_v6: config.ip_subnet_prefix_v6,
+            account_recovery_grace_days: config.account_recovery_grace_days,
+            ldap: config.ldap.clone(),
+            signature: ciel::app::signature::SignatureService::new(),
         };
 
         let router = ciel::http::router(state.clone());
 
-        TestApp { router, state }
+        TestApp {
+            router,
+            state,
+            mailer: test_mailer,
+            push_sender,
+        }
+    }
+
+    /// Most recent email sent to `email`, if any, via the in-memory
+    /// `TestMailer` installed for this test run.
+    pub fn last_email_for(&self, email: &str) -> Option<SentEmail> {
+        self.mailer
+            .sent
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|sent| sent.to == email)
+            .cloned()
     }
 
     // ------------------------------------------------------------------
@@ -395,13 +534,16 @@ impl TestApp {
         // Issue tokens directly via AuthService (avoids IP rate-limiting)
         let auth_service = AuthService::new(
             self.state.db.clone(),
+            self.state.cache.clone(),
             self.state.paseto_access_key,
             self.state.paseto_refresh_key,
             self.state.access_ttl_minutes,
             self.state.refresh_ttl_days,
+            self.state.account_recovery_grace_days,
+            self.state.ldap.clone(),
         );
         let tokens = auth_service
-            .issue_token_pair(user_id)
+            .issue_token_pair(user_id, &Scope::all(), &DeviceContext::default())
             .await
             .expect("issue_token_pair failed");
 
@@ -414,6 +556,264 @@ impl TestApp {
         }
     }
 
+    /// Like `create_user`, but mints an access token scoped down to only
+    /// `scopes` instead of the full set -- for exercising `RequireScope`
+    /// rejections on endpoints that need a permission the token lacks.
+    pub async fn create_user_with_scopes(&self, suffix: &str, scopes: &[Scope]) -> TestUser {
+        let user = self.create_user(suffix).await;
+
+        let auth_service = AuthService::new(
+            self.state.db.clone(),
+            self.state.cache.clone(),
+            self.state.paseto_access_key,
+            self.state.paseto_refresh_key,
+            self.state.access_ttl_minutes,
+            self.state.refresh_ttl_days,
+            self.state.account_recovery_grace_days,
+            self.state.ldap.clone(),
+        );
+        let tokens = auth_service
+            .issue_token_pair(user.id, scopes, &DeviceContext::default())
+            .await
+            .expect("issue_token_pair failed");
+
+        TestUser {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            ..user
+        }
+    }
+
+    /// Create a user with no `email_verified_at`, for exercising endpoints
+    /// gated on email verification. Mirrors `create_user` exactly -- new
+    /// accounts are unverified until they confirm a token, so this is just
+    /// an explicitly-named alias for tests that care about that state.
+    pub async fn create_unverified_user(&self, suffix: &str) -> TestUser {
+        self.create_user(suffix).await
+    }
+
+    /// Create a user and force their `user_trust_scores.trust_level`,
+    /// bypassing the normal earn-it-over-time progression, so admin search
+    /// filter tests can seed users across the full trust-level range.
+    pub async fn create_user_with_trust_level(&self, suffix: &str, trust_level: i32) -> TestUser {
+        let user = self.create_user(suffix).await;
+
+        sqlx::query("UPDATE user_trust_scores SET trust_level = $1 WHERE user_id = $2")
+            .bind(trust_level)
+            .bind(user.id)
+            .execute(self.state.db.pool())
+            .await
+            .expect("set trust level failed");
+
+        user
+    }
+
+    /// Create a user that signed up via an OAuth provider (no password),
+    /// bypassing the live IdP round-trip so tests can exercise
+    /// re-login/linking against a known `(provider, subject)` pair.
+    pub async fn create_oauth_user(&self, provider: &str, subject: &str) -> TestUser {
+        let handle = format!("{}_{}", provider, &Uuid::new_v4().simple().to_string()[..10]);
+        let email = format!("{}@{}.oauth.invalid", subject, provider);
+        let display_name = email.split('@').next().unwrap_or(provider).to_string();
+
+        let pool = self.state.db.pool();
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (handle, email, display_name, password_hash) \
+             VALUES ($1, $2, $3, '') RETURNING id",
+        )
+        .bind(&handle)
+        .bind(&email)
+        .bind(&display_name)
+        .fetch_one(pool)
+        .await
+        .expect("insert oauth test user failed");
+
+        sqlx::query(
+            "INSERT INTO user_trust_scores (user_id, trust_level, trust_points) \
+             VALUES ($1, 0, 0) ON CONFLICT (user_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .expect("init trust score failed");
+
+        sqlx::query(
+            "INSERT INTO oauth_identities (user_id, provider, provider_subject) VALUES ($1, $2, $3)",
+        )
+        .bind(user_id)
+        .bind(provider)
+        .bind(subject)
+        .execute(pool)
+        .await
+        .expect("insert oauth identity failed");
+
+        let auth_service = AuthService::new(
+            self.state.db.clone(),
+            self.state.cache.clone(),
+            self.state.paseto_access_key,
+            self.state.paseto_refresh_key,
+            self.state.access_ttl_minutes,
+            self.state.refresh_ttl_days,
+            self.state.account_recovery_grace_days,
+            self.state.ldap.clone(),
+        );
+        let tokens = auth_service
+            .issue_tokens_for_user(user_id, DeviceContext::default())
+            .await
+            .expect("issue_tokens_for_user failed");
+
+        TestUser {
+            id: user_id,
+            handle,
+            email,
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+        }
+    }
+
+    /// Register a deterministic passkey credential for `user_id`, bypassing
+    /// the real attestation ceremony. Returns the `credential_id`, which
+    /// `passkey_login` can later use to regenerate the matching keypair and
+    /// sign an assertion.
+    pub async fn register_passkey(&self, user_id: Uuid) -> String {
+        let credential_id = format!("test-cred-{}", user_id);
+        let signing_key = deterministic_passkey_key(&credential_id);
+        let verifying_key = p256::ecdsa::VerifyingKey::from(&signing_key);
+        let public_key = STANDARD.encode(verifying_key.to_encoded_point(false).as_bytes());
+
+        sqlx::query(
+            "INSERT INTO credentials (credential_id, public_key, sign_count, user_id) \
+             VALUES ($1, $2, 0, $3)",
+        )
+        .bind(&credential_id)
+        .bind(&public_key)
+        .bind(user_id)
+        .execute(self.state.db.pool())
+        .await
+        .expect("insert test credential failed");
+
+        credential_id
+    }
+
+    /// Drive the real `/auth/webauthn/authentication/*` endpoints end to end
+    /// for `credential_id`, signing the challenge with the deterministic
+    /// keypair `register_passkey` installed. `sign_count` is sent verbatim,
+    /// so tests can replay a stale counter to exercise clone-detection
+    /// rejection.
+    pub async fn passkey_login(
+        &self,
+        identifier: &str,
+        credential_id: &str,
+        sign_count: u32,
+    ) -> TestResponse {
+        use p256::ecdsa::signature::Signer;
+        use sha2::{Digest, Sha256};
+
+        let begin = self
+            .post_json(
+                "/auth/webauthn/authentication/begin",
+                serde_json::json!({ "identifier": identifier }),
+                None,
+            )
+            .await;
+        let begin_body = begin.json();
+        let handle = begin_body["handle"]
+            .as_str()
+            .expect("missing handle in authentication/begin response")
+            .to_string();
+        let challenge = begin_body["challenge"]
+            .as_str()
+            .expect("missing challenge in authentication/begin response")
+            .to_string();
+
+        let client_data_json = serde_json::json!({
+            "type": "webauthn.get",
+            "challenge": challenge,
+        })
+        .to_string();
+
+        // rpIdHash (32 bytes, unchecked here) + flags (1 byte) + sign count (4 bytes, BE)
+        let mut authenticator_data = vec![0u8; 32];
+        authenticator_data.push(0x01);
+        authenticator_data.extend_from_slice(&sign_count.to_be_bytes());
+
+        let mut signed_data = authenticator_data.clone();
+        signed_data.extend_from_slice(&Sha256::digest(client_data_json.as_bytes()));
+
+        let signing_key = deterministic_passkey_key(credential_id);
+        let signature: p256::ecdsa::Signature = signing_key.sign(&signed_data);
+
+        self.post_json(
+            "/auth/webauthn/authentication/finish",
+            serde_json::json!({
+                "handle": handle,
+                "credential_id": credential_id,
+                "authenticator_data": STANDARD.encode(&authenticator_data),
+                "client_data_json": client_data_json,
+                "signature": STANDARD.encode(signature.to_der().as_bytes()),
+            }),
+            None,
+        )
+        .await
+    }
+
+    /// Register a synthetic Web Push subscription for `user_id`, bypassing
+    /// the browser `PushManager.subscribe()` ceremony. Returns the endpoint
+    /// URL, which `drain_push` reports alongside any dispatched notification.
+    pub async fn subscribe_push(&self, user_id: Uuid) -> String {
+        let endpoint = format!("https://push.example.test/subscriptions/{}", Uuid::new_v4());
+        self.state
+            .push
+            .subscribe(user_id, &endpoint, TEST_P256DH, TEST_PUSH_AUTH_SECRET)
+            .await
+            .expect("subscribe_push failed");
+
+        endpoint
+    }
+
+    /// Synchronously drain every queued push job, dispatching it through the
+    /// in-memory `TestPushSender`, and return what was sent. Stands in for
+    /// the background `push_dispatcher::run` worker loop in tests.
+    pub async fn drain_push(&self) -> Vec<SentPush> {
+        let vapid = ciel::jobs::push_dispatcher::VapidConfig {
+            private_key: TEST_VAPID_PRIVATE_KEY_BYTES,
+            subject: "mailto:test@lumine.app".to_string(),
+        };
+
+        loop {
+            let message = self
+                .state
+                .queue
+                .receive_push_job(0)
+                .await
+                .expect("receive_push_job failed");
+            let message = match message {
+                Some(message) => message,
+                None => break,
+            };
+
+            ciel::jobs::push_dispatcher::process_job(
+                &self.state.push,
+                self.push_sender.as_ref(),
+                &vapid,
+                message.job.subscription_id,
+                &message.job.title,
+                &message.job.body,
+            )
+            .await
+            .expect("process_job failed");
+
+            self.state
+                .queue
+                .delete_push_message(&message.receipt_handle)
+                .await
+                .expect("delete_push_message failed");
+        }
+
+        self.push_sender.sent.lock().unwrap().clone()
+    }
+
     /// Create an invite code owned by the given user. Returns the code string.
     pub async fn create_invite_code(&self, user_id: Uuid) -> String {
         let code: String = {
@@ -477,19 +877,25 @@ impl TestApp {
         media_id
     }
 
-    /// Insert a media record + post directly in DB. Returns (post_id, media_id).
+    /// Insert a media record + post directly in DB, claiming the media as the
+    /// post's sole attachment. Returns (post_id, media_id).
     pub async fn create_post_for_user(&self, owner_id: Uuid) -> (Uuid, Uuid) {
         let media_id = self.create_media(owner_id).await;
         let pool = self.state.db.pool();
         let post_id: Uuid = sqlx::query_scalar(
-            "INSERT INTO posts (owner_id, media_id, caption, visibility) \
-             VALUES ($1, $2, 'test caption', 'public'::post_visibility) RETURNING id",
+            "INSERT INTO posts (owner_id, caption, visibility) \
+             VALUES ($1, 'test caption', 'public'::post_visibility) RETURNING id",
         )
         .bind(owner_id)
-        .bind(media_id)
         .fetch_one(pool)
         .await
         .expect("insert test post failed");
+        sqlx::query("UPDATE media SET post_id = $1 WHERE id = $2")
+            .bind(post_id)
+            .bind(media_id)
+            .execute(pool)
+            .await
+            .expect("claim test media failed");
         (post_id, media_id)
     }
 
@@ -559,6 +965,32 @@ impl TestApp {
         code
     }
 
+    /// Bump a user's session epoch directly in DB, simulating a
+    /// "log out everywhere" so tests can assert previously issued tokens
+    /// stop authenticating.
+    pub async fn bump_session_epoch(&self, user_id: Uuid) {
+        let pool = self.state.db.pool();
+        sqlx::query("UPDATE users SET session_epoch = session_epoch + 1 WHERE id = $1")
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .expect("bump session epoch failed");
+
+        // Invalidate the cached epoch so the next verification re-reads it.
+        use redis::AsyncCommands;
+        let mut conn = self
+            .state
+            .cache
+            .client()
+            .get_multiplexed_async_connection()
+            .await
+            .expect("redis connection failed");
+        let _: () = conn
+            .del(format!("session_epoch:{}", user_id))
+            .await
+            .expect("failed to invalidate cached session epoch");
+    }
+
     /// Block a device fingerprint directly in DB for testing.
     pub async fn block_device_fingerprint(&self, fingerprint_hash: &str) {
         let pool = self.state.db.pool();
@@ -573,3 +1005,24 @@ impl TestApp {
         .expect("block device fingerprint failed");
     }
 }
+
+/// Deterministically derive a P-256 signing key from `seed` by hashing it
+/// (with an incrementing counter in the rare case a digest isn't a valid
+/// scalar) until `SigningKey::from_bytes` accepts it. Used by
+/// `TestApp::register_passkey`/`passkey_login` so both sides of a test
+/// reconstruct the same keypair without persisting a private key anywhere.
+fn deterministic_passkey_key(seed: &str) -> p256::ecdsa::SigningKey {
+    use sha2::{Digest, Sha256};
+
+    let mut counter: u32 = 0;
+    loop {
+        let mut hasher = Sha256::new();
+        hasher.update(seed.as_bytes());
+        hasher.update(counter.to_be_bytes());
+        let digest = hasher.finalize();
+        if let Ok(key) = p256::ecdsa::SigningKey::from_bytes(&digest) {
+            return key;
+        }
+        counter += 1;
+    }
+}